@@ -18,6 +18,7 @@ enum Operation {
     Size,
     Encode,
     Decode,
+    DecodeWithLimits,
 }
 
 impl Operation {
@@ -25,7 +26,9 @@ impl Operation {
         match self {
             Operation::Size => parse_quote! { mls_rs_codec::MlsSize },
             Operation::Encode => parse_quote! { mls_rs_codec::MlsEncode },
-            Operation::Decode => parse_quote! { mls_rs_codec::MlsDecode },
+            Operation::Decode | Operation::DecodeWithLimits => {
+                parse_quote! { mls_rs_codec::MlsDecode }
+            }
         }
     }
 
@@ -34,6 +37,7 @@ impl Operation {
             Operation::Size => quote! { mls_encoded_len },
             Operation::Encode => quote! { mls_encode },
             Operation::Decode => quote! { mls_decode },
+            Operation::DecodeWithLimits => quote! { mls_decode_with_limits },
         }
     }
 
@@ -42,6 +46,7 @@ impl Operation {
             Operation::Size => quote! {},
             Operation::Encode => quote! { , writer },
             Operation::Decode => quote! { reader },
+            Operation::DecodeWithLimits => quote! { reader, limits },
         }
     }
 
@@ -50,8 +55,13 @@ impl Operation {
             Operation::Size => false,
             Operation::Encode => true,
             Operation::Decode => true,
+            Operation::DecodeWithLimits => true,
         }
     }
+
+    fn is_decode(&self) -> bool {
+        matches!(self, Operation::Decode | Operation::DecodeWithLimits)
+    }
 }
 
 #[derive(Debug, FromField)]
@@ -175,7 +185,7 @@ fn enum_impl(
     let extras = operation.extras();
     let enum_name = &ident;
     let repr_ident = repr_ident(attrs);
-    if matches!(operation, Operation::Decode) {
+    if operation.is_decode() {
         let cases = variants.iter().map(|variant| {
             let variant_name = &variant.ident;
 
@@ -215,7 +225,7 @@ fn enum_impl(
             let start = match operation {
                 Operation::Size => Some(quote! { + }),
                 Operation::Encode => Some(quote! {;}),
-                Operation::Decode => None,
+                Operation::Decode | Operation::DecodeWithLimits => None,
             };
 
             (
@@ -250,7 +260,9 @@ fn struct_impl(s: &Fields<MlsFieldReceiver>, operation: Operation) -> TokenStrea
             Operation::Size | Operation::Encode => {
                 (field.call_tokens(Index::from(index)), quote! {})
             }
-            Operation::Decode => (quote! {}, field.name(Index::from(index))),
+            Operation::Decode | Operation::DecodeWithLimits => {
+                (quote! {}, field.name(Index::from(index)))
+            }
         };
 
         let handle_error = operation.is_result().then_some(quote! { ? });
@@ -266,7 +278,7 @@ fn struct_impl(s: &Fields<MlsFieldReceiver>, operation: Operation) -> TokenStrea
     match operation {
         Operation::Size => quote! { 0 #(+ #recurse)* },
         Operation::Encode => quote! { #(#recurse;)* Ok(()) },
-        Operation::Decode => quote! { Ok(Self { #(#recurse,)* }) },
+        Operation::Decode | Operation::DecodeWithLimits => quote! { Ok(Self { #(#recurse,)* }) },
     }
 }
 
@@ -326,12 +338,84 @@ pub fn derive_encode(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 
 #[proc_macro_derive(MlsDecode, attributes(mls_codec))]
 pub fn derive_decode(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let trait_name = quote! { mls_rs_codec::MlsDecode };
+    let input = parse_macro_input!(input as DeriveInput);
 
-    let function_def =
-        quote! { fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> };
+    let input = MlsInputReceiver::from_derive_input(&input).unwrap();
 
-    derive_impl(input, trait_name, function_def, |input| {
-        input.handle_input(Operation::Decode)
-    })
+    let name = &input.ident;
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let decode = input.handle_input(Operation::Decode);
+    let decode_with_limits = input.handle_input(Operation::DecodeWithLimits);
+
+    let expanded = quote! {
+        impl #impl_generics mls_rs_codec::MlsDecode for #name #ty_generics #where_clause {
+            fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+                #decode
+            }
+
+            fn mls_decode_with_limits(
+                reader: &mut &[u8],
+                limits: &mls_rs_codec::DecodeLimits,
+            ) -> Result<Self, mls_rs_codec::Error> {
+                #decode_with_limits
+            }
+        }
+    };
+
+    proc_macro::TokenStream::from(expanded)
+}
+
+#[derive(FromDeriveInput)]
+#[darling(attributes(mls_extension))]
+struct MlsExtensionReceiver {
+    ident: Ident,
+    generics: Generics,
+    extension_type: Expr,
+    #[darling(rename = "crate")]
+    crate_path: Option<Path>,
+}
+
+/// Implement `MlsCodecExtension` from `mls_rs_core`, associating a type that
+/// implements the codec traits with an extension type.
+///
+/// The extension type is set with `#[mls_extension(extension_type = ...)]` and
+/// can be any expression convertible into an `ExtensionType`. The path to
+/// `mls_rs_core` can be overridden with `#[mls_extension(crate = "...")]`.
+#[proc_macro_derive(MlsExtension, attributes(mls_extension))]
+pub fn derive_extension(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let input = match MlsExtensionReceiver::from_derive_input(&input) {
+        Ok(input) => input,
+        Err(e) => return e.write_errors().into(),
+    };
+
+    let name = &input.ident;
+    let extension_type = &input.extension_type;
+
+    let crate_path = input
+        .crate_path
+        .unwrap_or_else(|| parse_quote! { mls_rs_core });
+
+    // Integer literals can not be inferred as `u16` through `From`.
+    let extension_type = match extension_type {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Int(_), ..
+        }) => quote! { #crate_path::extension::ExtensionType::new(#extension_type) },
+        _ => quote! { #crate_path::extension::ExtensionType::from(#extension_type) },
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let expanded = quote! {
+        impl #impl_generics #crate_path::extension::MlsCodecExtension for #name #ty_generics #where_clause {
+            fn extension_type() -> #crate_path::extension::ExtensionType {
+                #extension_type
+            }
+        }
+    };
+
+    proc_macro::TokenStream::from(expanded)
 }
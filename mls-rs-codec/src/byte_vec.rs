@@ -2,7 +2,9 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::{iter::mls_decode_split_on_collection, Error, MlsEncode, MlsSize, VarInt};
+use crate::{
+    iter::mls_decode_split_on_collection, DecodeLimits, Error, MlsEncode, MlsSize, VarInt,
+};
 
 use alloc::vec::Vec;
 
@@ -43,14 +45,28 @@ pub fn mls_decode<T>(reader: &mut &[u8]) -> Result<T, crate::Error>
 where
     T: From<Vec<u8>>,
 {
-    fn decode_vec(reader: &mut &[u8]) -> Result<Vec<u8>, crate::Error> {
+    mls_decode_with_limits(reader, &DecodeLimits::UNLIMITED)
+}
+
+/// Optimized decoding for types that can be represented as `Vec<u8>`,
+/// rejecting data longer than [`DecodeLimits::max_opaque_len`].
+pub fn mls_decode_with_limits<T>(
+    reader: &mut &[u8],
+    limits: &DecodeLimits,
+) -> Result<T, crate::Error>
+where
+    T: From<Vec<u8>>,
+{
+    fn decode_vec(reader: &mut &[u8], limits: &DecodeLimits) -> Result<Vec<u8>, crate::Error> {
         let (data, rest) = mls_decode_split_on_collection(reader)?;
 
+        limits.check_opaque_len(data.len())?;
+
         *reader = rest;
 
         Ok(data.to_vec())
     }
 
-    let out = decode_vec(reader)?;
+    let out = decode_vec(reader, limits)?;
     Ok(out.into())
 }
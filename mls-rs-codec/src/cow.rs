@@ -3,7 +3,7 @@ use alloc::{
     vec::Vec,
 };
 
-use crate::{Error, MlsDecode, MlsEncode, MlsSize};
+use crate::{DecodeLimits, Error, MlsDecode, MlsEncode, MlsSize};
 
 impl<T> MlsSize for Cow<'_, T>
 where
@@ -32,4 +32,8 @@ where
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, Error> {
         MlsDecode::mls_decode(reader).map(Cow::Owned)
     }
+
+    fn mls_decode_with_limits(reader: &mut &[u8], limits: &DecodeLimits) -> Result<Self, Error> {
+        MlsDecode::mls_decode_with_limits(reader, limits).map(Cow::Owned)
+    }
 }
@@ -18,6 +18,8 @@ pub mod byte_vec;
 
 pub mod iter;
 
+mod limits;
+
 mod bool;
 mod cow;
 mod map;
@@ -28,9 +30,10 @@ mod tuple;
 mod varint;
 mod vec;
 
+pub use limits::DecodeLimits;
 pub use varint::*;
 
-pub use mls_rs_codec_derive::*;
+pub use mls_rs_codec_derive::{MlsDecode, MlsEncode, MlsSize};
 
 #[derive(Debug)]
 #[cfg_attr(feature = "std", derive(thiserror::Error))]
@@ -52,6 +55,16 @@ pub enum Error {
     Utf8,
     #[cfg_attr(feature = "std", error("mls codec error: {0}"))]
     Custom(u8),
+    #[cfg_attr(
+        feature = "std",
+        error("Opaque field of {0} bytes exceeds the decode limit")
+    )]
+    OpaqueLimitExceeded(usize),
+    #[cfg_attr(
+        feature = "std",
+        error("Collection of {0} elements exceeds the decode limit")
+    )]
+    ElementLimitExceeded(usize),
 }
 
 /// Trait that determines the encoded length in MLS encoding.
@@ -120,6 +133,17 @@ where
 /// Trait to support deserialzing to a type using MLS encoding.
 pub trait MlsDecode: Sized {
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, Error>;
+
+    /// Decode while enforcing `limits` on all nested opaque fields and
+    /// collections.
+    ///
+    /// Types that contain no collections can rely on the default
+    /// implementation, which ignores `limits`.
+    #[inline]
+    fn mls_decode_with_limits(reader: &mut &[u8], limits: &DecodeLimits) -> Result<Self, Error> {
+        let _ = limits;
+        Self::mls_decode(reader)
+    }
 }
 
 impl<T: MlsDecode> MlsDecode for Box<T> {
@@ -127,4 +151,9 @@ impl<T: MlsDecode> MlsDecode for Box<T> {
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, Error> {
         T::mls_decode(reader).map(Box::new)
     }
+
+    #[inline]
+    fn mls_decode_with_limits(reader: &mut &[u8], limits: &DecodeLimits) -> Result<Self, Error> {
+        T::mls_decode_with_limits(reader, limits).map(Box::new)
+    }
 }
@@ -0,0 +1,61 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::Error;
+
+/// Limits applied while decoding with
+/// [`MlsDecode::mls_decode_with_limits`](crate::MlsDecode::mls_decode_with_limits).
+///
+/// The limits are checked before any memory is allocated for a field, so
+/// they bound the allocations an adversarial input can cause independently
+/// of the validation done after decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DecodeLimits {
+    /// Maximum length in bytes of a single opaque field, i.e. a field
+    /// decoded with [`byte_vec`](crate::byte_vec) or a string.
+    pub max_opaque_len: usize,
+    /// Maximum number of elements of a single vector or map.
+    pub max_elements: usize,
+}
+
+impl DecodeLimits {
+    /// No limits besides the length of the input.
+    pub const UNLIMITED: DecodeLimits = DecodeLimits {
+        max_opaque_len: usize::MAX,
+        max_elements: usize::MAX,
+    };
+
+    /// Create new limits.
+    pub const fn new(max_opaque_len: usize, max_elements: usize) -> Self {
+        Self {
+            max_opaque_len,
+            max_elements,
+        }
+    }
+
+    /// Check the length of an opaque field, for use in manual
+    /// [`MlsDecode`](crate::MlsDecode) implementations.
+    #[inline]
+    pub fn check_opaque_len(&self, len: usize) -> Result<(), Error> {
+        (len <= self.max_opaque_len)
+            .then_some(())
+            .ok_or(Error::OpaqueLimitExceeded(len))
+    }
+
+    /// Check the number of elements of a collection, for use in manual
+    /// [`MlsDecode`](crate::MlsDecode) implementations.
+    #[inline]
+    pub fn check_elements(&self, count: usize) -> Result<(), Error> {
+        (count <= self.max_elements)
+            .then_some(())
+            .ok_or(Error::ElementLimitExceeded(count))
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
@@ -7,7 +7,7 @@ use alloc::{collections::BTreeMap, vec::Vec};
 #[cfg(feature = "std")]
 use std::{collections::HashMap, hash::Hash};
 
-use crate::{MlsDecode, MlsEncode, MlsSize};
+use crate::{DecodeLimits, MlsDecode, MlsEncode, MlsSize};
 
 #[cfg(feature = "std")]
 impl<K, V> MlsSize for HashMap<K, V>
@@ -37,12 +37,27 @@ where
     K: MlsDecode + Hash + Eq,
     V: MlsDecode,
 {
+    #[inline]
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, crate::Error> {
+        Self::mls_decode_with_limits(reader, &DecodeLimits::UNLIMITED)
+    }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, crate::Error> {
         crate::iter::mls_decode_collection(reader, |data| {
             let mut items = HashMap::new();
+            let mut count = 0;
 
             while !data.is_empty() {
-                items.insert(K::mls_decode(data)?, V::mls_decode(data)?);
+                count += 1;
+                limits.check_elements(count)?;
+
+                items.insert(
+                    K::mls_decode_with_limits(data, limits)?,
+                    V::mls_decode_with_limits(data, limits)?,
+                );
             }
 
             Ok(items)
@@ -75,12 +90,27 @@ where
     K: MlsDecode + Eq + Ord,
     V: MlsDecode,
 {
+    #[inline]
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, crate::Error> {
+        Self::mls_decode_with_limits(reader, &DecodeLimits::UNLIMITED)
+    }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, crate::Error> {
         crate::iter::mls_decode_collection(reader, |data| {
             let mut items = BTreeMap::new();
+            let mut count = 0;
 
             while !data.is_empty() {
-                items.insert(K::mls_decode(data)?, V::mls_decode(data)?);
+                count += 1;
+                limits.check_elements(count)?;
+
+                items.insert(
+                    K::mls_decode_with_limits(data, limits)?,
+                    V::mls_decode_with_limits(data, limits)?,
+                );
             }
 
             Ok(items)
@@ -2,7 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::{MlsDecode, MlsEncode, MlsSize};
+use crate::{DecodeLimits, MlsDecode, MlsEncode, MlsSize};
 use alloc::vec::Vec;
 
 impl<T: MlsSize> MlsSize for Option<T> {
@@ -35,6 +35,17 @@ impl<T: MlsDecode> MlsDecode for Option<T> {
             n => Err(crate::Error::OptionOutOfRange(n)),
         }
     }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, crate::Error> {
+        match u8::mls_decode(reader)? {
+            0 => Ok(None),
+            1 => T::mls_decode_with_limits(reader, limits).map(Some),
+            n => Err(crate::Error::OptionOutOfRange(n)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-use crate::{MlsDecode, MlsEncode, MlsSize};
+use crate::{DecodeLimits, MlsDecode, MlsEncode, MlsSize};
 use alloc::{string::String, vec::Vec};
 
 impl MlsSize for str {
@@ -29,6 +29,14 @@ impl MlsDecode for String {
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, crate::Error> {
         String::from_utf8(Vec::mls_decode(reader)?).map_err(|_| crate::Error::Utf8)
     }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, crate::Error> {
+        let bytes = crate::byte_vec::mls_decode_with_limits::<Vec<u8>>(reader, limits)?;
+        String::from_utf8(bytes).map_err(|_| crate::Error::Utf8)
+    }
 }
 
 #[cfg(test)]
@@ -2,7 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::{MlsDecode, MlsEncode, MlsSize};
+use crate::{DecodeLimits, MlsDecode, MlsEncode, MlsSize};
 
 use alloc::vec::Vec;
 
@@ -35,4 +35,14 @@ where
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, crate::Error> {
         Ok((T::mls_decode(reader)?, U::mls_decode(reader)?))
     }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, crate::Error> {
+        Ok((
+            T::mls_decode_with_limits(reader, limits)?,
+            U::mls_decode_with_limits(reader, limits)?,
+        ))
+    }
 }
@@ -4,7 +4,7 @@
 
 use alloc::vec::Vec;
 
-use crate::{MlsDecode, MlsEncode, MlsSize};
+use crate::{DecodeLimits, MlsDecode, MlsEncode, MlsSize};
 
 impl<T> MlsSize for [T]
 where
@@ -48,12 +48,21 @@ impl<T> MlsDecode for Vec<T>
 where
     T: MlsDecode,
 {
+    #[inline]
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, crate::Error> {
+        Self::mls_decode_with_limits(reader, &DecodeLimits::UNLIMITED)
+    }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, crate::Error> {
         crate::iter::mls_decode_collection(reader, |data| {
             let mut items = Vec::new();
 
             while !data.is_empty() {
-                items.push(T::mls_decode(data)?);
+                limits.check_elements(items.len() + 1)?;
+                items.push(T::mls_decode_with_limits(data, limits)?);
             }
 
             Ok(items)
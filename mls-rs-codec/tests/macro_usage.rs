@@ -2,7 +2,8 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use assert_matches::assert_matches;
+use mls_rs_codec::{DecodeLimits, Error, MlsDecode, MlsEncode, MlsSize};
 
 #[derive(Debug, Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
 struct TestTupleStruct(u64);
@@ -147,6 +148,38 @@ fn round_trip_custom_module_enum() {
     assert_eq!(item, decoded)
 }
 
+#[test]
+fn decode_limits_are_enforced_by_derived_decoders() {
+    #[derive(Debug, PartialEq, Eq, Clone, MlsSize, MlsEncode, MlsDecode)]
+    struct TestLimits {
+        #[mls_codec(with = "mls_rs_codec::byte_vec")]
+        opaque: Vec<u8>,
+        items: Vec<Option<u16>>,
+    }
+
+    let item = TestLimits {
+        opaque: vec![1, 2, 3],
+        items: vec![Some(1), None],
+    };
+
+    let serialized = item.mls_encode_to_vec().unwrap();
+
+    let decoded =
+        TestLimits::mls_decode_with_limits(&mut &*serialized, &DecodeLimits::new(3, 2)).unwrap();
+
+    assert_eq!(decoded, item);
+
+    assert_matches!(
+        TestLimits::mls_decode_with_limits(&mut &*serialized, &DecodeLimits::new(2, 2)),
+        Err(Error::OpaqueLimitExceeded(3))
+    );
+
+    assert_matches!(
+        TestLimits::mls_decode_with_limits(&mut &*serialized, &DecodeLimits::new(3, 1)),
+        Err(Error::ElementLimitExceeded(2))
+    );
+}
+
 mod test_with {
     use mls_rs_codec::MlsDecode;
 
@@ -162,4 +195,11 @@ mod test_with {
     pub fn mls_decode(reader: &mut &[u8]) -> Result<u8, mls_rs_codec::Error> {
         Ok(<[u8; 2]>::mls_decode(reader)?[0])
     }
+
+    pub fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        _limits: &mls_rs_codec::DecodeLimits,
+    ) -> Result<u8, mls_rs_codec::Error> {
+        mls_decode(reader)
+    }
 }
@@ -0,0 +1,21 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::error::IntoAnyError;
+
+/// Compression algorithm used to reduce the size of serialized ratchet trees,
+/// group info and stored group state.
+pub trait Compressor: Send + Sync {
+    type Error: IntoAnyError;
+
+    /// Identifier of the algorithm written alongside compressed data, so that
+    /// data compressed with another algorithm can be detected.
+    fn algorithm_id(&self) -> u16;
+
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error>;
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
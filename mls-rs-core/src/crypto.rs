@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use crate::error::IntoAnyError;
+use crate::secret::{KeyScheduleSecret, KeyScheduleSecretError};
 use alloc::vec;
 use alloc::vec::Vec;
 use core::{
@@ -13,7 +14,11 @@ use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use zeroize::{ZeroizeOnDrop, Zeroizing};
 
 mod cipher_suite;
+mod fingerprint;
+mod signature_key_storage;
 pub use self::cipher_suite::*;
+pub use self::fingerprint::*;
+pub use self::signature_key_storage::*;
 
 #[cfg(feature = "test_suite")]
 pub mod test_suite;
@@ -389,6 +394,85 @@ pub trait CipherSuiteProvider: Send + Sync {
     /// and inputted to [kdf_expand](CipherSuiteProvider::kdf_expand).
     fn kdf_extract_size(&self) -> usize;
 
+    /// Return `true` if this provider may return a [SecretHandle](crate::secret::SecretHandle)
+    /// from [kdf_extract_secret](CipherSuiteProvider::kdf_extract_secret) and
+    /// [kdf_expand_secret](CipherSuiteProvider::kdf_expand_secret).
+    ///
+    /// Providers that return `true` must override all functions taking a
+    /// [KeyScheduleSecret]. Their default implementations only accept
+    /// extractable secrets and return
+    /// [KeyScheduleSecretError::NonExtractable] for handles.
+    fn supports_secret_handles(&self) -> bool {
+        false
+    }
+
+    /// Same as [kdf_extract](CipherSuiteProvider::kdf_extract) for secrets
+    /// that may be non-extractable.
+    async fn kdf_extract_secret(
+        &self,
+        salt: &KeyScheduleSecret,
+        ikm: &KeyScheduleSecret,
+    ) -> Result<KeyScheduleSecret, KeyScheduleSecretError> {
+        self.kdf_extract(salt.extractable()?, ikm.extractable()?)
+            .await
+            .map(Into::into)
+            .map_err(KeyScheduleSecretError::provider)
+    }
+
+    /// Same as [kdf_expand](CipherSuiteProvider::kdf_expand) for secrets
+    /// that may be non-extractable.
+    async fn kdf_expand_secret(
+        &self,
+        prk: &KeyScheduleSecret,
+        info: &[u8],
+        len: usize,
+    ) -> Result<KeyScheduleSecret, KeyScheduleSecretError> {
+        self.kdf_expand(prk.extractable()?, info, len)
+            .await
+            .map(Into::into)
+            .map_err(KeyScheduleSecretError::provider)
+    }
+
+    /// Same as [mac](CipherSuiteProvider::mac) for a key that may be
+    /// non-extractable.
+    async fn mac_with_secret(
+        &self,
+        key: &KeyScheduleSecret,
+        data: &[u8],
+    ) -> Result<Vec<u8>, KeyScheduleSecretError> {
+        self.mac(key.extractable()?, data)
+            .await
+            .map_err(KeyScheduleSecretError::provider)
+    }
+
+    /// Same as [aead_seal](CipherSuiteProvider::aead_seal) for a key that may
+    /// be non-extractable.
+    async fn aead_seal_with_secret(
+        &self,
+        key: &KeyScheduleSecret,
+        data: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, KeyScheduleSecretError> {
+        self.aead_seal(key.extractable()?, data, aad, nonce)
+            .await
+            .map_err(KeyScheduleSecretError::provider)
+    }
+
+    /// Same as [aead_open](CipherSuiteProvider::aead_open) for a key that may
+    /// be non-extractable.
+    async fn aead_open_with_secret(
+        &self,
+        key: &KeyScheduleSecret,
+        ciphertext: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, KeyScheduleSecretError> {
+        self.aead_open(key.extractable()?, ciphertext, aad, nonce)
+            .await
+            .map_err(KeyScheduleSecretError::provider)
+    }
+
     /// Encrypt the plaintext `pt` with optional public additional authenticated data `aad` to the
     /// public key `remote_key` using additional context information `info` (which can be empty if
     /// not needed). This function combines the action
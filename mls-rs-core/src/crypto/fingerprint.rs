@@ -0,0 +1,267 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+use mls_rs_codec::{MlsEncode, MlsSize};
+
+use super::{CipherSuite, CipherSuiteProvider, HpkePublicKey, SignaturePublicKey};
+
+const SIGNATURE_KEY_LABEL: &[u8] = b"MLS 1.0 signature key fingerprint";
+const HPKE_KEY_LABEL: &[u8] = b"MLS 1.0 HPKE key fingerprint";
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+#[derive(MlsSize, MlsEncode)]
+struct FingerprintInput<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    label: &'a [u8],
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    value: &'a [u8],
+}
+
+/// Multibase encoding used to render a [`Fingerprint`].
+///
+/// The output starts with the multibase prefix of the encoding so that
+/// fingerprints rendered with different encodings are never confused.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FingerprintEncoding {
+    /// Lowercase hexadecimal, prefix `f`.
+    Base16,
+    /// Lowercase RFC 4648 base32 without padding, prefix `b`.
+    #[default]
+    Base32,
+    /// Bitcoin base58 alphabet, prefix `z`.
+    Base58Btc,
+}
+
+/// Canonical fingerprint of a public key.
+///
+/// The fingerprint is the hash, using the hash function of the cipher suite,
+/// of a labeled structure containing the cipher suite and the key. The same
+/// key therefore has different fingerprints in different cipher suites and
+/// signature and HPKE keys never share a fingerprint.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    cipher_suite: CipherSuite,
+    hash: Vec<u8>,
+}
+
+impl Debug for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fingerprint")
+            .field("cipher_suite", &self.cipher_suite)
+            .field("hash", &crate::debug::pretty_bytes(&self.hash))
+            .finish()
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode(FingerprintEncoding::default()))
+    }
+}
+
+impl Fingerprint {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn compute<P: CipherSuiteProvider>(
+        label: &[u8],
+        value: &[u8],
+        cipher_suite_provider: &P,
+    ) -> Result<Self, P::Error> {
+        let cipher_suite = cipher_suite_provider.cipher_suite();
+
+        let input = FingerprintInput {
+            label,
+            cipher_suite,
+            value,
+        }
+        .mls_encode_to_vec()
+        // Encoding a structure of in-memory slices cannot fail
+        .unwrap_or_default();
+
+        let hash = cipher_suite_provider.hash(&input).await?;
+
+        Ok(Self { cipher_suite, hash })
+    }
+
+    /// Fingerprint of the signature key `key`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn signature_key<P: CipherSuiteProvider>(
+        key: &SignaturePublicKey,
+        cipher_suite_provider: &P,
+    ) -> Result<Self, P::Error> {
+        Self::compute(SIGNATURE_KEY_LABEL, key, cipher_suite_provider).await
+    }
+
+    /// Fingerprint of the HPKE key `key`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn hpke_key<P: CipherSuiteProvider>(
+        key: &HpkePublicKey,
+        cipher_suite_provider: &P,
+    ) -> Result<Self, P::Error> {
+        Self::compute(HPKE_KEY_LABEL, key, cipher_suite_provider).await
+    }
+
+    /// Cipher suite whose hash function produced this fingerprint.
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.hash
+    }
+
+    /// Fingerprint truncated to its first `len` bytes, for display in space
+    /// constrained UIs. Truncated fingerprints are only suitable for
+    /// comparison by users and not for key authentication.
+    pub fn short(&self, len: usize) -> Self {
+        Self {
+            cipher_suite: self.cipher_suite,
+            hash: self.hash[..len.min(self.hash.len())].to_vec(),
+        }
+    }
+
+    /// Render the fingerprint as a multibase string.
+    pub fn encode(&self, encoding: FingerprintEncoding) -> String {
+        match encoding {
+            FingerprintEncoding::Base16 => multibase('f', hex::encode(&self.hash).into_bytes()),
+            FingerprintEncoding::Base32 => multibase('b', base32(&self.hash)),
+            FingerprintEncoding::Base58Btc => multibase('z', base58(&self.hash)),
+        }
+    }
+}
+
+impl HpkePublicKey {
+    /// Canonical fingerprint of this key, as displayed to users.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn fingerprint<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+    ) -> Result<Fingerprint, P::Error> {
+        Fingerprint::hpke_key(self, cipher_suite_provider).await
+    }
+}
+
+impl SignaturePublicKey {
+    /// Canonical fingerprint of this key, as displayed to users.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn fingerprint<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+    ) -> Result<Fingerprint, P::Error> {
+        Fingerprint::signature_key(self, cipher_suite_provider).await
+    }
+}
+
+fn multibase(prefix: char, encoded: Vec<u8>) -> String {
+    let mut out = String::with_capacity(encoded.len() + 1);
+    out.push(prefix);
+    // All alphabets are ASCII
+    out.extend(encoded.into_iter().map(char::from));
+    out
+}
+
+fn base32(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer = 0u16;
+    let mut bits = 0;
+
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u16;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize]);
+        }
+    }
+
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize]);
+    }
+
+    out
+}
+
+fn base58(data: &[u8]) -> Vec<u8> {
+    let zeros = data.iter().take_while(|b| **b == 0).count();
+
+    // Little endian base 58 digits of the input
+    let mut digits = Vec::<u8>::with_capacity(data.len() * 138 / 100 + 1);
+
+    for byte in &data[zeros..] {
+        let mut carry = *byte as u32;
+
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    core::iter::repeat_n(BASE58_ALPHABET[0], zeros)
+        .chain(digits.iter().rev().map(|d| BASE58_ALPHABET[*d as usize]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fingerprint, FingerprintEncoding};
+    use crate::crypto::CipherSuite;
+
+    fn fingerprint(hash: &[u8]) -> Fingerprint {
+        Fingerprint {
+            cipher_suite: CipherSuite::CURVE25519_AES128,
+            hash: hash.to_vec(),
+        }
+    }
+
+    #[test]
+    fn multibase_encodings_match_reference_vectors() {
+        let fingerprint = fingerprint(b"hello world");
+
+        assert_eq!(
+            fingerprint.encode(FingerprintEncoding::Base16),
+            "f68656c6c6f20776f726c64"
+        );
+
+        assert_eq!(
+            fingerprint.encode(FingerprintEncoding::Base32),
+            "bnbswy3dpeb3w64tmmq"
+        );
+
+        assert_eq!(
+            fingerprint.encode(FingerprintEncoding::Base58Btc),
+            "zStV1DL6CwTryKyV"
+        );
+    }
+
+    #[test]
+    fn base58_keeps_leading_zeros() {
+        let fingerprint = fingerprint(&[0, 0, 0x28, 0x7f, 0xb4, 0xcd]);
+
+        assert_eq!(
+            fingerprint.encode(FingerprintEncoding::Base58Btc),
+            "z11233QC4"
+        );
+    }
+
+    #[test]
+    fn short_fingerprint_is_a_prefix() {
+        let fingerprint = fingerprint(b"hello world");
+
+        assert_eq!(fingerprint.short(4).as_bytes(), b"hell");
+        assert_eq!(fingerprint.short(100), fingerprint);
+    }
+}
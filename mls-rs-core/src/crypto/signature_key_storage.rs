@@ -0,0 +1,37 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::error::IntoAnyError;
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+
+use super::{SignaturePublicKey, SignatureSecretKey};
+
+/// Storage of signature secret keys indexed by their public key.
+///
+/// When a client generates a separate signature key for each group, `mls_rs`
+/// inserts every generated key into this storage. The key of a group is then
+/// found from the public key in the leaf of the client when the group is
+/// joined or loaded.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait SignatureKeyStorage: Send + Sync {
+    type Error: IntoAnyError;
+
+    /// Secret key matching `public_key`, if any.
+    async fn get(
+        &self,
+        public_key: &SignaturePublicKey,
+    ) -> Result<Option<SignatureSecretKey>, Self::Error>;
+
+    /// Store `secret_key` under `public_key`, replacing any previous key.
+    async fn insert(
+        &mut self,
+        public_key: &SignaturePublicKey,
+        secret_key: SignatureSecretKey,
+    ) -> Result<(), Self::Error>;
+
+    /// Delete the secret key matching `public_key`.
+    async fn delete(&mut self, public_key: &SignaturePublicKey) -> Result<(), Self::Error>;
+}
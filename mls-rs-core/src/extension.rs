@@ -15,6 +15,21 @@ mod list;
 
 pub use list::*;
 
+/// Derive macro implementing [`MlsCodecExtension`] for a type that implements
+/// the [mls_rs_codec] traits.
+///
+/// ```ignore
+/// #[derive(MlsSize, MlsEncode, MlsDecode, MlsExtension)]
+/// #[mls_extension(extension_type = 0xff00)]
+/// struct MyExtension {
+///     value: u64,
+/// }
+/// ```
+///
+/// Within `mls_rs_core` itself, or if it is renamed, the path to the crate
+/// is set with `#[mls_extension(crate = "...")]`.
+pub use mls_rs_codec_derive::MlsExtension;
+
 /// Wrapper type representing an extension identifier along with default values
 /// defined by the MLS RFC.
 #[derive(
@@ -37,6 +52,11 @@ impl ExtensionType {
     #[cfg(feature = "last_resort_key_package_ext")]
     pub const LAST_RESORT_KEY_PACKAGE: ExtensionType = ExtensionType(0x000A);
 
+    /// Capability advertised by members that are able to receive targeted
+    /// messages as defined by the MLS extensions draft.
+    #[cfg(feature = "targeted_messages")]
+    pub const TARGETED_MESSAGES_CAPABILITY: ExtensionType = ExtensionType(0x0006);
+
     /// Default extension types defined
     /// in [RFC 9420](https://www.rfc-editor.org/rfc/rfc9420.html#name-leaf-node-contents)
     pub const DEFAULT: &'static [ExtensionType] = &[
@@ -224,7 +244,9 @@ mod tests {
     use assert_matches::assert_matches;
     use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
-    use super::{Extension, ExtensionError, ExtensionType, MlsCodecExtension, MlsExtension};
+    use super::{
+        Extension, ExtensionError, ExtensionList, ExtensionType, MlsCodecExtension, MlsExtension,
+    };
 
     struct TestExtension;
 
@@ -262,6 +284,26 @@ mod tests {
         )
     }
 
+    #[derive(Debug, PartialEq, MlsSize, MlsEncode, MlsDecode, super::MlsExtension)]
+    #[mls_extension(crate = "crate", extension_type = 44)]
+    struct DerivedTestExtension {
+        value: u64,
+    }
+
+    #[test]
+    fn derived_extension_round_trips() {
+        let mut list = ExtensionList::new();
+
+        list.set_from(DerivedTestExtension { value: 7 }).unwrap();
+
+        assert!(list.has_extension(44.into()));
+
+        assert_eq!(
+            list.get_as::<DerivedTestExtension>().unwrap(),
+            Some(DerivedTestExtension { value: 7 })
+        );
+    }
+
     #[test]
     fn incorrect_type_is_discovered() {
         let ext = Extension::new(42.into(), vec![0]);
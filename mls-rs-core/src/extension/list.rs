@@ -5,7 +5,7 @@
 use super::{Extension, ExtensionError, ExtensionType, MlsExtension};
 use alloc::vec::Vec;
 use core::ops::Deref;
-use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_codec::{DecodeLimits, MlsDecode, MlsEncode, MlsSize};
 
 /// A collection of MLS [Extensions](super::Extension).
 ///
@@ -13,6 +13,11 @@ use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 /// # Warning
 ///
 /// Extension lists require that each type of extension has at most one entry.
+///
+/// Extensions added to a list are kept sorted by
+/// [ExtensionType](super::ExtensionType), so lists built locally always have
+/// the same encoding. Decoded lists keep the order they were received in,
+/// see [is_canonical](ExtensionList::is_canonical).
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(
     all(feature = "ffi", not(test)),
@@ -41,11 +46,20 @@ impl PartialEq for ExtensionList {
 
 impl MlsDecode for ExtensionList {
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        Self::mls_decode_with_limits(reader, &DecodeLimits::UNLIMITED)
+    }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, mls_rs_codec::Error> {
         mls_rs_codec::iter::mls_decode_collection(reader, |data| {
             let mut list = ExtensionList::new();
 
             while !data.is_empty() {
-                let ext = Extension::mls_decode(data)?;
+                limits.check_elements(list.0.len() + 1)?;
+
+                let ext = Extension::mls_decode_with_limits(data, limits)?;
                 let ext_type = ext.extension_type;
 
                 if list.0.iter().any(|e| e.extension_type == ext_type) {
@@ -128,7 +142,8 @@ impl ExtensionList {
     /// [Extension](super::Extension) value.
     ///
     /// If there is already an entry in the list for the same extension type,
-    /// then the prior value is removed as part of the insertion.
+    /// then the prior value is removed as part of the insertion. Otherwise,
+    /// the extension is inserted before the first entry of a greater type.
     pub fn set(&mut self, ext: Extension) {
         let mut found = self
             .0
@@ -138,7 +153,13 @@ impl ExtensionList {
         if let Some(found) = found.take() {
             *found = ext;
         } else {
-            self.0.push(ext);
+            let index = self
+                .0
+                .iter()
+                .position(|e| e.extension_type > ext.extension_type)
+                .unwrap_or(self.0.len());
+
+            self.0.insert(index, ext);
         }
     }
 
@@ -157,12 +178,32 @@ impl ExtensionList {
         self.0.retain(|e| e.extension_type != ext_type)
     }
 
+    /// Remove an extension from the list by
+    /// [ExtensionType](super::ExtensionType) and return it.
+    pub fn take(&mut self, ext_type: ExtensionType) -> Option<Extension> {
+        let index = self.0.iter().position(|e| e.extension_type == ext_type)?;
+        Some(self.0.remove(index))
+    }
+
     /// Append another extension list to this one.
     ///
     /// If there is already an entry in the list for the same extension type,
     /// then the existing value is removed.
     pub fn append(&mut self, others: Self) {
-        self.0.extend(others.0);
+        self.extend(others.0);
+    }
+
+    /// Determine if the extensions are sorted by strictly increasing
+    /// [ExtensionType](super::ExtensionType), which is the order used by
+    /// lists built locally.
+    ///
+    /// Lists received from other implementations may use a different order.
+    /// Such lists are still valid and are encoded in the order they were
+    /// received in.
+    pub fn is_canonical(&self) -> bool {
+        self.0
+            .windows(2)
+            .all(|pair| pair[0].extension_type < pair[1].extension_type)
     }
 }
 
@@ -346,6 +387,38 @@ mod tests {
         assert_eq!(list, expected);
     }
 
+    #[test]
+    fn extensions_are_kept_in_canonical_order() {
+        let mut list = ExtensionList::new();
+        list.set_from(TestExtensionC(1)).unwrap();
+        list.set_from(TestExtensionA(2)).unwrap();
+        list.set_from(TestExtensionB(vec![3])).unwrap();
+        list.set_from(TestExtensionA(4)).unwrap();
+
+        let types = list.iter().map(|e| e.extension_type).collect::<Vec<_>>();
+
+        assert_eq!(
+            types,
+            [ExtensionType(128), ExtensionType(129), ExtensionType(130)]
+        );
+
+        assert!(list.is_canonical());
+    }
+
+    #[test]
+    fn decoded_extension_order_is_preserved() {
+        let extensions = ExtensionsVec(vec![
+            TestExtensionC(1).into_extension().unwrap(),
+            TestExtensionA(2).into_extension().unwrap(),
+        ]);
+
+        let encoded = extensions.mls_encode_to_vec().unwrap();
+        let list = ExtensionList::mls_decode(&mut &*encoded).unwrap();
+
+        assert!(!list.is_canonical());
+        assert_eq!(list.mls_encode_to_vec().unwrap(), encoded);
+    }
+
     #[test]
     fn extension_list_from_vec_maintains_extension_uniqueness() {
         let list = ExtensionList::from(vec![
@@ -3,11 +3,17 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 mod context;
+mod group_id_mapping;
 mod group_state;
+mod proposal_cache;
 mod proposal_type;
 mod roster;
+mod security_watermark;
 
 pub use context::*;
+pub use group_id_mapping::*;
 pub use group_state::*;
+pub use proposal_cache::*;
 pub use proposal_type::*;
 pub use roster::*;
+pub use security_watermark::*;
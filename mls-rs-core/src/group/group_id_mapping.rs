@@ -0,0 +1,42 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::error::IntoAnyError;
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Storage mapping identifiers chosen by the application, such as
+/// conversation identifiers, to the id of the group they currently use.
+///
+/// Several external ids may map to the same group. When the group id
+/// changes because a group is reinitialized, `mls_rs` moves all external ids
+/// of the old group to the new group with
+/// [`replace_group_id`](GroupIdMappingStorage::replace_group_id) once the
+/// new group is first written to storage.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait GroupIdMappingStorage: Send + Sync {
+    type Error: IntoAnyError;
+
+    /// Group currently used by `external_id`, if any.
+    async fn group_id(&self, external_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// External ids mapped to the group `group_id`.
+    async fn external_ids(&self, group_id: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error>;
+
+    /// Map `external_id` to the group `group_id`, replacing any previous
+    /// mapping of `external_id`.
+    async fn insert(&mut self, external_id: &[u8], group_id: &[u8]) -> Result<(), Self::Error>;
+
+    /// Atomically map all external ids of `old_group_id` to `new_group_id`.
+    async fn replace_group_id(
+        &mut self,
+        old_group_id: &[u8],
+        new_group_id: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Delete the mapping of `external_id`.
+    async fn delete(&mut self, external_id: &[u8]) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,73 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::fmt::{self, Debug};
+
+use crate::error::IntoAnyError;
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Generic representation of a proposal that was received by reference and
+/// is waiting to be committed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct StoredProposal {
+    /// Reference of the proposal, unique within an epoch of a group.
+    pub proposal_ref: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+impl Debug for StoredProposal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoredProposal")
+            .field(
+                "proposal_ref",
+                &crate::debug::pretty_bytes(&self.proposal_ref),
+            )
+            .field("data", &crate::debug::pretty_bytes(&self.data))
+            .finish()
+    }
+}
+
+impl StoredProposal {
+    pub fn new(proposal_ref: Vec<u8>, data: Vec<u8>) -> Self {
+        Self { proposal_ref, data }
+    }
+}
+
+/// Storage for proposals that a group has cached by reference but does not
+/// keep in memory.
+///
+/// Proposals are moved to this storage once the in-memory proposal cache of
+/// a group exceeds the limit configured in `mls_rs`. They are loaded back when
+/// a commit is created or processed, and deleted once the group moves to a
+/// new epoch.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait ProposalCacheStorage: Send + Sync {
+    type Error: IntoAnyError;
+
+    /// Store a proposal cached in epoch `epoch` of the group `group_id`.
+    async fn insert(
+        &mut self,
+        group_id: &[u8],
+        epoch: u64,
+        proposal: StoredProposal,
+    ) -> Result<(), Self::Error>;
+
+    /// References of all proposals stored for epoch `epoch` of the group
+    /// `group_id`.
+    async fn proposal_refs(&self, group_id: &[u8], epoch: u64)
+        -> Result<Vec<Vec<u8>>, Self::Error>;
+
+    /// All proposals stored for epoch `epoch` of the group `group_id`.
+    async fn proposals(
+        &self,
+        group_id: &[u8],
+        epoch: u64,
+    ) -> Result<Vec<StoredProposal>, Self::Error>;
+
+    /// Delete all proposals stored for the group `group_id`, in any epoch.
+    async fn delete(&mut self, group_id: &[u8]) -> Result<(), Self::Error>;
+}
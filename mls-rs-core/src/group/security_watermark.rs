@@ -0,0 +1,42 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::error::IntoAnyError;
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Storage of the security watermark of each group.
+///
+/// A watermark records the security parameters of the last group state
+/// written to storage, and `mls_rs` refuses to load a stored group state
+/// that is below it. Watermarks are stored as opaque bytes, in the same way
+/// as a [`GroupState`](super::GroupState).
+///
+/// The storage should be independent of the
+/// [`GroupStateStorage`](super::GroupStateStorage), for example kept in a
+/// platform keystore, so that it is not rolled back together with the
+/// group states.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait SecurityWatermarkStorage: Send + Sync {
+    type Error: IntoAnyError;
+
+    /// Watermark of the group with `group_id`, if any.
+    async fn watermark(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// Replace the watermark of the group with `group_id` by `watermark` if
+    /// the current watermark is `expected`, returning whether it was
+    /// replaced. An `expected` value of `None` matches a group without a
+    /// watermark.
+    ///
+    /// The comparison and the write must be atomic, so that a concurrent
+    /// writer of the same group can not lower the watermark.
+    async fn compare_and_set_watermark(
+        &mut self,
+        group_id: &[u8],
+        expected: Option<&[u8]>,
+        watermark: Vec<u8>,
+    ) -> Result<bool, Self::Error>;
+}
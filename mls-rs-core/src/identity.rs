@@ -7,6 +7,9 @@ mod credential;
 mod provider;
 mod signing_identity;
 
+#[cfg(feature = "key_transparency")]
+mod key_transparency;
+
 #[cfg(feature = "x509")]
 mod x509;
 
@@ -15,5 +18,8 @@ pub use credential::*;
 pub use provider::*;
 pub use signing_identity::*;
 
+#[cfg(feature = "key_transparency")]
+pub use key_transparency::*;
+
 #[cfg(feature = "x509")]
 pub use x509::*;
@@ -8,7 +8,7 @@ use core::{
 };
 
 use alloc::vec::Vec;
-use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_codec::{DecodeLimits, MlsDecode, MlsEncode, MlsSize};
 
 use super::BasicCredential;
 
@@ -214,15 +214,26 @@ impl MlsEncode for Credential {
 
 impl MlsDecode for Credential {
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        Self::mls_decode_with_limits(reader, &DecodeLimits::UNLIMITED)
+    }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, mls_rs_codec::Error> {
         let credential_type = CredentialType::mls_decode(reader)?;
 
         Ok(match credential_type {
-            CredentialType::BASIC => Credential::Basic(BasicCredential::mls_decode(reader)?),
+            CredentialType::BASIC => {
+                Credential::Basic(BasicCredential::mls_decode_with_limits(reader, limits)?)
+            }
             #[cfg(feature = "x509")]
-            CredentialType::X509 => Credential::X509(CertificateChain::mls_decode(reader)?),
+            CredentialType::X509 => {
+                Credential::X509(CertificateChain::mls_decode_with_limits(reader, limits)?)
+            }
             custom => Credential::Custom(CustomCredential {
                 credential_type: custom,
-                data: mls_rs_codec::byte_vec::mls_decode(reader)?,
+                data: mls_rs_codec::byte_vec::mls_decode_with_limits(reader, limits)?,
             }),
         })
     }
@@ -0,0 +1,306 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{
+    crypto::CipherSuiteProvider,
+    error::{AnyError, IntoAnyError},
+    extension::ExtensionList,
+    group::GroupContext,
+    time::MlsTime,
+};
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use super::{
+    CredentialType, IdentityProvider, MemberValidation, MemberValidationContext, SigningIdentity,
+};
+
+/// An external key transparency (KT) log that signature keys of group
+/// members are published to and verified against.
+///
+/// A log is connected to a client by wrapping the client's
+/// [`IdentityProvider`] in a [`KeyTransparencyIdentityProvider`].
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait KeyTransparencyLog: Send + Sync {
+    /// Error type that this log returns on internal failure.
+    type Error: IntoAnyError;
+
+    /// Record a key package that was generated by this client.
+    ///
+    /// `key_package` is the MLS encoding of the signed key package.
+    async fn log_key_package(
+        &self,
+        signing_identity: &SigningIdentity,
+        key_package: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Record a leaf node that was added or updated in a group as the result
+    /// of a commit, including commits created by this client.
+    ///
+    /// `leaf_node` is the MLS encoding of the signed leaf node and `context`
+    /// is the group context of the epoch created by the commit.
+    async fn log_leaf_node(
+        &self,
+        signing_identity: &SigningIdentity,
+        leaf_node: &[u8],
+        context: &GroupContext,
+    ) -> Result<(), Self::Error>;
+
+    /// Determine if `signing_identity` is included in the log.
+    ///
+    /// This is called each time a member is validated. Implementations
+    /// will typically fetch an [`InclusionProof`] for the signature key
+    /// and check it with [`InclusionProof::verify`].
+    async fn verify_member(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Errors returned by [`KeyTransparencyIdentityProvider`].
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum KeyTransparencyError {
+    #[cfg_attr(feature = "std", error(transparent))]
+    IdentityProviderError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    LogError(AnyError),
+}
+
+impl IntoAnyError for KeyTransparencyError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+/// An [`IdentityProvider`] that additionally publishes key material to and
+/// verifies members against a [`KeyTransparencyLog`].
+///
+/// Identity decisions are made by the inner provider. A member is only
+/// considered valid if it is accepted by the inner provider and
+/// [`KeyTransparencyLog::verify_member`] succeeds.
+#[derive(Clone, Debug)]
+pub struct KeyTransparencyIdentityProvider<I, L> {
+    pub inner: I,
+    pub log: L,
+}
+
+impl<I, L> KeyTransparencyIdentityProvider<I, L> {
+    pub fn new(inner: I, log: L) -> Self {
+        Self { inner, log }
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<I, L> IdentityProvider for KeyTransparencyIdentityProvider<I, L>
+where
+    I: IdentityProvider,
+    L: KeyTransparencyLog,
+{
+    type Error = KeyTransparencyError;
+
+    async fn validate_member(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        context: MemberValidationContext<'_>,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .validate_member(signing_identity, timestamp, context)
+            .await
+            .map_err(|e| KeyTransparencyError::IdentityProviderError(e.into_any_error()))?;
+
+        self.log
+            .verify_member(signing_identity, timestamp)
+            .await
+            .map_err(|e| KeyTransparencyError::LogError(e.into_any_error()))
+    }
+
+    async fn validate_member_in_context(
+        &self,
+        signing_identity: &SigningIdentity,
+        validation: MemberValidation<'_>,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .validate_member_in_context(signing_identity, validation)
+            .await
+            .map_err(|e| KeyTransparencyError::IdentityProviderError(e.into_any_error()))?;
+
+        self.log
+            .verify_member(signing_identity, validation.timestamp)
+            .await
+            .map_err(|e| KeyTransparencyError::LogError(e.into_any_error()))
+    }
+
+    async fn validate_external_sender(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        extensions: Option<&ExtensionList>,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .validate_external_sender(signing_identity, timestamp, extensions)
+            .await
+            .map_err(|e| KeyTransparencyError::IdentityProviderError(e.into_any_error()))
+    }
+
+    async fn identity(
+        &self,
+        signing_identity: &SigningIdentity,
+        extensions: &ExtensionList,
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.inner
+            .identity(signing_identity, extensions)
+            .await
+            .map_err(|e| KeyTransparencyError::IdentityProviderError(e.into_any_error()))
+    }
+
+    async fn valid_successor(
+        &self,
+        predecessor: &SigningIdentity,
+        successor: &SigningIdentity,
+        extensions: &ExtensionList,
+    ) -> Result<bool, Self::Error> {
+        self.inner
+            .valid_successor(predecessor, successor, extensions)
+            .await
+            .map_err(|e| KeyTransparencyError::IdentityProviderError(e.into_any_error()))
+    }
+
+    fn supported_types(&self) -> Vec<CredentialType> {
+        self.inner.supported_types()
+    }
+
+    async fn credential_expiration(
+        &self,
+        signing_identity: &SigningIdentity,
+    ) -> Result<Option<MlsTime>, Self::Error> {
+        self.inner
+            .credential_expiration(signing_identity)
+            .await
+            .map_err(|e| KeyTransparencyError::IdentityProviderError(e.into_any_error()))
+    }
+
+    async fn key_package_published(
+        &self,
+        signing_identity: &SigningIdentity,
+        key_package: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.log
+            .log_key_package(signing_identity, key_package)
+            .await
+            .map_err(|e| KeyTransparencyError::LogError(e.into_any_error()))
+    }
+
+    async fn leaf_node_observed(
+        &self,
+        signing_identity: &SigningIdentity,
+        leaf_node: &[u8],
+        context: &GroupContext,
+    ) -> Result<(), Self::Error> {
+        self.log
+            .log_leaf_node(signing_identity, leaf_node, context)
+            .await
+            .map_err(|e| KeyTransparencyError::LogError(e.into_any_error()))
+    }
+}
+
+/// Proof that an entry is included in a Merkle tree based key transparency
+/// log, using the tree structure and hashing defined by
+/// [RFC 9162](https://www.rfc-editor.org/rfc/rfc9162.html#section-2.1).
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InclusionProof {
+    /// Index of the entry in the log.
+    pub leaf_index: u64,
+    /// Number of entries in the log the proof was produced for.
+    pub tree_size: u64,
+    /// Sibling hashes on the path from the entry to the root, ordered from
+    /// the leaf upwards.
+    pub audit_path: Vec<Vec<u8>>,
+}
+
+impl InclusionProof {
+    /// Hash of a log entry as defined by RFC 9162.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn leaf_hash<P: CipherSuiteProvider>(
+        cipher_suite_provider: &P,
+        entry: &[u8],
+    ) -> Result<Vec<u8>, P::Error> {
+        cipher_suite_provider
+            .hash(&[&[0u8][..], entry].concat())
+            .await
+    }
+
+    /// Compute the root hash of the log from `entry` and this proof.
+    ///
+    /// Returns `None` if the proof is malformed for the claimed
+    /// `leaf_index` and `tree_size`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn root<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        entry: &[u8],
+    ) -> Result<Option<Vec<u8>>, P::Error> {
+        if self.leaf_index >= self.tree_size {
+            return Ok(None);
+        }
+
+        let mut f_n = self.leaf_index;
+        let mut s_n = self.tree_size - 1;
+        let mut r = Self::leaf_hash(cipher_suite_provider, entry).await?;
+
+        for p in &self.audit_path {
+            if s_n == 0 {
+                return Ok(None);
+            }
+
+            if f_n & 1 == 1 || f_n == s_n {
+                r = node_hash(cipher_suite_provider, p, &r).await?;
+
+                while f_n & 1 == 0 && f_n != 0 {
+                    f_n >>= 1;
+                    s_n >>= 1;
+                }
+            } else {
+                r = node_hash(cipher_suite_provider, &r, p).await?;
+            }
+
+            f_n >>= 1;
+            s_n >>= 1;
+        }
+
+        Ok((s_n == 0).then_some(r))
+    }
+
+    /// Determine if `entry` is included in the log with root hash `root`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        entry: &[u8],
+        root: &[u8],
+    ) -> Result<bool, P::Error> {
+        let computed = self.root(cipher_suite_provider, entry).await?;
+
+        Ok(matches!(computed, Some(computed) if computed == root))
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn node_hash<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    left: &[u8],
+    right: &[u8],
+) -> Result<Vec<u8>, P::Error> {
+    cipher_suite_provider
+        .hash(&[&[1u8][..], left, right].concat())
+        .await
+}
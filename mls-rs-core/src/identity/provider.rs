@@ -31,6 +31,77 @@ impl MemberValidationContext<'_> {
             Self::None => None,
         }
     }
+
+    /// Context of the group the member is validated for, if any.
+    pub fn current_context(&self) -> Option<&GroupContext> {
+        match self {
+            Self::ForCommit {
+                current_context, ..
+            }
+            | Self::ForNewGroup { current_context } => Some(*current_context),
+            Self::None => None,
+        }
+    }
+}
+
+/// Reason a member is validated by
+/// [`IdentityProvider::validate_member_in_context`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize,))]
+#[non_exhaustive]
+pub enum MemberValidationPurpose {
+    /// Creator of a new group.
+    NewGroup,
+    /// Member of a group that is being joined, found in the ratchet tree of
+    /// a welcome message or as the signer of a group info.
+    Join,
+    /// New member added with a key package, or a key package validated
+    /// outside of a group.
+    Add,
+    /// Member replacing its leaf with an update proposal.
+    Update,
+    /// Committer replacing its leaf with the update path of a commit.
+    Commit,
+    /// New member joining with an external commit.
+    ExternalCommit,
+}
+
+/// Everything known about the validation of a member by
+/// [`IdentityProvider::validate_member_in_context`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize,))]
+#[non_exhaustive]
+pub struct MemberValidation<'a> {
+    pub context: MemberValidationContext<'a>,
+    pub purpose: MemberValidationPurpose,
+    /// Time of the validation, e.g. the time of the commit being processed.
+    pub timestamp: Option<MlsTime>,
+}
+
+impl<'a> MemberValidation<'a> {
+    pub fn new(
+        context: MemberValidationContext<'a>,
+        purpose: MemberValidationPurpose,
+        timestamp: Option<MlsTime>,
+    ) -> Self {
+        Self {
+            context,
+            purpose,
+            timestamp,
+        }
+    }
+
+    /// Id of the group the member is validated for, if any.
+    pub fn group_id(&self) -> Option<&[u8]> {
+        self.context
+            .current_context()
+            .map(|context| context.group_id.as_slice())
+    }
+
+    /// Current epoch of the group the member is validated for, if any.
+    pub fn epoch(&self) -> Option<u64> {
+        self.context.current_context().map(|context| context.epoch)
+    }
 }
 
 /// Identity system that can be used to validate a
@@ -53,6 +124,22 @@ pub trait IdentityProvider: Send + Sync {
         context: MemberValidationContext<'_>,
     ) -> Result<(), Self::Error>;
 
+    /// Determine if `signing_identity` is valid for a group member given the
+    /// group, purpose and time of the validation in `validation`.
+    ///
+    /// This method is called by mls-rs for all member validations. The
+    /// default implementation calls
+    /// [`validate_member`](IdentityProvider::validate_member) with the
+    /// timestamp and context of `validation`.
+    async fn validate_member_in_context(
+        &self,
+        signing_identity: &SigningIdentity,
+        validation: MemberValidation<'_>,
+    ) -> Result<(), Self::Error> {
+        self.validate_member(signing_identity, validation.timestamp, validation.context)
+            .await
+    }
+
     /// Determine if `signing_identity` is valid for an external sender in
     /// the ExternalSendersExtension stored in the group context.
     ///
@@ -96,4 +183,45 @@ pub trait IdentityProvider: Send + Sync {
 
     /// Credential types that are supported by this provider.
     fn supported_types(&self) -> Vec<CredentialType>;
+
+    /// Time after which the credential of `signing_identity` is no longer
+    /// valid, e.g. the earliest expiration of an X.509 certificate chain.
+    ///
+    /// `None` should be returned if the credential does not expire or its
+    /// expiration is unknown. The default implementation returns `None`.
+    async fn credential_expiration(
+        &self,
+        _signing_identity: &SigningIdentity,
+    ) -> Result<Option<MlsTime>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Called after this client generated a new key package.
+    ///
+    /// `key_package` is the MLS encoding of the signed key package. The
+    /// default implementation does nothing.
+    #[cfg(feature = "key_transparency")]
+    async fn key_package_published(
+        &self,
+        _signing_identity: &SigningIdentity,
+        _key_package: &[u8],
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called for each leaf node that was added or updated by a commit once
+    /// the commit was accepted.
+    ///
+    /// `leaf_node` is the MLS encoding of the signed leaf node and `context`
+    /// is the group context of the new epoch. The default implementation
+    /// does nothing.
+    #[cfg(feature = "key_transparency")]
+    async fn leaf_node_observed(
+        &self,
+        _signing_identity: &SigningIdentity,
+        _leaf_node: &[u8],
+        _context: &GroupContext,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
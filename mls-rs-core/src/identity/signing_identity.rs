@@ -4,7 +4,7 @@
 
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
-use crate::crypto::SignaturePublicKey;
+use crate::crypto::{CipherSuiteProvider, Fingerprint, SignaturePublicKey};
 
 use super::Credential;
 
@@ -30,4 +30,14 @@ impl SigningIdentity {
             signature_key,
         }
     }
+
+    /// Canonical fingerprint of the signature key of this identity, as
+    /// displayed to users.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn fingerprint<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+    ) -> Result<Fingerprint, P::Error> {
+        self.signature_key.fingerprint(cipher_suite_provider).await
+    }
 }
@@ -83,3 +83,29 @@ pub trait KeyPackageStorage: Send + Sync {
     /// that match `id`.
     async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error>;
 }
+
+/// Shared record of key packages that are about to be consumed by a commit.
+///
+/// Committers reserve the key packages of the members they add before
+/// sending the commit, so that two committers fetching the same key package
+/// don't both add the joiner with it. Implementations are typically backed by
+/// the key package directory or the delivery service and must be shared by
+/// all committers.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait KeyPackageReservation: Send + Sync {
+    /// Error type that the underlying reservation mechanism returns on
+    /// internal failure.
+    type Error: IntoAnyError;
+
+    /// Reserve the key package referenced by `id`.
+    ///
+    /// Returns `false` if the key package is already reserved.
+    async fn reserve(&self, id: &[u8]) -> Result<bool, Self::Error>;
+
+    /// Release the reservation of the key package referenced by `id`.
+    ///
+    /// This function should be called when the commit adding the key package
+    /// was rejected, so that the key package can be used by another commit.
+    async fn release(&self, id: &[u8]) -> Result<(), Self::Error>;
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use crate::error::IntoAnyError;
+use crate::secret::KeyScheduleSecret;
 #[cfg(mls_build_async)]
 use alloc::boxed::Box;
 use alloc::vec::Vec;
@@ -111,6 +112,12 @@ impl Deref for ExternalPskId {
     }
 }
 
+impl From<PreSharedKey> for KeyScheduleSecret {
+    fn from(psk: PreSharedKey) -> Self {
+        KeyScheduleSecret::Bytes(psk.0)
+    }
+}
+
 impl From<Vec<u8>> for ExternalPskId {
     fn from(value: Vec<u8>) -> Self {
         ExternalPskId(value)
@@ -130,6 +137,26 @@ pub trait PreSharedKeyStorage: Send + Sync {
     /// `None` should be returned if a pre-shared key can not be found for `id`.
     async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error>;
 
+    /// Get a pre-shared key by [`ExternalPskId`](ExternalPskId) as input to
+    /// the key schedule.
+    ///
+    /// Stores backed by an HSM or another hardware module can return a
+    /// [`SecretHandle`](crate::secret::SecretHandle) understood by the
+    /// [`CipherSuiteProvider`](crate::crypto::CipherSuiteProvider) in use,
+    /// so that the pre-shared key never exists in process memory. The
+    /// provider must then support secret handles. Such stores should return
+    /// `None` from [`get`](PreSharedKeyStorage::get) and override
+    /// [`contains`](PreSharedKeyStorage::contains).
+    ///
+    /// The default implementation returns the bytes produced by
+    /// [`get`](PreSharedKeyStorage::get).
+    async fn get_secret(
+        &self,
+        id: &ExternalPskId,
+    ) -> Result<Option<KeyScheduleSecret>, Self::Error> {
+        self.get(id).await.map(|key| key.map(Into::into))
+    }
+
     /// Determines if a PSK is located within the store
     async fn contains(&self, id: &ExternalPskId) -> Result<bool, Self::Error> {
         self.get(id).await.map(|key| key.is_some())
@@ -7,8 +7,11 @@ use core::{
     fmt::{self, Debug},
     ops::{Deref, DerefMut},
 };
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use zeroize::Zeroizing;
 
+use crate::error::{AnyError, IntoAnyError};
+
 #[cfg_attr(
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
@@ -55,3 +58,196 @@ impl DerefMut for Secret {
         &mut self.0
     }
 }
+
+/// Opaque reference to secret key material held by a
+/// [CipherSuiteProvider](crate::crypto::CipherSuiteProvider), e.g. a key in an
+/// HSM or a non-extractable WebCrypto `CryptoKey`.
+///
+/// The contents of a handle are defined by the provider that created it and
+/// are never interpreted as key material by mls-rs.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecretHandle(
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "crate::vec_serde"))]
+    Vec<u8>,
+);
+
+impl Debug for SecretHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::debug::pretty_bytes(&self.0)
+            .named("SecretHandle")
+            .fmt(f)
+    }
+}
+
+impl SecretHandle {
+    /// Create a handle from a provider specific identifier.
+    pub fn new(id: Vec<u8>) -> Self {
+        Self(id)
+    }
+
+    /// Provider specific identifier of the secret.
+    pub fn id(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Secret used as input or output of key schedule operations of a
+/// [CipherSuiteProvider](crate::crypto::CipherSuiteProvider).
+///
+/// Providers that do not support non-extractable secrets only ever create
+/// and receive [KeyScheduleSecret::Bytes].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum KeyScheduleSecret {
+    /// Secret whose bytes are known to the application.
+    Bytes(
+        #[mls_codec(with = "mls_rs_codec::byte_vec")]
+        #[cfg_attr(feature = "serde", serde(with = "crate::zeroizing_serde"))]
+        Zeroizing<Vec<u8>>,
+    ) = 1u8,
+    /// Secret that can only be used through the provider that created it.
+    Handle(SecretHandle) = 2u8,
+}
+
+impl Debug for KeyScheduleSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes(bytes) => f
+                .debug_tuple("Bytes")
+                .field(&crate::debug::pretty_bytes(bytes).named("Secret"))
+                .finish(),
+            Self::Handle(handle) => f.debug_tuple("Handle").field(handle).finish(),
+        }
+    }
+}
+
+impl KeyScheduleSecret {
+    /// Bytes of the secret, or `None` if the secret is non-extractable.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            Self::Handle(_) => None,
+        }
+    }
+
+    /// Determine if the bytes of this secret can be read.
+    pub fn is_extractable(&self) -> bool {
+        matches!(self, Self::Bytes(_))
+    }
+
+    /// Bytes of a secret that must be extractable, for example because it
+    /// is passed to a provider that does not support handles.
+    pub fn extractable(&self) -> Result<&[u8], KeyScheduleSecretError> {
+        self.as_bytes()
+            .ok_or(KeyScheduleSecretError::NonExtractable)
+    }
+
+    /// Same as [KeyScheduleSecret::extractable], taking ownership of the
+    /// bytes.
+    pub fn into_extractable(self) -> Result<Zeroizing<Vec<u8>>, KeyScheduleSecretError> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes),
+            Self::Handle(_) => Err(KeyScheduleSecretError::NonExtractable),
+        }
+    }
+}
+
+impl From<Zeroizing<Vec<u8>>> for KeyScheduleSecret {
+    fn from(bytes: Zeroizing<Vec<u8>>) -> Self {
+        Self::Bytes(bytes)
+    }
+}
+
+impl From<Vec<u8>> for KeyScheduleSecret {
+    fn from(bytes: Vec<u8>) -> Self {
+        Zeroizing::new(bytes).into()
+    }
+}
+
+impl From<SecretHandle> for KeyScheduleSecret {
+    fn from(handle: SecretHandle) -> Self {
+        Self::Handle(handle)
+    }
+}
+
+/// Error returned by the functions of a
+/// [CipherSuiteProvider](crate::crypto::CipherSuiteProvider) taking a
+/// [KeyScheduleSecret].
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum KeyScheduleSecretError {
+    #[cfg_attr(
+        feature = "std",
+        error("non-extractable secret used where its bytes are required")
+    )]
+    NonExtractable,
+    #[cfg_attr(feature = "std", error(transparent))]
+    ProviderError(AnyError),
+}
+
+impl KeyScheduleSecretError {
+    /// Wrap an error of the provider.
+    pub fn provider<E: IntoAnyError>(error: E) -> Self {
+        Self::ProviderError(error.into_any_error())
+    }
+}
+
+impl IntoAnyError for KeyScheduleSecretError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec};
+    use assert_matches::assert_matches;
+    use mls_rs_codec::{MlsDecode, MlsEncode};
+
+    use super::{KeyScheduleSecret, KeyScheduleSecretError, SecretHandle};
+
+    #[test]
+    fn handles_are_not_extractable() {
+        let bytes = KeyScheduleSecret::from(vec![1, 2, 3]);
+        let handle = KeyScheduleSecret::from(SecretHandle::new(vec![4, 5]));
+
+        assert_eq!(bytes.as_bytes(), Some(&[1u8, 2, 3][..]));
+        assert!(bytes.is_extractable());
+        assert_eq!(handle.as_bytes(), None);
+        assert!(!handle.is_extractable());
+
+        for secret in [bytes, handle] {
+            let encoded = secret.mls_encode_to_vec().unwrap();
+            assert_eq!(
+                KeyScheduleSecret::mls_decode(&mut &*encoded).unwrap(),
+                secret
+            );
+        }
+    }
+
+    #[test]
+    fn handles_can_not_be_read() {
+        let handle = KeyScheduleSecret::from(SecretHandle::new(vec![4, 5]));
+
+        assert_matches!(
+            handle.extractable(),
+            Err(KeyScheduleSecretError::NonExtractable)
+        );
+
+        assert_matches!(
+            handle.into_extractable(),
+            Err(KeyScheduleSecretError::NonExtractable)
+        );
+    }
+
+    #[test]
+    fn debug_does_not_show_secret_bytes() {
+        let secret = KeyScheduleSecret::from(vec![0xab; 4]);
+
+        assert!(!format!("{secret:?}").contains("abab"));
+    }
+}
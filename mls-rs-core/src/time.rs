@@ -36,8 +36,79 @@ impl MlsTime {
     pub fn seconds_since_epoch(&self) -> u64 {
         self.seconds
     }
+
+    /// `self + duration`, or `None` if the result would overflow.
+    pub fn checked_add(self, duration: Duration) -> Option<MlsTime> {
+        self.seconds.checked_add(duration.as_secs()).map(Self::from)
+    }
+
+    /// `self - duration`, or `None` if the result would be before the unix
+    /// epoch.
+    pub fn checked_sub(self, duration: Duration) -> Option<MlsTime> {
+        self.seconds.checked_sub(duration.as_secs()).map(Self::from)
+    }
+
+    /// `self + duration`, saturating at the latest representable time.
+    pub fn saturating_add(self, duration: Duration) -> MlsTime {
+        Self::from(self.seconds.saturating_add(duration.as_secs()))
+    }
+
+    /// `self - duration`, saturating at the unix epoch.
+    pub fn saturating_sub(self, duration: Duration) -> MlsTime {
+        Self::from(self.seconds.saturating_sub(duration.as_secs()))
+    }
+
+    /// Time elapsed from `earlier` to `self`, or `None` if `earlier` is
+    /// after `self`.
+    pub fn checked_duration_since(self, earlier: MlsTime) -> Option<Duration> {
+        self.seconds
+            .checked_sub(earlier.seconds)
+            .map(Duration::from_secs)
+    }
+
+    /// Time elapsed from `earlier` to `self`, or zero if `earlier` is after
+    /// `self`.
+    pub fn saturating_duration_since(self, earlier: MlsTime) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+
+    /// Time between `self` and `other`, regardless of their order.
+    pub fn abs_diff(self, other: MlsTime) -> Duration {
+        Duration::from_secs(self.seconds.abs_diff(other.seconds))
+    }
+
+    /// Whether `self` and `other` are at most `tolerance` apart, e.g. to
+    /// compare timestamps of machines whose clocks are not synchronized.
+    pub fn is_close_to(self, other: MlsTime, tolerance: Duration) -> bool {
+        self.abs_diff(other) <= tolerance
+    }
+
+    /// Whether `self` is in the range from `start` to `end`, both included,
+    /// extended by `tolerance` on both sides.
+    pub fn is_within(self, start: MlsTime, end: MlsTime, tolerance: Duration) -> bool {
+        start.saturating_sub(tolerance) <= self && self <= end.saturating_add(tolerance)
+    }
 }
 
+const SECONDS_PER_DAY: u64 = 24 * 3600;
+
+/// Duration of `hours` hours, or `None` if it can't be represented.
+pub fn checked_hours(hours: u64) -> Option<Duration> {
+    hours.checked_mul(3600).map(Duration::from_secs)
+}
+
+/// Duration of `days` days, or `None` if it can't be represented.
+pub fn checked_days(days: u64) -> Option<Duration> {
+    days.checked_mul(SECONDS_PER_DAY).map(Duration::from_secs)
+}
+
+/// Duration of `years` years of 365 days, or `None` if it can't be
+/// represented.
+pub fn checked_years(years: u64) -> Option<Duration> {
+    years.checked_mul(365).and_then(checked_days)
+}
+
+/// Panics if `rhs` is after `self`, see [`MlsTime::checked_duration_since`].
 impl core::ops::Sub<MlsTime> for MlsTime {
     type Output = Duration;
 
@@ -46,6 +117,8 @@ impl core::ops::Sub<MlsTime> for MlsTime {
     }
 }
 
+/// Panics if the result would be before the unix epoch, see
+/// [`MlsTime::checked_sub`].
 impl core::ops::Sub<Duration> for MlsTime {
     type Output = MlsTime;
 
@@ -54,6 +127,7 @@ impl core::ops::Sub<Duration> for MlsTime {
     }
 }
 
+/// Panics if the result would overflow, see [`MlsTime::checked_add`].
 impl core::ops::Add<Duration> for MlsTime {
     type Output = MlsTime;
 
@@ -146,3 +220,48 @@ impl MlsTime {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::{checked_days, checked_years, MlsTime};
+
+    #[test]
+    fn arithmetic_does_not_overflow() {
+        let max = MlsTime::from(u64::MAX);
+        let time = MlsTime::from(100);
+
+        assert_eq!(max.checked_add(Duration::from_secs(1)), None);
+        assert_eq!(max.saturating_add(Duration::from_secs(1)), max);
+        assert_eq!(time.checked_sub(Duration::from_secs(101)), None);
+        assert_eq!(
+            time.saturating_sub(Duration::from_secs(101)),
+            MlsTime::from(0)
+        );
+        assert_eq!(
+            time.checked_sub(Duration::from_secs(40)),
+            Some(MlsTime::from(60))
+        );
+
+        assert_eq!(time.checked_duration_since(max), None);
+        assert_eq!(time.saturating_duration_since(max), Duration::ZERO);
+        assert_eq!(time.abs_diff(MlsTime::from(130)), Duration::from_secs(30));
+
+        assert_eq!(checked_days(2), Some(Duration::from_secs(2 * 86400)));
+        assert_eq!(checked_years(u64::MAX / 365), None);
+    }
+
+    #[test]
+    fn comparison_with_tolerance() {
+        let tolerance = Duration::from_secs(10);
+        let time = MlsTime::from(100);
+
+        assert!(time.is_close_to(MlsTime::from(90), tolerance));
+        assert!(!time.is_close_to(MlsTime::from(111), tolerance));
+
+        assert!(time.is_within(MlsTime::from(105), MlsTime::from(200), tolerance));
+        assert!(!time.is_within(MlsTime::from(111), MlsTime::from(200), tolerance));
+        assert!(MlsTime::from(u64::MAX).is_within(time, MlsTime::from(u64::MAX - 5), tolerance));
+    }
+}
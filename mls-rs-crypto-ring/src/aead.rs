@@ -0,0 +1,206 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::{crypto::CipherSuite, error::IntoAnyError};
+use mls_rs_crypto_traits::{AeadId, AeadType, AES_TAG_LEN};
+use ring::aead::{self, Aad, LessSafeKey, Nonce, UnboundKey};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AeadError {
+    #[error("ring AEAD error")]
+    RingError,
+    #[error("AEAD ciphertext of length {0} is too short to fit the tag")]
+    InvalidCipherLen(usize),
+    #[error("encrypted message cannot be empty")]
+    EmptyPlaintext,
+    #[error("AEAD key of invalid length {0}. Expected length {1}")]
+    InvalidKeyLen(usize, usize),
+    #[error("AEAD nonce of invalid length {0}. Expected length {1}")]
+    InvalidNonceLen(usize, usize),
+    #[error("unsupported cipher suite")]
+    UnsupportedCipherSuite,
+}
+
+impl From<ring::error::Unspecified> for AeadError {
+    fn from(_: ring::error::Unspecified) -> Self {
+        AeadError::RingError
+    }
+}
+
+impl IntoAnyError for AeadError {
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RingAead(AeadId);
+
+impl RingAead {
+    pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
+        AeadId::new(cipher_suite).map(Self)
+    }
+
+    fn key(&self, key: &[u8]) -> Result<LessSafeKey, AeadError> {
+        (key.len() == self.key_size())
+            .then_some(())
+            .ok_or_else(|| AeadError::InvalidKeyLen(key.len(), self.key_size()))?;
+
+        let algorithm = match self.0 {
+            AeadId::Aes128Gcm => &aead::AES_128_GCM,
+            AeadId::Aes256Gcm => &aead::AES_256_GCM,
+            AeadId::Chacha20Poly1305 => &aead::CHACHA20_POLY1305,
+            _ => return Err(AeadError::UnsupportedCipherSuite),
+        };
+
+        Ok(LessSafeKey::new(UnboundKey::new(algorithm, key)?))
+    }
+}
+
+fn nonce(nonce: &[u8]) -> Result<Nonce, AeadError> {
+    Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| AeadError::InvalidNonceLen(nonce.len(), aead::NONCE_LEN))
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl AeadType for RingAead {
+    type Error = AeadError;
+
+    #[allow(clippy::needless_lifetimes)]
+    async fn seal<'a>(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&'a [u8]>,
+        nonce_bytes: &[u8],
+    ) -> Result<Vec<u8>, AeadError> {
+        (!data.is_empty())
+            .then_some(())
+            .ok_or(AeadError::EmptyPlaintext)?;
+
+        let key = self.key(key)?;
+        let mut in_out = data.to_vec();
+
+        key.seal_in_place_append_tag(
+            nonce(nonce_bytes)?,
+            Aad::from(aad.unwrap_or_default()),
+            &mut in_out,
+        )?;
+
+        Ok(in_out)
+    }
+
+    #[allow(clippy::needless_lifetimes)]
+    async fn open<'a>(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        aad: Option<&'a [u8]>,
+        nonce_bytes: &[u8],
+    ) -> Result<Vec<u8>, AeadError> {
+        (ciphertext.len() > AES_TAG_LEN)
+            .then_some(())
+            .ok_or(AeadError::InvalidCipherLen(ciphertext.len()))?;
+
+        let key = self.key(key)?;
+        let mut in_out = ciphertext.to_vec();
+
+        let plaintext_len = key
+            .open_in_place(
+                nonce(nonce_bytes)?,
+                Aad::from(aad.unwrap_or_default()),
+                &mut in_out,
+            )?
+            .len();
+
+        in_out.truncate(plaintext_len);
+
+        Ok(in_out)
+    }
+
+    #[inline(always)]
+    fn key_size(&self) -> usize {
+        self.0.key_size()
+    }
+
+    fn nonce_size(&self) -> usize {
+        self.0.nonce_size()
+    }
+
+    fn aead_id(&self) -> u16 {
+        self.0 as u16
+    }
+}
+
+#[cfg(all(not(mls_build_async), test))]
+mod test {
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::CipherSuite;
+    use mls_rs_crypto_traits::{AeadType, AES_TAG_LEN};
+
+    use super::{AeadError, RingAead};
+
+    fn get_aeads() -> Vec<RingAead> {
+        [
+            CipherSuite::CURVE25519_AES128,
+            CipherSuite::CURVE25519_CHACHA,
+            CipherSuite::P384_AES256,
+        ]
+        .into_iter()
+        .map(|cs| RingAead::new(cs).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn invalid_key() {
+        for aead in get_aeads() {
+            let nonce = vec![42u8; aead.nonce_size()];
+            let too_short = vec![42u8; aead.key_size() - 1];
+
+            assert_matches!(
+                aead.seal(&too_short, b"top secret", None, &nonce),
+                Err(AeadError::InvalidKeyLen(_, _))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_ciphertext() {
+        for aead in get_aeads() {
+            let key = vec![42u8; aead.key_size()];
+            let nonce = vec![42u8; aead.nonce_size()];
+
+            assert_matches!(
+                aead.open(&key, &[0u8; AES_TAG_LEN], None, &nonce),
+                Err(AeadError::InvalidCipherLen(_))
+            );
+        }
+    }
+
+    #[test]
+    fn aad_mismatch() {
+        for aead in get_aeads() {
+            let key = vec![42u8; aead.key_size()];
+            let nonce = vec![42u8; aead.nonce_size()];
+
+            let ciphertext = aead.seal(&key, b"message", Some(b"foo"), &nonce).unwrap();
+
+            assert_matches!(
+                aead.open(&key, &ciphertext, Some(b"bar"), &nonce),
+                Err(AeadError::RingError)
+            );
+
+            assert_eq!(
+                aead.open(&key, &ciphertext, Some(b"foo"), &nonce).unwrap(),
+                b"message"
+            );
+        }
+    }
+}
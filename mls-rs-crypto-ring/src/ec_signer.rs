@@ -0,0 +1,177 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::crypto::{CipherSuite, SignaturePublicKey, SignatureSecretKey};
+use mls_rs_crypto_traits::Curve;
+use ring::{
+    rand::SystemRandom,
+    signature::{self, EcdsaKeyPair, Ed25519KeyPair, KeyPair, UnparsedPublicKey},
+};
+use thiserror::Error;
+
+use crate::ecdh::{
+    p256_public_key_bytes, p256_random_secret_key, p256_secret_key, random_bytes, EcdhKemError,
+};
+
+const ED25519_SEED_LEN: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum EcSignerError {
+    #[error("invalid key data")]
+    InvalidKeyData,
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("unsupported curve type")]
+    UnsupportedCurve,
+    #[error("signing failed")]
+    SigningFailed,
+    #[error(transparent)]
+    EcdhKemError(#[from] EcdhKemError),
+}
+
+/// Ed25519 and ECDSA P-256 signatures computed by ring.
+///
+/// Secret keys use the same encoding as the other providers: the seed followed
+/// by the public key for Ed25519, and the raw scalar for P-256.
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct EcSigner(Curve);
+
+impl EcSigner {
+    pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
+        Curve::from_ciphersuite(cipher_suite, true)
+            .filter(|curve| matches!(curve, Curve::Ed25519 | Curve::P256))
+            .map(Self)
+    }
+
+    pub fn signature_key_generate(
+        &self,
+    ) -> Result<(SignatureSecretKey, SignaturePublicKey), EcSignerError> {
+        let secret_key = match self.0 {
+            Curve::Ed25519 => {
+                let seed = random_bytes(ED25519_SEED_LEN)?;
+
+                let public_key = Ed25519KeyPair::from_seed_unchecked(&seed)
+                    .map_err(|_| EcSignerError::InvalidKeyData)?
+                    .public_key()
+                    .as_ref()
+                    .to_vec();
+
+                [seed.as_slice(), &public_key].concat()
+            }
+            Curve::P256 => p256_random_secret_key()?.to_bytes().to_vec(),
+            _ => return Err(EcSignerError::UnsupportedCurve),
+        };
+
+        let secret_key = SignatureSecretKey::from(secret_key);
+        let public_key = self.signature_key_derive_public(&secret_key)?;
+
+        Ok((secret_key, public_key))
+    }
+
+    pub fn signature_key_derive_public(
+        &self,
+        secret_key: &SignatureSecretKey,
+    ) -> Result<SignaturePublicKey, EcSignerError> {
+        match self.0 {
+            Curve::Ed25519 => Ok(self
+                .ed25519_key_pair(secret_key)?
+                .public_key()
+                .as_ref()
+                .to_vec()
+                .into()),
+            Curve::P256 => Ok(p256_public_key_bytes(&p256_secret_key(secret_key)?).into()),
+            _ => Err(EcSignerError::UnsupportedCurve),
+        }
+    }
+
+    pub fn sign(
+        &self,
+        secret_key: &SignatureSecretKey,
+        data: &[u8],
+    ) -> Result<Vec<u8>, EcSignerError> {
+        match self.0 {
+            Curve::Ed25519 => Ok(self
+                .ed25519_key_pair(secret_key)?
+                .sign(data)
+                .as_ref()
+                .to_vec()),
+            Curve::P256 => {
+                let rng = SystemRandom::new();
+                let public_key = p256_public_key_bytes(&p256_secret_key(secret_key)?);
+
+                let key_pair = EcdsaKeyPair::from_private_key_and_public_key(
+                    &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+                    secret_key,
+                    &public_key,
+                    &rng,
+                )
+                .map_err(|_| EcSignerError::InvalidKeyData)?;
+
+                key_pair
+                    .sign(&rng, data)
+                    .map(|signature| signature.as_ref().to_vec())
+                    .map_err(|_| EcSignerError::SigningFailed)
+            }
+            _ => Err(EcSignerError::UnsupportedCurve),
+        }
+    }
+
+    pub fn verify(
+        &self,
+        public_key: &SignaturePublicKey,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<(), EcSignerError> {
+        let algorithm: &'static dyn signature::VerificationAlgorithm = match self.0 {
+            Curve::Ed25519 => &signature::ED25519,
+            Curve::P256 => &signature::ECDSA_P256_SHA256_ASN1,
+            _ => return Err(EcSignerError::UnsupportedCurve),
+        };
+
+        UnparsedPublicKey::new(algorithm, public_key)
+            .verify(data, signature)
+            .map_err(|_| EcSignerError::InvalidSignature)
+    }
+
+    fn ed25519_key_pair(&self, secret_key: &[u8]) -> Result<Ed25519KeyPair, EcSignerError> {
+        if secret_key.len() != self.0.secret_key_size() {
+            return Err(EcSignerError::InvalidKeyData);
+        }
+
+        let (seed, public_key) = secret_key.split_at(ED25519_SEED_LEN);
+
+        Ed25519KeyPair::from_seed_and_public_key(seed, public_key)
+            .map_err(|_| EcSignerError::InvalidKeyData)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::CipherSuite;
+
+    use super::{EcSigner, EcSignerError};
+
+    #[test]
+    fn signatures_round_trip() {
+        for cs in [CipherSuite::CURVE25519_AES128, CipherSuite::P256_AES128] {
+            let signer = EcSigner::new(cs).unwrap();
+            let (secret_key, public_key) = signer.signature_key_generate().unwrap();
+
+            assert_eq!(
+                signer.signature_key_derive_public(&secret_key).unwrap(),
+                public_key
+            );
+
+            let signature = signer.sign(&secret_key, b"message").unwrap();
+
+            signer.verify(&public_key, &signature, b"message").unwrap();
+
+            assert_matches!(
+                signer.verify(&public_key, &signature, b"other message"),
+                Err(EcSignerError::InvalidSignature)
+            );
+        }
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::ops::Deref;
+
+use mls_rs_core::{
+    crypto::{CipherSuite, HpkePublicKey, HpkeSecretKey},
+    error::IntoAnyError,
+};
+use mls_rs_crypto_traits::{Curve, DhType, SamplingMethod};
+use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use ring::rand::{SecureRandom, SystemRandom};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+#[derive(Debug, Error)]
+pub enum EcdhKemError {
+    #[error("invalid key data")]
+    InvalidKeyData,
+    #[error("unsupported curve type")]
+    UnsupportedCurve,
+    #[error("failed to generate random bytes")]
+    RandError,
+}
+
+impl IntoAnyError for EcdhKemError {
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+/// Static Diffie-Hellman for X25519 and P-256.
+///
+/// ring only supports ephemeral agreement keys, so HPKE secret keys are
+/// handled by `x25519-dalek` and `p256` instead.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ecdh(Curve);
+
+impl Deref for Ecdh {
+    type Target = Curve;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Ecdh {
+    pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
+        Curve::from_ciphersuite(cipher_suite, false)
+            .filter(|curve| matches!(curve, Curve::X25519 | Curve::P256))
+            .map(Self)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl DhType for Ecdh {
+    type Error = EcdhKemError;
+
+    async fn dh(
+        &self,
+        secret_key: &HpkeSecretKey,
+        public_key: &HpkePublicKey,
+    ) -> Result<Vec<u8>, Self::Error> {
+        match self.0 {
+            Curve::X25519 => {
+                let secret_key = x25519_secret_key(secret_key)?;
+                let public_key = x25519_public_key(public_key)?;
+
+                Ok(secret_key.diffie_hellman(&public_key).to_bytes().to_vec())
+            }
+            Curve::P256 => {
+                let secret_key = p256_secret_key(secret_key)?;
+                let public_key = p256_public_key(public_key)?;
+
+                let shared_secret = p256::ecdh::diffie_hellman(
+                    secret_key.to_nonzero_scalar(),
+                    public_key.as_affine(),
+                );
+
+                Ok(shared_secret.raw_secret_bytes().to_vec())
+            }
+            _ => Err(EcdhKemError::UnsupportedCurve),
+        }
+    }
+
+    async fn to_public(&self, secret_key: &HpkeSecretKey) -> Result<HpkePublicKey, Self::Error> {
+        match self.0 {
+            Curve::X25519 => {
+                let secret_key = x25519_secret_key(secret_key)?;
+                Ok(x25519_dalek::PublicKey::from(&secret_key)
+                    .to_bytes()
+                    .to_vec()
+                    .into())
+            }
+            Curve::P256 => Ok(p256_public_key_bytes(&p256_secret_key(secret_key)?).into()),
+            _ => Err(EcdhKemError::UnsupportedCurve),
+        }
+    }
+
+    async fn generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        let secret_key = match self.0 {
+            Curve::X25519 => random_bytes(32)?.to_vec(),
+            Curve::P256 => p256_random_secret_key()?.to_bytes().to_vec(),
+            _ => return Err(EcdhKemError::UnsupportedCurve),
+        };
+
+        let secret_key = HpkeSecretKey::from(secret_key);
+        let public_key = self.to_public(&secret_key).await?;
+
+        Ok((secret_key, public_key))
+    }
+
+    fn bitmask_for_rejection_sampling(&self) -> SamplingMethod {
+        self.hpke_sampling_method()
+    }
+
+    fn public_key_validate(&self, key: &HpkePublicKey) -> Result<(), Self::Error> {
+        match self.0 {
+            Curve::X25519 => x25519_public_key(key).map(|_| ()),
+            Curve::P256 => p256_public_key(key).map(|_| ()),
+            _ => Err(EcdhKemError::UnsupportedCurve),
+        }
+    }
+
+    fn secret_key_size(&self) -> usize {
+        self.0.secret_key_size()
+    }
+
+    fn public_key_size(&self) -> usize {
+        self.0.public_key_size()
+    }
+}
+
+pub(crate) fn random_bytes(len: usize) -> Result<Zeroizing<Vec<u8>>, EcdhKemError> {
+    let mut out = Zeroizing::new(vec![0u8; len]);
+
+    SystemRandom::new()
+        .fill(&mut out)
+        .map_err(|_| EcdhKemError::RandError)?;
+
+    Ok(out)
+}
+
+pub(crate) fn p256_random_secret_key() -> Result<p256::SecretKey, EcdhKemError> {
+    // Rejection sampling, the probability of a retry is about 2^-32
+    loop {
+        if let Ok(secret_key) = p256::SecretKey::from_slice(&random_bytes(32)?) {
+            return Ok(secret_key);
+        }
+    }
+}
+
+pub(crate) fn p256_secret_key(bytes: &[u8]) -> Result<p256::SecretKey, EcdhKemError> {
+    p256::SecretKey::from_slice(bytes).map_err(|_| EcdhKemError::InvalidKeyData)
+}
+
+pub(crate) fn p256_public_key_bytes(secret_key: &p256::SecretKey) -> Vec<u8> {
+    secret_key
+        .public_key()
+        .to_encoded_point(false)
+        .as_bytes()
+        .to_vec()
+}
+
+fn p256_public_key(bytes: &[u8]) -> Result<p256::PublicKey, EcdhKemError> {
+    let encoded_point =
+        p256::EncodedPoint::from_bytes(bytes).map_err(|_| EcdhKemError::InvalidKeyData)?;
+
+    Option::from(p256::PublicKey::from_encoded_point(&encoded_point))
+        .ok_or(EcdhKemError::InvalidKeyData)
+}
+
+fn x25519_secret_key(bytes: &[u8]) -> Result<x25519_dalek::StaticSecret, EcdhKemError> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| EcdhKemError::InvalidKeyData)?;
+    Ok(x25519_dalek::StaticSecret::from(bytes))
+}
+
+fn x25519_public_key(bytes: &[u8]) -> Result<x25519_dalek::PublicKey, EcdhKemError> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| EcdhKemError::InvalidKeyData)?;
+    Ok(x25519_dalek::PublicKey::from(bytes))
+}
+
+#[cfg(all(test, not(mls_build_async)))]
+mod test {
+    use mls_rs_core::crypto::{CipherSuite, HpkePublicKey, HpkeSecretKey};
+    use mls_rs_crypto_traits::DhType;
+    use serde::Deserialize;
+
+    use super::Ecdh;
+
+    #[derive(Deserialize)]
+    struct TestCase {
+        pub ciphersuite: u16,
+        #[serde(with = "hex::serde")]
+        pub alice_pub: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        pub alice_pri: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        pub bob_pub: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        pub bob_pri: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        pub shared_secret: Vec<u8>,
+    }
+
+    #[test]
+    fn test_algo_test_cases() {
+        let test_case_file = include_str!("../test_data/test_ecdh.json");
+        let test_cases: Vec<TestCase> = serde_json::from_str(test_case_file).unwrap();
+
+        for test_case in test_cases {
+            let ecdh = Ecdh::new(test_case.ciphersuite.into()).unwrap();
+
+            let alice_pri: HpkeSecretKey = test_case.alice_pri.into();
+            let bob_pri: HpkeSecretKey = test_case.bob_pri.into();
+            let alice_pub: HpkePublicKey = test_case.alice_pub.into();
+            let bob_pub: HpkePublicKey = test_case.bob_pub.into();
+
+            assert_eq!(ecdh.to_public(&alice_pri).unwrap(), alice_pub);
+            assert_eq!(ecdh.to_public(&bob_pri).unwrap(), bob_pub);
+
+            assert_eq!(
+                ecdh.dh(&alice_pri, &bob_pub).unwrap(),
+                test_case.shared_secret
+            );
+
+            assert_eq!(
+                ecdh.dh(&bob_pri, &alice_pub).unwrap(),
+                test_case.shared_secret
+            );
+        }
+    }
+
+    #[test]
+    fn test_mismatched_curve() {
+        let x25519 = Ecdh::new(CipherSuite::CURVE25519_AES128).unwrap();
+        let p256 = Ecdh::new(CipherSuite::P256_AES128).unwrap();
+
+        let secret_key = x25519.generate().unwrap().0;
+        let other_public_key = p256.generate().unwrap().1;
+
+        assert!(x25519.dh(&secret_key, &other_public_key).is_err());
+    }
+}
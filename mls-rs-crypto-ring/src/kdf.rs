@@ -0,0 +1,130 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::{crypto::CipherSuite, error::IntoAnyError};
+use mls_rs_crypto_traits::{KdfId, KdfType};
+use ring::{hkdf, hmac};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum KdfError {
+    #[error("invalid output length {0}")]
+    InvalidLength(usize),
+    #[error("the provided length of the key {0} is shorter than the minimum length {1}")]
+    TooShortKey(usize, usize),
+    #[error("unsupported cipher suite")]
+    UnsupportedCipherSuite,
+}
+
+impl IntoAnyError for KdfError {
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RingHkdf(KdfId);
+
+impl RingHkdf {
+    pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
+        KdfId::new(cipher_suite).map(Self)
+    }
+
+    fn algorithms(&self) -> Result<(hkdf::Algorithm, hmac::Algorithm), KdfError> {
+        match self.0 {
+            KdfId::HkdfSha256 => Ok((hkdf::HKDF_SHA256, hmac::HMAC_SHA256)),
+            KdfId::HkdfSha384 => Ok((hkdf::HKDF_SHA384, hmac::HMAC_SHA384)),
+            KdfId::HkdfSha512 => Ok((hkdf::HKDF_SHA512, hmac::HMAC_SHA512)),
+            _ => Err(KdfError::UnsupportedCipherSuite),
+        }
+    }
+}
+
+struct OutputLen(usize);
+
+impl hkdf::KeyType for OutputLen {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl KdfType for RingHkdf {
+    type Error = KdfError;
+
+    async fn expand(&self, prk: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, KdfError> {
+        if prk.len() < self.extract_size() {
+            return Err(KdfError::TooShortKey(prk.len(), self.extract_size()));
+        }
+
+        let (algorithm, _) = self.algorithms()?;
+        let mut out = vec![0u8; len];
+
+        hkdf::Prk::new_less_safe(algorithm, prk)
+            .expand(&[info], OutputLen(len))
+            .and_then(|okm| okm.fill(&mut out))
+            .map_err(|_| KdfError::InvalidLength(len))?;
+
+        Ok(out)
+    }
+
+    async fn extract(&self, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, KdfError> {
+        if ikm.is_empty() {
+            return Err(KdfError::TooShortKey(0, 1));
+        }
+
+        // ring doesn't expose the PRK, so HKDF-Extract is computed as HMAC(salt, ikm). An empty
+        // salt is equivalent to the all zero salt from RFC 5869 since HMAC pads the key.
+        let (_, algorithm) = self.algorithms()?;
+        let tag = hmac::sign(&hmac::Key::new(algorithm, salt), ikm);
+
+        Ok(tag.as_ref().to_vec())
+    }
+
+    fn extract_size(&self) -> usize {
+        self.0.extract_size()
+    }
+
+    fn kdf_id(&self) -> u16 {
+        self.0 as u16
+    }
+}
+
+#[cfg(all(test, not(mls_build_async)))]
+mod test {
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::CipherSuite;
+    use mls_rs_crypto_traits::KdfType;
+
+    use super::{KdfError, RingHkdf};
+
+    #[test]
+    fn no_key() {
+        let kdf = RingHkdf::new(CipherSuite::CURVE25519_AES128).unwrap();
+        assert!(kdf.extract(b"key", &[]).is_err());
+    }
+
+    #[test]
+    fn no_salt() {
+        let kdf = RingHkdf::new(CipherSuite::CURVE25519_AES128).unwrap();
+
+        assert_eq!(
+            kdf.extract(&[], b"key").unwrap(),
+            kdf.extract(&[0u8; 32], b"key").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_short_key() {
+        let kdf = RingHkdf::new(CipherSuite::CURVE25519_AES128).unwrap();
+        let key = vec![0u8; kdf.extract_size() - 1];
+
+        assert_matches!(kdf.expand(&key, &[], 42), Err(KdfError::TooShortKey(_, _)));
+    }
+}
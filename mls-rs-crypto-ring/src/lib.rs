@@ -0,0 +1,346 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! [ring](https://github.com/briansmith/ring) based
+//! [CryptoProvider](mls_rs_core::crypto::CryptoProvider) for mls-rs.
+//!
+//! Symmetric primitives and signatures are computed by ring. Since ring can't
+//! import static key agreement keys, HPKE Diffie-Hellman uses `x25519-dalek`
+//! and `p256` instead.
+
+pub mod aead;
+pub mod ec_signer;
+pub mod ecdh;
+pub mod kdf;
+pub mod mac;
+
+use mls_rs_core::{
+    crypto::{
+        CipherSuite, CipherSuiteProvider, CryptoProvider, HpkeCiphertext, HpkePublicKey,
+        HpkeSecretKey, SignaturePublicKey, SignatureSecretKey,
+    },
+    error::{AnyError, IntoAnyError},
+};
+use mls_rs_crypto_hpke::{
+    context::{ContextR, ContextS},
+    dhkem::DhKem,
+    hpke::{Hpke, HpkeError},
+};
+use mls_rs_crypto_traits::{AeadType, KdfType, KemId};
+use ring::rand::{SecureRandom, SystemRandom};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use aead::RingAead;
+use ec_signer::{EcSigner, EcSignerError};
+use ecdh::Ecdh;
+use kdf::RingHkdf;
+use mac::RingHash;
+
+#[derive(Debug, Error)]
+pub enum RingCryptoError {
+    #[error(transparent)]
+    AeadError(AnyError),
+    #[error(transparent)]
+    HpkeError(#[from] HpkeError),
+    #[error(transparent)]
+    KdfError(AnyError),
+    #[error(transparent)]
+    EcSignerError(#[from] EcSignerError),
+    #[error("failed to generate random bytes")]
+    RandError,
+}
+
+impl IntoAnyError for RingCryptoError {
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+pub type EcdhKem = DhKem<Ecdh, RingHkdf>;
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RingCryptoProvider {
+    pub enabled_cipher_suites: Vec<CipherSuite>,
+}
+
+impl RingCryptoProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enabled_cipher_suites(enabled_cipher_suites: Vec<CipherSuite>) -> Self {
+        Self {
+            enabled_cipher_suites,
+        }
+    }
+
+    pub fn all_supported_cipher_suites() -> Vec<CipherSuite> {
+        vec![
+            CipherSuite::CURVE25519_AES128,
+            CipherSuite::CURVE25519_CHACHA,
+            CipherSuite::P256_AES128,
+        ]
+    }
+}
+
+impl Default for RingCryptoProvider {
+    fn default() -> Self {
+        Self {
+            enabled_cipher_suites: Self::all_supported_cipher_suites(),
+        }
+    }
+}
+
+impl CryptoProvider for RingCryptoProvider {
+    type CipherSuiteProvider = RingCipherSuite;
+
+    fn supported_cipher_suites(&self) -> Vec<CipherSuite> {
+        self.enabled_cipher_suites.clone()
+    }
+
+    fn cipher_suite_provider(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Option<Self::CipherSuiteProvider> {
+        if !self.enabled_cipher_suites.contains(&cipher_suite) {
+            return None;
+        }
+
+        RingCipherSuite::new(cipher_suite)
+    }
+}
+
+#[derive(Clone)]
+pub struct RingCipherSuite {
+    cipher_suite: CipherSuite,
+    aead: RingAead,
+    kdf: RingHkdf,
+    hash: RingHash,
+    hpke: Hpke<EcdhKem, RingHkdf, RingAead>,
+    ec_signer: EcSigner,
+}
+
+impl RingCipherSuite {
+    pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
+        let kdf = RingHkdf::new(cipher_suite)?;
+        let aead = RingAead::new(cipher_suite)?;
+        let kem_id = KemId::new(cipher_suite)?;
+        let kem = DhKem::new(
+            Ecdh::new(cipher_suite)?,
+            kdf,
+            kem_id as u16,
+            kem_id.n_secret(),
+        );
+
+        Some(Self {
+            cipher_suite,
+            aead,
+            kdf,
+            hash: RingHash::new(cipher_suite)?,
+            hpke: Hpke::new(kem, kdf, Some(aead)),
+            ec_signer: EcSigner::new(cipher_suite)?,
+        })
+    }
+
+    pub fn random_bytes(&self, out: &mut [u8]) -> Result<(), RingCryptoError> {
+        SystemRandom::new()
+            .fill(out)
+            .map_err(|_| RingCryptoError::RandError)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl CipherSuiteProvider for RingCipherSuite {
+    type Error = RingCryptoError;
+    type HpkeContextR = ContextR<RingHkdf, RingAead>;
+    type HpkeContextS = ContextS<RingHkdf, RingAead>;
+
+    async fn hash(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.hash.hash(data))
+    }
+
+    async fn mac(&self, key: &[u8], data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.hash.mac(key, data))
+    }
+
+    async fn aead_seal(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.aead
+            .seal(key, data, aad, nonce)
+            .await
+            .map_err(|e| RingCryptoError::AeadError(e.into_any_error()))
+    }
+
+    async fn aead_open(
+        &self,
+        key: &[u8],
+        cipher_text: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.aead
+            .open(key, cipher_text, aad, nonce)
+            .await
+            .map_err(|e| RingCryptoError::AeadError(e.into_any_error()))
+            .map(Zeroizing::new)
+    }
+
+    fn aead_key_size(&self) -> usize {
+        self.aead.key_size()
+    }
+
+    fn aead_nonce_size(&self) -> usize {
+        self.aead.nonce_size()
+    }
+
+    async fn kdf_expand(
+        &self,
+        prk: &[u8],
+        info: &[u8],
+        len: usize,
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.kdf
+            .expand(prk, info, len)
+            .await
+            .map_err(|e| RingCryptoError::KdfError(e.into_any_error()))
+            .map(Zeroizing::new)
+    }
+
+    async fn kdf_extract(
+        &self,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.kdf
+            .extract(salt, ikm)
+            .await
+            .map_err(|e| RingCryptoError::KdfError(e.into_any_error()))
+            .map(Zeroizing::new)
+    }
+
+    fn kdf_extract_size(&self) -> usize {
+        self.kdf.extract_size()
+    }
+
+    async fn hpke_seal(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+        pt: &[u8],
+    ) -> Result<HpkeCiphertext, Self::Error> {
+        Ok(self.hpke.seal(remote_key, info, None, aad, pt).await?)
+    }
+
+    async fn hpke_open(
+        &self,
+        ciphertext: &HpkeCiphertext,
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        Ok(self
+            .hpke
+            .open(ciphertext, local_secret, local_public, info, None, aad)
+            .await?)
+    }
+
+    async fn hpke_setup_r(
+        &self,
+        enc: &[u8],
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<Self::HpkeContextR, Self::Error> {
+        Ok(self
+            .hpke
+            .setup_receiver(enc, local_secret, local_public, info, None)
+            .await?)
+    }
+
+    async fn hpke_setup_s(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<(Vec<u8>, Self::HpkeContextS), Self::Error> {
+        Ok(self.hpke.setup_sender(remote_key, info, None).await?)
+    }
+
+    async fn kem_derive(&self, ikm: &[u8]) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        Ok(self.hpke.derive(ikm).await?)
+    }
+
+    async fn kem_generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        Ok(self.hpke.generate().await?)
+    }
+
+    fn kem_public_key_validate(&self, key: &HpkePublicKey) -> Result<(), Self::Error> {
+        Ok(self.hpke.public_key_validate(key)?)
+    }
+
+    fn random_bytes(&self, out: &mut [u8]) -> Result<(), Self::Error> {
+        self.random_bytes(out)
+    }
+
+    fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    async fn sign(
+        &self,
+        secret_key: &SignatureSecretKey,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.ec_signer.sign(secret_key, data)?)
+    }
+
+    async fn verify(
+        &self,
+        public_key: &SignaturePublicKey,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        Ok(self.ec_signer.verify(public_key, signature, data)?)
+    }
+
+    async fn signature_key_generate(
+        &self,
+    ) -> Result<(SignatureSecretKey, SignaturePublicKey), Self::Error> {
+        Ok(self.ec_signer.signature_key_generate()?)
+    }
+
+    async fn signature_key_derive_public(
+        &self,
+        secret_key: &SignatureSecretKey,
+    ) -> Result<SignaturePublicKey, Self::Error> {
+        Ok(self.ec_signer.signature_key_derive_public(secret_key)?)
+    }
+}
+
+#[cfg(not(mls_build_async))]
+#[test]
+fn mls_core_tests() {
+    let provider = RingCryptoProvider::new();
+    mls_rs_core::crypto::test_suite::verify_tests(&provider, true);
+
+    for cs in RingCryptoProvider::all_supported_cipher_suites() {
+        let mut hpke = provider.cipher_suite_provider(cs).unwrap().hpke;
+
+        mls_rs_core::crypto::test_suite::verify_hpke_context_tests(&hpke, cs);
+        mls_rs_core::crypto::test_suite::verify_hpke_encap_tests(&mut hpke, cs);
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::crypto::CipherSuite;
+use ring::{digest, hmac};
+
+#[derive(Clone, Copy, Debug)]
+pub struct RingHash(hmac::Algorithm);
+
+impl RingHash {
+    pub fn new(cipher_suite: CipherSuite) -> Option<Self> {
+        match cipher_suite {
+            CipherSuite::CURVE25519_AES128
+            | CipherSuite::CURVE25519_CHACHA
+            | CipherSuite::P256_AES128 => Some(Self(hmac::HMAC_SHA256)),
+            CipherSuite::P384_AES256 => Some(Self(hmac::HMAC_SHA384)),
+            CipherSuite::P521_AES256 => Some(Self(hmac::HMAC_SHA512)),
+            _ => None,
+        }
+    }
+
+    pub fn hash(&self, data: &[u8]) -> Vec<u8> {
+        digest::digest(self.0.digest_algorithm(), data)
+            .as_ref()
+            .to_vec()
+    }
+
+    pub fn mac(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        hmac::sign(&hmac::Key::new(self.0, key), data)
+            .as_ref()
+            .to_vec()
+    }
+}
@@ -0,0 +1,933 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::{
+    crypto::{
+        CipherSuite, CipherSuiteProvider, CryptoProvider, HpkeCiphertext, HpkeContextR,
+        HpkeContextS, HpkePublicKey, HpkeSecretKey, SignaturePublicKey, SignatureSecretKey,
+    },
+    error::IntoAnyError,
+    secret::{KeyScheduleSecret, KeyScheduleSecretError},
+};
+use zeroize::Zeroizing;
+
+/// A [`CryptoProvider`] routing each cipher suite to one of two providers.
+///
+/// Cipher suites are served by the primary provider if it supports them and
+/// by the secondary provider otherwise. The set of cipher suites routed to
+/// the primary provider can be restricted with
+/// [`with_primary_cipher_suites`](Self::with_primary_cipher_suites), for
+/// example to use a hardware backed provider for P-256 only while all other
+/// cipher suites are implemented in software.
+///
+/// More than two providers can be combined by nesting composite providers.
+#[derive(Clone, Debug)]
+pub struct CompositeCryptoProvider<P, S> {
+    primary: P,
+    secondary: S,
+    primary_cipher_suites: Option<Vec<CipherSuite>>,
+}
+
+impl<P, S> CompositeCryptoProvider<P, S>
+where
+    P: CryptoProvider,
+    S: CryptoProvider,
+{
+    /// Create a provider routing every cipher suite supported by `primary`
+    /// to `primary` and all other cipher suites to `secondary`.
+    pub fn new(primary: P, secondary: S) -> Self {
+        Self {
+            primary,
+            secondary,
+            primary_cipher_suites: None,
+        }
+    }
+
+    /// Only route `cipher_suites` to the primary provider.
+    pub fn with_primary_cipher_suites(self, cipher_suites: Vec<CipherSuite>) -> Self {
+        Self {
+            primary_cipher_suites: Some(cipher_suites),
+            ..self
+        }
+    }
+
+    /// Provider that cipher suites are routed to first.
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    /// Provider of the cipher suites that are not routed to the primary
+    /// provider.
+    pub fn secondary(&self) -> &S {
+        &self.secondary
+    }
+
+    fn routed_to_primary(&self, cipher_suite: CipherSuite) -> bool {
+        match &self.primary_cipher_suites {
+            Some(suites) => suites.contains(&cipher_suite),
+            None => true,
+        }
+    }
+}
+
+impl<P, S> CryptoProvider for CompositeCryptoProvider<P, S>
+where
+    P: CryptoProvider,
+    S: CryptoProvider,
+{
+    type CipherSuiteProvider =
+        CompositeCipherSuiteProvider<P::CipherSuiteProvider, S::CipherSuiteProvider>;
+
+    fn supported_cipher_suites(&self) -> Vec<CipherSuite> {
+        let mut cipher_suites: Vec<_> = self
+            .primary
+            .supported_cipher_suites()
+            .into_iter()
+            .filter(|cs| self.routed_to_primary(*cs))
+            .collect();
+
+        for cipher_suite in self.secondary.supported_cipher_suites() {
+            if !cipher_suites.contains(&cipher_suite) {
+                cipher_suites.push(cipher_suite);
+            }
+        }
+
+        cipher_suites
+    }
+
+    fn cipher_suite_provider(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Option<Self::CipherSuiteProvider> {
+        self.routed_to_primary(cipher_suite)
+            .then(|| self.primary.cipher_suite_provider(cipher_suite))
+            .flatten()
+            .map(CompositeCipherSuiteProvider::Primary)
+            .or_else(|| {
+                self.secondary
+                    .cipher_suite_provider(cipher_suite)
+                    .map(CompositeCipherSuiteProvider::Secondary)
+            })
+    }
+}
+
+/// Error returned by the provider a [`CompositeCryptoProvider`] routed a
+/// cipher suite to.
+#[derive(Debug)]
+pub enum CompositeError<P, S> {
+    Primary(P),
+    Secondary(S),
+}
+
+impl<P: IntoAnyError, S: IntoAnyError> IntoAnyError for CompositeError<P, S> {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        match self {
+            Self::Primary(e) => e.into_dyn_error().map_err(Self::Primary),
+            Self::Secondary(e) => e.into_dyn_error().map_err(Self::Secondary),
+        }
+    }
+}
+
+/// [`CipherSuiteProvider`] of a [`CompositeCryptoProvider`].
+#[derive(Clone, Debug)]
+pub enum CompositeCipherSuiteProvider<P, S> {
+    Primary(P),
+    Secondary(S),
+}
+
+/// HPKE context of a [`CompositeCipherSuiteProvider`].
+#[derive(Debug)]
+pub enum CompositeHpkeContext<P, S> {
+    Primary(P),
+    Secondary(S),
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl<P, S> HpkeContextS for CompositeHpkeContext<P, S>
+where
+    P: HpkeContextS + Send + Sync,
+    S: HpkeContextS + Send + Sync,
+{
+    type Error = CompositeError<P::Error, S::Error>;
+
+    async fn seal(&mut self, aad: Option<&[u8]>, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::Primary(ctx) => ctx.seal(aad, data).await.map_err(CompositeError::Primary),
+            Self::Secondary(ctx) => ctx.seal(aad, data).await.map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::Primary(ctx) => HpkeContextS::export(ctx, exporter_context, len)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(ctx) => HpkeContextS::export(ctx, exporter_context, len)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl<P, S> HpkeContextR for CompositeHpkeContext<P, S>
+where
+    P: HpkeContextR + Send + Sync,
+    S: HpkeContextR + Send + Sync,
+{
+    type Error = CompositeError<P::Error, S::Error>;
+
+    async fn open(
+        &mut self,
+        aad: Option<&[u8]>,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::Primary(ctx) => ctx
+                .open(aad, ciphertext)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(ctx) => ctx
+                .open(aad, ciphertext)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::Primary(ctx) => HpkeContextR::export(ctx, exporter_context, len)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(ctx) => HpkeContextR::export(ctx, exporter_context, len)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl<P, S> CipherSuiteProvider for CompositeCipherSuiteProvider<P, S>
+where
+    P: CipherSuiteProvider,
+    S: CipherSuiteProvider,
+{
+    type Error = CompositeError<P::Error, S::Error>;
+
+    type HpkeContextS = CompositeHpkeContext<P::HpkeContextS, S::HpkeContextS>;
+    type HpkeContextR = CompositeHpkeContext<P::HpkeContextR, S::HpkeContextR>;
+
+    fn cipher_suite(&self) -> CipherSuite {
+        match self {
+            Self::Primary(p) => p.cipher_suite(),
+            Self::Secondary(s) => s.cipher_suite(),
+        }
+    }
+
+    async fn hash(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::Primary(p) => p.hash(data).await.map_err(CompositeError::Primary),
+            Self::Secondary(s) => s.hash(data).await.map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn mac(&self, key: &[u8], data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::Primary(p) => p.mac(key, data).await.map_err(CompositeError::Primary),
+            Self::Secondary(s) => s.mac(key, data).await.map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn aead_seal(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .aead_seal(key, data, aad, nonce)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .aead_seal(key, data, aad, nonce)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn aead_open(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .aead_open(key, ciphertext, aad, nonce)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .aead_open(key, ciphertext, aad, nonce)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    fn aead_key_size(&self) -> usize {
+        match self {
+            Self::Primary(p) => p.aead_key_size(),
+            Self::Secondary(s) => s.aead_key_size(),
+        }
+    }
+
+    fn aead_nonce_size(&self) -> usize {
+        match self {
+            Self::Primary(p) => p.aead_nonce_size(),
+            Self::Secondary(s) => s.aead_nonce_size(),
+        }
+    }
+
+    async fn kdf_extract(
+        &self,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .kdf_extract(salt, ikm)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .kdf_extract(salt, ikm)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn kdf_expand(
+        &self,
+        prk: &[u8],
+        info: &[u8],
+        len: usize,
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .kdf_expand(prk, info, len)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .kdf_expand(prk, info, len)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    fn kdf_extract_size(&self) -> usize {
+        match self {
+            Self::Primary(p) => p.kdf_extract_size(),
+            Self::Secondary(s) => s.kdf_extract_size(),
+        }
+    }
+
+    fn supports_secret_handles(&self) -> bool {
+        match self {
+            Self::Primary(p) => p.supports_secret_handles(),
+            Self::Secondary(s) => s.supports_secret_handles(),
+        }
+    }
+
+    async fn kdf_extract_secret(
+        &self,
+        salt: &KeyScheduleSecret,
+        ikm: &KeyScheduleSecret,
+    ) -> Result<KeyScheduleSecret, KeyScheduleSecretError> {
+        match self {
+            Self::Primary(p) => p.kdf_extract_secret(salt, ikm).await,
+            Self::Secondary(s) => s.kdf_extract_secret(salt, ikm).await,
+        }
+    }
+
+    async fn kdf_expand_secret(
+        &self,
+        prk: &KeyScheduleSecret,
+        info: &[u8],
+        len: usize,
+    ) -> Result<KeyScheduleSecret, KeyScheduleSecretError> {
+        match self {
+            Self::Primary(p) => p.kdf_expand_secret(prk, info, len).await,
+            Self::Secondary(s) => s.kdf_expand_secret(prk, info, len).await,
+        }
+    }
+
+    async fn mac_with_secret(
+        &self,
+        key: &KeyScheduleSecret,
+        data: &[u8],
+    ) -> Result<Vec<u8>, KeyScheduleSecretError> {
+        match self {
+            Self::Primary(p) => p.mac_with_secret(key, data).await,
+            Self::Secondary(s) => s.mac_with_secret(key, data).await,
+        }
+    }
+
+    async fn aead_seal_with_secret(
+        &self,
+        key: &KeyScheduleSecret,
+        data: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, KeyScheduleSecretError> {
+        match self {
+            Self::Primary(p) => p.aead_seal_with_secret(key, data, aad, nonce).await,
+            Self::Secondary(s) => s.aead_seal_with_secret(key, data, aad, nonce).await,
+        }
+    }
+
+    async fn aead_open_with_secret(
+        &self,
+        key: &KeyScheduleSecret,
+        ciphertext: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, KeyScheduleSecretError> {
+        match self {
+            Self::Primary(p) => p.aead_open_with_secret(key, ciphertext, aad, nonce).await,
+            Self::Secondary(s) => s.aead_open_with_secret(key, ciphertext, aad, nonce).await,
+        }
+    }
+
+    async fn hpke_seal(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+        pt: &[u8],
+    ) -> Result<HpkeCiphertext, Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .hpke_seal(remote_key, info, aad, pt)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .hpke_seal(remote_key, info, aad, pt)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn hpke_open(
+        &self,
+        ciphertext: &HpkeCiphertext,
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .hpke_open(ciphertext, local_secret, local_public, info, aad)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .hpke_open(ciphertext, local_secret, local_public, info, aad)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn hpke_setup_s(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<(Vec<u8>, Self::HpkeContextS), Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .hpke_setup_s(remote_key, info)
+                .await
+                .map(|(enc, ctx)| (enc, CompositeHpkeContext::Primary(ctx)))
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .hpke_setup_s(remote_key, info)
+                .await
+                .map(|(enc, ctx)| (enc, CompositeHpkeContext::Secondary(ctx)))
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn hpke_setup_r(
+        &self,
+        kem_output: &[u8],
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<Self::HpkeContextR, Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .hpke_setup_r(kem_output, local_secret, local_public, info)
+                .await
+                .map(CompositeHpkeContext::Primary)
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .hpke_setup_r(kem_output, local_secret, local_public, info)
+                .await
+                .map(CompositeHpkeContext::Secondary)
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn kem_derive(&self, ikm: &[u8]) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        match self {
+            Self::Primary(p) => p.kem_derive(ikm).await.map_err(CompositeError::Primary),
+            Self::Secondary(s) => s.kem_derive(ikm).await.map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn kem_generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        match self {
+            Self::Primary(p) => p.kem_generate().await.map_err(CompositeError::Primary),
+            Self::Secondary(s) => s.kem_generate().await.map_err(CompositeError::Secondary),
+        }
+    }
+
+    fn kem_public_key_validate(&self, key: &HpkePublicKey) -> Result<(), Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .kem_public_key_validate(key)
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .kem_public_key_validate(key)
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    fn random_bytes(&self, out: &mut [u8]) -> Result<(), Self::Error> {
+        match self {
+            Self::Primary(p) => p.random_bytes(out).map_err(CompositeError::Primary),
+            Self::Secondary(s) => s.random_bytes(out).map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn signature_key_generate(
+        &self,
+    ) -> Result<(SignatureSecretKey, SignaturePublicKey), Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .signature_key_generate()
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .signature_key_generate()
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn signature_key_derive_public(
+        &self,
+        secret_key: &SignatureSecretKey,
+    ) -> Result<SignaturePublicKey, Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .signature_key_derive_public(secret_key)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .signature_key_derive_public(secret_key)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn sign(
+        &self,
+        secret_key: &SignatureSecretKey,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .sign(secret_key, data)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .sign(secret_key, data)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+
+    async fn verify(
+        &self,
+        public_key: &SignaturePublicKey,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        match self {
+            Self::Primary(p) => p
+                .verify(public_key, signature, data)
+                .await
+                .map_err(CompositeError::Primary),
+            Self::Secondary(s) => s
+                .verify(public_key, signature, data)
+                .await
+                .map_err(CompositeError::Secondary),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use mls_rs_core::{
+        crypto::{
+            CipherSuite, CipherSuiteProvider, CryptoProvider, HpkeCiphertext, HpkeContextR,
+            HpkeContextS, HpkePublicKey, HpkeSecretKey, SignaturePublicKey, SignatureSecretKey,
+        },
+        error::IntoAnyError,
+    };
+    use zeroize::Zeroizing;
+
+    use super::{CompositeCipherSuiteProvider, CompositeCryptoProvider, CompositeError};
+
+    const SHARED: CipherSuite = CipherSuite::CURVE25519_AES128;
+    const PRIMARY_ONLY: CipherSuite = CipherSuite::P256_AES128;
+    const SECONDARY_ONLY: CipherSuite = CipherSuite::CURVE448_AES256;
+
+    #[derive(Debug, PartialEq)]
+    struct TestError(&'static str);
+
+    impl IntoAnyError for TestError {}
+
+    /// Provider whose cipher suite providers fail every operation with an
+    /// error naming the provider.
+    #[derive(Clone, Debug)]
+    struct TestProvider {
+        name: &'static str,
+        cipher_suites: Vec<CipherSuite>,
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestCipherSuiteProvider {
+        name: &'static str,
+        cipher_suite: CipherSuite,
+    }
+
+    struct TestHpkeContext;
+
+    impl CryptoProvider for TestProvider {
+        type CipherSuiteProvider = TestCipherSuiteProvider;
+
+        fn supported_cipher_suites(&self) -> Vec<CipherSuite> {
+            self.cipher_suites.clone()
+        }
+
+        fn cipher_suite_provider(
+            &self,
+            cipher_suite: CipherSuite,
+        ) -> Option<Self::CipherSuiteProvider> {
+            self.cipher_suites
+                .contains(&cipher_suite)
+                .then_some(TestCipherSuiteProvider {
+                    name: self.name,
+                    cipher_suite,
+                })
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl HpkeContextS for TestHpkeContext {
+        type Error = TestError;
+
+        async fn seal(&mut self, _aad: Option<&[u8]>, _data: &[u8]) -> Result<Vec<u8>, TestError> {
+            unimplemented!()
+        }
+
+        async fn export(&self, _context: &[u8], _len: usize) -> Result<Vec<u8>, TestError> {
+            unimplemented!()
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl HpkeContextR for TestHpkeContext {
+        type Error = TestError;
+
+        async fn open(&mut self, _aad: Option<&[u8]>, _data: &[u8]) -> Result<Vec<u8>, TestError> {
+            unimplemented!()
+        }
+
+        async fn export(&self, _context: &[u8], _len: usize) -> Result<Vec<u8>, TestError> {
+            unimplemented!()
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl CipherSuiteProvider for TestCipherSuiteProvider {
+        type Error = TestError;
+        type HpkeContextS = TestHpkeContext;
+        type HpkeContextR = TestHpkeContext;
+
+        fn cipher_suite(&self) -> CipherSuite {
+            self.cipher_suite
+        }
+
+        fn random_bytes(&self, _out: &mut [u8]) -> Result<(), TestError> {
+            Err(TestError(self.name))
+        }
+
+        async fn hash(&self, _data: &[u8]) -> Result<Vec<u8>, TestError> {
+            unimplemented!()
+        }
+
+        async fn mac(&self, _key: &[u8], _data: &[u8]) -> Result<Vec<u8>, TestError> {
+            unimplemented!()
+        }
+
+        async fn aead_seal(
+            &self,
+            _key: &[u8],
+            _data: &[u8],
+            _aad: Option<&[u8]>,
+            _nonce: &[u8],
+        ) -> Result<Vec<u8>, TestError> {
+            unimplemented!()
+        }
+
+        async fn aead_open(
+            &self,
+            _key: &[u8],
+            _ciphertext: &[u8],
+            _aad: Option<&[u8]>,
+            _nonce: &[u8],
+        ) -> Result<Zeroizing<Vec<u8>>, TestError> {
+            unimplemented!()
+        }
+
+        fn aead_key_size(&self) -> usize {
+            unimplemented!()
+        }
+
+        fn aead_nonce_size(&self) -> usize {
+            unimplemented!()
+        }
+
+        async fn kdf_extract(
+            &self,
+            _salt: &[u8],
+            _ikm: &[u8],
+        ) -> Result<Zeroizing<Vec<u8>>, TestError> {
+            unimplemented!()
+        }
+
+        async fn kdf_expand(
+            &self,
+            _prk: &[u8],
+            _info: &[u8],
+            _len: usize,
+        ) -> Result<Zeroizing<Vec<u8>>, TestError> {
+            unimplemented!()
+        }
+
+        fn kdf_extract_size(&self) -> usize {
+            unimplemented!()
+        }
+
+        async fn hpke_seal(
+            &self,
+            _remote_key: &HpkePublicKey,
+            _info: &[u8],
+            _aad: Option<&[u8]>,
+            _pt: &[u8],
+        ) -> Result<HpkeCiphertext, TestError> {
+            unimplemented!()
+        }
+
+        async fn hpke_open(
+            &self,
+            _ciphertext: &HpkeCiphertext,
+            _local_secret: &HpkeSecretKey,
+            _local_public: &HpkePublicKey,
+            _info: &[u8],
+            _aad: Option<&[u8]>,
+        ) -> Result<Vec<u8>, TestError> {
+            unimplemented!()
+        }
+
+        async fn hpke_setup_s(
+            &self,
+            _remote_key: &HpkePublicKey,
+            _info: &[u8],
+        ) -> Result<(Vec<u8>, TestHpkeContext), TestError> {
+            unimplemented!()
+        }
+
+        async fn hpke_setup_r(
+            &self,
+            _kem_output: &[u8],
+            _local_secret: &HpkeSecretKey,
+            _local_public: &HpkePublicKey,
+            _info: &[u8],
+        ) -> Result<TestHpkeContext, TestError> {
+            unimplemented!()
+        }
+
+        async fn kem_derive(
+            &self,
+            _ikm: &[u8],
+        ) -> Result<(HpkeSecretKey, HpkePublicKey), TestError> {
+            unimplemented!()
+        }
+
+        async fn kem_generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), TestError> {
+            unimplemented!()
+        }
+
+        fn kem_public_key_validate(&self, _key: &HpkePublicKey) -> Result<(), TestError> {
+            unimplemented!()
+        }
+
+        async fn signature_key_generate(
+            &self,
+        ) -> Result<(SignatureSecretKey, SignaturePublicKey), TestError> {
+            unimplemented!()
+        }
+
+        async fn signature_key_derive_public(
+            &self,
+            _secret_key: &SignatureSecretKey,
+        ) -> Result<SignaturePublicKey, TestError> {
+            unimplemented!()
+        }
+
+        async fn sign(
+            &self,
+            _secret_key: &SignatureSecretKey,
+            _data: &[u8],
+        ) -> Result<Vec<u8>, TestError> {
+            unimplemented!()
+        }
+
+        async fn verify(
+            &self,
+            _public_key: &SignaturePublicKey,
+            _signature: &[u8],
+            _data: &[u8],
+        ) -> Result<(), TestError> {
+            unimplemented!()
+        }
+    }
+
+    fn composite() -> CompositeCryptoProvider<TestProvider, TestProvider> {
+        CompositeCryptoProvider::new(
+            TestProvider {
+                name: "primary",
+                cipher_suites: vec![PRIMARY_ONLY, SHARED],
+            },
+            TestProvider {
+                name: "secondary",
+                cipher_suites: vec![SHARED, SECONDARY_ONLY],
+            },
+        )
+    }
+
+    /// Name of the provider that `cipher_suite` is routed to.
+    fn routed_to(
+        provider: &CompositeCryptoProvider<TestProvider, TestProvider>,
+        cipher_suite: CipherSuite,
+    ) -> Option<&'static str> {
+        match provider.cipher_suite_provider(cipher_suite)? {
+            CompositeCipherSuiteProvider::Primary(p) => Some(p.name),
+            CompositeCipherSuiteProvider::Secondary(s) => Some(s.name),
+        }
+    }
+
+    #[test]
+    fn cipher_suites_supported_by_the_primary_are_routed_to_it() {
+        let provider = composite();
+
+        assert_eq!(routed_to(&provider, PRIMARY_ONLY), Some("primary"));
+        assert_eq!(routed_to(&provider, SHARED), Some("primary"));
+        assert_eq!(routed_to(&provider, SECONDARY_ONLY), Some("secondary"));
+        assert_eq!(routed_to(&provider, CipherSuite::P521_AES256), None);
+    }
+
+    #[test]
+    fn primary_cipher_suites_restrict_routing() {
+        let provider = composite().with_primary_cipher_suites(vec![PRIMARY_ONLY, SECONDARY_ONLY]);
+
+        assert_eq!(routed_to(&provider, PRIMARY_ONLY), Some("primary"));
+        assert_eq!(routed_to(&provider, SHARED), Some("secondary"));
+
+        // The primary does not support this cipher suite
+        assert_eq!(routed_to(&provider, SECONDARY_ONLY), Some("secondary"));
+
+        let provider = composite().with_primary_cipher_suites(vec![]);
+        assert_eq!(routed_to(&provider, PRIMARY_ONLY), None);
+    }
+
+    #[test]
+    fn supported_cipher_suites_are_deduplicated() {
+        let provider = composite();
+
+        assert_eq!(
+            provider.supported_cipher_suites(),
+            vec![PRIMARY_ONLY, SHARED, SECONDARY_ONLY]
+        );
+
+        let provider = provider.with_primary_cipher_suites(vec![SHARED]);
+
+        assert_eq!(
+            provider.supported_cipher_suites(),
+            vec![SHARED, SECONDARY_ONLY]
+        );
+    }
+
+    #[test]
+    fn errors_are_mapped_to_the_provider() {
+        let provider = composite();
+
+        let res = provider
+            .cipher_suite_provider(PRIMARY_ONLY)
+            .unwrap()
+            .random_bytes(&mut [0u8; 4]);
+
+        assert!(matches!(
+            res,
+            Err(CompositeError::Primary(TestError("primary")))
+        ));
+
+        let res = provider
+            .cipher_suite_provider(SECONDARY_ONLY)
+            .unwrap()
+            .random_bytes(&mut [0u8; 4]);
+
+        assert!(matches!(
+            res,
+            Err(CompositeError::Secondary(TestError("secondary")))
+        ));
+    }
+}
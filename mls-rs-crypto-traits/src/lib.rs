@@ -6,12 +6,16 @@
 extern crate alloc;
 
 mod aead;
+mod composite;
 mod dh;
 mod ec;
 mod kdf;
 mod kem;
 
 pub use aead::{AeadId, AeadType, AEAD_ID_EXPORT_ONLY, AES_TAG_LEN};
+pub use composite::{
+    CompositeCipherSuiteProvider, CompositeCryptoProvider, CompositeError, CompositeHpkeContext,
+};
 pub use dh::{DhType, SamplingMethod};
 pub use ec::Curve;
 pub use kdf::{KdfId, KdfType};
@@ -50,6 +50,14 @@ pub trait X509CredentialValidator {
         chain: &CertificateChain,
         timestamp: Option<MlsTime>,
     ) -> Result<SignaturePublicKey, Self::Error>;
+
+    /// Earliest expiration time of the certificates in a chain.
+    ///
+    /// The default implementation returns `None`, meaning the expiration is
+    /// unknown.
+    fn chain_expiration(&self, _chain: &CertificateChain) -> Result<Option<MlsTime>, Self::Error> {
+        Ok(None)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -164,6 +172,17 @@ where
     fn supported_types(&self) -> Vec<CredentialType> {
         vec![CredentialType::X509]
     }
+
+    /// Expiration of a certificate chain based on the behavior of the
+    /// underlying validator provided.
+    async fn credential_expiration(
+        &self,
+        signing_identity: &SigningIdentity,
+    ) -> Result<Option<MlsTime>, X509IdentityError> {
+        self.validator
+            .chain_expiration(&credential_to_chain(&signing_identity.credential)?)
+            .map_err(|e| X509IdentityError::X509ValidationError(e.into_any_error()))
+    }
 }
 
 #[cfg(all(test, feature = "std"))]
@@ -0,0 +1,21 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Stable interface for crates implementing storage, identity and rules
+//! providers for mls-rs.
+//!
+//! Provider crates should depend on this crate instead of `mls-rs` or
+//! `mls-rs-core`, and only use the items of a versioned module such as
+//! [`v1`]. Items of a published version module never change in a breaking
+//! way. A breaking change is made in a new version module, and mls-rs keeps
+//! accepting providers of all version modules still published by this crate.
+//!
+//! Storage and identity providers implementing the traits of [`v1`] can be
+//! passed to the `mls-rs` client builder as is. Rules providers implementing
+//! [`v1::rules::GroupRules`] are wrapped in `mls_rs::mls_rules::ProviderRules`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
+pub mod v1;
@@ -0,0 +1,43 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Version 1 of the provider interface.
+
+/// Rules customizing commits and message encryption of a group.
+pub mod rules;
+
+/// Storage of group states, key packages, pre-shared keys, group id mappings
+/// and signature keys.
+pub mod storage {
+    pub use mls_rs_core::{
+        crypto::{SignatureKeyStorage, SignaturePublicKey, SignatureSecretKey},
+        group::{EpochRecord, GroupIdMappingStorage, GroupState, GroupStateStorage},
+        key_package::{KeyPackageData, KeyPackageStorage},
+        psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+    };
+}
+
+/// Validation of member and external sender identities.
+pub mod identity {
+    pub use mls_rs_core::identity::{
+        BasicCredential, Credential, CredentialType, CustomCredential, IdentityProvider,
+        MemberValidationContext, SigningIdentity,
+    };
+}
+
+/// Types describing a group, passed to providers.
+pub mod group {
+    pub use mls_rs_core::{
+        crypto::CipherSuite,
+        extension::{Extension, ExtensionList, ExtensionType},
+        group::{GroupContext, Member},
+        protocol_version::ProtocolVersion,
+        time::MlsTime,
+    };
+}
+
+/// Errors returned by providers.
+pub mod error {
+    pub use mls_rs_core::error::{AnyError, IntoAnyError};
+}
@@ -0,0 +1,134 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::{
+    error::IntoAnyError,
+    group::{GroupContext, Member},
+};
+
+/// Options controlling commit generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CommitPolicy {
+    /// Include an update path in all commits, even if not required by MLS.
+    pub path_required: bool,
+    /// Include the ratchet tree in welcome messages.
+    pub ratchet_tree_extension: bool,
+    /// Generate a single welcome message for all new members.
+    pub single_welcome_message: bool,
+    /// Publish a group info allowing external commits.
+    pub allow_external_commit: bool,
+}
+
+impl Default for CommitPolicy {
+    fn default() -> Self {
+        Self {
+            path_required: false,
+            ratchet_tree_extension: true,
+            single_welcome_message: true,
+            allow_external_commit: false,
+        }
+    }
+}
+
+impl CommitPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_path_required(self, path_required: bool) -> Self {
+        Self {
+            path_required,
+            ..self
+        }
+    }
+
+    pub fn with_ratchet_tree_extension(self, ratchet_tree_extension: bool) -> Self {
+        Self {
+            ratchet_tree_extension,
+            ..self
+        }
+    }
+
+    pub fn with_single_welcome_message(self, single_welcome_message: bool) -> Self {
+        Self {
+            single_welcome_message,
+            ..self
+        }
+    }
+
+    pub fn with_allow_external_commit(self, allow_external_commit: bool) -> Self {
+        Self {
+            allow_external_commit,
+            ..self
+        }
+    }
+}
+
+/// Padding of encrypted messages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Padding {
+    /// Padding increasing in steps with the size of the message.
+    #[default]
+    StepFunction,
+    /// Padme padding, with an overhead of at most 11.11%.
+    Padme,
+    /// No padding.
+    None,
+}
+
+/// Options controlling encryption of control and application messages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EncryptionPolicy {
+    /// Send proposals and commits of members as private messages.
+    pub encrypt_control_messages: bool,
+    pub padding: Padding,
+}
+
+impl EncryptionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_encrypt_control_messages(self, encrypt_control_messages: bool) -> Self {
+        Self {
+            encrypt_control_messages,
+            ..self
+        }
+    }
+
+    pub fn with_padding(self, padding: Padding) -> Self {
+        Self { padding, ..self }
+    }
+}
+
+/// Rules of a group, applied by all members.
+///
+/// Unlike the rules of mls-rs itself, these rules can't filter or modify
+/// the proposals of a commit. By default, the default policies are returned.
+pub trait GroupRules: Send + Sync {
+    type Error: IntoAnyError;
+
+    /// Options of a commit resulting in a group with `new_members` and
+    /// `new_context`.
+    fn commit_policy(
+        &self,
+        _new_members: &[Member],
+        _new_context: &GroupContext,
+    ) -> Result<CommitPolicy, Self::Error> {
+        Ok(CommitPolicy::default())
+    }
+
+    /// Options of messages sent in the group with `current_members` and
+    /// `current_context`.
+    fn encryption_policy(
+        &self,
+        _current_members: &[Member],
+        _current_context: &GroupContext,
+    ) -> Result<EncryptionPolicy, Self::Error> {
+        Ok(EncryptionPolicy::default())
+    }
+}
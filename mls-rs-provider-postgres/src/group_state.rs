@@ -0,0 +1,290 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::group::{EpochRecord, GroupState, GroupStateStorage};
+
+use crate::{from_bigint, to_bigint, PostgresDataStorageError, SharedClient};
+
+pub(crate) const DEFAULT_EPOCH_RETENTION_LIMIT: u64 = 3;
+
+#[derive(Debug, Clone)]
+/// PostgreSQL storage for MLS group states.
+pub struct PostgresGroupStateStorage {
+    client: SharedClient,
+    tenant_id: Vec<u8>,
+    max_epoch_retention: u64,
+}
+
+impl PostgresGroupStateStorage {
+    pub(crate) fn new(client: SharedClient, tenant_id: Vec<u8>) -> PostgresGroupStateStorage {
+        PostgresGroupStateStorage {
+            client,
+            tenant_id,
+            max_epoch_retention: DEFAULT_EPOCH_RETENTION_LIMIT,
+        }
+    }
+
+    pub fn with_max_epoch_retention(self, max_epoch_retention: u64) -> Self {
+        Self {
+            max_epoch_retention,
+            ..self
+        }
+    }
+
+    pub fn max_epoch_retention(&self) -> u64 {
+        self.max_epoch_retention
+    }
+
+    /// List all the group ids for groups that are stored for the tenant of
+    /// this storage.
+    pub fn group_ids(&self) -> Result<Vec<Vec<u8>>, PostgresDataStorageError> {
+        self.client
+            .lock()
+            .query(
+                "SELECT group_id FROM mls_group WHERE tenant_id = $1",
+                &[&self.tenant_id],
+            )
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?
+            .iter()
+            .map(|row| {
+                row.try_get(0)
+                    .map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))
+            })
+            .collect()
+    }
+
+    /// Delete a group from storage.
+    pub fn delete_group(&self, group_id: &[u8]) -> Result<(), PostgresDataStorageError> {
+        self.client
+            .lock()
+            .execute(
+                "DELETE FROM mls_group WHERE tenant_id = $1 AND group_id = $2",
+                &[&self.tenant_id, &group_id],
+            )
+            .map(|_| ())
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))
+    }
+
+    fn get_snapshot_data(
+        &self,
+        group_id: &[u8],
+    ) -> Result<Option<Vec<u8>>, PostgresDataStorageError> {
+        self.client
+            .lock()
+            .query_opt(
+                "SELECT snapshot FROM mls_group WHERE tenant_id = $1 AND group_id = $2",
+                &[&self.tenant_id, &group_id],
+            )
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?
+            .map(|row| {
+                row.try_get(0)
+                    .map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))
+            })
+            .transpose()
+    }
+
+    fn get_epoch_data(
+        &self,
+        group_id: &[u8],
+        epoch_id: u64,
+    ) -> Result<Option<Vec<u8>>, PostgresDataStorageError> {
+        self.client
+            .lock()
+            .query_opt(
+                "SELECT epoch_data FROM epoch WHERE tenant_id = $1 AND group_id = $2 AND epoch_id = $3",
+                &[&self.tenant_id, &group_id, &to_bigint(epoch_id)?],
+            )
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?
+            .map(|row| {
+                row.try_get(0)
+                    .map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))
+            })
+            .transpose()
+    }
+
+    fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, PostgresDataStorageError> {
+        let row = self
+            .client
+            .lock()
+            .query_one(
+                "SELECT MAX(epoch_id) FROM epoch WHERE tenant_id = $1 AND group_id = $2",
+                &[&self.tenant_id, &group_id],
+            )
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+
+        row.try_get::<_, Option<i64>>(0)
+            .map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))?
+            .map(from_bigint)
+            .transpose()
+    }
+
+    fn update_group_state(
+        &self,
+        group_id: &[u8],
+        group_snapshot: Vec<u8>,
+        inserts: Vec<EpochRecord>,
+        updates: Vec<EpochRecord>,
+    ) -> Result<(), PostgresDataStorageError> {
+        let max_epoch_id = inserts.last().map(|epoch| epoch.id);
+
+        let mut client = self.client.lock();
+
+        let mut transaction = client
+            .transaction()
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+
+        // Upsert into the group table to set the most recent snapshot
+        transaction
+            .execute(
+                "INSERT INTO mls_group (tenant_id, group_id, snapshot) VALUES ($1, $2, $3) ON CONFLICT (tenant_id, group_id) DO UPDATE SET snapshot = EXCLUDED.snapshot",
+                &[&self.tenant_id, &group_id, &group_snapshot],
+            )
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+
+        // Insert new epochs as needed
+        if !inserts.is_empty() {
+            let statement = transaction
+                .prepare(
+                    "INSERT INTO epoch (tenant_id, group_id, epoch_id, epoch_data) VALUES ($1, $2, $3, $4)",
+                )
+                .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+
+            for epoch in &inserts {
+                transaction
+                    .execute(
+                        &statement,
+                        &[
+                            &self.tenant_id,
+                            &group_id,
+                            &to_bigint(epoch.id)?,
+                            &epoch.data,
+                        ],
+                    )
+                    .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+            }
+        }
+
+        // Update existing epochs as needed
+        if !updates.is_empty() {
+            let statement = transaction
+                .prepare(
+                    "UPDATE epoch SET epoch_data = $1 WHERE tenant_id = $2 AND group_id = $3 AND epoch_id = $4",
+                )
+                .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+
+            for epoch in &updates {
+                transaction
+                    .execute(
+                        &statement,
+                        &[
+                            &epoch.data,
+                            &self.tenant_id,
+                            &group_id,
+                            &to_bigint(epoch.id)?,
+                        ],
+                    )
+                    .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+            }
+        }
+
+        // Delete old epochs as needed
+        if let Some(max_epoch_id) = max_epoch_id {
+            if max_epoch_id >= self.max_epoch_retention {
+                let delete_under = to_bigint(max_epoch_id - self.max_epoch_retention)?;
+
+                transaction
+                    .execute(
+                        "DELETE FROM epoch WHERE tenant_id = $1 AND group_id = $2 AND epoch_id <= $3",
+                        &[&self.tenant_id, &group_id, &delete_under],
+                    )
+                    .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+            }
+        }
+
+        // Execute the full transaction, it is rolled back on any error above
+        transaction
+            .commit()
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl GroupStateStorage for PostgresGroupStateStorage {
+    type Error = PostgresDataStorageError;
+
+    async fn write(
+        &mut self,
+        state: GroupState,
+        inserts: Vec<EpochRecord>,
+        updates: Vec<EpochRecord>,
+    ) -> Result<(), Self::Error> {
+        self.update_group_state(&state.id, state.data, inserts, updates)
+    }
+
+    async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.get_snapshot_data(group_id)
+    }
+
+    async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        self.max_epoch_id(group_id)
+    }
+
+    async fn epoch(&self, group_id: &[u8], epoch_id: u64) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.get_epoch_data(group_id, epoch_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mls_rs_core::group::EpochRecord;
+
+    use crate::test_utils::{gen_rand_bytes, test_engine};
+
+    #[test]
+    fn epochs_are_written_and_truncated() {
+        let Some(engine) = test_engine() else {
+            return;
+        };
+
+        let storage = engine.group_state_storage().unwrap();
+
+        let group_id = gen_rand_bytes(32);
+        let epochs = (0..4)
+            .map(|id| EpochRecord::new(id, gen_rand_bytes(64)))
+            .collect::<Vec<_>>();
+
+        storage
+            .update_group_state(&group_id, gen_rand_bytes(64), epochs[..2].to_vec(), vec![])
+            .unwrap();
+
+        let updated = EpochRecord::new(1, gen_rand_bytes(64));
+        let snapshot = gen_rand_bytes(64);
+
+        storage
+            .update_group_state(
+                &group_id,
+                snapshot.clone(),
+                epochs[2..].to_vec(),
+                vec![updated.clone()],
+            )
+            .unwrap();
+
+        assert_eq!(
+            storage.get_snapshot_data(&group_id).unwrap(),
+            Some(snapshot)
+        );
+        assert_eq!(storage.max_epoch_id(&group_id).unwrap(), Some(3));
+        assert_eq!(storage.get_epoch_data(&group_id, 0).unwrap(), None);
+        assert_eq!(
+            storage.get_epoch_data(&group_id, 1).unwrap(),
+            Some(updated.data)
+        );
+        assert_eq!(storage.group_ids().unwrap(), vec![group_id.clone()]);
+
+        storage.delete_group(&group_id).unwrap();
+        assert_eq!(storage.get_snapshot_data(&group_id).unwrap(), None);
+        assert_eq!(storage.max_epoch_id(&group_id).unwrap(), None);
+    }
+}
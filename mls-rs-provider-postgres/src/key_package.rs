@@ -0,0 +1,127 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::{
+    key_package::{KeyPackageData, KeyPackageStorage},
+    mls_rs_codec::{MlsDecode, MlsEncode},
+};
+
+use crate::{to_bigint, PostgresDataStorageError, SharedClient};
+
+#[derive(Debug, Clone)]
+/// PostgreSQL storage for MLS Key Packages.
+pub struct PostgresKeyPackageStorage {
+    client: SharedClient,
+    tenant_id: Vec<u8>,
+}
+
+impl PostgresKeyPackageStorage {
+    pub(crate) fn new(client: SharedClient, tenant_id: Vec<u8>) -> PostgresKeyPackageStorage {
+        PostgresKeyPackageStorage { client, tenant_id }
+    }
+
+    fn insert(
+        &self,
+        id: &[u8],
+        key_package: KeyPackageData,
+    ) -> Result<(), PostgresDataStorageError> {
+        let data = key_package
+            .mls_encode_to_vec()
+            .map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))?;
+
+        self.client
+            .lock()
+            .execute(
+                "INSERT INTO key_package (tenant_id, id, expiration, data) VALUES ($1, $2, $3, $4)",
+                &[
+                    &self.tenant_id,
+                    &id,
+                    &to_bigint(key_package.expiration)?,
+                    &data,
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))
+    }
+
+    fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, PostgresDataStorageError> {
+        self.client
+            .lock()
+            .query_opt(
+                "SELECT data FROM key_package WHERE tenant_id = $1 AND id = $2",
+                &[&self.tenant_id, &id],
+            )
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?
+            .map(|row| {
+                let data = row
+                    .try_get::<_, Vec<u8>>(0)
+                    .map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))?;
+
+                KeyPackageData::mls_decode(&mut data.as_slice())
+                    .map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))
+            })
+            .transpose()
+    }
+
+    /// Delete a specific key package from storage based on it's id.
+    pub fn delete(&self, id: &[u8]) -> Result<(), PostgresDataStorageError> {
+        self.client
+            .lock()
+            .execute(
+                "DELETE FROM key_package WHERE tenant_id = $1 AND id = $2",
+                &[&self.tenant_id, &id],
+            )
+            .map(|_| ())
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))
+    }
+
+    /// Delete key packages that are expired based on an application provided time in seconds since
+    /// unix epoch.
+    pub fn delete_expired_by_time(&self, time: u64) -> Result<(), PostgresDataStorageError> {
+        self.client
+            .lock()
+            .execute(
+                "DELETE FROM key_package WHERE tenant_id = $1 AND expiration < $2",
+                &[&self.tenant_id, &to_bigint(time)?],
+            )
+            .map(|_| ())
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))
+    }
+
+    /// Total number of key packages held in storage.
+    pub fn count(&self) -> Result<usize, PostgresDataStorageError> {
+        let row = self
+            .client
+            .lock()
+            .query_one(
+                "SELECT count(*) FROM key_package WHERE tenant_id = $1",
+                &[&self.tenant_id],
+            )
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+
+        let count = row
+            .try_get::<_, i64>(0)
+            .map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))?;
+
+        usize::try_from(count).map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl KeyPackageStorage for PostgresKeyPackageStorage {
+    type Error = PostgresDataStorageError;
+
+    async fn insert(&mut self, id: Vec<u8>, pkg: KeyPackageData) -> Result<(), Self::Error> {
+        (*self).insert(id.as_slice(), pkg)
+    }
+
+    async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error> {
+        self.get(id)
+    }
+
+    async fn delete(&mut self, id: &[u8]) -> Result<(), Self::Error> {
+        (*self).delete(id)
+    }
+}
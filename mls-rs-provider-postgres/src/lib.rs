@@ -0,0 +1,227 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! PostgreSQL storage for server side mls-rs clients.
+//!
+//! The schema and the semantics of all storages are the same as those of
+//! `mls-rs-provider-sqlite`. In particular, a group snapshot and its epochs
+//! are always written in a single transaction.
+//!
+//! Storages use the blocking `postgres` client. In async builds, they must
+//! not be used from within a tokio runtime thread.
+
+use group_state::PostgresGroupStateStorage;
+use key_package::PostgresKeyPackageStorage;
+use postgres::{
+    tls::{MakeTlsConnect, TlsConnect},
+    Client, Config, NoTls, Socket,
+};
+use psk::PostgresPreSharedKeyStorage;
+use std::{
+    fmt::{self, Debug},
+    sync::{Arc, Mutex, MutexGuard},
+};
+use thiserror::Error;
+
+mod group_state;
+mod key_package;
+mod psk;
+
+/// PostgreSQL storage components.
+pub mod storage {
+    pub use {
+        crate::group_state::PostgresGroupStateStorage,
+        crate::key_package::PostgresKeyPackageStorage, crate::psk::PostgresPreSharedKeyStorage,
+    };
+}
+
+#[derive(Debug, Error)]
+/// PostgreSQL data storage error.
+pub enum PostgresDataStorageError {
+    #[error(transparent)]
+    /// PostgreSQL error.
+    SqlEngineError(Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    /// Stored data is not compatible with the expected data type.
+    DataConversionError(Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+impl mls_rs_core::error::IntoAnyError for PostgresDataStorageError {
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS mls_group (
+        tenant_id BYTEA NOT NULL,
+        group_id BYTEA NOT NULL,
+        snapshot BYTEA NOT NULL,
+        PRIMARY KEY (tenant_id, group_id)
+    );
+    CREATE TABLE IF NOT EXISTS epoch (
+        tenant_id BYTEA NOT NULL,
+        group_id BYTEA NOT NULL,
+        epoch_id BIGINT NOT NULL,
+        epoch_data BYTEA NOT NULL,
+        FOREIGN KEY (tenant_id, group_id) REFERENCES mls_group (tenant_id, group_id) ON DELETE CASCADE,
+        PRIMARY KEY (tenant_id, group_id, epoch_id)
+    );
+    CREATE TABLE IF NOT EXISTS key_package (
+        tenant_id BYTEA NOT NULL,
+        id BYTEA NOT NULL,
+        expiration BIGINT,
+        data BYTEA NOT NULL,
+        PRIMARY KEY (tenant_id, id)
+    );
+    CREATE INDEX IF NOT EXISTS key_package_exp ON key_package (tenant_id, expiration);
+    CREATE TABLE IF NOT EXISTS psk (
+        tenant_id BYTEA NOT NULL,
+        psk_id BYTEA NOT NULL,
+        data BYTEA NOT NULL,
+        PRIMARY KEY (tenant_id, psk_id)
+    );";
+
+type Connect = dyn Fn() -> Result<Client, postgres::Error> + Send + Sync;
+
+#[derive(Clone)]
+/// PostgreSQL data storage engine.
+pub struct PostgresDataStorageEngine {
+    connect: Arc<Connect>,
+    tenant_id: Vec<u8>,
+}
+
+impl Debug for PostgresDataStorageEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PostgresDataStorageEngine")
+            .field("tenant_id", &self.tenant_id)
+            .finish()
+    }
+}
+
+impl PostgresDataStorageEngine {
+    /// Connect to the database configured by `config` without TLS.
+    pub fn new(config: Config) -> Self {
+        Self::with_tls(config, NoTls)
+    }
+
+    /// Connect to the database configured by `config` with `tls`.
+    pub fn with_tls<T>(config: Config, tls: T) -> Self
+    where
+        T: MakeTlsConnect<Socket> + Clone + Send + Sync + 'static,
+        T::Stream: Send,
+        T::TlsConnect: Send,
+        <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+    {
+        Self {
+            connect: Arc::new(move || config.connect(tls.clone())),
+            tenant_id: Vec::new(),
+        }
+    }
+
+    /// Scope all storages created by this engine to `tenant_id`.
+    ///
+    /// Storages of different tenants sharing the same database never see or
+    /// modify each other's data.
+    pub fn with_tenant(self, tenant_id: &[u8]) -> Self {
+        Self {
+            tenant_id: tenant_id.to_vec(),
+            ..self
+        }
+    }
+
+    /// Tenant that storages created by this engine are scoped to.
+    pub fn tenant_id(&self) -> &[u8] {
+        &self.tenant_id
+    }
+
+    // Each storage uses its own connection, shared by its clones.
+    fn shared_client(&self) -> Result<SharedClient, PostgresDataStorageError> {
+        let mut client =
+            (self.connect)().map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+
+        client
+            .batch_execute(SCHEMA)
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?;
+
+        Ok(SharedClient(Arc::new(Mutex::new(client))))
+    }
+
+    /// Returns a struct that implements the `GroupStateStorage` trait for use in MLS.
+    pub fn group_state_storage(
+        &self,
+    ) -> Result<PostgresGroupStateStorage, PostgresDataStorageError> {
+        Ok(PostgresGroupStateStorage::new(
+            self.shared_client()?,
+            self.tenant_id.clone(),
+        ))
+    }
+
+    /// Returns a struct that implements the `KeyPackageStorage` trait for use in MLS.
+    pub fn key_package_storage(
+        &self,
+    ) -> Result<PostgresKeyPackageStorage, PostgresDataStorageError> {
+        Ok(PostgresKeyPackageStorage::new(
+            self.shared_client()?,
+            self.tenant_id.clone(),
+        ))
+    }
+
+    /// Returns a struct that implements the `PreSharedKeyStorage` trait for use in MLS.
+    pub fn pre_shared_key_storage(
+        &self,
+    ) -> Result<PostgresPreSharedKeyStorage, PostgresDataStorageError> {
+        Ok(PostgresPreSharedKeyStorage::new(
+            self.shared_client()?,
+            self.tenant_id.clone(),
+        ))
+    }
+}
+
+/// Connection shared by the clones of a storage.
+#[derive(Clone)]
+pub(crate) struct SharedClient(Arc<Mutex<Client>>);
+
+impl Debug for SharedClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedClient")
+    }
+}
+
+impl SharedClient {
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Client> {
+        // A panic while holding the lock rolls back any open transaction
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Postgres has no unsigned integers, ids and timestamps are stored as BIGINT.
+pub(crate) fn to_bigint(value: u64) -> Result<i64, PostgresDataStorageError> {
+    i64::try_from(value).map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))
+}
+
+pub(crate) fn from_bigint(value: i64) -> Result<u64, PostgresDataStorageError> {
+    u64::try_from(value).map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use rand::RngCore;
+
+    use crate::PostgresDataStorageEngine;
+
+    /// Engine connected to the database at `MLS_RS_POSTGRES_TEST_URL`, if
+    /// set. Tests are skipped without a database.
+    pub fn test_engine() -> Option<PostgresDataStorageEngine> {
+        let url = std::env::var("MLS_RS_POSTGRES_TEST_URL").ok()?;
+
+        // Tests share the database, each uses its own tenant
+        Some(PostgresDataStorageEngine::new(url.parse().unwrap()).with_tenant(&gen_rand_bytes(16)))
+    }
+
+    pub fn gen_rand_bytes(size: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = vec![0; size];
+        rand::rng().fill_bytes(&mut bytes);
+        bytes
+    }
+}
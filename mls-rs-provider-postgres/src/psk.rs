@@ -0,0 +1,102 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage};
+use std::ops::Deref;
+
+use crate::{PostgresDataStorageError, SharedClient};
+
+#[derive(Debug, Clone)]
+/// PostgreSQL storage for MLS pre-shared keys.
+pub struct PostgresPreSharedKeyStorage {
+    client: SharedClient,
+    tenant_id: Vec<u8>,
+}
+
+impl PostgresPreSharedKeyStorage {
+    pub(crate) fn new(client: SharedClient, tenant_id: Vec<u8>) -> PostgresPreSharedKeyStorage {
+        PostgresPreSharedKeyStorage { client, tenant_id }
+    }
+
+    /// Insert a pre-shared key into storage.
+    pub fn insert(
+        &self,
+        psk_id: &[u8],
+        psk: &PreSharedKey,
+    ) -> Result<(), PostgresDataStorageError> {
+        self.client
+            .lock()
+            .execute(
+                "INSERT INTO psk (tenant_id, psk_id, data) VALUES ($1, $2, $3) ON CONFLICT (tenant_id, psk_id) DO UPDATE SET data = EXCLUDED.data",
+                &[&self.tenant_id, &psk_id, &psk.deref()],
+            )
+            .map(|_| ())
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))
+    }
+
+    /// Get a pre-shared key from storage based on a unique id.
+    pub fn get(&self, psk_id: &[u8]) -> Result<Option<PreSharedKey>, PostgresDataStorageError> {
+        self.client
+            .lock()
+            .query_opt(
+                "SELECT data FROM psk WHERE tenant_id = $1 AND psk_id = $2",
+                &[&self.tenant_id, &psk_id],
+            )
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))?
+            .map(|row| {
+                row.try_get(0)
+                    .map(PreSharedKey::new)
+                    .map_err(|e| PostgresDataStorageError::DataConversionError(e.into()))
+            })
+            .transpose()
+    }
+
+    /// Delete a pre-shared key from storage based on a unique id.
+    pub fn delete(&self, psk_id: &[u8]) -> Result<(), PostgresDataStorageError> {
+        self.client
+            .lock()
+            .execute(
+                "DELETE FROM psk WHERE tenant_id = $1 AND psk_id = $2",
+                &[&self.tenant_id, &psk_id],
+            )
+            .map(|_| ())
+            .map_err(|e| PostgresDataStorageError::SqlEngineError(e.into()))
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl PreSharedKeyStorage for PostgresPreSharedKeyStorage {
+    type Error = PostgresDataStorageError;
+
+    async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
+        self.get(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mls_rs_core::psk::PreSharedKey;
+
+    use crate::test_utils::{gen_rand_bytes, test_engine};
+
+    #[test]
+    fn psk_is_inserted_overwritten_and_deleted() {
+        let Some(engine) = test_engine() else {
+            return;
+        };
+
+        let storage = engine.pre_shared_key_storage().unwrap();
+        let psk_id = gen_rand_bytes(32);
+        let psk = PreSharedKey::new(gen_rand_bytes(64));
+        let new_psk = PreSharedKey::new(gen_rand_bytes(64));
+
+        storage.insert(&psk_id, &psk).unwrap();
+        storage.insert(&psk_id, &new_psk).unwrap();
+        assert_eq!(storage.get(&psk_id).unwrap(), Some(new_psk));
+
+        storage.delete(&psk_id).unwrap();
+        assert_eq!(storage.get(&psk_id).unwrap(), None);
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use mls_rs_provider_sqlite::{
+    connection_strategy::FileConnectionStrategy,
+    storage::Item,
+    test_utils::{gen_rand_bytes, GroupFixture},
+    SqLiteDataStorageEngine,
+};
+use tempfile::tempdir;
+
+// A capacity of 0 prepares every statement on each call, which is the
+// behavior before statements were cached.
+const CACHE_CAPACITIES: [usize; 2] = [0, 16];
+
+fn engine(
+    path: &std::path::Path,
+    capacity: usize,
+) -> SqLiteDataStorageEngine<FileConnectionStrategy> {
+    SqLiteDataStorageEngine::new(FileConnectionStrategy::new(path))
+        .unwrap()
+        .with_statement_cache_capacity(capacity)
+}
+
+fn bench_group_state(c: &mut Criterion) {
+    let mut bench_group = c.benchmark_group("sqlite_group_state_update");
+
+    for capacity in CACHE_CAPACITIES {
+        let temp = tempdir().unwrap();
+        let storage = engine(&temp.path().join("bench.sqlite"), capacity)
+            .group_state_storage()
+            .unwrap();
+
+        // Every iteration stores a new group, since epochs can only be
+        // inserted once.
+        bench_group.bench_with_input(BenchmarkId::new("cache", capacity), &capacity, |b, _| {
+            b.iter_batched(
+                || GroupFixture::new().with_epochs(3),
+                |fixture| fixture.insert(&storage).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    bench_group.finish();
+}
+
+fn bench_application_data(c: &mut Criterion) {
+    let mut bench_group = c.benchmark_group("sqlite_application_data");
+
+    let items = (0..100)
+        .map(|i| Item::new(format!("prefix/{i}"), gen_rand_bytes(64)))
+        .collect::<Vec<_>>();
+
+    for capacity in CACHE_CAPACITIES {
+        let temp = tempdir().unwrap();
+        let storage = engine(&temp.path().join("bench.sqlite"), capacity)
+            .application_data_storage()
+            .unwrap();
+
+        bench_group.bench_with_input(
+            BenchmarkId::new("transact_insert", capacity),
+            &capacity,
+            |b, _| b.iter(|| storage.transact_insert(&items).unwrap()),
+        );
+
+        bench_group.bench_with_input(BenchmarkId::new("get", capacity), &capacity, |b, _| {
+            b.iter(|| storage.get("prefix/42").unwrap())
+        });
+
+        bench_group.bench_with_input(
+            BenchmarkId::new("get_by_prefix", capacity),
+            &capacity,
+            |b, _| b.iter(|| storage.get_by_prefix("prefix/1").unwrap()),
+        );
+    }
+
+    bench_group.finish();
+}
+
+criterion::criterion_group!(benches, bench_group_state, bench_application_data);
+criterion::criterion_main!(benches);
@@ -4,26 +4,41 @@
 
 use std::{
     fmt::{self, Debug},
-    sync::{Arc, Mutex},
+    ops::ControlFlow,
+    sync::Arc,
 };
 
+use mls_rs_core::time::MlsTime;
 use rusqlite::{params, Connection, OptionalExtension};
 
-use crate::SqLiteDataStorageError;
+use crate::{
+    clock::Clock, maintenance::AutoMaintenance, recovery::SharedConnection, SqLiteDataStorageError,
+};
 
 const INSERT_SQL: &str =
-    "INSERT INTO kvs (key, value) VALUES (?,?) ON CONFLICT(key) DO UPDATE SET value=excluded.value WHERE value != excluded.value";
+    "INSERT INTO kvs (tenant_id, key, value, expiration) VALUES (?,?,?,?) ON CONFLICT(tenant_id, key) DO UPDATE SET value=excluded.value, expiration=excluded.expiration WHERE value != excluded.value OR expiration IS NOT excluded.expiration";
 
 #[derive(Debug, Clone)]
 /// SQLite key-value storage for application specific data.
 pub struct SqLiteApplicationStorage {
-    connection: Arc<Mutex<Connection>>,
+    connection: SharedConnection,
+    tenant_id: Vec<u8>,
+    auto_maintenance: Option<Arc<AutoMaintenance>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl SqLiteApplicationStorage {
-    pub(crate) fn new(connection: Connection) -> SqLiteApplicationStorage {
+    pub(crate) fn new(
+        connection: SharedConnection,
+        tenant_id: Vec<u8>,
+        auto_maintenance: Option<Arc<AutoMaintenance>>,
+        clock: Arc<dyn Clock>,
+    ) -> SqLiteApplicationStorage {
         SqLiteApplicationStorage {
-            connection: Arc::new(Mutex::new(connection)),
+            connection,
+            tenant_id,
+            auto_maintenance,
+            clock,
         }
     }
 
@@ -32,86 +47,273 @@ impl SqLiteApplicationStorage {
     /// If a value already exists for `key` it will be overwritten.
     /// Returns the number of rows modified (0 if the key-value pair already exists).
     pub fn insert(&self, key: &str, value: &[u8]) -> Result<usize, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
+        self.upsert(key, value, None)
+    }
 
-        // Use a query that only updates if the value is different
-        connection
-            .execute(INSERT_SQL, params![key, value])
-            .map_err(sql_engine_error)
+    /// Insert `value` into storage indexed by `key` until `expiration`.
+    ///
+    /// Once expired according to the [`Clock`](crate::Clock) of the engine,
+    /// the value is no longer returned by any read and is deleted by
+    /// [`SqLiteApplicationStorage::purge_expired`]. Inserting a value for
+    /// `key` later with [`SqLiteApplicationStorage::insert`] removes the
+    /// expiration.
+    pub fn insert_with_expiration(
+        &self,
+        key: &str,
+        value: &[u8],
+        expiration: MlsTime,
+    ) -> Result<usize, SqLiteDataStorageError> {
+        self.upsert(key, value, Some(expiration.seconds_since_epoch()))
+    }
+
+    fn upsert(
+        &self,
+        key: &str,
+        value: &[u8],
+        expiration: Option<u64>,
+    ) -> Result<usize, SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            // Use a query that only updates if the value or expiration is different
+            connection
+                .prepare_cached(INSERT_SQL)
+                .and_then(|mut stmt| stmt.execute(params![self.tenant_id, key, value, expiration]))
+                .map_err(sql_engine_error)
+        })
     }
 
     /// Execute multiple [`SqLiteApplicationStorage::insert`] operations in a transaction.
     /// Returns the total number of rows modified.
     pub fn transact_insert(&self, items: &[Item]) -> Result<usize, SqLiteDataStorageError> {
-        let mut connection = self.connection.lock().unwrap();
+        self.connection.with(|connection| {
+            // Upsert into the database
+            let tx = connection.transaction().map_err(sql_engine_error)?;
 
-        // Upsert into the database
-        let tx = connection.transaction().map_err(sql_engine_error)?;
+            // The statement is prepared once for all items.
+            let total_modified = {
+                let mut stmt = tx.prepare_cached(INSERT_SQL).map_err(sql_engine_error)?;
 
-        let total_modified = items.iter().try_fold(0, |acc, item| {
-            tx.execute(INSERT_SQL, params![item.key, item.value])
-                .map_err(sql_engine_error)
-                .map(|rows| acc + rows)
-        })?;
+                items.iter().try_fold(0, |acc, item| {
+                    stmt.execute(params![self.tenant_id, item.key, item.value, None::<u64>])
+                        .map_err(sql_engine_error)
+                        .map(|rows| acc + rows)
+                })?
+            };
 
-        tx.commit().map_err(sql_engine_error)?;
+            tx.commit().map_err(sql_engine_error)?;
 
-        Ok(total_modified)
+            Ok(total_modified)
+        })
     }
 
     /// Get a value from storage based on its `key`.
     pub fn get(&self, key: &str) -> Result<Option<Vec<u8>>, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .query_row("SELECT value FROM kvs WHERE key = ?", params![key], |row| {
-                row.get(0)
-            })
-            .optional()
-            .map_err(sql_engine_error)
+        self.connection.with(|connection| {
+            connection
+                .prepare_cached(
+                    "SELECT value FROM kvs WHERE tenant_id = ? AND key = ? AND (expiration IS NULL OR expiration >= ?)",
+                )
+                .and_then(|mut stmt| {
+                    stmt.query_row(params![self.tenant_id, key, self.now()], |row| row.get(0))
+                })
+                .optional()
+                .map_err(sql_engine_error)
+        })
     }
 
     /// Delete a value from storage based on its `key`.
     /// Returns the number of rows modified (0 if the key-value pair didnt exist).
     pub fn delete(&self, key: &str) -> Result<usize, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .execute("DELETE FROM kvs WHERE key = ?", params![key])
-            .map_err(sql_engine_error)
+        self.connection.with(|connection| {
+            let deleted = connection
+                .execute(
+                    "DELETE FROM kvs WHERE tenant_id = ? AND key = ?",
+                    params![self.tenant_id, key],
+                )
+                .map_err(sql_engine_error)?;
+
+            self.record_deletes(connection, deleted)?;
+
+            Ok(deleted)
+        })
     }
 
     /// Get all keys and values from storage for which key starts with `key_prefix`.
     pub fn get_by_prefix(&self, key_prefix: &str) -> Result<Vec<Item>, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-        let mut key_prefix = sanitize(key_prefix);
-        key_prefix.push('%');
+        self.connection.with(|connection| {
+            let mut key_prefix = sanitize(key_prefix);
+            key_prefix.push('%');
+
+            let mut stmt = connection
+                .prepare_cached(
+                    "SELECT key, value FROM kvs WHERE tenant_id = ? AND key LIKE ? ESCAPE '$' AND (expiration IS NULL OR expiration >= ?)",
+                )
+                .map_err(sql_engine_error)?;
+
+            let rows = stmt
+                .query(params![self.tenant_id, key_prefix, self.now()])
+                .map_err(sql_engine_error)?
+                .mapped(|row| Ok(Item::new(row.get(0)?, row.get(1)?)));
+
+            rows.collect::<Result<_, _>>().map_err(sql_engine_error)
+        })
+    }
 
-        let mut stmt = connection
-            .prepare("SELECT key, value FROM kvs WHERE key LIKE ? ESCAPE '$'")
-            .map_err(sql_engine_error)?;
+    /// Get at most `limit` keys and values for which key starts with
+    /// `key_prefix`, in ascending order of keys.
+    ///
+    /// The first page is returned for an `after` of `None`. Following pages
+    /// are returned by passing the [`ItemPage::next`] cursor of the previous
+    /// page. Unlike an offset, the cursor remains valid when items are
+    /// inserted or deleted between calls.
+    pub fn get_by_prefix_paged(
+        &self,
+        key_prefix: &str,
+        limit: usize,
+        after: Option<&str>,
+    ) -> Result<ItemPage, SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            let mut key_prefix = sanitize(key_prefix);
+            key_prefix.push('%');
+
+            let mut stmt = connection
+                .prepare_cached(
+                    "SELECT key, value FROM kvs WHERE tenant_id = ?1 AND key LIKE ?2 ESCAPE '$' AND (?3 IS NULL OR key > ?3) AND (expiration IS NULL OR expiration >= ?5) ORDER BY key LIMIT ?4",
+                )
+                .map_err(sql_engine_error)?;
+
+            let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+
+            let items = stmt
+                .query(params![self.tenant_id, key_prefix, after, limit, self.now()])
+                .map_err(sql_engine_error)?
+                .mapped(|row| Ok(Item::new(row.get(0)?, row.get(1)?)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(sql_engine_error)?;
+
+            // A partial page is the last one
+            let next = (items.len() as i64 == limit)
+                .then(|| items.last().map(|item| item.key.clone()))
+                .flatten();
+
+            Ok(ItemPage { items, next })
+        })
+    }
 
-        let rows = stmt
-            .query(params![key_prefix])
-            .map_err(sql_engine_error)?
-            .mapped(|row| Ok(Item::new(row.get(0)?, row.get(1)?)));
+    /// Call `f` with each key and value for which key starts with
+    /// `key_prefix`, in ascending order of keys, until `f` returns
+    /// [`ControlFlow::Break`]. Returns the number of items passed to `f`.
+    ///
+    /// Rows are read one at a time instead of being collected first. The
+    /// storage and its clones can't be used by `f` or by other threads until
+    /// iteration is done.
+    pub fn for_each_by_prefix<F>(
+        &self,
+        key_prefix: &str,
+        mut f: F,
+    ) -> Result<usize, SqLiteDataStorageError>
+    where
+        F: FnMut(Item) -> ControlFlow<()>,
+    {
+        self.connection.with(|connection| {
+            let mut key_prefix = sanitize(key_prefix);
+            key_prefix.push('%');
+
+            let mut stmt = connection
+                .prepare_cached(
+                    "SELECT key, value FROM kvs WHERE tenant_id = ? AND key LIKE ? ESCAPE '$' AND (expiration IS NULL OR expiration >= ?) ORDER BY key",
+                )
+                .map_err(sql_engine_error)?;
+
+            let mut rows = stmt
+                .query(params![self.tenant_id, key_prefix, self.now()])
+                .map_err(sql_engine_error)?;
+
+            let mut visited = 0;
+
+            while let Some(row) = rows.next().map_err(sql_engine_error)? {
+                let item = Item::new(
+                    row.get(0).map_err(data_conversion_error)?,
+                    row.get(1).map_err(data_conversion_error)?,
+                );
+
+                visited += 1;
+
+                if f(item).is_break() {
+                    break;
+                }
+            }
+
+            Ok(visited)
+        })
+    }
 
-        rows.collect::<Result<_, _>>().map_err(sql_engine_error)
+    /// Number of keys that start with `key_prefix`.
+    pub fn count_by_prefix(&self, key_prefix: &str) -> Result<usize, SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            let mut key_prefix = sanitize(key_prefix);
+            key_prefix.push('%');
+
+            connection
+                .prepare_cached(
+                    "SELECT COUNT(*) FROM kvs WHERE tenant_id = ? AND key LIKE ? ESCAPE '$' AND (expiration IS NULL OR expiration >= ?)",
+                )
+                .and_then(|mut stmt| {
+                    stmt.query_row(params![self.tenant_id, key_prefix, self.now()], |row| {
+                        row.get(0)
+                    })
+                })
+                .map_err(sql_engine_error)
+        })
     }
 
     /// Delete all values from storage for which key starts with `key_prefix`.
     /// Returns the total number of rows modified.
     pub fn delete_by_prefix(&self, key_prefix: &str) -> Result<usize, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-        let mut key_prefix = sanitize(key_prefix);
-        key_prefix.push('%');
-
-        connection
-            .execute(
-                "DELETE FROM kvs WHERE key LIKE ? ESCAPE '$'",
-                params![key_prefix],
-            )
-            .map_err(sql_engine_error)
+        self.connection.with(|connection| {
+            let mut key_prefix = sanitize(key_prefix);
+            key_prefix.push('%');
+
+            let deleted = connection
+                .prepare_cached("DELETE FROM kvs WHERE tenant_id = ? AND key LIKE ? ESCAPE '$'")
+                .and_then(|mut stmt| stmt.execute(params![self.tenant_id, key_prefix]))
+                .map_err(sql_engine_error)?;
+
+            self.record_deletes(connection, deleted)?;
+
+            Ok(deleted)
+        })
+    }
+
+    /// Delete all values that are expired based on the current time of the
+    /// [`Clock`](crate::Clock) of the engine. Returns the number of values
+    /// deleted.
+    pub fn purge_expired(&self) -> Result<usize, SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            let deleted = connection
+                .execute(
+                    "DELETE FROM kvs WHERE tenant_id = ? AND expiration < ?",
+                    params![self.tenant_id, self.now()],
+                )
+                .map_err(sql_engine_error)?;
+
+            self.record_deletes(connection, deleted)?;
+
+            Ok(deleted)
+        })
+    }
+
+    fn now(&self) -> u64 {
+        self.clock.now().seconds_since_epoch()
+    }
+
+    fn record_deletes(
+        &self,
+        connection: &Connection,
+        deleted: usize,
+    ) -> Result<(), SqLiteDataStorageError> {
+        self.auto_maintenance
+            .as_ref()
+            .map_or(Ok(()), |auto| auto.record_deletes(connection, deleted))
     }
 }
 
@@ -123,6 +325,10 @@ fn sql_engine_error(e: rusqlite::Error) -> SqLiteDataStorageError {
     SqLiteDataStorageError::SqlEngineError(e.into())
 }
 
+fn data_conversion_error(e: rusqlite::Error) -> SqLiteDataStorageError {
+    SqLiteDataStorageError::DataConversionError(e.into())
+}
+
 #[derive(Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Item {
     pub key: String,
@@ -152,6 +358,14 @@ impl Item {
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// Page of items returned by [`SqLiteApplicationStorage::get_by_prefix_paged`].
+pub struct ItemPage {
+    pub items: Vec<Item>,
+    /// Cursor of the next page, `None` if this is the last page.
+    pub next: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -159,6 +373,12 @@ mod tests {
         SqLiteDataStorageEngine,
     };
 
+    use std::ops::ControlFlow;
+
+    use mls_rs_core::time::MlsTime;
+
+    use crate::test_utils::FakeClock;
+
     use super::SqLiteApplicationStorage;
 
     fn test_kv() -> (String, Vec<u8>) {
@@ -302,6 +522,97 @@ mod tests {
         }
     }
 
+    #[test]
+    fn items_are_paged_and_streamed() {
+        let storage = test_storage();
+
+        let items = (0..25)
+            .map(|i| Item::new(format!("page_{i:02}"), gen_rand_bytes(5)))
+            .collect::<Vec<_>>();
+
+        storage.transact_insert(&items).unwrap();
+        storage.insert("other", &gen_rand_bytes(5)).unwrap();
+
+        assert_eq!(storage.count_by_prefix("page_").unwrap(), 25);
+
+        let mut paged = Vec::new();
+        let mut after = None;
+
+        loop {
+            let page = storage
+                .get_by_prefix_paged("page_", 10, after.as_deref())
+                .unwrap();
+
+            assert!(page.items.len() <= 10);
+            paged.extend(page.items);
+
+            match page.next {
+                Some(next) => after = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(paged, items);
+
+        let mut streamed = Vec::new();
+
+        let visited = storage
+            .for_each_by_prefix("page_", |item| {
+                streamed.push(item);
+
+                if streamed.len() == 5 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .unwrap();
+
+        assert_eq!(visited, 5);
+        assert_eq!(streamed, items[..5]);
+    }
+
+    #[test]
+    fn expired_items_are_hidden_and_purged() {
+        let clock = FakeClock::new(MlsTime::from(1_000));
+
+        let storage = SqLiteDataStorageEngine::new(MemoryStrategy)
+            .unwrap()
+            .with_clock(clock.clone())
+            .application_data_storage()
+            .unwrap();
+
+        storage
+            .insert_with_expiration("cache_a", b"a", MlsTime::from(2_000))
+            .unwrap();
+
+        storage.insert("cache_b", b"b").unwrap();
+
+        assert_eq!(storage.get("cache_a").unwrap(), Some(b"a".to_vec()));
+
+        clock.set(MlsTime::from(3_000));
+
+        assert_eq!(storage.get("cache_a").unwrap(), None);
+        assert_eq!(storage.count_by_prefix("cache_").unwrap(), 1);
+
+        assert_eq!(
+            storage.get_by_prefix("cache_").unwrap(),
+            vec![Item::new("cache_b".to_string(), b"b".to_vec())]
+        );
+
+        assert_eq!(storage.purge_expired().unwrap(), 1);
+        assert_eq!(storage.purge_expired().unwrap(), 0);
+
+        // Inserting without an expiration removes the expiration
+        storage
+            .insert_with_expiration("cache_b", b"b", MlsTime::from(2_000))
+            .unwrap();
+
+        assert_eq!(storage.get("cache_b").unwrap(), None);
+        assert_eq!(storage.insert("cache_b", b"b").unwrap(), 1);
+        assert_eq!(storage.get("cache_b").unwrap(), Some(b"b".to_vec()));
+    }
+
     fn test_item() -> Item {
         Item::new(hex::encode(gen_rand_bytes(5)), gen_rand_bytes(5))
     }
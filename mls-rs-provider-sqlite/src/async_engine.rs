@@ -0,0 +1,502 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::sync::Arc;
+
+use mls_rs_core::{
+    group::{EpochRecord, GroupState, GroupStateStorage},
+    key_package::{KeyPackageData, KeyPackageStorage},
+    psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+    time::MlsTime,
+};
+
+use crate::{
+    connection_strategy::ConnectionStrategy,
+    storage::{
+        Item, ItemPage, SqLiteApplicationStorage, SqLiteGroupStateStorage, SqLiteKeyPackageStorage,
+        SqLitePreSharedKeyStorage,
+    },
+    MaintenanceReport, SchemaVersion, SqLiteDataStorageEngine, SqLiteDataStorageError,
+};
+
+// Run a blocking storage operation on the blocking thread pool of the tokio
+// runtime, so that it never stalls the executor.
+async fn run_blocking<T, F>(operation: F) -> Result<T, SqLiteDataStorageError>
+where
+    F: FnOnce() -> Result<T, SqLiteDataStorageError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(operation)
+        .await
+        .map_err(|e| SqLiteDataStorageError::BlockingTaskError(e.into()))?
+}
+
+#[derive(Debug)]
+/// SQLite data storage engine for async clients.
+///
+/// Storages created by this engine implement the async storage traits of
+/// `mls-rs-core` by running every SQLite operation on the blocking thread
+/// pool of the current tokio runtime. The blocking storage of each async
+/// storage remains available for use outside of async code.
+pub struct AsyncSqLiteDataStorageEngine<CS>
+where
+    CS: ConnectionStrategy,
+{
+    engine: Arc<SqLiteDataStorageEngine<CS>>,
+}
+
+impl<CS> Clone for AsyncSqLiteDataStorageEngine<CS>
+where
+    CS: ConnectionStrategy,
+{
+    fn clone(&self) -> Self {
+        Self {
+            engine: self.engine.clone(),
+        }
+    }
+}
+
+impl<CS> From<SqLiteDataStorageEngine<CS>> for AsyncSqLiteDataStorageEngine<CS>
+where
+    CS: ConnectionStrategy,
+{
+    fn from(engine: SqLiteDataStorageEngine<CS>) -> Self {
+        Self::new(engine)
+    }
+}
+
+impl<CS> AsyncSqLiteDataStorageEngine<CS>
+where
+    CS: ConnectionStrategy,
+{
+    /// Create storages with the configuration of `engine`.
+    pub fn new(engine: SqLiteDataStorageEngine<CS>) -> Self {
+        Self {
+            engine: Arc::new(engine),
+        }
+    }
+
+    /// Blocking engine used to create storages.
+    pub fn blocking(&self) -> &SqLiteDataStorageEngine<CS> {
+        &self.engine
+    }
+}
+
+impl<CS> AsyncSqLiteDataStorageEngine<CS>
+where
+    CS: ConnectionStrategy + Send + Sync + 'static,
+{
+    /// See [`SqLiteDataStorageEngine::delete_tenant`].
+    pub async fn delete_tenant(&self, tenant_id: &[u8]) -> Result<usize, SqLiteDataStorageError> {
+        let engine = self.engine.clone();
+        let tenant_id = tenant_id.to_vec();
+        run_blocking(move || engine.delete_tenant(&tenant_id)).await
+    }
+
+    /// See [`SqLiteDataStorageEngine::maintenance`].
+    pub async fn maintenance(&self) -> Result<MaintenanceReport, SqLiteDataStorageError> {
+        let engine = self.engine.clone();
+        run_blocking(move || engine.maintenance()).await
+    }
+
+    /// See [`SqLiteDataStorageEngine::prune_epochs_older_than`].
+    pub async fn prune_epochs_older_than(
+        &self,
+        time: MlsTime,
+    ) -> Result<usize, SqLiteDataStorageError> {
+        let engine = self.engine.clone();
+        run_blocking(move || engine.prune_epochs_older_than(time)).await
+    }
+
+    /// See [`SqLiteDataStorageEngine::vacuum`].
+    pub async fn vacuum(&self) -> Result<MaintenanceReport, SqLiteDataStorageError> {
+        let engine = self.engine.clone();
+        run_blocking(move || engine.vacuum()).await
+    }
+
+    /// See [`SqLiteDataStorageEngine::schema_version`].
+    pub async fn schema_version(&self) -> Result<SchemaVersion, SqLiteDataStorageError> {
+        let engine = self.engine.clone();
+        run_blocking(move || engine.schema_version()).await
+    }
+
+    /// Returns a struct that implements the async `GroupStateStorage` trait for use in MLS.
+    pub async fn group_state_storage(
+        &self,
+    ) -> Result<AsyncSqLiteGroupStateStorage, SqLiteDataStorageError> {
+        let engine = self.engine.clone();
+
+        run_blocking(move || engine.group_state_storage())
+            .await
+            .map(AsyncSqLiteGroupStateStorage::new)
+    }
+
+    /// Returns a struct that implements the async `KeyPackageStorage` trait for use in MLS.
+    pub async fn key_package_storage(
+        &self,
+    ) -> Result<AsyncSqLiteKeyPackageStorage, SqLiteDataStorageError> {
+        let engine = self.engine.clone();
+
+        run_blocking(move || engine.key_package_storage())
+            .await
+            .map(AsyncSqLiteKeyPackageStorage::new)
+    }
+
+    /// Returns a struct that implements the async `PreSharedKeyStorage` trait for use in MLS.
+    pub async fn pre_shared_key_storage(
+        &self,
+    ) -> Result<AsyncSqLitePreSharedKeyStorage, SqLiteDataStorageError> {
+        let engine = self.engine.clone();
+
+        run_blocking(move || engine.pre_shared_key_storage())
+            .await
+            .map(AsyncSqLitePreSharedKeyStorage::new)
+    }
+
+    /// Returns an async key value store that can be used to store application specific data.
+    pub async fn application_data_storage(
+        &self,
+    ) -> Result<AsyncSqLiteApplicationStorage, SqLiteDataStorageError> {
+        let engine = self.engine.clone();
+
+        run_blocking(move || engine.application_data_storage())
+            .await
+            .map(AsyncSqLiteApplicationStorage::new)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Async SQLite storage for MLS group states.
+pub struct AsyncSqLiteGroupStateStorage {
+    inner: SqLiteGroupStateStorage,
+}
+
+impl AsyncSqLiteGroupStateStorage {
+    pub fn new(inner: SqLiteGroupStateStorage) -> Self {
+        Self { inner }
+    }
+
+    pub fn blocking(&self) -> &SqLiteGroupStateStorage {
+        &self.inner
+    }
+
+    /// See [`SqLiteGroupStateStorage::group_ids`].
+    pub async fn group_ids(&self) -> Result<Vec<Vec<u8>>, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        run_blocking(move || inner.group_ids()).await
+    }
+
+    /// See [`SqLiteGroupStateStorage::delete_group`].
+    pub async fn delete_group(&self, group_id: &[u8]) -> Result<(), SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let group_id = group_id.to_vec();
+        run_blocking(move || inner.delete_group(&group_id)).await
+    }
+}
+
+#[maybe_async::must_be_async]
+impl GroupStateStorage for AsyncSqLiteGroupStateStorage {
+    type Error = SqLiteDataStorageError;
+
+    async fn write(
+        &mut self,
+        state: GroupState,
+        inserts: Vec<EpochRecord>,
+        updates: Vec<EpochRecord>,
+    ) -> Result<(), Self::Error> {
+        let inner = self.inner.clone();
+
+        run_blocking(move || inner.update_group_state(&state.id, state.data, inserts, updates))
+            .await
+    }
+
+    async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let inner = self.inner.clone();
+        let group_id = group_id.to_vec();
+        run_blocking(move || inner.get_snapshot_data(&group_id)).await
+    }
+
+    async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        let inner = self.inner.clone();
+        let group_id = group_id.to_vec();
+        run_blocking(move || inner.max_epoch_id(&group_id)).await
+    }
+
+    async fn epoch(&self, group_id: &[u8], epoch_id: u64) -> Result<Option<Vec<u8>>, Self::Error> {
+        let inner = self.inner.clone();
+        let group_id = group_id.to_vec();
+        run_blocking(move || inner.get_epoch_data(&group_id, epoch_id)).await
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Async SQLite storage for MLS Key Packages.
+pub struct AsyncSqLiteKeyPackageStorage {
+    inner: SqLiteKeyPackageStorage,
+}
+
+impl AsyncSqLiteKeyPackageStorage {
+    pub fn new(inner: SqLiteKeyPackageStorage) -> Self {
+        Self { inner }
+    }
+
+    pub fn blocking(&self) -> &SqLiteKeyPackageStorage {
+        &self.inner
+    }
+
+    /// See [`SqLiteKeyPackageStorage::delete_expired`].
+    pub async fn delete_expired(&self) -> Result<(), SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        run_blocking(move || inner.delete_expired()).await
+    }
+
+    /// See [`SqLiteKeyPackageStorage::count`].
+    pub async fn count(&self) -> Result<usize, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        run_blocking(move || inner.count()).await
+    }
+}
+
+#[maybe_async::must_be_async]
+impl KeyPackageStorage for AsyncSqLiteKeyPackageStorage {
+    type Error = SqLiteDataStorageError;
+
+    async fn insert(&mut self, id: Vec<u8>, pkg: KeyPackageData) -> Result<(), Self::Error> {
+        let mut inner = self.inner.clone();
+        run_blocking(move || inner.insert(&id, pkg)).await
+    }
+
+    async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error> {
+        let inner = self.inner.clone();
+        let id = id.to_vec();
+        run_blocking(move || inner.get(&id)).await
+    }
+
+    async fn delete(&mut self, id: &[u8]) -> Result<(), Self::Error> {
+        let inner = self.inner.clone();
+        let id = id.to_vec();
+        run_blocking(move || inner.delete(&id)).await
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Async SQLite storage for MLS pre-shared keys.
+pub struct AsyncSqLitePreSharedKeyStorage {
+    inner: SqLitePreSharedKeyStorage,
+}
+
+impl AsyncSqLitePreSharedKeyStorage {
+    pub fn new(inner: SqLitePreSharedKeyStorage) -> Self {
+        Self { inner }
+    }
+
+    pub fn blocking(&self) -> &SqLitePreSharedKeyStorage {
+        &self.inner
+    }
+
+    /// Insert a pre-shared key into storage.
+    pub async fn insert(
+        &self,
+        psk_id: &[u8],
+        psk: &PreSharedKey,
+    ) -> Result<(), SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let (psk_id, psk) = (psk_id.to_vec(), psk.clone());
+        run_blocking(move || inner.insert(&psk_id, &psk)).await
+    }
+
+    /// Delete a pre-shared key from storage based on a unique id.
+    pub async fn delete(&self, psk_id: &[u8]) -> Result<(), SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let psk_id = psk_id.to_vec();
+        run_blocking(move || inner.delete(&psk_id)).await
+    }
+}
+
+#[maybe_async::must_be_async]
+impl PreSharedKeyStorage for AsyncSqLitePreSharedKeyStorage {
+    type Error = SqLiteDataStorageError;
+
+    async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
+        let inner = self.inner.clone();
+        let id = id.to_vec();
+        run_blocking(move || inner.get(&id)).await
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Async SQLite key value storage for application specific data.
+pub struct AsyncSqLiteApplicationStorage {
+    inner: SqLiteApplicationStorage,
+}
+
+impl AsyncSqLiteApplicationStorage {
+    pub fn new(inner: SqLiteApplicationStorage) -> Self {
+        Self { inner }
+    }
+
+    pub fn blocking(&self) -> &SqLiteApplicationStorage {
+        &self.inner
+    }
+
+    /// See [`SqLiteApplicationStorage::insert`].
+    pub async fn insert(&self, key: &str, value: &[u8]) -> Result<usize, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let (key, value) = (key.to_string(), value.to_vec());
+        run_blocking(move || inner.insert(&key, &value)).await
+    }
+
+    /// See [`SqLiteApplicationStorage::transact_insert`].
+    pub async fn transact_insert(&self, items: Vec<Item>) -> Result<usize, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        run_blocking(move || inner.transact_insert(&items)).await
+    }
+
+    /// See [`SqLiteApplicationStorage::get`].
+    pub async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let key = key.to_string();
+        run_blocking(move || inner.get(&key)).await
+    }
+
+    /// See [`SqLiteApplicationStorage::delete`].
+    pub async fn delete(&self, key: &str) -> Result<usize, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let key = key.to_string();
+        run_blocking(move || inner.delete(&key)).await
+    }
+
+    /// See [`SqLiteApplicationStorage::get_by_prefix`].
+    pub async fn get_by_prefix(
+        &self,
+        key_prefix: &str,
+    ) -> Result<Vec<Item>, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let key_prefix = key_prefix.to_string();
+        run_blocking(move || inner.get_by_prefix(&key_prefix)).await
+    }
+
+    /// See [`SqLiteApplicationStorage::insert_with_expiration`].
+    pub async fn insert_with_expiration(
+        &self,
+        key: &str,
+        value: &[u8],
+        expiration: MlsTime,
+    ) -> Result<usize, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let (key, value) = (key.to_string(), value.to_vec());
+        run_blocking(move || inner.insert_with_expiration(&key, &value, expiration)).await
+    }
+
+    /// See [`SqLiteApplicationStorage::purge_expired`].
+    pub async fn purge_expired(&self) -> Result<usize, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        run_blocking(move || inner.purge_expired()).await
+    }
+
+    /// See [`SqLiteApplicationStorage::get_by_prefix_paged`].
+    pub async fn get_by_prefix_paged(
+        &self,
+        key_prefix: &str,
+        limit: usize,
+        after: Option<&str>,
+    ) -> Result<ItemPage, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let key_prefix = key_prefix.to_string();
+        let after = after.map(str::to_string);
+
+        run_blocking(move || inner.get_by_prefix_paged(&key_prefix, limit, after.as_deref())).await
+    }
+
+    /// See [`SqLiteApplicationStorage::count_by_prefix`].
+    pub async fn count_by_prefix(&self, key_prefix: &str) -> Result<usize, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let key_prefix = key_prefix.to_string();
+        run_blocking(move || inner.count_by_prefix(&key_prefix)).await
+    }
+
+    /// See [`SqLiteApplicationStorage::delete_by_prefix`].
+    pub async fn delete_by_prefix(
+        &self,
+        key_prefix: &str,
+    ) -> Result<usize, SqLiteDataStorageError> {
+        let inner = self.inner.clone();
+        let key_prefix = key_prefix.to_string();
+        run_blocking(move || inner.delete_by_prefix(&key_prefix)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mls_rs_core::{
+        group::{EpochRecord, GroupState, GroupStateStorage},
+        psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+    };
+
+    use crate::{
+        connection_strategy::MemoryStrategy, test_utils::gen_rand_bytes, SqLiteDataStorageEngine,
+    };
+
+    use super::AsyncSqLiteDataStorageEngine;
+
+    fn test_engine() -> AsyncSqLiteDataStorageEngine<MemoryStrategy> {
+        SqLiteDataStorageEngine::new(MemoryStrategy).unwrap().into()
+    }
+
+    #[tokio::test]
+    async fn group_state_round_trip() {
+        let mut storage = test_engine().group_state_storage().await.unwrap();
+        let group_id = gen_rand_bytes(32);
+        let snapshot = gen_rand_bytes(64);
+        let epoch = EpochRecord::new(0, gen_rand_bytes(32));
+
+        storage
+            .write(
+                GroupState {
+                    id: group_id.clone(),
+                    data: snapshot.clone(),
+                },
+                vec![epoch.clone()],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(storage.state(&group_id).await.unwrap(), Some(snapshot));
+        assert_eq!(storage.max_epoch_id(&group_id).await.unwrap(), Some(0));
+        assert_eq!(storage.epoch(&group_id, 0).await.unwrap(), Some(epoch.data));
+        assert_eq!(storage.group_ids().await.unwrap(), vec![group_id.clone()]);
+
+        storage.delete_group(&group_id).await.unwrap();
+        assert_eq!(storage.state(&group_id).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn psk_and_application_data_round_trip() {
+        let engine = test_engine();
+
+        let psks = engine.pre_shared_key_storage().await.unwrap();
+        let psk_id = gen_rand_bytes(32);
+        let psk = PreSharedKey::new(gen_rand_bytes(64));
+
+        psks.insert(&psk_id, &psk).await.unwrap();
+
+        let found = psks.get(&ExternalPskId::new(psk_id)).await.unwrap();
+        assert_eq!(found, Some(psk));
+
+        let application = engine.application_data_storage().await.unwrap();
+        application.insert("key", b"value").await.unwrap();
+
+        assert_eq!(
+            application.get("key").await.unwrap(),
+            Some(b"value".to_vec())
+        );
+
+        // The blocking storage shares the connection of the async storage
+        assert_eq!(
+            application.blocking().get("key").unwrap(),
+            Some(b"value".to_vec())
+        );
+    }
+}
@@ -5,6 +5,7 @@
 use crate::connection_strategy::ConnectionStrategy;
 use crate::SqLiteDataStorageError;
 use rusqlite::Connection;
+use std::path::Path;
 
 use hex::ToHex;
 use zeroize::{ZeroizeOnDrop, Zeroizing};
@@ -118,6 +119,14 @@ where
 
         Ok(connection)
     }
+
+    fn is_read_only(&self) -> bool {
+        self.inner.is_read_only()
+    }
+
+    fn database_path(&self) -> Option<&Path> {
+        self.inner.database_path()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,22 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::fmt::Debug;
+
+use mls_rs_core::time::MlsTime;
+
+/// Source of the current time used to expire stored data.
+pub trait Clock: Send + Sync + Debug {
+    fn now(&self) -> MlsTime;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+/// [`Clock`] reading the system time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> MlsTime {
+        MlsTime::now()
+    }
+}
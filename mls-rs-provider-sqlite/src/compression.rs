@@ -0,0 +1,132 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::{fmt::Debug, sync::Arc};
+
+use mls_rs_core::{compression::Compressor, error::IntoAnyError};
+
+use crate::SqLiteDataStorageError;
+
+// Snapshots and epochs written by mls-rs start with a small version number,
+// so they never start with this prefix.
+const COMPRESSED_PREFIX: [u8; 4] = [0xff, b'M', b'L', b'Z'];
+const HEADER_LEN: usize = COMPRESSED_PREFIX.len() + 2;
+
+trait DynCompressor: Send + Sync {
+    fn algorithm_id(&self) -> u16;
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, SqLiteDataStorageError>;
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, SqLiteDataStorageError>;
+}
+
+impl<C: Compressor> DynCompressor for C {
+    fn algorithm_id(&self) -> u16 {
+        Compressor::algorithm_id(self)
+    }
+
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, SqLiteDataStorageError> {
+        Compressor::compress(self, data)
+            .map_err(|e| SqLiteDataStorageError::CompressionError(e.into_any_error().into()))
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, SqLiteDataStorageError> {
+        Compressor::decompress(self, data)
+            .map_err(|e| SqLiteDataStorageError::CompressionError(e.into_any_error().into()))
+    }
+}
+
+#[derive(Clone)]
+/// Compression of stored group state, applied transparently on write and
+/// removed on read.
+pub(crate) struct StateCompression(Arc<dyn DynCompressor>);
+
+impl Debug for StateCompression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateCompression")
+            .field("algorithm_id", &self.0.algorithm_id())
+            .finish()
+    }
+}
+
+impl StateCompression {
+    pub(crate) fn new<C: Compressor + 'static>(compressor: C) -> Self {
+        Self(Arc::new(compressor))
+    }
+
+    pub(crate) fn compress(&self, data: &[u8]) -> Result<Vec<u8>, SqLiteDataStorageError> {
+        let compressed = self.0.compress(data)?;
+
+        let mut out = Vec::with_capacity(HEADER_LEN + compressed.len());
+        out.extend_from_slice(&COMPRESSED_PREFIX);
+        out.extend_from_slice(&self.0.algorithm_id().to_be_bytes());
+        out.extend_from_slice(&compressed);
+
+        Ok(out)
+    }
+}
+
+/// Decompress `data` if it was written compressed. Uncompressed data is
+/// returned as is, so that compression can be enabled for existing databases.
+pub(crate) fn decompress(
+    compression: Option<&StateCompression>,
+    data: Vec<u8>,
+) -> Result<Vec<u8>, SqLiteDataStorageError> {
+    if data.len() < HEADER_LEN || data[..COMPRESSED_PREFIX.len()] != COMPRESSED_PREFIX {
+        return Ok(data);
+    }
+
+    let algorithm_id = u16::from_be_bytes([data[4], data[5]]);
+
+    match compression {
+        Some(compression) if compression.0.algorithm_id() == algorithm_id => {
+            compression.0.decompress(&data[HEADER_LEN..])
+        }
+        _ => Err(SqLiteDataStorageError::CompressionAlgorithmMismatch(
+            algorithm_id,
+            compression.map(|c| c.0.algorithm_id()),
+        )),
+    }
+}
+
+#[cfg(feature = "zstd")]
+#[derive(Clone, Copy, Debug)]
+/// [`Compressor`] using zstd.
+pub struct ZstdCompressor {
+    level: i32,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdCompressor {
+    pub const ALGORITHM_ID: u16 = 1;
+
+    /// Compress with the given zstd level. Level `0` selects the zstd default.
+    pub fn new(level: i32) -> Self {
+        Self { level }
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Default for ZstdCompressor {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Compressor for ZstdCompressor {
+    type Error = SqLiteDataStorageError;
+
+    fn algorithm_id(&self) -> u16 {
+        Self::ALGORITHM_ID
+    }
+
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        zstd::bulk::compress(data, self.level)
+            .map_err(|e| SqLiteDataStorageError::CompressionError(e.into()))
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        zstd::stream::decode_all(data)
+            .map_err(|e| SqLiteDataStorageError::CompressionError(e.into()))
+    }
+}
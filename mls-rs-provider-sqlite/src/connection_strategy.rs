@@ -2,9 +2,12 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use rusqlite::Connection;
+use rusqlite::{Connection, OpenFlags};
 
 use crate::SqLiteDataStorageError;
 
@@ -15,6 +18,18 @@ pub use crate::cipher::*;
 pub trait ConnectionStrategy {
     /// Connect to the SQLite database.
     fn make_connection(&self) -> Result<Connection, SqLiteDataStorageError>;
+
+    /// Whether connections are opened read-only. The storage engine never
+    /// creates or upgrades the schema of a read-only database.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// Path of the database file, used to restore the database from a backup
+    /// with [`RecoveryAction::RestoreFromBackup`](crate::RecoveryAction::RestoreFromBackup).
+    fn database_path(&self) -> Option<&Path> {
+        None
+    }
 }
 
 /// Connection strategy that creates an in-memory database.
@@ -26,22 +41,114 @@ impl ConnectionStrategy for MemoryStrategy {
     }
 }
 
+/// Function registering a custom SQLite VFS, see [`FileConnectionStrategy::with_vfs`].
+pub type VfsRegistration = Arc<dyn Fn() -> Result<(), SqLiteDataStorageError> + Send + Sync>;
+
 /// Connection strategy that connects to a database based on a file path.
+#[derive(Clone)]
 pub struct FileConnectionStrategy {
     db_path: PathBuf,
+    open_flags: OpenFlags,
+    vfs: Option<String>,
+    register_vfs: Option<VfsRegistration>,
 }
 
 impl FileConnectionStrategy {
     pub fn new(db_path: &Path) -> FileConnectionStrategy {
         FileConnectionStrategy {
             db_path: db_path.to_owned(),
+            open_flags: OpenFlags::default(),
+            vfs: None,
+            register_vfs: None,
+        }
+    }
+
+    /// Flags used to open the database, [`OpenFlags::default`] by default.
+    ///
+    /// Connections opened with [`OpenFlags::SQLITE_OPEN_READ_ONLY`] are
+    /// treated as read-only by the storage engine.
+    pub fn with_open_flags(self, open_flags: OpenFlags) -> Self {
+        Self { open_flags, ..self }
+    }
+
+    /// Open the database with the SQLite VFS registered under `name`, e.g. a
+    /// VFS implementing encryption or storage in a platform specific location.
+    ///
+    /// `register` is called before every connection is made and must
+    /// therefore be idempotent. It can be omitted if the VFS is built into
+    /// SQLite or registered by the application.
+    pub fn with_vfs(self, name: &str, register: Option<VfsRegistration>) -> Self {
+        Self {
+            vfs: Some(name.to_owned()),
+            register_vfs: register,
+            ..self
         }
     }
+
+    pub fn vfs(&self) -> Option<&str> {
+        self.vfs.as_deref()
+    }
 }
 
 impl ConnectionStrategy for FileConnectionStrategy {
     fn make_connection(&self) -> Result<Connection, SqLiteDataStorageError> {
-        Connection::open(&self.db_path)
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        if let Some(register) = &self.register_vfs {
+            register()?;
+        }
+
+        match &self.vfs {
+            Some(vfs) => Connection::open_with_flags_and_vfs(&self.db_path, self.open_flags, vfs),
+            None => Connection::open_with_flags(&self.db_path, self.open_flags),
+        }
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.open_flags.contains(OpenFlags::SQLITE_OPEN_READ_ONLY)
+    }
+
+    fn database_path(&self) -> Option<&Path> {
+        Some(&self.db_path)
+    }
+}
+
+/// Connection strategy that opens an existing database file without ever
+/// writing to it, e.g. to inspect a copy of a database.
+///
+/// Opening fails if the file does not exist or if it does not contain the
+/// schema created by [`SqLiteDataStorageEngine`](crate::SqLiteDataStorageEngine).
+pub struct ReadOnlyFileConnectionStrategy {
+    db_path: PathBuf,
+}
+
+impl ReadOnlyFileConnectionStrategy {
+    pub fn new(db_path: &Path) -> ReadOnlyFileConnectionStrategy {
+        ReadOnlyFileConnectionStrategy {
+            db_path: db_path.to_owned(),
+        }
+    }
+}
+
+impl ConnectionStrategy for ReadOnlyFileConnectionStrategy {
+    fn make_connection(&self) -> Result<Connection, SqLiteDataStorageError> {
+        let connection = Connection::open_with_flags(
+            &self.db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        connection
+            .pragma_update(None, "query_only", true)
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        Ok(connection)
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn database_path(&self) -> Option<&Path> {
+        Some(&self.db_path)
     }
 }
@@ -0,0 +1,211 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{maintenance::AutoMaintenance, recovery::SharedConnection, SqLiteDataStorageError};
+use mls_rs_core::group::GroupIdMappingStorage;
+use rusqlite::{params, OptionalExtension};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+/// SQLite storage mapping external ids to MLS group ids.
+pub struct SqLiteGroupIdMappingStorage {
+    connection: SharedConnection,
+    tenant_id: Vec<u8>,
+    auto_maintenance: Option<Arc<AutoMaintenance>>,
+}
+
+impl SqLiteGroupIdMappingStorage {
+    pub(crate) fn new(
+        connection: SharedConnection,
+        tenant_id: Vec<u8>,
+        auto_maintenance: Option<Arc<AutoMaintenance>>,
+    ) -> SqLiteGroupIdMappingStorage {
+        SqLiteGroupIdMappingStorage {
+            connection,
+            tenant_id,
+            auto_maintenance,
+        }
+    }
+
+    /// Map `external_id` to `group_id`, replacing any previous mapping.
+    pub fn insert(
+        &self,
+        external_id: &[u8],
+        group_id: &[u8],
+    ) -> Result<(), SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            connection
+                .prepare_cached(
+                    "INSERT INTO group_id_map (tenant_id, external_id, group_id) VALUES (?,?,?) ON CONFLICT(tenant_id, external_id) DO UPDATE SET group_id=excluded.group_id",
+                )
+                .and_then(|mut stmt| stmt.execute(params![self.tenant_id, external_id, group_id]))
+                .map(|_| ())
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
+    }
+
+    /// Get the group id currently mapped to `external_id`.
+    pub fn group_id(&self, external_id: &[u8]) -> Result<Option<Vec<u8>>, SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            connection
+                .prepare_cached(
+                    "SELECT group_id FROM group_id_map WHERE tenant_id = ? AND external_id = ?",
+                )
+                .and_then(|mut stmt| {
+                    stmt.query_row(params![self.tenant_id, external_id], |row| row.get(0))
+                })
+                .optional()
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
+    }
+
+    /// Get all external ids mapped to `group_id`.
+    pub fn external_ids(&self, group_id: &[u8]) -> Result<Vec<Vec<u8>>, SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            let mut stmt = connection
+                .prepare_cached(
+                    "SELECT external_id FROM group_id_map WHERE tenant_id = ? AND group_id = ? ORDER BY external_id ASC",
+                )
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            let res = stmt
+                .query_map(params![self.tenant_id, group_id], |row| row.get(0))
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?
+                .try_fold(Vec::new(), |mut ids, id| {
+                    ids.push(id.map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?);
+                    Ok::<_, SqLiteDataStorageError>(ids)
+                })?;
+
+            Ok(res)
+        })
+    }
+
+    /// Map all external ids of `old_group_id` to `new_group_id` in a single
+    /// statement.
+    pub fn replace_group_id(
+        &self,
+        old_group_id: &[u8],
+        new_group_id: &[u8],
+    ) -> Result<(), SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            connection
+                .execute(
+                    "UPDATE group_id_map SET group_id = ? WHERE tenant_id = ? AND group_id = ?",
+                    params![new_group_id, self.tenant_id, old_group_id],
+                )
+                .map(|_| ())
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
+    }
+
+    /// Delete the mapping of `external_id`.
+    pub fn delete(&self, external_id: &[u8]) -> Result<(), SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            let deleted = connection
+                .execute(
+                    "DELETE FROM group_id_map WHERE tenant_id = ? AND external_id = ?",
+                    params![self.tenant_id, external_id],
+                )
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            self.auto_maintenance
+                .as_ref()
+                .map_or(Ok(()), |auto| auto.record_deletes(connection, deleted))
+        })
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl GroupIdMappingStorage for SqLiteGroupIdMappingStorage {
+    type Error = SqLiteDataStorageError;
+
+    async fn group_id(&self, external_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.group_id(external_id)
+    }
+
+    async fn external_ids(&self, group_id: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.external_ids(group_id)
+    }
+
+    async fn insert(&mut self, external_id: &[u8], group_id: &[u8]) -> Result<(), Self::Error> {
+        (*self).insert(external_id, group_id)
+    }
+
+    async fn replace_group_id(
+        &mut self,
+        old_group_id: &[u8],
+        new_group_id: &[u8],
+    ) -> Result<(), Self::Error> {
+        (*self).replace_group_id(old_group_id, new_group_id)
+    }
+
+    async fn delete(&mut self, external_id: &[u8]) -> Result<(), Self::Error> {
+        (*self).delete(external_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        SqLiteDataStorageEngine,
+        {connection_strategy::MemoryStrategy, test_utils::gen_rand_bytes},
+    };
+
+    use super::SqLiteGroupIdMappingStorage;
+
+    fn test_storage() -> SqLiteGroupIdMappingStorage {
+        SqLiteDataStorageEngine::new(MemoryStrategy)
+            .unwrap()
+            .group_id_mapping_storage()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_insert_overwrites() {
+        let storage = test_storage();
+        let external_id = gen_rand_bytes(16);
+
+        storage.insert(&external_id, b"group a").unwrap();
+        storage.insert(&external_id, b"group b").unwrap();
+
+        let group_id = storage.group_id(&external_id).unwrap().unwrap();
+        assert_eq!(group_id, b"group b");
+    }
+
+    #[test]
+    fn test_replace_group_id() {
+        let storage = test_storage();
+
+        storage.insert(b"a", b"old group").unwrap();
+        storage.insert(b"b", b"old group").unwrap();
+        storage.insert(b"c", b"other group").unwrap();
+
+        storage
+            .replace_group_id(b"old group", b"new group")
+            .unwrap();
+
+        assert!(storage.external_ids(b"old group").unwrap().is_empty());
+
+        assert_eq!(
+            storage.external_ids(b"new group").unwrap(),
+            vec![b"a".to_vec(), b"b".to_vec()]
+        );
+
+        assert_eq!(
+            storage.group_id(b"c").unwrap().unwrap(),
+            b"other group".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_delete() {
+        let storage = test_storage();
+
+        storage.insert(b"a", b"group").unwrap();
+        storage.delete(b"a").unwrap();
+
+        assert!(storage.group_id(b"a").unwrap().is_none());
+    }
+}
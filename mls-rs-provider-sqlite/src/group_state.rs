@@ -4,181 +4,308 @@
 
 use mls_rs_core::group::{EpochRecord, GroupState, GroupStateStorage};
 use rusqlite::{params, Connection, OptionalExtension};
-use std::{
-    fmt::Debug,
-    sync::{Arc, Mutex},
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use crate::{
+    clock::Clock,
+    compression::{self, StateCompression},
+    maintenance::AutoMaintenance,
+    recovery::SharedConnection,
+    SqLiteDataStorageError,
 };
 
-use crate::SqLiteDataStorageError;
-
 pub(crate) const DEFAULT_EPOCH_RETENTION_LIMIT: u64 = 3;
 
 #[derive(Debug, Clone)]
 /// SQLite Storage for MLS group states.
 pub struct SqLiteGroupStateStorage {
-    connection: Arc<Mutex<Connection>>,
+    connection: SharedConnection,
+    tenant_id: Vec<u8>,
     max_epoch_retention: u64,
+    max_epoch_age: Option<Duration>,
+    auto_maintenance: Option<Arc<AutoMaintenance>>,
+    compression: Option<StateCompression>,
+    clock: Arc<dyn Clock>,
 }
 
 impl SqLiteGroupStateStorage {
-    pub(crate) fn new(connection: Connection) -> SqLiteGroupStateStorage {
+    pub(crate) fn new(
+        connection: SharedConnection,
+        tenant_id: Vec<u8>,
+        auto_maintenance: Option<Arc<AutoMaintenance>>,
+        compression: Option<StateCompression>,
+        clock: Arc<dyn Clock>,
+    ) -> SqLiteGroupStateStorage {
         SqLiteGroupStateStorage {
-            connection: Arc::new(Mutex::new(connection)),
+            connection,
+            tenant_id,
             max_epoch_retention: DEFAULT_EPOCH_RETENTION_LIMIT,
+            max_epoch_age: None,
+            auto_maintenance,
+            compression,
+            clock,
         }
     }
 
     pub fn with_max_epoch_retention(self, max_epoch_retention: u64) -> Self {
         Self {
-            connection: self.connection,
             max_epoch_retention,
+            ..self
         }
     }
 
-    /// List all the group ids for groups that are stored.
-    pub fn group_ids(&self) -> Result<Vec<Vec<u8>>, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
+    /// Delete the epochs of a group that were stored more than
+    /// `max_epoch_age` ago whenever the group is written, in addition to the
+    /// epochs beyond the [max epoch retention](Self::with_max_epoch_retention).
+    ///
+    /// Epochs stored by versions without epoch timestamps are considered
+    /// older than any age.
+    pub fn with_max_epoch_age(self, max_epoch_age: Option<Duration>) -> Self {
+        Self {
+            max_epoch_age,
+            ..self
+        }
+    }
 
-        let mut statement = connection
-            .prepare("SELECT group_id FROM mls_group")
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+    pub fn max_epoch_age(&self) -> Option<Duration> {
+        self.max_epoch_age
+    }
 
-        let res = statement
-            .query_map([], |row| row.get(0))
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?
-            .try_fold(Vec::new(), |mut ids, id| {
-                ids.push(id.map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?);
-                Ok::<_, SqLiteDataStorageError>(ids)
-            })
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+    /// List all the group ids for groups that are stored for the tenant of
+    /// this storage.
+    pub fn group_ids(&self) -> Result<Vec<Vec<u8>>, SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            let mut statement = connection
+                .prepare("SELECT group_id FROM mls_group WHERE tenant_id = ?")
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
 
-        Ok(res)
+            let res = statement
+                .query_map(params![self.tenant_id], |row| row.get(0))
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?
+                .try_fold(Vec::new(), |mut ids, id| {
+                    ids.push(
+                        id.map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?,
+                    );
+                    Ok::<_, SqLiteDataStorageError>(ids)
+                })
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            Ok(res)
+        })
     }
 
     /// Delete a group from storage.
     pub fn delete_group(&self, group_id: &[u8]) -> Result<(), SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
+        self.connection.with(|connection| {
+            let deleted = connection
+                .execute(
+                    "DELETE FROM mls_group WHERE tenant_id = ? AND group_id = ?",
+                    params![self.tenant_id, group_id],
+                )
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
 
-        connection
-            .execute(
-                "DELETE FROM mls_group WHERE group_id = ?",
-                params![group_id],
-            )
-            .map(|_| ())
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+            self.record_deletes(connection, deleted)
+        })
     }
 
     pub fn max_epoch_retention(&self) -> u64 {
         self.max_epoch_retention
     }
 
-    fn get_snapshot_data(
+    pub(crate) fn get_snapshot_data(
         &self,
         group_id: &[u8],
     ) -> Result<Option<Vec<u8>>, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .query_row(
-                "SELECT snapshot FROM mls_group where group_id = ?",
-                [group_id],
-                |row| row.get::<_, Vec<u8>>(0),
-            )
-            .optional()
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.connection.with(|connection| {
+            connection
+                .prepare_cached(
+                    "SELECT snapshot FROM mls_group WHERE tenant_id = ? AND group_id = ?",
+                )
+                .and_then(|mut stmt| {
+                    stmt.query_row(params![self.tenant_id, group_id], |row| {
+                        row.get::<_, Vec<u8>>(0)
+                    })
+                })
+                .optional()
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?
+                .map(|data| self.decompress(data))
+                .transpose()
+        })
     }
 
-    fn get_epoch_data(
+    pub(crate) fn get_epoch_data(
         &self,
         group_id: &[u8],
         epoch_id: u64,
     ) -> Result<Option<Vec<u8>>, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .query_row(
-                "SELECT epoch_data FROM epoch where group_id = ? AND epoch_id = ?",
-                params![group_id, epoch_id],
-                |row| row.get::<_, Vec<u8>>(0),
-            )
-            .optional()
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.connection.with(|connection| {
+            connection
+                .prepare_cached(
+                    "SELECT epoch_data FROM epoch WHERE tenant_id = ? AND group_id = ? AND epoch_id = ?",
+                )
+                .and_then(|mut stmt| {
+                    stmt.query_row(params![self.tenant_id, group_id, epoch_id], |row| {
+                        row.get::<_, Vec<u8>>(0)
+                    })
+                })
+                .optional()
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?
+                .map(|data| self.decompress(data))
+                .transpose()
+        })
     }
 
-    fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .query_row(
-                "SELECT MAX(epoch_id) FROM epoch WHERE group_id = ?",
-                params![group_id],
-                |row| row.get::<_, Option<u64>>(0),
-            )
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    pub(crate) fn max_epoch_id(
+        &self,
+        group_id: &[u8],
+    ) -> Result<Option<u64>, SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            connection
+                .prepare_cached(
+                    "SELECT MAX(epoch_id) FROM epoch WHERE tenant_id = ? AND group_id = ?",
+                )
+                .and_then(|mut stmt| {
+                    stmt.query_row(params![self.tenant_id, group_id], |row| {
+                        row.get::<_, Option<u64>>(0)
+                    })
+                })
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
     }
 
-    fn update_group_state(
+    pub(crate) fn update_group_state(
         &self,
         group_id: &[u8],
         group_snapshot: Vec<u8>,
         inserts: Vec<EpochRecord>,
         updates: Vec<EpochRecord>,
     ) -> Result<(), SqLiteDataStorageError> {
-        let mut max_epoch_id = None;
+        let group_snapshot = self.compress(group_snapshot)?;
 
-        let mut connection = self.connection.lock().unwrap();
-        let transaction = connection
-            .transaction()
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+        // Compress once, the transaction may be retried
+        let compress = |epochs: Vec<EpochRecord>| {
+            epochs
+                .into_iter()
+                .map(|epoch| Ok((epoch.id, self.compress(epoch.data)?)))
+                .collect::<Result<Vec<_>, SqLiteDataStorageError>>()
+        };
 
-        // Upsert into the group table to set the most recent snapshot
-        transaction.execute(
-            "INSERT INTO mls_group (group_id, snapshot) VALUES (?, ?) ON CONFLICT(group_id) DO UPDATE SET snapshot=excluded.snapshot",
-            params![group_id, group_snapshot],
-        ).map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+        let inserts = compress(inserts)?;
+        let updates = compress(updates)?;
 
-        // Insert new epochs as needed
-        for epoch in inserts {
-            max_epoch_id = Some(epoch.id);
+        let max_epoch_id = inserts.last().map(|(id, _)| *id);
+        let now = self.clock.now();
 
-            transaction
-                .execute(
-                    "INSERT INTO epoch (group_id, epoch_id, epoch_data) VALUES (?, ?, ?)",
-                    params![group_id, epoch.id, epoch.data],
-                )
-                .map(|_| ())
+        let prune_before = self
+            .max_epoch_age
+            .map(|age| now.saturating_sub(age).seconds_since_epoch());
+
+        self.connection.with(|connection| {
+            let mut deleted = 0;
+
+            let transaction = connection
+                .transaction()
                 .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
-        }
 
-        // Update existing epochs as needed
-        updates.into_iter().try_for_each(|epoch| {
+            // Upsert into the group table to set the most recent snapshot
             transaction
-                .execute(
-                    "UPDATE epoch SET epoch_data = ? WHERE group_id = ? AND epoch_id = ?",
-                    params![epoch.data, group_id, epoch.id],
+                .prepare_cached(
+                    "INSERT INTO mls_group (tenant_id, group_id, snapshot) VALUES (?, ?, ?) ON CONFLICT(tenant_id, group_id) DO UPDATE SET snapshot=excluded.snapshot",
                 )
-                .map(|_| ())
-                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
-        })?;
+                .and_then(|mut stmt| stmt.execute(params![self.tenant_id, group_id, group_snapshot]))
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            // Insert new epochs as needed
+            if !inserts.is_empty() {
+                let mut stmt = transaction
+                    .prepare_cached(
+                        "INSERT INTO epoch (tenant_id, group_id, epoch_id, epoch_data, created_at) VALUES (?, ?, ?, ?, ?)",
+                    )
+                    .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
 
-        // Delete old epochs as needed
-        if let Some(max_epoch_id) = max_epoch_id {
-            if max_epoch_id >= self.max_epoch_retention {
-                let delete_under = max_epoch_id - self.max_epoch_retention;
+                for (epoch_id, data) in &inserts {
+                    stmt.execute(params![
+                        self.tenant_id,
+                        group_id,
+                        epoch_id,
+                        data,
+                        now.seconds_since_epoch()
+                    ])
+                        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+                }
+            }
+
+            // Update existing epochs as needed
+            if !updates.is_empty() {
+                let mut stmt = transaction
+                    .prepare_cached(
+                        "UPDATE epoch SET epoch_data = ? WHERE tenant_id = ? AND group_id = ? AND epoch_id = ?",
+                    )
+                    .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+                updates.iter().try_for_each(|(epoch_id, data)| {
+                    stmt.execute(params![data, self.tenant_id, group_id, epoch_id])
+                        .map(|_| ())
+                        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+                })?;
+            }
+
+            // Delete old epochs as needed
+            if let Some(max_epoch_id) = max_epoch_id {
+                if max_epoch_id >= self.max_epoch_retention {
+                    let delete_under = max_epoch_id - self.max_epoch_retention;
+
+                    deleted = transaction
+                        .prepare_cached(
+                            "DELETE FROM epoch WHERE tenant_id = ? AND group_id = ? AND epoch_id <= ?",
+                        )
+                        .and_then(|mut stmt| {
+                            stmt.execute(params![self.tenant_id, group_id, delete_under])
+                        })
+                        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+                }
+            }
 
-                transaction
-                    .execute(
-                        "DELETE FROM epoch WHERE group_id = ? AND epoch_id <= ?",
-                        params![group_id, delete_under],
+            // Delete epochs older than the max epoch age
+            if let Some(prune_before) = prune_before {
+                deleted += transaction
+                    .prepare_cached(
+                        "DELETE FROM epoch WHERE tenant_id = ? AND group_id = ? AND (created_at IS NULL OR created_at < ?)",
                     )
+                    .and_then(|mut stmt| {
+                        stmt.execute(params![self.tenant_id, group_id, prune_before])
+                    })
                     .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
             }
+
+            // Execute the full transaction
+            transaction
+                .commit()
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            self.record_deletes(connection, deleted)
+        })
+    }
+
+    fn compress(&self, data: Vec<u8>) -> Result<Vec<u8>, SqLiteDataStorageError> {
+        match &self.compression {
+            Some(compression) => compression.compress(&data),
+            None => Ok(data),
         }
+    }
 
-        // Execute the full transaction
-        transaction
-            .commit()
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    fn decompress(&self, data: Vec<u8>) -> Result<Vec<u8>, SqLiteDataStorageError> {
+        compression::decompress(self.compression.as_ref(), data)
+    }
+
+    fn record_deletes(
+        &self,
+        connection: &Connection,
+        deleted: usize,
+    ) -> Result<(), SqLiteDataStorageError> {
+        self.auto_maintenance
+            .as_ref()
+            .map_or(Ok(()), |auto| auto.record_deletes(connection, deleted))
     }
 }
 
@@ -214,9 +341,17 @@ impl GroupStateStorage for SqLiteGroupStateStorage {
 
 #[cfg(test)]
 mod tests {
+    use std::convert::Infallible;
+
+    use assert_matches::assert_matches;
+    use mls_rs_core::{compression::Compressor, time::MlsTime};
+
     use crate::{
         SqLiteDataStorageEngine,
-        {connection_strategy::MemoryStrategy, test_utils::gen_rand_bytes},
+        {
+            connection_strategy::{FileConnectionStrategy, MemoryStrategy},
+            test_utils::{gen_rand_bytes, FakeClock, GroupFixture},
+        },
     };
 
     use super::*;
@@ -328,6 +463,102 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fixture_epochs_follow_retention() {
+        let storage = get_test_storage().with_max_epoch_retention(2);
+        let group = GroupFixture::new().with_epochs(5).insert(&storage).unwrap();
+
+        assert_eq!(storage.max_epoch_id(&group.group_id).unwrap(), Some(4));
+
+        for epoch in &group.epochs {
+            let stored = storage.get_epoch_data(&group.group_id, epoch.id).unwrap();
+
+            if epoch.id <= 2 {
+                assert!(stored.is_none());
+            } else {
+                assert_eq!(stored.unwrap(), epoch.data);
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Reverse(u16);
+
+    impl Compressor for Reverse {
+        type Error = Infallible;
+
+        fn algorithm_id(&self) -> u16 {
+            self.0
+        }
+
+        fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Infallible> {
+            Ok(data.iter().rev().copied().collect())
+        }
+
+        fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Infallible> {
+            Ok(data.iter().rev().copied().collect())
+        }
+    }
+
+    #[test]
+    fn compression_is_transparent() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        let engine = || SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path)).unwrap();
+
+        let uncompressed = GroupFixture::new()
+            .insert(&engine().group_state_storage().unwrap())
+            .unwrap();
+
+        let storage = engine()
+            .with_compression(Reverse(7))
+            .group_state_storage()
+            .unwrap();
+
+        let compressed = GroupFixture::new().insert(&storage).unwrap();
+
+        let raw_snapshot = storage
+            .connection
+            .lock()
+            .query_row(
+                "SELECT snapshot FROM mls_group WHERE group_id = ?",
+                [&compressed.group_id],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .unwrap();
+
+        assert_ne!(raw_snapshot, compressed.snapshot);
+
+        assert_eq!(
+            storage.get_snapshot_data(&compressed.group_id).unwrap(),
+            Some(compressed.snapshot)
+        );
+
+        assert_eq!(
+            storage.get_epoch_data(&compressed.group_id, 0).unwrap(),
+            Some(compressed.epochs[0].data.clone())
+        );
+
+        assert_eq!(
+            storage.get_snapshot_data(&uncompressed.group_id).unwrap(),
+            Some(uncompressed.snapshot)
+        );
+
+        let other = engine()
+            .with_compression(Reverse(8))
+            .group_state_storage()
+            .unwrap();
+
+        assert_matches!(
+            other.get_snapshot_data(&compressed.group_id),
+            Err(SqLiteDataStorageError::CompressionAlgorithmMismatch(
+                7,
+                Some(8)
+            ))
+        );
+    }
+
     #[test]
     fn epochs_are_truncated() {
         test_epochs_are_truncated(9);
@@ -365,6 +596,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn epochs_older_than_max_age_are_deleted() {
+        let clock = FakeClock::new(MlsTime::from(1_000_000));
+
+        let storage = SqLiteDataStorageEngine::new(MemoryStrategy)
+            .unwrap()
+            .with_clock(clock.clone())
+            .with_max_epoch_retention(10)
+            .with_max_epoch_age(Duration::from_secs(3600))
+            .group_state_storage()
+            .unwrap();
+
+        let group = GroupFixture::new().with_epochs(2).insert(&storage).unwrap();
+
+        clock.advance(Duration::from_secs(7200));
+
+        let epoch_2 = test_epoch(2);
+
+        storage
+            .update_group_state(
+                &group.group_id,
+                test_snapshot(),
+                vec![epoch_2.clone()],
+                vec![],
+            )
+            .unwrap();
+
+        assert_eq!(storage.get_epoch_data(&group.group_id, 0).unwrap(), None);
+        assert_eq!(storage.get_epoch_data(&group.group_id, 1).unwrap(), None);
+
+        assert_eq!(
+            storage.get_epoch_data(&group.group_id, 2).unwrap(),
+            Some(epoch_2.data)
+        );
+    }
+
     #[test]
     fn epoch_insert_update_old_epoch() {
         let test_data = setup_group_storage_test();
@@ -5,119 +5,148 @@
 use mls_rs_core::{
     key_package::{KeyPackageData, KeyPackageStorage},
     mls_rs_codec::{MlsDecode, MlsEncode},
-    time::MlsTime,
 };
 use rusqlite::{params, Connection, OptionalExtension};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
-use crate::SqLiteDataStorageError;
+use crate::{
+    clock::Clock, maintenance::AutoMaintenance, recovery::SharedConnection, SqLiteDataStorageError,
+};
 
 #[derive(Debug, Clone)]
 /// SQLite storage for MLS Key Packages.
 pub struct SqLiteKeyPackageStorage {
-    connection: Arc<Mutex<Connection>>,
+    connection: SharedConnection,
+    tenant_id: Vec<u8>,
+    auto_maintenance: Option<Arc<AutoMaintenance>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl SqLiteKeyPackageStorage {
-    pub(crate) fn new(connection: Connection) -> SqLiteKeyPackageStorage {
+    pub(crate) fn new(
+        connection: SharedConnection,
+        tenant_id: Vec<u8>,
+        auto_maintenance: Option<Arc<AutoMaintenance>>,
+        clock: Arc<dyn Clock>,
+    ) -> SqLiteKeyPackageStorage {
         SqLiteKeyPackageStorage {
-            connection: Arc::new(Mutex::new(connection)),
+            connection,
+            tenant_id,
+            auto_maintenance,
+            clock,
         }
     }
 
-    fn insert(
+    pub(crate) fn insert(
         &mut self,
         id: &[u8],
         key_package: KeyPackageData,
     ) -> Result<(), SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .execute(
-                "INSERT INTO key_package (id, expiration, data) VALUES (?,?,?)",
-                params![
-                    id,
-                    key_package.expiration,
-                    key_package
-                        .mls_encode_to_vec()
-                        .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?
-                ],
-            )
-            .map(|_| ())
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.connection.with(|connection| {
+            let data = key_package
+                .mls_encode_to_vec()
+                .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?;
+
+            // SQLite integers are signed. Expirations beyond their range are
+            // stored as the largest value, which is never reached in practice.
+            let expiration = i64::try_from(key_package.expiration).unwrap_or(i64::MAX);
+
+            connection
+                .prepare_cached(
+                    "INSERT INTO key_package (tenant_id, id, expiration, data) VALUES (?,?,?,?)",
+                )
+                .and_then(|mut stmt| stmt.execute(params![self.tenant_id, id, expiration, data]))
+                .map(|_| ())
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
     }
 
-    fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .query_row(
-                "SELECT data FROM key_package WHERE id = ?",
-                params![id],
-                |row| {
-                    Ok(
-                        KeyPackageData::mls_decode(&mut row.get::<_, Vec<u8>>(0)?.as_slice())
-                            .unwrap(),
-                    )
-                },
-            )
-            .optional()
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    pub(crate) fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, SqLiteDataStorageError> {
+        self.connection.with(|connection| {
+            connection
+                .prepare_cached("SELECT data FROM key_package WHERE tenant_id = ? AND id = ?")
+                .and_then(|mut stmt| {
+                    stmt.query_row(params![self.tenant_id, id], |row| {
+                        Ok(
+                            KeyPackageData::mls_decode(&mut row.get::<_, Vec<u8>>(0)?.as_slice())
+                                .unwrap(),
+                        )
+                    })
+                })
+                .optional()
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
     }
 
     /// Delete a specific key package from storage based on it's id.
     pub fn delete(&self, id: &[u8]) -> Result<(), SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .execute("DELETE FROM key_package where id = ?", params![id])
-            .map(|_| ())
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.connection.with(|connection| {
+            let deleted = connection
+                .prepare_cached("DELETE FROM key_package WHERE tenant_id = ? AND id = ?")
+                .and_then(|mut stmt| stmt.execute(params![self.tenant_id, id]))
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            self.record_deletes(connection, deleted)
+        })
     }
 
-    /// Delete key packages that are expired based on the current system clock time.
+    /// Delete key packages that are expired based on the current time of the
+    /// [`Clock`] of the engine, which is the system clock by default.
     pub fn delete_expired(&self) -> Result<(), SqLiteDataStorageError> {
-        self.delete_expired_by_time(MlsTime::now().seconds_since_epoch())
+        self.delete_expired_by_time(self.clock.now().seconds_since_epoch())
     }
 
     /// Delete key packages that are expired based on an application provided time in seconds since
     /// unix epoch.
     pub fn delete_expired_by_time(&self, time: u64) -> Result<(), SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .execute(
-                "DELETE FROM key_package where expiration < ?",
-                params![time],
-            )
-            .map(|_| ())
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.connection.with(|connection| {
+            let deleted = connection
+                .execute(
+                    "DELETE FROM key_package WHERE tenant_id = ? AND expiration < ?",
+                    params![self.tenant_id, time],
+                )
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            self.record_deletes(connection, deleted)
+        })
     }
 
     /// Total number of key packages held in storage.
     pub fn count(&self) -> Result<usize, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .query_row("SELECT count(*) FROM key_package", params![], |row| {
-                row.get(0)
-            })
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.connection.with(|connection| {
+            connection
+                .query_row(
+                    "SELECT count(*) FROM key_package WHERE tenant_id = ?",
+                    params![self.tenant_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
     }
 
     /// Total number of key packages that will still remain in storage at a specific application provided
     /// time in seconds since unix epoch. This assumes that the application would also be calling
     /// [SqLiteKeyPackageStorage::delete_expired] at a reasonable cadence to be accurate.
     pub fn count_at_time(&self, time: u64) -> Result<usize, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .query_row(
-                "SELECT count(*) FROM key_package where expiration >= ?",
-                params![time],
-                |row| row.get(0),
-            )
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.connection.with(|connection| {
+            connection
+                .query_row(
+                    "SELECT count(*) FROM key_package WHERE tenant_id = ? AND expiration >= ?",
+                    params![self.tenant_id, time],
+                    |row| row.get(0),
+                )
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
+    }
+
+    fn record_deletes(
+        &self,
+        connection: &Connection,
+        deleted: usize,
+    ) -> Result<(), SqLiteDataStorageError> {
+        self.auto_maintenance
+            .as_ref()
+            .map_or(Ok(()), |auto| auto.record_deletes(connection, deleted))
     }
 }
 
@@ -144,10 +173,14 @@ mod tests {
     use super::SqLiteKeyPackageStorage;
     use crate::{
         SqLiteDataStorageEngine, SqLiteDataStorageError,
-        {connection_strategy::MemoryStrategy, test_utils::gen_rand_bytes},
+        {
+            connection_strategy::MemoryStrategy,
+            test_utils::{gen_rand_bytes, FakeClock, KeyPackageFixture},
+        },
     };
     use assert_matches::assert_matches;
-    use mls_rs_core::{crypto::HpkeSecretKey, key_package::KeyPackageData};
+    use mls_rs_core::{crypto::HpkeSecretKey, key_package::KeyPackageData, time::MlsTime};
+    use std::time::Duration;
 
     fn test_storage() -> SqLiteKeyPackageStorage {
         SqLiteDataStorageEngine::new(MemoryStrategy)
@@ -277,4 +310,34 @@ mod tests {
         assert_eq!(storage.count_at_time(1).unwrap(), 2);
         assert_eq!(storage.count_at_time(0).unwrap(), 2);
     }
+
+    #[test]
+    fn expiry_follows_engine_clock() {
+        let clock = FakeClock::new(MlsTime::from(1000));
+
+        let mut storage = SqLiteDataStorageEngine::new(MemoryStrategy)
+            .unwrap()
+            .with_clock(clock.clone())
+            .key_package_storage()
+            .unwrap();
+
+        let short = KeyPackageFixture::new()
+            .with_lifetime(&clock, Duration::from_secs(60))
+            .insert(&mut storage)
+            .unwrap();
+
+        let long = KeyPackageFixture::new()
+            .with_lifetime(&clock, Duration::from_secs(3600))
+            .insert(&mut storage)
+            .unwrap();
+
+        storage.delete_expired().unwrap();
+        assert_eq!(storage.count().unwrap(), 2);
+
+        clock.advance(Duration::from_secs(61));
+        storage.delete_expired().unwrap();
+
+        assert!(storage.get(&short).unwrap().is_none());
+        storage.get(&long).unwrap().unwrap();
+    }
 }
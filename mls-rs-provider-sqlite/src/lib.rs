@@ -2,23 +2,59 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use compression::StateCompression;
 use connection_strategy::ConnectionStrategy;
 use group_state::SqLiteGroupStateStorage;
+use maintenance::AutoMaintenance;
+use migration::SCHEMA_VERSION;
+use mls_rs_core::{compression::Compressor, time::MlsTime};
 use psk::SqLitePreSharedKeyStorage;
-use rusqlite::Connection;
-use storage::{SqLiteApplicationStorage, SqLiteKeyPackageStorage};
+use recovery::{Recovery, SharedConnection};
+use rusqlite::{params, Connection};
+use std::{sync::Arc, time::Duration};
+use storage::{SqLiteApplicationStorage, SqLiteGroupIdMappingStorage, SqLiteKeyPackageStorage};
 use thiserror::Error;
 
+// Epochs are listed before their groups so that they are counted when a
+// tenant is deleted, instead of being removed by the cascade.
+const TABLES: &[&str] = &[
+    "epoch",
+    "mls_group",
+    "key_package",
+    "psk",
+    "kvs",
+    "group_id_map",
+];
+
 mod application;
+#[cfg(all(mls_build_async, feature = "tokio"))]
+mod async_engine;
+mod clock;
+mod compression;
+mod group_id_mapping;
 mod group_state;
 mod key_package;
+mod maintenance;
+mod migration;
 mod psk;
+mod recovery;
+
+#[cfg(all(mls_build_async, feature = "tokio"))]
+pub use async_engine::AsyncSqLiteDataStorageEngine;
+pub use clock::{Clock, SystemClock};
+#[cfg(feature = "zstd")]
+pub use compression::ZstdCompressor;
+pub use maintenance::{MaintenanceConfig, MaintenanceReport};
+pub use migration::{Migration, SchemaVersion};
+pub use recovery::{BusyRetry, FailureHandler, FailureKind, RecoveryAction, RecoveryConfig};
 
 #[cfg(any(feature = "sqlcipher", feature = "sqlcipher-bundled"))]
 mod cipher;
 
-#[cfg(test)]
-pub(crate) mod test_utils;
+#[cfg(any(test, feature = "test-utils"))]
+/// Fixtures and a controllable clock for tests of applications using this
+/// provider.
+pub mod test_utils;
 
 /// Connection strategies.
 pub mod connection_strategy;
@@ -26,11 +62,18 @@ pub mod connection_strategy;
 /// SQLite storage components.
 pub mod storage {
     pub use {
-        crate::application::{Item, SqLiteApplicationStorage},
+        crate::application::{Item, ItemPage, SqLiteApplicationStorage},
+        crate::group_id_mapping::SqLiteGroupIdMappingStorage,
         crate::group_state::SqLiteGroupStateStorage,
         crate::key_package::SqLiteKeyPackageStorage,
         crate::psk::SqLitePreSharedKeyStorage,
     };
+
+    #[cfg(all(mls_build_async, feature = "tokio"))]
+    pub use crate::async_engine::{
+        AsyncSqLiteApplicationStorage, AsyncSqLiteGroupStateStorage, AsyncSqLiteKeyPackageStorage,
+        AsyncSqLitePreSharedKeyStorage,
+    };
 }
 
 #[derive(Debug, Error)]
@@ -46,6 +89,28 @@ pub enum SqLiteDataStorageError {
     #[error("invalid key, must use SqlCipherKey::RawKeyWithSalt with plaintext_header_size > 0")]
     /// Invalid SQLCipher key header.
     SqlCipherKeyInvalidWithHeader,
+    #[error("unsupported schema version {0} in read-only database")]
+    /// A read-only database does not have the expected schema, which can't be
+    /// created without writing to it.
+    ReadOnlySchemaMismatch(u32),
+    #[error(transparent)]
+    /// Stored group state could not be compressed or decompressed.
+    CompressionError(Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("stored group state was compressed with algorithm {0}, configured algorithm is {1:?}")]
+    /// Stored group state was compressed with a different algorithm than the
+    /// one configured, or compression is no longer configured.
+    CompressionAlgorithmMismatch(u16, Option<u16>),
+    #[cfg(all(mls_build_async, feature = "tokio"))]
+    #[error(transparent)]
+    /// A storage operation run on the blocking thread pool panicked or was
+    /// cancelled.
+    BlockingTaskError(Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("application migration to version {0} failed")]
+    /// An application [`Migration`] failed, the database was left unchanged.
+    MigrationError(
+        u32,
+        #[source] Box<dyn std::error::Error + Send + Sync + 'static>,
+    ),
 }
 
 impl mls_rs_core::error::IntoAnyError for SqLiteDataStorageError {
@@ -85,8 +150,18 @@ pub struct SqLiteDataStorageEngine<CS>
 where
     CS: ConnectionStrategy,
 {
-    connection_strategy: CS,
+    connection_strategy: Arc<CS>,
     journal_mode: Option<JournalMode>,
+    maintenance_config: MaintenanceConfig,
+    auto_maintenance: Option<Arc<AutoMaintenance>>,
+    clock: Arc<dyn Clock>,
+    compression: Option<StateCompression>,
+    tenant_id: Vec<u8>,
+    statement_cache_capacity: Option<usize>,
+    recovery: Option<Recovery>,
+    max_epoch_retention: Option<u64>,
+    max_epoch_age: Option<Duration>,
+    migrations: Vec<Migration>,
 }
 
 impl<CS> SqLiteDataStorageEngine<CS>
@@ -97,11 +172,174 @@ where
         connection_strategy: CS,
     ) -> Result<SqLiteDataStorageEngine<CS>, SqLiteDataStorageError> {
         Ok(SqLiteDataStorageEngine {
-            connection_strategy,
+            connection_strategy: Arc::new(connection_strategy),
             journal_mode: None,
+            maintenance_config: Default::default(),
+            auto_maintenance: None,
+            clock: Arc::new(SystemClock),
+            compression: None,
+            tenant_id: Vec::new(),
+            statement_cache_capacity: None,
+            recovery: None,
+            max_epoch_retention: None,
+            max_epoch_age: None,
+            migrations: Vec::new(),
         })
     }
 
+    /// Scope all storages created by this engine to `tenant_id`.
+    ///
+    /// Storages of different tenants sharing the same database never see or
+    /// modify each other's data. Storages created without a tenant use the
+    /// empty tenant id, which also holds data stored before tenants were
+    /// supported.
+    pub fn with_tenant(self, tenant_id: &[u8]) -> Self {
+        Self {
+            tenant_id: tenant_id.to_vec(),
+            ..self
+        }
+    }
+
+    /// Tenant that storages created by this engine are scoped to.
+    pub fn tenant_id(&self) -> &[u8] {
+        &self.tenant_id
+    }
+
+    /// List the ids of all tenants that have data in the database, regardless
+    /// of the tenant of this engine.
+    pub fn tenant_ids(&self) -> Result<Vec<Vec<u8>>, SqLiteDataStorageError> {
+        let connection = self.create_connection()?;
+
+        let sql = TABLES
+            .iter()
+            .map(|table| format!("SELECT tenant_id FROM {table}"))
+            .collect::<Vec<_>>()
+            .join(" UNION ");
+
+        let mut statement = connection
+            .prepare(&sql)
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        let rows = statement
+            .query_map([], |row| row.get(0))
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))
+    }
+
+    /// Delete all groups, key packages, pre-shared keys and application data
+    /// of `tenant_id` in a single transaction, regardless of the tenant of
+    /// this engine. Returns the total number of rows deleted.
+    pub fn delete_tenant(&self, tenant_id: &[u8]) -> Result<usize, SqLiteDataStorageError> {
+        let mut connection = self.create_connection()?;
+
+        let transaction = connection
+            .transaction()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        let deleted = TABLES.iter().try_fold(0, |deleted, table| {
+            transaction
+                .execute(
+                    &format!("DELETE FROM {table} WHERE tenant_id = ?"),
+                    [tenant_id],
+                )
+                .map(|rows| deleted + rows)
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })?;
+
+        transaction
+            .commit()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        if let Some(auto) = self.auto_maintenance() {
+            auto.record_deletes(&connection, deleted)?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Clock used to determine which stored data is expired. Defaults to
+    /// [`SystemClock`].
+    pub fn with_clock<C: Clock + 'static>(self, clock: C) -> Self {
+        Self {
+            clock: Arc::new(clock),
+            ..self
+        }
+    }
+
+    /// Number of past epochs of each group kept by the group state storages
+    /// created by this engine. Older epochs are deleted whenever a group is
+    /// written. Defaults to 3.
+    pub fn with_max_epoch_retention(self, max_epoch_retention: u64) -> Self {
+        Self {
+            max_epoch_retention: Some(max_epoch_retention),
+            ..self
+        }
+    }
+
+    /// Delete the epochs of a group stored more than `max_epoch_age` ago
+    /// whenever the group is written by a group state storage created by this
+    /// engine. Age is measured with the [clock](Self::with_clock) of this
+    /// engine.
+    ///
+    /// Epochs of groups that are no longer written can be deleted with
+    /// [`SqLiteDataStorageEngine::prune_epochs_older_than`].
+    pub fn with_max_epoch_age(self, max_epoch_age: Duration) -> Self {
+        Self {
+            max_epoch_age: Some(max_epoch_age),
+            ..self
+        }
+    }
+
+    /// Delete all epochs of the tenant of this engine stored before `time`.
+    /// Returns the number of epochs deleted.
+    ///
+    /// Group snapshots are never deleted. Epochs stored by versions without
+    /// epoch timestamps are always deleted.
+    pub fn prune_epochs_older_than(&self, time: MlsTime) -> Result<usize, SqLiteDataStorageError> {
+        let connection = self.create_connection()?;
+
+        let deleted = connection
+            .execute(
+                "DELETE FROM epoch WHERE tenant_id = ? AND (created_at IS NULL OR created_at < ?)",
+                params![self.tenant_id, time.seconds_since_epoch()],
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        if let Some(auto) = self.auto_maintenance() {
+            auto.record_deletes(&connection, deleted)?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Compress group snapshots and epochs with `compressor` before storing
+    /// them.
+    ///
+    /// Group state stored without compression can still be read once
+    /// compression is enabled. Group state stored with compression can not be
+    /// read without the same compression algorithm.
+    pub fn with_compression<C: Compressor + 'static>(self, compressor: C) -> Self {
+        Self {
+            compression: Some(StateCompression::new(compressor)),
+            ..self
+        }
+    }
+
+    /// Number of prepared statements cached by each connection of the
+    /// storages created by this engine. Storage operations reuse cached
+    /// statements instead of preparing them on every call.
+    ///
+    /// Defaults to the rusqlite default of 16, which covers all statements
+    /// used by a single storage. A capacity of 0 disables caching.
+    pub fn with_statement_cache_capacity(self, capacity: usize) -> Self {
+        Self {
+            statement_cache_capacity: Some(capacity),
+            ..self
+        }
+    }
+
     /// A `journal_mode` of `None` means the SQLite default is used.
     pub fn with_journal_mode(self, journal_mode: Option<JournalMode>) -> Self {
         Self {
@@ -110,94 +348,231 @@ where
         }
     }
 
-    fn create_connection(&self) -> Result<Connection, SqLiteDataStorageError> {
-        let connection = self.connection_strategy.make_connection()?;
+    /// Thresholds used by [`SqLiteDataStorageEngine::maintenance`] and by
+    /// automatic maintenance after deletes.
+    ///
+    /// Automatic maintenance only applies to storages created after this
+    /// config is set.
+    pub fn with_maintenance_config(self, maintenance_config: MaintenanceConfig) -> Self {
+        Self {
+            auto_maintenance: AutoMaintenance::new(maintenance_config.clone()).map(Arc::new),
+            maintenance_config,
+            ..self
+        }
+    }
 
-        // Run SQL to establish the schema
-        let current_schema = connection
-            .pragma_query_value(None, "user_version", |rows| rows.get::<_, u32>(0))
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+    /// Release free pages back to the file system and refresh query planner
+    /// statistics according to the configured [`MaintenanceConfig`].
+    ///
+    /// Databases created by this engine use incremental vacuuming. Databases
+    /// created by older versions are rebuilt with a full `VACUUM` the first
+    /// time free pages are released.
+    pub fn maintenance(&self) -> Result<MaintenanceReport, SqLiteDataStorageError> {
+        maintenance::run_maintenance(&self.create_connection()?, &self.maintenance_config)
+    }
 
-        if let Some(journal_mode) = &self.journal_mode {
-            connection
-                .pragma_update(None, "journal_mode", journal_mode.as_str())
-                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
-        }
+    /// Release all free pages back to the file system, regardless of the
+    /// thresholds of the configured [`MaintenanceConfig`].
+    pub fn vacuum(&self) -> Result<MaintenanceReport, SqLiteDataStorageError> {
+        let config = MaintenanceConfig {
+            min_free_pages: 0,
+            max_vacuum_pages: None,
+            ..self.maintenance_config.clone()
+        };
+
+        maintenance::run_maintenance(&self.create_connection()?, &config)
+    }
+
+    /// Run `migration` on databases opened by this engine whose application
+    /// schema is older than its version. Replaces any migration with the same
+    /// version.
+    ///
+    /// Application migrations only apply to storages created after they are
+    /// added.
+    pub fn with_migration(mut self, migration: Migration) -> Self {
+        self.migrations
+            .retain(|existing| existing.version() != migration.version());
+
+        let index = self
+            .migrations
+            .partition_point(|existing| existing.version() < migration.version());
 
-        if current_schema != 1 {
-            create_tables_v1(&connection)?;
+        self.migrations.insert(index, migration);
+        self
+    }
+
+    /// Schema version of the database, after running all pending migrations.
+    pub fn schema_version(&self) -> Result<SchemaVersion, SqLiteDataStorageError> {
+        migration::schema_version(&self.create_connection()?)
+    }
+
+    fn create_connection(&self) -> Result<Connection, SqLiteDataStorageError> {
+        open_connection(&*self.connection_strategy, &self.connection_options())
+    }
+
+    fn connection_options(&self) -> ConnectionOptions {
+        ConnectionOptions {
+            journal_mode: self.journal_mode.clone(),
+            statement_cache_capacity: self.statement_cache_capacity,
+            migrations: self.migrations.clone(),
         }
+    }
 
-        Ok(connection)
+    // Connection of a storage, recovered according to the recovery config.
+    pub(crate) fn shared_connection(&self) -> Result<SharedConnection, SqLiteDataStorageError> {
+        let recovery = self
+            .recovery
+            .clone()
+            .map(|recovery| Arc::new(recovery.with_options(self.connection_options())));
+
+        Ok(SharedConnection::new(self.create_connection()?, recovery))
+    }
+
+    // Automatic maintenance writes to the database.
+    fn auto_maintenance(&self) -> Option<Arc<AutoMaintenance>> {
+        self.auto_maintenance
+            .clone()
+            .filter(|_| !self.connection_strategy.is_read_only())
     }
 
     /// Returns a struct that implements the `GroupStateStorage` trait for use in MLS.
     pub fn group_state_storage(&self) -> Result<SqLiteGroupStateStorage, SqLiteDataStorageError> {
-        Ok(SqLiteGroupStateStorage::new(self.create_connection()?))
+        let storage = SqLiteGroupStateStorage::new(
+            self.shared_connection()?,
+            self.tenant_id.clone(),
+            self.auto_maintenance(),
+            self.compression.clone(),
+            self.clock.clone(),
+        )
+        .with_max_epoch_age(self.max_epoch_age);
+
+        Ok(match self.max_epoch_retention {
+            Some(max_epoch_retention) => storage.with_max_epoch_retention(max_epoch_retention),
+            None => storage,
+        })
     }
 
     /// Returns a struct that implements the `KeyPackageStorage` trait for use in MLS.
     pub fn key_package_storage(&self) -> Result<SqLiteKeyPackageStorage, SqLiteDataStorageError> {
-        Ok(SqLiteKeyPackageStorage::new(self.create_connection()?))
+        Ok(SqLiteKeyPackageStorage::new(
+            self.shared_connection()?,
+            self.tenant_id.clone(),
+            self.auto_maintenance(),
+            self.clock.clone(),
+        ))
     }
 
     /// Returns a struct that implements the `PreSharedKeyStorage` trait for use in MLS.
     pub fn pre_shared_key_storage(
         &self,
     ) -> Result<SqLitePreSharedKeyStorage, SqLiteDataStorageError> {
-        Ok(SqLitePreSharedKeyStorage::new(self.create_connection()?))
+        Ok(SqLitePreSharedKeyStorage::new(
+            self.shared_connection()?,
+            self.tenant_id.clone(),
+            self.auto_maintenance(),
+        ))
+    }
+
+    /// Returns a struct that implements the `GroupIdMappingStorage` trait for use in MLS.
+    pub fn group_id_mapping_storage(
+        &self,
+    ) -> Result<SqLiteGroupIdMappingStorage, SqLiteDataStorageError> {
+        Ok(SqLiteGroupIdMappingStorage::new(
+            self.shared_connection()?,
+            self.tenant_id.clone(),
+            self.auto_maintenance(),
+        ))
     }
 
     /// Returns a key value store that can be used to store application specific data.
     pub fn application_data_storage(
         &self,
     ) -> Result<SqLiteApplicationStorage, SqLiteDataStorageError> {
-        Ok(SqLiteApplicationStorage::new(self.create_connection()?))
+        Ok(SqLiteApplicationStorage::new(
+            self.shared_connection()?,
+            self.tenant_id.clone(),
+            self.auto_maintenance(),
+            self.clock.clone(),
+        ))
     }
 }
 
-fn create_tables_v1(connection: &Connection) -> Result<(), SqLiteDataStorageError> {
-    connection
-        .execute_batch(
-            "BEGIN;
-            CREATE TABLE mls_group (
-                group_id BLOB PRIMARY KEY,
-                snapshot BLOB NOT NULL
-            ) WITHOUT ROWID;
-            CREATE TABLE epoch (
-                group_id BLOB,
-                epoch_id INTEGER,
-                epoch_data BLOB NOT NULL,
-                FOREIGN KEY (group_id) REFERENCES mls_group (group_id) ON DELETE CASCADE
-                PRIMARY KEY (group_id, epoch_id)
-            ) WITHOUT ROWID;
-            CREATE TABLE key_package (
-                id BLOB PRIMARY KEY,
-                expiration INTEGER,
-                data BLOB NOT NULL
-            ) WITHOUT ROWID;
-            CREATE INDEX key_package_exp ON key_package (expiration);
-            CREATE TABLE psk (
-                psk_id BLOB PRIMARY KEY,
-                data BLOB NOT NULL
-            ) WITHOUT ROWID;
-            CREATE TABLE kvs (
-                key TEXT PRIMARY KEY,
-                value BLOB NOT NULL
-            ) WITHOUT ROWID;
-            PRAGMA user_version = 1;
-            COMMIT;",
-        )
-        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+impl<CS> SqLiteDataStorageEngine<CS>
+where
+    CS: ConnectionStrategy + Send + Sync + 'static,
+{
+    /// Retry and recover operations of the storages created by this engine
+    /// according to `recovery`.
+    ///
+    /// Without a recovery config, all errors are returned immediately.
+    /// Recovery only applies to storages created after this config is set.
+    pub fn with_recovery(self, recovery: RecoveryConfig) -> Self {
+        Self {
+            recovery: Some(Recovery::new(recovery, self.connection_strategy.clone())),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ConnectionOptions {
+    journal_mode: Option<JournalMode>,
+    statement_cache_capacity: Option<usize>,
+    migrations: Vec<Migration>,
+}
+
+pub(crate) fn open_connection(
+    connection_strategy: &dyn ConnectionStrategy,
+    options: &ConnectionOptions,
+) -> Result<Connection, SqLiteDataStorageError> {
+    let mut connection = connection_strategy.make_connection()?;
+
+    if let Some(capacity) = options.statement_cache_capacity {
+        connection.set_prepared_statement_cache_capacity(capacity);
+    }
+
+    // Read-only databases can't be migrated, application migrations are not
+    // checked.
+    if connection_strategy.is_read_only() {
+        let current_schema = migration::schema_version(&connection)?.built_in;
+
+        return if current_schema == SCHEMA_VERSION {
+            Ok(connection)
+        } else {
+            Err(SqLiteDataStorageError::ReadOnlySchemaMismatch(
+                current_schema,
+            ))
+        };
+    }
+
+    if let Some(journal_mode) = &options.journal_mode {
+        connection
+            .pragma_update(None, "journal_mode", journal_mode.as_str())
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+    }
+
+    migration::migrate(&mut connection, &options.migrations)?;
+
+    Ok(connection)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use tempfile::tempdir;
 
+    use assert_matches::assert_matches;
+    use mls_rs_core::time::MlsTime;
+
     use crate::{
-        connection_strategy::{FileConnectionStrategy, MemoryStrategy},
-        SqLiteDataStorageEngine,
+        connection_strategy::{
+            FileConnectionStrategy, MemoryStrategy, ReadOnlyFileConnectionStrategy,
+        },
+        storage::Item,
+        test_utils::{FakeClock, GroupFixture, KeyPackageFixture},
+        MaintenanceConfig, Migration, SchemaVersion, SqLiteDataStorageEngine,
+        SqLiteDataStorageError, SCHEMA_VERSION,
     };
 
     #[test]
@@ -214,7 +589,242 @@ mod tests {
             .pragma_query_value(None, "user_version", |rows| rows.get::<_, u32>(0))
             .unwrap();
 
-        assert_eq!(current_schema, 1);
+        assert_eq!(current_schema, SCHEMA_VERSION);
+    }
+
+    #[test]
+    pub fn prune_epochs_older_than_test() {
+        let temp = tempdir().unwrap();
+        let clock = FakeClock::new(MlsTime::from(1_000_000));
+
+        let database = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(
+            &temp.path().join("test_db.sqlite"),
+        ))
+        .unwrap()
+        .with_clock(clock.clone());
+
+        let storage = database.group_state_storage().unwrap();
+        let old = GroupFixture::new().with_epochs(2).insert(&storage).unwrap();
+
+        clock.advance(Duration::from_secs(60));
+        let new = GroupFixture::new().insert(&storage).unwrap();
+
+        assert_eq!(
+            database
+                .prune_epochs_older_than(MlsTime::from(1_000_030))
+                .unwrap(),
+            2
+        );
+
+        assert_eq!(storage.max_epoch_id(&old.group_id).unwrap(), None);
+        assert_eq!(storage.max_epoch_id(&new.group_id).unwrap(), Some(0));
+
+        // Snapshots are never pruned
+        assert_eq!(
+            storage.get_snapshot_data(&old.group_id).unwrap(),
+            Some(old.snapshot)
+        );
+    }
+
+    #[test]
+    pub fn tenants_are_isolated_test() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        let database = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path)).unwrap();
+
+        let alice = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path))
+            .unwrap()
+            .with_tenant(b"alice");
+
+        let bob = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path))
+            .unwrap()
+            .with_tenant(b"bob");
+
+        let group = GroupFixture::new()
+            .insert(&alice.group_state_storage().unwrap())
+            .unwrap();
+
+        KeyPackageFixture::new()
+            .insert(&mut alice.key_package_storage().unwrap())
+            .unwrap();
+
+        alice
+            .application_data_storage()
+            .unwrap()
+            .insert("key", b"alice")
+            .unwrap();
+
+        bob.application_data_storage()
+            .unwrap()
+            .insert("key", b"bob")
+            .unwrap();
+
+        let bob_groups = bob.group_state_storage().unwrap();
+        assert!(bob_groups.group_ids().unwrap().is_empty());
+        assert_eq!(bob.key_package_storage().unwrap().count().unwrap(), 0);
+
+        assert_eq!(
+            alice.group_state_storage().unwrap().group_ids().unwrap(),
+            vec![group.group_id]
+        );
+
+        assert_eq!(
+            bob.application_data_storage().unwrap().get("key").unwrap(),
+            Some(b"bob".to_vec())
+        );
+
+        let mut tenants = database.tenant_ids().unwrap();
+        tenants.sort();
+        assert_eq!(tenants, vec![b"alice".to_vec(), b"bob".to_vec()]);
+
+        // 1 group, 1 epoch, 1 key package and 1 application item
+        assert_eq!(database.delete_tenant(b"alice").unwrap(), 4);
+
+        assert_eq!(database.tenant_ids().unwrap(), vec![b"bob".to_vec()]);
+        assert_eq!(alice.key_package_storage().unwrap().count().unwrap(), 0);
+
+        assert_eq!(
+            bob.application_data_storage().unwrap().get("key").unwrap(),
+            Some(b"bob".to_vec())
+        );
+    }
+
+    #[test]
+    pub fn application_migrations_run_once_test() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        let database = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path))
+            .unwrap()
+            .with_migration(Migration::from_sql(
+                2,
+                "ALTER TABLE contact ADD COLUMN name TEXT;",
+            ))
+            .with_migration(Migration::from_sql(
+                1,
+                "CREATE TABLE contact (id INTEGER PRIMARY KEY);",
+            ));
+
+        let expected = SchemaVersion {
+            built_in: SCHEMA_VERSION,
+            application: Some(2),
+        };
+
+        assert_eq!(database.schema_version().unwrap(), expected);
+
+        // Running the migrations again would fail to create the table.
+        let _storage = database.application_data_storage().unwrap();
+        assert_eq!(database.schema_version().unwrap(), expected);
+
+        database
+            .create_connection()
+            .unwrap()
+            .execute("INSERT INTO contact (id, name) VALUES (1, 'alice')", [])
+            .unwrap();
+    }
+
+    #[test]
+    pub fn failed_application_migration_is_rolled_back_test() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        let database = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path))
+            .unwrap()
+            .with_migration(Migration::from_sql(
+                1,
+                "CREATE TABLE contact (id INTEGER PRIMARY KEY);",
+            ))
+            .with_migration(Migration::new(2, |transaction| {
+                transaction.execute_batch("INSERT INTO missing VALUES (1);")
+            }));
+
+        assert_matches!(
+            database.schema_version(),
+            Err(SqLiteDataStorageError::MigrationError(2, _))
+        );
+
+        // Built-in and application migrations of the failed transaction are
+        // rolled back.
+        let version =
+            crate::migration::schema_version(&rusqlite::Connection::open(&path).unwrap()).unwrap();
+
+        assert_eq!(
+            version,
+            SchemaVersion {
+                built_in: 0,
+                application: None
+            }
+        );
+    }
+
+    #[test]
+    pub fn v1_schema_is_migrated_test() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE mls_group (
+                    group_id BLOB PRIMARY KEY,
+                    snapshot BLOB NOT NULL
+                ) WITHOUT ROWID;
+                CREATE TABLE epoch (
+                    group_id BLOB,
+                    epoch_id INTEGER,
+                    epoch_data BLOB NOT NULL,
+                    FOREIGN KEY (group_id) REFERENCES mls_group (group_id) ON DELETE CASCADE
+                    PRIMARY KEY (group_id, epoch_id)
+                ) WITHOUT ROWID;
+                CREATE TABLE key_package (
+                    id BLOB PRIMARY KEY,
+                    expiration INTEGER,
+                    data BLOB NOT NULL
+                ) WITHOUT ROWID;
+                CREATE INDEX key_package_exp ON key_package (expiration);
+                CREATE TABLE psk (
+                    psk_id BLOB PRIMARY KEY,
+                    data BLOB NOT NULL
+                ) WITHOUT ROWID;
+                CREATE TABLE kvs (
+                    key TEXT PRIMARY KEY,
+                    value BLOB NOT NULL
+                ) WITHOUT ROWID;
+                INSERT INTO mls_group VALUES (x'01', x'02');
+                INSERT INTO epoch VALUES (x'01', 0, x'03');
+                INSERT INTO kvs VALUES ('key', x'04');
+                PRAGMA user_version = 1;",
+            )
+            .unwrap();
+
+        let database = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path)).unwrap();
+        let groups = database.group_state_storage().unwrap();
+
+        assert_eq!(groups.group_ids().unwrap(), vec![vec![1]]);
+
+        let epochs = database
+            .create_connection()
+            .unwrap()
+            .query_row(
+                "SELECT count(*) FROM epoch WHERE tenant_id = x'' AND group_id = x'01'",
+                [],
+                |row| row.get::<_, u64>(0),
+            )
+            .unwrap();
+
+        assert_eq!(epochs, 1);
+
+        assert_eq!(
+            database
+                .application_data_storage()
+                .unwrap()
+                .get("key")
+                .unwrap(),
+            Some(vec![4])
+        );
+
+        assert_eq!(database.tenant_ids().unwrap(), vec![Vec::<u8>::new()]);
     }
 
     #[test]
@@ -238,4 +848,271 @@ mod tests {
 
         assert_eq!(journal_mode, "truncate");
     }
+
+    #[test]
+    pub fn statement_cache_capacity_test() {
+        // Capacities below the number of statements used evict cached
+        // statements, 0 disables caching.
+        for capacity in [0, 1, 16] {
+            let database = SqLiteDataStorageEngine::new(MemoryStrategy)
+                .unwrap()
+                .with_statement_cache_capacity(capacity);
+
+            let groups = database.group_state_storage().unwrap();
+            let group = GroupFixture::new().with_epochs(5).insert(&groups).unwrap();
+            GroupFixture::new().insert(&groups).unwrap();
+
+            assert!(groups.group_ids().unwrap().contains(&group.group_id));
+
+            let storage = database.application_data_storage().unwrap();
+            let items = vec![
+                Item::new("a/1".into(), vec![1]),
+                Item::new("a/2".into(), vec![2]),
+            ];
+
+            assert_eq!(storage.transact_insert(&items).unwrap(), 2);
+            assert_eq!(storage.get("a/2").unwrap(), Some(vec![2]));
+            assert_eq!(storage.get_by_prefix("a/").unwrap().len(), 2);
+        }
+    }
+
+    #[test]
+    pub fn read_only_mode_test() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path))
+            .unwrap()
+            .application_data_storage()
+            .unwrap()
+            .insert("key", b"value")
+            .unwrap();
+
+        let contents = std::fs::read(&path).unwrap();
+
+        let storage = SqLiteDataStorageEngine::new(ReadOnlyFileConnectionStrategy::new(&path))
+            .unwrap()
+            .with_journal_mode(Some(crate::JournalMode::Wal))
+            .application_data_storage()
+            .unwrap();
+
+        assert_eq!(storage.get("key").unwrap(), Some(b"value".to_vec()));
+        assert!(storage.insert("other", b"value").is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), contents);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn custom_vfs_test() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        let registrations = Arc::new(AtomicUsize::new(0));
+        let counter = registrations.clone();
+
+        let strategy = FileConnectionStrategy::new(&path).with_vfs(
+            "unix-dotfile",
+            Some(Arc::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })),
+        );
+
+        SqLiteDataStorageEngine::new(strategy)
+            .unwrap()
+            .application_data_storage()
+            .unwrap()
+            .insert("key", b"value")
+            .unwrap();
+
+        assert!(registrations.load(Ordering::SeqCst) > 0);
+
+        let res = SqLiteDataStorageEngine::new(
+            FileConnectionStrategy::new(&path).with_vfs("missing-vfs", None),
+        )
+        .unwrap()
+        .application_data_storage();
+
+        assert_matches!(res, Err(SqLiteDataStorageError::SqlEngineError(_)));
+
+        let storage =
+            SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path).with_open_flags(
+                rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY
+                    | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            ))
+            .unwrap()
+            .application_data_storage()
+            .unwrap();
+
+        assert_eq!(storage.get("key").unwrap(), Some(b"value".to_vec()));
+        assert!(storage.insert("other", b"value").is_err());
+    }
+
+    #[test]
+    pub fn read_only_mode_requires_schema_test() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch("CREATE TABLE other (id INTEGER);")
+            .unwrap();
+
+        let res = SqLiteDataStorageEngine::new(ReadOnlyFileConnectionStrategy::new(&path))
+            .unwrap()
+            .application_data_storage();
+
+        assert_matches!(res, Err(SqLiteDataStorageError::ReadOnlySchemaMismatch(0)));
+
+        let missing = temp.path().join("missing.sqlite");
+
+        let res = SqLiteDataStorageEngine::new(ReadOnlyFileConnectionStrategy::new(&missing))
+            .unwrap()
+            .application_data_storage();
+
+        assert_matches!(res, Err(SqLiteDataStorageError::SqlEngineError(_)));
+        assert!(!missing.exists());
+    }
+
+    fn fill_application_storage(database: &SqLiteDataStorageEngine<FileConnectionStrategy>) {
+        let storage = database.application_data_storage().unwrap();
+
+        let items = (0..256)
+            .map(|i| crate::storage::Item::new(format!("key_{i}"), vec![0u8; 1024]))
+            .collect::<Vec<_>>();
+
+        storage.transact_insert(&items).unwrap();
+    }
+
+    #[test]
+    pub fn maintenance_releases_free_pages_test() {
+        let temp = tempdir().unwrap();
+
+        let database = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(
+            &temp.path().join("test_db.sqlite"),
+        ))
+        .unwrap()
+        .with_maintenance_config(MaintenanceConfig {
+            min_free_pages: 1,
+            ..Default::default()
+        });
+
+        fill_application_storage(&database);
+
+        let deleted = database
+            .application_data_storage()
+            .unwrap()
+            .delete_by_prefix("key_")
+            .unwrap();
+
+        assert_eq!(deleted, 256);
+
+        let report = database.maintenance().unwrap();
+
+        assert!(report.free_pages > 0);
+        assert_eq!(report.pages_released, report.free_pages);
+        assert!(!report.full_vacuum);
+        assert!(report.analyzed);
+
+        let report = database.maintenance().unwrap();
+
+        assert_eq!(report.free_pages, 0);
+        assert_eq!(report.pages_released, 0);
+    }
+
+    #[test]
+    pub fn maintenance_below_threshold_test() {
+        let temp = tempdir().unwrap();
+
+        let database = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(
+            &temp.path().join("test_db.sqlite"),
+        ))
+        .unwrap()
+        .with_maintenance_config(MaintenanceConfig {
+            min_free_pages: u64::MAX,
+            analyze: false,
+            ..Default::default()
+        });
+
+        fill_application_storage(&database);
+
+        database
+            .application_data_storage()
+            .unwrap()
+            .delete_by_prefix("key_")
+            .unwrap();
+
+        let report = database.maintenance().unwrap();
+
+        assert!(report.free_pages > 0);
+        assert_eq!(report.pages_released, 0);
+        assert!(!report.analyzed);
+    }
+
+    #[test]
+    pub fn vacuum_ignores_threshold_test() {
+        let temp = tempdir().unwrap();
+
+        let database = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(
+            &temp.path().join("test_db.sqlite"),
+        ))
+        .unwrap()
+        .with_maintenance_config(MaintenanceConfig {
+            min_free_pages: u64::MAX,
+            max_vacuum_pages: Some(1),
+            ..Default::default()
+        });
+
+        fill_application_storage(&database);
+
+        database
+            .application_data_storage()
+            .unwrap()
+            .delete_by_prefix("key_")
+            .unwrap();
+
+        let report = database.vacuum().unwrap();
+
+        assert!(report.free_pages > 0);
+        assert_eq!(report.pages_released, report.free_pages);
+    }
+
+    #[test]
+    pub fn auto_maintenance_after_deletes_test() {
+        let temp = tempdir().unwrap();
+
+        let database = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(
+            &temp.path().join("test_db.sqlite"),
+        ))
+        .unwrap()
+        .with_maintenance_config(MaintenanceConfig {
+            min_free_pages: 1,
+            auto_maintenance_after_deletes: Some(200),
+            ..Default::default()
+        });
+
+        fill_application_storage(&database);
+
+        let storage = database.application_data_storage().unwrap();
+        let connection = database.create_connection().unwrap();
+
+        let free_pages = || {
+            connection
+                .pragma_query_value(None, "freelist_count", |rows| rows.get::<_, u64>(0))
+                .unwrap()
+        };
+
+        // 111 rows deleted (key_1, key_10..key_19, key_100..key_199)
+        assert_eq!(storage.delete_by_prefix("key_1").unwrap(), 111);
+        assert!(free_pages() > 0);
+
+        // The remaining 145 rows push the total over the threshold
+        storage.delete_by_prefix("key_").unwrap();
+        assert_eq!(free_pages(), 0);
+    }
 }
@@ -0,0 +1,170 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rusqlite::Connection;
+
+use crate::SqLiteDataStorageError;
+
+const AUTO_VACUUM_INCREMENTAL: u32 = 2;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Thresholds used when running database maintenance.
+pub struct MaintenanceConfig {
+    /// Minimum number of free pages in the database file before any of them
+    /// are released back to the file system.
+    pub min_free_pages: u64,
+    /// Maximum number of free pages released by a single maintenance run.
+    /// `None` releases all free pages.
+    pub max_vacuum_pages: Option<u64>,
+    /// Run `ANALYZE` to refresh query planner statistics.
+    pub analyze: bool,
+    /// Automatically run maintenance once this many rows have been deleted
+    /// through storages created by the same engine. `None` disables
+    /// automatic maintenance.
+    pub auto_maintenance_after_deletes: Option<u64>,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            min_free_pages: 64,
+            max_vacuum_pages: None,
+            analyze: true,
+            auto_maintenance_after_deletes: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Result of a maintenance run.
+pub struct MaintenanceReport {
+    /// Number of free pages in the database file before maintenance.
+    pub free_pages: u64,
+    /// Number of pages released back to the file system.
+    pub pages_released: u64,
+    /// A full `VACUUM` was required to enable incremental vacuuming on a
+    /// database created without it.
+    pub full_vacuum: bool,
+    /// `ANALYZE` was run.
+    pub analyzed: bool,
+}
+
+pub(crate) fn enable_incremental_vacuum(
+    connection: &Connection,
+) -> Result<(), SqLiteDataStorageError> {
+    // Only takes effect on a database that has no tables yet.
+    connection
+        .pragma_update(None, "auto_vacuum", "INCREMENTAL")
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+}
+
+pub(crate) fn run_maintenance(
+    connection: &Connection,
+    config: &MaintenanceConfig,
+) -> Result<MaintenanceReport, SqLiteDataStorageError> {
+    let auto_vacuum = pragma_u64(connection, "auto_vacuum")?;
+    let free_pages = pragma_u64(connection, "freelist_count")?;
+
+    let mut report = MaintenanceReport {
+        free_pages,
+        pages_released: 0,
+        full_vacuum: false,
+        analyzed: false,
+    };
+
+    if free_pages > 0 && free_pages >= config.min_free_pages {
+        if auto_vacuum == AUTO_VACUUM_INCREMENTAL as u64 {
+            let sql = match config.max_vacuum_pages {
+                Some(pages) => format!("PRAGMA incremental_vacuum({pages});"),
+                None => "PRAGMA incremental_vacuum;".to_string(),
+            };
+
+            // Each step of the pragma releases a single page, so all rows
+            // must be read for the vacuum to complete.
+            connection
+                .prepare(&sql)
+                .and_then(|mut stmt| {
+                    let mut rows = stmt.query([])?;
+                    while rows.next()?.is_some() {}
+                    Ok(())
+                })
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+        } else {
+            // Switching the vacuum mode of an existing database requires a full
+            // rebuild, after which free pages are released incrementally.
+            enable_incremental_vacuum(connection)?;
+
+            connection
+                .execute_batch("VACUUM;")
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            report.full_vacuum = true;
+        }
+
+        report.pages_released =
+            free_pages.saturating_sub(pragma_u64(connection, "freelist_count")?);
+    }
+
+    if config.analyze {
+        connection
+            .execute_batch("ANALYZE;")
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        report.analyzed = true;
+    }
+
+    Ok(report)
+}
+
+fn pragma_u64(connection: &Connection, pragma: &str) -> Result<u64, SqLiteDataStorageError> {
+    connection
+        .pragma_query_value(None, pragma, |row| row.get::<_, u64>(0))
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+}
+
+/// Deleted row counter shared between all storages created by an engine.
+#[derive(Debug)]
+pub(crate) struct AutoMaintenance {
+    config: MaintenanceConfig,
+    deleted_rows: AtomicU64,
+}
+
+impl AutoMaintenance {
+    pub(crate) fn new(config: MaintenanceConfig) -> Option<Self> {
+        config
+            .auto_maintenance_after_deletes
+            .is_some()
+            .then(|| Self {
+                config,
+                deleted_rows: AtomicU64::new(0),
+            })
+    }
+
+    /// Record `rows` deleted rows and run maintenance on `connection` if the
+    /// configured threshold is reached. Must not be called inside of a
+    /// transaction.
+    pub(crate) fn record_deletes(
+        &self,
+        connection: &Connection,
+        rows: usize,
+    ) -> Result<(), SqLiteDataStorageError> {
+        let Some(threshold) = self.config.auto_maintenance_after_deletes else {
+            return Ok(());
+        };
+
+        if rows == 0 {
+            return Ok(());
+        }
+
+        let total = self.deleted_rows.fetch_add(rows as u64, Ordering::SeqCst) + rows as u64;
+
+        if total >= threshold && self.deleted_rows.swap(0, Ordering::SeqCst) >= threshold {
+            run_maintenance(connection, &self.config)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::{fmt, sync::Arc};
+
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+
+use crate::{maintenance, SqLiteDataStorageError};
+
+/// Version of the tables created by this crate.
+pub(crate) const SCHEMA_VERSION: u32 = 6;
+
+// The migration at index `i` upgrades the built-in schema from version
+// `i + 1` to version `i + 2`.
+const BUILT_IN_MIGRATIONS: &[&[&str]] = &[
+    MIGRATE_V1_TO_V2,
+    &[TABLES_V3],
+    &[TABLES_V4],
+    &[TABLES_V5],
+    &[TABLES_V6],
+];
+
+type MigrationFn = dyn Fn(&Transaction<'_>) -> rusqlite::Result<()> + Send + Sync;
+
+#[derive(Clone)]
+/// Application defined migration, run when opening a connection to a database
+/// whose application schema is older than [`Migration::version`].
+///
+/// Pending application migrations run in ascending order of version, in the
+/// same transaction as the pending built-in migrations. If any of them fails,
+/// the database is left unchanged.
+pub struct Migration {
+    version: u32,
+    apply: Arc<MigrationFn>,
+}
+
+impl Migration {
+    /// Migration to application schema `version`, run by `apply`.
+    pub fn new<F>(version: u32, apply: F) -> Self
+    where
+        F: Fn(&Transaction<'_>) -> rusqlite::Result<()> + Send + Sync + 'static,
+    {
+        Self {
+            version,
+            apply: Arc::new(apply),
+        }
+    }
+
+    /// Migration to application schema `version` running the statements of
+    /// `sql`.
+    pub fn from_sql(version: u32, sql: impl Into<String>) -> Self {
+        let sql = sql.into();
+        Self::new(version, move |transaction| transaction.execute_batch(&sql))
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+impl fmt::Debug for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migration")
+            .field("version", &self.version)
+            .finish()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Schema version of a database.
+pub struct SchemaVersion {
+    /// Version of the tables created by this crate.
+    pub built_in: u32,
+    /// Version of the last application [`Migration`] run on the database, if
+    /// any.
+    pub application: Option<u32>,
+}
+
+impl SchemaVersion {
+    fn is_pending(&self, migrations: &[Migration]) -> bool {
+        self.built_in < SCHEMA_VERSION
+            || matches!(migrations.last(), Some(last) if Some(last.version) > self.application)
+    }
+}
+
+pub(crate) fn schema_version(
+    connection: &Connection,
+) -> Result<SchemaVersion, SqLiteDataStorageError> {
+    let built_in = connection
+        .pragma_query_value(None, "user_version", |rows| rows.get::<_, u32>(0))
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+    // Application migrations are tracked since version 6.
+    let application = if built_in >= 6 {
+        connection
+            .query_row("SELECT MAX(version) FROM app_migration", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?
+    } else {
+        None
+    };
+
+    Ok(SchemaVersion {
+        built_in,
+        application,
+    })
+}
+
+/// Run the pending built-in migrations and the pending `migrations`, which
+/// must be sorted by version, in a single transaction.
+pub(crate) fn migrate(
+    connection: &mut Connection,
+    migrations: &[Migration],
+) -> Result<(), SqLiteDataStorageError> {
+    let version = schema_version(connection)?;
+
+    if !version.is_pending(migrations) {
+        return Ok(());
+    }
+
+    if version.built_in == 0 {
+        maintenance::enable_incremental_vacuum(connection)?;
+    }
+
+    let transaction = connection
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+    // Another connection may have migrated the database in the meantime.
+    let version = schema_version(&transaction)?;
+    let mut built_in = version.built_in;
+
+    // New databases skip the migration of version 1 tables.
+    if built_in == 0 {
+        transaction
+            .execute_batch(TABLES_V2)
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        built_in = 2;
+    }
+
+    if built_in < SCHEMA_VERSION {
+        for sql in BUILT_IN_MIGRATIONS[built_in as usize - 1..]
+            .iter()
+            .copied()
+            .flatten()
+        {
+            transaction
+                .execute_batch(sql)
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+        }
+
+        transaction
+            .pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+    }
+
+    for migration in migrations
+        .iter()
+        .filter(|migration| Some(migration.version) > version.application)
+    {
+        (migration.apply)(&transaction)
+            .and_then(|_| {
+                transaction
+                    .execute(
+                        "INSERT INTO app_migration (version) VALUES (?)",
+                        [migration.version],
+                    )
+                    .map(|_| ())
+            })
+            .map_err(|e| SqLiteDataStorageError::MigrationError(migration.version, e.into()))?;
+    }
+
+    transaction
+        .commit()
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+}
+
+const TABLES_V2: &str = "CREATE TABLE mls_group (
+        tenant_id BLOB NOT NULL,
+        group_id BLOB,
+        snapshot BLOB NOT NULL,
+        PRIMARY KEY (tenant_id, group_id)
+    ) WITHOUT ROWID;
+    CREATE TABLE epoch (
+        tenant_id BLOB NOT NULL,
+        group_id BLOB,
+        epoch_id INTEGER,
+        epoch_data BLOB NOT NULL,
+        FOREIGN KEY (tenant_id, group_id) REFERENCES mls_group (tenant_id, group_id) ON DELETE CASCADE
+        PRIMARY KEY (tenant_id, group_id, epoch_id)
+    ) WITHOUT ROWID;
+    CREATE TABLE key_package (
+        tenant_id BLOB NOT NULL,
+        id BLOB,
+        expiration INTEGER,
+        data BLOB NOT NULL,
+        PRIMARY KEY (tenant_id, id)
+    ) WITHOUT ROWID;
+    CREATE INDEX key_package_exp ON key_package (tenant_id, expiration);
+    CREATE TABLE psk (
+        tenant_id BLOB NOT NULL,
+        psk_id BLOB,
+        data BLOB NOT NULL,
+        PRIMARY KEY (tenant_id, psk_id)
+    ) WITHOUT ROWID;
+    CREATE TABLE kvs (
+        tenant_id BLOB NOT NULL,
+        key TEXT,
+        value BLOB NOT NULL,
+        PRIMARY KEY (tenant_id, key)
+    ) WITHOUT ROWID;";
+
+// Data stored before tenants were supported belongs to the empty tenant id.
+const MIGRATE_V1_TO_V2: &[&str] = &[
+    "DROP INDEX key_package_exp;
+    ALTER TABLE mls_group RENAME TO mls_group_v1;
+    ALTER TABLE epoch RENAME TO epoch_v1;
+    ALTER TABLE key_package RENAME TO key_package_v1;
+    ALTER TABLE psk RENAME TO psk_v1;
+    ALTER TABLE kvs RENAME TO kvs_v1;",
+    TABLES_V2,
+    "INSERT INTO mls_group SELECT x'', group_id, snapshot FROM mls_group_v1;
+    INSERT INTO epoch SELECT x'', group_id, epoch_id, epoch_data FROM epoch_v1;
+    INSERT INTO key_package SELECT x'', id, expiration, data FROM key_package_v1;
+    INSERT INTO psk SELECT x'', psk_id, data FROM psk_v1;
+    INSERT INTO kvs SELECT x'', key, value FROM kvs_v1;
+    DROP TABLE epoch_v1;
+    DROP TABLE mls_group_v1;
+    DROP TABLE key_package_v1;
+    DROP TABLE psk_v1;
+    DROP TABLE kvs_v1;",
+];
+
+const TABLES_V3: &str = "CREATE TABLE group_id_map (
+        tenant_id BLOB NOT NULL,
+        external_id BLOB,
+        group_id BLOB NOT NULL,
+        PRIMARY KEY (tenant_id, external_id)
+    ) WITHOUT ROWID;
+    CREATE INDEX group_id_map_group ON group_id_map (tenant_id, group_id);";
+
+// Epochs stored before version 4 have no creation time.
+const TABLES_V4: &str = "ALTER TABLE epoch ADD COLUMN created_at INTEGER;
+    CREATE INDEX epoch_created ON epoch (tenant_id, created_at);";
+
+// Application data stored before version 5 never expires.
+const TABLES_V5: &str = "ALTER TABLE kvs ADD COLUMN expiration INTEGER;
+    CREATE INDEX kvs_exp ON kvs (tenant_id, expiration);";
+
+const TABLES_V6: &str = "CREATE TABLE app_migration (
+        version INTEGER PRIMARY KEY
+    ) WITHOUT ROWID;";
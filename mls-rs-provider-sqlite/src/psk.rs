@@ -2,63 +2,83 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::SqLiteDataStorageError;
+use crate::{maintenance::AutoMaintenance, recovery::SharedConnection, SqLiteDataStorageError};
 use mls_rs_core::psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage};
 use rusqlite::{params, Connection, OptionalExtension};
-use std::{
-    ops::Deref,
-    sync::{Arc, Mutex},
-};
+use std::{ops::Deref, sync::Arc};
 
 #[derive(Debug, Clone)]
 /// SQLite storage for MLS pre-shared keys.
 pub struct SqLitePreSharedKeyStorage {
-    connection: Arc<Mutex<Connection>>,
+    connection: SharedConnection,
+    tenant_id: Vec<u8>,
+    auto_maintenance: Option<Arc<AutoMaintenance>>,
 }
 
 impl SqLitePreSharedKeyStorage {
-    pub(crate) fn new(connection: Connection) -> SqLitePreSharedKeyStorage {
+    pub(crate) fn new(
+        connection: SharedConnection,
+        tenant_id: Vec<u8>,
+        auto_maintenance: Option<Arc<AutoMaintenance>>,
+    ) -> SqLitePreSharedKeyStorage {
         SqLitePreSharedKeyStorage {
-            connection: Arc::new(Mutex::new(connection)),
+            connection,
+            tenant_id,
+            auto_maintenance,
         }
     }
 
     /// Insert a pre-shared key into storage.
     pub fn insert(&self, psk_id: &[u8], psk: &PreSharedKey) -> Result<(), SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        // Upsert into the database
-        connection
-            .execute(
-                "INSERT INTO psk (psk_id, data) VALUES (?,?) ON CONFLICT(psk_id) DO UPDATE SET data=excluded.data",
-                params![psk_id, psk.deref()],
-            )
-            .map(|_| ())
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.connection.with(|connection| {
+            // Upsert into the database
+            connection
+                .prepare_cached(
+                    "INSERT INTO psk (tenant_id, psk_id, data) VALUES (?,?,?) ON CONFLICT(tenant_id, psk_id) DO UPDATE SET data=excluded.data",
+                )
+                .and_then(|mut stmt| stmt.execute(params![self.tenant_id, psk_id, psk.deref()]))
+                .map(|_| ())
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
     }
 
     /// Get a pre-shared key from storage based on a unique id.
     pub fn get(&self, psk_id: &[u8]) -> Result<Option<PreSharedKey>, SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .query_row(
-                "SELECT data FROM psk WHERE psk_id = ?",
-                params![psk_id],
-                |row| Ok(PreSharedKey::new(row.get(0)?)),
-            )
-            .optional()
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.connection.with(|connection| {
+            connection
+                .prepare_cached("SELECT data FROM psk WHERE tenant_id = ? AND psk_id = ?")
+                .and_then(|mut stmt| {
+                    stmt.query_row(params![self.tenant_id, psk_id], |row| {
+                        Ok(PreSharedKey::new(row.get(0)?))
+                    })
+                })
+                .optional()
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        })
     }
 
     /// Delete a pre-shared key from storage based on a unique id.
     pub fn delete(&self, psk_id: &[u8]) -> Result<(), SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
+        self.connection.with(|connection| {
+            let deleted = connection
+                .execute(
+                    "DELETE FROM psk WHERE tenant_id = ? AND psk_id = ?",
+                    params![self.tenant_id, psk_id],
+                )
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            self.record_deletes(connection, deleted)
+        })
+    }
 
-        connection
-            .execute("DELETE FROM psk WHERE psk_id = ?", params![psk_id])
-            .map(|_| ())
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    fn record_deletes(
+        &self,
+        connection: &Connection,
+        deleted: usize,
+    ) -> Result<(), SqLiteDataStorageError> {
+        self.auto_maintenance
+            .as_ref()
+            .map_or(Ok(()), |auto| auto.record_deletes(connection, deleted))
     }
 }
 
@@ -0,0 +1,475 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::{
+    fmt::{self, Debug},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rusqlite::{Connection, ErrorCode};
+
+use crate::{
+    connection_strategy::ConnectionStrategy, open_connection, ConnectionOptions,
+    SqLiteDataStorageError,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Retries of storage operations failing because the database is busy or
+/// locked by another connection.
+///
+/// The delay before retry `n` is `initial_backoff * 2^n`, capped at
+/// `max_backoff`.
+pub struct BusyRetry {
+    /// Maximum number of retries of a single operation.
+    pub max_retries: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Maximum delay between two retries.
+    pub max_backoff: Duration,
+}
+
+impl Default for BusyRetry {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl BusyRetry {
+    fn backoff(&self, retry: u32) -> Option<Duration> {
+        (retry < self.max_retries).then(|| {
+            self.initial_backoff
+                .checked_mul(1 << retry.min(31))
+                .unwrap_or(self.max_backoff)
+                .min(self.max_backoff)
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Kind of failure reported to a [`FailureHandler`].
+pub enum FailureKind {
+    /// The database file is malformed or is not a database.
+    Corruption,
+    /// The operating system reported an I/O error.
+    Io,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// What a storage does after a failure reported to a [`FailureHandler`].
+pub enum RecoveryAction {
+    /// Reopen the connection of the storage and retry the operation.
+    Reopen,
+    /// Replace the database file, and therefore the data of all tenants,
+    /// with a copy of the backup file at the given path, then reopen the
+    /// connection of the storage and retry the operation.
+    ///
+    /// The backup must be readable with the same connection settings, e.g.
+    /// the same SQLCipher key. Other connections to the database should be
+    /// idle while it is restored. Connection strategies without a database
+    /// file, see [`ConnectionStrategy::database_path`], return the error
+    /// instead.
+    RestoreFromBackup(PathBuf),
+    /// Return the error.
+    Fail,
+}
+
+/// Hook deciding how storages recover from a corrupted database or from I/O
+/// errors.
+///
+/// The handler is called at most once per failed operation. If the operation
+/// fails again after recovery, the error is returned.
+pub trait FailureHandler: Send + Sync + Debug {
+    fn on_failure(&self, kind: FailureKind, error: &SqLiteDataStorageError) -> RecoveryAction;
+}
+
+#[derive(Clone, Debug, Default)]
+/// Recovery from transient and persistent failures of storage operations,
+/// applied by all storages created by an engine.
+pub struct RecoveryConfig {
+    busy_retry: Option<BusyRetry>,
+    failure_handler: Option<Arc<dyn FailureHandler>>,
+}
+
+impl RecoveryConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retry operations failing with `SQLITE_BUSY` or `SQLITE_LOCKED`.
+    /// Without retries, these errors are returned immediately.
+    pub fn with_busy_retry(self, busy_retry: BusyRetry) -> Self {
+        Self {
+            busy_retry: Some(busy_retry),
+            ..self
+        }
+    }
+
+    /// Call `handler` when an operation fails because the database is
+    /// corrupted or because of an I/O error. Without a handler, these errors
+    /// are returned immediately.
+    pub fn with_failure_handler<H: FailureHandler + 'static>(self, handler: H) -> Self {
+        Self {
+            failure_handler: Some(Arc::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn busy_retry(&self) -> Option<&BusyRetry> {
+        self.busy_retry.as_ref()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Recovery {
+    config: RecoveryConfig,
+    connection_strategy: Arc<dyn ConnectionStrategy + Send + Sync>,
+    options: ConnectionOptions,
+}
+
+impl Debug for Recovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recovery")
+            .field("config", &self.config)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl Recovery {
+    pub(crate) fn new(
+        config: RecoveryConfig,
+        connection_strategy: Arc<dyn ConnectionStrategy + Send + Sync>,
+    ) -> Self {
+        Self {
+            config,
+            connection_strategy,
+            options: ConnectionOptions::default(),
+        }
+    }
+
+    /// Reopen connections with `options`.
+    pub(crate) fn with_options(self, options: ConnectionOptions) -> Self {
+        Self { options, ..self }
+    }
+
+    fn recover(
+        &self,
+        connection: &mut Connection,
+        kind: FailureKind,
+        error: SqLiteDataStorageError,
+    ) -> Result<(), SqLiteDataStorageError> {
+        let action = match &self.config.failure_handler {
+            Some(handler) => handler.on_failure(kind, &error),
+            None => RecoveryAction::Fail,
+        };
+
+        match action {
+            RecoveryAction::Reopen => self.reopen(connection),
+            RecoveryAction::RestoreFromBackup(backup) => {
+                let Some(database) = self.connection_strategy.database_path() else {
+                    return Err(error);
+                };
+
+                // Close the database file before replacing it
+                *connection = Connection::open_in_memory()
+                    .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+                restore_from_backup(database, &backup)?;
+                self.reopen(connection)
+            }
+            RecoveryAction::Fail => Err(error),
+        }
+    }
+
+    fn reopen(&self, connection: &mut Connection) -> Result<(), SqLiteDataStorageError> {
+        *connection = open_connection(&*self.connection_strategy, &self.options)?;
+        Ok(())
+    }
+}
+
+/// Connection shared by the clones of a storage.
+#[derive(Clone, Debug)]
+pub(crate) struct SharedConnection {
+    connection: Arc<Mutex<Connection>>,
+    recovery: Option<Arc<Recovery>>,
+}
+
+impl SharedConnection {
+    pub(crate) fn new(connection: Connection, recovery: Option<Arc<Recovery>>) -> Self {
+        Self {
+            connection: Arc::new(Mutex::new(connection)),
+            recovery,
+        }
+    }
+
+    /// Run `operation` with the connection, retrying it according to the
+    /// recovery config of the engine.
+    ///
+    /// Transactions of a failed attempt are rolled back when dropped, so
+    /// `operation` is retried from a clean state.
+    pub(crate) fn with<T, F>(&self, mut operation: F) -> Result<T, SqLiteDataStorageError>
+    where
+        F: FnMut(&mut Connection) -> Result<T, SqLiteDataStorageError>,
+    {
+        let mut connection = self.connection.lock().unwrap();
+
+        let Some(recovery) = &self.recovery else {
+            return operation(&mut connection);
+        };
+
+        let mut busy_retries = 0;
+        let mut recovered = false;
+
+        loop {
+            let error = match operation(&mut connection) {
+                Ok(res) => return Ok(res),
+                Err(error) => error,
+            };
+
+            match classify(&error) {
+                Some(Failure::Busy) => {
+                    let backoff = recovery
+                        .config
+                        .busy_retry
+                        .as_ref()
+                        .and_then(|retry| retry.backoff(busy_retries));
+
+                    let Some(backoff) = backoff else {
+                        return Err(error);
+                    };
+
+                    busy_retries += 1;
+                    std::thread::sleep(backoff);
+                }
+                Some(Failure::Handled(kind)) if !recovered => {
+                    recovered = true;
+                    recovery.recover(&mut connection, kind, error)?;
+                }
+                _ => return Err(error),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap()
+    }
+}
+
+enum Failure {
+    Busy,
+    Handled(FailureKind),
+}
+
+fn classify(error: &SqLiteDataStorageError) -> Option<Failure> {
+    let SqLiteDataStorageError::SqlEngineError(error) = error else {
+        return None;
+    };
+
+    match error
+        .downcast_ref::<rusqlite::Error>()?
+        .sqlite_error_code()?
+    {
+        ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => Some(Failure::Busy),
+        ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => {
+            Some(Failure::Handled(FailureKind::Corruption))
+        }
+        ErrorCode::SystemIoFailure => Some(Failure::Handled(FailureKind::Io)),
+        _ => None,
+    }
+}
+
+// Stale journal files would be applied to the restored database.
+const JOURNAL_SUFFIXES: &[&str] = &["-journal", "-wal", "-shm"];
+
+fn restore_from_backup(database: &Path, backup: &Path) -> Result<(), SqLiteDataStorageError> {
+    let restore_error = |e: std::io::Error| SqLiteDataStorageError::SqlEngineError(e.into());
+
+    JOURNAL_SUFFIXES.iter().try_for_each(|suffix| {
+        let mut journal = database.as_os_str().to_owned();
+        journal.push(suffix);
+
+        match std::fs::remove_file(journal) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(restore_error(e)),
+            _ => Ok(()),
+        }
+    })?;
+
+    std::fs::copy(backup, database)
+        .map(|_| ())
+        .map_err(restore_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use assert_matches::assert_matches;
+    use tempfile::tempdir;
+
+    use crate::{
+        connection_strategy::{FileConnectionStrategy, MemoryStrategy},
+        SqLiteDataStorageEngine, SqLiteDataStorageError,
+    };
+
+    use super::{BusyRetry, FailureHandler, FailureKind, RecoveryAction, RecoveryConfig};
+
+    #[derive(Debug)]
+    struct TestHandler {
+        action: RecoveryAction,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl FailureHandler for TestHandler {
+        fn on_failure(&self, kind: FailureKind, _: &SqLiteDataStorageError) -> RecoveryAction {
+            assert_eq!(kind, FailureKind::Corruption);
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.action.clone()
+        }
+    }
+
+    #[test]
+    fn backoff_is_exponential_and_capped() {
+        let retry = BusyRetry {
+            max_retries: 4,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        };
+
+        let delays = (0..5).map(|n| retry.backoff(n)).collect::<Vec<_>>();
+
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_millis(10)),
+                Some(Duration::from_millis(20)),
+                Some(Duration::from_millis(40)),
+                Some(Duration::from_millis(50)),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn busy_operations_are_retried() {
+        let retry = BusyRetry {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        };
+
+        let connection = SqLiteDataStorageEngine::new(MemoryStrategy)
+            .unwrap()
+            .with_recovery(RecoveryConfig::new().with_busy_retry(retry))
+            .shared_connection()
+            .unwrap();
+
+        let busy = || {
+            let error = rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY);
+            SqLiteDataStorageError::SqlEngineError(
+                rusqlite::Error::SqliteFailure(error, None).into(),
+            )
+        };
+
+        let mut attempts = 0;
+
+        let res = connection.with(|_| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(busy())
+            } else {
+                Ok(attempts)
+            }
+        });
+
+        assert_eq!(res.unwrap(), 3);
+
+        attempts = 0;
+
+        let res = connection.with(|_| {
+            attempts += 1;
+            Err::<(), _>(busy())
+        });
+
+        assert_matches!(res, Err(SqLiteDataStorageError::SqlEngineError(_)));
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn corrupted_database_is_restored_from_backup() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+        let backup = temp.path().join("backup.sqlite");
+
+        let engine = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path)).unwrap();
+
+        engine
+            .application_data_storage()
+            .unwrap()
+            .insert("key", b"backed up")
+            .unwrap();
+
+        std::fs::copy(&path, &backup).unwrap();
+
+        let calls = Arc::new(AtomicU32::new(0));
+
+        let handler = TestHandler {
+            action: RecoveryAction::RestoreFromBackup(backup),
+            calls: calls.clone(),
+        };
+
+        let storage = engine
+            .with_recovery(RecoveryConfig::new().with_failure_handler(handler))
+            .application_data_storage()
+            .unwrap();
+
+        storage.insert("key", b"lost").unwrap();
+
+        // Overwrite the database file while the storage is connected
+        std::fs::write(&path, vec![0xab; 4096]).unwrap();
+
+        assert_eq!(storage.get("key").unwrap(), Some(b"backed up".to_vec()));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn corruption_is_returned_when_handler_fails() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test_db.sqlite");
+
+        let calls = Arc::new(AtomicU32::new(0));
+
+        let handler = TestHandler {
+            action: RecoveryAction::Fail,
+            calls: calls.clone(),
+        };
+
+        let storage = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(&path))
+            .unwrap()
+            .with_recovery(RecoveryConfig::new().with_failure_handler(handler))
+            .application_data_storage()
+            .unwrap();
+
+        std::fs::write(&path, vec![0xab; 4096]).unwrap();
+
+        assert_matches!(
+            storage.get("key"),
+            Err(SqLiteDataStorageError::SqlEngineError(_))
+        );
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
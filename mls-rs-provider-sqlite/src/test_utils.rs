@@ -2,9 +2,176 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use mls_rs_core::{
+    crypto::HpkeSecretKey, group::EpochRecord, key_package::KeyPackageData, time::MlsTime,
+};
 use rand::RngCore;
+
+use crate::{
+    storage::{SqLiteGroupStateStorage, SqLiteKeyPackageStorage},
+    Clock, SqLiteDataStorageError,
+};
+
 pub fn gen_rand_bytes(size: usize) -> Vec<u8> {
     let mut bytes: Vec<u8> = vec![0; size];
     rand::rng().fill_bytes(&mut bytes);
     bytes
 }
+
+#[derive(Clone, Debug, Default)]
+/// [`Clock`] that only moves when told to. Clones share the same time.
+pub struct FakeClock {
+    seconds: Arc<AtomicU64>,
+}
+
+impl FakeClock {
+    pub fn new(time: MlsTime) -> Self {
+        Self {
+            seconds: Arc::new(AtomicU64::new(time.seconds_since_epoch())),
+        }
+    }
+
+    pub fn set(&self, time: MlsTime) {
+        self.seconds
+            .store(time.seconds_since_epoch(), Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.seconds.fetch_add(duration.as_secs(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> MlsTime {
+        MlsTime::from(self.seconds.load(Ordering::SeqCst))
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Builder of key packages with random content and a chosen expiration.
+pub struct KeyPackageFixture {
+    id: Vec<u8>,
+    expiration: u64,
+}
+
+impl Default for KeyPackageFixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyPackageFixture {
+    /// Key package with a random id that never expires.
+    pub fn new() -> Self {
+        Self {
+            id: gen_rand_bytes(32),
+            expiration: u64::MAX,
+        }
+    }
+
+    pub fn with_id(self, id: Vec<u8>) -> Self {
+        Self { id, ..self }
+    }
+
+    pub fn with_expiration(self, expiration: MlsTime) -> Self {
+        Self {
+            expiration: expiration.seconds_since_epoch(),
+            ..self
+        }
+    }
+
+    /// Expire `lifetime` after the current time of `clock`.
+    pub fn with_lifetime(self, clock: &dyn Clock, lifetime: Duration) -> Self {
+        self.with_expiration(clock.now() + lifetime)
+    }
+
+    pub fn build(&self) -> (Vec<u8>, KeyPackageData) {
+        let key_package = KeyPackageData::new(
+            gen_rand_bytes(256),
+            HpkeSecretKey::from(gen_rand_bytes(256)),
+            HpkeSecretKey::from(gen_rand_bytes(256)),
+            self.expiration,
+        );
+
+        (self.id.clone(), key_package)
+    }
+
+    /// Insert the key package into `storage` and return its id.
+    pub fn insert(
+        &self,
+        storage: &mut SqLiteKeyPackageStorage,
+    ) -> Result<Vec<u8>, SqLiteDataStorageError> {
+        let (id, key_package) = self.build();
+        storage.insert(&id, key_package)?;
+
+        Ok(id)
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Builder of stored groups with random snapshots and epochs.
+pub struct GroupFixture {
+    group_id: Vec<u8>,
+    epochs: u64,
+}
+
+impl Default for GroupFixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Data written to storage by [`GroupFixture::insert`].
+pub struct StoredGroupFixture {
+    pub group_id: Vec<u8>,
+    pub snapshot: Vec<u8>,
+    pub epochs: Vec<EpochRecord>,
+}
+
+impl GroupFixture {
+    /// Group with a random id and a single epoch.
+    pub fn new() -> Self {
+        Self {
+            group_id: gen_rand_bytes(32),
+            epochs: 1,
+        }
+    }
+
+    pub fn with_group_id(self, group_id: Vec<u8>) -> Self {
+        Self { group_id, ..self }
+    }
+
+    /// Store epochs `0..epochs`. Epochs beyond the retention limit of the
+    /// storage are deleted as they would be for a real group.
+    pub fn with_epochs(self, epochs: u64) -> Self {
+        Self { epochs, ..self }
+    }
+
+    pub fn insert(
+        &self,
+        storage: &SqLiteGroupStateStorage,
+    ) -> Result<StoredGroupFixture, SqLiteDataStorageError> {
+        let snapshot = gen_rand_bytes(1024);
+
+        let epochs = (0..self.epochs)
+            .map(|id| EpochRecord::new(id, gen_rand_bytes(256)))
+            .collect::<Vec<_>>();
+
+        storage.update_group_state(&self.group_id, snapshot.clone(), epochs.clone(), vec![])?;
+
+        Ok(StoredGroupFixture {
+            group_id: self.group_id.clone(),
+            snapshot,
+            epochs,
+        })
+    }
+}
@@ -774,6 +774,11 @@ impl Group {
             group::ReceivedMessage::GroupInfo(_) => Ok(ReceivedMessage::GroupInfo),
             group::ReceivedMessage::Welcome => Ok(ReceivedMessage::Welcome),
             group::ReceivedMessage::KeyPackage(_) => Ok(ReceivedMessage::KeyPackage),
+            group::ReceivedMessage::PredecessorApplicationMessage(application_message) => {
+                let sender = Arc::new(application_message.sender.into());
+                let data = application_message.message.data().to_vec();
+                Ok(ReceivedMessage::ApplicationMessage { sender, data })
+            }
         }
     }
 }
@@ -0,0 +1,390 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Adversarial decoding corpus and a harness checking that every input is
+//! rejected within a memory and time budget.
+//!
+//! The corpus targets [`MlsMessage::from_bytes_with_limits`]: oversized
+//! vectors and opaque fields, length prefixes inconsistent with the data
+//! they wrap, truncated structures and duplicated extensions. The MLS
+//! encoding has no recursive types, so deeply nested inputs nest the
+//! length-prefixed collections of the schema as far as it allows.
+//!
+//! Memory is measured with [`CountingAllocator`], which must be installed
+//! as the global allocator of the test binary:
+//!
+//! ```ignore
+//! use mls_rs::adversarial::{check_corpus, CountingAllocator, DecodeBudget};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+//!
+//! #[test]
+//! fn limits_hold() {
+//!     let violations = check_corpus(&DecodeBudget::default(), Some(&ALLOCATOR));
+//!     assert!(violations.is_empty(), "{violations:?}");
+//! }
+//! ```
+
+use alloc::{format, string::String, vec, vec::Vec};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::time::{Duration, Instant};
+
+use mls_rs_codec::{DecodeLimits, MlsEncode, VarInt};
+
+use crate::MlsMessage;
+
+/// Largest input generated for collections and opaque fields that exceed
+/// the decode limits. Limits above this value are not exercised by
+/// [`corpus`].
+pub const MAX_GENERATED_LEN: usize = 1 << 24;
+
+const MLS_10: u16 = 1;
+const WIRE_FORMAT_PUBLIC_MESSAGE: u16 = 1;
+const WIRE_FORMAT_WELCOME: u16 = 3;
+const WIRE_FORMAT_KEY_PACKAGE: u16 = 5;
+const CIPHER_SUITE: u16 = 1;
+
+/// An input of the adversarial corpus.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct AdversarialInput {
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+impl AdversarialInput {
+    fn new(name: impl Into<String>, bytes: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            bytes,
+        }
+    }
+}
+
+/// Budget every input of the corpus must be rejected within.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DecodeBudget {
+    /// Limits passed to [`MlsMessage::from_bytes_with_limits`].
+    pub limits: DecodeLimits,
+    /// Maximum time spent decoding a single input.
+    pub max_time: Duration,
+    /// Maximum number of bytes allocated at once while decoding a single
+    /// input, in addition to the input itself.
+    pub max_memory: usize,
+}
+
+impl DecodeBudget {
+    pub fn new(limits: DecodeLimits, max_time: Duration, max_memory: usize) -> Self {
+        Self {
+            limits,
+            max_time,
+            max_memory,
+        }
+    }
+}
+
+impl Default for DecodeBudget {
+    /// [`MlsMessage::DEFAULT_DECODE_LIMITS`], one second and 256 MiB.
+    ///
+    /// The memory budget accounts for collections of up to the maximum
+    /// number of elements allowed by the limits, which are allocated before
+    /// the next element is rejected.
+    fn default() -> Self {
+        Self::new(
+            MlsMessage::DEFAULT_DECODE_LIMITS,
+            Duration::from_secs(1),
+            1 << 28,
+        )
+    }
+}
+
+/// Way an input of the corpus exceeded its [`DecodeBudget`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BudgetViolation {
+    /// The input was decoded successfully.
+    Accepted { input: String },
+    /// Decoding took longer than [`DecodeBudget::max_time`].
+    TooSlow { input: String, elapsed: Duration },
+    /// Decoding allocated more than [`DecodeBudget::max_memory`].
+    TooMuchMemory { input: String, allocated: usize },
+}
+
+/// Global allocator forwarding to [`System`] and recording the peak number
+/// of bytes allocated, see the [module documentation](self).
+#[derive(Debug, Default)]
+pub struct CountingAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl CountingAllocator {
+    pub const fn new() -> Self {
+        Self {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    /// Reset the peak to the number of bytes currently allocated, which is
+    /// returned.
+    pub fn reset_peak(&self) -> usize {
+        let current = self.current.load(Ordering::SeqCst);
+        self.peak.store(current, Ordering::SeqCst);
+        current
+    }
+
+    /// Peak number of bytes allocated since the last call to
+    /// [`reset_peak`](Self::reset_peak).
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+
+    fn allocated(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::SeqCst) + size;
+        self.peak.fetch_max(current, Ordering::SeqCst);
+    }
+
+    fn deallocated(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::SeqCst);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+
+        if !ptr.is_null() {
+            self.allocated(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+
+        if !ptr.is_null() {
+            self.allocated(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.deallocated(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+
+        if !new_ptr.is_null() {
+            self.deallocated(layout.size());
+            self.allocated(new_size);
+        }
+
+        new_ptr
+    }
+}
+
+/// Decode every input of [`corpus`] for `budget.limits` and report the
+/// inputs that were accepted or exceeded the budget.
+///
+/// Memory is only checked if `allocator` is provided, in which case it must
+/// be the global allocator. Other threads allocating while the corpus is
+/// checked are counted as well.
+pub fn check_corpus(
+    budget: &DecodeBudget,
+    allocator: Option<&CountingAllocator>,
+) -> Vec<BudgetViolation> {
+    corpus(&budget.limits)
+        .into_iter()
+        .filter_map(|input| check_input(budget, allocator, input))
+        .collect()
+}
+
+fn check_input(
+    budget: &DecodeBudget,
+    allocator: Option<&CountingAllocator>,
+    input: AdversarialInput,
+) -> Option<BudgetViolation> {
+    let baseline = allocator.map(CountingAllocator::reset_peak);
+    let start = Instant::now();
+
+    let accepted = MlsMessage::from_bytes_with_limits(&input.bytes, &budget.limits).is_ok();
+
+    let elapsed = start.elapsed();
+    let allocated = allocator.zip(baseline).map(|(a, b)| a.peak() - b);
+
+    if accepted {
+        Some(BudgetViolation::Accepted { input: input.name })
+    } else if elapsed > budget.max_time {
+        Some(BudgetViolation::TooSlow {
+            input: input.name,
+            elapsed,
+        })
+    } else {
+        allocated
+            .filter(|allocated| *allocated > budget.max_memory)
+            .map(|allocated| BudgetViolation::TooMuchMemory {
+                input: input.name,
+                allocated,
+            })
+    }
+}
+
+/// Inputs that a decoder using `limits` must reject.
+///
+/// Inputs exceeding `limits` are only generated if they are at most
+/// [`MAX_GENERATED_LEN`] bytes long.
+pub fn corpus(limits: &DecodeLimits) -> Vec<AdversarialInput> {
+    let mut corpus = vec![
+        AdversarialInput::new("unknown_wire_format", header(0x7f7f)),
+        AdversarialInput::new("unknown_protocol_version", {
+            let mut bytes = 0x7f7fu16.to_be_bytes().to_vec();
+            bytes.extend(WIRE_FORMAT_KEY_PACKAGE.to_be_bytes());
+            bytes
+        }),
+        AdversarialInput::new("group_id_length_beyond_input", {
+            let mut bytes = header(WIRE_FORMAT_PUBLIC_MESSAGE);
+            bytes.extend(varint(u32::from(VarInt::MAX) as usize));
+            bytes.extend([0u8; 16]);
+            bytes
+        }),
+        AdversarialInput::new("welcome_secrets_length_beyond_input", {
+            let mut bytes = header(WIRE_FORMAT_WELCOME);
+            bytes.extend(CIPHER_SUITE.to_be_bytes());
+            bytes.extend(varint(u32::from(VarInt::MAX) as usize));
+            bytes
+        }),
+        AdversarialInput::new("nested_length_beyond_parent", {
+            let mut leaf_extensions = Vec::new();
+            leaf_extensions.extend(0x0aaau16.to_be_bytes());
+            leaf_extensions.extend(varint(200));
+            leaf_extensions.extend([0u8; 200]);
+
+            let mut extensions = varint(6);
+            extensions.extend(leaf_extensions);
+
+            key_package_with_leaf_extensions(extensions)
+        }),
+        AdversarialInput::new("duplicated_extensions", {
+            let extension = (0x0aaau16, vec![1u8, 2, 3]);
+            key_package_with_leaf_extensions(encode(&vec![extension.clone(), extension]))
+        }),
+        AdversarialInput::new("nested_empty_collections", {
+            // Each encrypted group secret nests two empty opaque fields in
+            // its HPKE ciphertext.
+            let secret = [0u8; 3];
+            let count = limits
+                .max_elements
+                .saturating_add(1)
+                .min(MAX_GENERATED_LEN / 3);
+
+            let mut secrets = Vec::with_capacity(count * secret.len());
+            (0..count).for_each(|_| secrets.extend(secret));
+
+            let mut bytes = header(WIRE_FORMAT_WELCOME);
+            bytes.extend(CIPHER_SUITE.to_be_bytes());
+            bytes.extend(varint(secrets.len()));
+            bytes.extend(secrets);
+            // Missing encrypted group info
+            bytes
+        }),
+    ];
+
+    let valid_key_package = key_package_with_leaf_extensions(encode(&Vec::<(u16, Vec<u8>)>::new()));
+
+    corpus.extend((0..valid_key_package.len() - 1).step_by(7).map(|len| {
+        AdversarialInput::new(
+            format!("truncated_key_package_{len}"),
+            valid_key_package[..len].to_vec(),
+        )
+    }));
+
+    let opaque_len = limits.max_opaque_len.saturating_add(1);
+
+    if opaque_len <= MAX_GENERATED_LEN {
+        let mut bytes = header(WIRE_FORMAT_PUBLIC_MESSAGE);
+        bytes.extend(varint(opaque_len));
+        bytes.resize(bytes.len() + opaque_len, 0);
+
+        corpus.push(AdversarialInput::new("oversized_group_id", bytes));
+    }
+
+    let element_count = limits.max_elements.saturating_add(1);
+
+    if element_count * 2 <= MAX_GENERATED_LEN {
+        let mut bytes = header(WIRE_FORMAT_KEY_PACKAGE);
+        bytes.extend(MLS_10.to_be_bytes());
+        bytes.extend(CIPHER_SUITE.to_be_bytes());
+        bytes.extend(encode(&vec![0u8; 32]));
+        bytes.extend(encode(&vec![0u8; 32]));
+        bytes.extend(encode(&vec![0u8; 32]));
+        bytes.extend(basic_credential());
+        bytes.extend(varint(element_count * 2));
+        bytes.resize(bytes.len() + element_count * 2, 0);
+
+        corpus.push(AdversarialInput::new("oversized_capabilities", bytes));
+    }
+
+    corpus
+}
+
+fn header(wire_format: u16) -> Vec<u8> {
+    let mut bytes = MLS_10.to_be_bytes().to_vec();
+    bytes.extend(wire_format.to_be_bytes());
+    bytes
+}
+
+fn varint(len: usize) -> Vec<u8> {
+    // Lengths above VarInt::MAX are not generated.
+    VarInt::try_from(len.min(u32::from(VarInt::MAX) as usize))
+        .and_then(|v| v.mls_encode_to_vec())
+        .unwrap_or_default()
+}
+
+fn encode<T: MlsEncode>(value: &T) -> Vec<u8> {
+    value.mls_encode_to_vec().unwrap_or_default()
+}
+
+fn basic_credential() -> Vec<u8> {
+    let mut bytes = 1u16.to_be_bytes().to_vec();
+    bytes.extend(encode(&b"adversary".to_vec()));
+    bytes
+}
+
+/// Key package with well formed fields and an invalid signature, whose leaf
+/// node extensions are the already encoded `extensions`.
+fn key_package_with_leaf_extensions(extensions: Vec<u8>) -> Vec<u8> {
+    let mut bytes = header(WIRE_FORMAT_KEY_PACKAGE);
+    bytes.extend(MLS_10.to_be_bytes());
+    bytes.extend(CIPHER_SUITE.to_be_bytes());
+    // Init key, then the encryption and signature keys of the leaf node
+    (0..3).for_each(|_| bytes.extend(encode(&vec![0u8; 32])));
+    bytes.extend(basic_credential());
+
+    // Capabilities
+    bytes.extend(encode(&vec![MLS_10]));
+    bytes.extend(encode(&vec![CIPHER_SUITE]));
+    (0..3).for_each(|_| bytes.extend(encode(&Vec::<u16>::new())));
+
+    // Leaf node source with its lifetime
+    bytes.push(1);
+    bytes.extend(0u64.to_be_bytes());
+    bytes.extend(u64::MAX.to_be_bytes());
+
+    bytes.extend(extensions);
+    bytes.extend(encode(&vec![0u8; 64]));
+
+    // Key package extensions and signature
+    bytes.extend(encode(&Vec::<(u16, Vec<u8>)>::new()));
+    bytes.extend(encode(&vec![0u8; 64]));
+
+    bytes
+}
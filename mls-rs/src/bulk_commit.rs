@@ -0,0 +1,469 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::error::IntoAnyError;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{CommitOutput, Group},
+    Client,
+};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+
+/// Response of the delivery service to a commit sent by
+/// [`CommitDelivery::deliver`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeliveryResponse {
+    /// The commit was accepted and will be fanned out to the group.
+    Accepted,
+    /// The commit was rejected, typically because another commit for the
+    /// same epoch was accepted first.
+    Rejected,
+}
+
+/// Connection to the delivery service used by [`BulkRemoval`].
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait CommitDelivery: Send + Sync {
+    type Error: IntoAnyError;
+
+    /// Send the commit in `commit` for the group `group_id` and wait for the
+    /// response of the delivery service.
+    ///
+    /// Welcome messages in `commit`, if any, must be delivered as well.
+    async fn deliver(
+        &self,
+        group_id: &[u8],
+        commit: &CommitOutput,
+    ) -> Result<DeliveryResponse, Self::Error>;
+}
+
+/// Result of [`BulkRemoval`] in a single group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GroupOutcome {
+    /// The member was removed and the group advanced to `epoch`.
+    Committed { epoch: u64 },
+    /// The member is not in the group, nothing was done.
+    NotMember,
+    /// The delivery service rejected the commit. The group must process the
+    /// pending messages of the delivery service before trying again.
+    Rejected,
+    /// The group has a pending commit left by an interrupted run. The
+    /// application must either process it, by receiving it back from the
+    /// delivery service, or clear it before trying again.
+    PendingCommit,
+}
+
+impl GroupOutcome {
+    /// Whether the group needs no further work.
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Self::Committed { .. } | Self::NotMember)
+    }
+}
+
+/// Outcome of [`BulkRemoval::run`] in one group.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct GroupResult {
+    pub group_id: Vec<u8>,
+    pub outcome: Result<GroupOutcome, MlsError>,
+}
+
+/// Groups completed by previous runs of a [`BulkRemoval`].
+///
+/// The progress should be persisted by the application after every run, so
+/// that an interrupted operation resumes with the groups that were not
+/// completed yet.
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct BulkProgress {
+    completed: Vec<Vec<u8>>,
+}
+
+impl BulkProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_complete(&self, group_id: &[u8]) -> bool {
+        self.completed
+            .binary_search_by(|id| id.as_slice().cmp(group_id))
+            .is_ok()
+    }
+
+    /// Number of completed groups.
+    pub fn completed(&self) -> usize {
+        self.completed.len()
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    fn record(&mut self, group_id: &[u8]) {
+        if let Err(i) = self
+            .completed
+            .binary_search_by(|id| id.as_slice().cmp(group_id))
+        {
+            self.completed.insert(i, group_id.to_vec());
+        }
+    }
+}
+
+/// Remove a member identified by its identity from many groups of a client.
+///
+/// Each group is loaded from the storage of the client, a commit removing
+/// the member is sent with [`CommitDelivery`] and the group is written back
+/// to storage according to the response of the delivery service. Failures
+/// are reported per group and don't stop the operation.
+///
+/// In async builds, up to [`BulkRemoval::with_max_concurrency`] groups are
+/// processed concurrently. Sync builds process groups one at a time.
+#[derive(Clone, Debug)]
+pub struct BulkRemoval {
+    identity: Vec<u8>,
+    max_concurrency: usize,
+}
+
+impl BulkRemoval {
+    /// Remove the member whose
+    /// [identity](crate::IdentityProvider::identity) is `identity`.
+    pub fn new(identity: Vec<u8>) -> Self {
+        Self {
+            identity,
+            max_concurrency: 16,
+        }
+    }
+
+    pub fn with_max_concurrency(self, max_concurrency: usize) -> Self {
+        Self {
+            max_concurrency: max_concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Maximum number of groups processed concurrently in async builds.
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// Process all groups in `group_ids` not yet completed according to
+    /// `progress`, and record the groups completed by this run in
+    /// `progress`.
+    ///
+    /// Results are returned in the order of `group_ids`, skipping groups
+    /// that were already completed.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn run<C, D>(
+        &self,
+        client: &Client<C>,
+        group_ids: &[Vec<u8>],
+        delivery: &D,
+        progress: &mut BulkProgress,
+    ) -> Vec<GroupResult>
+    where
+        C: ClientConfig + Clone,
+        D: CommitDelivery,
+    {
+        let pending = group_ids
+            .iter()
+            .filter(|id| !progress.is_complete(id))
+            .collect::<Vec<_>>();
+
+        let results = self.run_pending(client, pending, delivery).await;
+
+        results
+            .iter()
+            .filter(|r| matches!(&r.outcome, Ok(outcome) if outcome.is_complete()))
+            .for_each(|r| progress.record(&r.group_id));
+
+        results
+    }
+
+    #[cfg(not(mls_build_async))]
+    fn run_pending<C, D>(
+        &self,
+        client: &Client<C>,
+        pending: Vec<&Vec<u8>>,
+        delivery: &D,
+    ) -> Vec<GroupResult>
+    where
+        C: ClientConfig + Clone,
+        D: CommitDelivery,
+    {
+        pending
+            .into_iter()
+            .map(|group_id| GroupResult {
+                group_id: group_id.clone(),
+                outcome: self.remove_from_group(client, group_id, delivery),
+            })
+            .collect()
+    }
+
+    #[cfg(mls_build_async)]
+    async fn run_pending<C, D>(
+        &self,
+        client: &Client<C>,
+        pending: Vec<&Vec<u8>>,
+        delivery: &D,
+    ) -> Vec<GroupResult>
+    where
+        C: ClientConfig + Clone,
+        D: CommitDelivery,
+    {
+        use futures::StreamExt;
+
+        futures::stream::iter(pending)
+            .map(|group_id| async move {
+                GroupResult {
+                    group_id: group_id.clone(),
+                    outcome: self.remove_from_group(client, group_id, delivery).await,
+                }
+            })
+            .buffered(self.max_concurrency)
+            .collect()
+            .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn remove_from_group<C, D>(
+        &self,
+        client: &Client<C>,
+        group_id: &[u8],
+        delivery: &D,
+    ) -> Result<GroupOutcome, MlsError>
+    where
+        C: ClientConfig + Clone,
+        D: CommitDelivery,
+    {
+        let mut group = client.load_group(group_id).await?;
+
+        if group.has_pending_commit() {
+            return Ok(GroupOutcome::PendingCommit);
+        }
+
+        let Some(index) = self.member_index(&group).await? else {
+            return Ok(GroupOutcome::NotMember);
+        };
+
+        let output = group.commit_builder().remove_member(index)?.build().await?;
+
+        // If the run is interrupted while waiting for the delivery service,
+        // the next run reports the pending commit instead of creating a
+        // second one for the same epoch.
+        group.write_to_storage().await?;
+
+        let response = delivery
+            .deliver(group_id, &output)
+            .await
+            .map_err(|e| MlsError::CommitDeliveryError(e.into_any_error()))?;
+
+        let outcome = match response {
+            DeliveryResponse::Accepted => {
+                group.apply_pending_commit().await?;
+
+                GroupOutcome::Committed {
+                    epoch: group.current_epoch(),
+                }
+            }
+            DeliveryResponse::Rejected => {
+                group.clear_pending_commit();
+                GroupOutcome::Rejected
+            }
+        };
+
+        group.write_to_storage().await?;
+
+        Ok(outcome)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn member_index<C>(&self, group: &Group<C>) -> Result<Option<u32>, MlsError>
+    where
+        C: ClientConfig + Clone,
+    {
+        match group.member_with_identity(&self.identity).await {
+            Ok(member) => Ok(Some(member.index)),
+            Err(MlsError::MemberNotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use assert_matches::assert_matches;
+    use core::convert::Infallible;
+
+    #[cfg(feature = "std")]
+    use std::sync::Mutex;
+
+    #[cfg(not(feature = "std"))]
+    use spin::Mutex;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::CommitOutput,
+        ExtensionList,
+    };
+
+    use super::{BulkProgress, BulkRemoval, CommitDelivery, DeliveryResponse, GroupOutcome};
+
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+
+    struct TestDelivery {
+        reject: Vec<Vec<u8>>,
+        delivered: Mutex<Vec<Vec<u8>>>,
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl CommitDelivery for TestDelivery {
+        type Error = Infallible;
+
+        async fn deliver(
+            &self,
+            group_id: &[u8],
+            _commit: &CommitOutput,
+        ) -> Result<DeliveryResponse, Infallible> {
+            #[cfg(feature = "std")]
+            let mut delivered = self.delivered.lock().unwrap();
+
+            #[cfg(not(feature = "std"))]
+            let mut delivered = self.delivered.lock();
+
+            delivered.push(group_id.to_vec());
+
+            if self.reject.iter().any(|id| id == group_id) {
+                Ok(DeliveryResponse::Rejected)
+            } else {
+                Ok(DeliveryResponse::Accepted)
+            }
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn member_is_removed_from_all_groups_and_run_resumes() {
+        let (alice, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let (bob, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let mut group_ids = Vec::new();
+
+        for i in 0..3 {
+            let mut group = alice
+                .create_group(ExtensionList::new(), ExtensionList::new(), None)
+                .await
+                .unwrap();
+
+            // Bob is not in the last group
+            if i < 2 {
+                let key_package = bob
+                    .generate_key_package_message(Default::default(), Default::default(), None)
+                    .await
+                    .unwrap();
+
+                group
+                    .commit_builder()
+                    .add_member(key_package)
+                    .unwrap()
+                    .build()
+                    .await
+                    .unwrap();
+
+                group.apply_pending_commit().await.unwrap();
+            }
+
+            group.write_to_storage().await.unwrap();
+            group_ids.push(group.group_id().to_vec());
+        }
+
+        let removal = BulkRemoval::new(b"bob".to_vec()).with_max_concurrency(2);
+        let mut progress = BulkProgress::new();
+
+        let delivery = TestDelivery {
+            reject: vec![group_ids[1].clone()],
+            delivered: Default::default(),
+        };
+
+        let results = removal
+            .run(&alice, &group_ids, &delivery, &mut progress)
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert_matches!(results[0].outcome, Ok(GroupOutcome::Committed { epoch: 2 }));
+        assert_matches!(results[1].outcome, Ok(GroupOutcome::Rejected));
+        assert_matches!(results[2].outcome, Ok(GroupOutcome::NotMember));
+
+        let group = alice.load_group(&group_ids[0]).await.unwrap();
+        assert_eq!(group.roster().members_iter().count(), 1);
+
+        let group = alice.load_group(&group_ids[1]).await.unwrap();
+        assert!(!group.has_pending_commit());
+        assert_eq!(group.roster().members_iter().count(), 2);
+
+        // Resume from persisted progress, only the rejected group is retried
+        let mut progress = BulkProgress::from_bytes(&progress.to_bytes().unwrap()).unwrap();
+        assert_eq!(progress.completed(), 2);
+
+        let delivery = TestDelivery {
+            reject: Vec::new(),
+            delivered: Default::default(),
+        };
+
+        let results = removal
+            .run(&alice, &group_ids, &delivery, &mut progress)
+            .await;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].group_id, group_ids[1]);
+        assert_matches!(results[0].outcome, Ok(GroupOutcome::Committed { .. }));
+        assert_eq!(progress.completed(), 3);
+
+        #[cfg(feature = "std")]
+        let delivered = delivery.delivered.lock().unwrap().clone();
+
+        #[cfg(not(feature = "std"))]
+        let delivered = delivery.delivered.lock().clone();
+
+        assert_eq!(delivered, vec![group_ids[1].clone()]);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn missing_group_is_reported_as_failure() {
+        let (alice, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let delivery = TestDelivery {
+            reject: Vec::new(),
+            delivered: Default::default(),
+        };
+
+        let mut progress = BulkProgress::new();
+
+        let results = BulkRemoval::new(b"bob".to_vec())
+            .run(&alice, &[b"missing".to_vec()], &delivery, &mut progress)
+            .await;
+
+        assert_matches!(results[0].outcome, Err(MlsError::GroupNotFound));
+        assert_eq!(progress.completed(), 0);
+    }
+}
@@ -7,9 +7,14 @@ use crate::client_builder::{recreate_config, BaseConfig, ClientBuilder, MakeConf
 use crate::client_config::ClientConfig;
 use crate::group::framing::MlsMessage;
 
-use crate::group::{cipher_suite_provider, validate_group_info_joiner, GroupInfo};
 use crate::group::{
-    framing::MlsMessagePayload, snapshot::Snapshot, ExportedTree, Group, NewMemberInfo,
+    cipher_suite_provider, negotiate_group_context_extensions,
+    security_watermark::check_security_watermark, signature_keys::generate_signature_key,
+    validate_group_info_joiner, GroupInfo, WatermarkViolation,
+};
+use crate::group::{
+    framing::MlsMessagePayload, snapshot::Snapshot, CipherSuiteSelection, ExportedTree, Group,
+    JoinPreview, MembershipStatement, NewMemberInfo,
 };
 #[cfg(feature = "by_ref_proposal")]
 use crate::group::{
@@ -17,22 +22,37 @@ use crate::group::{
     message_signature::AuthenticatedContent,
     proposal::{AddProposal, Proposal},
 };
+use crate::group_cache::GroupCache;
+pub use crate::group_cache::GroupReadiness;
 use crate::identity::SigningIdentity;
-use crate::key_package::{KeyPackageGeneration, KeyPackageGenerator};
+use crate::key_package::{KeyPackageGeneration, KeyPackageGenerator, KeyPackageRef};
 use crate::protocol_version::ProtocolVersion;
 use crate::time::MlsTime;
 use crate::tree_kem::node::NodeIndex;
+use alloc::string::String;
 use alloc::vec::Vec;
-use mls_rs_codec::MlsDecode;
+#[cfg(feature = "key_transparency")]
+use mls_rs_codec::MlsEncode;
 use mls_rs_core::crypto::{CryptoProvider, SignatureSecretKey};
 use mls_rs_core::error::{AnyError, IntoAnyError};
 use mls_rs_core::extension::{ExtensionError, ExtensionList, ExtensionType};
 use mls_rs_core::group::{GroupStateStorage, ProposalType};
-use mls_rs_core::identity::{CredentialType, IdentityProvider, MemberValidationContext};
+use mls_rs_core::identity::{
+    CredentialType, IdentityProvider, MemberValidation, MemberValidationContext,
+    MemberValidationPurpose,
+};
 use mls_rs_core::key_package::KeyPackageStorage;
 
 use crate::group::external_commit::ExternalCommitBuilder;
 
+#[cfg(feature = "private_message")]
+use crate::group::{decrypt_only::decrypt_only, ApplicationMessageDescription};
+
+#[cfg(feature = "psk")]
+use crate::group::{
+    sealed_group_info::get_external_psk, CommitOutput, MemberStateExport, SealedGroupInfo,
+};
+
 #[cfg(feature = "by_ref_proposal")]
 use alloc::boxed::Box;
 
@@ -50,6 +70,14 @@ pub enum MlsError {
     #[cfg_attr(feature = "std", error(transparent))]
     GroupStorageError(AnyError),
     #[cfg_attr(feature = "std", error(transparent))]
+    GroupIdMappingStorageError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    SignatureKeyStorageError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    SecurityWatermarkStorageError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    CommitDeliveryError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
     PskStoreError(AnyError),
     #[cfg_attr(feature = "std", error(transparent))]
     MlsRulesError(AnyError),
@@ -354,6 +382,198 @@ pub enum MlsError {
     ExporterDeleted,
     #[cfg_attr(feature = "std", error("Self-remove already proposed"))]
     SelfRemoveAlreadyProposed,
+    #[cfg_attr(feature = "std", error("Invalid targeted message recipient"))]
+    InvalidTargetedMessageRecipient,
+    #[cfg_attr(
+        feature = "std",
+        error("Membership statement was not signed by this identity")
+    )]
+    MembershipStatementSignerMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("Cipher suite {0:?} is weaker than the minimum allowed by policy")
+    )]
+    CipherSuiteBelowMinimum(CipherSuite),
+    #[cfg_attr(
+        feature = "std",
+        error("No cipher suite is supported by this client and all key packages")
+    )]
+    NoCommonCipherSuite,
+    #[cfg_attr(feature = "std", error(transparent))]
+    ProposalCacheStorageError(AnyError),
+    #[cfg_attr(
+        feature = "std",
+        error("Maximum number of cached proposals for this epoch reached")
+    )]
+    ProposalCacheQuotaExceeded,
+    #[cfg_attr(
+        feature = "std",
+        error("commits that require an update path can not be signed offline")
+    )]
+    OfflineSigningRequiresNoPathUpdate,
+    #[cfg_attr(
+        feature = "std",
+        error("unsigned message was created in a different epoch")
+    )]
+    UnsignedMessageEpochMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("number of signatures does not match the number of group info messages")
+    )]
+    GroupInfoSignatureCountMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("Unknown extension type {0:?} rejected by policy")
+    )]
+    UnknownExtensionRejected(ExtensionType),
+    #[cfg_attr(
+        feature = "std",
+        error("Unknown proposal type {0:?} rejected by policy")
+    )]
+    UnknownProposalTypeRejected(ProposalType),
+    #[cfg_attr(
+        feature = "std",
+        error("header of an exported group state does not match the encrypted group state")
+    )]
+    SealedSnapshotMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "encryption secret of the epoch was not retained, the secret tree can not be recovered"
+        )
+    )]
+    SecretTreeRecoveryUnavailable,
+    #[cfg_attr(
+        feature = "std",
+        error("resume token does not belong to the current epoch of this group")
+    )]
+    ResumeTokenMismatch,
+    #[cfg_attr(feature = "std", error(transparent))]
+    CompressionError(AnyError),
+    #[cfg_attr(
+        feature = "std",
+        error("message was compressed with algorithm {0}, expected {1}")
+    )]
+    CompressionAlgorithmMismatch(u16, u16),
+    #[cfg_attr(
+        feature = "std",
+        error("emergency rekey can not remove the member performing it")
+    )]
+    EmergencyRekeyIncludesSelf,
+    #[cfg_attr(feature = "std", error(transparent))]
+    KeyPackageReservationError(AnyError),
+    #[cfg_attr(feature = "std", error("key package {0:?} is already reserved"))]
+    KeyPackageReserved(KeyPackageRef),
+    #[cfg_attr(
+        feature = "std",
+        error("key package was already added to the group as member {0}")
+    )]
+    KeyPackageAlreadyAdded(u32),
+    #[cfg_attr(
+        feature = "std",
+        error("multiple added key packages use the same init key")
+    )]
+    DuplicateKeyPackageInitKey,
+    #[cfg_attr(
+        feature = "std",
+        error("group state is encrypted with unknown storage master key {0}")
+    )]
+    StorageMasterKeyNotFound(u32),
+    #[cfg_attr(
+        feature = "std",
+        error("migrated group does not match the source group state")
+    )]
+    StorageMigrationVerificationFailed(Vec<u8>),
+    #[cfg_attr(feature = "std", error("group alias not found in welcome message"))]
+    GroupAliasNotFound,
+    #[cfg_attr(
+        feature = "std",
+        error("group alias in welcome message does not match the expected alias")
+    )]
+    GroupAliasMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("leaf index {0} is not a designated broadcaster")
+    )]
+    UnknownBroadcaster(u32),
+    #[cfg_attr(
+        feature = "std",
+        error("broadcast sender used all of its reserved generations")
+    )]
+    BroadcastGenerationsExhausted,
+    #[cfg_attr(feature = "std", error("group has no recovery psk"))]
+    RecoveryPskNotConfigured,
+    #[cfg_attr(
+        feature = "std",
+        error("commit using the recovery psk was rejected by the mls rules")
+    )]
+    RecoveryPskRejected,
+    #[cfg_attr(
+        feature = "std",
+        error("group state at epoch {local_epoch} is out of sync with message from epoch {observed_epoch}")
+    )]
+    OutOfSync {
+        local_epoch: u64,
+        observed_epoch: u64,
+    },
+    #[cfg_attr(feature = "std", error("invalid compliance test vectors: {0}"))]
+    InvalidComplianceData(String),
+    #[cfg_attr(
+        feature = "std",
+        error("secret handle used with a provider that does not support secret handles")
+    )]
+    SecretHandlesNotSupported,
+    #[cfg_attr(feature = "std", error("unsupported snapshot format version {0}"))]
+    UnsupportedSnapshotVersion(u16),
+    #[cfg_attr(feature = "std", error("snapshot is missing required section {0}"))]
+    MissingSnapshotSection(u16),
+    #[cfg_attr(
+        feature = "std",
+        error("application message from member {0} denied by the MLS rules")
+    )]
+    ApplicationMessageDenied(u32),
+    #[cfg_attr(
+        feature = "std",
+        error("group info for epoch {0} does not follow the current epoch")
+    )]
+    StaleGroupInfo(u64),
+    #[cfg_attr(
+        feature = "std",
+        error("wire format override denied by the MLS rules or invalid for the sender")
+    )]
+    WireFormatOverrideDenied,
+    #[cfg_attr(feature = "std", error("invalid armored message"))]
+    InvalidArmor,
+    #[cfg_attr(feature = "std", error("armored message checksum mismatch"))]
+    ArmorChecksumMismatch,
+    #[cfg_attr(feature = "std", error("unsupported armor version {0}"))]
+    UnsupportedArmorVersion(u8),
+    #[cfg_attr(feature = "std", error(transparent))]
+    CredentialIssuerError(AnyError),
+    #[cfg_attr(
+        feature = "std",
+        error("issued signing identity does not contain the generated signature key")
+    )]
+    IssuedSignatureKeyMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("stored group state is below its security watermark: {0:?}")
+    )]
+    BelowSecurityWatermark(WatermarkViolation),
+    #[cfg_attr(feature = "std", error("invalid group archive"))]
+    InvalidArchive,
+    #[cfg_attr(
+        feature = "std",
+        error("welcome message was not sent by the expected inviter")
+    )]
+    UnexpectedInviter,
+    #[cfg_attr(feature = "std", error("key package not found in storage"))]
+    KeyPackageNotFound,
+    #[cfg_attr(
+        feature = "std",
+        error("key package can't be repackaged by the current signing identity, cipher suite and protocol version")
+    )]
+    UnsafeKeyPackageRepackaging,
 }
 
 impl IntoAnyError for MlsError {
@@ -392,6 +612,8 @@ pub struct Client<C> {
     pub(crate) signing_identity: Option<(SigningIdentity, CipherSuite)>,
     pub(crate) signer: Option<SignatureSecretKey>,
     pub(crate) version: ProtocolVersion,
+    pub(crate) registered_identities: Vec<(SigningIdentity, SignatureSecretKey, CipherSuite)>,
+    pub(crate) group_cache: GroupCache<C>,
 }
 
 impl Client<()> {
@@ -418,6 +640,8 @@ where
             signer,
             signing_identity,
             version,
+            registered_identities: Vec::new(),
+            group_cache: Default::default(),
         }
     }
 
@@ -474,10 +698,17 @@ where
             .cipher_suite_provider(cipher_suite)
             .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))?;
 
+        let per_group_key = generate_signature_key(&self.config, cipher_suite).await?;
+
+        let (signing_identity, signing_key) = match &per_group_key {
+            Some((signing_identity, signer)) => (signing_identity, signer),
+            None => (signing_identity, self.signer()?),
+        };
+
         let key_package_generator = KeyPackageGenerator {
             protocol_version: self.version,
             cipher_suite_provider: &cipher_suite_provider,
-            signing_key: self.signer()?,
+            signing_key,
             signing_identity,
         };
 
@@ -498,9 +729,110 @@ where
             .await
             .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
 
+        #[cfg(feature = "key_transparency")]
+        self.config
+            .identity_provider()
+            .key_package_published(
+                signing_identity,
+                &key_pkg_gen.key_package.mls_encode_to_vec()?,
+            )
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
         Ok(key_pkg_gen)
     }
 
+    /// Replace the stored key package referenced by `key_package_ref` by a
+    /// new key package with `key_package_extensions`, `leaf_node_extensions`
+    /// and the current lifetime, capabilities and signing identity of this
+    /// client.
+    ///
+    /// The new key package reuses the HPKE init key pair of the stored key
+    /// package, so no new init key is generated, and the stored key package
+    /// is deleted. Copies of the old key package that were already published
+    /// can no longer be used to add this client to a group.
+    ///
+    /// Reusing the init key is only possible for the cipher suite, protocol
+    /// version and signing identity that the key package was generated for.
+    /// Key packages of another cipher suite, such as during a cipher suite
+    /// migration, or signed with a per group signing key, are refused with
+    /// [`MlsError::UnsafeKeyPackageRepackaging`] and must be regenerated with
+    /// [`Client::generate_key_package_message`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn repackage_key_package(
+        &self,
+        key_package_ref: &KeyPackageRef,
+        key_package_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+        timestamp: Option<MlsTime>,
+    ) -> Result<MlsMessage, MlsError> {
+        let mut key_package_repo = self.config.key_package_repo();
+
+        let data = key_package_repo
+            .get(key_package_ref)
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?
+            .ok_or(MlsError::KeyPackageNotFound)?;
+
+        let stored = KeyPackageGeneration::from_storage(key_package_ref.to_vec(), data)?;
+
+        let (signing_identity, cipher_suite) = self.signing_identity()?;
+
+        if stored.key_package.cipher_suite != cipher_suite {
+            return Err(MlsError::UnsafeKeyPackageRepackaging);
+        }
+
+        let cipher_suite_provider = self
+            .config
+            .crypto_provider()
+            .cipher_suite_provider(cipher_suite)
+            .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))?;
+
+        let key_package_generator = KeyPackageGenerator {
+            protocol_version: self.version,
+            cipher_suite_provider: &cipher_suite_provider,
+            signing_key: self.signer()?,
+            signing_identity,
+        };
+
+        let key_pkg_gen = key_package_generator
+            .repackage(
+                stored,
+                self.config.lifetime(timestamp),
+                self.config.capabilities(),
+                key_package_extensions,
+                leaf_node_extensions,
+            )
+            .await?;
+
+        let (id, key_package_data) = key_pkg_gen.to_storage()?;
+
+        // The new key package is stored before the old one is deleted so that
+        // the init key is never lost.
+        key_package_repo
+            .insert(id, key_package_data)
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
+
+        key_package_repo
+            .delete(key_package_ref)
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
+
+        #[cfg(feature = "key_transparency")]
+        self.config
+            .identity_provider()
+            .key_package_published(
+                signing_identity,
+                &key_pkg_gen.key_package.mls_encode_to_vec()?,
+            )
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+        Ok(key_pkg_gen.key_package_message())
+    }
+
     /// Create a group with a specific group_id.
     ///
     /// This function behaves the same way as
@@ -521,22 +853,42 @@ where
         leaf_node_extensions: ExtensionList,
         timestamp: Option<MlsTime>,
     ) -> Result<Group<C>, MlsError> {
-        let (signing_identity, cipher_suite) = self.signing_identity()?;
+        let (signing_identity, signer, cipher_suite) = self.group_signing_identity().await?;
 
         Group::new(
             self.config.clone(),
             Some(group_id),
             cipher_suite,
             self.version,
-            signing_identity.clone(),
+            signing_identity,
             group_context_extensions,
             leaf_node_extensions,
-            self.signer()?.clone(),
+            signer,
             timestamp,
         )
         .await
     }
 
+    /// Signing identity, signer and cipher suite of a new group. The signing
+    /// identity and signer are freshly generated if the client uses per group
+    /// signature keys.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn group_signing_identity(
+        &self,
+    ) -> Result<(SigningIdentity, SignatureSecretKey, CipherSuite), MlsError> {
+        let (signing_identity, cipher_suite) = self.signing_identity()?;
+
+        self.config.cipher_suite_policy().check(cipher_suite)?;
+
+        let (signing_identity, signer) =
+            match generate_signature_key(&self.config, cipher_suite).await? {
+                Some(per_group_key) => per_group_key,
+                None => (signing_identity.clone(), self.signer()?.clone()),
+            };
+
+        Ok((signing_identity, signer, cipher_suite))
+    }
+
     /// Create a MLS group.
     ///
     /// The `cipher_suite` provided must be supported by the
@@ -550,22 +902,95 @@ where
         leaf_node_extensions: ExtensionList,
         timestamp: Option<MlsTime>,
     ) -> Result<Group<C>, MlsError> {
-        let (signing_identity, cipher_suite) = self.signing_identity()?;
+        let (signing_identity, signer, cipher_suite) = self.group_signing_identity().await?;
 
         Group::new(
             self.config.clone(),
             None,
             cipher_suite,
             self.version,
-            signing_identity.clone(),
+            signing_identity,
             group_context_extensions,
             leaf_node_extensions,
-            self.signer()?.clone(),
+            signer,
             timestamp,
         )
         .await
     }
 
+    /// Choose the strongest cipher suite that this client and every joiner
+    /// in `key_packages` support, ranked by the
+    /// [`CipherSuitePolicy`](crate::group::CipherSuitePolicy) of the client.
+    ///
+    /// The result reports whether joiners forced a downgrade from the
+    /// cipher suite preferred by this client. If the selected cipher suite is
+    /// weaker than the minimum of the policy, a warning is included or
+    /// [`MlsError::CipherSuiteBelowMinimum`] is returned, depending on the
+    /// [`DowngradeAction`](crate::group::DowngradeAction) of the policy.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn select_cipher_suite(
+        &self,
+        key_packages: &[MlsMessage],
+    ) -> Result<CipherSuiteSelection, MlsError> {
+        self.config.cipher_suite_policy().select(
+            &self.config.crypto_provider().supported_cipher_suites(),
+            key_packages,
+        )
+    }
+
+    /// Restrict `group_context_extensions` to the capabilities supported by
+    /// both this client and the owner of `key_package`.
+    ///
+    /// Extensions of a non-default type that either party doesn't support
+    /// are removed, and a [`RequiredCapabilitiesExt`](crate::extension::built_in::RequiredCapabilitiesExt)
+    /// listing the extensions, proposals and credentials supported by both
+    /// parties is set. If `group_context_extensions` already requires
+    /// capabilities, they are narrowed to the common ones instead.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn negotiate_group_context_extensions(
+        &self,
+        key_package: &MlsMessage,
+        group_context_extensions: ExtensionList,
+    ) -> Result<ExtensionList, MlsError> {
+        let key_package = key_package
+            .as_key_package()
+            .ok_or(MlsError::UnexpectedMessageType)?;
+
+        let (_, cipher_suite) = self.signing_identity()?;
+
+        if key_package.cipher_suite != cipher_suite {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        negotiate_group_context_extensions(
+            &self.config.capabilities(),
+            &key_package.leaf_node.capabilities,
+            group_context_extensions,
+        )
+    }
+
+    /// Create a group to be shared with the owner of `key_package`, with
+    /// group context extensions computed by
+    /// [`negotiate_group_context_extensions`](Client::negotiate_group_context_extensions).
+    ///
+    /// The peer is not added to the group, which is done by committing
+    /// `key_package` with [`Group::commit_builder`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn create_group_for_peer(
+        &self,
+        key_package: &MlsMessage,
+        group_context_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+        timestamp: Option<MlsTime>,
+    ) -> Result<Group<C>, MlsError> {
+        let group_context_extensions =
+            self.negotiate_group_context_extensions(key_package, group_context_extensions)?;
+
+        self.create_group(group_context_extensions, leaf_node_extensions, timestamp)
+            .await
+    }
+
     /// Join a MLS group via a welcome message created by a
     /// [Commit](crate::group::CommitOutput).
     ///
@@ -583,42 +1008,137 @@ where
         welcome_message: &MlsMessage,
         maybe_time: Option<MlsTime>,
     ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
-        Group::join(
+        let res = Group::join(
             welcome_message,
             tree_data,
             self.config.clone(),
             self.signer()?.clone(),
             maybe_time,
         )
-        .await
+        .await;
+
+        #[cfg(feature = "security_events")]
+        if let Err(e) = &res {
+            crate::group::security_event::report_security_event(&self.config, e, None, None);
+        }
+
+        res
     }
 
-    /// Decrypt GroupInfo encrypted in the Welcome message without actually joining
-    /// the group. The ratchet tree is not needed.
+    /// Join a MLS group via a welcome message created by a
+    /// [Commit](crate::group::CommitOutput) and check that it carries a
+    /// [`GroupAliasExt`](crate::group::GroupAliasExt) equal to
+    /// `expected_alias`.
+    ///
+    /// `expected_alias` is usually the conversation id under which the
+    /// Delivery Service advertised the group. If the alias is missing or
+    /// different, [`MlsError::GroupAliasNotFound`] or
+    /// [`MlsError::GroupAliasMismatch`] is returned and the group is not
+    /// joined. See [`Client::join_group`] for the other parameters.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn examine_welcome_message(
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn join_group_with_alias(
         &self,
+        tree_data: Option<ExportedTree<'_>>,
         welcome_message: &MlsMessage,
-    ) -> Result<GroupInfo, MlsError> {
-        Group::decrypt_group_info(welcome_message, &self.config).await
+        maybe_time: Option<MlsTime>,
+        expected_alias: &[u8],
+    ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        let (group, new_member_info) = self
+            .join_group(tree_data, welcome_message, maybe_time)
+            .await?;
+
+        new_member_info.verify_group_alias(expected_alias)?;
+
+        Ok((group, new_member_info))
     }
 
-    /// Validate GroupInfo message. This does NOT validate the ratchet tree in case
-    /// it is provided in the extension. It validates the signature, identity of the
-    /// signer, identities of external senders and cipher suite.
+    /// Join a MLS group via a welcome message created by a
+    /// [Commit](crate::group::CommitOutput) and check that it was sent by
+    /// the member with identity `expected_inviter`.
+    ///
+    /// The identity of the member who signed the group info of the welcome
+    /// message is resolved with the
+    /// [`IdentityProvider`](crate::IdentityProvider) of the client, the same
+    /// way as for [`IdentityProvider::identity`](crate::IdentityProvider::identity).
+    /// If it is different from `expected_inviter`,
+    /// [`MlsError::UnexpectedInviter`] is returned and the group is not
+    /// joined. See [`Client::join_group`] for the other parameters.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn validate_group_info(
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn join_group_with_inviter(
         &self,
-        group_info_message: &MlsMessage,
-        signer: &SigningIdentity,
-    ) -> Result<(), MlsError> {
-        let MlsMessagePayload::GroupInfo(group_info) = &group_info_message.payload else {
-            return Err(MlsError::UnexpectedMessageType);
-        };
-
-        let cs = cipher_suite_provider(
-            self.config.crypto_provider(),
-            group_info.group_context.cipher_suite,
+        tree_data: Option<ExportedTree<'_>>,
+        welcome_message: &MlsMessage,
+        maybe_time: Option<MlsTime>,
+        expected_inviter: &[u8],
+    ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        let (group, new_member_info) = self
+            .join_group(tree_data, welcome_message, maybe_time)
+            .await?;
+
+        let inviter = group
+            .member_at_index(new_member_info.sender)
+            .ok_or(MlsError::UnexpectedInviter)?;
+
+        let inviter_identity = self
+            .config
+            .identity_provider()
+            .identity(&inviter.signing_identity, &group.context().extensions)
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+        if inviter_identity != expected_inviter {
+            return Err(MlsError::UnexpectedInviter);
+        }
+
+        Ok((group, new_member_info))
+    }
+
+    /// Decrypt GroupInfo encrypted in the Welcome message without actually joining
+    /// the group. The ratchet tree is not needed.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn examine_welcome_message(
+        &self,
+        welcome_message: &MlsMessage,
+    ) -> Result<GroupInfo, MlsError> {
+        Group::decrypt_group_info(welcome_message, &self.config).await
+    }
+
+    /// Describe the group that `welcome_message` invites this client to,
+    /// without joining it.
+    ///
+    /// The ratchet tree and the signature of the group info are validated as
+    /// in [`Client::join_group`], which takes the same parameters. No state
+    /// is stored and the key package used by the Welcome message is not
+    /// consumed, so the group can still be joined afterwards.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn preview_welcome(
+        &self,
+        tree_data: Option<ExportedTree<'_>>,
+        welcome_message: &MlsMessage,
+        maybe_time: Option<MlsTime>,
+    ) -> Result<JoinPreview, MlsError> {
+        Group::preview_welcome(welcome_message, tree_data, &self.config, maybe_time).await
+    }
+
+    /// Validate GroupInfo message. This does NOT validate the ratchet tree in case
+    /// it is provided in the extension. It validates the signature, identity of the
+    /// signer, identities of external senders and cipher suite.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn validate_group_info(
+        &self,
+        group_info_message: &MlsMessage,
+        signer: &SigningIdentity,
+    ) -> Result<(), MlsError> {
+        let MlsMessagePayload::GroupInfo(group_info) = &group_info_message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        let cs = cipher_suite_provider(
+            self.config.crypto_provider(),
+            group_info.group_context.cipher_suite,
         )?;
 
         let id = self.config.identity_provider();
@@ -630,7 +1150,9 @@ where
             current_context: &group_info.group_context,
         };
 
-        id.validate_member(signer, None, context)
+        let validation = MemberValidation::new(context, MemberValidationPurpose::Join, None);
+
+        id.validate_member_in_context(signer, validation)
             .await
             .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
 
@@ -697,6 +1219,91 @@ where
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     #[inline(never)]
     pub async fn load_group(&self, group_id: &[u8]) -> Result<Group<C>, MlsError> {
+        if let Some(group) = self.group_cache.take(group_id) {
+            return Ok(group);
+        }
+
+        let snapshot = self.stored_snapshot(group_id).await?;
+
+        Group::from_snapshot(self.config.clone(), snapshot).await
+    }
+
+    /// Load groups from storage and prepare them for use ahead of time, e.g.
+    /// during application launch, so that the following calls to
+    /// [`Client::load_group`] return them without loading and parsing the
+    /// stored state.
+    ///
+    /// A preloaded group is handed out once by [`Client::load_group`] and
+    /// then removed from the cache. It does not reflect changes written to
+    /// storage after it was preloaded; use
+    /// [`Client::clear_preloaded_groups`] if the stored state can be changed
+    /// by other clients. Groups that are already loading or ready are
+    /// skipped. Loading stops at the first group that fails to load, the
+    /// groups preloaded before it remain ready.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn preload_groups<I>(&self, group_ids: I) -> Result<(), MlsError>
+    where
+        C: 'static,
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        for group_id in group_ids {
+            let group_id = group_id.as_ref();
+
+            if !self.group_cache.start_loading(group_id) {
+                continue;
+            }
+
+            let res = self.load_prepared_group(group_id).await;
+
+            match res {
+                Ok(group) => self.group_cache.finish_loading(group_id, Some(group)),
+                Err(e) => {
+                    self.group_cache.finish_loading(group_id, None);
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn load_prepared_group(&self, group_id: &[u8]) -> Result<Group<C>, MlsError> {
+        let snapshot = self.stored_snapshot(group_id).await?;
+        let mut group = Group::from_snapshot(self.config.clone(), snapshot).await?;
+        group.prepare().await?;
+
+        Ok(group)
+    }
+
+    /// Load an existing group state like [`Client::load_group`] even if it is
+    /// below the [`SecurityWatermark`](crate::group::SecurityWatermark) of the
+    /// group.
+    ///
+    /// This is an explicit override for states that were intentionally
+    /// restored from a backup. The watermark is lowered to the parameters of
+    /// the restored state.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn load_group_below_watermark(&self, group_id: &[u8]) -> Result<Group<C>, MlsError> {
+        let snapshot = self.stored_snapshot_unchecked(group_id).await?;
+        let group = Group::from_snapshot(self.config.clone(), snapshot).await?;
+        group.reset_security_watermark().await?;
+
+        Ok(group)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn stored_snapshot(&self, group_id: &[u8]) -> Result<Snapshot, MlsError> {
+        let snapshot = self.stored_snapshot_unchecked(group_id).await?;
+        check_security_watermark(&self.config, &snapshot).await?;
+
+        Ok(snapshot)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn stored_snapshot_unchecked(&self, group_id: &[u8]) -> Result<Snapshot, MlsError> {
         let snapshot = self
             .config
             .group_state_storage()
@@ -705,9 +1312,20 @@ where
             .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
             .ok_or(MlsError::GroupNotFound)?;
 
-        let snapshot = Snapshot::mls_decode(&mut &*snapshot)?;
+        Snapshot::from_bytes(&snapshot)
+    }
 
-        Group::from_snapshot(self.config.clone(), snapshot).await
+    /// Whether the group with `group_id` was preloaded with
+    /// [`Client::preload_groups`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn group_readiness(&self, group_id: &[u8]) -> GroupReadiness {
+        self.group_cache.readiness(group_id)
+    }
+
+    /// Drop all preloaded groups. Groups that are still loading are kept.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn clear_preloaded_groups(&self) {
+        self.group_cache.clear()
     }
 
     /// Load an existing group state into this client using the
@@ -721,20 +1339,45 @@ where
         group_id: &[u8],
         tree_data: ExportedTree<'_>,
     ) -> Result<Group<C>, MlsError> {
-        let snapshot = self
-            .config
-            .group_state_storage()
-            .state(group_id)
-            .await
-            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
-            .ok_or(MlsError::GroupNotFound)?;
-
-        let mut snapshot = Snapshot::mls_decode(&mut &*snapshot)?;
+        let mut snapshot = self.stored_snapshot(group_id).await?;
         snapshot.state.public_tree.nodes = tree_data.0.into_owned();
 
         Group::from_snapshot(self.config.clone(), snapshot).await
     }
 
+    /// Decrypt a single application message for a group stored in the
+    /// [GroupStateStorage](crate::GroupStateStorage) that this client was
+    /// configured to use, without loading the full [Group].
+    ///
+    /// Only the secrets of the epoch the message was sent in are loaded. This
+    /// is intended for constrained environments such as notification service
+    /// extensions that need to display a message with a minimal memory and
+    /// time budget.
+    ///
+    /// # Warning
+    ///
+    /// The ratcheted secret tree is not written back to storage, so the same
+    /// message can (and should) still be processed by the full group using
+    /// [Group::process_incoming_message]. The key used to decrypt the message
+    /// is therefore not deleted by this call.
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn decrypt_only(
+        &self,
+        message: &MlsMessage,
+    ) -> Result<ApplicationMessageDescription, MlsError> {
+        if !self.config.version_supported(message.version) {
+            return Err(MlsError::UnsupportedProtocolVersion(message.version));
+        }
+
+        let MlsMessagePayload::Cipher(ciphertext) = &message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        decrypt_only(&self.config, ciphertext).await
+    }
+
     /// Request to join an existing [group](crate::group::Group).
     ///
     /// An existing group member will need to perform a
@@ -814,6 +1457,116 @@ where
         })
     }
 
+    /// Verify a [`MembershipStatement`] created by another device of the same
+    /// user with [`Group::signed_membership_statement`].
+    ///
+    /// The statement must be signed by the signature key of its signer, and
+    /// the identity of the signer, as determined by the
+    /// [IdentityProvider](crate::IdentityProvider), must match the identity of
+    /// this client.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn verify_membership_statement(
+        &self,
+        statement: &MembershipStatement,
+    ) -> Result<(), MlsError> {
+        let cipher_suite_provider = self
+            .config
+            .crypto_provider()
+            .cipher_suite_provider(statement.cipher_suite())
+            .ok_or(MlsError::UnsupportedCipherSuite(statement.cipher_suite()))?;
+
+        statement.verify_signature(&cipher_suite_provider).await?;
+
+        let identity_provider = self.config.identity_provider();
+        let (signing_identity, _) = self.signing_identity()?;
+
+        let own_identity = identity_provider
+            .identity(signing_identity, &Default::default())
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+        let signer_identity = identity_provider
+            .identity(statement.signer(), &Default::default())
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+        (own_identity == signer_identity)
+            .then_some(())
+            .ok_or(MlsError::MembershipStatementSignerMismatch)
+    }
+
+    /// Decrypt a [`SealedGroupInfo`] created by
+    /// [`Group::sealed_group_info_message`] using the external pre-shared key
+    /// it is bound to.
+    ///
+    /// The pre-shared key is looked up in the
+    /// [PreSharedKeyStorage](crate::PreSharedKeyStorage) of this client. The
+    /// returned group info message can be used for an external commit.
+    #[cfg(feature = "psk")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn open_sealed_group_info(
+        &self,
+        sealed: &SealedGroupInfo,
+    ) -> Result<MlsMessage, MlsError> {
+        if !self.config.version_supported(sealed.version()) {
+            return Err(MlsError::UnsupportedProtocolVersion(sealed.version()));
+        }
+
+        let psk_id = sealed.external_psk_id().ok_or(MlsError::UnexpectedPskId)?;
+
+        let psk = get_external_psk(&self.config.secret_store(), psk_id).await?;
+
+        let cipher_suite_provider = self
+            .config
+            .crypto_provider()
+            .cipher_suite_provider(sealed.cipher_suite())
+            .ok_or(MlsError::UnsupportedCipherSuite(sealed.cipher_suite()))?;
+
+        sealed.open(&cipher_suite_provider, psk).await
+    }
+
+    /// Resume a membership exported by [`Group::export_member_state`] on
+    /// another device.
+    ///
+    /// The pre-shared key the export is bound to is looked up in the
+    /// [PreSharedKeyStorage](crate::PreSharedKeyStorage) of this client. The
+    /// signing key of the exported member is used by the returned group.
+    ///
+    /// If `self_update` is set, a commit with a path update is created
+    /// immediately and returned as pending commit. Once it is accepted by the
+    /// group, the leaf keys held by the exporting device are no longer valid.
+    #[cfg(feature = "psk")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn import_member_state(
+        &self,
+        export: &MemberStateExport,
+        self_update: bool,
+    ) -> Result<(Group<C>, Option<CommitOutput>), MlsError> {
+        if !self.config.version_supported(export.version()) {
+            return Err(MlsError::UnsupportedProtocolVersion(export.version()));
+        }
+
+        Group::import_member_state(self.config.clone(), export, self_update).await
+    }
+
+    /// Resume a group exported by [`Group::export_state_blob`] on another
+    /// device or storage backend.
+    ///
+    /// The imported group, including its prior epochs, is written to the
+    /// [GroupStateStorage](crate::GroupStateStorage) of this client. The
+    /// signing key of the exported member is used by the returned group.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn import_state_blob(
+        &self,
+        blob: &[u8],
+        protection_key: &[u8],
+    ) -> Result<Group<C>, MlsError> {
+        Group::import_state_blob(self.config.clone(), blob, protection_key).await
+    }
+
     fn signer(&self) -> Result<&SignatureSecretKey, MlsError> {
         self.signer.as_ref().ok_or(MlsError::SignerNotFound)
     }
@@ -826,6 +1579,91 @@ where
             .ok_or(MlsError::SignerNotFound)
     }
 
+    /// Register an additional signing identity with the matching signer and
+    /// cipher suite, e.g. to use different credentials for work and personal
+    /// groups. Registering an identity again replaces its signer.
+    ///
+    /// Use [`Client::with_signing_identity`] to select the identity used to
+    /// generate key packages, create groups and join groups.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn register_signing_identity(
+        &mut self,
+        signing_identity: SigningIdentity,
+        signer: SignatureSecretKey,
+        cipher_suite: CipherSuite,
+    ) {
+        self.registered_identities
+            .retain(|(id, _, cs)| id != &signing_identity || *cs != cipher_suite);
+
+        self.registered_identities
+            .push((signing_identity, signer, cipher_suite));
+    }
+
+    /// The signing identities known to this client, including the one it was
+    /// built with.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn signing_identities(&self) -> Vec<(&SigningIdentity, CipherSuite)> {
+        self.signing_identity
+            .iter()
+            .map(|(id, cs)| (id, *cs))
+            .chain(
+                self.registered_identities
+                    .iter()
+                    .map(|(id, _, cs)| (id, *cs)),
+            )
+            .collect()
+    }
+
+    /// A copy of this client that uses `signing_identity` for the given
+    /// cipher suite, which must be registered with
+    /// [`Client::register_signing_identity`] or be the identity the client
+    /// was built with.
+    ///
+    /// A group joined with a key package must be joined by a client using
+    /// the identity that generated the key package. Groups keep the signer
+    /// they were created or joined with, so groups loaded with
+    /// [`Client::load_group`] use the right signer regardless of the
+    /// identity selected on the loading client.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn with_signing_identity(
+        &self,
+        signing_identity: &SigningIdentity,
+        cipher_suite: CipherSuite,
+    ) -> Result<Client<C>, MlsError> {
+        let is_current = matches!(
+            &self.signing_identity,
+            Some((id, cs)) if id == signing_identity && *cs == cipher_suite
+        );
+
+        if is_current {
+            return Ok(self.clone());
+        }
+
+        let (_, signer, _) = self
+            .registered_identities
+            .iter()
+            .find(|(id, _, cs)| id == signing_identity && *cs == cipher_suite)
+            .ok_or(MlsError::SignerNotFound)?;
+
+        let mut client = self.clone();
+
+        // Keep the current identity selectable from the new client.
+        if let (Some((id, cs)), Some(current_signer)) =
+            (client.signing_identity.take(), client.signer.take())
+        {
+            client.register_signing_identity(id, current_signer, cs);
+        }
+
+        client
+            .registered_identities
+            .retain(|(id, _, cs)| id != signing_identity || *cs != cipher_suite);
+
+        client.signer = Some(signer.clone());
+        client.signing_identity = Some((signing_identity.clone(), cipher_suite));
+
+        Ok(client)
+    }
+
     /// The [KeyPackageStorage] that this client was configured to use.
     #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
     pub fn key_package_store(&self) -> <C as ClientConfig>::KeyPackageRepository {
@@ -917,11 +1755,12 @@ mod tests {
 
     use super::*;
     use crate::{
-        crypto::test_utils::TestCryptoProvider,
+        crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
         identity::test_utils::{get_test_basic_credential, get_test_signing_identity},
         tree_kem::leaf_node::LeafNodeSource,
     };
     use assert_matches::assert_matches;
+    use mls_rs_core::crypto::{SignatureKeyStorage, SignaturePublicKey};
 
     #[cfg(feature = "by_ref_proposal")]
     use crate::group::message_processor::ProposalMessageDescription;
@@ -930,7 +1769,7 @@ mod tests {
     use crate::group::test_utils::test_group;
     #[cfg(feature = "psk")]
     use crate::group::test_utils::test_group_custom_config;
-    #[cfg(feature = "by_ref_proposal")]
+    #[cfg(any(feature = "by_ref_proposal", feature = "private_message"))]
     use crate::group::ReceivedMessage;
     #[cfg(feature = "psk")]
     use crate::psk::{ExternalPskId, PreSharedKey};
@@ -978,6 +1817,91 @@ mod tests {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn signing_identity_can_be_selected_per_group() {
+        let (work, work_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"work").await;
+        let (personal, personal_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, b"personal").await;
+
+        let mut client = TestClientBuilder::new_for_test()
+            .signing_identity(work.clone(), work_key, TEST_CIPHER_SUITE)
+            .build();
+
+        client.register_signing_identity(personal.clone(), personal_key, TEST_CIPHER_SUITE);
+        assert_eq!(client.signing_identities().len(), 2);
+
+        let personal_client = client
+            .with_signing_identity(&personal, TEST_CIPHER_SUITE)
+            .unwrap();
+
+        let key_package = personal_client
+            .generate_key_package_message(Default::default(), Default::default(), None)
+            .await
+            .unwrap()
+            .into_key_package()
+            .unwrap();
+
+        assert_eq!(key_package.leaf_node.signing_identity, personal);
+
+        let mut group = personal_client
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+
+        // The group keeps its signer when loaded by a client using another identity.
+        let mut group = client.load_group(group.group_id()).await.unwrap();
+        assert_eq!(group.current_member_signing_identity().unwrap(), &personal);
+        group.commit(vec![]).await.unwrap();
+
+        let work_client = personal_client
+            .with_signing_identity(&work, TEST_CIPHER_SUITE)
+            .unwrap();
+
+        assert_eq!(work_client.signing_identity().unwrap().0, &work);
+        assert_eq!(work_client.signing_identities().len(), 2);
+
+        let (unknown, _) = get_test_signing_identity(TEST_CIPHER_SUITE, b"unknown").await;
+
+        assert_matches!(
+            client.with_signing_identity(&unknown, TEST_CIPHER_SUITE),
+            Err(MlsError::SignerNotFound)
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn preloaded_groups_are_returned_by_load_group() {
+        let (client, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let mut group = client
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+        let group_id = group.group_id().to_vec();
+
+        assert_eq!(client.group_readiness(&group_id), GroupReadiness::Cold);
+        client.preload_groups([&group_id]).await.unwrap();
+        assert_eq!(client.group_readiness(&group_id), GroupReadiness::Ready);
+
+        let loaded = client.load_group(&group_id).await.unwrap();
+
+        assert_eq!(
+            loaded.epoch_authenticator().unwrap(),
+            group.epoch_authenticator().unwrap()
+        );
+
+        assert_eq!(client.group_readiness(&group_id), GroupReadiness::Cold);
+
+        let res = client.preload_groups([b"unknown"]).await;
+
+        assert_matches!(res, Err(MlsError::GroupNotFound));
+        assert_eq!(client.group_readiness(b"unknown"), GroupReadiness::Cold);
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn new_member_add_proposal_adds_to_group() {
@@ -1233,6 +2157,111 @@ mod tests {
         assert_eq!(expected_group_info, group_info);
     }
 
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn decrypt_only_opens_message_without_loading_group() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
+            .await
+            .group;
+
+        let (bob, kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .commit_builder()
+            .add_member(kp)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let (mut bob_group, _) = bob
+            .join_group(None, &commit.welcome_messages[0], None)
+            .await
+            .unwrap();
+
+        bob_group.write_to_storage().await.unwrap();
+
+        let message = alice
+            .encrypt_application_message(b"hello", b"aad".to_vec())
+            .await
+            .unwrap();
+
+        let decrypted = bob.decrypt_only(&message).await.unwrap();
+
+        assert_eq!(decrypted.data(), b"hello");
+        assert_eq!(decrypted.authenticated_data, b"aad");
+        assert_eq!(decrypted.sender_index, 0);
+
+        // Nothing was written back so the full group can still process the message
+        let received = bob_group.process_incoming_message(message).await.unwrap();
+
+        assert_matches!(received, ReceivedMessage::ApplicationMessage(m) if m == decrypted);
+    }
+
+    #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn decrypt_only_opens_message_from_prior_epoch() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
+            .await
+            .group;
+
+        let (bob, kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .commit_builder()
+            .add_member(kp)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let (mut bob_group, _) = bob
+            .join_group(None, &commit.welcome_messages[0], None)
+            .await
+            .unwrap();
+
+        let message = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+
+        bob_group.process_incoming_message(commit).await.unwrap();
+        bob_group.write_to_storage().await.unwrap();
+
+        let decrypted = bob.decrypt_only(&message).await.unwrap();
+
+        assert_eq!(decrypted.data(), b"hello");
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn decrypt_only_requires_stored_group() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
+            .await
+            .group;
+
+        let (bob, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let message = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let res = bob.decrypt_only(&message).await;
+
+        assert_matches!(res, Err(MlsError::GroupNotFound));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn validate_group_info() {
         let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
@@ -1257,4 +2286,213 @@ mod tests {
         let res = bob.validate_group_info(&group_info, &other_signer).await;
         assert_matches!(res, Err(MlsError::InvalidSignature));
     }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn per_group_key_client(name: &'static str) -> Client<TestClientConfig> {
+        TestClientBuilder::new_for_test()
+            .with_random_signing_identity(name, TEST_CIPHER_SUITE)
+            .await
+            .per_group_signature_keys(
+                move |key: &SignaturePublicKey,
+                      _: CipherSuite|
+                      -> Result<SigningIdentity, AnyError> {
+                    let credential = get_test_basic_credential(name.as_bytes().to_vec());
+                    Ok(SigningIdentity::new(credential, key.clone()))
+                },
+            )
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn per_group_signature_keys_are_generated_and_selected() {
+        let alice = per_group_key_client("alice").await;
+        let bob = per_group_key_client("bob").await;
+
+        let base_key = &alice.signing_identity().unwrap().0.signature_key;
+
+        let mut alice_group = alice
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let other_group = alice
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let group_key = alice_group
+            .current_member_signing_identity()
+            .unwrap()
+            .signature_key
+            .clone();
+
+        let other_key = &other_group
+            .current_member_signing_identity()
+            .unwrap()
+            .signature_key;
+
+        assert_ne!(&group_key, base_key);
+        assert_ne!(&group_key, other_key);
+
+        let stored = alice
+            .config
+            .signature_key_storage()
+            .get(&group_key)
+            .await
+            .unwrap();
+
+        assert!(stored.is_some());
+
+        // Bob joins with a key package signed with a fresh key
+        let key_package = bob
+            .generate_key_package_message(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let commit = alice_group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice_group.apply_pending_commit().await.unwrap();
+        alice_group.write_to_storage().await.unwrap();
+
+        let (mut bob_group, _) = bob
+            .join_group(None, &commit.welcome_messages[0], None)
+            .await
+            .unwrap();
+
+        let bob_commit = bob_group.commit(vec![]).await.unwrap();
+        bob_group.apply_pending_commit().await.unwrap();
+
+        // The loaded group signs with the key matching its leaf
+        let mut alice_group = alice.load_group(alice_group.group_id()).await.unwrap();
+
+        alice_group
+            .process_incoming_message(bob_commit.commit_message)
+            .await
+            .unwrap();
+
+        let alice_commit = alice_group.commit(vec![]).await.unwrap();
+
+        bob_group
+            .process_incoming_message(alice_commit.commit_message)
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn per_group_signature_key_must_be_certified() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let issued = identity.clone();
+
+        let alice = TestClientBuilder::new_for_test()
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .per_group_signature_keys(
+                move |_: &SignaturePublicKey,
+                      _: CipherSuite|
+                      -> Result<SigningIdentity, AnyError> { Ok(issued.clone()) },
+            )
+            .build();
+
+        let res = alice
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::IssuedSignatureKeyMismatch));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn inviter_is_verified_on_join() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob, bob_kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .commit_builder()
+            .add_member(bob_kp)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let welcome = &commit.welcome_messages[0];
+        let tree = Some(alice.export_tree());
+
+        let res = bob
+            .join_group_with_inviter(tree.clone(), welcome, None, b"mallory")
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::UnexpectedInviter));
+
+        let (group, info) = bob
+            .join_group_with_inviter(tree, welcome, None, b"member")
+            .await
+            .unwrap();
+
+        assert_eq!(group.group_id(), alice.group_id());
+        assert_eq!(info.sender, alice.current_member_index());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_package_is_repackaged_with_the_same_init_key() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob, old_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let old_ref = old_key_package
+            .key_package_reference(&cs)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let new_key_package = bob
+            .repackage_key_package(&old_ref, Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let new_ref = new_key_package.key_package_reference(&cs).await.unwrap();
+
+        assert_ne!(new_ref, Some(old_ref.clone()));
+
+        assert_eq!(
+            new_key_package.as_key_package().unwrap().hpke_init_key,
+            old_key_package.as_key_package().unwrap().hpke_init_key
+        );
+
+        let res = bob
+            .repackage_key_package(&old_ref, Default::default(), Default::default(), None)
+            .await;
+
+        assert_matches!(res, Err(MlsError::KeyPackageNotFound));
+
+        let commit = alice
+            .commit_builder()
+            .add_member(new_key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let (group, _) = bob
+            .join_group(Some(alice.export_tree()), &commit.welcome_messages[0], None)
+            .await
+            .unwrap();
+
+        assert_eq!(group.group_id(), alice.group_id());
+    }
 }
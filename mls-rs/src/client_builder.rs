@@ -14,13 +14,19 @@ use crate::{
     group::{
         mls_rules::{DefaultMlsRules, MlsRules},
         proposal::ProposalType,
+        CipherSuitePolicy, SnapshotFormat, UnknownTypePolicy,
     },
     identity::CredentialType,
     identity::SigningIdentity,
+    identity::{CredentialIssuer, SharedCredentialIssuer},
     protocol_version::ProtocolVersion,
     psk::{ExternalPskId, PreSharedKey},
-    storage_provider::in_memory::{
-        InMemoryGroupStateStorage, InMemoryKeyPackageStorage, InMemoryPreSharedKeyStorage,
+    storage_provider::{
+        in_memory::{
+            InMemoryGroupIdMappingStorage, InMemoryGroupStateStorage, InMemoryKeyPackageStorage,
+            InMemoryPreSharedKeyStorage, InMemoryProposalCacheStorage, InMemorySignatureKeyStorage,
+        },
+        GroupStateObserver, ObservedGroupStateStorage,
     },
     time::MlsTime,
     tree_kem::{Capabilities, Lifetime},
@@ -35,13 +41,26 @@ use mls_rs_provider_sqlite::{
     SqLiteDataStorageEngine, SqLiteDataStorageError,
     {
         connection_strategy::ConnectionStrategy,
-        storage::{SqLiteGroupStateStorage, SqLiteKeyPackageStorage, SqLitePreSharedKeyStorage},
+        storage::{
+            SqLiteGroupIdMappingStorage, SqLiteGroupStateStorage, SqLiteKeyPackageStorage,
+            SqLitePreSharedKeyStorage,
+        },
     },
 };
 
+#[cfg(feature = "by_ref_proposal")]
+use crate::group::proposal_cache::ProposalCacheLimits;
+
+use crate::group::{security_watermark::SharedSecurityWatermarkStorage, SecurityWatermarkStorage};
+
 #[cfg(feature = "private_message")]
 pub use crate::group::padding::PaddingMode;
 
+#[cfg(feature = "security_events")]
+use crate::group::{security_event::SharedSecurityEventSink, SecurityEventSink};
+
+use alloc::sync::Arc;
+
 /// Base client configuration type when instantiating `ClientBuilder`
 pub type BaseConfig = Config<
     InMemoryKeyPackageStorage,
@@ -50,6 +69,9 @@ pub type BaseConfig = Config<
     Missing,
     DefaultMlsRules,
     Missing,
+    InMemoryProposalCacheStorage,
+    InMemoryGroupIdMappingStorage,
+    InMemorySignatureKeyStorage,
 >;
 
 /// Base client configuration type when instantiating `ClientBuilder`
@@ -60,9 +82,13 @@ pub type BaseInMemoryConfig = Config<
     Missing,
     Missing,
     Missing,
+    InMemoryProposalCacheStorage,
+    InMemoryGroupIdMappingStorage,
+    InMemorySignatureKeyStorage,
 >;
 
-pub type EmptyConfig = Config<Missing, Missing, Missing, Missing, Missing, Missing>;
+pub type EmptyConfig =
+    Config<Missing, Missing, Missing, Missing, Missing, Missing, Missing, Missing, Missing>;
 
 /// Base client configuration that is backed by SQLite storage.
 #[cfg(feature = "sqlite")]
@@ -73,6 +99,9 @@ pub type BaseSqlConfig = Config<
     Missing,
     DefaultMlsRules,
     Missing,
+    InMemoryProposalCacheStorage,
+    SqLiteGroupIdMappingStorage,
+    InMemorySignatureKeyStorage,
 >;
 
 /// Builder for [`Client`]
@@ -199,6 +228,9 @@ impl ClientBuilder<BaseConfig> {
             identity_provider: Missing,
             mls_rules: DefaultMlsRules::new(),
             crypto_provider: Missing,
+            proposal_cache_storage: Default::default(),
+            group_id_mapping_storage: Default::default(),
+            signature_key_storage: Default::default(),
             signer: Default::default(),
             signing_identity: Default::default(),
             version: ProtocolVersion::MLS_10,
@@ -216,6 +248,9 @@ impl ClientBuilder<EmptyConfig> {
             identity_provider: Missing,
             mls_rules: Missing,
             crypto_provider: Missing,
+            proposal_cache_storage: Missing,
+            group_id_mapping_storage: Missing,
+            signature_key_storage: Missing,
             signer: Default::default(),
             signing_identity: Default::default(),
             version: ProtocolVersion::MLS_10,
@@ -237,6 +272,9 @@ impl ClientBuilder<BaseSqlConfig> {
             identity_provider: Missing,
             mls_rules: DefaultMlsRules::new(),
             crypto_provider: Missing,
+            proposal_cache_storage: Default::default(),
+            group_id_mapping_storage: storage.group_id_mapping_storage()?,
+            signature_key_storage: Default::default(),
             signer: Default::default(),
             signing_identity: Default::default(),
             version: ProtocolVersion::MLS_10,
@@ -303,6 +341,190 @@ impl<C: IntoConfig> ClientBuilder<C> {
         ClientBuilder(c)
     }
 
+    /// Keep a group that was reinitialized able to decrypt application
+    /// messages for `epochs` epochs of the group that replaces it.
+    ///
+    /// Application messages sent in the predecessor group that arrive late are
+    /// processed by the new group and reported as
+    /// [`ReceivedMessage::PredecessorApplicationMessage`](crate::group::ReceivedMessage::PredecessorApplicationMessage).
+    /// The predecessor group is kept in memory only. By default, it is
+    /// discarded as soon as [`Group::get_reinit_client`](crate::Group::get_reinit_client)
+    /// is called.
+    ///
+    /// The window is stored with each group when it is created or joined, so
+    /// changing it does not affect existing groups.
+    #[cfg(all(feature = "psk", feature = "private_message"))]
+    pub fn reinit_predecessor_window(self, epochs: u64) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.reinit_predecessor_window = epochs;
+        ClientBuilder(c)
+    }
+
+    /// Report messages sent more than `epochs` epochs after the current epoch
+    /// of a group with [`MlsError::OutOfSync`](crate::error::MlsError::OutOfSync)
+    /// instead of [`MlsError::InvalidEpoch`](crate::error::MlsError::InvalidEpoch).
+    ///
+    /// Such a gap typically means the group state was restored from an old
+    /// backup and the commits in between are no longer available, so the
+    /// member has to rejoin with
+    /// [`Group::resync_commit_builder`](crate::Group::resync_commit_builder).
+    /// The default is 3.
+    pub fn out_of_sync_epoch_lag(self, epochs: u64) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.out_of_sync_epoch_lag = epochs;
+        ClientBuilder(c)
+    }
+
+    /// Let [`Group::write_ratchet_checkpoint`](crate::Group::write_ratchet_checkpoint)
+    /// skip writing the group to storage until `messages` application
+    /// messages were decrypted since the last write.
+    ///
+    /// A larger interval reduces storage writes at the cost of deriving the
+    /// keys of up to `messages` messages again after the group is loaded
+    /// from storage. The default is 1, which writes after every message.
+    ///
+    /// The interval is stored with each group when it is created or joined,
+    /// so changing it does not affect existing groups.
+    #[cfg(feature = "private_message")]
+    pub fn ratchet_checkpoint_interval(self, messages: u32) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.ratchet_checkpoint_interval = messages;
+        ClientBuilder(c)
+    }
+
+    /// Report security relevant failures, such as rejected credentials,
+    /// invalid signatures or protocol downgrades, to `sink`.
+    ///
+    /// Events are emitted in addition to the error returned to the caller
+    /// and never contain secret material. See
+    /// [`SecurityEvent`](crate::group::SecurityEvent).
+    #[cfg(feature = "security_events")]
+    pub fn security_event_sink<S>(self, sink: S) -> ClientBuilder<IntoConfigOutput<C>>
+    where
+        S: SecurityEventSink + 'static,
+    {
+        let mut c = self.0.into_config();
+        c.0.settings.security_event_sink = Some(Arc::new(sink));
+        ClientBuilder(c)
+    }
+
+    /// Use a separate signature key for every group of the client.
+    ///
+    /// A fresh signature key pair is generated whenever the client creates a
+    /// group or generates a key package, and `issuer` is asked for a
+    /// credential certifying it. Secret keys are kept in the
+    /// [signature key storage](ClientBuilder::signature_key_storage) and
+    /// selected automatically when joining or loading a group, so the
+    /// signing identity configured with
+    /// [`signing_identity`](ClientBuilder::signing_identity) only determines
+    /// the cipher suite of the client.
+    pub fn per_group_signature_keys<I>(self, issuer: I) -> ClientBuilder<IntoConfigOutput<C>>
+    where
+        I: CredentialIssuer + 'static,
+    {
+        let mut c = self.0.into_config();
+        c.0.settings.credential_issuer = Some(Arc::new(issuer));
+        ClientBuilder(c)
+    }
+
+    /// Record the security parameters of each group whenever its state is
+    /// written, and refuse to load a stored state below them.
+    ///
+    /// This detects a rollback of the group state storage to an earlier epoch
+    /// or to weaker parameters, which fails with
+    /// [`MlsError::BelowSecurityWatermark`](crate::client::MlsError::BelowSecurityWatermark).
+    /// See [`SecurityWatermark`](crate::group::SecurityWatermark).
+    pub fn security_watermark_storage<S>(self, storage: S) -> ClientBuilder<IntoConfigOutput<C>>
+    where
+        S: SecurityWatermarkStorage + Clone + 'static,
+    {
+        let mut c = self.0.into_config();
+        c.0.settings.security_watermark_storage = Some(Arc::new(storage));
+        ClientBuilder(c)
+    }
+
+    /// Keep at most `bytes` bytes of proposals cached by reference in the
+    /// memory of each group.
+    ///
+    /// Once the limit is exceeded, cached proposals are moved to the
+    /// [proposal cache storage](ClientBuilder::proposal_cache_storage) and
+    /// loaded back when a commit is created or processed. By default, all
+    /// cached proposals are kept in memory.
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn proposal_cache_memory_limit(self, bytes: usize) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.proposal_cache_limits.max_memory_bytes = Some(bytes);
+        ClientBuilder(c)
+    }
+
+    /// Reject proposals received by reference once a group has cached
+    /// `proposals` proposals in the current epoch, in memory and in the
+    /// [proposal cache storage](ClientBuilder::proposal_cache_storage)
+    /// combined. By default, the number of cached proposals is not limited.
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn proposal_cache_max_proposals(
+        self,
+        proposals: usize,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.proposal_cache_limits.max_proposals = Some(proposals);
+        ClientBuilder(c)
+    }
+
+    /// Set the policy used to rank cipher suites by strength and to warn
+    /// about or block groups that use a weak cipher suite.
+    ///
+    /// The policy is applied when creating groups, when committing additions
+    /// and by [`Client::select_cipher_suite`](crate::Client::select_cipher_suite).
+    pub fn cipher_suite_policy(
+        self,
+        policy: CipherSuitePolicy,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.cipher_suite_policy = policy;
+        ClientBuilder(c)
+    }
+
+    /// Set how extension and proposal types that are not supported by this
+    /// client are handled when they are received.
+    ///
+    /// By default, unknown types are ignored.
+    pub fn unknown_type_policy(
+        self,
+        policy: UnknownTypePolicy,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.unknown_type_policy = policy;
+        ClientBuilder(c)
+    }
+
+    /// Set the format used when writing group state to storage.
+    ///
+    /// Both formats can always be read by this version. By default,
+    /// [`SnapshotFormat::Legacy`] is used so that older clients sharing the
+    /// same storage can still read the group state.
+    /// [`SnapshotFormat::Sectioned`] should only be selected once all readers
+    /// support it, for example with [`SnapshotFormat::negotiate`].
+    pub fn snapshot_format(self, format: SnapshotFormat) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.snapshot_format = format;
+        ClientBuilder(c)
+    }
+
+    /// Keep the ratchet tree extension of Welcome messages in
+    /// [`NewMemberInfo::group_info_extensions`](crate::group::NewMemberInfo::group_info_extensions).
+    ///
+    /// If set to `false`, the encoded tree is released as soon as it is
+    /// decoded when joining, which reduces peak memory for groups with large
+    /// trees. The tree of the joined group remains available through
+    /// [`Group::export_tree`](crate::Group::export_tree). The default is
+    /// `true`.
+    pub fn keep_welcome_ratchet_tree(self, keep: bool) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.keep_welcome_ratchet_tree = keep;
+        ClientBuilder(c)
+    }
+
     /// Set the key package repository to be used by the client.
     ///
     /// By default, an in-memory repository is used.
@@ -320,6 +542,9 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider: c.crypto_provider,
+            proposal_cache_storage: c.proposal_cache_storage,
+            group_id_mapping_storage: c.group_id_mapping_storage,
+            signature_key_storage: c.signature_key_storage,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -343,6 +568,9 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider: c.crypto_provider,
+            proposal_cache_storage: c.proposal_cache_storage,
+            group_id_mapping_storage: c.group_id_mapping_storage,
+            signature_key_storage: c.signature_key_storage,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -369,6 +597,43 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             crypto_provider: c.crypto_provider,
             mls_rules: c.mls_rules,
+            proposal_cache_storage: c.proposal_cache_storage,
+            group_id_mapping_storage: c.group_id_mapping_storage,
+            signature_key_storage: c.signature_key_storage,
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            version: c.version,
+        }))
+    }
+
+    /// Notify `observer` every time the configured group state storage
+    /// persists a group state, e.g. to trigger backups when a new epoch is
+    /// written.
+    ///
+    /// This wraps the current group state storage in an
+    /// [`ObservedGroupStateStorage`], so it should be called after
+    /// [`group_state_storage`](Self::group_state_storage).
+    pub fn group_state_observer<O>(
+        self,
+        observer: O,
+    ) -> ClientBuilder<WithGroupStateStorage<ObservedGroupStateStorage<C::GroupStateStorage, O>, C>>
+    where
+        C::GroupStateStorage: GroupStateStorage,
+        O: GroupStateObserver,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: ObservedGroupStateStorage::new(c.group_state_storage, observer),
+            identity_provider: c.identity_provider,
+            crypto_provider: c.crypto_provider,
+            mls_rules: c.mls_rules,
+            proposal_cache_storage: c.proposal_cache_storage,
+            group_id_mapping_storage: c.group_id_mapping_storage,
+            signature_key_storage: c.signature_key_storage,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -393,6 +658,9 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider: c.crypto_provider,
+            proposal_cache_storage: c.proposal_cache_storage,
+            group_id_mapping_storage: c.group_id_mapping_storage,
+            signature_key_storage: c.signature_key_storage,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -417,6 +685,9 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules: c.mls_rules,
             crypto_provider,
+            proposal_cache_storage: c.proposal_cache_storage,
+            group_id_mapping_storage: c.group_id_mapping_storage,
+            signature_key_storage: c.signature_key_storage,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -444,6 +715,105 @@ impl<C: IntoConfig> ClientBuilder<C> {
             identity_provider: c.identity_provider,
             mls_rules,
             crypto_provider: c.crypto_provider,
+            proposal_cache_storage: c.proposal_cache_storage,
+            group_id_mapping_storage: c.group_id_mapping_storage,
+            signature_key_storage: c.signature_key_storage,
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            version: c.version,
+        }))
+    }
+
+    /// Set the storage used for proposals that groups cache by reference but
+    /// do not keep in memory.
+    ///
+    /// Proposals are only moved to this storage if a limit is set with
+    /// [`ClientBuilder::proposal_cache_memory_limit`]. By default, an
+    /// in-memory storage is used.
+    pub fn proposal_cache_storage<S>(
+        self,
+        proposal_cache_storage: S,
+    ) -> ClientBuilder<WithProposalCacheStorage<S, C>>
+    where
+        S: ProposalCacheStorage,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: c.crypto_provider,
+            proposal_cache_storage,
+            group_id_mapping_storage: c.group_id_mapping_storage,
+            signature_key_storage: c.signature_key_storage,
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            version: c.version,
+        }))
+    }
+
+    /// Set the storage mapping external ids, such as conversation ids, to
+    /// group ids.
+    ///
+    /// The external ids of a group are moved to its successor when the group
+    /// is reinitialized, see
+    /// [`GroupIdMappingStorage`](mls_rs_core::group::GroupIdMappingStorage).
+    /// By default, an in-memory storage is used.
+    pub fn group_id_mapping_storage<S>(
+        self,
+        group_id_mapping_storage: S,
+    ) -> ClientBuilder<WithGroupIdMappingStorage<S, C>>
+    where
+        S: GroupIdMappingStorage,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: c.crypto_provider,
+            proposal_cache_storage: c.proposal_cache_storage,
+            group_id_mapping_storage,
+            signature_key_storage: c.signature_key_storage,
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            version: c.version,
+        }))
+    }
+
+    /// Set the storage of the signature keys generated by the client when
+    /// [`per_group_signature_keys`](ClientBuilder::per_group_signature_keys)
+    /// is enabled, see
+    /// [`SignatureKeyStorage`](mls_rs_core::crypto::SignatureKeyStorage).
+    /// By default, an in-memory storage is used.
+    pub fn signature_key_storage<S>(
+        self,
+        signature_key_storage: S,
+    ) -> ClientBuilder<WithSignatureKeyStorage<S, C>>
+    where
+        S: SignatureKeyStorage,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: c.crypto_provider,
+            proposal_cache_storage: c.proposal_cache_storage,
+            group_id_mapping_storage: c.group_id_mapping_storage,
+            signature_key_storage,
             signer: c.signer,
             signing_identity: c.signing_identity,
             version: c.version,
@@ -500,6 +870,9 @@ where
     C::IdentityProvider: IdentityProvider + Clone,
     C::MlsRules: MlsRules + Clone,
     C::CryptoProvider: CryptoProvider + Clone,
+    C::ProposalCacheStorage: ProposalCacheStorage + Clone,
+    C::GroupIdMappingStorage: GroupIdMappingStorage + Clone,
+    C::SignatureKeyStorage: SignatureKeyStorage + Clone,
 {
     pub(crate) fn build_config(self) -> IntoConfigOutput<C> {
         let mut c = self.0.into_config();
@@ -552,6 +925,9 @@ pub type WithKeyPackageRepo<K, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::ProposalCacheStorage,
+    <C as IntoConfig>::GroupIdMappingStorage,
+    <C as IntoConfig>::SignatureKeyStorage,
 >;
 
 /// Change the PSK store used by a client configuration.
@@ -564,6 +940,9 @@ pub type WithPskStore<P, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::ProposalCacheStorage,
+    <C as IntoConfig>::GroupIdMappingStorage,
+    <C as IntoConfig>::SignatureKeyStorage,
 >;
 
 /// Change the group state storage used by a client configuration.
@@ -576,6 +955,9 @@ pub type WithGroupStateStorage<G, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::ProposalCacheStorage,
+    <C as IntoConfig>::GroupIdMappingStorage,
+    <C as IntoConfig>::SignatureKeyStorage,
 >;
 
 /// Change the identity validator used by a client configuration.
@@ -588,6 +970,9 @@ pub type WithIdentityProvider<I, C> = Config<
     I,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::ProposalCacheStorage,
+    <C as IntoConfig>::GroupIdMappingStorage,
+    <C as IntoConfig>::SignatureKeyStorage,
 >;
 
 /// Change the proposal rules used by a client configuration.
@@ -600,6 +985,9 @@ pub type WithMlsRules<Pr, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     Pr,
     <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::ProposalCacheStorage,
+    <C as IntoConfig>::GroupIdMappingStorage,
+    <C as IntoConfig>::SignatureKeyStorage,
 >;
 
 /// Change the crypto provider used by a client configuration.
@@ -612,6 +1000,54 @@ pub type WithCryptoProvider<Cp, C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     Cp,
+    <C as IntoConfig>::ProposalCacheStorage,
+    <C as IntoConfig>::GroupIdMappingStorage,
+    <C as IntoConfig>::SignatureKeyStorage,
+>;
+
+/// Change the proposal cache storage used by a client configuration.
+///
+/// See [`ClientBuilder::proposal_cache_storage`].
+pub type WithProposalCacheStorage<S, C> = Config<
+    <C as IntoConfig>::KeyPackageRepository,
+    <C as IntoConfig>::PskStore,
+    <C as IntoConfig>::GroupStateStorage,
+    <C as IntoConfig>::IdentityProvider,
+    <C as IntoConfig>::MlsRules,
+    <C as IntoConfig>::CryptoProvider,
+    S,
+    <C as IntoConfig>::GroupIdMappingStorage,
+    <C as IntoConfig>::SignatureKeyStorage,
+>;
+
+/// Change the group id mapping storage used by a client configuration.
+///
+/// See [`ClientBuilder::group_id_mapping_storage`].
+pub type WithGroupIdMappingStorage<S, C> = Config<
+    <C as IntoConfig>::KeyPackageRepository,
+    <C as IntoConfig>::PskStore,
+    <C as IntoConfig>::GroupStateStorage,
+    <C as IntoConfig>::IdentityProvider,
+    <C as IntoConfig>::MlsRules,
+    <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::ProposalCacheStorage,
+    S,
+    <C as IntoConfig>::SignatureKeyStorage,
+>;
+
+/// Change the signature key storage used by a client configuration.
+///
+/// See [`ClientBuilder::signature_key_storage`].
+pub type WithSignatureKeyStorage<S, C> = Config<
+    <C as IntoConfig>::KeyPackageRepository,
+    <C as IntoConfig>::PskStore,
+    <C as IntoConfig>::GroupStateStorage,
+    <C as IntoConfig>::IdentityProvider,
+    <C as IntoConfig>::MlsRules,
+    <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::ProposalCacheStorage,
+    <C as IntoConfig>::GroupIdMappingStorage,
+    S,
 >;
 
 /// Helper alias for `Config`.
@@ -622,6 +1058,9 @@ pub type IntoConfigOutput<C> = Config<
     <C as IntoConfig>::IdentityProvider,
     <C as IntoConfig>::MlsRules,
     <C as IntoConfig>::CryptoProvider,
+    <C as IntoConfig>::ProposalCacheStorage,
+    <C as IntoConfig>::GroupIdMappingStorage,
+    <C as IntoConfig>::SignatureKeyStorage,
 >;
 
 /// Helper alias to make a `Config` from a `ClientConfig`
@@ -632,9 +1071,13 @@ pub type MakeConfig<C> = Config<
     <C as ClientConfig>::IdentityProvider,
     <C as ClientConfig>::MlsRules,
     <C as ClientConfig>::CryptoProvider,
+    <C as ClientConfig>::ProposalCacheStorage,
+    <C as ClientConfig>::GroupIdMappingStorage,
+    <C as ClientConfig>::SignatureKeyStorage,
 >;
 
-impl<Kpr, Ps, Gss, Ip, Pr, Cp> ClientConfig for ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp>
+impl<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks> ClientConfig
+    for ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks>
 where
     Kpr: KeyPackageStorage + Clone,
     Ps: PreSharedKeyStorage + Clone,
@@ -642,6 +1085,9 @@ where
     Ip: IdentityProvider + Clone,
     Pr: MlsRules + Clone,
     Cp: CryptoProvider + Clone,
+    Pcs: ProposalCacheStorage + Clone,
+    Gim: GroupIdMappingStorage + Clone,
+    Sks: SignatureKeyStorage + Clone,
 {
     type KeyPackageRepository = Kpr;
     type PskStore = Ps;
@@ -649,6 +1095,9 @@ where
     type IdentityProvider = Ip;
     type MlsRules = Pr;
     type CryptoProvider = Cp;
+    type ProposalCacheStorage = Pcs;
+    type GroupIdMappingStorage = Gim;
+    type SignatureKeyStorage = Sks;
 
     fn supported_extensions(&self) -> Vec<ExtensionType> {
         self.settings.extension_types.clone()
@@ -682,6 +1131,18 @@ where
         self.crypto_provider.clone()
     }
 
+    fn proposal_cache_storage(&self) -> Self::ProposalCacheStorage {
+        self.proposal_cache_storage.clone()
+    }
+
+    fn group_id_mapping_storage(&self) -> Self::GroupIdMappingStorage {
+        self.group_id_mapping_storage.clone()
+    }
+
+    fn signature_key_storage(&self) -> Self::SignatureKeyStorage {
+        self.signature_key_storage.clone()
+    }
+
     fn lifetime(&self, timestamp: Option<MlsTime>) -> Lifetime {
         #[cfg(feature = "std")]
         let now_timestamp = MlsTime::now();
@@ -703,18 +1164,70 @@ where
 
         Lifetime {
             not_before: now_timestamp,
-            not_after: now_timestamp + self.settings.lifetime,
+            not_after: now_timestamp.saturating_add(self.settings.lifetime),
         }
     }
 
     fn supported_custom_proposals(&self) -> Vec<crate::group::proposal::ProposalType> {
         self.settings.custom_proposal_types.clone()
     }
+
+    #[cfg(all(feature = "psk", feature = "private_message"))]
+    fn reinit_predecessor_window(&self) -> u64 {
+        self.settings.reinit_predecessor_window
+    }
+
+    #[cfg(feature = "private_message")]
+    fn ratchet_checkpoint_interval(&self) -> u32 {
+        self.settings.ratchet_checkpoint_interval
+    }
+
+    fn out_of_sync_epoch_lag(&self) -> u64 {
+        self.settings.out_of_sync_epoch_lag
+    }
+
+    #[cfg(feature = "security_events")]
+    fn security_event_sink(&self) -> Option<SharedSecurityEventSink> {
+        self.settings.security_event_sink.clone()
+    }
+
+    fn credential_issuer(&self) -> Option<SharedCredentialIssuer> {
+        self.settings.credential_issuer.clone()
+    }
+
+    fn security_watermark_storage(&self) -> Option<SharedSecurityWatermarkStorage> {
+        self.settings.security_watermark_storage.clone()
+    }
+
+    fn cipher_suite_policy(&self) -> CipherSuitePolicy {
+        self.settings.cipher_suite_policy.clone()
+    }
+
+    fn unknown_type_policy(&self) -> UnknownTypePolicy {
+        self.settings.unknown_type_policy
+    }
+
+    fn snapshot_format(&self) -> SnapshotFormat {
+        self.settings.snapshot_format
+    }
+
+    fn keep_welcome_ratchet_tree(&self) -> bool {
+        self.settings.keep_welcome_ratchet_tree
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn proposal_cache_limits(&self) -> ProposalCacheLimits {
+        self.settings.proposal_cache_limits
+    }
 }
 
-impl<Kpr, Ps, Gss, Ip, Pr, Cp> Sealed for Config<Kpr, Ps, Gss, Ip, Pr, Cp> {}
+impl<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks> Sealed
+    for Config<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks>
+{
+}
 
-impl<Kpr, Ps, Gss, Ip, Pr, Cp> MlsConfig for Config<Kpr, Ps, Gss, Ip, Pr, Cp>
+impl<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks> MlsConfig
+    for Config<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks>
 where
     Kpr: KeyPackageStorage + Clone,
 
@@ -723,8 +1236,11 @@ where
     Ip: IdentityProvider + Clone,
     Pr: MlsRules + Clone,
     Cp: CryptoProvider + Clone,
+    Pcs: ProposalCacheStorage + Clone,
+    Gim: GroupIdMappingStorage + Clone,
+    Sks: SignatureKeyStorage + Clone,
 {
-    type Output = ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp>;
+    type Output = ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks>;
 
     fn get(&self) -> &Self::Output {
         &self.0
@@ -750,6 +1266,9 @@ impl<T: MlsConfig> ClientConfig for T {
     type IdentityProvider = <T::Output as ClientConfig>::IdentityProvider;
     type MlsRules = <T::Output as ClientConfig>::MlsRules;
     type CryptoProvider = <T::Output as ClientConfig>::CryptoProvider;
+    type ProposalCacheStorage = <T::Output as ClientConfig>::ProposalCacheStorage;
+    type GroupIdMappingStorage = <T::Output as ClientConfig>::GroupIdMappingStorage;
+    type SignatureKeyStorage = <T::Output as ClientConfig>::SignatureKeyStorage;
 
     fn supported_extensions(&self) -> Vec<ExtensionType> {
         self.get().supported_extensions()
@@ -787,6 +1306,18 @@ impl<T: MlsConfig> ClientConfig for T {
         self.get().crypto_provider()
     }
 
+    fn proposal_cache_storage(&self) -> Self::ProposalCacheStorage {
+        self.get().proposal_cache_storage()
+    }
+
+    fn group_id_mapping_storage(&self) -> Self::GroupIdMappingStorage {
+        self.get().group_id_mapping_storage()
+    }
+
+    fn signature_key_storage(&self) -> Self::SignatureKeyStorage {
+        self.get().signature_key_storage()
+    }
+
     fn lifetime(&self, timestamp: Option<MlsTime>) -> Lifetime {
         self.get().lifetime(timestamp)
     }
@@ -802,6 +1333,54 @@ impl<T: MlsConfig> ClientConfig for T {
     fn supported_credential_types(&self) -> Vec<CredentialType> {
         self.get().supported_credential_types()
     }
+
+    #[cfg(all(feature = "psk", feature = "private_message"))]
+    fn reinit_predecessor_window(&self) -> u64 {
+        self.get().reinit_predecessor_window()
+    }
+
+    #[cfg(feature = "private_message")]
+    fn ratchet_checkpoint_interval(&self) -> u32 {
+        self.get().ratchet_checkpoint_interval()
+    }
+
+    fn out_of_sync_epoch_lag(&self) -> u64 {
+        self.get().out_of_sync_epoch_lag()
+    }
+
+    #[cfg(feature = "security_events")]
+    fn security_event_sink(&self) -> Option<SharedSecurityEventSink> {
+        self.get().security_event_sink()
+    }
+
+    fn credential_issuer(&self) -> Option<SharedCredentialIssuer> {
+        self.get().credential_issuer()
+    }
+
+    fn security_watermark_storage(&self) -> Option<SharedSecurityWatermarkStorage> {
+        self.get().security_watermark_storage()
+    }
+
+    fn cipher_suite_policy(&self) -> CipherSuitePolicy {
+        self.get().cipher_suite_policy()
+    }
+
+    fn unknown_type_policy(&self) -> UnknownTypePolicy {
+        self.get().unknown_type_policy()
+    }
+
+    fn snapshot_format(&self) -> SnapshotFormat {
+        self.get().snapshot_format()
+    }
+
+    fn keep_welcome_ratchet_tree(&self) -> bool {
+        self.get().keep_welcome_ratchet_tree()
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn proposal_cache_limits(&self) -> ProposalCacheLimits {
+        self.get().proposal_cache_limits()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -812,6 +1391,21 @@ pub(crate) struct Settings {
     pub(crate) lifetime: Duration,
     #[cfg(any(test, feature = "test_util"))]
     pub(crate) key_package_not_before: Option<MlsTime>,
+    #[cfg(all(feature = "psk", feature = "private_message"))]
+    pub(crate) reinit_predecessor_window: u64,
+    #[cfg(feature = "private_message")]
+    pub(crate) ratchet_checkpoint_interval: u32,
+    pub(crate) out_of_sync_epoch_lag: u64,
+    #[cfg(feature = "security_events")]
+    pub(crate) security_event_sink: Option<SharedSecurityEventSink>,
+    pub(crate) credential_issuer: Option<SharedCredentialIssuer>,
+    pub(crate) security_watermark_storage: Option<SharedSecurityWatermarkStorage>,
+    pub(crate) cipher_suite_policy: CipherSuitePolicy,
+    pub(crate) unknown_type_policy: UnknownTypePolicy,
+    pub(crate) snapshot_format: SnapshotFormat,
+    pub(crate) keep_welcome_ratchet_tree: bool,
+    #[cfg(feature = "by_ref_proposal")]
+    pub(crate) proposal_cache_limits: ProposalCacheLimits,
 }
 
 impl Default for Settings {
@@ -823,6 +1417,21 @@ impl Default for Settings {
             custom_proposal_types: Default::default(),
             #[cfg(any(test, feature = "test_util"))]
             key_package_not_before: None,
+            #[cfg(all(feature = "psk", feature = "private_message"))]
+            reinit_predecessor_window: 0,
+            #[cfg(feature = "private_message")]
+            ratchet_checkpoint_interval: 1,
+            out_of_sync_epoch_lag: 3,
+            #[cfg(feature = "security_events")]
+            security_event_sink: None,
+            credential_issuer: None,
+            security_watermark_storage: None,
+            cipher_suite_policy: Default::default(),
+            unknown_type_policy: Default::default(),
+            snapshot_format: Default::default(),
+            keep_welcome_ratchet_tree: true,
+            #[cfg(feature = "by_ref_proposal")]
+            proposal_cache_limits: Default::default(),
         }
     }
 }
@@ -845,6 +1454,21 @@ pub(crate) fn recreate_config<T: ClientConfig>(
             },
             #[cfg(any(test, feature = "test_util"))]
             key_package_not_before: None,
+            #[cfg(all(feature = "psk", feature = "private_message"))]
+            reinit_predecessor_window: c.reinit_predecessor_window(),
+            #[cfg(feature = "private_message")]
+            ratchet_checkpoint_interval: c.ratchet_checkpoint_interval(),
+            out_of_sync_epoch_lag: c.out_of_sync_epoch_lag(),
+            #[cfg(feature = "security_events")]
+            security_event_sink: c.security_event_sink(),
+            credential_issuer: c.credential_issuer(),
+            security_watermark_storage: c.security_watermark_storage(),
+            cipher_suite_policy: c.cipher_suite_policy(),
+            unknown_type_policy: c.unknown_type_policy(),
+            snapshot_format: c.snapshot_format(),
+            keep_welcome_ratchet_tree: c.keep_welcome_ratchet_tree(),
+            #[cfg(feature = "by_ref_proposal")]
+            proposal_cache_limits: c.proposal_cache_limits(),
         },
         key_package_repo: c.key_package_repo(),
         psk_store: c.secret_store(),
@@ -852,6 +1476,9 @@ pub(crate) fn recreate_config<T: ClientConfig>(
         identity_provider: c.identity_provider(),
         mls_rules: c.mls_rules(),
         crypto_provider: c.crypto_provider(),
+        proposal_cache_storage: c.proposal_cache_storage(),
+        group_id_mapping_storage: c.group_id_mapping_storage(),
+        signature_key_storage: c.signature_key_storage(),
         signer,
         signing_identity,
         version,
@@ -870,10 +1497,12 @@ mod private {
     use crate::client_builder::{IntoConfigOutput, Settings};
 
     #[derive(Clone, Debug)]
-    pub struct Config<Kpr, Ps, Gss, Ip, Pr, Cp>(pub(crate) ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp>);
+    pub struct Config<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks>(
+        pub(crate) ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks>,
+    );
 
     #[derive(Clone, Debug)]
-    pub struct ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp> {
+    pub struct ConfigInner<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks> {
         pub(crate) settings: Settings,
         pub(crate) key_package_repo: Kpr,
         pub(crate) psk_store: Ps,
@@ -881,6 +1510,9 @@ mod private {
         pub(crate) identity_provider: Ip,
         pub(crate) mls_rules: Pr,
         pub(crate) crypto_provider: Cp,
+        pub(crate) proposal_cache_storage: Pcs,
+        pub(crate) group_id_mapping_storage: Gim,
+        pub(crate) signature_key_storage: Sks,
         pub(crate) signer: Option<SignatureSecretKey>,
         pub(crate) signing_identity: Option<(SigningIdentity, CipherSuite)>,
         pub(crate) version: ProtocolVersion,
@@ -893,17 +1525,25 @@ mod private {
         type IdentityProvider;
         type MlsRules;
         type CryptoProvider;
+        type ProposalCacheStorage;
+        type GroupIdMappingStorage;
+        type SignatureKeyStorage;
 
         fn into_config(self) -> IntoConfigOutput<Self>;
     }
 
-    impl<Kpr, Ps, Gss, Ip, Pr, Cp> IntoConfig for Config<Kpr, Ps, Gss, Ip, Pr, Cp> {
+    impl<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks> IntoConfig
+        for Config<Kpr, Ps, Gss, Ip, Pr, Cp, Pcs, Gim, Sks>
+    {
         type KeyPackageRepository = Kpr;
         type PskStore = Ps;
         type GroupStateStorage = Gss;
         type IdentityProvider = Ip;
         type MlsRules = Pr;
         type CryptoProvider = Cp;
+        type ProposalCacheStorage = Pcs;
+        type GroupIdMappingStorage = Gim;
+        type SignatureKeyStorage = Sks;
 
         fn into_config(self) -> Self {
             self
@@ -912,8 +1552,8 @@ mod private {
 }
 
 use mls_rs_core::{
-    crypto::{CryptoProvider, SignatureSecretKey},
-    group::GroupStateStorage,
+    crypto::{CryptoProvider, SignatureKeyStorage, SignatureSecretKey},
+    group::{GroupIdMappingStorage, GroupStateStorage, ProposalCacheStorage},
     identity::IdentityProvider,
     key_package::KeyPackageStorage,
     psk::PreSharedKeyStorage,
@@ -4,8 +4,12 @@
 
 use crate::{
     extension::ExtensionType,
-    group::{mls_rules::MlsRules, proposal::ProposalType},
-    identity::CredentialType,
+    group::{
+        mls_rules::MlsRules, proposal::ProposalType,
+        security_watermark::SharedSecurityWatermarkStorage, CipherSuitePolicy, SnapshotFormat,
+        UnknownTypePolicy,
+    },
+    identity::{CredentialType, SharedCredentialIssuer},
     protocol_version::ProtocolVersion,
     time::MlsTime,
     tree_kem::{leaf_node::ConfigProperties, Capabilities, Lifetime},
@@ -13,10 +17,19 @@ use crate::{
 };
 use alloc::vec::Vec;
 use mls_rs_core::{
-    crypto::CryptoProvider, group::GroupStateStorage, identity::IdentityProvider,
-    key_package::KeyPackageStorage, psk::PreSharedKeyStorage,
+    crypto::{CryptoProvider, SignatureKeyStorage},
+    group::{GroupIdMappingStorage, GroupStateStorage, ProposalCacheStorage},
+    identity::IdentityProvider,
+    key_package::KeyPackageStorage,
+    psk::PreSharedKeyStorage,
 };
 
+#[cfg(feature = "by_ref_proposal")]
+use crate::group::proposal_cache::ProposalCacheLimits;
+
+#[cfg(feature = "security_events")]
+use crate::group::security_event::SharedSecurityEventSink;
+
 pub trait ClientConfig: Send + Sync + Clone {
     type KeyPackageRepository: KeyPackageStorage + Clone;
     type PskStore: PreSharedKeyStorage + Clone;
@@ -24,6 +37,9 @@ pub trait ClientConfig: Send + Sync + Clone {
     type IdentityProvider: IdentityProvider + Clone;
     type MlsRules: MlsRules + Clone;
     type CryptoProvider: CryptoProvider + Clone;
+    type ProposalCacheStorage: ProposalCacheStorage + Clone;
+    type GroupIdMappingStorage: GroupIdMappingStorage + Clone;
+    type SignatureKeyStorage: SignatureKeyStorage + Clone;
 
     fn supported_extensions(&self) -> Vec<ExtensionType>;
     fn supported_custom_proposals(&self) -> Vec<ProposalType>;
@@ -37,6 +53,9 @@ pub trait ClientConfig: Send + Sync + Clone {
     fn group_state_storage(&self) -> Self::GroupStateStorage;
     fn identity_provider(&self) -> Self::IdentityProvider;
     fn crypto_provider(&self) -> Self::CryptoProvider;
+    fn proposal_cache_storage(&self) -> Self::ProposalCacheStorage;
+    fn group_id_mapping_storage(&self) -> Self::GroupIdMappingStorage;
+    fn signature_key_storage(&self) -> Self::SignatureKeyStorage;
 
     fn lifetime(&self, timestamp: Option<MlsTime>) -> Lifetime;
 
@@ -58,6 +77,54 @@ pub trait ClientConfig: Send + Sync + Clone {
         self.identity_provider().supported_types()
     }
 
+    #[cfg(all(feature = "psk", feature = "private_message"))]
+    fn reinit_predecessor_window(&self) -> u64 {
+        0
+    }
+
+    #[cfg(feature = "private_message")]
+    fn ratchet_checkpoint_interval(&self) -> u32 {
+        1
+    }
+
+    fn out_of_sync_epoch_lag(&self) -> u64 {
+        3
+    }
+
+    #[cfg(feature = "security_events")]
+    fn security_event_sink(&self) -> Option<SharedSecurityEventSink> {
+        None
+    }
+
+    fn credential_issuer(&self) -> Option<SharedCredentialIssuer> {
+        None
+    }
+
+    fn security_watermark_storage(&self) -> Option<SharedSecurityWatermarkStorage> {
+        None
+    }
+
+    fn cipher_suite_policy(&self) -> CipherSuitePolicy {
+        CipherSuitePolicy::default()
+    }
+
+    fn unknown_type_policy(&self) -> UnknownTypePolicy {
+        UnknownTypePolicy::default()
+    }
+
+    fn snapshot_format(&self) -> SnapshotFormat {
+        SnapshotFormat::default()
+    }
+
+    fn keep_welcome_ratchet_tree(&self) -> bool {
+        true
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn proposal_cache_limits(&self) -> ProposalCacheLimits {
+        ProposalCacheLimits::default()
+    }
+
     fn leaf_properties(&self, leaf_node_extensions: ExtensionList) -> ConfigProperties {
         ConfigProperties {
             capabilities: self.capabilities(),
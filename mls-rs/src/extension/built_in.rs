@@ -5,7 +5,7 @@
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
-use mls_rs_core::extension::{ExtensionType, MlsCodecExtension};
+use mls_rs_core::extension::{ExtensionType, MlsExtension};
 
 use mls_rs_core::{group::ProposalType, identity::CredentialType};
 
@@ -28,7 +28,8 @@ use mls_rs_core::crypto::HpkePublicKey;
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
 )]
-#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode, MlsExtension)]
+#[mls_extension(extension_type = ExtensionType::APPLICATION_ID)]
 pub struct ApplicationIdExt {
     /// Application level identifier presented by this extension.
     #[mls_codec(with = "mls_rs_codec::byte_vec")]
@@ -60,12 +61,6 @@ impl ApplicationIdExt {
     }
 }
 
-impl MlsCodecExtension for ApplicationIdExt {
-    fn extension_type() -> ExtensionType {
-        ExtensionType::APPLICATION_ID
-    }
-}
-
 /// Representation of an MLS ratchet tree.
 ///
 /// Used to provide new members
@@ -74,7 +69,8 @@ impl MlsCodecExtension for ApplicationIdExt {
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
 )]
-#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode, MlsExtension)]
+#[mls_extension(extension_type = ExtensionType::RATCHET_TREE)]
 pub struct RatchetTreeExt {
     pub tree_data: ExportedTree<'static>,
 }
@@ -88,12 +84,6 @@ impl RatchetTreeExt {
     }
 }
 
-impl MlsCodecExtension for RatchetTreeExt {
-    fn extension_type() -> ExtensionType {
-        ExtensionType::RATCHET_TREE
-    }
-}
-
 /// Require members to have certain capabilities.
 ///
 /// Used within a
@@ -110,7 +100,8 @@ impl MlsCodecExtension for RatchetTreeExt {
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
 )]
-#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode, MlsExtension, Default)]
+#[mls_extension(extension_type = ExtensionType::REQUIRED_CAPABILITIES)]
 pub struct RequiredCapabilitiesExt {
     pub extensions: Vec<ExtensionType>,
     pub proposals: Vec<ProposalType>,
@@ -151,12 +142,6 @@ impl RequiredCapabilitiesExt {
     }
 }
 
-impl MlsCodecExtension for RequiredCapabilitiesExt {
-    fn extension_type() -> ExtensionType {
-        ExtensionType::REQUIRED_CAPABILITIES
-    }
-}
-
 /// External public key used for [External Commits](crate::Client::commit_external).
 ///
 /// This proposal type is optionally provided as part of a
@@ -165,7 +150,8 @@ impl MlsCodecExtension for RequiredCapabilitiesExt {
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
 )]
-#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode, MlsExtension)]
+#[mls_extension(extension_type = ExtensionType::EXTERNAL_PUB)]
 pub struct ExternalPubExt {
     /// Public key to be used for an external commit.
     #[mls_codec(with = "mls_rs_codec::byte_vec")]
@@ -181,19 +167,14 @@ impl ExternalPubExt {
     }
 }
 
-impl MlsCodecExtension for ExternalPubExt {
-    fn extension_type() -> ExtensionType {
-        ExtensionType::EXTERNAL_PUB
-    }
-}
-
 /// Enable proposals by an [ExternalClient](crate::external_client::ExternalClient).
 #[cfg(feature = "by_ref_proposal")]
 #[cfg_attr(
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
 )]
-#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode, MlsExtension)]
+#[mls_extension(extension_type = ExtensionType::EXTERNAL_SENDERS)]
 #[non_exhaustive]
 pub struct ExternalSendersExt {
     pub allowed_senders: Vec<SigningIdentity>,
@@ -228,13 +209,6 @@ impl ExternalSendersExt {
     }
 }
 
-#[cfg(feature = "by_ref_proposal")]
-impl MlsCodecExtension for ExternalSendersExt {
-    fn extension_type() -> ExtensionType {
-        ExtensionType::EXTERNAL_SENDERS
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
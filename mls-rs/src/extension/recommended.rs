@@ -10,7 +10,7 @@
 //! [1]: https://datatracker.ietf.org/doc/html/draft-ietf-mls-extensions-04
 
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
-use mls_rs_core::extension::{ExtensionType, MlsCodecExtension};
+use mls_rs_core::extension::{ExtensionType, MlsExtension};
 
 /// Last resort key packages.
 ///
@@ -18,12 +18,6 @@ use mls_rs_core::extension::{ExtensionType, MlsCodecExtension};
 /// signal to the Delivery Service which key packages are meant to be
 /// used as last resort key packages.
 #[cfg(feature = "last_resort_key_package_ext")]
-#[derive(Debug, Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[derive(Debug, Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode, MlsExtension)]
+#[mls_extension(extension_type = ExtensionType::LAST_RESORT_KEY_PACKAGE)]
 pub struct LastResortKeyPackageExt;
-
-#[cfg(feature = "last_resort_key_package_ext")]
-impl MlsCodecExtension for LastResortKeyPackageExt {
-    fn extension_type() -> ExtensionType {
-        ExtensionType::LAST_RESORT_KEY_PACKAGE
-    }
-}
@@ -15,6 +15,8 @@ use crate::{
 pub mod builder;
 mod config;
 mod group;
+#[cfg(feature = "by_ref_proposal")]
+mod proposal_builder;
 
 pub(crate) use config::ExternalClientConfig;
 use mls_rs_core::{
@@ -24,7 +26,10 @@ use mls_rs_core::{
 
 use builder::{ExternalBaseConfig, ExternalClientBuilder};
 
-pub use group::{ExternalGroup, ExternalReceivedMessage, ExternalSnapshot};
+pub use group::{ExternalGroup, ExternalReanchor, ExternalReceivedMessage, ExternalSnapshot};
+
+#[cfg(feature = "by_ref_proposal")]
+pub use proposal_builder::ExternalProposalBuilder;
 
 /// A client capable of observing a group's state without having
 /// private keys required to read content.
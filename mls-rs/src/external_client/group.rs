@@ -16,12 +16,13 @@ use crate::{
     group::{
         cipher_suite_provider,
         confirmation_tag::ConfirmationTag,
-        framing::PublicMessage,
+        framing::{MlsMessagePayload, PublicMessage, Sender},
         member_from_leaf_node,
         message_processor::{
             ApplicationMessageDescription, CommitMessageDescription, EventOrContent,
             MessageProcessor, ProposalMessageDescription, ProvisionalState,
         },
+        message_verifier::{verify_plaintext_authentication, SignaturePublicKeysContainer},
         proposal::RemoveProposal,
         proposal_filter::ProposalInfo,
         snapshot::RawGroupState,
@@ -47,12 +48,8 @@ use crate::group::proposal::SelfRemoveProposal;
 #[cfg(feature = "by_ref_proposal")]
 use crate::{
     group::{
-        framing::{Content, MlsMessagePayload},
-        message_processor::CachedProposal,
-        message_signature::AuthenticatedContent,
-        proposal::Proposal,
-        proposal_ref::ProposalRef,
-        Sender,
+        framing::Content, message_processor::CachedProposal,
+        message_signature::AuthenticatedContent, proposal::Proposal, proposal_ref::ProposalRef,
     },
     WireFormat,
 };
@@ -63,6 +60,9 @@ use crate::group::proposal::CustomProposal;
 #[cfg(feature = "by_ref_proposal")]
 use mls_rs_core::{crypto::CipherSuiteProvider, psk::ExternalPskId};
 
+#[cfg(feature = "by_ref_proposal")]
+use super::ExternalProposalBuilder;
+
 #[cfg(feature = "by_ref_proposal")]
 use crate::{
     extension::ExternalSendersExt,
@@ -80,6 +80,9 @@ use crate::{
 #[cfg(feature = "private_message")]
 use crate::group::framing::PrivateMessage;
 
+#[cfg(feature = "targeted_messages")]
+use crate::group::targeted_message::TargetedMessage;
+
 use alloc::boxed::Box;
 
 /// The result of processing an [ExternalGroup](ExternalGroup) message using
@@ -123,48 +126,83 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
         tree_data: Option<ExportedTree<'_>>,
         maybe_time: Option<MlsTime>,
     ) -> Result<Self, MlsError> {
-        let protocol_version = group_info.version;
+        let (state, cipher_suite_provider) =
+            state_from_group_info(&config, group_info, tree_data, maybe_time).await?;
+
+        Ok(Self {
+            config,
+            signing_data,
+            state,
+            cipher_suite_provider,
+        })
+    }
+
+    /// Move this group to the epoch of a newer `group_info`, for instance
+    /// one published periodically by the delivery service, after missing
+    /// one or more commits.
+    ///
+    /// The group info and its ratchet tree are validated the same way as
+    /// when joining with [`ExternalClient::observe_group`](crate::external_client::ExternalClient::observe_group).
+    /// In addition, `group_info` must describe the same group, with the same
+    /// protocol version and cipher suite, at an epoch that is not older than
+    /// the current one. A group info for the current epoch is only accepted
+    /// if its tree hash matches the current tree hash, in which case the
+    /// group is left unchanged.
+    ///
+    /// Proposals cached for the current epoch are discarded when the epoch
+    /// changes. The returned [`ExternalReanchor`] reports the epochs that
+    /// were skipped.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn reanchor(
+        &mut self,
+        group_info: MlsMessage,
+        tree_data: Option<ExportedTree<'_>>,
+        maybe_time: Option<MlsTime>,
+    ) -> Result<ExternalReanchor, MlsError> {
+        let current = &self.state.context;
 
-        if !config.version_supported(protocol_version) {
-            return Err(MlsError::UnsupportedProtocolVersion(protocol_version));
+        if group_info.version != current.protocol_version {
+            return Err(MlsError::ProtocolVersionMismatch);
         }
 
-        let group_info = group_info
-            .into_group_info()
+        let info = group_info
+            .as_group_info()
             .ok_or(MlsError::UnexpectedMessageType)?;
 
-        let cipher_suite_provider = cipher_suite_provider(
-            config.crypto_provider(),
-            group_info.group_context.cipher_suite,
-        )?;
-
-        let public_tree = validate_tree_and_info_joiner(
-            protocol_version,
-            &group_info,
-            tree_data,
-            &config.identity_provider(),
-            &cipher_suite_provider,
-            maybe_time,
-        )
-        .await?;
+        let context = &info.group_context;
 
-        let interim_transcript_hash = InterimTranscriptHash::create(
-            &cipher_suite_provider,
-            &group_info.group_context.confirmed_transcript_hash,
-            &group_info.confirmation_tag,
-        )
-        .await?;
+        if context.group_id != current.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
 
-        Ok(Self {
-            config,
-            signing_data,
-            state: GroupState::new(
-                group_info.group_context,
-                public_tree,
-                interim_transcript_hash,
-                group_info.confirmation_tag,
-            ),
-            cipher_suite_provider,
+        if context.cipher_suite != current.cipher_suite {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        let previous_epoch = current.epoch;
+
+        if context.epoch < previous_epoch
+            || (context.epoch == previous_epoch && context.tree_hash != current.tree_hash)
+        {
+            return Err(MlsError::StaleGroupInfo(context.epoch));
+        }
+
+        if context.epoch == previous_epoch {
+            return Ok(ExternalReanchor {
+                previous_epoch,
+                epoch: previous_epoch,
+            });
+        }
+
+        let (state, cipher_suite_provider) =
+            state_from_group_info(&self.config, group_info, tree_data, maybe_time).await?;
+
+        self.state = state;
+        self.cipher_suite_provider = cipher_suite_provider;
+
+        Ok(ExternalReanchor {
+            previous_epoch,
+            epoch: self.state.context.epoch,
         })
     }
 
@@ -229,6 +267,48 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
         .await
     }
 
+    /// Verify that `message` is a [`PublicMessage`] for the current epoch of
+    /// this group, signed by its sender.
+    ///
+    /// Unlike [`process_incoming_message`](Self::process_incoming_message),
+    /// the content of the message is not validated and the group state is
+    /// left untouched: proposals are not cached and commits are not applied.
+    /// This makes it suitable for filtering untrusted traffic before it
+    /// reaches the members of the group.
+    ///
+    /// Returns the sender of the message.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_signature_only(&self, message: &MlsMessage) -> Result<Sender, MlsError> {
+        let context = &self.state.context;
+
+        if message.version != context.protocol_version {
+            return Err(MlsError::ProtocolVersionMismatch);
+        }
+
+        let MlsMessagePayload::Plain(plaintext) = &message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        if plaintext.content.group_id != context.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if plaintext.content.epoch != context.epoch {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        let auth_content = verify_plaintext_authentication(
+            &self.cipher_suite_provider,
+            plaintext.clone(),
+            None,
+            context,
+            SignaturePublicKeysContainer::RatchetTree(&self.state.public_tree),
+        )
+        .await?;
+
+        Ok(auth_content.content.sender)
+    }
+
     /// Replay a proposal message into the group skipping all validation steps.
     #[cfg(feature = "by_ref_proposal")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -371,7 +451,7 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
     }
 
     #[cfg(all(feature = "by_ref_proposal", feature = "psk"))]
-    fn psk_proposal(&self, key_id: JustPreSharedKeyID) -> Result<Proposal, MlsError> {
+    pub(super) fn psk_proposal(&self, key_id: JustPreSharedKeyID) -> Result<Proposal, MlsError> {
         Ok(Proposal::Psk(PreSharedKeyProposal {
             psk: PreSharedKeyID {
                 key_id,
@@ -467,8 +547,29 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
         let (signer, signing_identity) =
-            self.signing_data.as_ref().ok_or(MlsError::SignerNotFound)?;
+            self.signing_data.clone().ok_or(MlsError::SignerNotFound)?;
+
+        let sender = self.external_sender(&signing_identity, None)?;
+
+        self.propose_as(proposal, authenticated_data, &signer, sender)
+            .await
+    }
+
+    /// Create a builder for one or more external proposals.
+    ///
+    /// See [`ExternalProposalBuilder`](crate::external_client::ExternalProposalBuilder)
+    /// for details.
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn proposal_builder(&mut self) -> ExternalProposalBuilder<'_, C> {
+        ExternalProposalBuilder::new(self)
+    }
 
+    #[cfg(feature = "by_ref_proposal")]
+    pub(super) fn external_sender(
+        &self,
+        signing_identity: &SigningIdentity,
+        sender_index: Option<u32>,
+    ) -> Result<Sender, MlsError> {
         let external_senders_ext = self
             .state
             .context
@@ -476,14 +577,32 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
             .get_as::<ExternalSendersExt>()?
             .ok_or(MlsError::ExternalProposalsDisabled)?;
 
-        let sender_index = external_senders_ext
-            .allowed_senders
-            .iter()
-            .position(|allowed_signer| signing_identity == allowed_signer)
-            .ok_or(MlsError::InvalidExternalSigningIdentity)?;
+        let allowed_senders = &external_senders_ext.allowed_senders;
+
+        let sender_index = match sender_index {
+            Some(index) => allowed_senders
+                .get(index as usize)
+                .filter(|allowed_signer| *allowed_signer == signing_identity)
+                .map(|_| index),
+            None => allowed_senders
+                .iter()
+                .position(|allowed_signer| signing_identity == allowed_signer)
+                .map(|index| index as u32),
+        }
+        .ok_or(MlsError::InvalidExternalSigningIdentity)?;
 
-        let sender = Sender::External(sender_index as u32);
+        Ok(Sender::External(sender_index))
+    }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(super) async fn propose_as(
+        &mut self,
+        proposal: Proposal,
+        authenticated_data: Vec<u8>,
+        signer: &SignatureSecretKey,
+        sender: Sender,
+    ) -> Result<MlsMessage, MlsError> {
         let auth_content = AuthenticatedContent::new_signed(
             &self.cipher_suite_provider,
             &self.state.context,
@@ -599,6 +718,78 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
     }
 }
 
+/// Result of [`ExternalGroup::reanchor`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExternalReanchor {
+    /// Epoch of the group before re-anchoring.
+    pub previous_epoch: u64,
+    /// Epoch of the group after re-anchoring.
+    pub epoch: u64,
+}
+
+impl ExternalReanchor {
+    /// Number of epochs whose commits were never observed.
+    pub fn skipped_epochs(&self) -> u64 {
+        self.epoch.saturating_sub(self.previous_epoch + 1)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn state_from_group_info<C: ExternalClientConfig>(
+    config: &C,
+    group_info: MlsMessage,
+    tree_data: Option<ExportedTree<'_>>,
+    maybe_time: Option<MlsTime>,
+) -> Result<
+    (
+        GroupState,
+        <C::CryptoProvider as CryptoProvider>::CipherSuiteProvider,
+    ),
+    MlsError,
+> {
+    let protocol_version = group_info.version;
+
+    if !config.version_supported(protocol_version) {
+        return Err(MlsError::UnsupportedProtocolVersion(protocol_version));
+    }
+
+    let group_info = group_info
+        .into_group_info()
+        .ok_or(MlsError::UnexpectedMessageType)?;
+
+    let cipher_suite_provider = cipher_suite_provider(
+        config.crypto_provider(),
+        group_info.group_context.cipher_suite,
+    )?;
+
+    let public_tree = validate_tree_and_info_joiner(
+        protocol_version,
+        &group_info,
+        tree_data,
+        &config.identity_provider(),
+        &cipher_suite_provider,
+        maybe_time,
+    )
+    .await?;
+
+    let interim_transcript_hash = InterimTranscriptHash::create(
+        &cipher_suite_provider,
+        &group_info.group_context.confirmed_transcript_hash,
+        &group_info.confirmation_tag,
+    )
+    .await?;
+
+    let state = GroupState::new(
+        group_info.group_context,
+        public_tree,
+        interim_transcript_hash,
+        group_info.confirmation_tag,
+    );
+
+    Ok((state, cipher_suite_provider))
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
 #[cfg_attr(
@@ -624,14 +815,12 @@ where
         &self,
         message: PublicMessage,
     ) -> Result<EventOrContent<Self::OutputType>, MlsError> {
-        let auth_content = crate::group::message_verifier::verify_plaintext_authentication(
+        let auth_content = verify_plaintext_authentication(
             &self.cipher_suite_provider,
             message,
             None,
             &self.state.context,
-            crate::group::message_verifier::SignaturePublicKeysContainer::RatchetTree(
-                &self.state.public_tree,
-            ),
+            SignaturePublicKeysContainer::RatchetTree(&self.state.public_tree),
         )
         .await?;
 
@@ -648,6 +837,16 @@ where
         )))
     }
 
+    #[cfg(feature = "targeted_messages")]
+    async fn process_targeted_message(
+        &mut self,
+        _message: &TargetedMessage,
+    ) -> Result<Self::OutputType, MlsError> {
+        Ok(ExternalReceivedMessage::Ciphertext(
+            ContentType::Application,
+        ))
+    }
+
     async fn update_key_schedule(
         &mut self,
         _secrets: Option<(TreeKemPrivate, PathSecret)>,
@@ -867,7 +1066,7 @@ mod tests {
             proposal_ref::ProposalRef,
             snapshot::RawGroupState,
             test_utils::{test_group, TestGroup},
-            CommitMessageDescription, ExportedTree, ProposalMessageDescription,
+            CommitMessageDescription, ExportedTree, ProposalMessageDescription, Sender,
         },
         identity::{test_utils::get_test_signing_identity, SigningIdentity},
         key_package::test_utils::{test_key_package, test_key_package_message},
@@ -942,6 +1141,39 @@ mod tests {
         assert_eq!(alice.state, server.state);
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_group_can_reanchor_after_missing_commits() {
+        let mut alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let mut server = make_external_group(&alice).await;
+
+        let stale_info = alice.group_info_message(true).await.unwrap();
+
+        for _ in 0..2 {
+            alice.commit(Vec::new()).await.unwrap();
+            alice.apply_pending_commit().await.unwrap();
+        }
+
+        let group_info = alice.group_info_message(true).await.unwrap();
+        let report = server.reanchor(group_info, None, None).await.unwrap();
+
+        assert_eq!(report.skipped_epochs(), 1);
+        assert_eq!(report.epoch, alice.context().epoch);
+        assert_eq!(server.tree_hash(), alice.context().tree_hash.as_slice());
+
+        let res = server.reanchor(stale_info, None, None).await;
+        assert_matches!(res, Err(MlsError::StaleGroupInfo(_)));
+
+        let commit_output = alice.commit(Vec::new()).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        server
+            .process_incoming_message(commit_output.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(server.group_context(), alice.context());
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn external_group_can_process_proposals_by_reference() {
         let mut alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
@@ -1061,6 +1293,50 @@ mod tests {
         assert_matches!(res, Err(MlsError::InvalidSignature));
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_group_verifies_signature_without_processing() {
+        let mut alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let server = make_external_group(&alice).await;
+
+        let commit_output = alice.commit(Vec::new()).await.unwrap();
+
+        let sender = server
+            .verify_signature_only(&commit_output.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(sender, Sender::Member(0));
+        assert_eq!(server.state.context.epoch, alice.context().epoch);
+
+        let mut tampered = commit_output.commit_message;
+
+        match tampered.payload {
+            MlsMessagePayload::Plain(ref mut plain) => plain.auth.signature = Vec::new().into(),
+            _ => panic!("Unexpected non-plaintext data"),
+        };
+
+        let res = server.verify_signature_only(&tampered).await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_group_signature_only_rejects_other_epoch() {
+        let mut alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let server = make_external_group(&alice).await;
+
+        alice.commit(Vec::new()).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        let commit_output = alice.commit(Vec::new()).await.unwrap();
+
+        let res = server
+            .verify_signature_only(&commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn external_group_rejects_unencrypted_application_message() {
         let mut alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
@@ -1266,6 +1542,98 @@ mod tests {
         assert_matches!(res, Err(MlsError::InvalidExternalSigningIdentity));
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_group_can_build_proposals() {
+        let (server_identity, server_key, mut alice) = setup_extern_proposal_test(true).await;
+
+        let mut server = make_external_group(&alice).await;
+
+        let charlie_key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "charlie").await;
+
+        let epoch = server.group_context().epoch;
+
+        let proposals = server
+            .proposal_builder()
+            .signer(server_key, server_identity)
+            .target_epoch(epoch)
+            .add_member(charlie_key_package)
+            .unwrap()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(proposals.len(), 2);
+
+        for proposal in proposals.iter() {
+            let sender = proposal.clone().into_plaintext().unwrap().content.sender;
+            assert_eq!(sender, Sender::External(0));
+        }
+
+        let (first, second) = (proposals[0].clone(), proposals[1].clone());
+
+        alice.process_message(first).await.unwrap();
+        test_external_proposal(&mut server, &mut alice, second).await;
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_proposal_builder_rejects_wrong_epoch() {
+        let (server_identity, server_key, alice) = setup_extern_proposal_test(true).await;
+        let mut server = make_external_group(&alice).await;
+
+        server.signing_data = Some((server_key, server_identity));
+
+        let epoch = server.group_context().epoch;
+
+        let res = server
+            .proposal_builder()
+            .target_epoch(epoch + 1)
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_proposal_builder_rejects_mismatched_sender_index() {
+        let (server_identity, server_key, alice) = setup_extern_proposal_test(true).await;
+        let mut server = make_external_group(&alice).await;
+
+        let res = server
+            .proposal_builder()
+            .signer(server_key, server_identity)
+            .sender_index(1)
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidExternalSigningIdentity));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_proposal_builder_requires_signer() {
+        let (_, _, alice) = setup_extern_proposal_test(true).await;
+        let mut server = make_external_group(&alice).await;
+
+        let res = server
+            .proposal_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::SignerNotFound));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn external_group_errors_on_old_epoch() {
         let mut alice = test_group_with_one_commit(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
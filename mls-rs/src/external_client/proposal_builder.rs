@@ -0,0 +1,239 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use mls_rs_core::{
+    crypto::SignatureSecretKey, extension::ExtensionList, identity::SigningIdentity,
+};
+
+use crate::{
+    client::MlsError,
+    external_client::ExternalClientConfig,
+    group::proposal::{AddProposal, Proposal, RemoveProposal},
+    tree_kem::node::LeafIndex,
+    MlsMessage,
+};
+
+#[cfg(feature = "custom_proposal")]
+use crate::group::proposal::CustomProposal;
+
+#[cfg(feature = "psk")]
+use mls_rs_core::psk::ExternalPskId;
+
+#[cfg(feature = "psk")]
+use crate::psk::{JustPreSharedKeyID, PskGroupId, ResumptionPSKUsage, ResumptionPsk};
+
+use super::ExternalGroup;
+
+/// A builder that aids with the construction of proposals sent by an
+/// external sender.
+///
+/// Each proposal added to the builder results in a separate, signed
+/// [`PublicMessage`](crate::group::framing::PublicMessage) framed for the
+/// current epoch of the observed group. By default the signing data the
+/// [`ExternalClient`](crate::external_client::ExternalClient) was configured
+/// with is used and the sender index is looked up in the
+/// [`ExternalSendersExt`](crate::extension::built_in::ExternalSendersExt) of
+/// the group context.
+///
+/// Created with
+/// [`ExternalGroup::proposal_builder`](crate::external_client::ExternalGroup::proposal_builder).
+pub struct ExternalProposalBuilder<'a, C>
+where
+    C: ExternalClientConfig + Clone,
+{
+    group: &'a mut ExternalGroup<C>,
+    proposals: Vec<Proposal>,
+    authenticated_data: Vec<u8>,
+    signing_data: Option<(SignatureSecretKey, SigningIdentity)>,
+    sender_index: Option<u32>,
+    target_epoch: Option<u64>,
+}
+
+impl<'a, C> ExternalProposalBuilder<'a, C>
+where
+    C: ExternalClientConfig + Clone,
+{
+    pub(crate) fn new(group: &'a mut ExternalGroup<C>) -> Self {
+        Self {
+            group,
+            proposals: Vec::new(),
+            authenticated_data: Vec::new(),
+            signing_data: None,
+            sender_index: None,
+            target_epoch: None,
+        }
+    }
+
+    /// Insert an [`AddProposal`](crate::group::proposal::AddProposal) into
+    /// the set of proposals that is being built.
+    pub fn add_member(mut self, key_package: MlsMessage) -> Result<Self, MlsError> {
+        let key_package = key_package
+            .into_key_package()
+            .ok_or(MlsError::UnexpectedMessageType)?;
+
+        self.proposals
+            .push(Proposal::Add(Box::new(AddProposal { key_package })));
+
+        Ok(self)
+    }
+
+    /// Insert a [`RemoveProposal`](crate::group::proposal::RemoveProposal) into
+    /// the set of proposals that is being built.
+    pub fn remove_member(mut self, index: u32) -> Result<Self, MlsError> {
+        let to_remove = LeafIndex::try_from(index)?;
+
+        // Verify that this leaf is actually in the tree
+        self.group.state.public_tree.get_leaf_node(to_remove)?;
+
+        self.proposals
+            .push(Proposal::Remove(RemoveProposal { to_remove }));
+
+        Ok(self)
+    }
+
+    /// Insert a
+    /// [`GroupContextExtensions`](crate::group::proposal::Proposal::GroupContextExtensions)
+    /// proposal into the set of proposals that is being built.
+    pub fn set_group_context_ext(mut self, extensions: ExtensionList) -> Self {
+        self.proposals
+            .push(Proposal::GroupContextExtensions(extensions));
+
+        self
+    }
+
+    /// Insert a
+    /// [`PreSharedKeyProposal`](crate::group::proposal::PreSharedKeyProposal) with
+    /// an external PSK into the set of proposals that is being built.
+    #[cfg(feature = "psk")]
+    pub fn add_external_psk(mut self, psk: ExternalPskId) -> Result<Self, MlsError> {
+        let proposal = self.group.psk_proposal(JustPreSharedKeyID::External(psk))?;
+        self.proposals.push(proposal);
+        Ok(self)
+    }
+
+    /// Insert a
+    /// [`PreSharedKeyProposal`](crate::group::proposal::PreSharedKeyProposal) with
+    /// a resumption PSK into the set of proposals that is being built.
+    #[cfg(feature = "psk")]
+    pub fn add_resumption_psk(mut self, psk_epoch: u64) -> Result<Self, MlsError> {
+        let key_id = ResumptionPsk {
+            psk_epoch,
+            usage: ResumptionPSKUsage::Application,
+            psk_group_id: PskGroupId(self.group.group_context().group_id().to_vec()),
+        };
+
+        let proposal = self
+            .group
+            .psk_proposal(JustPreSharedKeyID::Resumption(key_id))?;
+
+        self.proposals.push(proposal);
+        Ok(self)
+    }
+
+    /// Insert a [`CustomProposal`](crate::group::proposal::CustomProposal) into
+    /// the set of proposals that is being built.
+    #[cfg(feature = "custom_proposal")]
+    pub fn custom_proposal(mut self, proposal: CustomProposal) -> Self {
+        self.proposals.push(Proposal::Custom(proposal));
+        self
+    }
+
+    /// Insert a proposal that was previously constructed such as when a
+    /// proposal is returned from
+    /// [`NewEpoch::unused_proposals`](crate::group::NewEpoch::unused_proposals).
+    pub fn raw_proposal(mut self, proposal: Proposal) -> Self {
+        self.proposals.push(proposal);
+        self
+    }
+
+    /// Add additional authenticated data to each resulting proposal message.
+    ///
+    /// # Warning
+    ///
+    /// The data provided here is always sent unencrypted.
+    pub fn authenticated_data(self, authenticated_data: Vec<u8>) -> Self {
+        Self {
+            authenticated_data,
+            ..self
+        }
+    }
+
+    /// Sign the resulting proposals with `signer` and `signing_identity`
+    /// instead of the signing data the external client was configured with.
+    pub fn signer(self, signer: SignatureSecretKey, signing_identity: SigningIdentity) -> Self {
+        Self {
+            signing_data: Some((signer, signing_identity)),
+            ..self
+        }
+    }
+
+    /// Use an explicit index into the
+    /// [`ExternalSendersExt`](crate::extension::built_in::ExternalSendersExt)
+    /// of the group as the sender of the resulting proposals.
+    ///
+    /// This is useful when the same signing identity is listed more than once.
+    /// The entry at `index` must match the signing identity in use.
+    pub fn sender_index(self, index: u32) -> Self {
+        Self {
+            sender_index: Some(index),
+            ..self
+        }
+    }
+
+    /// Require that the observed group is at `epoch` when the proposals are
+    /// built. [`build`](Self::build) fails with
+    /// [`MlsError::InvalidEpoch`] otherwise.
+    pub fn target_epoch(self, epoch: u64) -> Self {
+        Self {
+            target_epoch: Some(epoch),
+            ..self
+        }
+    }
+
+    /// Sign and frame the proposals, returning one message per proposal in
+    /// the order they were inserted.
+    ///
+    /// The resulting proposals are also cached by the observed group so that
+    /// a later commit referencing them can be processed.
+    ///
+    /// # Warning
+    ///
+    /// In order for the proposals generated by this function to be successfully
+    /// committed, the group needs to have the signing identity in use as an entry
+    /// within an [ExternalSendersExt](crate::extension::built_in::ExternalSendersExt)
+    /// as part of its group context extensions.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn build(self) -> Result<Vec<MlsMessage>, MlsError> {
+        if let Some(epoch) = self.target_epoch {
+            if epoch != self.group.group_context().epoch {
+                return Err(MlsError::InvalidEpoch);
+            }
+        }
+
+        let (signer, signing_identity) = self
+            .signing_data
+            .or_else(|| self.group.signing_data.clone())
+            .ok_or(MlsError::SignerNotFound)?;
+
+        let sender = self
+            .group
+            .external_sender(&signing_identity, self.sender_index)?;
+
+        let mut messages = Vec::with_capacity(self.proposals.len());
+
+        for proposal in self.proposals {
+            let message = self
+                .group
+                .propose_as(proposal, self.authenticated_data.clone(), &signer, sender)
+                .await?;
+
+            messages.push(message);
+        }
+
+        Ok(messages)
+    }
+}
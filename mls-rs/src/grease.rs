@@ -67,6 +67,12 @@ impl NewMemberInfo {
     }
 }
 
+/// Returns true if `value` is one of the GREASE values reserved by RFC 9420,
+/// which may be sent by any implementation regardless of the `grease` feature.
+pub(crate) fn is_grease_value(value: u16) -> bool {
+    value & 0x0F0F == 0x0A0A && value >> 12 == (value >> 4) & 0x0F && value != 0xFAFA
+}
+
 #[cfg(feature = "grease")]
 mod grease_functions {
     use core::ops::Deref;
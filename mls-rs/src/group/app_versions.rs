@@ -0,0 +1,239 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::extension::{ExtensionType, MlsCodecExtension};
+
+use crate::{client_config::ClientConfig, tree_kem::TreeKemPublic};
+
+use super::Group;
+
+/// Range of versions of the application protocol carried in application
+/// messages, bounds included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, MlsSize, MlsEncode, MlsDecode)]
+pub struct AppVersionRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl AppVersionRange {
+    /// Create a range from `min` to `max`, both included. Returns `None` if
+    /// `min` is greater than `max`.
+    pub fn new(min: u32, max: u32) -> Option<Self> {
+        (min <= max).then_some(Self { min, max })
+    }
+
+    pub fn contains(&self, version: u32) -> bool {
+        self.min <= version && version <= self.max
+    }
+
+    /// Versions in both `self` and `other`, if any.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Self::new(self.min.max(other.min), self.max.min(other.max))
+    }
+}
+
+/// Leaf node extension announcing the versions of the application protocol
+/// supported by a member.
+///
+/// The extension is set in the leaf node extensions of key packages and
+/// updates. Its type must be listed in the capabilities of the member, see
+/// [`ClientBuilder::extension_type`](crate::client_builder::ClientBuilder::extension_type).
+/// The versions used by the whole group are available with
+/// [`Group::app_versions`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct AppVersionsExt {
+    pub supported: AppVersionRange,
+}
+
+impl AppVersionsExt {
+    /// Extension type of the announcement, taken from the private use range.
+    pub const EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF0A3);
+
+    pub fn new(supported: AppVersionRange) -> Self {
+        Self { supported }
+    }
+}
+
+impl MlsCodecExtension for AppVersionsExt {
+    fn extension_type() -> ExtensionType {
+        Self::EXTENSION_TYPE
+    }
+}
+
+/// Change of the versions supported by all members, reported in
+/// [`CommitMessageDescription::app_versions_change`](crate::group::CommitMessageDescription::app_versions_change).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct AppVersionsChange {
+    pub previous: Option<AppVersionRange>,
+    pub current: Option<AppVersionRange>,
+}
+
+impl AppVersionsChange {
+    /// Highest version usable by the group after the change, e.g. to enable
+    /// the features of a new version once every member supports it.
+    pub fn current_max(&self) -> Option<u32> {
+        self.current.map(|r| r.max)
+    }
+}
+
+/// Versions supported by every member of `tree`. Members without a valid
+/// [`AppVersionsExt`] support no version.
+pub(crate) fn negotiate_app_versions(tree: &TreeKemPublic) -> Option<AppVersionRange> {
+    let mut leaves = tree.non_empty_leaves();
+    let mut negotiated = announced(leaves.next()?.1)?;
+
+    for (_, leaf) in leaves {
+        negotiated = negotiated.intersection(&announced(leaf)?)?;
+    }
+
+    Some(negotiated)
+}
+
+fn announced(leaf: &crate::tree_kem::leaf_node::LeafNode) -> Option<AppVersionRange> {
+    let ext = leaf.extensions.get_as::<AppVersionsExt>().ok()??;
+    AppVersionRange::new(ext.supported.min, ext.supported.max)
+}
+
+/// Change of the negotiated versions between the trees of two epochs, if
+/// any.
+pub(crate) fn app_versions_change(
+    previous: &TreeKemPublic,
+    current: &TreeKemPublic,
+) -> Option<AppVersionsChange> {
+    let change = AppVersionsChange {
+        previous: negotiate_app_versions(previous),
+        current: negotiate_app_versions(current),
+    };
+
+    (change.previous != change.current).then_some(change)
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Versions of the application protocol supported by every member, as
+    /// announced with [`AppVersionsExt`].
+    ///
+    /// The value is derived from the leaf nodes of the ratchet tree, which
+    /// are covered by the tree hash of the group context, so every member
+    /// computes the same value for an epoch. It is `None` if a member did not
+    /// announce its versions or if the members have no version in common.
+    pub fn app_versions(&self) -> Option<AppVersionRange> {
+        negotiate_app_versions(&self.state.public_tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mls_rs_core::extension::ExtensionList;
+
+    use crate::{
+        client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+        client_builder::MlsConfig,
+        group::{CommitMessageDescription, ReceivedMessage},
+        Client,
+    };
+
+    use super::{AppVersionRange, AppVersionsChange, AppVersionsExt};
+
+    fn announcement(min: u32, max: u32) -> ExtensionList {
+        let mut extensions = ExtensionList::new();
+
+        extensions
+            .set_from(AppVersionsExt::new(AppVersionRange::new(min, max).unwrap()))
+            .unwrap();
+
+        extensions
+    }
+
+    #[test]
+    fn ranges_intersect() {
+        let a = AppVersionRange::new(1, 5).unwrap();
+
+        assert_eq!(
+            a.intersection(&AppVersionRange::new(3, 9).unwrap()),
+            AppVersionRange::new(3, 5)
+        );
+
+        assert_eq!(a.intersection(&AppVersionRange::new(6, 9).unwrap()), None);
+        assert!(AppVersionRange::new(2, 1).is_none());
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_client(name: &str) -> Client<impl MlsConfig> {
+        TestClientBuilder::new_for_test()
+            .with_random_signing_identity(name, TEST_CIPHER_SUITE)
+            .await
+            .extension_type(AppVersionsExt::EXTENSION_TYPE)
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commits_report_negotiated_versions() {
+        let alice = test_client("alice").await;
+        let bob = test_client("bob").await;
+
+        let mut alice = alice
+            .create_group(Default::default(), announcement(1, 3), None)
+            .await
+            .unwrap();
+
+        assert_eq!(alice.app_versions(), AppVersionRange::new(1, 3));
+
+        let key_package = bob
+            .generate_key_package_message(Default::default(), announcement(2, 5), None)
+            .await
+            .unwrap();
+
+        let commit = alice
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let CommitMessageDescription {
+            app_versions_change,
+            ..
+        } = alice.apply_pending_commit().await.unwrap();
+
+        let expected = AppVersionsChange {
+            previous: AppVersionRange::new(1, 3),
+            current: AppVersionRange::new(2, 3),
+        };
+
+        assert_eq!(app_versions_change, Some(expected));
+
+        let (mut bob, _) = bob
+            .join_group(commit.ratchet_tree, &commit.welcome_messages[0], None)
+            .await
+            .unwrap();
+
+        assert_eq!(bob.app_versions(), alice.app_versions());
+
+        let commit = alice
+            .commit_builder()
+            .set_leaf_node_extensions(announcement(2, 5))
+            .build()
+            .await
+            .unwrap();
+
+        let ReceivedMessage::Commit(description) = bob
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap()
+        else {
+            panic!("expected a commit");
+        };
+
+        let change = description.app_versions_change.unwrap();
+
+        assert_eq!(change.current_max(), Some(5));
+        assert_eq!(bob.app_versions(), AppVersionRange::new(2, 5));
+        assert_eq!(change.previous, AppVersionRange::new(2, 3));
+    }
+}
@@ -0,0 +1,399 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider},
+    error::IntoAnyError,
+    identity::SigningIdentity,
+};
+
+use crate::{client::MlsError, client_config::ClientConfig, signer::Signable};
+
+use super::{EpochDigest, EpochHistory, Group};
+
+/// A member listed in an [`ArchivedEpoch`].
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct ArchivedMember {
+    pub leaf_index: u32,
+    pub signing_identity: SigningIdentity,
+}
+
+/// Membership of a group in one epoch, without any message content or
+/// secret.
+///
+/// Epochs are recorded by the application with [`Group::archived_epoch`]
+/// while they are current, the same way as an [`EpochDigest`] for an
+/// [`EpochHistory`], and later exported with [`Group::export_archive`].
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct ArchivedEpoch {
+    /// Digest of the epoch, without its epoch authenticator.
+    pub digest: EpochDigest,
+    /// Members of the group in the epoch, ordered by leaf index.
+    pub members: Vec<ArchivedMember>,
+}
+
+/// An [`ArchivedEpoch`] with the hash chaining it to the previous epochs of
+/// a [`GroupArchive`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct ArchiveRecord {
+    pub epoch: ArchivedEpoch,
+    /// Hash of the chain hash of the previous record and of `epoch`.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub chain_hash: Vec<u8>,
+}
+
+impl Debug for ArchiveRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchiveRecord")
+            .field("epoch", &self.epoch)
+            .field(
+                "chain_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.chain_hash),
+            )
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct ChainHashInput<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    previous: &'a [u8],
+    epoch: &'a ArchivedEpoch,
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct GroupArchiveTBS<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    head: &'a [u8],
+    exporter: &'a SigningIdentity,
+}
+
+/// Frozen, verifiable archive of the membership history of a group, for
+/// example for legal hold.
+///
+/// Epochs are hash-chained and the last chain hash is signed by the member
+/// that exported the archive, which must be a member of the last archived
+/// epoch. An archive is checked with [`GroupArchive::verify`], which does not
+/// require a client or the group.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct GroupArchive {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    cipher_suite: CipherSuite,
+    records: Vec<ArchiveRecord>,
+    exporter: SigningIdentity,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+impl Debug for GroupArchive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupArchive")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("cipher_suite", &self.cipher_suite)
+            .field("records", &self.records)
+            .field("exporter", &self.exporter)
+            .field(
+                "signature",
+                &mls_rs_core::debug::pretty_bytes(&self.signature),
+            )
+            .finish()
+    }
+}
+
+impl GroupArchive {
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    /// Archived epochs, sorted by epoch.
+    pub fn records(&self) -> &[ArchiveRecord] {
+        &self.records
+    }
+
+    /// Signing identity of the member that exported the archive.
+    pub fn exporter(&self) -> &SigningIdentity {
+        &self.exporter
+    }
+
+    /// History of the archived epochs, to be compared with the
+    /// [`EpochHistory`] of a member.
+    pub fn history(&self) -> EpochHistory {
+        EpochHistory::new(
+            self.group_id.clone(),
+            self.records
+                .iter()
+                .map(|record| record.epoch.digest.clone())
+                .collect(),
+        )
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Check the hash chain of the archive and its signature by
+    /// [`GroupArchive::exporter`].
+    ///
+    /// Fails with [`MlsError::InvalidArchive`] if epochs are not increasing,
+    /// a chain hash doesn't match or the exporter isn't a member of the last
+    /// epoch, and with [`MlsError::InvalidSignature`] if the signature is
+    /// invalid.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+    ) -> Result<(), MlsError> {
+        if cipher_suite_provider.cipher_suite() != self.cipher_suite {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        let mut previous: &[u8] = &[];
+        let mut previous_epoch = None;
+
+        for record in self.records.iter() {
+            let epoch = record.epoch.digest.epoch;
+
+            if matches!(previous_epoch, Some(previous_epoch) if previous_epoch >= epoch) {
+                return Err(MlsError::InvalidArchive);
+            }
+
+            let chain_hash = chain_hash(cipher_suite_provider, previous, &record.epoch).await?;
+
+            if chain_hash != record.chain_hash {
+                return Err(MlsError::InvalidArchive);
+            }
+
+            previous = &record.chain_hash;
+            previous_epoch = Some(epoch);
+        }
+
+        let exporter_is_member = self
+            .records
+            .last()
+            .into_iter()
+            .flat_map(|record| record.epoch.members.iter())
+            .any(|member| member.signing_identity == self.exporter);
+
+        if !exporter_is_member {
+            return Err(MlsError::InvalidArchive);
+        }
+
+        Signable::verify(
+            self,
+            cipher_suite_provider,
+            &self.exporter.signature_key,
+            &(),
+        )
+        .await
+    }
+
+    fn head(&self) -> &[u8] {
+        self.records
+            .last()
+            .map_or(&[][..], |record| &record.chain_hash[..])
+    }
+}
+
+impl<'a> Signable<'a> for GroupArchive {
+    const SIGN_LABEL: &'static str = "GroupArchiveTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        GroupArchiveTBS {
+            group_id: &self.group_id,
+            cipher_suite: self.cipher_suite,
+            head: self.head(),
+            exporter: &self.exporter,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn chain_hash<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    previous: &[u8],
+    epoch: &ArchivedEpoch,
+) -> Result<Vec<u8>, MlsError> {
+    let input = ChainHashInput { previous, epoch }.mls_encode_to_vec()?;
+
+    cipher_suite_provider
+        .hash(&input)
+        .await
+        .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Membership of the current epoch, to be recorded by applications that
+    /// want to export a [`GroupArchive`] later.
+    pub fn archived_epoch(&self) -> ArchivedEpoch {
+        let mut digest = self.epoch_digest();
+        digest.epoch_authenticator = None;
+
+        let members = self
+            .roster()
+            .members_iter()
+            .map(|member| ArchivedMember {
+                leaf_index: member.index,
+                signing_identity: member.signing_identity,
+            })
+            .collect();
+
+        ArchivedEpoch { digest, members }
+    }
+
+    /// Export a [`GroupArchive`] of `epochs`, signed by this member.
+    ///
+    /// Epochs are sorted and the current epoch is added if it is missing.
+    /// Epochs after the current epoch are rejected with
+    /// [`MlsError::InvalidArchive`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn export_archive(
+        &self,
+        mut epochs: Vec<ArchivedEpoch>,
+    ) -> Result<GroupArchive, MlsError> {
+        let current_epoch = self.current_epoch();
+
+        epochs.sort_by_key(|epoch| epoch.digest.epoch);
+        epochs.dedup_by_key(|epoch| epoch.digest.epoch);
+
+        match epochs.last() {
+            Some(last) if last.digest.epoch > current_epoch => {
+                return Err(MlsError::InvalidArchive)
+            }
+            Some(last) if last.digest.epoch == current_epoch => {}
+            _ => epochs.push(self.archived_epoch()),
+        }
+
+        let mut records: Vec<ArchiveRecord> = Vec::with_capacity(epochs.len());
+
+        for epoch in epochs {
+            let previous = records.last().map_or(&[][..], |r| &r.chain_hash[..]);
+            let chain_hash = chain_hash(&self.cipher_suite_provider, previous, &epoch).await?;
+
+            records.push(ArchiveRecord { epoch, chain_hash });
+        }
+
+        let mut archive = GroupArchive {
+            group_id: self.group_id().to_vec(),
+            cipher_suite: self.cipher_suite(),
+            records,
+            exporter: self.current_member_signing_identity()?.clone(),
+            signature: Vec::new(),
+        };
+
+        archive
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        Ok(archive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_group,
+    };
+
+    use super::GroupArchive;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn archive_of_membership_history_verifies() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let mut epochs = vec![alice.archived_epoch()];
+
+        let (mut bob, _) = alice.join("bob").await;
+        epochs.push(alice.archived_epoch());
+
+        let commit = alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        bob.process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let archive = alice.export_archive(epochs).await.unwrap();
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        assert_eq!(archive.records().len(), 3);
+        assert_eq!(archive.records()[0].epoch.members.len(), 1);
+        assert_eq!(archive.records()[2].epoch.members.len(), 2);
+
+        let archive = GroupArchive::from_bytes(&archive.to_bytes().unwrap()).unwrap();
+        archive.verify(&cs).await.unwrap();
+
+        let history = bob.epoch_history(0).await.unwrap();
+        assert!(archive.history().compare(&history).unwrap().is_consistent());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tampered_archive_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let first = alice.archived_epoch();
+
+        alice.join("bob").await;
+
+        let archive = alice.export_archive(vec![first]).await.unwrap();
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut removed_member = archive.clone();
+        removed_member.records[1].epoch.members.pop();
+
+        let res = removed_member.verify(&cs).await;
+
+        assert_matches!(res, Err(MlsError::InvalidArchive));
+
+        let mut truncated = archive.clone();
+        truncated.records.pop();
+
+        let res = truncated.verify(&cs).await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+}
@@ -0,0 +1,334 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::client::MlsError;
+
+use super::framing::{MlsMessage, WireFormat};
+
+const ARMOR_VERSION: u8 = 1;
+const VERSION_HEADER: &str = "Armor-Version: ";
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const CRC24_INIT: u32 = 0xb704ce;
+const CRC24_POLY: u32 = 0x1864cfb;
+
+/// Layout of the output of [`MlsMessage::armor`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ArmorOptions {
+    /// Maximum length of the lines of base64 data, or `None` to keep the
+    /// data on a single line, e.g. to render a QR code.
+    pub line_width: Option<usize>,
+}
+
+impl Default for ArmorOptions {
+    fn default() -> Self {
+        Self {
+            line_width: Some(64),
+        }
+    }
+}
+
+impl ArmorOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_line_width(self, line_width: Option<usize>) -> Self {
+        Self { line_width }
+    }
+}
+
+impl MlsMessage {
+    /// Encode the message as text for out of band transport, such as a
+    /// copy-pasted invite or a QR code.
+    ///
+    /// The output has the form
+    ///
+    /// ```text
+    /// -----BEGIN MLS KEY PACKAGE-----
+    /// Armor-Version: 1
+    /// <base64 encoded message>
+    /// =<base64 encoded CRC-24 of the message>
+    /// -----END MLS KEY PACKAGE-----
+    /// ```
+    ///
+    /// where the label depends on the wire format of the message. The
+    /// checksum detects transcription errors, it does not authenticate the
+    /// message.
+    pub fn armor(&self, options: &ArmorOptions) -> Result<String, MlsError> {
+        let bytes = self.to_bytes()?;
+        let label = armor_label(self.wire_format());
+        let data = base64_encode(&bytes);
+
+        let mut out = String::new();
+
+        out.push_str("-----BEGIN MLS ");
+        out.push_str(label);
+        out.push_str("-----\n");
+
+        out.push_str(VERSION_HEADER);
+        out.push_str(&ARMOR_VERSION.to_string());
+        out.push('\n');
+
+        match options.line_width.filter(|w| *w > 0) {
+            Some(width) => data.as_bytes().chunks(width).for_each(|line| {
+                // Base64 output is ASCII
+                out.extend(line.iter().map(|c| char::from(*c)));
+                out.push('\n');
+            }),
+            None => {
+                out.push_str(&data);
+                out.push('\n');
+            }
+        }
+
+        out.push('=');
+        out.push_str(&base64_encode(&crc24(&bytes).to_be_bytes()[1..]));
+        out.push('\n');
+
+        out.push_str("-----END MLS ");
+        out.push_str(label);
+        out.push_str("-----\n");
+
+        Ok(out)
+    }
+
+    /// Decode a message produced by [`MlsMessage::armor`].
+    ///
+    /// Whitespace around lines and line endings is ignored, so the input can
+    /// be re-wrapped in transit.
+    pub fn dearmor(text: &str) -> Result<Self, MlsError> {
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+
+        let label = lines
+            .next()
+            .and_then(|l| l.strip_prefix("-----BEGIN MLS "))
+            .and_then(|l| l.strip_suffix("-----"))
+            .ok_or(MlsError::InvalidArmor)?;
+
+        let version = lines
+            .next()
+            .and_then(|l| l.strip_prefix(VERSION_HEADER))
+            .and_then(|v| v.parse::<u8>().ok())
+            .ok_or(MlsError::InvalidArmor)?;
+
+        if version != ARMOR_VERSION {
+            return Err(MlsError::UnsupportedArmorVersion(version));
+        }
+
+        // The checksum is the last line before the footer. A line of data
+        // can also start with `=` when the padding is wrapped.
+        let mut rest = lines.collect::<Vec<_>>();
+
+        let end_label = rest
+            .pop()
+            .and_then(|l| l.strip_prefix("-----END MLS "))
+            .and_then(|l| l.strip_suffix("-----"));
+
+        if end_label != Some(label) {
+            return Err(MlsError::InvalidArmor);
+        }
+
+        let checksum = rest
+            .pop()
+            .and_then(|l| l.strip_prefix('='))
+            .ok_or(MlsError::InvalidArmor)?;
+
+        let data = rest.concat();
+
+        let bytes = base64_decode(&data).ok_or(MlsError::InvalidArmor)?;
+        let checksum = base64_decode(checksum).ok_or(MlsError::InvalidArmor)?;
+
+        if checksum[..] != crc24(&bytes).to_be_bytes()[1..] {
+            return Err(MlsError::ArmorChecksumMismatch);
+        }
+
+        let message = Self::from_bytes(&bytes)?;
+
+        if armor_label(message.wire_format()) != label {
+            return Err(MlsError::InvalidArmor);
+        }
+
+        Ok(message)
+    }
+}
+
+fn armor_label(wire_format: WireFormat) -> &'static str {
+    match wire_format {
+        WireFormat::PublicMessage => "PUBLIC MESSAGE",
+        WireFormat::PrivateMessage => "PRIVATE MESSAGE",
+        WireFormat::Welcome => "WELCOME",
+        WireFormat::GroupInfo => "GROUP INFO",
+        WireFormat::KeyPackage => "KEY PACKAGE",
+        #[cfg(feature = "targeted_messages")]
+        WireFormat::TargetedMessage => "TARGETED MESSAGE",
+    }
+}
+
+// CRC-24 as defined by RFC 4880, section 6.1.
+fn crc24(data: &[u8]) -> u32 {
+    data.iter().fold(CRC24_INIT, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u32) << 16), |crc, _| {
+            let crc = crc << 1;
+
+            if crc & 0x1000000 != 0 {
+                crc ^ CRC24_POLY
+            } else {
+                crc
+            }
+        })
+    }) & 0xffffff
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4);
+
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+        (0..4).for_each(|i| {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        });
+    }
+
+    out
+}
+
+fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let data = data.as_bytes();
+
+    if data.len() % 4 != 0 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(data.len() / 4 * 3);
+
+    for (i, chunk) in data.chunks(4).enumerate() {
+        let is_last = i == data.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+
+        if padding > 2 || (padding > 0 && !is_last) {
+            return None;
+        }
+
+        let n = chunk[..4 - padding].iter().try_fold(0u32, |n, c| {
+            let value = BASE64_ALPHABET.iter().position(|a| a == c)?;
+            Some((n << 6) | value as u32)
+        })? << (6 * padding);
+
+        out.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{framing::MlsMessage, test_utils::test_group},
+        key_package::test_utils::test_key_package_message,
+    };
+
+    use super::{base64_decode, base64_encode, crc24, ArmorOptions};
+
+    #[test]
+    fn base64_and_crc24_match_reference_vectors() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+
+        assert_eq!(base64_decode("Zm9vYg==").unwrap(), b"foob");
+        assert!(base64_decode("Zm9=Ymar").is_none());
+
+        assert_eq!(crc24(b"123456789"), 0x21cf02);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_package_round_trips_through_armor() {
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let armored = key_package.armor(&ArmorOptions::default()).unwrap();
+
+        assert!(armored.starts_with("-----BEGIN MLS KEY PACKAGE-----\nArmor-Version: 1\n"));
+        assert!(armored.lines().all(|l| l.len() <= 64));
+
+        // Copy-paste may change line endings and indentation
+        let pasted = armored.replace('\n', "\r\n  ");
+
+        assert_eq!(MlsMessage::dearmor(&pasted).unwrap(), key_package);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn single_line_armor_for_qr_codes() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let group_info = group.group_info_message(true).await.unwrap();
+
+        let options = ArmorOptions::new().with_line_width(None);
+        let armored = group_info.armor(&options).unwrap();
+
+        assert_eq!(armored.lines().count(), 5);
+        assert_eq!(MlsMessage::dearmor(&armored).unwrap(), group_info);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn corrupted_armor_is_detected() {
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let armored = key_package.armor(&ArmorOptions::default()).unwrap();
+
+        let mut lines = armored.lines().map(String::from).collect::<Vec<_>>();
+
+        // Swap two characters of the data, keeping valid base64
+        let mut line = lines[2].clone().into_bytes();
+        line.swap(0, 1);
+
+        if line == lines[2].as_bytes() {
+            line[0] = if line[0] == b'A' { b'B' } else { b'A' };
+        }
+
+        lines[2] = String::from_utf8(line).unwrap();
+
+        assert_matches!(
+            MlsMessage::dearmor(&lines.join("\n")),
+            Err(MlsError::ArmorChecksumMismatch)
+        );
+
+        let relabeled = armored.replace("KEY PACKAGE", "WELCOME");
+
+        assert_matches!(MlsMessage::dearmor(&relabeled), Err(MlsError::InvalidArmor));
+
+        let future = armored.replace("Armor-Version: 1", "Armor-Version: 2");
+
+        assert_matches!(
+            MlsMessage::dearmor(&future),
+            Err(MlsError::UnsupportedArmorVersion(2))
+        );
+    }
+}
@@ -0,0 +1,704 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{
+        CipherSuite, CipherSuiteProvider, CryptoProvider, SignaturePublicKey, SignatureSecretKey,
+    },
+    error::IntoAnyError,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, group::key_schedule::kdf_expand_with_label,
+    signer::Signable,
+};
+
+use super::{component_operation::ComponentID, Group};
+
+/// Component of the exporter reserved for broadcast keys.
+const BROADCAST_COMPONENT_ID: ComponentID = 0x4D42_4300;
+const BROADCAST_SECRET_LABEL: &[u8] = b"broadcast sender secret";
+
+/// Application message sent by a designated broadcaster of a group with a
+/// [`BroadcastSender`].
+///
+/// The content is encrypted with a key derived from the exporter of the
+/// epoch the message was sent in and signed with the MLS signature key of
+/// the broadcaster.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct BroadcastMessage {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    broadcaster: u32,
+    generation: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    ciphertext: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+impl Debug for BroadcastMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BroadcastMessage")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("broadcaster", &self.broadcaster)
+            .field("generation", &self.generation)
+            .field(
+                "ciphertext",
+                &mls_rs_core::debug::pretty_bytes(&self.ciphertext),
+            )
+            .field(
+                "signature",
+                &mls_rs_core::debug::pretty_bytes(&self.signature),
+            )
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct BroadcastMessageTBS<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    broadcaster: u32,
+    generation: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    ciphertext: &'a [u8],
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct BroadcastAAD<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    broadcaster: u32,
+    generation: u32,
+}
+
+impl BroadcastMessage {
+    /// Id of the group the message was sent to.
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    /// Epoch the message was sent in.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Leaf index of the broadcaster.
+    pub fn broadcaster(&self) -> u32 {
+        self.broadcaster
+    }
+
+    /// Position of the message among the messages sent by the broadcaster
+    /// in [`BroadcastMessage::epoch`].
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Serialize the message.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Deserialize a message created by [`BroadcastMessage::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    fn aad(&self) -> Result<Vec<u8>, MlsError> {
+        BroadcastAAD {
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            broadcaster: self.broadcaster,
+            generation: self.generation,
+        }
+        .mls_encode_to_vec()
+        .map_err(Into::into)
+    }
+}
+
+impl<'a> Signable<'a> for BroadcastMessage {
+    const SIGN_LABEL: &'static str = "BroadcastMessageTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        BroadcastMessageTBS {
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            broadcaster: self.broadcaster,
+            generation: self.generation,
+            ciphertext: &self.ciphertext,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+/// Broadcast generations of an epoch already handed out to a
+/// [`BroadcastSender`] of this member.
+///
+/// Stored with the group state, so that no generation is used twice in an
+/// epoch, even by a client restarted from storage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct BroadcastReservation {
+    epoch: u64,
+    next_generation: u32,
+}
+
+impl BroadcastReservation {
+    /// Reserve `count` generations of `epoch`, returning the first one.
+    fn reserve(&mut self, epoch: u64, count: u32) -> Result<u32, MlsError> {
+        if self.epoch != epoch {
+            *self = Self {
+                epoch,
+                next_generation: 0,
+            };
+        }
+
+        let start = self.next_generation;
+
+        self.next_generation = start
+            .checked_add(count)
+            .ok_or(MlsError::InvalidFutureGeneration(start))?;
+
+        Ok(start)
+    }
+}
+
+/// Keys of a designated broadcaster for a single epoch of a group.
+///
+/// Created with [`Group::broadcast_sender`]. A new sender must be created
+/// after each commit, so that removed members can not decrypt messages
+/// sent after their removal.
+pub struct BroadcastSender<CS> {
+    group_id: Vec<u8>,
+    epoch: u64,
+    broadcaster: u32,
+    generation: u32,
+    end_generation: u32,
+    secret: Zeroizing<Vec<u8>>,
+    signer: SignatureSecretKey,
+    cipher_suite_provider: CS,
+}
+
+impl<CS> Debug for BroadcastSender<CS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BroadcastSender")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("broadcaster", &self.broadcaster)
+            .field("generation", &self.generation)
+            .field("end_generation", &self.end_generation)
+            .finish()
+    }
+}
+
+impl<CS: CipherSuiteProvider> BroadcastSender<CS> {
+    /// Epoch of the group the sender is bound to.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Number of messages the sender can still seal.
+    pub fn remaining(&self) -> u32 {
+        self.end_generation - self.generation
+    }
+
+    /// Encrypt and sign `plaintext` for all members of the group in
+    /// [`BroadcastSender::epoch`].
+    ///
+    /// Returns [`MlsError::BroadcastGenerationsExhausted`] once all
+    /// generations reserved for the sender were used.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn seal(&mut self, plaintext: &[u8]) -> Result<BroadcastMessage, MlsError> {
+        if self.generation == self.end_generation {
+            return Err(MlsError::BroadcastGenerationsExhausted);
+        }
+
+        let mut message = BroadcastMessage {
+            group_id: self.group_id.clone(),
+            epoch: self.epoch,
+            broadcaster: self.broadcaster,
+            generation: self.generation,
+            ciphertext: Vec::new(),
+            signature: Vec::new(),
+        };
+
+        let (key, nonce) =
+            generation_key(&self.cipher_suite_provider, &self.secret, self.generation).await?;
+
+        message.ciphertext = self
+            .cipher_suite_provider
+            .aead_seal(&key, plaintext, Some(&message.aad()?), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        message
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        self.generation += 1;
+
+        Ok(message)
+    }
+}
+
+/// Number of generations below the newest one opened from a broadcaster
+/// that can still be opened out of order.
+const REPLAY_WINDOW: u32 = 64;
+
+/// Generations opened from a broadcaster, used to reject replayed messages.
+#[derive(Default)]
+struct ReplayWindow {
+    newest: Option<u32>,
+    // Bit `i` is set if generation `newest - i` was opened.
+    opened: u64,
+}
+
+impl ReplayWindow {
+    fn check(&self, generation: u32) -> Result<(), MlsError> {
+        let Some(newest) = self.newest.filter(|newest| generation <= *newest) else {
+            return Ok(());
+        };
+
+        let offset = newest - generation;
+
+        if offset >= REPLAY_WINDOW || self.opened & (1 << offset) != 0 {
+            return Err(MlsError::KeyMissing(generation));
+        }
+
+        Ok(())
+    }
+
+    fn insert(&mut self, generation: u32) {
+        match self.newest {
+            Some(newest) if generation <= newest => self.opened |= 1 << (newest - generation),
+            Some(newest) => {
+                let shift = generation - newest;
+                self.opened = self.opened.checked_shl(shift).unwrap_or_default() | 1;
+                self.newest = Some(generation);
+            }
+            None => {
+                self.opened = 1;
+                self.newest = Some(generation);
+            }
+        }
+    }
+}
+
+struct BroadcasterKeys {
+    index: u32,
+    secret: Zeroizing<Vec<u8>>,
+    signature_key: SignaturePublicKey,
+    replay_window: ReplayWindow,
+}
+
+/// Keys needed to receive messages of the designated broadcasters of a
+/// group in a single epoch.
+///
+/// Created with [`Group::broadcast_receiver`]. Only keys of the designated
+/// broadcasters are derived, so receiving is cheap even in very large
+/// groups.
+pub struct BroadcastReceiver<CS> {
+    group_id: Vec<u8>,
+    epoch: u64,
+    cipher_suite: CipherSuite,
+    broadcasters: Vec<BroadcasterKeys>,
+    cipher_suite_provider: CS,
+}
+
+impl<CS> Debug for BroadcastReceiver<CS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BroadcastReceiver")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "broadcasters",
+                &self
+                    .broadcasters
+                    .iter()
+                    .map(|b| b.index)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<CS: CipherSuiteProvider> BroadcastReceiver<CS> {
+    /// Epoch of the group the receiver is bound to.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Check the signature of `message` and decrypt it.
+    ///
+    /// Messages from other groups or epochs, and messages from members that
+    /// were not designated as broadcasters, are rejected. Each generation of
+    /// a broadcaster can be opened once, and only while it is less than 64
+    /// generations older than the newest message opened from the same
+    /// broadcaster. Other messages are rejected with
+    /// [`MlsError::KeyMissing`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn open(&mut self, message: &BroadcastMessage) -> Result<Vec<u8>, MlsError> {
+        if message.group_id != self.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if message.epoch != self.epoch {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        let broadcaster = self
+            .broadcasters
+            .iter_mut()
+            .find(|b| b.index == message.broadcaster)
+            .ok_or(MlsError::UnknownBroadcaster(message.broadcaster))?;
+
+        broadcaster.replay_window.check(message.generation)?;
+
+        message
+            .verify(&self.cipher_suite_provider, &broadcaster.signature_key, &())
+            .await?;
+
+        let (key, nonce) = generation_key(
+            &self.cipher_suite_provider,
+            &broadcaster.secret,
+            message.generation,
+        )
+        .await?;
+
+        let plaintext = self
+            .cipher_suite_provider
+            .aead_open(&key, &message.ciphertext, Some(&message.aad()?), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        broadcaster.replay_window.insert(message.generation);
+
+        Ok(plaintext.to_vec())
+    }
+}
+
+/// AEAD key and nonce for a single broadcast generation.
+type GenerationKey = (Zeroizing<Vec<u8>>, Zeroizing<Vec<u8>>);
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn generation_key<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    secret: &[u8],
+    generation: u32,
+) -> Result<GenerationKey, MlsError> {
+    let context = generation.to_be_bytes();
+
+    let key = kdf_expand_with_label(
+        cipher_suite_provider,
+        secret,
+        b"key",
+        &context,
+        Some(cipher_suite_provider.aead_key_size()),
+    )
+    .await?;
+
+    let nonce = kdf_expand_with_label(
+        cipher_suite_provider,
+        secret,
+        b"nonce",
+        &context,
+        Some(cipher_suite_provider.aead_nonce_size()),
+    )
+    .await?;
+
+    Ok((key, nonce))
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a [`BroadcastSender`] for this member in the current epoch that
+    /// can seal up to `max_messages` messages.
+    ///
+    /// Broadcast mode lets designated members of very large groups send
+    /// messages that cost a single symmetric encryption and signature,
+    /// independent of the size of the group. The keys are derived from the
+    /// exporter of the current epoch and the leaf index of this member, and
+    /// change with every commit.
+    ///
+    /// Each message of an epoch uses its own generation of the key. The
+    /// generations of the new sender are reserved in the group state, which
+    /// is written to the [GroupStateStorage](crate::GroupStateStorage)
+    /// before the sender is returned, so that no other sender of this member
+    /// uses them in the same epoch.
+    ///
+    /// Which members may broadcast is decided by the application; receivers
+    /// list the designated broadcasters in [`Group::broadcast_receiver`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn broadcast_sender(
+        &mut self,
+        max_messages: u32,
+    ) -> Result<BroadcastSender<<C::CryptoProvider as CryptoProvider>::CipherSuiteProvider>, MlsError>
+    {
+        let broadcaster = self.current_member_index();
+        let epoch = self.current_epoch();
+        let secret = self.broadcaster_secret(broadcaster).await?;
+
+        let generation = self.broadcast_reservation.reserve(epoch, max_messages)?;
+
+        self.write_to_storage().await?;
+
+        Ok(BroadcastSender {
+            group_id: self.group_id().to_vec(),
+            epoch,
+            broadcaster,
+            generation,
+            end_generation: generation + max_messages,
+            secret,
+            signer: self.signer.clone(),
+            cipher_suite_provider: self.cipher_suite_provider.clone(),
+        })
+    }
+
+    /// Create a [`BroadcastReceiver`] accepting messages from the members at
+    /// the leaf indices `broadcasters` in the current epoch.
+    ///
+    /// Returns [`MlsError::MemberNotFound`] if one of `broadcasters` is not a
+    /// member of the group.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn broadcast_receiver(
+        &self,
+        broadcasters: &[u32],
+    ) -> Result<
+        BroadcastReceiver<<C::CryptoProvider as CryptoProvider>::CipherSuiteProvider>,
+        MlsError,
+    > {
+        let mut keys = Vec::with_capacity(broadcasters.len());
+
+        for &index in broadcasters {
+            let member = self
+                .member_at_index(index)
+                .ok_or(MlsError::MemberNotFound)?;
+
+            keys.push(BroadcasterKeys {
+                index,
+                secret: self.broadcaster_secret(index).await?,
+                signature_key: member.signing_identity.signature_key,
+                replay_window: Default::default(),
+            });
+        }
+
+        Ok(BroadcastReceiver {
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            cipher_suite: self.cipher_suite(),
+            broadcasters: keys,
+            cipher_suite_provider: self.cipher_suite_provider.clone(),
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn broadcaster_secret(&self, broadcaster: u32) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        let secret = self
+            .export_component_secret(
+                BROADCAST_COMPONENT_ID,
+                BROADCAST_SECRET_LABEL,
+                &broadcaster.to_be_bytes(),
+                self.cipher_suite_provider.kdf_extract_size(),
+            )
+            .await?;
+
+        Ok(Zeroizing::new(secret.as_bytes().to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use mls_rs_codec::MlsDecode;
+    use mls_rs_core::group::GroupStateStorage;
+
+    use crate::client::{
+        test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        MlsError,
+    };
+    use crate::client_config::ClientConfig;
+    use crate::group::{snapshot::Snapshot, test_utils::test_n_member_group, Group};
+
+    use super::BroadcastMessage;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn broadcast_message_round_trip() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let mut sender = groups[0].broadcast_sender(10).await.unwrap();
+        let mut receiver = groups[2].broadcast_receiver(&[0]).await.unwrap();
+
+        let message = sender.seal(b"hello").await.unwrap();
+        let message = BroadcastMessage::from_bytes(&message.to_bytes().unwrap()).unwrap();
+
+        let plaintext = receiver.open(&message).await.unwrap();
+        assert_eq!(plaintext, b"hello".to_vec());
+
+        let second = sender.seal(b"world").await.unwrap();
+        assert_eq!(second.generation(), 1);
+
+        let plaintext = receiver.open(&second).await.unwrap();
+        assert_eq!(plaintext, b"world".to_vec());
+
+        let mut other_sender = groups[1].broadcast_sender(10).await.unwrap();
+        let other = other_sender.seal(b"spoof").await.unwrap();
+
+        let res = receiver.open(&other).await;
+        assert_matches!(res, Err(MlsError::UnknownBroadcaster(1)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn broadcast_keys_rotate_on_membership_change() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let mut sender = groups[0].broadcast_sender(10).await.unwrap();
+        let mut old_receiver = groups[2].broadcast_receiver(&[0]).await.unwrap();
+
+        let commit = groups[0]
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        groups[0].apply_pending_commit().await.unwrap();
+        groups[1]
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let old_message = sender.seal(b"old").await.unwrap();
+
+        let mut sender = groups[0].broadcast_sender(10).await.unwrap();
+        let mut receiver = groups[1].broadcast_receiver(&[0]).await.unwrap();
+        let message = sender.seal(b"new").await.unwrap();
+
+        // The reservation starts over in the new epoch.
+        assert_eq!(message.generation(), 0);
+
+        let res = old_receiver.open(&message).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+
+        let res = receiver.open(&old_message).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+
+        let plaintext = receiver.open(&message).await.unwrap();
+        assert_eq!(plaintext, b"new".to_vec());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn senders_never_share_generations() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let mut first = groups[0].broadcast_sender(2).await.unwrap();
+        let mut second = groups[0].broadcast_sender(2).await.unwrap();
+
+        let message = first.seal(b"a").await.unwrap();
+        assert_eq!(message.generation(), 0);
+
+        let message = second.seal(b"b").await.unwrap();
+        assert_eq!(message.generation(), 2);
+
+        let message = first.seal(b"c").await.unwrap();
+        assert_eq!(message.generation(), 1);
+        assert_eq!(first.remaining(), 0);
+
+        let res = first.seal(b"d").await;
+        assert_matches!(res, Err(MlsError::BroadcastGenerationsExhausted));
+
+        // The reservation was written to storage, so a restarted client
+        // continues after the generations of both senders.
+        let stored = groups[0]
+            .config
+            .group_state_storage()
+            .state(groups[0].group_id())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let snapshot = Snapshot::mls_decode(&mut &*stored).unwrap();
+
+        let mut restarted = Group::from_snapshot(groups[0].config.clone(), snapshot)
+            .await
+            .unwrap();
+
+        let mut sender = restarted.broadcast_sender(1).await.unwrap();
+        let message = sender.seal(b"e").await.unwrap();
+        assert_eq!(message.generation(), 4);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn replayed_broadcast_messages_are_rejected() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let mut sender = groups[0].broadcast_sender(100).await.unwrap();
+        let mut receiver = groups[1].broadcast_receiver(&[0]).await.unwrap();
+
+        let first = sender.seal(b"first").await.unwrap();
+        let second = sender.seal(b"second").await.unwrap();
+
+        receiver.open(&second).await.unwrap();
+
+        // Messages can be opened out of order, but only once.
+        receiver.open(&first).await.unwrap();
+
+        let res = receiver.open(&first).await;
+        assert_matches!(res, Err(MlsError::KeyMissing(0)));
+
+        let res = receiver.open(&second).await;
+        assert_matches!(res, Err(MlsError::KeyMissing(1)));
+
+        let mut late = None;
+
+        for i in 0..70 {
+            let message = sender.seal(b"message").await.unwrap();
+
+            if i == 0 {
+                late = Some(message);
+            } else {
+                receiver.open(&message).await.unwrap();
+            }
+        }
+
+        // Messages older than the replay window are rejected.
+        let res = receiver.open(&late.unwrap()).await;
+        assert_matches!(res, Err(MlsError::KeyMissing(2)));
+    }
+}
@@ -0,0 +1,547 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        framing::{Content, ContentType, MlsMessagePayload},
+        message_hash::MessageHash,
+        message_processor::{EventOrContent, MessageProcessor},
+        proposal_filter::ProposalApplier,
+        AuthenticatedContent, ReceivedMessage, Sender,
+    },
+    key_package::{KeyPackage, KeyPackageRef},
+    time::MlsTime,
+    MlsMessage,
+};
+
+#[cfg(not(feature = "by_ref_proposal"))]
+use crate::group::proposal_cache::resolve_for_commit;
+
+use super::Group;
+
+/// Outcome of [`Group::process_incoming_message_with_budget`] and
+/// [`Group::resume_processing`].
+#[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum BudgetedProcessing {
+    /// The message was fully processed.
+    Done(ReceivedMessage),
+    /// The budget was exhausted. Processing can be continued by passing the
+    /// token to [`Group::resume_processing`].
+    Pending(ResumeToken),
+}
+
+/// Progress of a commit whose processing was interrupted because the work
+/// budget was exhausted.
+///
+/// The token holds the authenticated commit along with the key packages that
+/// were already validated. It is only valid for the epoch it was created in.
+#[derive(Clone)]
+pub struct ResumeToken {
+    group_id: Vec<u8>,
+    epoch: u64,
+    content: AuthenticatedContent,
+    time: Option<MlsTime>,
+    validated: Vec<KeyPackageRef>,
+    next: usize,
+}
+
+impl Debug for ResumeToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumeToken")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("validated", &self.validated.len())
+            .finish()
+    }
+}
+
+impl ResumeToken {
+    /// Epoch of the group the interrupted commit was sent in.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Process an inbound message, performing at most `budget` units of work
+    /// before returning.
+    ///
+    /// One unit of work corresponds to validating one key package added by a
+    /// commit, which dominates the cost of processing large commits.
+    /// Authenticating the message and applying the commit once all key
+    /// packages are validated are not counted against the budget. Messages
+    /// other than commits are always processed in full.
+    ///
+    /// If the budget is exhausted, [`BudgetedProcessing::Pending`] is returned
+    /// and the group state is left unchanged apart from the consumed message
+    /// key. The returned token can be passed to
+    /// [`Group::resume_processing`] to continue without repeating the work
+    /// that was already done.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn process_incoming_message_with_budget(
+        &mut self,
+        message: MlsMessage,
+        budget: usize,
+    ) -> Result<BudgetedProcessing, MlsError> {
+        self.process_incoming_message_with_budget_at(message, budget, None)
+            .await
+    }
+
+    /// Process an inbound message with a work budget, providing a message
+    /// timestamp as in [`Group::process_incoming_message_with_time`].
+    ///
+    /// The timestamp is used both for the key packages validated within the
+    /// budget and for applying the commit once they are all validated.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn process_incoming_message_with_budget_and_time(
+        &mut self,
+        message: MlsMessage,
+        budget: usize,
+        time: MlsTime,
+    ) -> Result<BudgetedProcessing, MlsError> {
+        self.process_incoming_message_with_budget_at(message, budget, Some(time))
+            .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn process_incoming_message_with_budget_at(
+        &mut self,
+        message: MlsMessage,
+        budget: usize,
+        time: Option<MlsTime>,
+    ) -> Result<BudgetedProcessing, MlsError> {
+        if !is_commit(&message) || self.is_pending_commit(&message).await? {
+            return self
+                .process_incoming_message_at(message, time)
+                .await
+                .map(BudgetedProcessing::Done);
+        }
+
+        #[cfg(all(feature = "psk", feature = "private_message"))]
+        if self.is_predecessor_message(&message) {
+            return self
+                .process_incoming_message_at(message, time)
+                .await
+                .map(BudgetedProcessing::Done);
+        }
+
+        #[cfg(feature = "by_ref_proposal")]
+        self.load_offloaded_proposals_for(&message).await?;
+
+        let content =
+            match MessageProcessor::get_event_from_incoming_message(self, message, time).await? {
+                EventOrContent::Content(content) => content,
+                EventOrContent::Event(received) => return Ok(BudgetedProcessing::Done(received)),
+            };
+
+        let token = ResumeToken {
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            content,
+            time,
+            validated: Vec::new(),
+            next: 0,
+        };
+
+        self.continue_processing(token, budget).await
+    }
+
+    /// Continue processing a commit that was interrupted by
+    /// [`Group::process_incoming_message_with_budget`], performing at most
+    /// `budget` additional units of work.
+    ///
+    /// Fails with [`MlsError::ResumeTokenMismatch`] if the token was created
+    /// by another group or in another epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn resume_processing(
+        &mut self,
+        token: ResumeToken,
+        budget: usize,
+    ) -> Result<BudgetedProcessing, MlsError> {
+        if token.group_id != self.group_id() || token.epoch != self.current_epoch() {
+            return Err(MlsError::ResumeTokenMismatch);
+        }
+
+        #[cfg(feature = "by_ref_proposal")]
+        self.load_offloaded_proposals().await?;
+
+        self.continue_processing(token, budget).await
+    }
+
+    /// Process an inbound message, validating the key packages added by a
+    /// commit in chunks of `chunk_size`.
+    ///
+    /// Validating the key packages of a commit, i.e. verifying their
+    /// signatures and credentials, is the part of commit processing that
+    /// grows with the number of added members and is done in chunks. Once
+    /// all key packages are validated, the commit is applied to the group in
+    /// a single step, which is identical to the one performed by
+    /// [`Group::process_incoming_message`].
+    ///
+    /// In async builds, the task yields to the executor between chunks so
+    /// that commits adding a very large number of members do not starve
+    /// other tasks.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn process_incoming_message_chunked(
+        &mut self,
+        message: MlsMessage,
+        chunk_size: usize,
+    ) -> Result<ReceivedMessage, MlsError> {
+        let chunk_size = chunk_size.max(1);
+
+        let mut processed = self
+            .process_incoming_message_with_budget(message, chunk_size)
+            .await?;
+
+        loop {
+            match processed {
+                BudgetedProcessing::Done(received) => return Ok(received),
+                BudgetedProcessing::Pending(token) => {
+                    yield_now().await;
+
+                    processed = self.continue_processing(token, chunk_size).await?;
+                }
+            }
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn continue_processing(
+        &mut self,
+        mut token: ResumeToken,
+        mut budget: usize,
+    ) -> Result<BudgetedProcessing, MlsError> {
+        let key_packages = self.key_packages_to_validate(&token.content)?;
+
+        for key_package in key_packages.iter().skip(token.next) {
+            if budget == 0 {
+                return Ok(BudgetedProcessing::Pending(token));
+            }
+
+            let reference = self
+                .validate_added_key_package(key_package, token.time)
+                .await?;
+
+            token.validated.push(reference);
+            token.next += 1;
+            budget -= 1;
+        }
+
+        self.validated_key_packages = token.validated;
+
+        let received = MessageProcessor::process_event_or_content(
+            self,
+            EventOrContent::Content(token.content),
+            #[cfg(feature = "by_ref_proposal")]
+            true,
+            token.time,
+        )
+        .await;
+
+        self.validated_key_packages = Vec::new();
+        let received = received?;
+
+        #[cfg(feature = "by_ref_proposal")]
+        self.enforce_proposal_cache_limits(&received).await?;
+
+        Ok(BudgetedProcessing::Done(received))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn is_pending_commit(&self, message: &MlsMessage) -> Result<bool, MlsError> {
+        let Some(pending) = self.pending_commit.commit_hash()? else {
+            return Ok(false);
+        };
+
+        let message_hash = MessageHash::compute(&self.cipher_suite_provider, message).await?;
+
+        Ok(message_hash == pending)
+    }
+
+    /// Key packages added by the commit in `content` that can be validated
+    /// ahead of applying the commit.
+    ///
+    /// Key packages are validated against the current group context, so
+    /// commits that change the group context extensions or that are sent by
+    /// new members are left to regular processing.
+    fn key_packages_to_validate(
+        &self,
+        content: &AuthenticatedContent,
+    ) -> Result<Vec<KeyPackage>, MlsError> {
+        let sender = content.content.sender;
+
+        #[cfg(any(feature = "private_message", feature = "by_ref_proposal"))]
+        let Content::Commit(commit) = &content.content.content
+        else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        #[cfg(not(any(feature = "private_message", feature = "by_ref_proposal")))]
+        let Content::Commit(commit) = &content.content.content;
+
+        if !matches!(sender, Sender::Member(_)) {
+            return Ok(Vec::new());
+        }
+
+        #[cfg(feature = "by_ref_proposal")]
+        let proposals = self
+            .state
+            .proposals
+            .resolve_for_commit(sender, commit.proposals.clone())?;
+
+        #[cfg(not(feature = "by_ref_proposal"))]
+        let proposals = resolve_for_commit(sender, commit.proposals.clone())?;
+
+        if proposals.group_context_extensions_proposal().is_some() {
+            return Ok(Vec::new());
+        }
+
+        Ok(proposals
+            .add_proposals()
+            .iter()
+            .map(|p| p.proposal.key_package.clone())
+            .collect())
+    }
+
+    /// Validate `key_package` the same way the proposal applier validates
+    /// added key packages when the commit is applied.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn validate_added_key_package(
+        &self,
+        key_package: &KeyPackage,
+        commit_time: Option<MlsTime>,
+    ) -> Result<KeyPackageRef, MlsError> {
+        let identity_provider = self.config.identity_provider();
+        let psk_storage = self.config.secret_store();
+
+        let applier = ProposalApplier::new(
+            &self.state.public_tree,
+            &self.cipher_suite_provider,
+            self.context(),
+            None,
+            &identity_provider,
+            &psk_storage,
+        );
+
+        let context_extensions = &self.context().extensions;
+        let add_validator = applier.add_validator(context_extensions);
+
+        applier
+            .validate_new_node(&add_validator, key_package, commit_time)
+            .await?;
+
+        key_package.to_reference(&self.cipher_suite_provider).await
+    }
+}
+
+#[cfg(mls_build_async)]
+async fn yield_now() {
+    YieldNow(false).await
+}
+
+#[cfg(not(mls_build_async))]
+fn yield_now() {}
+
+/// Future that is pending once, waking itself so that the executor can run
+/// other tasks before it completes.
+#[cfg(mls_build_async)]
+struct YieldNow(bool);
+
+#[cfg(mls_build_async)]
+impl core::future::Future for YieldNow {
+    type Output = ();
+
+    fn poll(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<()> {
+        if self.0 {
+            return core::task::Poll::Ready(());
+        }
+
+        self.0 = true;
+        cx.waker().wake_by_ref();
+
+        core::task::Poll::Pending
+    }
+}
+
+fn is_commit(message: &MlsMessage) -> bool {
+    match &message.payload {
+        MlsMessagePayload::Plain(plaintext) => {
+            plaintext.content.content_type() == ContentType::Commit
+        }
+        #[cfg(feature = "private_message")]
+        MlsMessagePayload::Cipher(ciphertext) => ciphertext.content_type == ContentType::Commit,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{test_utils::test_group, ReceivedMessage},
+    };
+
+    use super::BudgetedProcessing;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_is_processed_across_budgets() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut builder = alice.commit_builder();
+
+        for name in ["carol", "dave", "erin"] {
+            let (_, key_package) =
+                test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await;
+
+            builder = builder.add_member(key_package).unwrap();
+        }
+
+        let commit = builder.build().await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+
+        let mut processed = bob
+            .process_incoming_message_with_budget(commit, 1)
+            .await
+            .unwrap();
+
+        let mut interruptions = 0;
+
+        let received = loop {
+            match processed {
+                BudgetedProcessing::Done(received) => break received,
+                BudgetedProcessing::Pending(token) => {
+                    interruptions += 1;
+                    assert_eq!(bob.current_epoch(), token.epoch());
+                    processed = bob.resume_processing(token, 1).await.unwrap();
+                }
+            }
+        };
+
+        assert_eq!(interruptions, 2);
+        assert_matches!(received, ReceivedMessage::Commit(_));
+        assert_eq!(bob.roster().members().len(), 5);
+        assert_eq!(alice.context(), bob.context());
+
+        assert_eq!(
+            alice.epoch_authenticator().unwrap(),
+            bob.epoch_authenticator().unwrap()
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn chunked_processing_matches_one_shot_processing() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut builder = alice.commit_builder();
+
+        for name in ["carol", "dave", "erin", "frank"] {
+            let (_, key_package) =
+                test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await;
+
+            builder = builder.add_member(key_package).unwrap();
+        }
+
+        let commit = builder.build().await.unwrap().commit_message;
+
+        let mut bob_one_shot = bob.group.clone();
+
+        bob_one_shot
+            .process_incoming_message(commit.clone())
+            .await
+            .unwrap();
+
+        let received = bob
+            .process_incoming_message_chunked(commit, 3)
+            .await
+            .unwrap();
+
+        assert_matches!(received, ReceivedMessage::Commit(_));
+        assert_eq!(bob.context(), bob_one_shot.context());
+        assert_eq!(bob.export_tree(), bob_one_shot.export_tree());
+
+        assert_eq!(
+            bob.epoch_authenticator().unwrap(),
+            bob_one_shot.epoch_authenticator().unwrap()
+        );
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn non_commit_messages_are_processed_in_full() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let message = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let res = bob.process_incoming_message_with_budget(message, 0).await;
+
+        assert_matches!(
+            res,
+            Ok(BudgetedProcessing::Done(
+                ReceivedMessage::ApplicationMessage(_)
+            ))
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn resume_token_of_past_epoch_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let commit = alice
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        let BudgetedProcessing::Pending(token) = bob
+            .process_incoming_message_with_budget(commit, 0)
+            .await
+            .unwrap()
+        else {
+            panic!("expected processing to be pending")
+        };
+
+        alice.clear_pending_commit();
+        let commit = alice.commit(vec![]).await.unwrap().commit_message;
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_message(commit).await.unwrap();
+
+        let res = bob.resume_processing(token, 1).await;
+
+        assert_matches!(res, Err(MlsError::ResumeTokenMismatch));
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::{extension::ExtensionList, group::Capabilities};
+
+use crate::{client::MlsError, extension::RequiredCapabilitiesExt};
+
+/// Restrict `group_context_extensions` to what both `founder` and `peer`
+/// support and require every capability they have in common.
+///
+/// Extensions of a non-default type that one of the parties doesn't support
+/// are dropped. If `group_context_extensions` already contains
+/// [`RequiredCapabilitiesExt`], it is narrowed to the common capabilities,
+/// otherwise all common capabilities are required.
+pub(crate) fn negotiate_group_context_extensions(
+    founder: &Capabilities,
+    peer: &Capabilities,
+    group_context_extensions: ExtensionList,
+) -> Result<ExtensionList, MlsError> {
+    let common = RequiredCapabilitiesExt {
+        extensions: intersection(&founder.extensions, &peer.extensions),
+        proposals: intersection(&founder.proposals, &peer.proposals),
+        credentials: intersection(&founder.credentials, &peer.credentials),
+    };
+
+    let required = match group_context_extensions.get_as::<RequiredCapabilitiesExt>()? {
+        Some(requested) => RequiredCapabilitiesExt {
+            extensions: intersection(&requested.extensions, &common.extensions),
+            proposals: intersection(&requested.proposals, &common.proposals),
+            credentials: intersection(&requested.credentials, &common.credentials),
+        },
+        None => common,
+    };
+
+    let mut negotiated = group_context_extensions
+        .iter()
+        .filter(|ext| {
+            ext.extension_type.is_default() || required.extensions.contains(&ext.extension_type)
+        })
+        .cloned()
+        .collect::<ExtensionList>();
+
+    negotiated.set_from(required)?;
+
+    Ok(negotiated)
+}
+
+fn intersection<T: PartialEq + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    a.iter().filter(|v| b.contains(v)).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use mls_rs_core::{
+        extension::{ExtensionList, ExtensionType, MlsExtension},
+        group::{Capabilities, ProposalType},
+        identity::CredentialType,
+    };
+
+    use crate::{
+        client::{
+            test_utils::{TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        extension::{test_utils::TestExtension, RequiredCapabilitiesExt},
+        identity::test_utils::get_test_signing_identity,
+        key_package::test_utils::test_key_package_message,
+    };
+
+    use super::negotiate_group_context_extensions;
+
+    const EXT_A: ExtensionType = ExtensionType::new(65000);
+    const EXT_B: ExtensionType = ExtensionType::new(65001);
+
+    fn capabilities(extensions: &[ExtensionType], proposals: &[u16]) -> Capabilities {
+        Capabilities {
+            extensions: extensions.to_vec(),
+            proposals: proposals.iter().copied().map(ProposalType::new).collect(),
+            credentials: vec![CredentialType::BASIC],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn negotiation_keeps_common_capabilities() {
+        let founder = capabilities(&[EXT_A, EXT_B], &[65000, 65001]);
+        let peer = capabilities(&[EXT_B], &[65001]);
+
+        let mut requested = ExtensionList::new();
+        requested.set(TestExtension { foo: 1 }.into_extension().unwrap());
+
+        let negotiated = negotiate_group_context_extensions(&founder, &peer, requested).unwrap();
+
+        let required = negotiated
+            .get_as::<RequiredCapabilitiesExt>()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(required.extensions, vec![EXT_B]);
+        assert_eq!(required.proposals, vec![ProposalType::new(65001)]);
+        assert_eq!(required.credentials, vec![CredentialType::BASIC]);
+
+        // The test extension isn't supported by the peer
+        assert!(!negotiated.has_extension(TestExtension::extension_type()));
+    }
+
+    #[test]
+    fn negotiation_narrows_requested_capabilities() {
+        let founder = capabilities(&[EXT_A, EXT_B], &[]);
+        let peer = capabilities(&[EXT_A, EXT_B], &[]);
+
+        let mut requested = ExtensionList::new();
+
+        requested
+            .set_from(RequiredCapabilitiesExt {
+                extensions: vec![EXT_A],
+                ..Default::default()
+            })
+            .unwrap();
+
+        let negotiated = negotiate_group_context_extensions(&founder, &peer, requested).unwrap();
+
+        let required = negotiated
+            .get_as::<RequiredCapabilitiesExt>()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(required.extensions, vec![EXT_A]);
+        assert!(required.credentials.is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_created_for_peer_can_add_it() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let alice = TestClientBuilder::new_for_test()
+            .extension_type(EXT_A)
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let bob = test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let mut group = alice
+            .create_group_for_peer(&bob, Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let required = group
+            .context()
+            .extensions
+            .get_as::<RequiredCapabilitiesExt>()
+            .unwrap()
+            .unwrap();
+
+        // Bob doesn't support the custom extension of alice
+        assert!(required.extensions.is_empty());
+
+        group
+            .commit_builder()
+            .add_member(bob)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_for_peer_requires_key_package() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let alice = TestClientBuilder::new_for_test()
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let group_info = alice
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap()
+            .group_info_message(false)
+            .await
+            .unwrap();
+
+        let res = alice
+            .create_group_for_peer(&group_info, Default::default(), Default::default(), None)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::UnexpectedMessageType));
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use mls_rs_core::{
+    extension::{ExtensionList, ExtensionType},
+    group::{Capabilities, ProposalType},
+    identity::CredentialType,
+};
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, extension::RequiredCapabilitiesExt, CipherSuite,
+    ProtocolVersion,
+};
+
+use super::Group;
+
+/// Members supporting a single value of a capability.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapabilitySupport<T> {
+    pub value: T,
+    /// Leaf indexes of the members advertising support for `value`.
+    pub supported_by: Vec<u32>,
+}
+
+/// Capabilities advertised by the current members of a group, produced by
+/// [`Group::capability_report`].
+///
+/// Each list is sorted by value and only contains values supported by at
+/// least one member.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CapabilityReport {
+    /// Leaf indexes of all members of the group.
+    pub members: Vec<u32>,
+    pub protocol_versions: Vec<CapabilitySupport<ProtocolVersion>>,
+    pub cipher_suites: Vec<CapabilitySupport<CipherSuite>>,
+    pub extensions: Vec<CapabilitySupport<ExtensionType>>,
+    pub proposals: Vec<CapabilitySupport<ProposalType>>,
+    pub credentials: Vec<CapabilitySupport<CredentialType>>,
+}
+
+impl CapabilityReport {
+    fn new<'a, I>(members: I) -> Self
+    where
+        I: Iterator<Item = (u32, &'a Capabilities)> + Clone,
+    {
+        Self {
+            members: members.clone().map(|(index, _)| index).collect(),
+            protocol_versions: support(members.clone(), |c| c.protocol_versions.as_slice()),
+            cipher_suites: support(members.clone(), |c| c.cipher_suites.as_slice()),
+            extensions: support(members.clone(), |c| c.extensions.as_slice()),
+            proposals: support(members.clone(), |c| c.proposals.as_slice()),
+            credentials: support(members, |c| c.credentials.as_slice()),
+        }
+    }
+
+    /// Members that do not advertise support for `extension_type`.
+    pub fn members_without_extension(&self, extension_type: ExtensionType) -> Vec<u32> {
+        self.members_without(&self.extensions, &extension_type)
+    }
+
+    /// Members that do not advertise support for `proposal_type`.
+    pub fn members_without_proposal(&self, proposal_type: ProposalType) -> Vec<u32> {
+        self.members_without(&self.proposals, &proposal_type)
+    }
+
+    /// Members that do not advertise support for `credential_type`.
+    pub fn members_without_credential(&self, credential_type: CredentialType) -> Vec<u32> {
+        self.members_without(&self.credentials, &credential_type)
+    }
+
+    /// Members that do not advertise support for `protocol_version`.
+    pub fn members_without_protocol_version(&self, protocol_version: ProtocolVersion) -> Vec<u32> {
+        self.members_without(&self.protocol_versions, &protocol_version)
+    }
+
+    /// Members that do not advertise support for `cipher_suite`.
+    pub fn members_without_cipher_suite(&self, cipher_suite: CipherSuite) -> Vec<u32> {
+        self.members_without(&self.cipher_suites, &cipher_suite)
+    }
+
+    /// Members that would prevent the group context extensions of the group
+    /// from being replaced by `extensions`.
+    ///
+    /// A member is incompatible if it doesn't support one of the non-default
+    /// extensions in `extensions` or one of the capabilities required by the
+    /// [`RequiredCapabilitiesExt`] in `extensions`, if any.
+    pub fn incompatible_members(&self, extensions: &ExtensionList) -> Result<Vec<u32>, MlsError> {
+        let mut incompatible = Vec::new();
+
+        for extension_type in extensions.iter().map(|ext| ext.extension_type) {
+            if !extension_type.is_default() {
+                incompatible.extend(self.members_without_extension(extension_type));
+            }
+        }
+
+        if let Some(required) = extensions.get_as::<RequiredCapabilitiesExt>()? {
+            for extension_type in required.extensions {
+                incompatible.extend(self.members_without_extension(extension_type));
+            }
+
+            for proposal_type in required.proposals {
+                incompatible.extend(self.members_without_proposal(proposal_type));
+            }
+
+            for credential_type in required.credentials {
+                incompatible.extend(self.members_without_credential(credential_type));
+            }
+        }
+
+        incompatible.sort_unstable();
+        incompatible.dedup();
+
+        Ok(incompatible)
+    }
+
+    fn members_without<T: PartialEq>(
+        &self,
+        support: &[CapabilitySupport<T>],
+        value: &T,
+    ) -> Vec<u32> {
+        let supported_by = support
+            .iter()
+            .find(|s| &s.value == value)
+            .map(|s| s.supported_by.as_slice())
+            .unwrap_or_default();
+
+        self.members
+            .iter()
+            .copied()
+            .filter(|index| !supported_by.contains(index))
+            .collect()
+    }
+}
+
+fn support<'a, I, T, F>(members: I, values: F) -> Vec<CapabilitySupport<T>>
+where
+    I: Iterator<Item = (u32, &'a Capabilities)>,
+    T: Ord + Clone + 'a,
+    F: Fn(&'a Capabilities) -> &'a [T],
+{
+    let mut support = BTreeMap::<T, Vec<u32>>::new();
+
+    for (index, capabilities) in members {
+        for value in values(capabilities) {
+            let supported_by = support.entry(value.clone()).or_default();
+
+            // Capabilities may list the same value more than once.
+            if supported_by.last() != Some(&index) {
+                supported_by.push(index);
+            }
+        }
+    }
+
+    support
+        .into_iter()
+        .map(|(value, supported_by)| CapabilitySupport {
+            value,
+            supported_by,
+        })
+        .collect()
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Summarize which members support each protocol version, cipher suite,
+    /// extension, proposal and credential type advertised in the group.
+    ///
+    /// This can be used to find the members that are incompatible with a
+    /// group context extensions change before proposing it.
+    pub fn capability_report(&self) -> CapabilityReport {
+        let capabilities = self
+            .current_epoch_tree()
+            .non_empty_leaves()
+            .map(|(index, leaf)| (*index, leaf.ungreased_capabilities()))
+            .collect::<Vec<_>>();
+
+        CapabilityReport::new(capabilities.iter().map(|(index, c)| (*index, c)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use mls_rs_core::extension::{ExtensionList, ExtensionType};
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        extension::RequiredCapabilitiesExt,
+        group::test_utils::test_group,
+        identity::basic::BasicCredential,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn report_lists_supporting_members() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let report = bob.capability_report();
+        assert_eq!(report.members, vec![0, 1]);
+
+        let credential = report
+            .credentials
+            .iter()
+            .find(|s| s.value == BasicCredential::credential_type())
+            .unwrap();
+
+        assert_eq!(credential.supported_by, vec![0, 1]);
+
+        assert!(report
+            .members_without_cipher_suite(TEST_CIPHER_SUITE)
+            .is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unsupported_required_capabilities_are_reported() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let report = bob.capability_report();
+        let unsupported = ExtensionType::new(65000);
+
+        assert_eq!(report.members_without_extension(unsupported), vec![0, 1]);
+
+        let mut extensions = ExtensionList::new();
+
+        extensions
+            .set_from(RequiredCapabilitiesExt {
+                extensions: vec![unsupported],
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(
+            report.incompatible_members(&extensions).unwrap(),
+            vec![0, 1]
+        );
+
+        assert!(report
+            .incompatible_members(&ExtensionList::new())
+            .unwrap()
+            .is_empty());
+    }
+}
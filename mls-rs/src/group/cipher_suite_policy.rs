@@ -0,0 +1,339 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_core::crypto::CipherSuite;
+
+use crate::{client::MlsError, MlsMessage};
+
+/// Action taken when a group would use a cipher suite that is weaker than
+/// the minimum of a [`CipherSuitePolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DowngradeAction {
+    /// Use the weaker cipher suite without reporting it.
+    #[default]
+    Allow,
+    /// Use the weaker cipher suite and report a [`CipherSuiteDowngrade`].
+    Warn,
+    /// Fail with [`MlsError::CipherSuiteBelowMinimum`].
+    Block,
+}
+
+/// Ordering of cipher suites by strength, used to choose a cipher suite for
+/// new groups and to detect groups that use a weak cipher suite.
+///
+/// The default policy has no ordering and no minimum, which allows any
+/// cipher suite supported by the crypto provider.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct CipherSuitePolicy {
+    strength_order: Vec<CipherSuite>,
+    minimum: Option<CipherSuite>,
+    action: DowngradeAction,
+}
+
+impl CipherSuitePolicy {
+    /// Create a policy from cipher suites ordered from strongest to weakest.
+    ///
+    /// Cipher suites that are not in `strength_order` are considered weaker
+    /// than all cipher suites that are.
+    pub fn new(strength_order: Vec<CipherSuite>) -> Self {
+        Self {
+            strength_order,
+            ..Default::default()
+        }
+    }
+
+    /// Set the weakest acceptable cipher suite and the action taken when a
+    /// weaker one is used.
+    pub fn with_minimum(self, minimum: CipherSuite, action: DowngradeAction) -> Self {
+        Self {
+            minimum: Some(minimum),
+            action,
+            ..self
+        }
+    }
+
+    /// Cipher suites ordered from strongest to weakest.
+    pub fn strength_order(&self) -> &[CipherSuite] {
+        &self.strength_order
+    }
+
+    /// Weakest acceptable cipher suite, if any.
+    pub fn minimum(&self) -> Option<CipherSuite> {
+        self.minimum
+    }
+
+    /// Action taken when a cipher suite weaker than the minimum is used.
+    pub fn action(&self) -> DowngradeAction {
+        self.action
+    }
+
+    /// Returns true if `cipher_suite` is strictly weaker than `other`.
+    pub fn is_weaker(&self, cipher_suite: CipherSuite, other: CipherSuite) -> bool {
+        match (self.rank(cipher_suite), self.rank(other)) {
+            (Some(rank), Some(other_rank)) => rank > other_rank,
+            (None, Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn rank(&self, cipher_suite: CipherSuite) -> Option<usize> {
+        self.strength_order
+            .iter()
+            .position(|cs| *cs == cipher_suite)
+    }
+
+    /// Apply the policy to a group using `cipher_suite`.
+    pub(crate) fn check(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Result<Option<CipherSuiteDowngrade>, MlsError> {
+        let Some(minimum) = self.minimum else {
+            return Ok(None);
+        };
+
+        if cipher_suite == minimum || !self.is_weaker(cipher_suite, minimum) {
+            return Ok(None);
+        }
+
+        match self.action {
+            DowngradeAction::Allow => Ok(None),
+            DowngradeAction::Warn => Ok(Some(CipherSuiteDowngrade {
+                cipher_suite,
+                minimum,
+            })),
+            DowngradeAction::Block => Err(MlsError::CipherSuiteBelowMinimum(cipher_suite)),
+        }
+    }
+
+    /// Choose the strongest cipher suite in `supported` that every key package
+    /// in `key_packages` can use.
+    pub(crate) fn select(
+        &self,
+        supported: &[CipherSuite],
+        key_packages: &[MlsMessage],
+    ) -> Result<CipherSuiteSelection, MlsError> {
+        let key_packages = key_packages
+            .iter()
+            .map(|kp| kp.as_key_package().ok_or(MlsError::UnexpectedMessageType))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut candidates = supported.to_vec();
+
+        // Stable sort keeps the order of the crypto provider for suites that
+        // are not ranked by the policy.
+        candidates.sort_by_key(|cs| self.rank(*cs).unwrap_or(usize::MAX));
+
+        let preferred = *candidates.first().ok_or(MlsError::NoCommonCipherSuite)?;
+
+        let cipher_suite = candidates
+            .into_iter()
+            .find(|cs| {
+                key_packages.iter().all(|kp| {
+                    kp.cipher_suite == *cs || kp.leaf_node.capabilities.cipher_suites.contains(cs)
+                })
+            })
+            .ok_or(MlsError::NoCommonCipherSuite)?;
+
+        Ok(CipherSuiteSelection {
+            cipher_suite,
+            preferred,
+            downgrade: self.check(cipher_suite)?,
+        })
+    }
+}
+
+/// Warning that a group uses a cipher suite weaker than the minimum of the
+/// [`CipherSuitePolicy`] of the client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CipherSuiteDowngrade {
+    /// Cipher suite used by the group.
+    pub cipher_suite: CipherSuite,
+    /// Minimum cipher suite of the policy.
+    pub minimum: CipherSuite,
+}
+
+/// Result of [`Client::select_cipher_suite`](crate::Client::select_cipher_suite).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CipherSuiteSelection {
+    /// Strongest cipher suite supported by this client and all joiners.
+    pub cipher_suite: CipherSuite,
+    /// Strongest cipher suite supported by this client.
+    pub preferred: CipherSuite,
+    /// Set if `cipher_suite` is below the minimum of a policy using
+    /// [`DowngradeAction::Warn`].
+    pub downgrade: Option<CipherSuiteDowngrade>,
+}
+
+impl CipherSuiteSelection {
+    /// Returns true if joiners forced the selection of a cipher suite weaker
+    /// than the one preferred by this client.
+    pub fn is_downgraded(&self) -> bool {
+        self.cipher_suite != self.preferred
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::CipherSuite;
+
+    use crate::{
+        client::{
+            test_utils::{
+                test_client_with_key_pkg, TestClientBuilder, TEST_CIPHER_SUITE,
+                TEST_PROTOCOL_VERSION,
+            },
+            MlsError,
+        },
+        crypto::test_utils::TestCryptoProvider,
+        group::{framing::MlsMessagePayload, test_utils::test_group_custom_config},
+        identity::test_utils::get_test_signing_identity,
+        MlsMessage,
+    };
+
+    use super::{CipherSuitePolicy, DowngradeAction};
+
+    fn stronger_suite() -> CipherSuite {
+        TestCryptoProvider::all_supported_cipher_suites()
+            .into_iter()
+            .find(|cs| *cs != TEST_CIPHER_SUITE)
+            .unwrap()
+    }
+
+    fn policy(action: DowngradeAction) -> CipherSuitePolicy {
+        CipherSuitePolicy::new(vec![stronger_suite(), TEST_CIPHER_SUITE])
+            .with_minimum(stronger_suite(), action)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn key_package_supporting(cipher_suites: Vec<CipherSuite>) -> MlsMessage {
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let mut key_package = key_package.into_key_package().unwrap();
+        key_package.leaf_node.capabilities.cipher_suites = cipher_suites;
+
+        MlsMessage::new(
+            TEST_PROTOCOL_VERSION,
+            MlsMessagePayload::KeyPackage(key_package),
+        )
+    }
+
+    #[test]
+    fn policy_applies_action_below_minimum() {
+        assert_matches!(
+            policy(DowngradeAction::Allow).check(TEST_CIPHER_SUITE),
+            Ok(None)
+        );
+
+        let downgrade = policy(DowngradeAction::Warn)
+            .check(TEST_CIPHER_SUITE)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(downgrade.cipher_suite, TEST_CIPHER_SUITE);
+        assert_eq!(downgrade.minimum, stronger_suite());
+
+        assert_matches!(
+            policy(DowngradeAction::Block).check(TEST_CIPHER_SUITE),
+            Err(MlsError::CipherSuiteBelowMinimum(cs)) if cs == TEST_CIPHER_SUITE
+        );
+
+        assert_matches!(
+            policy(DowngradeAction::Block).check(stronger_suite()),
+            Ok(None)
+        );
+
+        assert_matches!(
+            CipherSuitePolicy::default().check(TEST_CIPHER_SUITE),
+            Ok(None)
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn selection_reports_downgrade_forced_by_joiners() {
+        let client = TestClientBuilder::new_for_test()
+            .cipher_suite_policy(policy(DowngradeAction::Warn))
+            .build();
+
+        let strong_joiner = key_package_supporting(vec![stronger_suite(), TEST_CIPHER_SUITE]).await;
+
+        let selection = client
+            .select_cipher_suite(core::slice::from_ref(&strong_joiner))
+            .unwrap();
+
+        assert_eq!(selection.cipher_suite, stronger_suite());
+        assert!(!selection.is_downgraded());
+        assert_eq!(selection.downgrade, None);
+
+        let weak_joiner = key_package_supporting(vec![TEST_CIPHER_SUITE]).await;
+
+        let selection = client
+            .select_cipher_suite(&[strong_joiner, weak_joiner.clone()])
+            .unwrap();
+
+        assert_eq!(selection.cipher_suite, TEST_CIPHER_SUITE);
+        assert_eq!(selection.preferred, stronger_suite());
+        assert!(selection.is_downgraded());
+        assert!(selection.downgrade.is_some());
+
+        let client = TestClientBuilder::new_for_test()
+            .cipher_suite_policy(policy(DowngradeAction::Block))
+            .build();
+
+        assert_matches!(
+            client.select_cipher_suite(&[weak_joiner]),
+            Err(MlsError::CipherSuiteBelowMinimum(_))
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_creation_is_blocked_below_minimum() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let res = TestClientBuilder::new_for_test()
+            .cipher_suite_policy(policy(DowngradeAction::Block))
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build()
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::CipherSuiteBelowMinimum(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn adding_members_below_minimum_is_reported() {
+        let mut group = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |c| {
+            c.cipher_suite_policy(policy(DowngradeAction::Warn))
+        })
+        .await;
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let output = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            output.cipher_suite_downgrade.map(|d| d.cipher_suite),
+            Some(TEST_CIPHER_SUITE)
+        );
+
+        group.apply_pending_commit().await.unwrap();
+
+        let output = group.commit(Vec::new()).await.unwrap();
+
+        assert_eq!(output.cipher_suite_downgrade, None);
+    }
+}
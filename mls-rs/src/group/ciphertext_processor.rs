@@ -20,6 +20,7 @@ use crate::{
     client::MlsError,
     tree_kem::node::{LeafIndex, NodeIndex},
 };
+use alloc::vec::Vec;
 use mls_rs_codec::MlsEncode;
 use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
 use zeroize::Zeroizing;
@@ -48,6 +49,7 @@ where
 {
     group_state: &'a mut GS,
     cipher_suite_provider: CP,
+    context_binding: Option<Vec<u8>>,
 }
 
 impl<'a, GS, CP> CiphertextProcessor<'a, GS, CP>
@@ -62,9 +64,30 @@ where
         Self {
             group_state,
             cipher_suite_provider,
+            context_binding: None,
         }
     }
 
+    /// Bind the content of sealed and opened messages to `context_binding`.
+    pub fn with_context_binding(self, context_binding: Option<Vec<u8>>) -> Self {
+        Self {
+            context_binding,
+            ..self
+        }
+    }
+
+    // The context binding is not part of the message. It is appended to the
+    // encoded AAD so that both sides must agree on it for decryption to work.
+    fn content_aad(&self, aad: &PrivateContentAAD) -> Result<Vec<u8>, MlsError> {
+        let mut aad = aad.mls_encode_to_vec()?;
+
+        if let Some(context_binding) = &self.context_binding {
+            mls_rs_codec::byte_vec::mls_encode(context_binding, &mut aad)?;
+        }
+
+        Ok(aad)
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn next_encryption_key(
         &mut self,
@@ -150,7 +173,7 @@ where
             .encrypt(
                 &self.cipher_suite_provider,
                 &serialized_private_content,
-                &aad.mls_encode_to_vec()?,
+                &self.content_aad(&aad)?,
                 &reuse_guard,
             )
             .await
@@ -191,11 +214,24 @@ where
         })
     }
 
+    #[cfg(any(test, feature = "compliance"))]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn open(
         &mut self,
         ciphertext: &PrivateMessage,
     ) -> Result<AuthenticatedContent, MlsError> {
+        self.open_with_generation(ciphertext)
+            .await
+            .map(|(content, _)| content)
+    }
+
+    /// Decrypt `ciphertext`, also returning the generation of the key used to
+    /// decrypt the message.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn open_with_generation(
+        &mut self,
+        ciphertext: &PrivateMessage,
+    ) -> Result<(AuthenticatedContent, u32), MlsError> {
         // Decrypt the sender data with the derived sender_key and sender_nonce from the message
         // epoch's key schedule
         let sender_data_aad = SenderDataAAD {
@@ -236,7 +272,7 @@ where
             .decrypt(
                 &self.cipher_suite_provider,
                 &ciphertext.ciphertext,
-                &PrivateContentAAD::from(ciphertext).mls_encode_to_vec()?,
+                &self.content_aad(&PrivateContentAAD::from(ciphertext))?,
                 &sender_data.reuse_guard,
             )
             .await
@@ -258,7 +294,7 @@ where
             auth: ciphertext_content.auth,
         };
 
-        Ok(auth_content)
+        Ok((auth_content, sender_data.generation))
     }
 }
 
@@ -347,6 +383,36 @@ mod test {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn context_binding_must_match() {
+        let mut test_data = test_data(TEST_CIPHER_SUITE).await;
+        let mut receiver_group = test_data.group.clone();
+        receiver_group.private_tree.self_index = LeafIndex::unchecked(1);
+
+        let ciphertext = test_processor(&mut test_data.group, TEST_CIPHER_SUITE)
+            .with_context_binding(Some(b"tenant".to_vec()))
+            .seal(test_data.content.clone(), PaddingMode::None)
+            .await
+            .unwrap();
+
+        for binding in [None, Some(b"other tenant".to_vec())] {
+            let res = test_processor(&mut receiver_group.clone(), TEST_CIPHER_SUITE)
+                .with_context_binding(binding)
+                .open(&ciphertext)
+                .await;
+
+            assert!(res.is_err());
+        }
+
+        let decrypted = test_processor(&mut receiver_group, TEST_CIPHER_SUITE)
+            .with_context_binding(Some(b"tenant".to_vec()))
+            .open(&ciphertext)
+            .await
+            .unwrap();
+
+        assert_eq!(decrypted, test_data.content);
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_padding_use() {
         let mut test_data = test_data(TEST_CIPHER_SUITE).await;
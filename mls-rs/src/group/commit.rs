@@ -16,10 +16,11 @@ use crate::{
     extension::RatchetTreeExt,
     identity::SigningIdentity,
     protocol_version::ProtocolVersion,
+    psk::secret::PskSecret,
     signer::Signable,
     time::MlsTime,
     tree_kem::{kem::TreeKem, path_secret::PathSecret, TreeKemPrivate, UpdatePath},
-    ExtensionList, MlsRules,
+    ExtensionList, KeyPackage, MlsRules,
 };
 
 #[cfg(all(not(mls_build_async), feature = "rayon"))]
@@ -27,27 +28,31 @@ use {crate::iter::ParallelIteratorExt, rayon::prelude::*};
 
 use crate::tree_kem::leaf_node::LeafNode;
 
-#[cfg(not(feature = "private_message"))]
 use crate::WireFormat;
 
+#[cfg(feature = "private_message")]
+use super::ContentType;
+
 #[cfg(feature = "psk")]
 use crate::{
     group::{JustPreSharedKeyID, PskGroupId, ResumptionPSKUsage, ResumptionPsk},
-    psk::ExternalPskId,
+    psk::{ExternalPskId, PreSharedKeyID},
 };
 
 use super::{
+    app_versions::app_versions_change,
     confirmation_tag::ConfirmationTag,
+    feature_flags::feature_flags_change,
     framing::{Content, MlsMessage, MlsMessagePayload, Sender},
-    key_schedule::{KeySchedule, WelcomeSecret},
+    key_schedule::{KeySchedule, KeyScheduleDerivationResult, WelcomeSecret},
     message_hash::MessageHash,
-    message_processor::{path_update_required, MessageProcessor},
-    message_signature::AuthenticatedContent,
-    mls_rules::CommitDirection,
+    message_processor::{path_update_required, MessageProcessor, ProvisionalState},
+    message_signature::{AuthenticatedContent, MessageSigningContext},
+    mls_rules::{CommitDirection, CommitOptions, CommitSource},
     proposal::{Proposal, ProposalOrRef},
-    CommitEffect, CommitMessageDescription, EncryptedGroupSecrets, EpochSecrets, ExportedTree,
-    Group, GroupContext, GroupInfo, GroupState, InterimTranscriptHash, NewEpoch,
-    PendingCommitSnapshot, Welcome,
+    CipherSuiteDowngrade, CommitEffect, CommitMessageDescription, EncryptedGroupSecrets,
+    EpochSecrets, ExportedTree, Group, GroupContext, GroupInfo, GroupState, InterimTranscriptHash,
+    NewEpoch, PendingCommitSnapshot, UnsignedCommit, Welcome,
 };
 
 #[cfg(not(feature = "by_ref_proposal"))]
@@ -77,6 +82,51 @@ pub(crate) struct PendingCommit {
     pub(crate) commit_message_hash: MessageHash,
 }
 
+/// State of a commit whose content is built but not yet signed.
+pub(crate) struct CommitInProgress {
+    pub(crate) auth_content: AuthenticatedContent,
+    provisional_state: ProvisionalState,
+    provisional_private_tree: TreeKemPrivate,
+    commit_options: CommitOptions,
+    perform_path_update: bool,
+    path_secrets: Option<Vec<Option<PathSecret>>>,
+    commit_secret: PathSecret,
+    psk_secret: PskSecret,
+    #[cfg(feature = "psk")]
+    psks: Vec<PreSharedKeyID>,
+    added_key_pkgs: Vec<KeyPackage>,
+    cipher_suite_downgrade: Option<CipherSuiteDowngrade>,
+    pub(crate) new_signer: SignatureSecretKey,
+    welcome_group_info_extensions: ExtensionList,
+}
+
+/// State of a commit whose content is signed but whose group info messages
+/// are not yet signed.
+pub(crate) struct SignedCommitInProgress {
+    pub(crate) commit: CommitInProgress,
+    key_schedule_result: KeyScheduleDerivationResult,
+    interim_transcript_hash: InterimTranscriptHash,
+    confirmation_tag: ConfirmationTag,
+    external_commit_group_info: Option<GroupInfo>,
+    welcome_group_info: GroupInfo,
+}
+
+impl SignedCommitInProgress {
+    /// Group info messages that must be signed by the committer, in a fixed
+    /// order.
+    pub(crate) fn group_infos(&self) -> impl Iterator<Item = &GroupInfo> {
+        self.external_commit_group_info
+            .iter()
+            .chain(core::iter::once(&self.welcome_group_info))
+    }
+
+    pub(crate) fn group_infos_mut(&mut self) -> impl Iterator<Item = &mut GroupInfo> {
+        self.external_commit_group_info
+            .iter_mut()
+            .chain(core::iter::once(&mut self.welcome_group_info))
+    }
+}
+
 #[cfg_attr(
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
@@ -96,6 +146,37 @@ impl CommitSecrets {
     }
 }
 
+/// Stable identifier of a commit attempt, computed as the hash of the framed
+/// commit message.
+///
+/// The same commit always has the same identifier, which makes it suitable as
+/// an idempotency token when uploading the commit to the delivery service.
+/// The identifier is stored with the pending commit, so it can be queried with
+/// [`Group::pending_commit_attempt_id`](crate::group::Group::pending_commit_attempt_id)
+/// after the group is reloaded from storage.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CommitAttemptId(Vec<u8>);
+
+impl CommitAttemptId {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Debug for CommitAttemptId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        mls_rs_core::debug::pretty_bytes(&self.0)
+            .named("CommitAttemptId")
+            .fmt(f)
+    }
+}
+
+impl From<MessageHash> for CommitAttemptId {
+    fn from(hash: MessageHash) -> Self {
+        Self(hash.into_bytes())
+    }
+}
+
 #[cfg_attr(
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
@@ -128,6 +209,12 @@ pub struct CommitOutput {
     pub unused_proposals: Vec<crate::mls_rules::ProposalInfo<Proposal>>,
     /// Indicator that the commit contains a path update
     pub contains_update_path: bool,
+    /// Set if the commit adds members to a group whose cipher suite is weaker
+    /// than the minimum of the [`CipherSuitePolicy`](crate::group::CipherSuitePolicy)
+    /// of the client.
+    pub cipher_suite_downgrade: Option<CipherSuiteDowngrade>,
+    /// Identifier of this commit attempt, see [`CommitAttemptId`].
+    pub attempt_id: CommitAttemptId,
 }
 
 #[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
@@ -178,14 +265,17 @@ pub struct CommitBuilder<'a, C>
 where
     C: ClientConfig + Clone,
 {
-    group: &'a mut Group<C>,
+    pub(super) group: &'a mut Group<C>,
     pub(super) proposals: Vec<Proposal>,
-    authenticated_data: Vec<u8>,
-    group_info_extensions: ExtensionList,
+    pub(super) authenticated_data: Vec<u8>,
+    pub(super) group_info_extensions: ExtensionList,
     new_signer: Option<SignatureSecretKey>,
     new_signing_identity: Option<SigningIdentity>,
     new_leaf_node_extensions: Option<ExtensionList>,
     commit_time: Option<MlsTime>,
+    wire_format: Option<WireFormat>,
+    intents: Vec<(Vec<u8>, Vec<Proposal>)>,
+    intent_start: usize,
 }
 
 impl<'a, C> CommitBuilder<'a, C>
@@ -350,6 +440,37 @@ where
         }
     }
 
+    /// Send the commit as `wire_format` instead of the wire format derived
+    /// from the [`EncryptionOptions`](crate::mls_rules::EncryptionOptions)
+    /// returned by the [`MlsRules`](crate::MlsRules).
+    ///
+    /// `wire_format` must be [`WireFormat::PublicMessage`] or
+    /// [`WireFormat::PrivateMessage`] and the override must be allowed by
+    /// [`MlsRules::allow_wire_format_override`](crate::MlsRules::allow_wire_format_override),
+    /// otherwise building the commit fails with
+    /// [`MlsError::WireFormatOverrideDenied`].
+    #[cfg(feature = "private_message")]
+    pub fn wire_format(self, wire_format: WireFormat) -> Self {
+        Self {
+            wire_format: Some(wire_format),
+            ..self
+        }
+    }
+
+    /// Attach the opaque `intent_id` to the proposals inserted since the
+    /// previous call to this function, e.g. to track "invite Alice and Bob"
+    /// in a user interface.
+    ///
+    /// The outcome of each intent is reported by
+    /// [`Group::take_intent_results`] once the commit is applied or
+    /// superseded.
+    pub fn intent(mut self, intent_id: Vec<u8>) -> Self {
+        let proposals = self.proposals[self.intent_start..].to_vec();
+        self.intent_start = self.proposals.len();
+        self.intents.push((intent_id, proposals));
+        self
+    }
+
     /// Finalize the commit to send.
     ///
     /// # Errors
@@ -371,10 +492,12 @@ where
                 self.new_signing_identity,
                 self.new_leaf_node_extensions,
                 self.commit_time,
+                self.wire_format,
             )
             .await?;
 
         self.group.pending_commit = pending_commit.try_into()?;
+        self.group.intent_log.set_pending(self.intents);
 
         Ok(output)
     }
@@ -396,9 +519,12 @@ where
                 self.new_signing_identity,
                 self.new_leaf_node_extensions,
                 self.commit_time,
+                self.wire_format,
             )
             .await?;
 
+        self.group.intent_log.set_pending(self.intents);
+
         Ok((
             output,
             CommitSecrets(PendingCommitSnapshot::PendingCommit(
@@ -406,6 +532,39 @@ where
             )),
         ))
     }
+
+    /// Create the commit without signing it, so that the signature can be
+    /// computed outside of this client, for example by an offline device.
+    ///
+    /// The content of the returned commit is signed with
+    /// [`Group::sign_commit`] and the commit is completed with
+    /// [`Group::finalize_commit`].
+    ///
+    /// # Errors
+    ///
+    /// Commits that require an update path can not be signed offline because
+    /// the new leaf node is signed while the path is generated. This function
+    /// returns [`MlsError::OfflineSigningRequiresNoPathUpdate`] for them.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn build_unsigned(self) -> Result<UnsignedCommit, MlsError> {
+        let commit = self
+            .group
+            .start_commit(
+                self.proposals,
+                None,
+                self.authenticated_data,
+                self.group_info_extensions,
+                self.new_signer,
+                self.new_signing_identity,
+                self.new_leaf_node_extensions,
+                self.commit_time,
+                self.wire_format,
+                false,
+            )
+            .await?;
+
+        self.group.unsigned_commit(commit)
+    }
 }
 
 impl<C> Group<C>
@@ -487,6 +646,9 @@ where
             new_signing_identity: Default::default(),
             new_leaf_node_extensions: Default::default(),
             commit_time: None,
+            wire_format: None,
+            intents: Default::default(),
+            intent_start: 0,
         }
     }
 
@@ -499,12 +661,71 @@ where
         proposals: Vec<Proposal>,
         external_leaf: Option<&LeafNode>,
         authenticated_data: Vec<u8>,
-        mut welcome_group_info_extensions: ExtensionList,
+        welcome_group_info_extensions: ExtensionList,
         new_signer: Option<SignatureSecretKey>,
         new_signing_identity: Option<SigningIdentity>,
         new_leaf_node_extensions: Option<ExtensionList>,
         commit_time: Option<MlsTime>,
+        wire_format: Option<WireFormat>,
     ) -> Result<(CommitOutput, PendingCommit), MlsError> {
+        let mut commit = self
+            .start_commit(
+                proposals,
+                external_leaf,
+                authenticated_data,
+                welcome_group_info_extensions,
+                new_signer,
+                new_signing_identity,
+                new_leaf_node_extensions,
+                commit_time,
+                wire_format,
+                true,
+            )
+            .await?;
+
+        let signing_context = MessageSigningContext {
+            group_context: Some(self.context()),
+            protocol_version: self.protocol_version(),
+        };
+
+        commit
+            .auth_content
+            .sign(&self.cipher_suite_provider, &self.signer, &signing_context)
+            .await?;
+
+        let mut commit = self.continue_commit(commit).await?;
+        let new_signer = commit.commit.new_signer.clone();
+
+        for group_info in commit.group_infos_mut() {
+            group_info
+                .sign(&self.cipher_suite_provider, &new_signer, &())
+                .await?;
+        }
+
+        self.finish_commit(commit).await
+    }
+
+    /// Build the content of a commit up to its signature.
+    ///
+    /// If `allow_path_update` is false, the commit must not require an update
+    /// path, since the leaf node of the path is signed while it is built.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(super) async fn start_commit(
+        &mut self,
+        proposals: Vec<Proposal>,
+        external_leaf: Option<&LeafNode>,
+        authenticated_data: Vec<u8>,
+        welcome_group_info_extensions: ExtensionList,
+        new_signer: Option<SignatureSecretKey>,
+        new_signing_identity: Option<SigningIdentity>,
+        new_leaf_node_extensions: Option<ExtensionList>,
+        commit_time: Option<MlsTime>,
+        #[cfg_attr(not(feature = "private_message"), allow(unused_variables))] wire_format: Option<
+            WireFormat,
+        >,
+        allow_path_update: bool,
+    ) -> Result<CommitInProgress, MlsError> {
         if !self.pending_commit.is_none() {
             return Err(MlsError::ExistingPendingCommit);
         }
@@ -513,6 +734,9 @@ where
             return Err(MlsError::GroupUsedAfterReInit);
         }
 
+        #[cfg(feature = "by_ref_proposal")]
+        self.load_offloaded_proposals().await?;
+
         let mls_rules = self.config.mls_rules();
 
         let is_external = external_leaf.is_some();
@@ -527,7 +751,6 @@ where
         };
 
         let new_signer = new_signer.unwrap_or_else(|| self.signer.clone());
-        let old_signer = &self.signer;
 
         #[cfg(feature = "std")]
         let time = Some(crate::time::MlsTime::now());
@@ -541,6 +764,14 @@ where
             time
         };
 
+        let source = match external_leaf {
+            Some(leaf) => CommitSource::NewMember(leaf.signing_identity.clone()),
+            None => CommitSource::ExistingMember(
+                self.roster()
+                    .member_with_index(*self.private_tree.self_index)?,
+            ),
+        };
+
         #[cfg(feature = "by_ref_proposal")]
         let proposals = self.state.proposals.prepare_commit(sender, proposals);
 
@@ -559,6 +790,7 @@ where
                 &mls_rules,
                 time,
                 CommitDirection::Send,
+                &[],
             )
             .await?;
 
@@ -582,11 +814,24 @@ where
                 &provisional_state.group_context,
                 &provisional_state.applied_proposals,
             )
+            .and_then(|options| {
+                mls_rules.adjust_commit_options(
+                    options,
+                    &source,
+                    &self.roster(),
+                    self.context(),
+                    &provisional_state.applied_proposals,
+                )
+            })
             .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
 
         let perform_path_update = commit_options.path_required
             || path_update_required(&provisional_state.applied_proposals);
 
+        if perform_path_update && !allow_path_update {
+            return Err(MlsError::OfflineSigningRequiresNoPathUpdate);
+        }
+
         let (update_path, path_secrets, commit_secret) = if perform_path_update {
             // If populating the path field: Create an UpdatePath using the new tree. Any new
             // member (from an add proposal) MUST be excluded from the resolution during the
@@ -658,31 +903,63 @@ where
             .map(|info| info.proposal.key_package.clone())
             .collect();
 
+        let cipher_suite_downgrade = if added_key_pkgs.is_empty() {
+            None
+        } else {
+            self.config
+                .cipher_suite_policy()
+                .check(self.cipher_suite())?
+        };
+
         let commit = Commit {
             proposals: provisional_state.applied_proposals.proposals_or_refs(),
             path: update_path,
         };
 
-        let mut auth_content = AuthenticatedContent::new_signed(
-            &self.cipher_suite_provider,
+        let auth_content = AuthenticatedContent::new(
             self.context(),
             sender,
             Content::Commit(Box::new(commit)),
-            old_signer,
+            authenticated_data,
             #[cfg(feature = "private_message")]
-            self.encryption_options()?.control_wire_format(sender),
+            self.control_wire_format(sender, ContentType::Commit, wire_format)?,
             #[cfg(not(feature = "private_message"))]
             WireFormat::PublicMessage,
-            authenticated_data,
-        )
-        .await?;
+        );
+
+        Ok(CommitInProgress {
+            auth_content,
+            provisional_state,
+            provisional_private_tree,
+            commit_options,
+            perform_path_update,
+            path_secrets,
+            commit_secret,
+            psk_secret,
+            #[cfg(feature = "psk")]
+            psks,
+            added_key_pkgs,
+            cipher_suite_downgrade,
+            new_signer,
+            welcome_group_info_extensions,
+        })
+    }
+
+    /// Advance the key schedule of a commit with a signed content and build
+    /// its group info messages, which are left unsigned.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(super) async fn continue_commit(
+        &self,
+        mut commit: CommitInProgress,
+    ) -> Result<SignedCommitInProgress, MlsError> {
+        let provisional_state = &mut commit.provisional_state;
 
         // Use the signature, the commit_secret and the psk_secret to advance the key schedule and
         // compute the confirmation_tag value in the MlsPlaintext.
         let confirmed_transcript_hash = super::transcript_hash::create(
             self.cipher_suite_provider(),
             &self.state.interim_transcript_hash,
-            &auth_content,
+            &commit.auth_content,
         )
         .await?;
 
@@ -690,11 +967,11 @@ where
 
         let key_schedule_result = KeySchedule::from_key_schedule(
             &self.key_schedule,
-            &commit_secret,
+            &commit.commit_secret,
             &provisional_state.group_context,
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
             provisional_state.public_tree.total_leaf_count(),
-            &psk_secret,
+            &commit.psk_secret,
             &self.cipher_suite_provider,
         )
         .await?;
@@ -713,16 +990,18 @@ where
         )
         .await?;
 
-        auth_content.auth.confirmation_tag = Some(confirmation_tag.clone());
+        commit.auth_content.auth.confirmation_tag = Some(confirmation_tag.clone());
 
-        let ratchet_tree_ext = commit_options
-            .ratchet_tree_extension
-            .then(|| RatchetTreeExt {
-                tree_data: ExportedTree::new(provisional_state.public_tree.nodes.clone()),
-            });
+        let ratchet_tree_ext =
+            commit
+                .commit_options
+                .ratchet_tree_extension
+                .then(|| RatchetTreeExt {
+                    tree_data: ExportedTree::new(provisional_state.public_tree.nodes.clone()),
+                });
 
         // Generate external commit group info if required by commit_options
-        let external_commit_group_info = match commit_options.allow_external_commit {
+        let external_commit_group_info = match commit.commit_options.allow_external_commit {
             true => {
                 let mut extensions = ExtensionList::new();
 
@@ -734,42 +1013,80 @@ where
                 })?;
 
                 if let Some(ref ratchet_tree_ext) = ratchet_tree_ext {
-                    if !commit_options.always_out_of_band_ratchet_tree {
+                    if !commit.commit_options.always_out_of_band_ratchet_tree {
                         extensions.set_from(ratchet_tree_ext.clone())?;
                     }
                 }
 
-                let info = self
-                    .make_group_info(
-                        &provisional_state.group_context,
-                        extensions,
-                        &confirmation_tag,
-                        &new_signer,
-                    )
-                    .await?;
-
-                let msg =
-                    MlsMessage::new(self.protocol_version(), MlsMessagePayload::GroupInfo(info));
-
-                Some(msg)
+                Some(self.make_group_info(
+                    &provisional_state.group_context,
+                    extensions,
+                    &confirmation_tag,
+                )?)
             }
             false => None,
         };
 
         // Build the group info that will be placed into the welcome messages.
         // Add the ratchet tree extension if necessary
+        let mut welcome_group_info_extensions =
+            core::mem::take(&mut commit.welcome_group_info_extensions);
+
         if let Some(ratchet_tree_ext) = ratchet_tree_ext {
             welcome_group_info_extensions.set_from(ratchet_tree_ext)?;
         }
 
-        let welcome_group_info = self
-            .make_group_info(
-                &provisional_state.group_context,
-                welcome_group_info_extensions,
-                &confirmation_tag,
-                &new_signer,
-            )
-            .await?;
+        let welcome_group_info = self.make_group_info(
+            &provisional_state.group_context,
+            welcome_group_info_extensions,
+            &confirmation_tag,
+        )?;
+
+        Ok(SignedCommitInProgress {
+            commit,
+            key_schedule_result,
+            interim_transcript_hash,
+            confirmation_tag,
+            external_commit_group_info,
+            welcome_group_info,
+        })
+    }
+
+    /// Build the messages of a commit whose content and group info messages
+    /// are signed.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(super) async fn finish_commit(
+        &mut self,
+        commit: SignedCommitInProgress,
+    ) -> Result<(CommitOutput, PendingCommit), MlsError> {
+        let SignedCommitInProgress {
+            commit,
+            key_schedule_result,
+            interim_transcript_hash,
+            confirmation_tag,
+            external_commit_group_info,
+            welcome_group_info,
+        } = commit;
+
+        let CommitInProgress {
+            auth_content,
+            provisional_state,
+            provisional_private_tree,
+            commit_options,
+            perform_path_update,
+            path_secrets,
+            psk_secret,
+            #[cfg(feature = "psk")]
+            psks,
+            added_key_pkgs,
+            cipher_suite_downgrade,
+            new_signer,
+            ..
+        } = commit;
+
+        let external_commit_group_info = external_commit_group_info.map(|info| {
+            MlsMessage::new(self.protocol_version(), MlsMessagePayload::GroupInfo(info))
+        });
 
         // Encrypt the GroupInfo using the key and nonce derived from the joiner_secret for
         // the new epoch
@@ -851,11 +1168,29 @@ where
             .reinitializations
             .first();
 
+        let commit_message_hash =
+            MessageHash::compute(&self.cipher_suite_provider, &commit_message).await?;
+
         let pending_commit = PendingCommit {
             output: CommitMessageDescription {
                 is_external: matches!(auth_content.content.sender, Sender::NewMemberCommit),
                 authenticated_data: auth_content.content.authenticated_data,
                 committer: *provisional_private_tree.self_index,
+                filtered_proposals: provisional_state.filtered_proposals.clone(),
+                unknown_types: Vec::new(),
+                confirmed_transcript_hash: provisional_state
+                    .group_context
+                    .confirmed_transcript_hash
+                    .to_vec(),
+                interim_transcript_hash: interim_transcript_hash.to_vec(),
+                app_versions_change: app_versions_change(
+                    &self.state.public_tree,
+                    &provisional_state.public_tree,
+                ),
+                feature_flags_change: feature_flags_change(
+                    &self.state.context.extensions,
+                    &provisional_state.group_context.extensions,
+                ),
                 effect: match pending_reinit {
                     Some(r) => CommitEffect::ReInit(r.clone()),
                     None => CommitEffect::NewEpoch(
@@ -877,8 +1212,7 @@ where
                 confirmation_tag,
             },
 
-            commit_message_hash: MessageHash::compute(&self.cipher_suite_provider, &commit_message)
-                .await?,
+            commit_message_hash: commit_message_hash.clone(),
             signer: new_signer,
             epoch_secrets: key_schedule_result.epoch_secrets,
             key_schedule: key_schedule_result.key_schedule,
@@ -892,22 +1226,23 @@ where
             ratchet_tree,
             external_commit_group_info,
             contains_update_path: perform_path_update,
+            cipher_suite_downgrade,
             #[cfg(feature = "by_ref_proposal")]
             unused_proposals: provisional_state.unused_proposals,
+            attempt_id: commit_message_hash.into(),
         };
 
         Ok((output, pending_commit))
     }
 
     // Construct a GroupInfo reflecting the new state
-    // Group ID, epoch, tree, and confirmed transcript hash from the new state
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn make_group_info(
+    // Group ID, epoch, tree, and confirmed transcript hash from the new state.
+    // The GroupInfo is signed by the caller.
+    fn make_group_info(
         &self,
         group_context: &GroupContext,
         extensions: ExtensionList,
         confirmation_tag: &ConfirmationTag,
-        signer: &SignatureSecretKey,
     ) -> Result<GroupInfo, MlsError> {
         let mut group_info = GroupInfo {
             group_context: group_context.clone(),
@@ -919,11 +1254,6 @@ where
 
         group_info.grease(self.cipher_suite_provider())?;
 
-        // Sign the GroupInfo using the member's private signing key
-        group_info
-            .sign(&self.cipher_suite_provider, signer, &())
-            .await?;
-
         Ok(group_info)
     }
 
@@ -1079,6 +1409,35 @@ mod tests {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_attempt_id_is_persisted_with_pending_commit() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        assert_eq!(group.pending_commit_attempt_id().unwrap(), None);
+
+        let output = group.commit(vec![]).await.unwrap();
+
+        let expected: CommitAttemptId =
+            MessageHash::compute(&group.cipher_suite_provider, &output.commit_message)
+                .await
+                .unwrap()
+                .into();
+
+        assert_eq!(output.attempt_id, expected);
+
+        let restored = Group::from_snapshot(group.config.clone(), group.snapshot().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            restored.pending_commit_attempt_id().unwrap(),
+            Some(output.attempt_id)
+        );
+
+        group.clear_pending_commit();
+        assert_eq!(group.pending_commit_attempt_id().unwrap(), None);
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_commit_builder_add() {
         let mut group = test_commit_builder_group().await;
@@ -1784,4 +2143,88 @@ mod tests {
         group.apply_detached_commit(secrets).await.unwrap();
         assert_eq!(group.context().epoch, 1);
     }
+
+    #[derive(Clone, Debug)]
+    struct AddsRequirePath;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl MlsRules for AddsRequirePath {
+        type Error = core::convert::Infallible;
+
+        async fn filter_proposals(
+            &self,
+            _: CommitDirection,
+            _: CommitSource,
+            _: &crate::group::Roster,
+            _: &GroupContext,
+            proposals: crate::mls_rules::ProposalBundle,
+        ) -> Result<crate::mls_rules::ProposalBundle, Self::Error> {
+            Ok(proposals)
+        }
+
+        fn commit_options(
+            &self,
+            _: &crate::group::Roster,
+            _: &GroupContext,
+            _: &crate::mls_rules::ProposalBundle,
+        ) -> Result<CommitOptions, Self::Error> {
+            Ok(CommitOptions::new())
+        }
+
+        fn adjust_commit_options(
+            &self,
+            options: CommitOptions,
+            source: &CommitSource,
+            current_roster: &crate::group::Roster,
+            _: &GroupContext,
+            proposals: &crate::mls_rules::ProposalBundle,
+        ) -> Result<CommitOptions, Self::Error> {
+            let adds = !proposals.add_proposals().is_empty();
+            let by_member = matches!(source, CommitSource::ExistingMember(_));
+
+            Ok(options
+                .with_path_required(adds && by_member)
+                .with_allow_external_commit(current_roster.members_iter().count() > 1))
+        }
+
+        fn encryption_options(
+            &self,
+            _: &crate::group::Roster,
+            _: &GroupContext,
+        ) -> Result<crate::mls_rules::EncryptionOptions, Self::Error> {
+            Ok(Default::default())
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn mls_rules_can_adjust_commit_options_per_commit() {
+        let mut group = crate::client_builder::test_utils::TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .mls_rules(AddsRequirePath)
+            .build()
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let output = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert!(output.contains_update_path);
+        assert!(output.external_commit_group_info.is_none());
+
+        group.apply_pending_commit().await.unwrap();
+
+        let output = group.commit(vec![]).await.unwrap();
+        assert!(output.external_commit_group_info.is_some());
+    }
 }
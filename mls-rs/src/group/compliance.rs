@@ -0,0 +1,275 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Runtime self-test against the RFC 9420 test vectors published at
+//! <https://github.com/mlswg/mls-implementations/tree/main/test-vectors>.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use core::fmt::Display;
+
+use mls_rs_core::crypto::{CipherSuite, CryptoProvider};
+use serde::de::DeserializeOwned;
+
+use crate::client::MlsError;
+
+mod deserialization;
+mod key_schedule;
+mod message_protection;
+mod passive_client;
+mod tree_validation;
+mod welcome;
+
+/// Category of test vectors, named after the corresponding file of the
+/// `mls-implementations` repository.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ComplianceCategory {
+    /// `deserialization.json`
+    Deserialization,
+    /// `key-schedule.json`
+    KeySchedule,
+    /// `message-protection.json`
+    MessageProtection,
+    /// `tree-validation.json`
+    TreeValidation,
+    /// `welcome.json`
+    Welcome,
+    /// `passive-client-welcome.json`, `passive-client-handle-commit.json`
+    /// and `passive-client-random.json`
+    PassiveClient,
+}
+
+/// Outcome of a single test case.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "reason")]
+pub enum CaseOutcome {
+    Passed,
+    /// The case was run and a check did not hold.
+    Failed(String),
+    /// The case was not run, e.g. because the crypto provider does not
+    /// support its cipher suite.
+    Skipped(String),
+}
+
+/// Result of a single test case of a [`ComplianceReport`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[non_exhaustive]
+pub struct CaseResult {
+    pub category: ComplianceCategory,
+    /// Index of the case within the test vector file.
+    pub index: usize,
+    /// Cipher suite of the case, if the category is cipher suite specific.
+    pub cipher_suite: Option<u16>,
+    pub outcome: CaseOutcome,
+}
+
+/// Machine readable report produced by a [`ComplianceRunner`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[non_exhaustive]
+pub struct ComplianceReport {
+    /// Version of this library.
+    pub library_version: String,
+    /// Cipher suites supported by the crypto provider under test.
+    pub cipher_suites: Vec<u16>,
+    pub results: Vec<CaseResult>,
+}
+
+impl ComplianceReport {
+    /// Returns `true` if no case failed. Skipped cases are not failures.
+    pub fn is_compliant(&self) -> bool {
+        !self
+            .results
+            .iter()
+            .any(|r| matches!(r.outcome, CaseOutcome::Failed(_)))
+    }
+
+    /// Results of the cases that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &CaseResult> {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, CaseOutcome::Failed(_)))
+    }
+
+    /// Serialize the report as JSON.
+    pub fn to_json(&self) -> Result<String, MlsError> {
+        serde_json::to_string(self).map_err(|e| MlsError::InvalidComplianceData(e.to_string()))
+    }
+}
+
+/// Runs the RFC 9420 test vectors against a [`CryptoProvider`].
+///
+/// The test vectors are not bundled with this library. Each file is
+/// supplied as a JSON string with [`ComplianceRunner::run`], and results
+/// accumulate in a [`ComplianceReport`]. Cases using a cipher suite not
+/// supported by the provider are reported as skipped.
+#[derive(Clone, Debug)]
+pub struct ComplianceRunner<P> {
+    crypto_provider: P,
+    report: ComplianceReport,
+}
+
+impl<P> ComplianceRunner<P>
+where
+    P: CryptoProvider + Clone,
+{
+    pub fn new(crypto_provider: P) -> Self {
+        let cipher_suites = crypto_provider
+            .supported_cipher_suites()
+            .into_iter()
+            .map(u16::from)
+            .collect();
+
+        let report = ComplianceReport {
+            library_version: env!("CARGO_PKG_VERSION").to_string(),
+            cipher_suites,
+            results: Vec::new(),
+        };
+
+        Self {
+            crypto_provider,
+            report,
+        }
+    }
+
+    /// Run all cases of the test vector file `json` of the given category.
+    ///
+    /// An error is returned only if `json` can not be parsed. Failing cases
+    /// are recorded in the report.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn run(&mut self, category: ComplianceCategory, json: &str) -> Result<(), MlsError> {
+        let results = match category {
+            ComplianceCategory::Deserialization => deserialization::run(json)?,
+            ComplianceCategory::KeySchedule => {
+                key_schedule::run(&self.crypto_provider, json).await?
+            }
+            ComplianceCategory::MessageProtection => {
+                message_protection::run(&self.crypto_provider, json).await?
+            }
+            ComplianceCategory::TreeValidation => {
+                tree_validation::run(&self.crypto_provider, json).await?
+            }
+            ComplianceCategory::Welcome => welcome::run(&self.crypto_provider, json).await?,
+            ComplianceCategory::PassiveClient => {
+                passive_client::run(&self.crypto_provider, json).await?
+            }
+        };
+
+        self.report.results.extend(results);
+
+        Ok(())
+    }
+
+    pub fn report(&self) -> &ComplianceReport {
+        &self.report
+    }
+
+    pub fn into_report(self) -> ComplianceReport {
+        self.report
+    }
+}
+
+fn parse<T: DeserializeOwned>(json: &str) -> Result<Vec<T>, MlsError> {
+    serde_json::from_str(json).map_err(|e| MlsError::InvalidComplianceData(e.to_string()))
+}
+
+fn cipher_suite_provider<P: CryptoProvider>(
+    crypto_provider: &P,
+    cipher_suite: u16,
+) -> Result<P::CipherSuiteProvider, CaseOutcome> {
+    crypto_provider
+        .cipher_suite_provider(CipherSuite::from(cipher_suite))
+        .ok_or_else(|| CaseOutcome::Skipped(format!("unsupported cipher suite {cipher_suite}")))
+}
+
+fn case_result(
+    category: ComplianceCategory,
+    index: usize,
+    cipher_suite: Option<u16>,
+    res: Result<(), String>,
+) -> CaseResult {
+    let outcome = match res {
+        Ok(()) => CaseOutcome::Passed,
+        Err(reason) => CaseOutcome::Failed(reason),
+    };
+
+    CaseResult {
+        category,
+        index,
+        cipher_suite,
+        outcome,
+    }
+}
+
+fn skipped(
+    category: ComplianceCategory,
+    index: usize,
+    cipher_suite: u16,
+    outcome: CaseOutcome,
+) -> CaseResult {
+    CaseResult {
+        category,
+        index,
+        cipher_suite: Some(cipher_suite),
+        outcome,
+    }
+}
+
+fn ensure(condition: bool, what: &str) -> Result<(), String> {
+    condition
+        .then_some(())
+        .ok_or_else(|| format!("{what} mismatch"))
+}
+
+fn failed<E: Display>(what: &'static str) -> impl FnOnce(E) -> String {
+    move |e| format!("{what}: {e}")
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{client::MlsError, crypto::test_utils::TestCryptoProvider};
+
+    use super::{CaseOutcome, ComplianceCategory, ComplianceRunner};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn deserialization_vectors_are_reported() {
+        let mut runner = ComplianceRunner::new(TestCryptoProvider::new());
+
+        let json = r#"[
+            {"vlbytes_header": "00", "length": 0},
+            {"vlbytes_header": "3f", "length": 63},
+            {"vlbytes_header": "4040", "length": 64},
+            {"vlbytes_header": "4041", "length": 64}
+        ]"#;
+
+        runner
+            .run(ComplianceCategory::Deserialization, json)
+            .await
+            .unwrap();
+
+        let report = runner.into_report();
+        assert!(!report.is_compliant());
+        assert_eq!(report.failures().count(), 1);
+        assert_eq!(report.failures().next().unwrap().index, 3);
+        assert_eq!(report.results[0].outcome, CaseOutcome::Passed);
+        assert!(report.to_json().unwrap().contains("\"deserialization\""));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn malformed_vectors_are_rejected() {
+        let mut runner = ComplianceRunner::new(TestCryptoProvider::new());
+
+        let res = runner.run(ComplianceCategory::KeySchedule, "{}").await;
+
+        assert_matches!(res, Err(MlsError::InvalidComplianceData(_)));
+        assert!(runner.report().results.is_empty());
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{string::String, vec::Vec};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, VarInt};
+
+use crate::client::MlsError;
+
+use super::{case_result, ensure, failed, parse, CaseResult, ComplianceCategory};
+
+const CATEGORY: ComplianceCategory = ComplianceCategory::Deserialization;
+
+#[derive(serde::Deserialize)]
+struct TestCase {
+    #[serde(with = "hex::serde")]
+    vlbytes_header: Vec<u8>,
+    length: u32,
+}
+
+pub(super) fn run(json: &str) -> Result<Vec<CaseResult>, MlsError> {
+    let results = parse::<TestCase>(json)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, case)| case_result(CATEGORY, index, None, run_case(case)))
+        .collect();
+
+    Ok(results)
+}
+
+fn run_case(case: TestCase) -> Result<(), String> {
+    let reader = &mut &*case.vlbytes_header;
+    let length = VarInt::mls_decode(reader).map_err(failed("decoding header"))?;
+
+    ensure(reader.is_empty(), "header size")?;
+    ensure(u32::from(length) == case.length, "length")?;
+
+    let encoded = length
+        .mls_encode_to_vec()
+        .map_err(failed("encoding header"))?;
+
+    ensure(encoded == case.vlbytes_header, "encoded header")
+}
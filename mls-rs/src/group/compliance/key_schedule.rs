@@ -0,0 +1,239 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{string::String, vec::Vec};
+
+use mls_rs_codec::MlsEncode;
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, CryptoProvider},
+    extension::ExtensionList,
+    protocol_version::ProtocolVersion,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    group::{
+        key_schedule::{get_welcome_secret, KeySchedule},
+        secret_tree::SecretTree,
+        GroupContext,
+    },
+    psk::secret::PskSecret,
+    tree_kem::path_secret::PathSecret,
+};
+
+use super::{
+    case_result, cipher_suite_provider, ensure, failed, parse, skipped, CaseResult,
+    ComplianceCategory,
+};
+
+const CATEGORY: ComplianceCategory = ComplianceCategory::KeySchedule;
+
+// The secret tree size does not affect the root encryption secret.
+const SECRET_TREE_SIZE: u32 = 32;
+
+#[derive(serde::Deserialize)]
+struct TestCase {
+    cipher_suite: u16,
+    #[serde(with = "hex::serde")]
+    group_id: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    initial_init_secret: Vec<u8>,
+    epochs: Vec<Epoch>,
+}
+
+#[derive(serde::Deserialize)]
+struct Epoch {
+    #[serde(with = "hex::serde")]
+    commit_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    psk_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    confirmed_transcript_hash: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    tree_hash: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    group_context: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    joiner_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    welcome_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    init_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    sender_data_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    encryption_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    exporter_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    epoch_authenticator: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    external_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    confirmation_key: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    membership_key: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    resumption_psk: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    external_pub: Vec<u8>,
+    exporter: Exporter,
+}
+
+#[derive(serde::Deserialize)]
+struct Exporter {
+    label: String,
+    #[serde(with = "hex::serde")]
+    context: Vec<u8>,
+    length: usize,
+    #[serde(with = "hex::serde")]
+    secret: Vec<u8>,
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(super) async fn run<P: CryptoProvider>(
+    crypto_provider: &P,
+    json: &str,
+) -> Result<Vec<CaseResult>, MlsError> {
+    let mut results = Vec::new();
+
+    for (index, case) in parse::<TestCase>(json)?.into_iter().enumerate() {
+        let cipher_suite = case.cipher_suite;
+
+        match cipher_suite_provider(crypto_provider, cipher_suite) {
+            Ok(cs) => {
+                let res = run_case(&cs, case).await;
+                results.push(case_result(CATEGORY, index, Some(cipher_suite), res));
+            }
+            Err(outcome) => results.push(skipped(CATEGORY, index, cipher_suite, outcome)),
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn run_case<P: CipherSuiteProvider>(cs: &P, case: TestCase) -> Result<(), String> {
+    let mut key_schedule = KeySchedule::from_init_secret(case.initial_init_secret);
+
+    for (epoch_id, epoch) in case.epochs.into_iter().enumerate() {
+        let context = GroupContext {
+            protocol_version: ProtocolVersion::MLS_10,
+            cipher_suite: cs.cipher_suite(),
+            group_id: case.group_id.clone(),
+            epoch: epoch_id as u64,
+            tree_hash: epoch.tree_hash,
+            confirmed_transcript_hash: epoch.confirmed_transcript_hash.into(),
+            extensions: ExtensionList::new(),
+        };
+
+        let encoded_context = context
+            .mls_encode_to_vec()
+            .map_err(failed("encoding group context"))?;
+
+        ensure(encoded_context == epoch.group_context, "group_context")?;
+
+        let psk = PskSecret::from(epoch.psk_secret);
+        let commit = PathSecret::from(epoch.commit_secret);
+
+        let res = KeySchedule::from_key_schedule(
+            &key_schedule,
+            &commit,
+            &context,
+            SECRET_TREE_SIZE,
+            &psk,
+            cs,
+        )
+        .await
+        .map_err(failed("deriving key schedule"))?;
+
+        key_schedule = res.key_schedule;
+
+        ensure(
+            res.joiner_secret.as_bytes() == epoch.joiner_secret,
+            "joiner_secret",
+        )?;
+
+        let welcome_secret = get_welcome_secret(cs, &res.joiner_secret, &psk)
+            .await
+            .map_err(failed("deriving welcome secret"))?;
+
+        ensure(
+            welcome_secret.as_bytes() == Some(&*epoch.welcome_secret),
+            "welcome_secret",
+        )?;
+
+        ensure(
+            key_schedule.init_secret_bytes() == epoch.init_secret,
+            "init_secret",
+        )?;
+
+        ensure(
+            res.epoch_secrets.sender_data_secret.as_ref() == epoch.sender_data_secret,
+            "sender_data_secret",
+        )?;
+
+        let expected_tree =
+            SecretTree::new(SECRET_TREE_SIZE, Zeroizing::new(epoch.encryption_secret));
+
+        ensure(
+            res.epoch_secrets.secret_tree == expected_tree,
+            "encryption_secret",
+        )?;
+
+        ensure(
+            key_schedule.exporter_secret_bytes() == epoch.exporter_secret,
+            "exporter_secret",
+        )?;
+
+        ensure(
+            *key_schedule.authentication_secret == epoch.epoch_authenticator,
+            "epoch_authenticator",
+        )?;
+
+        ensure(
+            key_schedule.external_secret_bytes() == epoch.external_secret,
+            "external_secret",
+        )?;
+
+        ensure(
+            *res.confirmation_key == epoch.confirmation_key,
+            "confirmation_key",
+        )?;
+
+        ensure(
+            *key_schedule.membership_key == epoch.membership_key,
+            "membership_key",
+        )?;
+
+        ensure(
+            res.epoch_secrets.resumption_secret.raw_value() == epoch.resumption_psk,
+            "resumption_psk",
+        )?;
+
+        let (_, external_pub) = key_schedule
+            .get_external_key_pair(cs)
+            .await
+            .map_err(failed("deriving external key pair"))?;
+
+        ensure(*external_pub == epoch.external_pub, "external_pub")?;
+
+        let exporter = epoch.exporter;
+
+        let exported = key_schedule
+            .export_secret(
+                exporter.label.as_bytes(),
+                &exporter.context,
+                exporter.length,
+                cs,
+            )
+            .await
+            .map_err(failed("exporting secret"))?;
+
+        ensure(*exported == exporter.secret, "exporter.secret")?;
+    }
+
+    Ok(())
+}
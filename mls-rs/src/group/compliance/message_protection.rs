@@ -0,0 +1,248 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use mls_rs_codec::MlsDecode;
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, CryptoProvider, SignaturePublicKey},
+    extension::ExtensionList,
+    protocol_version::ProtocolVersion,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    group::{
+        ciphertext_processor::{CiphertextProcessor, GroupStateProvider},
+        epoch::EpochSecrets,
+        framing::{Content, MlsMessage, MlsMessagePayload, PrivateMessage},
+        message_signature::AuthenticatedContent,
+        message_verifier::{
+            verify_auth_content_signature, verify_plaintext_authentication,
+            SignaturePublicKeysContainer,
+        },
+        proposal::Proposal,
+        secret_tree::SecretTree,
+        Commit, GroupContext,
+    },
+    tree_kem::node::LeafIndex,
+};
+
+use super::{
+    case_result, cipher_suite_provider, ensure, failed, parse, skipped, CaseResult,
+    ComplianceCategory,
+};
+
+const CATEGORY: ComplianceCategory = ComplianceCategory::MessageProtection;
+
+// Test vectors use a group of two members where the sender is at leaf 1.
+const N_LEAVES: u32 = 2;
+
+#[derive(serde::Deserialize)]
+struct TestCase {
+    cipher_suite: u16,
+    #[serde(with = "hex::serde")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    #[serde(with = "hex::serde")]
+    tree_hash: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    confirmed_transcript_hash: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    signature_pub: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    encryption_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    sender_data_secret: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    membership_key: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    proposal: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    proposal_priv: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    proposal_pub: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    commit: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    commit_priv: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    commit_pub: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    application: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    application_priv: Vec<u8>,
+}
+
+/// State of the receiving member at leaf 0.
+struct Receiver {
+    context: GroupContext,
+    secrets: EpochSecrets,
+    signature_keys: Vec<Option<SignaturePublicKey>>,
+    membership_key: Vec<u8>,
+}
+
+impl GroupStateProvider for Receiver {
+    fn group_context(&self) -> &GroupContext {
+        &self.context
+    }
+
+    fn self_index(&self) -> LeafIndex {
+        LeafIndex::unchecked(0)
+    }
+
+    fn epoch_secrets_mut(&mut self) -> &mut EpochSecrets {
+        &mut self.secrets
+    }
+
+    fn epoch_secrets(&self) -> &EpochSecrets {
+        &self.secrets
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(super) async fn run<P: CryptoProvider>(
+    crypto_provider: &P,
+    json: &str,
+) -> Result<Vec<CaseResult>, MlsError> {
+    let mut results = Vec::new();
+
+    for (index, case) in parse::<TestCase>(json)?.into_iter().enumerate() {
+        let cipher_suite = case.cipher_suite;
+
+        match cipher_suite_provider(crypto_provider, cipher_suite) {
+            Ok(cs) => {
+                let res = run_case(&cs, case).await;
+                results.push(case_result(CATEGORY, index, Some(cipher_suite), res));
+            }
+            Err(outcome) => results.push(skipped(CATEGORY, index, cipher_suite, outcome)),
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn run_case<P: CipherSuiteProvider + Clone>(cs: &P, case: TestCase) -> Result<(), String> {
+    let proposal = Proposal::mls_decode(&mut &*case.proposal).map_err(failed("proposal"))?;
+    let commit = Commit::mls_decode(&mut &*case.commit).map_err(failed("commit"))?;
+
+    for (message, name) in [
+        (&case.proposal_pub, "proposal_pub"),
+        (&case.proposal_priv, "proposal_priv"),
+    ] {
+        match process_message(cs, &case, message)
+            .await
+            .map_err(failed(name))?
+        {
+            Content::Proposal(p) => ensure(*p == proposal, name)?,
+            _ => return Err(format!("{name}: content is not a proposal")),
+        }
+    }
+
+    for (message, name) in [
+        (&case.commit_pub, "commit_pub"),
+        (&case.commit_priv, "commit_priv"),
+    ] {
+        match process_message(cs, &case, message)
+            .await
+            .map_err(failed(name))?
+        {
+            Content::Commit(c) => ensure(*c == commit, name)?,
+            _ => return Err(format!("{name}: content is not a commit")),
+        }
+    }
+
+    match process_message(cs, &case, &case.application_priv)
+        .await
+        .map_err(failed("application_priv"))?
+    {
+        Content::Application(data) => ensure(*data == case.application, "application_priv"),
+        _ => Err("application_priv: content is not application data".into()),
+    }
+}
+
+fn receiver<P: CipherSuiteProvider>(cs: &P, case: &TestCase) -> Receiver {
+    let context = GroupContext {
+        protocol_version: ProtocolVersion::MLS_10,
+        cipher_suite: cs.cipher_suite(),
+        group_id: case.group_id.clone(),
+        epoch: case.epoch,
+        tree_hash: case.tree_hash.clone(),
+        confirmed_transcript_hash: case.confirmed_transcript_hash.clone().into(),
+        extensions: ExtensionList::new(),
+    };
+
+    let secrets = EpochSecrets {
+        resumption_secret: vec![0u8; cs.kdf_extract_size()].into(),
+        sender_data_secret: case.sender_data_secret.clone().into(),
+        secret_tree: SecretTree::new(N_LEAVES, Zeroizing::new(case.encryption_secret.clone())),
+        #[cfg(feature = "secret_tree_recovery")]
+        encryption_secret: case.encryption_secret.clone().into(),
+    };
+
+    Receiver {
+        context,
+        secrets,
+        signature_keys: vec![None, Some(case.signature_pub.clone().into())],
+        membership_key: case.membership_key.clone(),
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn process_message<P: CipherSuiteProvider + Clone>(
+    cs: &P,
+    case: &TestCase,
+    message: &[u8],
+) -> Result<Content, MlsError> {
+    let mut receiver = receiver(cs, case);
+    let message = MlsMessage::mls_decode(&mut &*message)?;
+
+    let auth_content = match message.payload {
+        MlsMessagePayload::Plain(plaintext) => {
+            verify_plaintext_authentication(
+                cs,
+                plaintext,
+                Some(receiver.membership_key.as_slice()),
+                &receiver.context,
+                SignaturePublicKeysContainer::List(&receiver.signature_keys),
+            )
+            .await?
+        }
+        MlsMessagePayload::Cipher(ciphertext) => {
+            open_ciphertext(cs, &mut receiver, &ciphertext).await?
+        }
+        _ => return Err(MlsError::UnexpectedMessageType),
+    };
+
+    Ok(auth_content.content.content)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn open_ciphertext<P: CipherSuiteProvider + Clone>(
+    cs: &P,
+    receiver: &mut Receiver,
+    ciphertext: &PrivateMessage,
+) -> Result<AuthenticatedContent, MlsError> {
+    let auth_content = CiphertextProcessor::new(receiver, cs.clone())
+        .open(ciphertext)
+        .await?;
+
+    verify_auth_content_signature(
+        cs,
+        SignaturePublicKeysContainer::List(&receiver.signature_keys),
+        &receiver.context,
+        &auth_content,
+        #[cfg(feature = "by_ref_proposal")]
+        &[],
+    )
+    .await?;
+
+    Ok(auth_content)
+}
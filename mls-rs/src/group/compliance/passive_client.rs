@@ -0,0 +1,187 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{format, string::String, vec::Vec};
+
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, CryptoProvider},
+    psk::ExternalPskId,
+    time::MlsTime,
+};
+
+use crate::{
+    client::MlsError,
+    client_builder::ClientBuilder,
+    client_config::ClientConfig,
+    group::{ExportedTree, MlsMessage},
+    identity::basic::BasicIdentityProvider,
+    key_package::KeyPackageGeneration,
+};
+
+use super::{
+    case_result, cipher_suite_provider, ensure, failed, parse, skipped, CaseResult,
+    ComplianceCategory,
+};
+
+const CATEGORY: ComplianceCategory = ComplianceCategory::PassiveClient;
+
+#[derive(serde::Deserialize)]
+struct TestCase {
+    cipher_suite: u16,
+    external_psks: Vec<ExternalPsk>,
+    #[serde(with = "hex::serde")]
+    key_package: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    signature_priv: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    encryption_priv: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    init_priv: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    welcome: Vec<u8>,
+    ratchet_tree: Option<HexBytes>,
+    #[serde(with = "hex::serde")]
+    initial_epoch_authenticator: Vec<u8>,
+    epochs: Vec<Epoch>,
+}
+
+#[derive(serde::Deserialize)]
+struct ExternalPsk {
+    #[serde(with = "hex::serde")]
+    psk_id: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    psk: Vec<u8>,
+}
+
+#[derive(serde::Deserialize)]
+struct Epoch {
+    proposals: Vec<HexBytes>,
+    #[serde(with = "hex::serde")]
+    commit: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    epoch_authenticator: Vec<u8>,
+}
+
+#[derive(serde::Deserialize)]
+struct HexBytes(#[serde(with = "hex::serde")] Vec<u8>);
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(super) async fn run<P: CryptoProvider + Clone>(
+    crypto_provider: &P,
+    json: &str,
+) -> Result<Vec<CaseResult>, MlsError> {
+    let mut results = Vec::new();
+
+    for (index, case) in parse::<TestCase>(json)?.into_iter().enumerate() {
+        let cipher_suite = case.cipher_suite;
+
+        match cipher_suite_provider(crypto_provider, cipher_suite) {
+            Ok(cs) => {
+                let res = run_case(crypto_provider, &cs, case).await;
+                results.push(case_result(CATEGORY, index, Some(cipher_suite), res));
+            }
+            Err(outcome) => results.push(skipped(CATEGORY, index, cipher_suite, outcome)),
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn run_case<P, CS>(crypto_provider: &P, cs: &CS, case: TestCase) -> Result<(), String>
+where
+    P: CryptoProvider + Clone,
+    CS: CipherSuiteProvider,
+{
+    let key_package = MlsMessage::from_bytes(&case.key_package)
+        .map_err(failed("decoding key_package"))?
+        .into_key_package()
+        .ok_or("key_package: message is not a key package")?;
+
+    let signing_identity = key_package.leaf_node.signing_identity.clone();
+
+    let mut builder = ClientBuilder::new()
+        .crypto_provider(crypto_provider.clone())
+        .identity_provider(BasicIdentityProvider::new());
+
+    for psk in case.external_psks {
+        builder = builder.psk(ExternalPskId::new(psk.psk_id), psk.psk.into());
+    }
+
+    let client = builder
+        .signing_identity(
+            signing_identity,
+            case.signature_priv.into(),
+            cs.cipher_suite(),
+        )
+        .build();
+
+    let key_package_generation = KeyPackageGeneration {
+        reference: key_package
+            .to_reference(cs)
+            .await
+            .map_err(failed("computing key package reference"))?,
+        key_package,
+        init_secret_key: case.init_priv.into(),
+        leaf_node_secret_key: case.encryption_priv.into(),
+    };
+
+    let (id, data) = key_package_generation
+        .to_storage()
+        .map_err(failed("storing key package"))?;
+
+    client.config.key_package_repo().insert(id, data);
+
+    let welcome = MlsMessage::from_bytes(&case.welcome).map_err(failed("decoding welcome"))?;
+
+    let tree = case
+        .ratchet_tree
+        .map(|tree| ExportedTree::from_bytes(&tree.0))
+        .transpose()
+        .map_err(failed("decoding ratchet_tree"))?;
+
+    let (mut group, _) = client
+        .join_group(tree, &welcome, None)
+        .await
+        .map_err(failed("joining group"))?;
+
+    let epoch_authenticator = group
+        .epoch_authenticator()
+        .map_err(failed("epoch authenticator"))?;
+
+    ensure(
+        *epoch_authenticator == case.initial_epoch_authenticator,
+        "initial_epoch_authenticator",
+    )?;
+
+    for (i, epoch) in case.epochs.into_iter().enumerate() {
+        for proposal in epoch.proposals {
+            let message =
+                MlsMessage::from_bytes(&proposal.0).map_err(failed("decoding proposal"))?;
+
+            group
+                .process_incoming_message_with_time(message, MlsTime::now())
+                .await
+                .map_err(failed("processing proposal"))?;
+        }
+
+        let message = MlsMessage::from_bytes(&epoch.commit).map_err(failed("decoding commit"))?;
+
+        group
+            .process_incoming_message_with_time(message, MlsTime::now())
+            .await
+            .map_err(failed("processing commit"))?;
+
+        let epoch_authenticator = group
+            .epoch_authenticator()
+            .map_err(failed("epoch authenticator"))?;
+
+        ensure(
+            *epoch_authenticator == epoch.epoch_authenticator,
+            &format!("epochs[{i}].epoch_authenticator"),
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{format, string::String, vec::Vec};
+
+use mls_rs_codec::MlsDecode;
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, CryptoProvider},
+    extension::ExtensionList,
+    protocol_version::ProtocolVersion,
+};
+
+use crate::{
+    client::MlsError,
+    group::GroupContext,
+    identity::basic::BasicIdentityProvider,
+    tree_kem::{node::NodeVec, tree_validator::TreeValidator, TreeKemPublic},
+};
+
+use super::{
+    case_result, cipher_suite_provider, ensure, failed, parse, skipped, CaseResult,
+    ComplianceCategory,
+};
+
+const CATEGORY: ComplianceCategory = ComplianceCategory::TreeValidation;
+
+#[derive(serde::Deserialize)]
+struct TestCase {
+    cipher_suite: u16,
+    #[serde(with = "hex::serde")]
+    tree: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    group_id: Vec<u8>,
+    tree_hashes: Vec<TreeHash>,
+    resolutions: Vec<Vec<u32>>,
+}
+
+#[derive(serde::Deserialize)]
+struct TreeHash(#[serde(with = "hex::serde")] Vec<u8>);
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(super) async fn run<P: CryptoProvider>(
+    crypto_provider: &P,
+    json: &str,
+) -> Result<Vec<CaseResult>, MlsError> {
+    let mut results = Vec::new();
+
+    for (index, case) in parse::<TestCase>(json)?.into_iter().enumerate() {
+        let cipher_suite = case.cipher_suite;
+
+        match cipher_suite_provider(crypto_provider, cipher_suite) {
+            Ok(cs) => {
+                let res = run_case(&cs, case).await;
+                results.push(case_result(CATEGORY, index, Some(cipher_suite), res));
+            }
+            Err(outcome) => results.push(skipped(CATEGORY, index, cipher_suite, outcome)),
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn run_case<P: CipherSuiteProvider>(cs: &P, case: TestCase) -> Result<(), String> {
+    let nodes = NodeVec::mls_decode(&mut &*case.tree).map_err(failed("decoding tree"))?;
+
+    let mut tree =
+        TreeKemPublic::import_node_data(nodes, &BasicIdentityProvider, &Default::default())
+            .await
+            .map_err(failed("importing tree"))?;
+
+    let tree_hash = tree
+        .tree_hash(cs)
+        .await
+        .map_err(failed("computing tree hash"))?;
+
+    let tree_hashes = tree.node_tree_hashes();
+
+    ensure(
+        tree_hashes.len() == case.tree_hashes.len(),
+        "number of tree_hashes",
+    )?;
+
+    for (i, (computed, expected)) in tree_hashes.iter().zip(&case.tree_hashes).enumerate() {
+        ensure(*computed == expected.0, &format!("tree_hashes[{i}]"))?;
+    }
+
+    for (i, expected) in case.resolutions.iter().enumerate() {
+        let resolution = tree
+            .nodes
+            .get_resolution_index(i as u32)
+            .map_err(failed("computing resolution"))?;
+
+        ensure(resolution == *expected, &format!("resolutions[{i}]"))?;
+    }
+
+    let context = GroupContext {
+        protocol_version: ProtocolVersion::MLS_10,
+        cipher_suite: cs.cipher_suite(),
+        group_id: case.group_id,
+        epoch: 1,
+        tree_hash,
+        confirmed_transcript_hash: Vec::<u8>::new().into(),
+        extensions: ExtensionList::new(),
+    };
+
+    TreeValidator::new(cs, &context, &BasicIdentityProvider)
+        .validate(&mut tree, None)
+        .await
+        .map_err(failed("validating tree"))
+}
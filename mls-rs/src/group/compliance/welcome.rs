@@ -0,0 +1,141 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{string::String, vec::Vec};
+
+use mls_rs_codec::MlsDecode;
+use mls_rs_core::crypto::{CipherSuiteProvider, CryptoProvider, HpkeSecretKey, SignaturePublicKey};
+
+use crate::{
+    client::MlsError,
+    group::{
+        key_schedule::{KeySchedule, WelcomeSecret},
+        GroupInfo, GroupSecrets, MlsMessage,
+    },
+    psk::secret::PskSecret,
+    signer::Signable,
+    tree_kem::hpke_encryption::HpkeEncryptable,
+};
+
+use super::{
+    case_result, cipher_suite_provider, ensure, failed, parse, skipped, CaseResult,
+    ComplianceCategory,
+};
+
+const CATEGORY: ComplianceCategory = ComplianceCategory::Welcome;
+
+// The ratchet tree is not part of the test vectors. Its size only affects
+// the secret tree, which is not checked.
+const SECRET_TREE_SIZE: u32 = 1;
+
+#[derive(serde::Deserialize)]
+struct TestCase {
+    cipher_suite: u16,
+    #[serde(with = "hex::serde")]
+    init_priv: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    signer_pub: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    key_package: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    welcome: Vec<u8>,
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(super) async fn run<P: CryptoProvider>(
+    crypto_provider: &P,
+    json: &str,
+) -> Result<Vec<CaseResult>, MlsError> {
+    let mut results = Vec::new();
+
+    for (index, case) in parse::<TestCase>(json)?.into_iter().enumerate() {
+        let cipher_suite = case.cipher_suite;
+
+        match cipher_suite_provider(crypto_provider, cipher_suite) {
+            Ok(cs) => {
+                let res = run_case(&cs, case).await;
+                results.push(case_result(CATEGORY, index, Some(cipher_suite), res));
+            }
+            Err(outcome) => results.push(skipped(CATEGORY, index, cipher_suite, outcome)),
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn run_case<P: CipherSuiteProvider>(cs: &P, case: TestCase) -> Result<(), String> {
+    let key_package = MlsMessage::from_bytes(&case.key_package)
+        .map_err(failed("decoding key_package"))?
+        .into_key_package()
+        .ok_or("key_package: message is not a key package")?;
+
+    let welcome = MlsMessage::from_bytes(&case.welcome)
+        .map_err(failed("decoding welcome"))?
+        .into_welcome()
+        .ok_or("welcome: message is not a welcome")?;
+
+    let key_package_ref = key_package
+        .to_reference(cs)
+        .await
+        .map_err(failed("computing key package reference"))?;
+
+    let secrets = welcome
+        .secrets
+        .iter()
+        .find(|s| s.new_member == key_package_ref)
+        .ok_or("welcome: no secrets for the key package")?;
+
+    let group_secrets = GroupSecrets::decrypt(
+        cs,
+        &HpkeSecretKey::from(case.init_priv),
+        &key_package.hpke_init_key,
+        &welcome.encrypted_group_info,
+        &secrets.encrypted_group_secrets,
+    )
+    .await
+    .map_err(failed("decrypting group secrets"))?;
+
+    ensure(group_secrets.psks.is_empty(), "psks")?;
+
+    let psk_secret = PskSecret::new(cs);
+
+    let group_info =
+        WelcomeSecret::from_joiner_secret(cs, &group_secrets.joiner_secret, &psk_secret)
+            .await
+            .map_err(failed("deriving welcome secret"))?
+            .decrypt(&welcome.encrypted_group_info)
+            .await
+            .map_err(failed("decrypting group info"))?;
+
+    let group_info =
+        GroupInfo::mls_decode(&mut &**group_info).map_err(failed("decoding group info"))?;
+
+    group_info
+        .verify(cs, &SignaturePublicKey::from(case.signer_pub), &())
+        .await
+        .map_err(failed("verifying group info signature"))?;
+
+    let key_schedule = KeySchedule::from_joiner(
+        cs,
+        &group_secrets.joiner_secret,
+        &group_info.group_context,
+        SECRET_TREE_SIZE,
+        &psk_secret,
+    )
+    .await
+    .map_err(failed("deriving key schedule"))?;
+
+    let tag_matches = group_info
+        .confirmation_tag
+        .matches(
+            &key_schedule.confirmation_key,
+            &group_info.group_context.confirmed_transcript_hash,
+            cs,
+        )
+        .await
+        .map_err(failed("computing confirmation tag"))?;
+
+    ensure(tag_matches, "confirmation_tag")
+}
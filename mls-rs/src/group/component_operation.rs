@@ -24,3 +24,22 @@ impl<'a> ComponentOperationLabel<'a> {
         self.mls_encode_to_vec().map_err(Into::into)
     }
 }
+
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode)]
+pub(crate) struct ComponentSecretLabel<'a> {
+    component_id: ComponentID,
+    label: &'a [u8],
+}
+
+impl<'a> ComponentSecretLabel<'a> {
+    pub(crate) fn new(component_id: ComponentID, label: &'a [u8]) -> Self {
+        Self {
+            component_id,
+            label,
+        }
+    }
+
+    pub(crate) fn get_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{compression::Compressor, error::IntoAnyError};
+
+use crate::client::MlsError;
+
+use super::framing::MlsMessage;
+
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+struct CompressedMessage {
+    algorithm_id: u16,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    data: Vec<u8>,
+}
+
+impl MlsMessage {
+    /// Serialize the message and compress it with `compressor`.
+    ///
+    /// This is mostly useful for welcome and group info messages that carry
+    /// a ratchet tree. The output is not an MLS message and can only be read
+    /// with [`MlsMessage::from_compressed_bytes`].
+    pub fn to_compressed_bytes<C: Compressor>(&self, compressor: &C) -> Result<Vec<u8>, MlsError> {
+        let data = compressor
+            .compress(&self.mls_encode_to_vec()?)
+            .map_err(|e| MlsError::CompressionError(e.into_any_error()))?;
+
+        CompressedMessage {
+            algorithm_id: compressor.algorithm_id(),
+            data,
+        }
+        .mls_encode_to_vec()
+        .map_err(Into::into)
+    }
+
+    /// Deserialize a message produced by [`MlsMessage::to_compressed_bytes`].
+    pub fn from_compressed_bytes<C: Compressor>(
+        bytes: &[u8],
+        compressor: &C,
+    ) -> Result<Self, MlsError> {
+        let compressed = CompressedMessage::mls_decode(&mut &*bytes)?;
+
+        if compressed.algorithm_id != compressor.algorithm_id() {
+            return Err(MlsError::CompressionAlgorithmMismatch(
+                compressed.algorithm_id,
+                compressor.algorithm_id(),
+            ));
+        }
+
+        let data = compressor
+            .decompress(&compressed.data)
+            .map_err(|e| MlsError::CompressionError(e.into_any_error()))?;
+
+        Self::from_bytes(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use core::convert::Infallible;
+    use mls_rs_core::compression::Compressor;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{framing::MlsMessage, test_utils::test_group},
+    };
+
+    // Run length encoding of bytes, enough to exercise the envelope.
+    struct RunLength(u16);
+
+    impl Compressor for RunLength {
+        type Error = Infallible;
+
+        fn algorithm_id(&self) -> u16 {
+            self.0
+        }
+
+        fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Infallible> {
+            let mut out = Vec::new();
+
+            for byte in data {
+                match out.len() {
+                    len if len >= 2 && out[len - 1] == *byte && out[len - 2] < u8::MAX => {
+                        out[len - 2] += 1
+                    }
+                    _ => out.extend([1, *byte]),
+                }
+            }
+
+            Ok(out)
+        }
+
+        fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Infallible> {
+            Ok(data
+                .chunks(2)
+                .flat_map(|run| core::iter::repeat(run[1]).take(run[0] as usize))
+                .collect())
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn compressed_group_info_round_trips() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let group_info = group.group_info_message(true).await.unwrap();
+
+        let bytes = group_info.to_compressed_bytes(&RunLength(1)).unwrap();
+        let decoded = MlsMessage::from_compressed_bytes(&bytes, &RunLength(1)).unwrap();
+
+        assert_eq!(decoded, group_info);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn other_compression_algorithm_is_rejected() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let group_info = group.group_info_message(true).await.unwrap();
+
+        let bytes = group_info.to_compressed_bytes(&RunLength(1)).unwrap();
+        let res = MlsMessage::from_compressed_bytes(&bytes, &RunLength(2));
+
+        assert_matches!(res, Err(MlsError::CompressionAlgorithmMismatch(1, 2)));
+    }
+}
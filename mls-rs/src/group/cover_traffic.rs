@@ -0,0 +1,191 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::{framing::MlsMessage, Group, ReceivedMessage};
+
+/// Prefix of the application data of cover messages.
+///
+/// The marker is encrypted together with the rest of the application data,
+/// so cover messages can not be told apart from application messages of the
+/// same padded size on the wire.
+const COVER_TRAFFIC_MARKER: [u8; 16] = *b"\x00mls-rs-cover\x00\x00\x00";
+
+/// Distribution of the size of the application data of cover messages.
+pub trait CoverSizeDistribution: Send + Sync {
+    /// Map `random`, drawn uniformly from the cipher suite's random number
+    /// generator, to a size in bytes.
+    fn sample(&self, random: u64) -> usize;
+}
+
+impl<F> CoverSizeDistribution for F
+where
+    F: Fn(u64) -> usize + Send + Sync,
+{
+    fn sample(&self, random: u64) -> usize {
+        self(random)
+    }
+}
+
+/// Sizes drawn uniformly from `min..=max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UniformSize {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl UniformSize {
+    pub fn new(min: usize, max: usize) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+        }
+    }
+}
+
+impl CoverSizeDistribution for UniformSize {
+    fn sample(&self, random: u64) -> usize {
+        let range = ((self.max - self.min) as u64).saturating_add(1);
+        self.min + (random % range) as usize
+    }
+}
+
+/// Generates and filters cover traffic, i.e. dummy application messages
+/// used to hide the timing and volume of real ones.
+///
+/// Cover messages are regular encrypted application messages padded with
+/// the [`EncryptionOptions`](crate::mls_rules::EncryptionOptions) of the
+/// group. Receivers pass incoming messages through
+/// [`CoverTraffic::process_incoming_message`], which drops cover messages
+/// after they have been decrypted and authenticated.
+///
+/// Application data starting with the reserved marker used by cover messages
+/// is always treated as cover traffic.
+#[derive(Clone, Debug)]
+pub struct CoverTraffic<D = UniformSize> {
+    size_distribution: D,
+}
+
+impl Default for CoverTraffic {
+    fn default() -> Self {
+        Self::new(UniformSize::new(0, 256))
+    }
+}
+
+impl<D: CoverSizeDistribution> CoverTraffic<D> {
+    /// Create a new helper sampling the size of the application data of cover
+    /// messages from `size_distribution`, before padding.
+    pub fn new(size_distribution: D) -> Self {
+        Self { size_distribution }
+    }
+
+    /// Encrypt a new cover message for `group`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn generate<C>(&self, group: &mut Group<C>) -> Result<MlsMessage, MlsError>
+    where
+        C: ClientConfig + Clone,
+    {
+        let random = group
+            .cipher_suite_provider
+            .random_bytes_vec(8)
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let mut seed = [0u8; 8];
+        seed.copy_from_slice(&random);
+
+        let size = self
+            .size_distribution
+            .sample(u64::from_le_bytes(seed))
+            .max(COVER_TRAFFIC_MARKER.len());
+
+        let mut data = Vec::with_capacity(size);
+        data.extend_from_slice(&COVER_TRAFFIC_MARKER);
+        data.resize(size, 0);
+
+        group.encrypt_application_message(&data, Vec::new()).await
+    }
+
+    /// Process `message` with [`Group::process_incoming_message`], returning
+    /// `None` if it is a cover message.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn process_incoming_message<C>(
+        &self,
+        group: &mut Group<C>,
+        message: MlsMessage,
+    ) -> Result<Option<ReceivedMessage>, MlsError>
+    where
+        C: ClientConfig + Clone,
+    {
+        let received = group.process_incoming_message(message).await?;
+
+        Ok((!Self::is_cover(&received)).then_some(received))
+    }
+
+    /// Returns `true` if `message` is a decrypted cover message.
+    pub fn is_cover(message: &ReceivedMessage) -> bool {
+        matches!(
+            message,
+            ReceivedMessage::ApplicationMessage(m) if m.data().starts_with(&COVER_TRAFFIC_MARKER)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{test_utils::test_group, ReceivedMessage},
+    };
+
+    use super::{CoverTraffic, UniformSize};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn cover_messages_are_dropped_by_receivers() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let cover = CoverTraffic::new(UniformSize::new(10, 100));
+
+        let dummy = cover.generate(&mut alice.group).await.unwrap();
+        let res = cover.process_incoming_message(&mut bob.group, dummy).await;
+        assert_matches!(res, Ok(None));
+
+        let real = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let res = cover
+            .process_incoming_message(&mut bob.group, real)
+            .await
+            .unwrap();
+
+        assert_matches!(res, Some(ReceivedMessage::ApplicationMessage(m)) if m.data() == b"hello");
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn cover_size_follows_distribution() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let cover = CoverTraffic::new(|_: u64| 500);
+        let dummy = cover.generate(&mut alice.group).await.unwrap();
+
+        let ReceivedMessage::ApplicationMessage(m) =
+            bob.process_incoming_message(dummy).await.unwrap()
+        else {
+            panic!("expected application message");
+        };
+
+        assert_eq!(m.data().len(), 500);
+    }
+}
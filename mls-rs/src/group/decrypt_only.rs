@@ -0,0 +1,167 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::{
+    error::IntoAnyError,
+    group::{GroupStateStorage, Member},
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        cipher_suite_provider,
+        ciphertext_processor::{CiphertextProcessor, GroupStateProvider},
+        context_binding,
+        epoch::EpochSecrets,
+        framing::{Content, ContentType, PrivateMessage},
+        message_processor::ApplicationMessageDescription,
+        message_signature::AuthenticatedContent,
+        message_verifier::{verify_auth_content_signature, SignaturePublicKeysContainer},
+        roster::member_from_leaf_node,
+        snapshot::Snapshot,
+        snapshot_format::EpochSnapshot,
+        GroupContext, Sender,
+    },
+    tree_kem::node::LeafIndex,
+};
+
+#[cfg(feature = "prior_epoch")]
+use crate::group::epoch::PriorEpoch;
+
+#[cfg(feature = "prior_epoch")]
+use mls_rs_codec::MlsDecode;
+
+impl GroupStateProvider for EpochSnapshot {
+    fn group_context(&self) -> &GroupContext {
+        &self.context
+    }
+
+    fn self_index(&self) -> LeafIndex {
+        self.self_index
+    }
+
+    fn epoch_secrets_mut(&mut self) -> &mut EpochSecrets {
+        &mut self.epoch_secrets
+    }
+
+    fn epoch_secrets(&self) -> &EpochSecrets {
+        &self.epoch_secrets
+    }
+}
+
+/// Decrypt an application message using only the stored secrets of the epoch
+/// it was sent in.
+///
+/// Prior epochs are loaded from their individual
+/// [`EpochRecord`](mls_rs_core::group::EpochRecord). For the current epoch,
+/// only the context, ratchet tree and epoch secrets are decoded from the
+/// stored group state. Nothing is written back to storage.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn decrypt_only<C>(
+    config: &C,
+    ciphertext: &PrivateMessage,
+) -> Result<ApplicationMessageDescription, MlsError>
+where
+    C: ClientConfig,
+{
+    if ciphertext.content_type != ContentType::Application {
+        return Err(MlsError::UnexpectedMessageType);
+    }
+
+    let storage = config.group_state_storage();
+
+    #[cfg(feature = "prior_epoch")]
+    if let Some(epoch) = storage
+        .epoch(&ciphertext.group_id, ciphertext.epoch)
+        .await
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
+    {
+        let mut epoch = PriorEpoch::mls_decode(&mut &*epoch)?;
+        let cs = cipher_suite_provider(config.crypto_provider(), epoch.context.cipher_suite)?;
+
+        let context_binding = context_binding(config, &epoch.context)?;
+
+        let (content, generation) = CiphertextProcessor::new(&mut epoch, cs.clone())
+            .with_context_binding(context_binding)
+            .open_with_generation(ciphertext)
+            .await?;
+
+        verify_auth_content_signature(
+            &cs,
+            SignaturePublicKeysContainer::List(&epoch.signature_public_keys),
+            &epoch.context,
+            &content,
+            #[cfg(feature = "by_ref_proposal")]
+            &[],
+        )
+        .await?;
+
+        return application_message(content, generation, |_| None);
+    }
+
+    let snapshot = storage
+        .state(&ciphertext.group_id)
+        .await
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
+        .ok_or(MlsError::GroupNotFound)?;
+
+    let mut epoch = Snapshot::epoch_from_bytes(&snapshot)?;
+
+    if epoch.context.epoch != ciphertext.epoch {
+        return Err(MlsError::EpochNotFound);
+    }
+
+    let cs = cipher_suite_provider(config.crypto_provider(), epoch.context.cipher_suite)?;
+
+    let context_binding = context_binding(config, &epoch.context)?;
+
+    let (content, generation) = CiphertextProcessor::new(&mut epoch, cs.clone())
+        .with_context_binding(context_binding)
+        .open_with_generation(ciphertext)
+        .await?;
+
+    verify_auth_content_signature(
+        &cs,
+        SignaturePublicKeysContainer::RatchetTree(&epoch.public_tree),
+        &epoch.context,
+        &content,
+        #[cfg(feature = "by_ref_proposal")]
+        &[],
+    )
+    .await?;
+
+    application_message(content, generation, |sender_index| {
+        let leaf_index = LeafIndex::try_from(sender_index).ok()?;
+
+        epoch
+            .public_tree
+            .get_leaf_node(leaf_index)
+            .ok()
+            .map(|leaf_node| member_from_leaf_node(leaf_node, leaf_index))
+    })
+}
+
+fn application_message(
+    content: AuthenticatedContent,
+    generation: u32,
+    resolve_sender: impl FnOnce(u32) -> Option<Member>,
+) -> Result<ApplicationMessageDescription, MlsError> {
+    let Content::Application(data) = content.content.content else {
+        return Err(MlsError::UnexpectedMessageType);
+    };
+
+    let Sender::Member(sender_index) = content.content.sender else {
+        return Err(MlsError::InvalidSender);
+    };
+
+    Ok(ApplicationMessageDescription {
+        sender_index,
+        sender: resolve_sender(sender_index),
+        epoch: content.content.epoch,
+        generation,
+        data,
+        authenticated_data: content.content.authenticated_data,
+    })
+}
@@ -0,0 +1,302 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::Group;
+
+/// View of a single epoch held by a member, used to compare the histories of
+/// two members with [`EpochHistory::compare`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct EpochDigest {
+    pub epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub tree_hash: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub confirmed_transcript_hash: Vec<u8>,
+    /// Epoch authenticator, only known for the current epoch of the member
+    /// or if recorded by the application while the epoch was current.
+    pub epoch_authenticator: Option<EpochAuthenticator>,
+}
+
+/// Epoch authenticator of an [`EpochDigest`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct EpochAuthenticator(#[mls_codec(with = "mls_rs_codec::byte_vec")] pub Vec<u8>);
+
+impl Debug for EpochAuthenticator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        mls_rs_core::debug::pretty_bytes(&self.0)
+            .named("EpochAuthenticator")
+            .fmt(f)
+    }
+}
+
+impl Debug for EpochDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EpochDigest")
+            .field("epoch", &self.epoch)
+            .field(
+                "tree_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.tree_hash),
+            )
+            .field(
+                "confirmed_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.confirmed_transcript_hash),
+            )
+            .field("epoch_authenticator", &self.epoch_authenticator)
+            .finish()
+    }
+}
+
+impl EpochDigest {
+    // Epoch authenticators are only compared if both sides know them.
+    fn agrees_with(&self, other: &EpochDigest) -> bool {
+        let authenticators_agree = match (&self.epoch_authenticator, &other.epoch_authenticator) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+
+        self.tree_hash == other.tree_hash
+            && self.confirmed_transcript_hash == other.confirmed_transcript_hash
+            && authenticators_agree
+    }
+}
+
+/// Epochs known to a member of a group, sorted by epoch, that can be
+/// exported with [`EpochHistory::to_bytes`] and compared with the history of
+/// another member.
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct EpochHistory {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epochs: Vec<EpochDigest>,
+}
+
+impl EpochHistory {
+    /// Create a history from digests recorded by the application.
+    pub fn new(group_id: Vec<u8>, mut epochs: Vec<EpochDigest>) -> Self {
+        epochs.sort_by_key(|e| e.epoch);
+        epochs.dedup_by_key(|e| e.epoch);
+
+        Self { group_id, epochs }
+    }
+
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    pub fn epochs(&self) -> &[EpochDigest] {
+        &self.epochs
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Find the first epoch in which this history and `other` disagree.
+    pub fn compare(&self, other: &EpochHistory) -> Result<DivergenceReport, MlsError> {
+        if self.group_id != other.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        let mut report = DivergenceReport {
+            local_epoch: self.epochs.last().map(|e| e.epoch),
+            remote_epoch: other.epochs.last().map(|e| e.epoch),
+            last_agreed_epoch: None,
+            divergence: None,
+        };
+
+        let mut remote = other.epochs.iter().peekable();
+
+        for local in self.epochs.iter() {
+            while remote.next_if(|r| r.epoch < local.epoch).is_some() {}
+
+            let Some(remote) = remote.next_if(|r| r.epoch == local.epoch) else {
+                continue;
+            };
+
+            if !local.agrees_with(remote) {
+                report.divergence = Some(Divergence {
+                    epoch: local.epoch,
+                    local: local.clone(),
+                    remote: remote.clone(),
+                });
+
+                break;
+            }
+
+            report.last_agreed_epoch = Some(local.epoch);
+        }
+
+        Ok(report)
+    }
+}
+
+/// First epoch in which two histories disagree, with both views of it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Divergence {
+    pub epoch: u64,
+    pub local: EpochDigest,
+    pub remote: EpochDigest,
+}
+
+/// Result of [`EpochHistory::compare`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DivergenceReport {
+    /// Latest epoch of the local history.
+    pub local_epoch: Option<u64>,
+    /// Latest epoch of the remote history.
+    pub remote_epoch: Option<u64>,
+    /// Latest epoch known to both sides, before any divergence, on which
+    /// they agree.
+    pub last_agreed_epoch: Option<u64>,
+    /// First epoch known to both sides on which they disagree.
+    pub divergence: Option<Divergence>,
+}
+
+impl DivergenceReport {
+    /// Returns `true` if the histories have at least one epoch in common and
+    /// agree on all common epochs.
+    pub fn is_consistent(&self) -> bool {
+        self.divergence.is_none() && self.last_agreed_epoch.is_some()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Digest of the current epoch, to be recorded by applications that want
+    /// to keep epoch authenticators in their [`EpochHistory`].
+    pub fn epoch_digest(&self) -> EpochDigest {
+        EpochDigest {
+            epoch: self.context().epoch,
+            tree_hash: self.context().tree_hash.clone(),
+            confirmed_transcript_hash: self.context().confirmed_transcript_hash.to_vec(),
+            epoch_authenticator: Some(EpochAuthenticator(
+                self.key_schedule.authentication_secret.to_vec(),
+            )),
+        }
+    }
+
+    /// History of the current epoch and up to `max_prior_epochs` prior epochs
+    /// retained by the group state storage.
+    ///
+    /// Epoch authenticators are not retained for prior epochs.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn epoch_history(&mut self, max_prior_epochs: u64) -> Result<EpochHistory, MlsError> {
+        #[allow(unused_mut)]
+        let mut epochs = alloc::vec![self.epoch_digest()];
+
+        #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+        {
+            let current = self.context().epoch;
+            let oldest = current.saturating_sub(max_prior_epochs);
+
+            for epoch_id in (oldest..current).rev() {
+                let Some(epoch) = self.state_repo.get_epoch_mut(epoch_id).await? else {
+                    break;
+                };
+
+                epochs.push(EpochDigest {
+                    epoch: epoch_id,
+                    tree_hash: epoch.context.tree_hash.clone(),
+                    confirmed_transcript_hash: epoch.context.confirmed_transcript_hash.to_vec(),
+                    epoch_authenticator: None,
+                });
+            }
+        }
+
+        #[cfg(not(all(feature = "prior_epoch", feature = "private_message")))]
+        let _ = max_prior_epochs;
+
+        Ok(EpochHistory::new(self.group_id().to_vec(), epochs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    use super::EpochHistory;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn divergence_epoch_is_found() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut alice_digests = vec![alice.epoch_digest()];
+        let mut bob_digests = vec![bob.epoch_digest()];
+
+        let commit = alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        alice_digests.push(alice.epoch_digest());
+        bob_digests.push(bob.epoch_digest());
+
+        // Both commit concurrently and only apply their own commit.
+        alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+        bob.commit(vec![]).await.unwrap();
+        bob.apply_pending_commit().await.unwrap();
+
+        alice_digests.push(alice.epoch_digest());
+        bob_digests.push(bob.epoch_digest());
+
+        let alice_history = EpochHistory::new(alice.group_id().to_vec(), alice_digests);
+        let bob_history = EpochHistory::new(bob.group_id().to_vec(), bob_digests);
+        let bob_history = EpochHistory::from_bytes(&bob_history.to_bytes().unwrap()).unwrap();
+
+        let report = alice_history.compare(&bob_history).unwrap();
+        let divergence = report.divergence.clone().unwrap();
+
+        assert!(!report.is_consistent());
+        assert_eq!(report.last_agreed_epoch, Some(alice.current_epoch() - 1));
+        assert_eq!(divergence.epoch, alice.current_epoch());
+        assert_eq!(divergence.local, alice.epoch_digest());
+        assert_eq!(divergence.remote, bob.epoch_digest());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn histories_of_synced_members_are_consistent() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        for _ in 0..2 {
+            let commit = alice.commit(vec![]).await.unwrap();
+            alice.apply_pending_commit().await.unwrap();
+            bob.process_incoming_message(commit.commit_message)
+                .await
+                .unwrap();
+        }
+
+        let alice_history = alice.epoch_history(5).await.unwrap();
+        let bob_history = bob.epoch_history(5).await.unwrap();
+
+        let report = alice_history.compare(&bob_history).unwrap();
+
+        assert!(report.is_consistent());
+        assert_eq!(report.last_agreed_epoch, Some(alice.current_epoch()));
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::{crypto::SignatureSecretKey, identity::SigningIdentity};
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::{CommitOutput, Group};
+
+/// Output of [`Group::emergency_rekey`].
+///
+/// The commit in `commit_output` is pending: until it is accepted by the
+/// delivery service and applied with [`Group::apply_pending_commit`], the
+/// group remains in `compromised_epoch` and the compromised members can still
+/// read messages sent in it.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct EmergencyRekeyOutput {
+    /// Commit removing the compromised members and replacing every secret
+    /// known to this member.
+    pub commit_output: CommitOutput,
+    /// Leaf indexes of the removed members.
+    pub removed: Vec<u32>,
+    /// Last epoch whose secrets must be considered known to an attacker.
+    ///
+    /// Values obtained from [`Group::export_secret`] in this epoch or any
+    /// earlier epoch must be discarded once the commit is applied.
+    pub compromised_epoch: u64,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Rotate all secrets of the group after the compromise of a device.
+    ///
+    /// The steps are, in order:
+    ///
+    /// 1. Discard the pending commit and all cached proposals, as they may
+    ///    originate from the compromised members.
+    /// 2. Remove every member in `compromised_members`.
+    /// 3. Replace the signature key of this member with `new_signer` and
+    ///    `new_signing_identity`.
+    /// 4. Send a full update path, which replaces the HPKE keys of this member
+    ///    and of every node on its path and derives a new epoch secret
+    ///    unknown to the removed members.
+    ///
+    /// The update path is always included, even when `compromised_members`
+    /// is empty. Exported secrets are invalidated by the epoch change, see
+    /// [`EmergencyRekeyOutput::compromised_epoch`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MlsError::EmergencyRekeyIncludesSelf`] if this member is
+    /// listed as compromised, since it can't rotate keys it no longer
+    /// controls. In that case, this member should leave the group instead.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn emergency_rekey(
+        &mut self,
+        compromised_members: &[u32],
+        new_signer: SignatureSecretKey,
+        new_signing_identity: SigningIdentity,
+    ) -> Result<EmergencyRekeyOutput, MlsError> {
+        let mut removed = compromised_members.to_vec();
+        removed.sort_unstable();
+        removed.dedup();
+
+        if removed.contains(&self.current_member_index()) {
+            return Err(MlsError::EmergencyRekeyIncludesSelf);
+        }
+
+        self.clear_pending_commit();
+
+        #[cfg(feature = "by_ref_proposal")]
+        self.clear_proposal_cache();
+
+        let compromised_epoch = self.current_epoch();
+
+        let commit_output = removed
+            .iter()
+            .try_fold(self.commit_builder(), |builder, index| {
+                builder.remove_member(*index)
+            })?
+            .set_new_signing_identity(new_signer, new_signing_identity)
+            .build()
+            .await?;
+
+        Ok(EmergencyRekeyOutput {
+            commit_output,
+            removed,
+            compromised_epoch,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_n_member_group,
+        identity::test_utils::get_test_signing_identity,
+        tree_kem::node::LeafIndex,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn emergency_rekey_removes_members_and_rotates_keys() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"member").await;
+
+        let old_leaf = groups[0].current_user_leaf_node().unwrap().clone();
+        let old_secret = groups[0].export_secret(b"label", b"", 32).await.unwrap();
+
+        let output = groups[0]
+            .emergency_rekey(&[1, 1], secret_key, identity.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(output.removed, [1]);
+        assert_eq!(output.compromised_epoch, groups[0].current_epoch());
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        groups[2]
+            .process_message(output.commit_output.commit_message)
+            .await
+            .unwrap();
+
+        for group in [&groups[0], &groups[2]] {
+            assert!(group.roster().member_with_index(1).is_err());
+
+            let leaf = group
+                .current_epoch_tree()
+                .get_leaf_node(LeafIndex::try_from(0).unwrap())
+                .unwrap();
+            assert_ne!(leaf.public_key, old_leaf.public_key);
+            assert_eq!(leaf.signing_identity, identity);
+        }
+
+        let new_secret = groups[0].export_secret(b"label", b"", 32).await.unwrap();
+        assert_ne!(new_secret, old_secret);
+
+        let other_secret = groups[2].export_secret(b"label", b"", 32).await.unwrap();
+
+        assert_eq!(new_secret, other_secret);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn emergency_rekey_rejects_own_index() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"member").await;
+
+        let res = groups[0]
+            .emergency_rekey(&[0, 1], secret_key, identity)
+            .await;
+
+        assert_matches!(res, Err(MlsError::EmergencyRekeyIncludesSelf));
+    }
+}
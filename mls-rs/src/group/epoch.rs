@@ -16,9 +16,15 @@ use core::{
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use zeroize::Zeroizing;
 
+#[cfg(feature = "secret_tree_recovery")]
+use crate::client::MlsError;
+
 #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
 use super::ciphertext_processor::GroupStateProvider;
 
+#[cfg(feature = "prior_epoch_transcript_hash")]
+use super::transcript_hash::InterimTranscriptHash;
+
 #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
 use crate::group::secret_tree::SecretTree;
 
@@ -32,6 +38,8 @@ pub(crate) struct PriorEpoch {
     pub(crate) signature_public_keys: Vec<Option<SignaturePublicKey>>,
     #[cfg(feature = "prior_epoch_membership_key")]
     pub(crate) membership_key: Vec<u8>,
+    #[cfg(feature = "prior_epoch_transcript_hash")]
+    pub(crate) interim_transcript_hash: InterimTranscriptHash,
 }
 
 #[cfg(feature = "prior_epoch")]
@@ -76,6 +84,25 @@ pub(crate) struct EpochSecrets {
     pub(crate) sender_data_secret: SenderDataSecret,
     #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
     pub(crate) secret_tree: SecretTree<NodeIndex>,
+    #[cfg(feature = "secret_tree_recovery")]
+    pub(crate) encryption_secret: EncryptionSecret,
+}
+
+#[cfg(feature = "secret_tree_recovery")]
+impl EpochSecrets {
+    /// Replace the secret tree with one derived again from the encryption
+    /// secret of the epoch. The ratchets of `self_index` are kept so that
+    /// message keys already used for sending are not reused.
+    pub(crate) fn recover_secret_tree(&mut self, self_index: NodeIndex) -> Result<(), MlsError> {
+        if self.encryption_secret.is_empty() {
+            return Err(MlsError::SecretTreeRecoveryUnavailable);
+        }
+
+        self.secret_tree
+            .recover(self.encryption_secret.0.clone(), self_index);
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, PartialEq, MlsEncode, MlsDecode, MlsSize)]
@@ -120,6 +147,47 @@ impl From<Zeroizing<Vec<u8>>> for SenderDataSecret {
     }
 }
 
+#[cfg(feature = "secret_tree_recovery")]
+#[derive(Clone, Default, PartialEq, MlsEncode, MlsDecode, MlsSize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct EncryptionSecret(
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::zeroizing_serde"))]
+    Zeroizing<Vec<u8>>,
+);
+
+#[cfg(feature = "secret_tree_recovery")]
+impl Debug for EncryptionSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        mls_rs_core::debug::pretty_bytes(&self.0)
+            .named("EncryptionSecret")
+            .fmt(f)
+    }
+}
+
+#[cfg(feature = "secret_tree_recovery")]
+impl Deref for EncryptionSecret {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "secret_tree_recovery")]
+impl From<Vec<u8>> for EncryptionSecret {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(Zeroizing::new(bytes))
+    }
+}
+
+#[cfg(feature = "secret_tree_recovery")]
+impl From<Zeroizing<Vec<u8>>> for EncryptionSecret {
+    fn from(bytes: Zeroizing<Vec<u8>>) -> Self {
+        Self(bytes)
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test_utils {
     use mls_rs_core::crypto::CipherSuiteProvider;
@@ -139,8 +207,15 @@ pub(crate) mod test_utils {
     pub(crate) fn get_test_epoch_secrets(cipher_suite: CipherSuite) -> EpochSecrets {
         let cs_provider = test_cipher_suite_provider(cipher_suite);
 
+        #[cfg(any(
+            feature = "secret_tree_access",
+            feature = "private_message",
+            feature = "secret_tree_recovery"
+        ))]
+        let encryption_secret = random_bytes(cs_provider.kdf_extract_size());
+
         #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
-        let secret_tree = get_test_tree(random_bytes(cs_provider.kdf_extract_size()), 2);
+        let secret_tree = get_test_tree(encryption_secret.clone(), 2);
 
         EpochSecrets {
             #[cfg(feature = "psk")]
@@ -148,6 +223,8 @@ pub(crate) mod test_utils {
             sender_data_secret: random_bytes(cs_provider.kdf_extract_size()).into(),
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
             secret_tree,
+            #[cfg(feature = "secret_tree_recovery")]
+            encryption_secret: encryption_secret.into(),
         }
     }
 
@@ -164,6 +241,8 @@ pub(crate) mod test_utils {
             signature_public_keys: Default::default(),
             #[cfg(feature = "prior_epoch_membership_key")]
             membership_key: Default::default(),
+            #[cfg(feature = "prior_epoch_transcript_hash")]
+            interim_transcript_hash: Vec::new().into(),
         }
     }
 }
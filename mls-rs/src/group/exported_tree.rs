@@ -3,6 +3,9 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use alloc::{borrow::Cow, vec::Vec};
+
+#[cfg(feature = "tree_visualization")]
+use alloc::string::String;
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
 use crate::{client::MlsError, tree_kem::node::NodeVec};
@@ -52,6 +55,24 @@ impl ExportedTree<'static> {
     }
 }
 
+#[cfg(feature = "tree_visualization")]
+impl ExportedTree<'_> {
+    /// Render the tree as indented text for debugging.
+    ///
+    /// Each node is shown with its index, blank nodes are marked and leaves
+    /// are labeled with the identity of the member. Parent hashes are
+    /// truncated and unmerged leaves are listed by leaf index.
+    pub fn to_ascii(&self) -> String {
+        crate::tree_kem::tree_visualization::render_ascii(&self.0)
+    }
+
+    /// Render the tree as a Graphviz DOT graph for debugging, with the same
+    /// node details as [`ExportedTree::to_ascii`].
+    pub fn to_dot(&self) -> String {
+        crate::tree_kem::tree_visualization::render_dot(&self.0)
+    }
+}
+
 impl From<ExportedTree<'_>> for NodeVec {
     fn from(value: ExportedTree) -> Self {
         value.0.into_owned()
@@ -214,6 +214,8 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
             sender_data_secret: SenderDataSecret::from(vec![]),
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
             secret_tree: SecretTree::empty(),
+            #[cfg(feature = "secret_tree_recovery")]
+            encryption_secret: vec![].into(),
         };
 
         let (mut group, _) = Group::join_with(
@@ -287,6 +289,7 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
                 None,
                 None,
                 self.commit_time,
+                None,
             )
             .await?;
 
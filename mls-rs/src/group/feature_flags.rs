@@ -0,0 +1,274 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::extension::{ExtensionList, ExtensionType, MlsCodecExtension};
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::{commit::CommitBuilder, Group};
+
+/// Application defined feature, such as a padding policy or the use of a
+/// new extension, that can be enabled for a whole group.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, MlsSize, MlsEncode, MlsDecode,
+)]
+pub struct FeatureFlag(u16);
+
+impl FeatureFlag {
+    pub const fn new(raw_value: u16) -> Self {
+        Self(raw_value)
+    }
+
+    pub const fn raw_value(&self) -> u16 {
+        self.0
+    }
+}
+
+impl From<u16> for FeatureFlag {
+    fn from(value: u16) -> Self {
+        Self(value)
+    }
+}
+
+/// Group context extension listing the features enabled in a group.
+///
+/// The extension is part of the group context, so all members agree on the
+/// enabled features in every epoch and restored groups see the features of
+/// their epoch. Features are changed with
+/// [`CommitBuilder::set_feature_flags`] and reported in
+/// [`CommitMessageDescription::feature_flags_change`](crate::group::CommitMessageDescription::feature_flags_change).
+/// Its type must be listed in the capabilities of all members, see
+/// [`ClientBuilder::extension_type`](crate::client_builder::ClientBuilder::extension_type).
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct FeatureFlagsExt {
+    enabled: Vec<FeatureFlag>,
+}
+
+impl FeatureFlagsExt {
+    /// Extension type of the feature flags, taken from the private use range.
+    pub const EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF0A4);
+
+    /// Create an extension with no feature enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self, flag: FeatureFlag) -> bool {
+        self.enabled.binary_search(&flag).is_ok()
+    }
+
+    /// Enabled features in ascending order.
+    pub fn enabled(&self) -> &[FeatureFlag] {
+        &self.enabled
+    }
+
+    pub fn with_enabled(mut self, flag: FeatureFlag) -> Self {
+        self.enable(flag);
+        self
+    }
+
+    pub fn with_disabled(mut self, flag: FeatureFlag) -> Self {
+        self.disable(flag);
+        self
+    }
+
+    pub fn enable(&mut self, flag: FeatureFlag) {
+        if let Err(index) = self.enabled.binary_search(&flag) {
+            self.enabled.insert(index, flag);
+        }
+    }
+
+    pub fn disable(&mut self, flag: FeatureFlag) {
+        self.enabled.retain(|enabled| *enabled != flag);
+    }
+
+    // Flags received from other members may not be sorted.
+    fn from_extensions(extensions: &ExtensionList) -> Result<Self, MlsError> {
+        let mut flags = extensions.get_as::<Self>()?.unwrap_or_default();
+        flags.enabled.sort_unstable();
+        flags.enabled.dedup();
+        Ok(flags)
+    }
+}
+
+impl MlsCodecExtension for FeatureFlagsExt {
+    fn extension_type() -> ExtensionType {
+        Self::EXTENSION_TYPE
+    }
+}
+
+/// Change of the features enabled in a group, reported in
+/// [`CommitMessageDescription::feature_flags_change`](crate::group::CommitMessageDescription::feature_flags_change).
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct FeatureFlagsChange {
+    pub enabled: Vec<FeatureFlag>,
+    pub disabled: Vec<FeatureFlag>,
+}
+
+/// Change of the enabled features between the group context extensions of
+/// two epochs, if any. Invalid extensions enable no feature.
+pub(crate) fn feature_flags_change(
+    previous: &ExtensionList,
+    current: &ExtensionList,
+) -> Option<FeatureFlagsChange> {
+    let previous = FeatureFlagsExt::from_extensions(previous).unwrap_or_default();
+    let current = FeatureFlagsExt::from_extensions(current).unwrap_or_default();
+
+    let change = FeatureFlagsChange {
+        enabled: difference(&current, &previous),
+        disabled: difference(&previous, &current),
+    };
+
+    (!change.enabled.is_empty() || !change.disabled.is_empty()).then_some(change)
+}
+
+fn difference(a: &FeatureFlagsExt, b: &FeatureFlagsExt) -> Vec<FeatureFlag> {
+    a.enabled
+        .iter()
+        .copied()
+        .filter(|flag| !b.is_enabled(*flag))
+        .collect()
+}
+
+impl<'a, C> CommitBuilder<'a, C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Replace the features enabled in the group by `flags`.
+    ///
+    /// This inserts a group context extensions proposal keeping all other
+    /// group context extensions of the current epoch, so it can't be
+    /// combined with [`CommitBuilder::set_group_context_ext`].
+    pub fn set_feature_flags(self, flags: FeatureFlagsExt) -> Result<Self, MlsError> {
+        let mut extensions = self.group.context().extensions.clone();
+        extensions.set_from(flags)?;
+        self.set_group_context_ext(extensions)
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Features enabled in the current epoch, as set with
+    /// [`CommitBuilder::set_feature_flags`]. No feature is enabled in groups
+    /// without a [`FeatureFlagsExt`].
+    pub fn feature_flags(&self) -> Result<FeatureFlagsExt, MlsError> {
+        FeatureFlagsExt::from_extensions(&self.context().extensions)
+    }
+
+    pub fn is_feature_enabled(&self, flag: FeatureFlag) -> Result<bool, MlsError> {
+        Ok(self.feature_flags()?.is_enabled(flag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+        client_builder::MlsConfig,
+        group::{CommitMessageDescription, ReceivedMessage},
+        Client,
+    };
+
+    use super::{FeatureFlag, FeatureFlagsChange, FeatureFlagsExt};
+
+    const PADME: FeatureFlag = FeatureFlag::new(1);
+    const NEW_EXTENSION: FeatureFlag = FeatureFlag::new(2);
+
+    #[test]
+    fn flags_are_kept_sorted() {
+        let flags = FeatureFlagsExt::new()
+            .with_enabled(NEW_EXTENSION)
+            .with_enabled(PADME)
+            .with_enabled(PADME);
+
+        assert_eq!(flags.enabled(), &[PADME, NEW_EXTENSION]);
+        assert!(!flags.with_disabled(PADME).is_enabled(PADME));
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_client(name: &str) -> Client<impl MlsConfig> {
+        TestClientBuilder::new_for_test()
+            .with_random_signing_identity(name, TEST_CIPHER_SUITE)
+            .await
+            .extension_type(FeatureFlagsExt::EXTENSION_TYPE)
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn feature_changes_are_reported_and_persisted() {
+        let alice = test_client("alice").await;
+        let bob = test_client("bob").await;
+
+        let mut alice_group = alice
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let key_package = bob
+            .generate_key_package_message(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let commit = alice_group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice_group.apply_pending_commit().await.unwrap();
+
+        let (mut bob_group, _) = bob
+            .join_group(commit.ratchet_tree, &commit.welcome_messages[0], None)
+            .await
+            .unwrap();
+
+        assert!(!bob_group.is_feature_enabled(PADME).unwrap());
+
+        let flags = alice_group.feature_flags().unwrap().with_enabled(PADME);
+
+        let commit = alice_group
+            .commit_builder()
+            .set_feature_flags(flags)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let CommitMessageDescription {
+            feature_flags_change,
+            ..
+        } = alice_group.apply_pending_commit().await.unwrap();
+
+        let expected = FeatureFlagsChange {
+            enabled: vec![PADME],
+            disabled: vec![],
+        };
+
+        assert_eq!(feature_flags_change, Some(expected.clone()));
+
+        let ReceivedMessage::Commit(description) = bob_group
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap()
+        else {
+            panic!("expected a commit");
+        };
+
+        assert_eq!(description.feature_flags_change, Some(expected));
+
+        bob_group.write_to_storage().await.unwrap();
+
+        let restored = bob.load_group(bob_group.group_id()).await.unwrap();
+
+        assert!(restored.is_feature_enabled(PADME).unwrap());
+        assert!(!restored.is_feature_enabled(NEW_EXTENSION).unwrap());
+    }
+}
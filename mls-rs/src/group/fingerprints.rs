@@ -0,0 +1,110 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::{crypto::Fingerprint, error::IntoAnyError};
+
+use crate::{client::MlsError, client_config::ClientConfig, tree_kem::node::LeafIndex};
+
+use super::Group;
+
+/// Fingerprints of the keys of a group member, computed with the cipher
+/// suite of the group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MemberFingerprints {
+    /// Fingerprint of the signature key of the member.
+    pub signature_key: Fingerprint,
+    /// Fingerprint of the HPKE key in the leaf of the member.
+    pub hpke_key: Fingerprint,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Fingerprints of the keys of the member at `index`, for display in a
+    /// roster.
+    ///
+    /// The HPKE key fingerprint changes every time the member updates its
+    /// leaf while the signature key fingerprint only changes when the member
+    /// rotates its signing identity.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn member_fingerprints(&self, index: u32) -> Result<MemberFingerprints, MlsError> {
+        let leaf = self
+            .current_epoch_tree()
+            .get_leaf_node(LeafIndex::try_from(index)?)?;
+
+        let signature_key = leaf
+            .signing_identity
+            .fingerprint(&self.cipher_suite_provider)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let hpke_key = leaf
+            .public_key
+            .fingerprint(&self.cipher_suite_provider)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        Ok(MemberFingerprints {
+            signature_key,
+            hpke_key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_n_member_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_render_identical_fingerprints() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let alice_view = groups[0].member_fingerprints(1).await.unwrap();
+        let bob_view = groups[1].member_fingerprints(1).await.unwrap();
+
+        assert_eq!(alice_view, bob_view);
+        assert_eq!(
+            alice_view.signature_key.to_string(),
+            bob_view.signature_key.to_string()
+        );
+
+        let identity = groups[1].current_member_signing_identity().unwrap();
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let identity_key = identity.fingerprint(&cs).await.unwrap();
+
+        assert_eq!(identity_key, alice_view.signature_key);
+
+        assert_ne!(alice_view.signature_key, alice_view.hpke_key);
+
+        let commit = groups[1].commit(vec![]).await.unwrap();
+        groups[1].process_pending_commit().await.unwrap();
+
+        groups[0]
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let updated = groups[0].member_fingerprints(1).await.unwrap();
+
+        assert_eq!(updated.signature_key, alice_view.signature_key);
+        assert_ne!(updated.hpke_key, alice_view.hpke_key);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn fingerprint_of_missing_member_fails() {
+        let groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let res = groups[0].member_fingerprints(5).await;
+
+        assert!(res.is_err());
+    }
+}
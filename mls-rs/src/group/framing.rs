@@ -13,9 +13,9 @@ use crate::{group::Proposal, mls_rules::ProposalRef};
 
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
-use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_codec::{DecodeLimits, MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::{
-    crypto::{CipherSuite, CipherSuiteProvider},
+    crypto::{CipherSuite, CipherSuiteProvider, CryptoProvider},
     protocol_version::ProtocolVersion,
 };
 use zeroize::ZeroizeOnDrop;
@@ -26,6 +26,9 @@ use alloc::boxed::Box;
 #[cfg(feature = "custom_proposal")]
 use crate::group::proposal::{CustomProposal, ProposalOrRef};
 
+#[cfg(feature = "targeted_messages")]
+use super::targeted_message::TargetedMessage;
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[repr(u8)]
@@ -175,7 +178,14 @@ impl MlsEncode for PublicMessage {
 
 impl MlsDecode for PublicMessage {
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
-        let content = FramedContent::mls_decode(reader)?;
+        Self::mls_decode_with_limits(reader, &mls_rs_codec::DecodeLimits::UNLIMITED)
+    }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &mls_rs_codec::DecodeLimits,
+    ) -> Result<Self, mls_rs_codec::Error> {
+        let content = FramedContent::mls_decode_with_limits(reader, limits)?;
         let auth = FramedContentAuthData::mls_decode(reader, content.content_type())?;
 
         let membership_tag = match content.sender {
@@ -375,6 +385,12 @@ impl MlsMessage {
                 epoch_id: c.epoch,
                 content_type: c.content_type,
             },
+            #[cfg(feature = "targeted_messages")]
+            MlsMessagePayload::Targeted(t) => MlsMessageDescription::ProtocolMessage {
+                group_id: &t.group_id,
+                epoch_id: t.epoch,
+                content_type: ContentType::Application,
+            },
             MlsMessagePayload::GroupInfo(_) => MlsMessageDescription::GroupInfo,
             MlsMessagePayload::KeyPackage(_) => MlsMessageDescription::KeyPackage,
         }
@@ -465,6 +481,8 @@ impl MlsMessage {
             MlsMessagePayload::Welcome(_) => WireFormat::Welcome,
             MlsMessagePayload::GroupInfo(_) => WireFormat::GroupInfo,
             MlsMessagePayload::KeyPackage(_) => WireFormat::KeyPackage,
+            #[cfg(feature = "targeted_messages")]
+            MlsMessagePayload::Targeted(_) => WireFormat::TargetedMessage,
         }
     }
 
@@ -478,6 +496,8 @@ impl MlsMessage {
             MlsMessagePayload::Plain(p) => Some(p.content.epoch),
             #[cfg(feature = "private_message")]
             MlsMessagePayload::Cipher(c) => Some(c.epoch),
+            #[cfg(feature = "targeted_messages")]
+            MlsMessagePayload::Targeted(t) => Some(t.epoch),
             MlsMessagePayload::GroupInfo(gi) => Some(gi.group_context.epoch),
             _ => None,
         }
@@ -498,15 +518,34 @@ impl MlsMessage {
             MlsMessagePayload::Plain(p) => Some(&p.content.group_id),
             #[cfg(feature = "private_message")]
             MlsMessagePayload::Cipher(p) => Some(&p.group_id),
+            #[cfg(feature = "targeted_messages")]
+            MlsMessagePayload::Targeted(p) => Some(&p.group_id),
             MlsMessagePayload::GroupInfo(p) => Some(&p.group_context.group_id),
             MlsMessagePayload::KeyPackage(_) | MlsMessagePayload::Welcome(_) => None,
         }
     }
 
+    /// Limits applied by [`MlsMessage::from_bytes`].
+    ///
+    /// Opaque fields, such as an extension carrying the ratchet tree, may be
+    /// at most 64 MiB long and collections, such as the nodes of a ratchet
+    /// tree, may have at most 2^21 elements. This covers groups of up to
+    /// 2^20 members.
+    pub const DEFAULT_DECODE_LIMITS: DecodeLimits = DecodeLimits::new(1 << 26, 1 << 21);
+
     /// Deserialize a message from transport.
+    ///
+    /// The message is decoded with [`MlsMessage::DEFAULT_DECODE_LIMITS`].
     #[inline(never)]
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
-        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+        Self::from_bytes_with_limits(bytes, &Self::DEFAULT_DECODE_LIMITS)
+    }
+
+    /// Deserialize a message from transport, rejecting messages with opaque
+    /// fields or collections exceeding `limits` before they are allocated.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn from_bytes_with_limits(bytes: &[u8], limits: &DecodeLimits) -> Result<Self, MlsError> {
+        Self::mls_decode_with_limits(&mut &*bytes, limits).map_err(Into::into)
     }
 
     /// Serialize a message for transport.
@@ -566,6 +605,57 @@ impl MlsMessage {
             .await
             .map(|r| Some(r.to_vec()))
     }
+
+    /// If this is a key package, return the [`KeyPackageRef`] identifying it.
+    ///
+    /// The cipher suite provider matching the cipher suite of the key package
+    /// is taken from `crypto_provider`. The result is identical to the
+    /// reference mls-rs uses internally, e.g. in
+    /// [`MlsMessage::welcome_key_package_references`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn key_package_ref<P: CryptoProvider>(
+        &self,
+        crypto_provider: &P,
+    ) -> Result<Option<KeyPackageRef>, MlsError> {
+        let MlsMessagePayload::KeyPackage(kp) = &self.payload else {
+            return Ok(None);
+        };
+
+        let cipher_suite_provider = crypto_provider
+            .cipher_suite_provider(kp.cipher_suite)
+            .ok_or(MlsError::UnsupportedCipherSuite(kp.cipher_suite))?;
+
+        kp.to_reference(&cipher_suite_provider).await.map(Some)
+    }
+
+    /// If this is a plaintext proposal, return the [`ProposalRef`]
+    /// identifying it, computed with the cipher suite of the group.
+    ///
+    /// The result is identical to the reference members use to refer to
+    /// the proposal in a commit, and to
+    /// [`ProposalMessageDescription::proposal_ref`](super::ProposalMessageDescription::proposal_ref).
+    /// Returns `None` for all other messages, including encrypted proposals
+    /// whose content is not visible without processing them.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn proposal_ref<C: CipherSuiteProvider>(
+        &self,
+        cipher_suite: &C,
+    ) -> Result<Option<ProposalRef>, MlsError> {
+        let MlsMessagePayload::Plain(public_message) = &self.payload else {
+            return Ok(None);
+        };
+
+        if public_message.content.content_type() != ContentType::Proposal {
+            return Ok(None);
+        }
+
+        ProposalRef::from_content(cipher_suite, &public_message.clone().into())
+            .await
+            .map(Some)
+    }
 }
 
 #[cfg(feature = "custom_proposal")]
@@ -596,6 +686,8 @@ pub(crate) enum MlsMessagePayload {
     Welcome(Welcome) = 3u16,
     GroupInfo(GroupInfo) = 4u16,
     KeyPackage(KeyPackage) = 5u16,
+    #[cfg(feature = "targeted_messages")]
+    Targeted(TargetedMessage) = 6u16,
 }
 
 impl From<PublicMessage> for MlsMessagePayload {
@@ -618,6 +710,8 @@ pub enum WireFormat {
     Welcome = 3u16,
     GroupInfo = 4u16,
     KeyPackage = 5u16,
+    #[cfg(feature = "targeted_messages")]
+    TargetedMessage = 6u16,
 }
 
 #[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
@@ -719,11 +813,12 @@ mod tests {
 
     use crate::{
         client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
-        crypto::test_utils::test_cipher_suite_provider,
+        crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
         group::{
             framing::test_utils::get_test_ciphertext_content,
-            proposal_ref::test_utils::auth_content_from_proposal, test_utils::test_group,
-            RemoveProposal,
+            proposal_ref::test_utils::auth_content_from_proposal,
+            test_utils::{test_group, test_n_member_group},
+            ReceivedMessage, RemoveProposal,
         },
         key_package::test_utils::test_key_package_message,
     };
@@ -788,6 +883,57 @@ mod tests {
         assert_eq!(computed_ref, expected_ref.to_vec());
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn decode_limits_are_enforced() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let message = group
+            .encrypt_application_message(&[0u8; 100], vec![])
+            .await
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+
+        MlsMessage::from_bytes(&message).unwrap();
+
+        let res = MlsMessage::from_bytes_with_limits(&message, &DecodeLimits::new(64, 16));
+        assert_matches!(res, Err(MlsError::SerializationError(_)));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn public_references_match_internal_references() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let proposal = groups[0].propose_remove(2, vec![]).await.unwrap();
+        let proposal_ref = proposal.proposal_ref(&cs).await.unwrap().unwrap();
+
+        let ReceivedMessage::Proposal(description) =
+            groups[1].process_message(proposal).await.unwrap()
+        else {
+            panic!("expected proposal");
+        };
+
+        assert_eq!(description.proposal_ref(), proposal_ref.to_vec());
+
+        let commit = groups[0].commit(vec![]).await.unwrap().commit_message;
+        let commit_ref = commit.proposal_ref(&cs).await.unwrap();
+        assert_eq!(commit_ref, None);
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "a").await;
+
+        let internal_ref = key_package
+            .key_package_ref(&TestCryptoProvider::new())
+            .await
+            .unwrap();
+
+        let public_ref = key_package.key_package_reference(&cs).await.unwrap();
+
+        assert_eq!(internal_ref, public_ref);
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn message_description() {
         let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
@@ -0,0 +1,179 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::extension::{ExtensionList, ExtensionType, MlsCodecExtension};
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::{commit::CommitBuilder, NewMemberInfo};
+
+/// Alias of a group assigned by the Delivery Service, such as a
+/// conversation id.
+///
+/// The extension is set by the member that adds new members with
+/// [`CommitBuilder::set_group_alias`] and is carried in the group info of
+/// the resulting Welcome messages, where it is covered by the signature of
+/// that member. Joiners can check it against the alias advertised by the
+/// Delivery Service with
+/// [`Client::join_group_with_alias`](crate::Client::join_group_with_alias).
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct GroupAliasExt {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    alias: Vec<u8>,
+}
+
+impl Debug for GroupAliasExt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupAliasExt")
+            .field("alias", &mls_rs_core::debug::pretty_bytes(&self.alias))
+            .finish()
+    }
+}
+
+impl GroupAliasExt {
+    /// Extension type of the group alias, taken from the private use range.
+    pub const EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF0A1);
+
+    /// Create a new group alias extension.
+    pub fn new(alias: Vec<u8>) -> Self {
+        Self { alias }
+    }
+
+    /// Alias of the group.
+    pub fn alias(&self) -> &[u8] {
+        &self.alias
+    }
+
+    pub(crate) fn verify(extensions: &ExtensionList, expected: &[u8]) -> Result<(), MlsError> {
+        let ext = extensions
+            .get_as::<Self>()?
+            .ok_or(MlsError::GroupAliasNotFound)?;
+
+        (ext.alias == expected)
+            .then_some(())
+            .ok_or(MlsError::GroupAliasMismatch)
+    }
+}
+
+impl MlsCodecExtension for GroupAliasExt {
+    fn extension_type() -> ExtensionType {
+        Self::EXTENSION_TYPE
+    }
+}
+
+impl<'a, C> CommitBuilder<'a, C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Add a [`GroupAliasExt`] with `alias` to the group info extensions of
+    /// the welcome messages produced by this commit.
+    ///
+    /// Other group info extensions set with
+    /// [`CommitBuilder::set_group_info_ext`] before this call are kept.
+    pub fn set_group_alias(mut self, alias: Vec<u8>) -> Result<Self, MlsError> {
+        self.group_info_extensions
+            .set_from(GroupAliasExt::new(alias))?;
+
+        Ok(self)
+    }
+}
+
+impl NewMemberInfo {
+    /// Group alias found within the Welcome message used to join the group.
+    pub fn group_alias(&self) -> Result<Option<Vec<u8>>, MlsError> {
+        Ok(self
+            .group_info_extensions
+            .get_as::<GroupAliasExt>()?
+            .map(|ext| ext.alias))
+    }
+
+    pub(crate) fn verify_group_alias(&self, expected: &[u8]) -> Result<(), MlsError> {
+        GroupAliasExt::verify(&self.group_info_extensions, expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_alias_is_verified_on_join() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob, bob_kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .commit_builder()
+            .add_member(bob_kp)
+            .unwrap()
+            .set_group_alias(b"conversation".to_vec())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let welcome = &commit.welcome_messages[0];
+        let tree = Some(alice.export_tree());
+
+        let res = bob
+            .join_group_with_alias(tree.clone(), welcome, None, b"other")
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::GroupAliasMismatch));
+
+        let (group, info) = bob
+            .join_group_with_alias(tree, welcome, None, b"conversation")
+            .await
+            .unwrap();
+
+        assert_eq!(group.group_id(), alice.group_id());
+        assert_eq!(info.group_alias().unwrap(), Some(b"conversation".to_vec()));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn missing_group_alias_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob, bob_kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .commit_builder()
+            .add_member(bob_kp)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let res = bob
+            .join_group_with_alias(
+                Some(alice.export_tree()),
+                &commit.welcome_messages[0],
+                None,
+                b"conversation",
+            )
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::GroupAliasNotFound));
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::client_config::ClientConfig;
+
+use super::{proposal::Proposal, CommitEffect, CommitMessageDescription, Group};
+
+/// Outcome of an intent attached with [`CommitBuilder::intent`](super::CommitBuilder::intent).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IntentOutcome {
+    /// The commit was applied along with all proposals of the intent.
+    Succeeded,
+    /// The commit was applied, but some proposals of the intent were
+    /// filtered out by the [`MlsRules`](crate::MlsRules) or by the rules of
+    /// RFC 9420.
+    Filtered,
+    /// The commit was never applied, because a commit of another member was
+    /// processed for the same epoch, the pending commit was cleared or
+    /// another commit was built in its place.
+    Superseded,
+}
+
+/// Resolved intent, see [`Group::take_intent_results`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IntentResult {
+    pub intent_id: Vec<u8>,
+    pub outcome: IntentOutcome,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct IntentLog {
+    pending: Vec<(Vec<u8>, Vec<Proposal>)>,
+    resolved: Vec<IntentResult>,
+}
+
+impl IntentLog {
+    pub(crate) fn set_pending(&mut self, intents: Vec<(Vec<u8>, Vec<Proposal>)>) {
+        self.supersede();
+        self.pending = intents;
+    }
+
+    pub(crate) fn supersede(&mut self) {
+        let superseded = self.pending.drain(..).map(|(intent_id, _)| IntentResult {
+            intent_id,
+            outcome: IntentOutcome::Superseded,
+        });
+
+        self.resolved.extend(superseded);
+    }
+
+    pub(crate) fn commit_applied(&mut self, description: &CommitMessageDescription) {
+        let reinit;
+
+        let applied = match &description.effect {
+            CommitEffect::NewEpoch(new_epoch) | CommitEffect::Removed { new_epoch, .. } => {
+                new_epoch
+                    .applied_proposals
+                    .iter()
+                    .map(|p| &p.proposal)
+                    .collect::<Vec<_>>()
+            }
+            CommitEffect::ReInit(info) => {
+                reinit = Proposal::ReInit(info.proposal.clone());
+                alloc::vec![&reinit]
+            }
+        };
+
+        let results = self.pending.drain(..).map(|(intent_id, proposals)| {
+            let outcome = if proposals.iter().all(|p| applied.contains(&p)) {
+                IntentOutcome::Succeeded
+            } else {
+                IntentOutcome::Filtered
+            };
+
+            IntentResult { intent_id, outcome }
+        });
+
+        self.resolved.extend(results);
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Ids of the intents attached to the pending commit, in the order they
+    /// were attached.
+    pub fn pending_intents(&self) -> Vec<&[u8]> {
+        self.intent_log
+            .pending
+            .iter()
+            .map(|(intent_id, _)| intent_id.as_slice())
+            .collect()
+    }
+
+    /// Take the results of intents resolved since the last call, in the
+    /// order they were resolved.
+    ///
+    /// Intents are not part of the stored group state. Intents of a pending
+    /// commit are lost if the group is reloaded from storage before the
+    /// commit is applied.
+    pub fn take_intent_results(&mut self) -> Vec<IntentResult> {
+        core::mem::take(&mut self.intent_log.resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    use super::{IntentOutcome, IntentResult};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn intents_are_resolved_when_commit_is_applied() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+        let (carol, _) = alice.join("carol").await;
+
+        alice
+            .commit_builder()
+            .remove_member(bob.current_member_index())
+            .unwrap()
+            .intent(b"remove bob".to_vec())
+            .remove_member(carol.current_member_index())
+            .unwrap()
+            .intent(b"remove carol".to_vec())
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            alice.pending_intents(),
+            vec![b"remove bob".as_slice(), b"remove carol"]
+        );
+
+        alice.apply_pending_commit().await.unwrap();
+
+        assert!(alice.pending_intents().is_empty());
+
+        assert_eq!(
+            alice.take_intent_results(),
+            vec![
+                IntentResult {
+                    intent_id: b"remove bob".to_vec(),
+                    outcome: IntentOutcome::Succeeded,
+                },
+                IntentResult {
+                    intent_id: b"remove carol".to_vec(),
+                    outcome: IntentOutcome::Succeeded,
+                },
+            ]
+        );
+
+        assert!(alice.take_intent_results().is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn intents_are_superseded_by_other_commits() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        alice
+            .commit_builder()
+            .intent(b"rotate keys".to_vec())
+            .build()
+            .await
+            .unwrap();
+
+        let commit = bob.commit(vec![]).await.unwrap();
+        bob.apply_pending_commit().await.unwrap();
+
+        alice
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            alice.take_intent_results(),
+            vec![IntentResult {
+                intent_id: b"rotate keys".to_vec(),
+                outcome: IntentOutcome::Superseded,
+            }]
+        );
+    }
+}
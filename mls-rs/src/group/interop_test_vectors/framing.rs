@@ -433,6 +433,8 @@ async fn make_group<P: CipherSuiteProvider>(
         secret_tree,
         resumption_secret: vec![0_u8; cs.kdf_extract_size()].into(),
         sender_data_secret: test_case.sender_data_secret.clone().into(),
+        #[cfg(feature = "secret_tree_recovery")]
+        encryption_secret: test_case.encryption_secret.clone().into(),
     };
 
     group.epoch_secrets = secrets;
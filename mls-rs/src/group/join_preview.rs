@@ -0,0 +1,133 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::{extension::ExtensionList, group::Member, time::MlsTime};
+
+use crate::{
+    cipher_suite::CipherSuite, client::MlsError, client_config::ClientConfig,
+    protocol_version::ProtocolVersion,
+};
+
+use super::{
+    cipher_suite_provider, framing::MlsMessage, member_from_leaf_node,
+    validate_tree_and_info_joiner, ExportedTree, Group,
+};
+
+/// Contents of a Welcome message, obtained with
+/// [`Client::preview_welcome`](crate::Client::preview_welcome) before
+/// deciding whether to join the group.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct JoinPreview {
+    pub group_id: Vec<u8>,
+    pub epoch: u64,
+    pub protocol_version: ProtocolVersion,
+    pub cipher_suite: CipherSuite,
+    /// Group context extensions of the group, such as its required
+    /// capabilities or external senders.
+    pub group_context_extensions: ExtensionList,
+    /// Member that signed the group info of the Welcome message, i.e. the
+    /// member who committed the addition of this client.
+    pub inviter: Member,
+    /// Number of members of the group, including this client.
+    pub member_count: u32,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn preview_welcome(
+        welcome: &MlsMessage,
+        tree_data: Option<ExportedTree<'_>>,
+        config: &C,
+        maybe_time: Option<MlsTime>,
+    ) -> Result<JoinPreview, MlsError> {
+        let group_info = Self::decrypt_group_info(welcome, config).await?;
+        let context = &group_info.group_context;
+
+        let cs = cipher_suite_provider(config.crypto_provider(), context.cipher_suite)?;
+
+        // The tree and the signature are validated as when joining so that
+        // the inviter shown to the user is authentic.
+        let public_tree = validate_tree_and_info_joiner(
+            welcome.version,
+            &group_info,
+            tree_data,
+            &config.identity_provider(),
+            &cs,
+            maybe_time,
+        )
+        .await?;
+
+        let inviter = member_from_leaf_node(
+            public_tree.get_leaf_node(group_info.signer)?,
+            group_info.signer,
+        );
+
+        Ok(JoinPreview {
+            group_id: context.group_id.clone(),
+            epoch: context.epoch,
+            protocol_version: context.protocol_version,
+            cipher_suite: context.cipher_suite,
+            group_context_extensions: context.extensions.clone(),
+            inviter,
+            member_count: public_tree.occupied_leaf_count(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_preview_describes_the_group() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob, kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .commit_builder()
+            .add_member(kp)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let welcome = &commit.welcome_messages[0];
+        let tree = commit.ratchet_tree.clone();
+
+        let preview = bob
+            .preview_welcome(tree.clone(), welcome, None)
+            .await
+            .unwrap();
+
+        assert_eq!(preview.group_id, alice.group_id());
+        assert_eq!(preview.epoch, alice.current_epoch());
+        assert_eq!(preview.cipher_suite, TEST_CIPHER_SUITE);
+        assert_eq!(preview.member_count, 2);
+        assert_eq!(preview.inviter.index, alice.current_member_index());
+
+        assert_eq!(
+            preview.inviter.signing_identity,
+            alice.current_member_signing_identity().unwrap().clone()
+        );
+
+        assert_eq!(preview.group_context_extensions, alice.context().extensions);
+
+        // The preview does not consume the key package
+        let (bob_group, _) = bob.join_group(tree, welcome, None).await.unwrap();
+        assert_eq!(bob_group.group_id(), preview.group_id);
+    }
+}
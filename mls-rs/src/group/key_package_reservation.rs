@@ -0,0 +1,175 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::{error::IntoAnyError, key_package::KeyPackageReservation};
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, key_package::KeyPackageRef, MlsMessage,
+};
+
+use super::Group;
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Reserve `key_packages` in `reservation` before adding them with a
+    /// commit.
+    ///
+    /// Returns the references of the reserved key packages, which should be
+    /// passed to [`Group::release_key_packages`] if the commit is rejected by
+    /// the delivery service. If one of the key packages is already reserved,
+    /// [`MlsError::KeyPackageReserved`] is returned and the key packages
+    /// reserved by this call are released.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn reserve_key_packages<R: KeyPackageReservation>(
+        &self,
+        reservation: &R,
+        key_packages: &[MlsMessage],
+    ) -> Result<Vec<KeyPackageRef>, MlsError> {
+        let mut reserved = Vec::with_capacity(key_packages.len());
+
+        let res = self
+            .reserve_each_key_package(reservation, key_packages, &mut reserved)
+            .await;
+
+        if res.is_err() {
+            self.release_key_packages(reservation, &reserved).await?;
+        }
+
+        res.map(|_| reserved)
+    }
+
+    /// Release key packages reserved with [`Group::reserve_key_packages`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn release_key_packages<R: KeyPackageReservation>(
+        &self,
+        reservation: &R,
+        key_packages: &[KeyPackageRef],
+    ) -> Result<(), MlsError> {
+        for key_package in key_packages {
+            reservation
+                .release(key_package)
+                .await
+                .map_err(|e| MlsError::KeyPackageReservationError(e.into_any_error()))?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn reserve_each_key_package<R: KeyPackageReservation>(
+        &self,
+        reservation: &R,
+        key_packages: &[MlsMessage],
+        reserved: &mut Vec<KeyPackageRef>,
+    ) -> Result<(), MlsError> {
+        for key_package in key_packages {
+            let reference = key_package
+                .key_package_reference(&self.cipher_suite_provider)
+                .await?
+                .ok_or(MlsError::UnexpectedMessageType)?;
+
+            let is_reserved = reservation
+                .reserve(&reference)
+                .await
+                .map_err(|e| MlsError::KeyPackageReservationError(e.into_any_error()))?;
+
+            if !is_reserved {
+                return Err(MlsError::KeyPackageReserved(reference));
+            }
+
+            reserved.push(reference);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_n_member_group,
+        key_package::test_utils::test_key_package_message,
+        storage_provider::in_memory::InMemoryKeyPackageReservation,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn reserved_key_package_cannot_be_reserved_by_other_committer() {
+        let groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let reservation = InMemoryKeyPackageReservation::new();
+
+        let carol =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+        let dave = test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "dave").await;
+
+        let reserved = groups[0]
+            .reserve_key_packages(&reservation, core::slice::from_ref(&carol))
+            .await
+            .unwrap();
+
+        let res = groups[1]
+            .reserve_key_packages(&reservation, &[dave.clone(), carol.clone()])
+            .await;
+
+        assert_matches!(res, Err(MlsError::KeyPackageReserved(r)) if r == reserved[0]);
+
+        // The reservation of dave was released
+        groups[0]
+            .reserve_key_packages(&reservation, &[dave])
+            .await
+            .unwrap();
+
+        // Carol can be reserved again after the first commit was rejected
+        groups[0]
+            .release_key_packages(&reservation, &reserved)
+            .await
+            .unwrap();
+
+        groups[1]
+            .reserve_key_packages(&reservation, &[carol])
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_package_added_by_overlapping_commit_is_rejected() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let carol =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let commit = groups[0]
+            .commit_builder()
+            .add_member(carol.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        groups[1]
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let res = groups[1]
+            .commit_builder()
+            .add_member(carol)
+            .unwrap()
+            .build()
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::KeyPackageAlreadyAdded(2)));
+    }
+}
@@ -19,10 +19,12 @@ use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::error::IntoAnyError;
+use mls_rs_core::secret::KeyScheduleSecret;
 use zeroize::Zeroizing;
 
 use crate::crypto::{HpkeContextR, HpkeContextS, HpkePublicKey, HpkeSecretKey};
 
+use super::component_operation::{ComponentID, ComponentSecretLabel};
 use super::epoch::{EpochSecrets, SenderDataSecret};
 use super::message_signature::AuthenticatedContent;
 
@@ -68,6 +70,25 @@ impl Debug for KeySchedule {
     }
 }
 
+#[cfg(feature = "compliance")]
+impl KeySchedule {
+    pub(crate) fn from_init_secret(init_secret: Vec<u8>) -> Self {
+        Self::new(InitSecret(Zeroizing::new(init_secret)))
+    }
+
+    pub(crate) fn init_secret_bytes(&self) -> &[u8] {
+        &self.init_secret.0
+    }
+
+    pub(crate) fn exporter_secret_bytes(&self) -> &[u8] {
+        &self.exporter_secret
+    }
+
+    pub(crate) fn external_secret_bytes(&self) -> &[u8] {
+        &self.external_secret
+    }
+}
+
 pub(crate) struct KeyScheduleDerivationResult {
     pub(crate) key_schedule: KeySchedule,
     pub(crate) confirmation_key: Zeroizing<Vec<u8>>,
@@ -114,18 +135,24 @@ impl KeySchedule {
         cipher_suite_provider: &P,
     ) -> Result<KeyScheduleDerivationResult, MlsError> {
         let joiner_seed = cipher_suite_provider
-            .kdf_extract(&last_key_schedule.init_secret.0, commit_secret)
+            .kdf_extract_secret(
+                &last_key_schedule.init_secret.0.clone().into(),
+                &commit_secret.to_vec().into(),
+            )
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
-        let joiner_secret = kdf_expand_with_label(
+        // The joiner secret is sent to new members, so it must be
+        // extractable.
+        let joiner_secret = kdf_expand_with_label_secret(
             cipher_suite_provider,
             &joiner_seed,
             b"joiner",
             &context.mls_encode_to_vec()?,
             None,
         )
-        .await?
+        .await
+        .and_then(into_bytes)?
         .into();
 
         let key_schedule_result = Self::from_joiner(
@@ -159,9 +186,14 @@ impl KeySchedule {
             get_pre_epoch_secret(cipher_suite_provider, psk_secret, joiner_secret).await?;
         let context = context.mls_encode_to_vec()?;
 
-        let epoch_secret =
-            kdf_expand_with_label(cipher_suite_provider, &epoch_seed, b"epoch", &context, None)
-                .await?;
+        let epoch_secret = kdf_expand_with_label_secret(
+            cipher_suite_provider,
+            &epoch_seed,
+            b"epoch",
+            &context,
+            None,
+        )
+        .await?;
 
         Self::from_epoch_secret(
             cipher_suite_provider,
@@ -180,7 +212,7 @@ impl KeySchedule {
     ) -> Result<KeyScheduleDerivationResult, MlsError> {
         let epoch_secret = cipher_suite_provider
             .random_bytes_vec(cipher_suite_provider.kdf_extract_size())
-            .map(Zeroizing::new)
+            .map(KeyScheduleSecret::from)
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
         Self::from_epoch_secret(
@@ -195,12 +227,15 @@ impl KeySchedule {
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn from_epoch_secret<P: CipherSuiteProvider>(
         cipher_suite_provider: &P,
-        epoch_secret: &[u8],
+        epoch_secret: &KeyScheduleSecret,
         #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
         secret_tree_size: u32,
     ) -> Result<KeyScheduleDerivationResult, MlsError> {
         let secrets_producer = SecretsProducer::new(cipher_suite_provider, epoch_secret);
 
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        let encryption_secret = secrets_producer.derive(b"encryption").await?;
+
         let epoch_secrets = EpochSecrets {
             #[cfg(feature = "psk")]
             resumption_secret: PreSharedKey::from(secrets_producer.derive(b"resumption").await?),
@@ -208,10 +243,9 @@ impl KeySchedule {
                 secrets_producer.derive(b"sender data").await?,
             ),
             #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
-            secret_tree: SecretTree::new(
-                secret_tree_size,
-                secrets_producer.derive(b"encryption").await?,
-            ),
+            secret_tree: SecretTree::new(secret_tree_size, encryption_secret.clone()),
+            #[cfg(feature = "secret_tree_recovery")]
+            encryption_secret: encryption_secret.into(),
         };
 
         let key_schedule = Self {
@@ -252,6 +286,46 @@ impl KeySchedule {
         kdf_expand_with_label(cipher_suite, &secret, b"exported", &context_hash, Some(len)).await
     }
 
+    /// Derive a secret for the component `component_id` from the extension
+    /// secret of the epoch, which is itself exported with the label
+    /// `extension`. Secrets of different components are independent.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn export_component_secret<P: CipherSuiteProvider>(
+        &self,
+        component_id: ComponentID,
+        label: &[u8],
+        context: &[u8],
+        len: usize,
+        cipher_suite: &P,
+    ) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        let extension_secret = self
+            .export_secret(
+                b"extension",
+                &[],
+                cipher_suite.kdf_extract_size(),
+                cipher_suite,
+            )
+            .await?;
+
+        let component_label = ComponentSecretLabel::new(component_id, label).get_bytes()?;
+
+        let secret = kdf_expand_with_label(
+            cipher_suite,
+            &extension_secret,
+            b"component",
+            &component_label,
+            None,
+        )
+        .await?;
+
+        let context_hash = cipher_suite
+            .hash(context)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        kdf_expand_with_label(cipher_suite, &secret, b"exported", &context_hash, Some(len)).await
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn get_membership_tag<P: CipherSuiteProvider>(
         &self,
@@ -327,6 +401,32 @@ pub(crate) async fn kdf_expand_with_label<P: CipherSuiteProvider>(
         .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
 }
 
+/// Same as [kdf_expand_with_label] for a secret that may be non-extractable.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn kdf_expand_with_label_secret<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    secret: &KeyScheduleSecret,
+    label: &[u8],
+    context: &[u8],
+    len: Option<usize>,
+) -> Result<KeyScheduleSecret, MlsError> {
+    let len = len.unwrap_or(cipher_suite_provider.kdf_extract_size());
+    let label = Label::new(len as u16, label, context);
+
+    cipher_suite_provider
+        .kdf_expand_secret(secret, &label.mls_encode_to_vec()?, len)
+        .await
+        .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+}
+
+// Secrets that are stored or used outside of provider operations must be
+// extractable.
+pub(crate) fn into_bytes(secret: KeyScheduleSecret) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+    secret
+        .into_extractable()
+        .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 pub(crate) async fn kdf_derive_secret<P: CipherSuiteProvider>(
     cipher_suite_provider: &P,
@@ -353,25 +453,32 @@ impl From<Zeroizing<Vec<u8>>> for JoinerSecret {
     }
 }
 
+#[cfg(feature = "compliance")]
+impl JoinerSecret {
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 pub(crate) async fn get_pre_epoch_secret<P: CipherSuiteProvider>(
     cipher_suite_provider: &P,
     psk_secret: &PskSecret,
     joiner_secret: &JoinerSecret,
-) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+) -> Result<KeyScheduleSecret, MlsError> {
     cipher_suite_provider
-        .kdf_extract(&joiner_secret.0, psk_secret)
+        .kdf_extract_secret(&joiner_secret.0.clone().into(), psk_secret)
         .await
         .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
 }
 
 struct SecretsProducer<'a, P: CipherSuiteProvider> {
     cipher_suite_provider: &'a P,
-    epoch_secret: &'a [u8],
+    epoch_secret: &'a KeyScheduleSecret,
 }
 
 impl<'a, P: CipherSuiteProvider> SecretsProducer<'a, P> {
-    fn new(cipher_suite_provider: &'a P, epoch_secret: &'a [u8]) -> Self {
+    fn new(cipher_suite_provider: &'a P, epoch_secret: &'a KeyScheduleSecret) -> Self {
         Self {
             cipher_suite_provider,
             epoch_secret,
@@ -383,7 +490,15 @@ impl<'a, P: CipherSuiteProvider> SecretsProducer<'a, P> {
     // lengths match in the crypto provider
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn derive(&self, label: &[u8]) -> Result<Zeroizing<Vec<u8>>, MlsError> {
-        kdf_derive_secret(self.cipher_suite_provider, self.epoch_secret, label).await
+        kdf_expand_with_label_secret(
+            self.cipher_suite_provider,
+            self.epoch_secret,
+            label,
+            &[],
+            None,
+        )
+        .await
+        .and_then(into_bytes)
     }
 }
 
@@ -448,7 +563,7 @@ impl InitSecret {
 
 pub(crate) struct WelcomeSecret<'a, P: CipherSuiteProvider> {
     cipher_suite: &'a P,
-    key: Zeroizing<Vec<u8>>,
+    key: KeyScheduleSecret,
     nonce: Zeroizing<Vec<u8>>,
 }
 
@@ -462,19 +577,22 @@ impl<'a, P: CipherSuiteProvider> WelcomeSecret<'a, P> {
         let welcome_secret = get_welcome_secret(cipher_suite, joiner_secret, psk_secret).await?;
 
         let key_len = cipher_suite.aead_key_size();
-        let key = kdf_expand_with_label(cipher_suite, &welcome_secret, b"key", &[], Some(key_len))
-            .await?;
+
+        let key =
+            kdf_expand_with_label_secret(cipher_suite, &welcome_secret, b"key", &[], Some(key_len))
+                .await?;
 
         let nonce_len = cipher_suite.aead_nonce_size();
 
-        let nonce = kdf_expand_with_label(
+        let nonce = kdf_expand_with_label_secret(
             cipher_suite,
             &welcome_secret,
             b"nonce",
             &[],
             Some(nonce_len),
         )
-        .await?;
+        .await
+        .and_then(into_bytes)?;
 
         Ok(Self {
             cipher_suite,
@@ -486,7 +604,7 @@ impl<'a, P: CipherSuiteProvider> WelcomeSecret<'a, P> {
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, MlsError> {
         self.cipher_suite
-            .aead_seal(&self.key, plaintext, None, &self.nonce)
+            .aead_seal_with_secret(&self.key, plaintext, None, &self.nonce)
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
     }
@@ -494,20 +612,20 @@ impl<'a, P: CipherSuiteProvider> WelcomeSecret<'a, P> {
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn decrypt(&self, ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>, MlsError> {
         self.cipher_suite
-            .aead_open(&self.key, ciphertext, None, &self.nonce)
+            .aead_open_with_secret(&self.key, ciphertext, None, &self.nonce)
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
     }
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-async fn get_welcome_secret<P: CipherSuiteProvider>(
+pub(crate) async fn get_welcome_secret<P: CipherSuiteProvider>(
     cipher_suite: &P,
     joiner_secret: &JoinerSecret,
     psk_secret: &PskSecret,
-) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+) -> Result<KeyScheduleSecret, MlsError> {
     let epoch_seed = get_pre_epoch_secret(cipher_suite, psk_secret, joiner_secret).await?;
-    kdf_derive_secret(cipher_suite, &epoch_seed, b"welcome").await
+    kdf_expand_with_label_secret(cipher_suite, &epoch_seed, b"welcome", &[], None).await
 }
 
 #[cfg(test)]
@@ -717,7 +835,7 @@ mod tests {
                         .await
                         .unwrap();
 
-                assert_eq!(*welcome, epoch.welcome_secret);
+                assert_eq!(welcome.as_bytes(), Some(&*epoch.welcome_secret));
 
                 let expected: Vec<u8> = key_schedule_res.joiner_secret.into();
                 assert_eq!(epoch.joiner_secret, expected);
@@ -904,13 +1022,15 @@ mod tests {
 
             let welcome_secret =
                 get_welcome_secret(cs, &key_schedule_res.joiner_secret, &psk_secret)
+                    .unwrap()
+                    .extractable()
                     .unwrap()
                     .to_vec();
 
             KeyScheduleEpoch {
                 commit_secret,
                 welcome_secret,
-                psk_secret: psk_secret.to_vec(),
+                psk_secret: psk_secret.as_bytes().unwrap().to_vec(),
                 group_context: group_context.mls_encode_to_vec().unwrap(),
                 joiner_secret: key_schedule_res.joiner_secret.into(),
                 init_secret: key_schedule_res.key_schedule.init_secret.0.to_vec(),
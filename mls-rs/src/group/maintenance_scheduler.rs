@@ -0,0 +1,237 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::time::Duration;
+
+use mls_rs_core::{crypto::HpkePublicKey, time::MlsTime};
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::{CommitOutput, Group};
+
+/// When [`MaintenanceScheduler`] considers an empty commit to be due.
+///
+/// A commit is due as soon as one of the configured limits is reached. With
+/// no limit configured, a commit is never due.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MaintenancePolicy {
+    /// Maximum time since the leaf of this member was last updated.
+    pub max_age: Option<Duration>,
+    /// Maximum number of application messages since the leaf of this member
+    /// was last updated.
+    pub max_messages: Option<u32>,
+}
+
+impl MaintenancePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_age(self, max_age: Duration) -> Self {
+        Self {
+            max_age: Some(max_age),
+            ..self
+        }
+    }
+
+    pub fn with_max_messages(self, max_messages: u32) -> Self {
+        Self {
+            max_messages: Some(max_messages),
+            ..self
+        }
+    }
+}
+
+/// Schedule empty commits that update the path of this member for
+/// post-compromise security.
+///
+/// The scheduler tracks the HPKE public key of the leaf of this member. Any
+/// change of the key, whether by a commit of this member or by an update
+/// proposal committed by another member, resets the age and the message count,
+/// so that no empty commit is produced right after the leaf was updated.
+///
+/// The scheduler is not part of the group state and should be kept next to
+/// the group by the application. After restoring a group, a new scheduler
+/// starts counting from the time of its first use.
+#[derive(Clone, Debug)]
+pub struct MaintenanceScheduler {
+    policy: MaintenancePolicy,
+    leaf_public_key: Option<HpkePublicKey>,
+    last_update: MlsTime,
+    messages: u32,
+}
+
+impl MaintenanceScheduler {
+    pub fn new(policy: MaintenancePolicy) -> Self {
+        Self {
+            policy,
+            leaf_public_key: None,
+            last_update: MlsTime::default(),
+            messages: 0,
+        }
+    }
+
+    pub fn policy(&self) -> &MaintenancePolicy {
+        &self.policy
+    }
+
+    /// Count an application message sent or received in the group.
+    pub fn record_message(&mut self) {
+        self.messages = self.messages.saturating_add(1);
+    }
+
+    /// Whether an empty commit should be sent in `group` at time `now`.
+    ///
+    /// No commit is due while `group` has a pending commit, since applying it
+    /// updates the leaf of this member.
+    pub fn is_due<C>(&mut self, group: &Group<C>, now: MlsTime) -> Result<bool, MlsError>
+    where
+        C: ClientConfig + Clone,
+    {
+        let leaf_public_key = &group.current_user_leaf_node()?.public_key;
+
+        if self.leaf_public_key.as_ref() != Some(leaf_public_key) {
+            self.leaf_public_key = Some(leaf_public_key.clone());
+            self.last_update = now;
+            self.messages = 0;
+        }
+
+        if group.has_pending_commit() {
+            return Ok(false);
+        }
+
+        let age = Duration::from_secs(
+            now.seconds_since_epoch()
+                .saturating_sub(self.last_update.seconds_since_epoch()),
+        );
+
+        let aged = self.policy.max_age.map_or(false, |max_age| age >= max_age);
+
+        let busy = self
+            .policy
+            .max_messages
+            .map_or(false, |max_messages| self.messages >= max_messages);
+
+        Ok(aged || busy)
+    }
+
+    /// Create an empty commit with a full update path in `group` if one is
+    /// [due](MaintenanceScheduler::is_due).
+    ///
+    /// The commit is pending and must be applied with
+    /// [`Group::apply_pending_commit`] once accepted by the delivery service.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn commit_if_due<C>(
+        &mut self,
+        group: &mut Group<C>,
+        now: MlsTime,
+    ) -> Result<Option<CommitOutput>, MlsError>
+    where
+        C: ClientConfig + Clone,
+    {
+        if !self.is_due(group, now)? {
+            return Ok(None);
+        }
+
+        group.commit_builder().build().await.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use core::time::Duration;
+
+    use mls_rs_core::time::MlsTime;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_n_member_group,
+    };
+
+    use super::{MaintenancePolicy, MaintenanceScheduler};
+
+    const DAY: Duration = Duration::from_secs(86400);
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_is_due_after_max_age() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let mut scheduler = MaintenanceScheduler::new(MaintenancePolicy::new().with_max_age(DAY));
+
+        let start = MlsTime::from(1000);
+
+        assert!(!scheduler.is_due(&groups[0], start).unwrap());
+        assert!(!scheduler.is_due(&groups[0], start + DAY / 2).unwrap());
+
+        let output = scheduler
+            .commit_if_due(&mut groups[0], start + DAY)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Nothing is due until the pending commit is applied
+        assert!(!scheduler.is_due(&groups[0], start + DAY * 2).unwrap());
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        groups[1]
+            .process_message(output.commit_message)
+            .await
+            .unwrap();
+
+        // The updated leaf resets the schedule
+        assert!(!scheduler.is_due(&groups[0], start + DAY * 2).unwrap());
+        assert!(scheduler.is_due(&groups[0], start + DAY * 3).unwrap());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_is_due_after_max_messages() {
+        let groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let mut scheduler =
+            MaintenanceScheduler::new(MaintenancePolicy::new().with_max_messages(2));
+
+        let now = MlsTime::from(1000);
+
+        assert!(!scheduler.is_due(&groups[0], now).unwrap());
+
+        scheduler.record_message();
+        assert!(!scheduler.is_due(&groups[0], now).unwrap());
+
+        scheduler.record_message();
+        assert!(scheduler.is_due(&groups[0], now).unwrap());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn leaf_update_by_other_commit_resets_schedule() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let mut scheduler = MaintenanceScheduler::new(MaintenancePolicy::new().with_max_age(DAY));
+
+        let start = MlsTime::from(1000);
+        assert!(!scheduler.is_due(&groups[0], start).unwrap());
+
+        // A commit of this member with a path, e.g. for another purpose
+        let commit = groups[0].commit(vec![]).await.unwrap();
+        groups[0].process_pending_commit().await.unwrap();
+
+        groups[1]
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert!(!scheduler.is_due(&groups[0], start + DAY).unwrap());
+
+        // A commit of another member doesn't update the leaf of this member
+        let commit = groups[1].commit(vec![]).await.unwrap();
+        groups[1].process_pending_commit().await.unwrap();
+
+        groups[0]
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert!(scheduler.is_due(&groups[0], start + DAY * 2).unwrap());
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use mls_rs_core::{
+    error::IntoAnyError,
+    identity::{IdentityProvider, SigningIdentity},
+    time::MlsTime,
+};
+
+use crate::{client::MlsError, client_config::ClientConfig, tree_kem::leaf_node::LeafNodeSource};
+
+use super::Group;
+
+/// Part of a leaf that expires, see [`Group::expiring_members`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExpiryKind {
+    /// Lifetime of a leaf node created from a key package. The member can
+    /// replace it by sending an update proposal or a commit with a path,
+    /// since the resulting leaf node has no lifetime.
+    LeafLifetime,
+    /// Credential expiration reported by
+    /// [`IdentityProvider::credential_expiration`]. The member must obtain a
+    /// new credential and update its leaf node with it.
+    Credential,
+}
+
+/// Member whose leaf node expires, see [`Group::expiring_members`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MemberExpiry {
+    /// Leaf index of the member.
+    pub index: u32,
+    pub signing_identity: SigningIdentity,
+    pub kind: ExpiryKind,
+    pub not_after: MlsTime,
+}
+
+impl MemberExpiry {
+    /// Determine if the expiration already happened at `time`.
+    pub fn is_expired(&self, time: MlsTime) -> bool {
+        time > self.not_after
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Members whose leaf lifetime or credential expires within `within`
+    /// from now, including members that already expired.
+    ///
+    /// Expired leaf nodes fail validation when new members join the group,
+    /// so affected members should update their leaf in time. Reports are
+    /// sorted by expiration time, a member can have one report of each
+    /// [`ExpiryKind`].
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn expiring_members(&self, within: Duration) -> Result<Vec<MemberExpiry>, MlsError> {
+        self.expiring_members_at(MlsTime::now(), within).await
+    }
+
+    /// Same as [`Group::expiring_members`] relative to `time` instead of the
+    /// current time.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn expiring_members_at(
+        &self,
+        time: MlsTime,
+        within: Duration,
+    ) -> Result<Vec<MemberExpiry>, MlsError> {
+        let deadline = time.saturating_add(within);
+        let identity_provider = self.config.identity_provider();
+        let mut reports = Vec::new();
+
+        for (index, leaf) in self.current_epoch_tree().non_empty_leaves() {
+            let mut report = |kind, not_after| {
+                if not_after <= deadline {
+                    reports.push(MemberExpiry {
+                        index: *index,
+                        signing_identity: leaf.signing_identity.clone(),
+                        kind,
+                        not_after,
+                    });
+                }
+            };
+
+            if let LeafNodeSource::KeyPackage(lifetime) = &leaf.leaf_node_source {
+                report(ExpiryKind::LeafLifetime, lifetime.not_after);
+            }
+
+            let credential_expiration = identity_provider
+                .credential_expiration(&leaf.signing_identity)
+                .await
+                .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+            if let Some(not_after) = credential_expiration {
+                report(ExpiryKind::Credential, not_after);
+            }
+        }
+
+        reports.sort_by_key(|r| r.not_after);
+
+        Ok(reports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+        tree_kem::{leaf_node::LeafNodeSource, node::LeafIndex},
+    };
+
+    use super::ExpiryKind;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_with_expiring_lifetime_are_reported() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+        let bob_index = bob.current_member_index();
+
+        let leaf = alice
+            .current_epoch_tree()
+            .get_leaf_node(LeafIndex::unchecked(bob_index))
+            .unwrap();
+
+        let LeafNodeSource::KeyPackage(lifetime) = &leaf.leaf_node_source else {
+            panic!("bob joined with a key package");
+        };
+
+        let not_after = lifetime.not_after;
+        let within = Duration::from_secs(60);
+
+        let reports = alice
+            .expiring_members_at(not_after - Duration::from_secs(10), within)
+            .await
+            .unwrap();
+
+        let report = reports.iter().find(|r| r.index == bob_index).unwrap();
+
+        assert_eq!(report.kind, ExpiryKind::LeafLifetime);
+        assert_eq!(report.not_after, not_after);
+        assert!(!report.is_expired(not_after));
+        assert!(report.is_expired(not_after + within));
+
+        let reports = alice
+            .expiring_members_at(not_after - Duration::from_secs(120), within)
+            .await
+            .unwrap();
+
+        assert!(!reports.iter().any(|r| r.index == bob_index));
+    }
+}
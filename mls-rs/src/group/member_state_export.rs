@@ -0,0 +1,305 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider, CryptoProvider},
+    protocol_version::ProtocolVersion,
+    psk::{ExternalPskId, PreSharedKey},
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    psk::{
+        secret::{PskSecret, PskSecretInput},
+        JustPreSharedKeyID, PreSharedKeyID,
+    },
+};
+
+use super::{
+    sealed_group_info::get_external_psk, sealed_snapshot::SealedSnapshotHeader, snapshot::Snapshot,
+    snapshot_format::SnapshotFormat, CommitOutput, Group,
+};
+
+/// The state of this member in a group, encrypted with a key derived from an
+/// external pre-shared key, used to move a membership to another device.
+///
+/// The export contains the leaf private keys, signing key and epoch secrets
+/// of the member. It is created by [`Group::export_member_state`] and
+/// imported with
+/// [`Client::import_member_state`](crate::Client::import_member_state).
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct MemberStateExport {
+    pub(crate) header: SealedSnapshotHeader,
+    pub(crate) psk_id: PreSharedKeyID,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub(crate) ciphertext: Vec<u8>,
+}
+
+impl Debug for MemberStateExport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemberStateExport")
+            .field("header", &self.header)
+            .field("psk_id", &self.psk_id)
+            .field(
+                "ciphertext",
+                &mls_rs_core::debug::pretty_bytes(&self.ciphertext),
+            )
+            .finish()
+    }
+}
+
+impl MemberStateExport {
+    /// Protocol version of the group.
+    pub fn version(&self) -> ProtocolVersion {
+        self.header.version
+    }
+
+    /// Cipher suite of the group.
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.header.cipher_suite
+    }
+
+    /// Id of the group the member state belongs to.
+    pub fn group_id(&self) -> &[u8] {
+        &self.header.group_id
+    }
+
+    /// Epoch of the group at the time of the export.
+    pub fn epoch(&self) -> u64 {
+        self.header.epoch
+    }
+
+    /// Id of the external pre-shared key required to import the member state.
+    pub fn external_psk_id(&self) -> Option<&ExternalPskId> {
+        match &self.psk_id.key_id {
+            JustPreSharedKeyID::External(id) => Some(id),
+            JustPreSharedKeyID::Resumption(_) => None,
+        }
+    }
+
+    /// Deserialize a member state export from bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Serialize a member state export for transport.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn seal<P: CipherSuiteProvider>(
+        cipher_suite_provider: &P,
+        snapshot: &Snapshot,
+        psk_id: ExternalPskId,
+        psk: PreSharedKey,
+    ) -> Result<Self, MlsError> {
+        let psk_id =
+            PreSharedKeyID::new(JustPreSharedKeyID::External(psk_id), cipher_suite_provider)?;
+
+        let header = SealedSnapshotHeader::new(&snapshot.state.context);
+        let secret = psk_secret(cipher_suite_provider, &psk_id, psk).await?;
+        let plaintext = Zeroizing::new(snapshot.to_bytes(SnapshotFormat::default())?);
+
+        let ciphertext = header
+            .seal(
+                cipher_suite_provider,
+                &secret,
+                &psk_id.mls_encode_to_vec()?,
+                &plaintext,
+            )
+            .await?;
+
+        Ok(Self {
+            header,
+            psk_id,
+            ciphertext,
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn open<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        psk: PreSharedKey,
+    ) -> Result<Snapshot, MlsError> {
+        let secret = psk_secret(cipher_suite_provider, &self.psk_id, psk).await?;
+
+        let plaintext = self
+            .header
+            .open(
+                cipher_suite_provider,
+                &secret,
+                &self.psk_id.mls_encode_to_vec()?,
+                &self.ciphertext,
+            )
+            .await?;
+
+        let snapshot = Snapshot::from_bytes(&plaintext)?;
+        self.header.verify(&snapshot.state.context)?;
+
+        Ok(snapshot)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn psk_secret<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    psk_id: &PreSharedKeyID,
+    psk: PreSharedKey,
+) -> Result<PskSecret, MlsError> {
+    let input = PskSecretInput {
+        id: psk_id.clone(),
+        psk: psk.into(),
+    };
+
+    PskSecret::calculate(&[input], cipher_suite_provider).await
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Encrypt the state of this member with the external pre-shared key
+    /// identified by `psk_id` so that the membership can be resumed on
+    /// another device.
+    ///
+    /// The group is consumed so that it can't be used on this device anymore.
+    /// Any copy of the group in the
+    /// [GroupStateStorage](crate::GroupStateStorage) of this client should be
+    /// deleted by the application. The keys of the exported leaf remain valid
+    /// until the importing device commits a path update, see
+    /// [`Client::import_member_state`](crate::Client::import_member_state).
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn export_member_state(
+        self,
+        psk_id: ExternalPskId,
+    ) -> Result<MemberStateExport, MlsError> {
+        let psk = get_external_psk(&self.config.secret_store(), &psk_id).await?;
+
+        MemberStateExport::seal(&self.cipher_suite_provider, &self.snapshot()?, psk_id, psk).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn import_member_state(
+        config: C,
+        export: &MemberStateExport,
+        self_update: bool,
+    ) -> Result<(Self, Option<CommitOutput>), MlsError> {
+        let psk_id = export.external_psk_id().ok_or(MlsError::UnexpectedPskId)?;
+
+        let psk = get_external_psk(&config.secret_store(), psk_id).await?;
+
+        let cipher_suite_provider = config
+            .crypto_provider()
+            .cipher_suite_provider(export.cipher_suite())
+            .ok_or(MlsError::UnsupportedCipherSuite(export.cipher_suite()))?;
+
+        let snapshot = export.open(&cipher_suite_provider, psk).await?;
+        let mut group = Group::from_snapshot(config, snapshot).await?;
+
+        let commit_output = if self_update {
+            Some(group.commit(Vec::new()).await?)
+        } else {
+            None
+        };
+
+        Ok((group, commit_output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TestClientConfig, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{
+            sealed_snapshot::test_utils::new_device, test_utils::test_group_custom_config, Group,
+        },
+        psk::{ExternalPskId, PreSharedKey},
+        Client,
+    };
+
+    use super::MemberStateExport;
+
+    fn psk_id() -> ExternalPskId {
+        ExternalPskId::new(b"transfer psk".to_vec())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn device_with_psk(psk: PreSharedKey) -> Client<TestClientConfig> {
+        new_device(|builder| builder.psk(psk_id(), psk)).await
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn member_state_can_be_moved_to_another_device() {
+        let psk = PreSharedKey::from(b"psk".to_vec());
+
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |c| {
+            c.psk(psk_id(), psk.clone())
+        })
+        .await;
+
+        let (mut bob, _) = alice.join("bob").await;
+
+        let export = alice
+            .group
+            .clone()
+            .export_member_state(psk_id())
+            .await
+            .unwrap();
+
+        assert_eq!(export.epoch(), 1);
+        assert_eq!(export.external_psk_id(), Some(&psk_id()));
+
+        let export = MemberStateExport::from_bytes(&export.to_bytes().unwrap()).unwrap();
+
+        let (mut moved, commit) = device_with_psk(psk)
+            .await
+            .import_member_state(&export, true)
+            .await
+            .unwrap();
+
+        assert!(Group::equal_group_state(&alice, &moved));
+
+        let commit = commit.unwrap();
+        let old_leaf = alice.current_user_leaf_node().unwrap().clone();
+
+        moved.apply_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        assert_ne!(moved.current_user_leaf_node().unwrap(), &old_leaf);
+        assert_eq!(moved.context(), bob.context());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn importing_requires_transfer_psk() {
+        let psk = PreSharedKey::from(b"psk".to_vec());
+
+        let alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |c| {
+            c.psk(psk_id(), psk.clone())
+        })
+        .await;
+
+        let export = alice.group.export_member_state(psk_id()).await.unwrap();
+
+        let res = device_with_psk(PreSharedKey::from(b"other psk".to_vec()))
+            .await
+            .import_member_state(&export, false)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+    }
+}
@@ -0,0 +1,362 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider},
+    error::IntoAnyError,
+    identity::{IdentityProvider, SigningIdentity},
+};
+
+use crate::{client::MlsError, client_config::ClientConfig, signer::Signable};
+
+use super::Group;
+
+const MEMBERSHIP_STATEMENT_EXPORTER_LABEL: &[u8] = b"membership statement";
+
+/// A member listed in a [`MembershipStatement`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MembershipStatementEntry {
+    /// Leaf index of the member.
+    pub leaf_index: u32,
+    /// Hash of the identity of the member as returned by
+    /// [`IdentityProvider::identity`].
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub identity_hash: Vec<u8>,
+}
+
+impl Debug for MembershipStatementEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MembershipStatementEntry")
+            .field("leaf_index", &self.leaf_index)
+            .field(
+                "identity_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.identity_hash),
+            )
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct MembershipStatementTBS<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    tree_hash: &'a [u8],
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    epoch_binding: &'a [u8],
+    members: &'a [MembershipStatementEntry],
+    signer: &'a SigningIdentity,
+}
+
+/// Signed statement of the membership of a group at a specific epoch.
+///
+/// A statement is created by a member with
+/// [`Group::signed_membership_statement`] and signed with that member's
+/// MLS signature key. It can be stored outside of the group and later
+/// checked with [`Client::verify_membership_statement`](crate::Client::verify_membership_statement),
+/// for example by another device of the same user restoring from a backup.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MembershipStatement {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub(crate) group_id: Vec<u8>,
+    pub(crate) epoch: u64,
+    pub(crate) cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub(crate) tree_hash: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub(crate) epoch_binding: Vec<u8>,
+    pub(crate) members: Vec<MembershipStatementEntry>,
+    pub(crate) signer: SigningIdentity,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub(crate) signature: Vec<u8>,
+}
+
+impl Debug for MembershipStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MembershipStatement")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "tree_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.tree_hash),
+            )
+            .field(
+                "epoch_binding",
+                &mls_rs_core::debug::pretty_bytes(&self.epoch_binding),
+            )
+            .field("members", &self.members)
+            .field("signer", &self.signer)
+            .field(
+                "signature",
+                &mls_rs_core::debug::pretty_bytes(&self.signature),
+            )
+            .finish()
+    }
+}
+
+impl MembershipStatement {
+    /// Group id of the group the statement was created for.
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    /// Epoch of the group the statement was created in.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Cipher suite of the group.
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    /// Tree hash of the group at [`MembershipStatement::epoch`].
+    pub fn tree_hash(&self) -> &[u8] {
+        &self.tree_hash
+    }
+
+    /// Value exported from the key schedule of [`MembershipStatement::epoch`].
+    ///
+    /// Members of the same epoch can compare this value with
+    /// [`Group::export_secret`] to check that the signer was a member.
+    pub fn epoch_binding(&self) -> &[u8] {
+        &self.epoch_binding
+    }
+
+    /// Members of the group, ordered by leaf index.
+    pub fn members(&self) -> &[MembershipStatementEntry] {
+        &self.members
+    }
+
+    /// Signing identity of the member that created the statement.
+    pub fn signer(&self) -> &SigningIdentity {
+        &self.signer
+    }
+
+    /// Deserialize a statement from bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Serialize a statement for storage or transport.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    fn tbs(&self) -> MembershipStatementTBS<'_> {
+        MembershipStatementTBS {
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            cipher_suite: self.cipher_suite,
+            tree_hash: &self.tree_hash,
+            epoch_binding: &self.epoch_binding,
+            members: &self.members,
+            signer: &self.signer,
+        }
+    }
+
+    /// Check the signature of the statement against the signature key of
+    /// [`MembershipStatement::signer`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn verify_signature<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+    ) -> Result<(), MlsError> {
+        if cipher_suite_provider.cipher_suite() != self.cipher_suite {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        self.verify(cipher_suite_provider, &self.signer.signature_key, &())
+            .await
+    }
+}
+
+impl<'a> Signable<'a> for MembershipStatement {
+    const SIGN_LABEL: &'static str = "MembershipStatementTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        self.tbs().mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a [`MembershipStatement`] describing the membership of the
+    /// group in the current epoch, signed by this member.
+    ///
+    /// The statement contains the tree hash, a hash of the identity of each
+    /// member and a value exported from the key schedule of the current
+    /// epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn signed_membership_statement(&self) -> Result<MembershipStatement, MlsError> {
+        let context = self.context();
+        let identity_provider = self.config.identity_provider();
+        let mut members = Vec::new();
+
+        for member in self.roster().members_iter() {
+            let identity = identity_provider
+                .identity(&member.signing_identity, &context.extensions)
+                .await
+                .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+            let identity_hash = self
+                .cipher_suite_provider
+                .hash(&identity)
+                .await
+                .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+            members.push(MembershipStatementEntry {
+                leaf_index: member.index,
+                identity_hash,
+            });
+        }
+
+        let epoch_binding = self
+            .export_secret(
+                MEMBERSHIP_STATEMENT_EXPORTER_LABEL,
+                &context.group_id,
+                self.cipher_suite_provider.kdf_extract_size(),
+            )
+            .await?;
+
+        let mut statement = MembershipStatement {
+            group_id: context.group_id.clone(),
+            epoch: context.epoch,
+            cipher_suite: context.cipher_suite,
+            tree_hash: context.tree_hash.clone(),
+            epoch_binding: epoch_binding.as_bytes().to_vec(),
+            members,
+            signer: self.current_member_signing_identity()?.clone(),
+            signature: Vec::new(),
+        };
+
+        statement
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        Ok(statement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group,
+    };
+
+    use super::{MembershipStatement, MEMBERSHIP_STATEMENT_EXPORTER_LABEL};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn membership_statement_describes_current_epoch() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        group
+            .join_with_custom_config("bob", false, |_| {})
+            .await
+            .unwrap();
+
+        let statement = group.signed_membership_statement().await.unwrap();
+
+        assert_eq!(statement.epoch(), group.current_epoch());
+        assert_eq!(statement.group_id(), group.group_id());
+        assert_eq!(statement.tree_hash(), group.context().tree_hash);
+        assert_eq!(statement.members().len(), 2);
+
+        let exported = group
+            .export_secret(
+                MEMBERSHIP_STATEMENT_EXPORTER_LABEL,
+                group.group_id(),
+                statement.epoch_binding().len(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(statement.epoch_binding(), exported.as_bytes());
+
+        let decoded = MembershipStatement::from_bytes(&statement.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(decoded, statement);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn membership_statement_verifies_for_other_device_of_same_user() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let statement = group.signed_membership_statement().await.unwrap();
+
+        // Same credential as the group creator with a different signature key
+        let (other_device, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "member").await;
+
+        other_device
+            .verify_membership_statement(&statement)
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn membership_statement_from_other_user_is_rejected() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let statement = group.signed_membership_statement().await.unwrap();
+
+        let (bob, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let res = bob.verify_membership_statement(&statement).await;
+
+        assert_matches!(res, Err(MlsError::MembershipStatementSignerMismatch));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn membership_statement_with_modified_roster_is_rejected() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let mut statement = group.signed_membership_statement().await.unwrap();
+
+        statement.members[0].identity_hash[0] ^= 1;
+
+        let (other_device, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "member").await;
+
+        let res = other_device.verify_membership_statement(&statement).await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+}
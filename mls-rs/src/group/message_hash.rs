@@ -34,4 +34,8 @@ impl MessageHash {
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
             .map(Self)
     }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
 }
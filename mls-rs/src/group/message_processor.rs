@@ -9,8 +9,10 @@
 ))]
 use super::SelfRemoveProposal;
 use super::{
+    app_versions::{app_versions_change, AppVersionsChange},
     commit_sender,
     confirmation_tag::ConfirmationTag,
+    feature_flags::{feature_flags_change, FeatureFlagsChange},
     framing::{
         ApplicationData, Content, ContentType, MlsMessage, MlsMessagePayload, PublicMessage, Sender,
     },
@@ -24,7 +26,7 @@ use super::{
 };
 use crate::{
     client::MlsError,
-    key_package::validate_key_package_properties,
+    key_package::{validate_key_package_properties, KeyPackageRef},
     time::MlsTime,
     tree_kem::{
         leaf_node_validator::{LeafNodeValidator, ValidationContext},
@@ -41,6 +43,7 @@ use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_core::{
+    group::Member,
     identity::{IdentityProvider, MemberValidationContext},
     protocol_version::ProtocolVersion,
     psk::PreSharedKeyStorage,
@@ -49,6 +52,8 @@ use mls_rs_core::{
 #[cfg(feature = "by_ref_proposal")]
 use super::proposal_ref::ProposalRef;
 
+use super::unknown_type_policy::{UnknownType, UnknownTypeChecker};
+
 #[cfg(not(feature = "by_ref_proposal"))]
 use crate::group::proposal_cache::resolve_for_commit;
 
@@ -56,7 +61,19 @@ use super::proposal::Proposal;
 use super::proposal_filter::ProposalInfo;
 
 #[cfg(feature = "private_message")]
-use crate::group::framing::PrivateMessage;
+use crate::group::{framing::PrivateMessage, mls_rules::MessageAuthorization};
+
+#[cfg(feature = "targeted_messages")]
+use super::targeted_message::{TargetedMessage, TargetedMessageDescription};
+
+#[cfg(all(feature = "psk", feature = "private_message"))]
+use super::PredecessorApplicationMessageDescription;
+
+#[cfg(any(feature = "key_transparency", feature = "private_message"))]
+use mls_rs_core::error::IntoAnyError;
+
+#[cfg(feature = "key_transparency")]
+use mls_rs_core::identity::SigningIdentity;
 
 #[derive(Debug)]
 pub(crate) struct ProvisionalState {
@@ -66,6 +83,7 @@ pub(crate) struct ProvisionalState {
     pub(crate) external_init_index: Option<LeafIndex>,
     pub(crate) indexes_of_added_kpkgs: Vec<LeafIndex>,
     pub(crate) unused_proposals: Vec<ProposalInfo<Proposal>>,
+    pub(crate) filtered_proposals: Vec<FilteredProposal>,
 }
 
 //By default, the path field of a Commit MUST be populated. The path field MAY be omitted if
@@ -144,6 +162,38 @@ impl NewEpoch {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[repr(u8)]
+#[non_exhaustive]
+/// Reason a proposal was not applied by a commit.
+pub enum ProposalFilterReason {
+    /// The proposal was cached when the commit was received, but the committer
+    /// did not include it.
+    NotCommitted = 1u8,
+    /// The proposal was removed by
+    /// [`MlsRules::filter_proposals`](crate::MlsRules::filter_proposals) of
+    /// this client.
+    FilteredByRules = 2u8,
+    /// The proposal was found invalid and left out while creating the commit.
+    Invalid = 3u8,
+}
+
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+/// A proposal that was not applied by a commit.
+pub struct FilteredProposal {
+    /// The proposal that was not applied.
+    pub proposal: ProposalInfo<Proposal>,
+    /// Why the proposal was not applied.
+    pub reason: ProposalFilterReason,
+}
+
+impl FilteredProposal {
+    pub(crate) fn new(proposal: ProposalInfo<Proposal>, reason: ProposalFilterReason) -> Self {
+        Self { proposal, reason }
+    }
+}
+
 #[cfg_attr(
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
@@ -228,6 +278,13 @@ pub enum ReceivedMessage {
     Welcome,
     /// Validated key package
     KeyPackage(KeyPackage),
+    /// A targeted message was decrypted.
+    #[cfg(feature = "targeted_messages")]
+    TargetedMessage(TargetedMessageDescription),
+    /// An application message sent in the predecessor of a reinitialized
+    /// group was decrypted.
+    #[cfg(all(feature = "psk", feature = "private_message"))]
+    PredecessorApplicationMessage(PredecessorApplicationMessageDescription),
 }
 
 impl TryFrom<ApplicationMessageDescription> for ReceivedMessage {
@@ -277,8 +334,21 @@ impl From<KeyPackage> for ReceivedMessage {
 pub struct ApplicationMessageDescription {
     /// Index of this user in the group state.
     pub sender_index: u32,
+    /// Member that sent the message, resolved from the ratchet tree.
+    ///
+    /// This is `None` if the message was sent in a prior epoch, in which case
+    /// the current ratchet tree may no longer describe the sender.
+    pub sender: Option<Member>,
+    /// Epoch in which the message was sent.
+    pub epoch: u64,
+    /// Generation of the message in the sequence of application messages
+    /// sent by `sender_index` during `epoch`, starting at 0.
+    ///
+    /// Together with [`Group::sender_generations`](crate::Group::sender_generations)
+    /// this can be used to detect missing messages.
+    pub generation: u32,
     /// Received application data.
-    data: ApplicationData,
+    pub(crate) data: ApplicationData,
     /// Plaintext authenticated data in the received MLS packet.
     pub authenticated_data: Vec<u8>,
 }
@@ -287,6 +357,9 @@ impl Debug for ApplicationMessageDescription {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ApplicationMessageDescription")
             .field("sender_index", &self.sender_index)
+            .field("sender", &self.sender)
+            .field("epoch", &self.epoch)
+            .field("generation", &self.generation)
             .field("data", &self.data)
             .field(
                 "authenticated_data",
@@ -320,6 +393,25 @@ pub struct CommitMessageDescription {
     /// Plaintext authenticated data in the received MLS packet.
     #[mls_codec(with = "mls_rs_codec::byte_vec")]
     pub authenticated_data: Vec<u8>,
+    /// Proposals that were pending or sent with this commit but not applied
+    /// by it, along with the reason each one was left out.
+    pub filtered_proposals: Vec<FilteredProposal>,
+    /// Unknown types found in the proposals and update path of this commit
+    /// and reported by the [`UnknownTypePolicy`](super::UnknownTypePolicy)
+    /// of the client.
+    pub unknown_types: Vec<UnknownType>,
+    /// Confirmed transcript hash of the epoch created by this commit.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub confirmed_transcript_hash: Vec<u8>,
+    /// Interim transcript hash of the epoch created by this commit.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub interim_transcript_hash: Vec<u8>,
+    /// Change of the application protocol versions supported by all members,
+    /// if the commit changed them. See [`Group::app_versions`](crate::Group::app_versions).
+    pub app_versions_change: Option<AppVersionsChange>,
+    /// Change of the features enabled in the group, if the commit changed
+    /// them. See [`Group::feature_flags`](crate::Group::feature_flags).
+    pub feature_flags_change: Option<FeatureFlagsChange>,
 }
 
 impl Debug for CommitMessageDescription {
@@ -332,6 +424,18 @@ impl Debug for CommitMessageDescription {
                 "authenticated_data",
                 &mls_rs_core::debug::pretty_bytes(&self.authenticated_data),
             )
+            .field("filtered_proposals", &self.filtered_proposals)
+            .field("unknown_types", &self.unknown_types)
+            .field(
+                "confirmed_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.confirmed_transcript_hash),
+            )
+            .field(
+                "interim_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.interim_transcript_hash),
+            )
+            .field("app_versions_change", &self.app_versions_change)
+            .field("feature_flags_change", &self.feature_flags_change)
             .finish()
     }
 }
@@ -383,6 +487,9 @@ pub struct ProposalMessageDescription {
     pub authenticated_data: Vec<u8>,
     /// Proposal reference.
     pub proposal_ref: ProposalRef,
+    /// Unknown types found in the proposal and reported by the
+    /// [`UnknownTypePolicy`](super::UnknownTypePolicy) of the client.
+    pub unknown_types: Vec<UnknownType>,
 }
 
 #[cfg(feature = "by_ref_proposal")]
@@ -396,6 +503,7 @@ impl Debug for ProposalMessageDescription {
                 &mls_rs_core::debug::pretty_bytes(&self.authenticated_data),
             )
             .field("proposal_ref", &self.proposal_ref)
+            .field("unknown_types", &self.unknown_types)
             .finish()
     }
 }
@@ -441,6 +549,16 @@ impl ProposalMessageDescription {
         self.proposal_ref.to_vec()
     }
 
+    /// The pre-shared key referenced by the proposal, if it is a
+    /// [`PreSharedKeyProposal`](super::proposal::PreSharedKeyProposal).
+    #[cfg(feature = "psk")]
+    pub fn psk_reference(&self) -> Option<crate::psk::PreSharedKeyReference> {
+        match &self.proposal {
+            Proposal::Psk(psk) => Some(psk.reference()),
+            _ => None,
+        }
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn new<C: CipherSuiteProvider>(
         cs: &C,
@@ -452,6 +570,7 @@ impl ProposalMessageDescription {
             proposal,
             sender: content.content.sender.try_into()?,
             proposal_ref: ProposalRef::from_content(cs, content).await?,
+            unknown_types: Vec::new(),
         })
     }
 }
@@ -475,6 +594,39 @@ pub(crate) enum EventOrContent<E> {
     Content(AuthenticatedContent),
 }
 
+/// Leaves of `new_tree` that were added or replaced compared to `old_tree`,
+/// along with their encoding.
+#[cfg(feature = "key_transparency")]
+pub(crate) fn changed_leaves(
+    old_tree: &TreeKemPublic,
+    new_tree: &TreeKemPublic,
+) -> Result<Vec<(SigningIdentity, Vec<u8>)>, MlsError> {
+    new_tree
+        .non_empty_leaves()
+        .filter(|(index, leaf)| old_tree.get_leaf_node(*index).ok() != Some(*leaf))
+        .map(|(_, leaf)| Ok((leaf.signing_identity.clone(), leaf.mls_encode_to_vec()?)))
+        .collect()
+}
+
+/// Report leaves returned by [`changed_leaves`] to the identity provider
+/// once the commit that changed them was applied.
+#[cfg(feature = "key_transparency")]
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn report_changed_leaves<I: IdentityProvider>(
+    identity_provider: &I,
+    leaves: Vec<(SigningIdentity, Vec<u8>)>,
+    context: &GroupContext,
+) -> Result<(), MlsError> {
+    for (signing_identity, leaf_node) in leaves {
+        identity_provider
+            .leaf_node_observed(&signing_identity, &leaf_node, context)
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+    }
+
+    Ok(())
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
 #[cfg_attr(
@@ -563,6 +715,11 @@ pub(crate) trait MessageProcessor: Send + Sync {
 
                 Ok(EventOrContent::Event(key_package.into()))
             }
+            #[cfg(feature = "targeted_messages")]
+            MlsMessagePayload::Targeted(targeted) => self
+                .process_targeted_message(&targeted)
+                .await
+                .map(EventOrContent::Event),
         }
     }
 
@@ -599,8 +756,9 @@ pub(crate) trait MessageProcessor: Send + Sync {
             Content::Application(data) => {
                 let authenticated_data = auth_content.content.authenticated_data;
                 let sender = auth_content.content.sender;
+                let epoch = auth_content.content.epoch;
 
-                self.process_application_message(data, sender, authenticated_data)
+                self.process_application_message(data, sender, epoch, authenticated_data)
                     .and_then(Self::OutputType::try_from)
             }
             Content::Commit(_) => self
@@ -622,15 +780,41 @@ pub(crate) trait MessageProcessor: Send + Sync {
         &self,
         data: ApplicationData,
         sender: Sender,
+        epoch: u64,
         authenticated_data: Vec<u8>,
     ) -> Result<ApplicationMessageDescription, MlsError> {
         let Sender::Member(sender_index) = sender else {
             return Err(MlsError::InvalidSender);
         };
 
+        let group_state = self.group_state();
+
+        let sender = if epoch == group_state.context.epoch {
+            group_state.member_at_index(sender_index)
+        } else {
+            None
+        };
+
+        let authorization = self
+            .mls_rules()
+            .authorize_application_message(
+                sender_index,
+                sender.as_ref(),
+                &authenticated_data,
+                &group_state.context,
+            )
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
+
+        if authorization == MessageAuthorization::Deny {
+            return Err(MlsError::ApplicationMessageDenied(sender_index));
+        }
+
         Ok(ApplicationMessageDescription {
             authenticated_data,
             sender_index,
+            sender,
+            epoch,
+            generation: self.decrypted_generation().unwrap_or_default(),
             data,
         })
     }
@@ -643,13 +827,18 @@ pub(crate) trait MessageProcessor: Send + Sync {
         proposal: &Proposal,
         cache_proposal: bool,
     ) -> Result<ProposalMessageDescription, MlsError> {
-        let proposal = ProposalMessageDescription::new(
+        let mut unknown_types = self.unknown_type_checker()?;
+        unknown_types.check_proposal(proposal)?;
+
+        let mut proposal = ProposalMessageDescription::new(
             self.cipher_suite_provider(),
             auth_content,
             proposal.clone(),
         )
         .await?;
 
+        proposal.unknown_types = unknown_types.into_reports();
+
         let group_state = self.group_state_mut();
 
         if cache_proposal {
@@ -711,11 +900,27 @@ pub(crate) trait MessageProcessor: Send + Sync {
                 &self.mls_rules(),
                 time_sent,
                 CommitDirection::Receive,
+                self.validated_key_packages(),
             )
             .await?;
 
         let sender = commit_sender(&auth_content.content.sender, &provisional_state)?;
 
+        let mut unknown_types = self.unknown_type_checker()?;
+
+        for addition in &provisional_state.applied_proposals.additions {
+            unknown_types.check_key_package(&addition.proposal.key_package)?;
+        }
+
+        #[cfg(feature = "by_ref_proposal")]
+        for update in &provisional_state.applied_proposals.updates {
+            unknown_types.check_leaf_node(&update.proposal.leaf_node)?;
+        }
+
+        if let Some(path) = &commit.path {
+            unknown_types.check_leaf_node(&path.leaf_node)?;
+        }
+
         //Verify that the path value is populated if the proposals vector contains any Update
         // or Remove proposals, or if it's empty. Otherwise, the path value MAY be omitted.
         if path_update_required(&provisional_state.applied_proposals) && commit.path.is_none() {
@@ -786,6 +991,8 @@ pub(crate) trait MessageProcessor: Send + Sync {
             commit_effect
         };
 
+        let filtered_proposals = core::mem::take(&mut provisional_state.filtered_proposals);
+
         let new_secrets = match update_path {
             Some(update_path) if !is_self_removed => {
                 self.apply_update_path(sender, &update_path, &mut provisional_state)
@@ -809,8 +1016,31 @@ pub(crate) trait MessageProcessor: Send + Sync {
             .tree_hash(self.cipher_suite_provider())
             .await?;
 
+        let confirmed_transcript_hash = provisional_state
+            .group_context
+            .confirmed_transcript_hash
+            .to_vec();
+
+        let interim_transcript_hash_bytes = interim_transcript_hash.to_vec();
+
+        let app_versions_change = app_versions_change(
+            &self.group_state().public_tree,
+            &provisional_state.public_tree,
+        );
+
+        let feature_flags_change = feature_flags_change(
+            &self.group_state().context.extensions,
+            &provisional_state.group_context.extensions,
+        );
+
         if let Some(confirmation_tag) = &auth_content.auth.confirmation_tag {
             if !is_self_removed {
+                #[cfg(feature = "key_transparency")]
+                let observed_leaves = changed_leaves(
+                    &self.group_state().public_tree,
+                    &provisional_state.public_tree,
+                )?;
+
                 // Update the key schedule to calculate new private keys
                 self.update_key_schedule(
                     new_secrets,
@@ -819,12 +1049,26 @@ pub(crate) trait MessageProcessor: Send + Sync {
                     provisional_state,
                 )
                 .await?;
+
+                #[cfg(feature = "key_transparency")]
+                report_changed_leaves(
+                    &self.identity_provider(),
+                    observed_leaves,
+                    &self.group_state().context,
+                )
+                .await?;
             }
             Ok(CommitMessageDescription {
                 is_external: matches!(auth_content.content.sender, Sender::NewMemberCommit),
                 authenticated_data: auth_content.content.authenticated_data,
                 committer: *sender,
                 effect: commit_effect,
+                filtered_proposals,
+                unknown_types: unknown_types.into_reports(),
+                confirmed_transcript_hash,
+                interim_transcript_hash: interim_transcript_hash_bytes,
+                app_versions_change,
+                feature_flags_change,
             })
         } else {
             Err(MlsError::InvalidConfirmationTag)
@@ -857,6 +1101,28 @@ pub(crate) trait MessageProcessor: Send + Sync {
     #[cfg(feature = "private_message")]
     fn min_epoch_available(&self) -> Option<u64>;
 
+    fn unknown_type_checker(&self) -> Result<UnknownTypeChecker, MlsError> {
+        Ok(UnknownTypeChecker::default())
+    }
+
+    /// Key packages that were already validated against the current group
+    /// context and don't need to be validated again when processing a commit.
+    fn validated_key_packages(&self) -> &[KeyPackageRef] {
+        &[]
+    }
+
+    /// Number of epochs a message may be ahead of the current epoch before it
+    /// is reported as [`MlsError::OutOfSync`].
+    fn out_of_sync_epoch_lag(&self) -> Option<u64> {
+        None
+    }
+
+    /// Generation of the key used to decrypt the last private message.
+    #[cfg(feature = "private_message")]
+    fn decrypted_generation(&self) -> Option<u32> {
+        None
+    }
+
     fn check_metadata(&self, message: &MlsMessage) -> Result<(), MlsError> {
         let context = &self.group_state().context;
 
@@ -864,6 +1130,18 @@ pub(crate) trait MessageProcessor: Send + Sync {
             return Err(MlsError::ProtocolVersionMismatch);
         }
 
+        #[cfg(feature = "targeted_messages")]
+        if let MlsMessagePayload::Targeted(targeted) = &message.payload {
+            if targeted.group_id != context.group_id {
+                return Err(MlsError::GroupIdMismatch);
+            }
+
+            // Targeted messages can only be decrypted in the epoch they were sent in
+            if targeted.epoch != context.epoch {
+                return Err(MlsError::InvalidEpoch);
+            }
+        }
+
         if let Some((group_id, epoch, content_type)) = match &message.payload {
             MlsMessagePayload::Plain(plaintext) => Some((
                 &plaintext.content.group_id,
@@ -882,6 +1160,15 @@ pub(crate) trait MessageProcessor: Send + Sync {
                 return Err(MlsError::GroupIdMismatch);
             }
 
+            if let Some(lag) = self.out_of_sync_epoch_lag() {
+                if epoch > context.epoch.saturating_add(lag) {
+                    return Err(MlsError::OutOfSync {
+                        local_epoch: context.epoch,
+                        observed_epoch: epoch,
+                    });
+                }
+            }
+
             match content_type {
                 ContentType::Commit => {
                     if context.epoch != epoch {
@@ -965,6 +1252,12 @@ pub(crate) trait MessageProcessor: Send + Sync {
         cipher_text: &PrivateMessage,
     ) -> Result<EventOrContent<Self::OutputType>, MlsError>;
 
+    #[cfg(feature = "targeted_messages")]
+    async fn process_targeted_message(
+        &mut self,
+        message: &TargetedMessage,
+    ) -> Result<Self::OutputType, MlsError>;
+
     async fn verify_plaintext_authentication(
         &self,
         message: PublicMessage,
@@ -1037,6 +1330,27 @@ mod tests {
         group::{test_utils::get_test_group_context, GroupState, Sender},
     };
 
+    #[cfg(feature = "private_message")]
+    use crate::{
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        client_builder::test_utils::TestClientBuilder,
+        group::{
+            mls_rules::{
+                CommitDirection, CommitOptions, CommitSource, EncryptionOptions,
+                MessageAuthorization, MlsRules,
+            },
+            proposal_filter::ProposalBundle,
+            test_utils::test_group,
+            GroupContext, ReceivedMessage, Roster,
+        },
+    };
+
+    #[cfg(feature = "private_message")]
+    use assert_matches::assert_matches;
+
+    #[cfg(feature = "private_message")]
+    use mls_rs_core::group::Member;
+
     use super::{CommitEffect, NewEpoch};
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
@@ -1071,4 +1385,105 @@ mod tests {
             Vec::<CommitEffect>::mls_decode(&mut &*bytes).unwrap()
         );
     }
+
+    #[cfg(feature = "private_message")]
+    #[derive(Clone, Debug)]
+    struct MutedMembers(Vec<u32>);
+
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl MlsRules for MutedMembers {
+        type Error = MlsError;
+
+        async fn filter_proposals(
+            &self,
+            _: CommitDirection,
+            _: CommitSource,
+            _: &Roster,
+            _: &GroupContext,
+            proposals: ProposalBundle,
+        ) -> Result<ProposalBundle, MlsError> {
+            Ok(proposals)
+        }
+
+        fn commit_options(
+            &self,
+            _: &Roster,
+            _: &GroupContext,
+            _: &ProposalBundle,
+        ) -> Result<CommitOptions, MlsError> {
+            Ok(Default::default())
+        }
+
+        fn encryption_options(
+            &self,
+            _: &Roster,
+            _: &GroupContext,
+        ) -> Result<EncryptionOptions, MlsError> {
+            Ok(Default::default())
+        }
+
+        fn authorize_application_message(
+            &self,
+            sender_index: u32,
+            _: Option<&Member>,
+            authenticated_data: &[u8],
+            _: &GroupContext,
+        ) -> Result<MessageAuthorization, MlsError> {
+            if self.0.contains(&sender_index) && authenticated_data != b"moderator" {
+                Ok(MessageAuthorization::Deny)
+            } else {
+                Ok(MessageAuthorization::Allow)
+            }
+        }
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn application_messages_from_muted_members_are_denied() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .mls_rules(MutedMembers(vec![alice.current_member_index()]))
+            .build();
+
+        let key_package = bob
+            .generate_key_package_message(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let commit = alice
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let (mut bob, _) = bob
+            .join_group(commit.ratchet_tree, &commit.welcome_messages[0], None)
+            .await
+            .unwrap();
+
+        let message = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let res = bob.process_incoming_message(message).await;
+        assert_matches!(res, Err(MlsError::ApplicationMessageDenied(0)));
+
+        let message = alice
+            .encrypt_application_message(b"hello", b"moderator".to_vec())
+            .await
+            .unwrap();
+
+        let res = bob.process_incoming_message(message).await;
+        assert_matches!(res, Ok(ReceivedMessage::ApplicationMessage(_)));
+    }
 }
@@ -6,11 +6,13 @@ use crate::group::{proposal_filter::ProposalBundle, Roster};
 
 #[cfg(feature = "private_message")]
 use crate::{
-    group::{padding::PaddingMode, Sender},
+    group::{padding::PaddingMode, ContentType, Sender},
     WireFormat,
 };
 
 use alloc::boxed::Box;
+#[cfg(feature = "private_message")]
+use alloc::vec::Vec;
 use core::convert::Infallible;
 use mls_rs_core::{error::IntoAnyError, group::Member, identity::SigningIdentity};
 
@@ -30,6 +32,17 @@ pub enum CommitSource {
     NewMember(SigningIdentity),
 }
 
+/// Result of [`MlsRules::authorize_application_message`].
+#[cfg(feature = "private_message")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageAuthorization {
+    /// The message is returned to the application.
+    Allow,
+    /// The message is dropped and processing fails with
+    /// [`MlsError::ApplicationMessageDenied`](crate::client::MlsError::ApplicationMessageDenied).
+    Deny,
+}
+
 /// Options controlling commit generation
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
@@ -174,6 +187,25 @@ pub trait MlsRules: Send + Sync {
         proposals: &ProposalBundle,
     ) -> Result<CommitOptions, Self::Error>;
 
+    /// This is called when preparing a commit right after [commit_options](MlsRules::commit_options)
+    /// to adjust the returned `options` for this specific commit, e.g. to require an update path or
+    /// publish a group info allowing external commits depending on the proposals in the commit.
+    ///
+    /// Unlike in [commit_options](MlsRules::commit_options), the `current_roster` and
+    /// `current_context` describe the group state before the commit, and `source` is the
+    /// committer. The returned options are used for the whole commit, including the
+    /// welcome messages and the group info. By default, `options` are returned unchanged.
+    fn adjust_commit_options(
+        &self,
+        options: CommitOptions,
+        _source: &CommitSource,
+        _current_roster: &Roster,
+        _current_context: &GroupContext,
+        _proposals: &ProposalBundle,
+    ) -> Result<CommitOptions, Self::Error> {
+        Ok(options)
+    }
+
     /// This is called when sending any packet. For proposals and commits, this determines whether to
     /// encrypt them. For any encrypted packet, this determines the padding mode used.
     ///
@@ -184,6 +216,63 @@ pub trait MlsRules: Send + Sync {
         current_roster: &Roster,
         current_context: &GroupContext,
     ) -> Result<EncryptionOptions, Self::Error>;
+
+    /// This is called when encrypting or decrypting a private message to get
+    /// application context the message is bound to, such as a conversation or
+    /// tenant identifier.
+    ///
+    /// The returned value is never sent. It is appended to the additional
+    /// data used to encrypt the message content, so the message can only be
+    /// decrypted by members that return the same value. Each member of a
+    /// group MUST return the same value for the group.
+    ///
+    /// For messages of a prior epoch, the group context of that epoch is
+    /// passed.
+    #[cfg(feature = "private_message")]
+    fn context_binding(&self, _context: &GroupContext) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(None)
+    }
+
+    /// This is called when receiving an application message, after it was
+    /// decrypted and authenticated and before its content is returned, e.g.
+    /// to drop messages from members that are muted or banned by the
+    /// application.
+    ///
+    /// `sender` is `None` if the message was sent in a prior epoch, in which
+    /// case the current ratchet tree may no longer describe the sender at
+    /// `sender_index`. Denied messages are reported as
+    /// [`SecurityEventKind::UnauthorizedSender`](crate::group::SecurityEventKind::UnauthorizedSender)
+    /// when the `security_events` feature is enabled. By default, all
+    /// messages are allowed.
+    #[cfg(feature = "private_message")]
+    fn authorize_application_message(
+        &self,
+        _sender_index: u32,
+        _sender: Option<&Member>,
+        _authenticated_data: &[u8],
+        _current_context: &GroupContext,
+    ) -> Result<MessageAuthorization, Self::Error> {
+        Ok(MessageAuthorization::Allow)
+    }
+
+    /// This is called when the application requests `wire_format` for a
+    /// single proposal or commit instead of the one derived from
+    /// [encryption_options](MlsRules::encryption_options), e.g. with
+    /// [`CommitBuilder::wire_format`](crate::group::CommitBuilder::wire_format).
+    ///
+    /// Returning `false` fails the operation with
+    /// [`MlsError::WireFormatOverrideDenied`](crate::client::MlsError::WireFormatOverrideDenied).
+    /// By default, all overrides are allowed.
+    #[cfg(feature = "private_message")]
+    fn allow_wire_format_override(
+        &self,
+        _wire_format: WireFormat,
+        _content_type: ContentType,
+        _current_roster: &Roster,
+        _current_context: &GroupContext,
+    ) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
 }
 
 macro_rules! delegate_mls_rules {
@@ -216,6 +305,23 @@ macro_rules! delegate_mls_rules {
                 (**self).commit_options(roster, context, proposals)
             }
 
+            fn adjust_commit_options(
+                &self,
+                options: CommitOptions,
+                source: &CommitSource,
+                current_roster: &Roster,
+                current_context: &GroupContext,
+                proposals: &ProposalBundle,
+            ) -> Result<CommitOptions, Self::Error> {
+                (**self).adjust_commit_options(
+                    options,
+                    source,
+                    current_roster,
+                    current_context,
+                    proposals,
+                )
+            }
+
             fn encryption_options(
                 &self,
                 roster: &Roster,
@@ -223,6 +329,46 @@ macro_rules! delegate_mls_rules {
             ) -> Result<EncryptionOptions, Self::Error> {
                 (**self).encryption_options(roster, context)
             }
+
+            #[cfg(feature = "private_message")]
+            fn context_binding(
+                &self,
+                context: &GroupContext,
+            ) -> Result<Option<Vec<u8>>, Self::Error> {
+                (**self).context_binding(context)
+            }
+
+            #[cfg(feature = "private_message")]
+            fn authorize_application_message(
+                &self,
+                sender_index: u32,
+                sender: Option<&Member>,
+                authenticated_data: &[u8],
+                current_context: &GroupContext,
+            ) -> Result<MessageAuthorization, Self::Error> {
+                (**self).authorize_application_message(
+                    sender_index,
+                    sender,
+                    authenticated_data,
+                    current_context,
+                )
+            }
+
+            #[cfg(feature = "private_message")]
+            fn allow_wire_format_override(
+                &self,
+                wire_format: WireFormat,
+                content_type: ContentType,
+                current_roster: &Roster,
+                current_context: &GroupContext,
+            ) -> Result<bool, Self::Error> {
+                (**self).allow_wire_format_override(
+                    wire_format,
+                    content_type,
+                    current_roster,
+                    current_context,
+                )
+            }
         }
     };
 }
@@ -236,6 +382,8 @@ delegate_mls_rules!(&T);
 pub struct DefaultMlsRules {
     pub commit_options: CommitOptions,
     pub encryption_options: EncryptionOptions,
+    #[cfg(feature = "private_message")]
+    pub context_binding: Option<Vec<u8>>,
 }
 
 impl DefaultMlsRules {
@@ -249,15 +397,25 @@ impl DefaultMlsRules {
     pub fn with_commit_options(self, commit_options: CommitOptions) -> Self {
         Self {
             commit_options,
-            encryption_options: self.encryption_options,
+            ..self
         }
     }
 
     /// Set encryption options.
     pub fn with_encryption_options(self, encryption_options: EncryptionOptions) -> Self {
         Self {
-            commit_options: self.commit_options,
             encryption_options,
+            ..self
+        }
+    }
+
+    /// Bind private messages to `context_binding`. See
+    /// [`MlsRules::context_binding`].
+    #[cfg(feature = "private_message")]
+    pub fn with_context_binding(self, context_binding: Vec<u8>) -> Self {
+        Self {
+            context_binding: Some(context_binding),
+            ..self
         }
     }
 }
@@ -294,4 +452,9 @@ impl MlsRules for DefaultMlsRules {
     ) -> Result<EncryptionOptions, Self::Error> {
         Ok(self.encryption_options)
     }
+
+    #[cfg(feature = "private_message")]
+    fn context_binding(&self, _: &GroupContext) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.context_binding.clone())
+    }
 }
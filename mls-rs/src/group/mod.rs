@@ -9,7 +9,7 @@ use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::error::IntoAnyError;
 #[cfg(feature = "last_resort_key_package_ext")]
 use mls_rs_core::extension::MlsExtension;
-use mls_rs_core::identity::MemberValidationContext;
+use mls_rs_core::identity::{MemberValidationContext, MemberValidationPurpose};
 use mls_rs_core::secret::Secret;
 use mls_rs_core::time::MlsTime;
 use snapshot::PendingCommitSnapshot;
@@ -51,6 +51,15 @@ use self::mls_rules::{EncryptionOptions, MlsRules};
 #[cfg(feature = "psk")]
 pub use self::resumption::ReinitClient;
 
+#[cfg(all(feature = "psk", feature = "private_message"))]
+use self::resumption::PredecessorGroup;
+
+#[cfg(feature = "private_message")]
+use self::ratchet_checkpoint::RatchetCheckpoint;
+
+#[cfg(all(feature = "psk", feature = "private_message"))]
+pub use self::resumption::PredecessorApplicationMessageDescription;
+
 #[cfg(feature = "psk")]
 use crate::psk::{
     resolver::PskResolver, secret::PskSecretInput, ExternalPskId, JustPreSharedKeyID, PskGroupId,
@@ -87,13 +96,16 @@ use secret_tree::*;
 use self::epoch::PriorEpoch;
 
 use self::epoch::EpochSecrets;
+use self::intent::IntentLog;
 pub use self::message_processor::{
-    ApplicationMessageDescription, CommitEffect, CommitMessageDescription, NewEpoch,
-    ProposalMessageDescription, ProposalSender, ReceivedMessage,
+    ApplicationMessageDescription, CommitEffect, CommitMessageDescription, FilteredProposal,
+    NewEpoch, ProposalFilterReason, ProposalMessageDescription, ProposalSender, ReceivedMessage,
 };
 use self::message_processor::{EventOrContent, MessageProcessor, ProvisionalState};
 #[cfg(feature = "by_ref_proposal")]
 use self::proposal_ref::ProposalRef;
+#[cfg(feature = "by_ref_proposal")]
+use self::sent_proposals::SentProposalLog;
 use self::state_repo::GroupStateRepository;
 pub use group_info::GroupInfo;
 
@@ -105,38 +117,168 @@ pub use roster::*;
 pub(crate) use mls_rs_core::group::ConfirmedTranscriptHash;
 pub(crate) use util::*;
 
+pub use self::security_watermark::{ForwardSecrecyPolicy, SecurityWatermark, WatermarkViolation};
+pub use mls_rs_core::group::SecurityWatermarkStorage;
+
+use self::signature_keys::stored_signer;
+
 #[cfg(all(feature = "by_ref_proposal", feature = "external_client"))]
 pub use self::message_processor::CachedProposal;
 
+#[cfg(feature = "targeted_messages")]
+pub use self::targeted_message::TargetedMessageDescription;
+
+pub use self::membership_statement::{MembershipStatement, MembershipStatementEntry};
+
+pub use self::cipher_suite_policy::{
+    CipherSuiteDowngrade, CipherSuitePolicy, CipherSuiteSelection, DowngradeAction,
+};
+
+#[cfg(feature = "psk")]
+pub use self::sealed_group_info::SealedGroupInfo;
+
+#[cfg(feature = "psk")]
+pub use self::member_state_export::MemberStateExport;
+
+pub use self::offline_signing::{UnsignedCommit, UnsignedCommitGroupInfo};
+
+pub use self::unknown_type_policy::{
+    ExtensionContainer, UnknownExtension, UnknownType, UnknownTypeAction, UnknownTypePolicy,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+pub use self::offline_signing::UnsignedProposal;
+
+pub use self::rebase::RebasedCommit;
+
+#[cfg(feature = "prior_epoch_transcript_hash")]
+pub use self::transcript_hash::TranscriptHashes;
+
+pub use self::app_versions::{AppVersionRange, AppVersionsChange, AppVersionsExt};
+pub use self::armored_message::ArmorOptions;
+#[cfg(feature = "broadcast")]
+pub use self::broadcast::{BroadcastMessage, BroadcastReceiver, BroadcastSender};
+
+#[cfg(feature = "broadcast")]
+use self::broadcast::BroadcastReservation;
+pub use self::budgeted_processing::{BudgetedProcessing, ResumeToken};
+
+pub use self::archive::{ArchiveRecord, ArchivedEpoch, ArchivedMember, GroupArchive};
+pub(crate) use self::capability_negotiation::negotiate_group_context_extensions;
+pub use self::capability_report::{CapabilityReport, CapabilitySupport};
+#[cfg(feature = "compliance")]
+pub use self::compliance::{
+    CaseOutcome, CaseResult, ComplianceCategory, ComplianceReport, ComplianceRunner,
+};
+#[cfg(feature = "private_message")]
+pub use self::cover_traffic::{CoverSizeDistribution, CoverTraffic, UniformSize};
+pub use self::divergence::{
+    Divergence, DivergenceReport, EpochAuthenticator, EpochDigest, EpochHistory,
+};
+pub use self::emergency_rekey::EmergencyRekeyOutput;
+pub use self::feature_flags::{FeatureFlag, FeatureFlagsChange, FeatureFlagsExt};
+pub use self::fingerprints::MemberFingerprints;
+pub use self::group_alias::GroupAliasExt;
+pub use self::intent::{IntentOutcome, IntentResult};
+pub use self::join_preview::JoinPreview;
+pub use self::maintenance_scheduler::{MaintenancePolicy, MaintenanceScheduler};
+pub use self::member_expiry::{ExpiryKind, MemberExpiry};
+#[cfg(feature = "psk")]
+pub use self::recovery_psk::{RecoveryPskExt, RecoveryPskRules, RecoveryPskUse};
+#[cfg(feature = "by_ref_proposal")]
+pub use self::sent_proposals::{SentProposal, SentProposalStatus};
+pub use self::size_estimate::CommitSizeEstimate;
+pub use self::snapshot_format::{SnapshotFormat, SNAPSHOT_MAGIC};
+pub use self::stale_members::{
+    LeafActivity, StaleMember, StaleMemberAction, StaleMemberPolicy, StaleMemberTracker,
+};
+
+#[cfg(feature = "security_events")]
+pub use self::security_event::{SecurityEvent, SecurityEventKind, SecurityEventSink};
+
+mod app_versions;
+mod archive;
+mod armored_message;
+#[cfg(feature = "broadcast")]
+mod broadcast;
+mod budgeted_processing;
+mod capability_negotiation;
+mod capability_report;
 #[cfg(feature = "private_message")]
 mod ciphertext_processor;
 
+mod cipher_suite_policy;
 mod commit;
+#[cfg(feature = "compliance")]
+mod compliance;
 pub mod component_operation;
+mod compressed_message;
 pub(crate) mod confirmation_tag;
+#[cfg(feature = "private_message")]
+mod cover_traffic;
+#[cfg(feature = "private_message")]
+pub(crate) mod decrypt_only;
+mod divergence;
+mod emergency_rekey;
 pub(crate) mod epoch;
+mod feature_flags;
+mod fingerprints;
 pub(crate) mod framing;
+mod group_alias;
 mod group_info;
+mod intent;
+mod join_preview;
+mod key_package_reservation;
 pub(crate) mod key_schedule;
+mod maintenance_scheduler;
+mod member_expiry;
+#[cfg(feature = "psk")]
+mod member_state_export;
+mod membership_statement;
 mod membership_tag;
 pub(crate) mod message_hash;
 pub(crate) mod message_processor;
 pub(crate) mod message_signature;
 pub(crate) mod message_verifier;
 pub mod mls_rules;
+mod offline_signing;
 #[cfg(feature = "private_message")]
 pub(crate) mod padding;
 /// Proposals to evolve a MLS [`Group`]
 pub mod proposal;
-mod proposal_cache;
+pub(crate) mod proposal_cache;
 pub(crate) mod proposal_filter;
 #[cfg(feature = "by_ref_proposal")]
 pub(crate) mod proposal_ref;
+#[cfg(feature = "provider_api")]
+pub(crate) mod provider_rules;
+#[cfg(feature = "psk")]
+mod psk_proposal;
+#[cfg(feature = "private_message")]
+mod ratchet_checkpoint;
+mod rebase;
+#[cfg(feature = "psk")]
+mod recovery_psk;
 #[cfg(feature = "psk")]
 mod resumption;
 mod roster;
+#[cfg(feature = "psk")]
+pub(crate) mod sealed_group_info;
+mod sealed_snapshot;
+#[cfg(feature = "security_events")]
+pub(crate) mod security_event;
+pub(crate) mod security_watermark;
+#[cfg(feature = "by_ref_proposal")]
+mod sent_proposals;
+pub(crate) mod signature_keys;
+mod size_estimate;
 pub(crate) mod snapshot;
+mod snapshot_format;
+mod stale_members;
 pub(crate) mod state;
+mod state_blob;
+#[cfg(feature = "targeted_messages")]
+pub(crate) mod targeted_message;
 
 #[cfg(feature = "prior_epoch")]
 pub(crate) mod state_repo;
@@ -146,6 +288,9 @@ pub(crate) mod state_repo_light;
 pub(crate) use state_repo_light as state_repo;
 
 pub(crate) mod transcript_hash;
+pub(crate) mod unknown_type_policy;
+
+use unknown_type_policy::UnknownTypeChecker;
 mod util;
 
 /// External commit building.
@@ -154,9 +299,18 @@ pub mod external_commit;
 #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
 pub(crate) mod secret_tree;
 
+#[cfg(feature = "private_message")]
+mod sender_generations;
+
+#[cfg(feature = "private_message")]
+pub use sender_generations::SenderGenerations;
+
 #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
 pub use secret_tree::MessageKeyData as MessageKey;
 
+#[cfg(feature = "secret_tree_recovery")]
+mod secret_tree_recovery;
+
 #[cfg(all(test, feature = "rfc_compliant"))]
 mod interop_test_vectors;
 
@@ -222,19 +376,32 @@ impl Debug for Welcome {
 pub struct NewMemberInfo {
     /// Group info extensions found within the Welcome message used to join
     /// the group.
+    ///
+    /// The [`RatchetTreeExt`](crate::extension::built_in::RatchetTreeExt) is
+    /// not included if the client was built with
+    /// [`ClientBuilder::keep_welcome_ratchet_tree`](crate::client_builder::ClientBuilder::keep_welcome_ratchet_tree)
+    /// set to `false`.
     pub group_info_extensions: ExtensionList,
     /// The group member who generated the Commit adding the joiner to the
     /// group. This may not be the party who generated the corresponding
     /// add proposal
     pub sender: u32,
+    /// Unknown types found in the group info and ratchet tree and reported
+    /// by the [`UnknownTypePolicy`] of the client.
+    pub unknown_types: Vec<UnknownType>,
 }
 
 #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen)]
 impl NewMemberInfo {
-    pub(crate) fn new(group_info_extensions: ExtensionList, sender: u32) -> Self {
+    pub(crate) fn new(
+        group_info_extensions: ExtensionList,
+        sender: u32,
+        unknown_types: Vec<UnknownType>,
+    ) -> Self {
         let mut new_member_info = Self {
             group_info_extensions,
             sender,
+            unknown_types,
         };
 
         new_member_info.ungrease();
@@ -277,8 +444,25 @@ where
     pending_updates:
         crate::map::SmallMap<HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>)>,
     pending_commit: PendingCommitSnapshot,
+    intent_log: IntentLog,
     #[cfg(feature = "psk")]
     previous_psk: Option<PskSecretInput>,
+    #[cfg(all(feature = "psk", feature = "private_message"))]
+    predecessor: Option<PredecessorGroup<C>>,
+    #[cfg(feature = "psk")]
+    replaced_group_id: Option<Vec<u8>>,
+    #[cfg(feature = "by_ref_proposal")]
+    offloaded_proposals: Vec<ProposalRef>,
+    #[cfg(feature = "by_ref_proposal")]
+    sent_proposals: SentProposalLog,
+    validated_key_packages: Vec<KeyPackageRef>,
+    #[cfg(feature = "private_message")]
+    decrypted_generation: Option<u32>,
+    #[cfg(feature = "private_message")]
+    ratchet_checkpoint: RatchetCheckpoint,
+    #[cfg(feature = "broadcast")]
+    broadcast_reservation: BroadcastReservation,
+    forward_secrecy: ForwardSecrecyPolicy,
     #[cfg(test)]
     pub(crate) commit_modifiers: CommitModifiers,
     pub(crate) signer: SignatureSecretKey,
@@ -347,7 +531,8 @@ where
             &cipher_suite_provider,
             &identity_provider,
             member_validation_context,
-        );
+        )
+        .with_purpose(MemberValidationPurpose::NewGroup);
 
         leaf_node_validator
             .check_if_valid(
@@ -362,7 +547,8 @@ where
             config.group_state_storage(),
             config.key_package_repo(),
             None,
-        )?;
+        )?
+        .with_snapshot_format(config.snapshot_format());
 
         let key_schedule_result = KeySchedule::from_random_epoch_secret(
             &cipher_suite_provider,
@@ -385,6 +571,8 @@ where
         )
         .await?;
 
+        let forward_secrecy = ForwardSecrecyPolicy::from_config(&config);
+
         Ok(Self {
             config,
             state: GroupState::new(context, public_tree, interim_hash, confirmation_tag),
@@ -393,6 +581,7 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: Default::default(),
             pending_commit: Default::default(),
+            intent_log: Default::default(),
             #[cfg(test)]
             commit_modifiers: Default::default(),
             epoch_secrets: key_schedule_result.epoch_secrets,
@@ -400,6 +589,22 @@ where
             cipher_suite_provider,
             #[cfg(feature = "psk")]
             previous_psk: None,
+            #[cfg(all(feature = "psk", feature = "private_message"))]
+            predecessor: None,
+            #[cfg(feature = "psk")]
+            replaced_group_id: None,
+            #[cfg(feature = "by_ref_proposal")]
+            offloaded_proposals: Vec::new(),
+            #[cfg(feature = "by_ref_proposal")]
+            sent_proposals: Default::default(),
+            validated_key_packages: Vec::new(),
+            #[cfg(feature = "private_message")]
+            decrypted_generation: None,
+            #[cfg(feature = "private_message")]
+            ratchet_checkpoint: Default::default(),
+            #[cfg(feature = "broadcast")]
+            broadcast_reservation: Default::default(),
+            forward_secrecy,
             signer,
         })
     }
@@ -433,7 +638,7 @@ where
         #[cfg(feature = "psk")] additional_psk: Option<PskSecretInput>,
         maybe_time: Option<MlsTime>,
     ) -> Result<(Self, NewMemberInfo), MlsError> {
-        let (group_info, key_package_generation, group_secrets, psk_secret) =
+        let (mut group_info, key_package_generation, group_secrets, psk_secret) =
             Self::decrypt_group_info_internal(
                 welcome,
                 &config,
@@ -449,10 +654,11 @@ where
 
         let id_provider = config.identity_provider();
 
-        let public_tree = validate_tree_and_info_joiner(
+        let public_tree = validate_tree_and_info_welcome(
             welcome.version,
-            &group_info,
+            &mut group_info,
             tree_data,
+            config.keep_welcome_ratchet_tree(),
             &id_provider,
             &cipher_suite_provider,
             maybe_time,
@@ -519,6 +725,15 @@ where
             return Err(MlsError::InvalidConfirmationTag);
         }
 
+        // A key package generated with a per group signature key is signed
+        // with that key rather than with the signer of the client.
+        let signer = stored_signer(
+            &config,
+            &key_package.leaf_node.signing_identity.signature_key,
+        )
+        .await?
+        .unwrap_or(signer);
+
         Self::join_with(
             config,
             group_info,
@@ -544,6 +759,19 @@ where
         used_key_package_ref: Option<KeyPackageRef>,
         signer: SignatureSecretKey,
     ) -> Result<(Self, NewMemberInfo), MlsError> {
+        let mut unknown_types = UnknownTypeChecker::new(
+            config.unknown_type_policy(),
+            config.supported_extensions(),
+            config.supported_custom_proposals(),
+            &group_info.group_context.extensions,
+        )?;
+
+        unknown_types.check_extensions(ExtensionContainer::GroupInfo, &group_info.extensions)?;
+
+        for (_, leaf_node) in public_tree.non_empty_leaves() {
+            unknown_types.check_leaf_node(leaf_node)?;
+        }
+
         let cs = group_info.group_context.cipher_suite;
 
         let cs = config
@@ -566,7 +794,10 @@ where
             config.group_state_storage(),
             config.key_package_repo(),
             used_key_package_ref,
-        )?;
+        )?
+        .with_snapshot_format(config.snapshot_format());
+
+        let forward_secrecy = ForwardSecrecyPolicy::from_config(&config);
 
         let group = Group {
             config,
@@ -581,6 +812,7 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: Default::default(),
             pending_commit: Default::default(),
+            intent_log: Default::default(),
             #[cfg(test)]
             commit_modifiers: Default::default(),
             epoch_secrets,
@@ -588,12 +820,32 @@ where
             cipher_suite_provider: cs,
             #[cfg(feature = "psk")]
             previous_psk: None,
+            #[cfg(all(feature = "psk", feature = "private_message"))]
+            predecessor: None,
+            #[cfg(feature = "psk")]
+            replaced_group_id: None,
+            #[cfg(feature = "by_ref_proposal")]
+            offloaded_proposals: Vec::new(),
+            #[cfg(feature = "by_ref_proposal")]
+            sent_proposals: Default::default(),
+            validated_key_packages: Vec::new(),
+            #[cfg(feature = "private_message")]
+            decrypted_generation: None,
+            #[cfg(feature = "private_message")]
+            ratchet_checkpoint: Default::default(),
+            #[cfg(feature = "broadcast")]
+            broadcast_reservation: Default::default(),
+            forward_secrecy,
             signer,
         };
 
         Ok((
             group,
-            NewMemberInfo::new(group_info.extensions, *group_info.signer),
+            NewMemberInfo::new(
+                group_info.extensions,
+                *group_info.signer,
+                unknown_types.into_reports(),
+            ),
         ))
     }
 
@@ -774,6 +1026,26 @@ where
         self.current_user_leaf_node().map(|ln| &ln.signing_identity)
     }
 
+    /// Start rejoining the group by external commit after processing a
+    /// message failed with [`MlsError::OutOfSync`].
+    ///
+    /// The returned builder reuses the signing identity of the local member
+    /// and removes its current leaf, which other members still have in their
+    /// tree. The commit is produced by
+    /// [`ExternalCommitBuilder::build`](external_commit::ExternalCommitBuilder::build)
+    /// with a recent group info obtained from another member or the Delivery
+    /// Service, and the resulting group replaces this one.
+    pub fn resync_commit_builder(
+        &self,
+    ) -> Result<external_commit::ExternalCommitBuilder<C>, MlsError> {
+        Ok(external_commit::ExternalCommitBuilder::new(
+            self.signer.clone(),
+            self.current_member_signing_identity()?.clone(),
+            self.config.clone(),
+        )
+        .with_removal(self.current_member_index()))
+    }
+
     /// Member at a specific index in the group state.
     ///
     /// These indexes correspond to indexes in content descriptions within
@@ -788,6 +1060,20 @@ where
         &mut self,
         proposal: Proposal,
         authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        self.proposal_message_with_wire_format(proposal, authenticated_data, None)
+            .await
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn proposal_message_with_wire_format(
+        &mut self,
+        proposal: Proposal,
+        authenticated_data: Vec<u8>,
+        #[cfg_attr(not(feature = "private_message"), allow(unused_variables))] wire_format: Option<
+            WireFormat,
+        >,
     ) -> Result<MlsMessage, MlsError> {
         let sender = Sender::Member(*self.private_tree.self_index);
 
@@ -798,13 +1084,23 @@ where
             Content::Proposal(alloc::boxed::Box::new(proposal.clone())),
             &self.signer,
             #[cfg(feature = "private_message")]
-            self.encryption_options()?.control_wire_format(sender),
+            self.control_wire_format(sender, ContentType::Proposal, wire_format)?,
             #[cfg(not(feature = "private_message"))]
             WireFormat::PublicMessage,
             authenticated_data,
         )
         .await?;
 
+        self.send_signed_proposal(auth_content, proposal).await
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn send_signed_proposal(
+        &mut self,
+        auth_content: AuthenticatedContent,
+        proposal: Proposal,
+    ) -> Result<MlsMessage, MlsError> {
         let sender = auth_content.content.sender;
 
         let proposal_desc =
@@ -813,11 +1109,19 @@ where
 
         let message = self.format_for_wire(auth_content).await?;
 
+        self.sent_proposals.sent(
+            proposal_desc.proposal_ref.clone(),
+            proposal_desc.proposal.clone(),
+            self.current_epoch(),
+        );
+
         self.state
             .proposals
             .insert_own(proposal_desc, &message, sender, &self.cipher_suite_provider)
             .await?;
 
+        self.offload_proposals().await?;
+
         Ok(message)
     }
 
@@ -1214,6 +1518,35 @@ where
         Proposal::GroupContextExtensions(extensions)
     }
 
+    /// Create a proposal message sent as `wire_format`, overriding the
+    /// [`EncryptionOptions`] returned by the
+    /// [`MlsRules`](crate::MlsRules) for this message only.
+    ///
+    /// `wire_format` must be [`WireFormat::PublicMessage`] or
+    /// [`WireFormat::PrivateMessage`] and the override must be allowed by
+    /// [`MlsRules::allow_wire_format_override`](crate::MlsRules::allow_wire_format_override),
+    /// otherwise [`MlsError::WireFormatOverrideDenied`] is returned.
+    /// Update proposals must be created with [`Group::propose_update`]
+    /// since their secret keys are generated by the group.
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(all(feature = "by_ref_proposal", feature = "private_message"))]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn propose_with_wire_format(
+        &mut self,
+        proposal: Proposal,
+        wire_format: WireFormat,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        if matches!(proposal, Proposal::Update(_)) {
+            return Err(MlsError::UpdateErrorNoSecretKey);
+        }
+
+        self.proposal_message_with_wire_format(proposal, authenticated_data, Some(wire_format))
+            .await
+    }
+
     /// Create a custom proposal message.
     ///
     /// `authenticated_data` will be sent unencrypted along with the contents
@@ -1328,7 +1661,8 @@ where
     /// Delete all sent and received proposals cached for commit.
     #[cfg(feature = "by_ref_proposal")]
     pub fn clear_proposal_cache(&mut self) {
-        self.state.proposals.clear()
+        self.state.proposals.clear();
+        self.offloaded_proposals.clear();
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -1378,8 +1712,12 @@ where
         auth_content: AuthenticatedContent,
     ) -> Result<PrivateMessage, MlsError> {
         let padding_mode = self.encryption_options()?.padding_mode;
+        let context_binding = context_binding(&self.config, self.context())?;
 
-        let mut encryptor = CiphertextProcessor::new(self, self.cipher_suite_provider.clone());
+        self.ratchet_checkpoint.record_sent();
+
+        let mut encryptor = CiphertextProcessor::new(self, self.cipher_suite_provider.clone())
+            .with_context_binding(context_binding);
 
         encryptor.seal(auth_content, padding_mode).await
     }
@@ -1398,7 +1736,7 @@ where
         // A group member that has observed one or more proposals within an epoch MUST send a Commit message
         // before sending application data
         #[cfg(feature = "by_ref_proposal")]
-        if !self.state.proposals.is_empty() {
+        if self.has_cached_proposals() {
             return Err(MlsError::CommitRequired);
         }
 
@@ -1424,10 +1762,14 @@ where
     ) -> Result<AuthenticatedContent, MlsError> {
         let epoch_id = message.epoch;
 
-        let auth_content = if epoch_id == self.context().epoch {
-            let content = CiphertextProcessor::new(self, self.cipher_suite_provider.clone())
-                .open(message)
-                .await?;
+        let (auth_content, generation) = if epoch_id == self.context().epoch {
+            let context_binding = context_binding(&self.config, self.context())?;
+
+            let (content, generation) =
+                CiphertextProcessor::new(self, self.cipher_suite_provider.clone())
+                    .with_context_binding(context_binding)
+                    .open_with_generation(message)
+                    .await?;
 
             verify_auth_content_signature(
                 &self.cipher_suite_provider,
@@ -1439,7 +1781,7 @@ where
             )
             .await?;
 
-            Ok::<_, MlsError>(content)
+            Ok::<_, MlsError>((content, generation))
         } else {
             #[cfg(feature = "prior_epoch")]
             {
@@ -1449,9 +1791,13 @@ where
                     .await?
                     .ok_or(MlsError::EpochNotFound)?;
 
-                let content = CiphertextProcessor::new(epoch, self.cipher_suite_provider.clone())
-                    .open(message)
-                    .await?;
+                let context_binding = context_binding(&self.config, &epoch.context)?;
+
+                let (content, generation) =
+                    CiphertextProcessor::new(epoch, self.cipher_suite_provider.clone())
+                        .with_context_binding(context_binding)
+                        .open_with_generation(message)
+                        .await?;
 
                 verify_auth_content_signature(
                     &self.cipher_suite_provider,
@@ -1463,13 +1809,15 @@ where
                 )
                 .await?;
 
-                Ok(content)
+                Ok((content, generation))
             }
 
             #[cfg(not(feature = "prior_epoch"))]
             Err(MlsError::EpochNotFound)
         }?;
 
+        self.decrypted_generation = Some(generation);
+
         Ok(auth_content)
     }
 
@@ -1504,12 +1852,49 @@ where
 
         self.insert_past_epoch().await?;
 
+        #[cfg(feature = "key_transparency")]
+        let observed_leaves =
+            message_processor::changed_leaves(&self.state.public_tree, &pending.state.public_tree)?;
+
         self.state = pending.state;
         self.epoch_secrets = pending.epoch_secrets;
         self.private_tree = pending.private_tree;
         self.key_schedule = pending.key_schedule;
         self.signer = pending.signer;
 
+        #[cfg(feature = "by_ref_proposal")]
+        self.delete_offloaded_proposals().await?;
+
+        #[cfg(feature = "key_transparency")]
+        message_processor::report_changed_leaves(
+            &self.config.identity_provider(),
+            observed_leaves,
+            &self.state.context,
+        )
+        .await?;
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            let committed: Vec<ProposalRef> = match &pending.output.effect {
+                CommitEffect::NewEpoch(new_epoch) | CommitEffect::Removed { new_epoch, .. } => {
+                    new_epoch
+                        .applied_proposals
+                        .iter()
+                        .filter_map(|p| p.proposal_ref().cloned())
+                        .collect()
+                }
+                CommitEffect::ReInit(info) => info.proposal_ref().cloned().into_iter().collect(),
+            };
+
+            self.sent_proposals.new_epoch(
+                self.current_epoch(),
+                &committed,
+                self.state.pending_reinit.as_ref(),
+            );
+        }
+
+        self.intent_log.commit_applied(&pending.output);
+
         Ok(pending.output)
     }
 
@@ -1536,13 +1921,21 @@ where
         !self.pending_commit.is_none()
     }
 
+    /// Identifier of the pending commit, equal to the
+    /// [`attempt_id`](CommitOutput::attempt_id) returned when the commit was
+    /// created, or `None` if there is no pending commit.
+    pub fn pending_commit_attempt_id(&self) -> Result<Option<CommitAttemptId>, MlsError> {
+        Ok(self.pending_commit.commit_hash()?.map(Into::into))
+    }
+
     /// Clear the currently pending commit.
     ///
     /// This function will automatically be called in the event that a
     /// commit message is processed using [`Group::process_incoming_message`]
     /// before [`Group::apply_pending_commit`] is called.
     pub fn clear_pending_commit(&mut self) {
-        self.pending_commit = Default::default()
+        self.pending_commit = Default::default();
+        self.intent_log.supersede();
     }
 
     /// Returns true if the client has received or issued a proposal
@@ -1550,7 +1943,7 @@ where
     /// application message.
     #[cfg(feature = "by_ref_proposal")]
     pub fn commit_required(&self) -> bool {
-        !self.state.proposals.is_empty()
+        self.has_cached_proposals()
     }
 
     /// Process an inbound message for this group.
@@ -1567,36 +1960,7 @@ where
         &mut self,
         message: MlsMessage,
     ) -> Result<ReceivedMessage, MlsError> {
-        if let Some(pending) = self.pending_commit.commit_hash()? {
-            let message_hash = MessageHash::compute(&self.cipher_suite_provider, &message).await?;
-
-            if message_hash == pending {
-                let message_description = self.apply_pending_commit().await?;
-
-                return Ok(ReceivedMessage::Commit(message_description));
-            }
-        }
-
-        #[cfg(feature = "by_ref_proposal")]
-        if message.wire_format() == WireFormat::PrivateMessage {
-            let cached_own_proposal = self
-                .state
-                .proposals
-                .get_own(&self.cipher_suite_provider, &message)
-                .await?;
-
-            if let Some(cached) = cached_own_proposal {
-                return Ok(ReceivedMessage::Proposal(cached));
-            }
-        }
-
-        MessageProcessor::process_incoming_message(
-            self,
-            message,
-            #[cfg(feature = "by_ref_proposal")]
-            true,
-        )
-        .await
+        self.process_incoming_message_at(message, None).await
     }
 
     /// Process an inbound message for this group, providing additional context
@@ -1621,6 +1985,43 @@ where
         message: MlsMessage,
         time: MlsTime,
     ) -> Result<ReceivedMessage, MlsError> {
+        self.process_incoming_message_at(message, Some(time)).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn process_incoming_message_at(
+        &mut self,
+        message: MlsMessage,
+        time: Option<MlsTime>,
+    ) -> Result<ReceivedMessage, MlsError> {
+        let res = self.process_incoming_message_inner(message, time).await;
+
+        #[cfg(feature = "security_events")]
+        if let Err(e) = &res {
+            security_event::report_security_event(
+                &self.config,
+                e,
+                Some(self.group_id()),
+                Some(self.current_epoch()),
+            );
+        }
+
+        res
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn process_incoming_message_inner(
+        &mut self,
+        message: MlsMessage,
+        time: Option<MlsTime>,
+    ) -> Result<ReceivedMessage, MlsError> {
+        #[cfg(all(feature = "psk", feature = "private_message"))]
+        if self.is_predecessor_message(&message) {
+            if let Some(received) = self.process_predecessor_message(&message).await? {
+                return Ok(received);
+            }
+        }
+
         if let Some(pending) = self.pending_commit.commit_hash()? {
             let message_hash = MessageHash::compute(&self.cipher_suite_provider, &message).await?;
 
@@ -1644,14 +2045,25 @@ where
             }
         }
 
-        MessageProcessor::process_incoming_message_with_time(
+        #[cfg(feature = "by_ref_proposal")]
+        self.load_offloaded_proposals_for(&message).await?;
+
+        let received = MessageProcessor::process_incoming_message_with_time(
             self,
             message,
             #[cfg(feature = "by_ref_proposal")]
             true,
-            Some(time),
+            time,
         )
-        .await
+        .await?;
+
+        #[cfg(feature = "by_ref_proposal")]
+        self.enforce_proposal_cache_limits(&received).await?;
+
+        #[cfg(feature = "private_message")]
+        self.ratchet_checkpoint.record(&received);
+
+        Ok(received)
     }
 
     /// Find a group member by
@@ -1753,6 +2165,45 @@ where
         &self.group_state().context
     }
 
+    /// Get the confirmed transcript hash of the current epoch.
+    #[inline(always)]
+    pub fn confirmed_transcript_hash(&self) -> &[u8] {
+        &self.group_state().context.confirmed_transcript_hash
+    }
+
+    /// Get the interim transcript hash of the current epoch.
+    #[inline(always)]
+    pub fn interim_transcript_hash(&self) -> &[u8] {
+        &self.group_state().interim_transcript_hash
+    }
+
+    /// Get the transcript hashes of a prior epoch that is still retained
+    /// by the group state storage.
+    ///
+    /// Returns `None` if the epoch is not found.
+    #[cfg(feature = "prior_epoch_transcript_hash")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn epoch_transcript_hashes(
+        &mut self,
+        epoch_id: u64,
+    ) -> Result<Option<TranscriptHashes>, MlsError> {
+        if epoch_id == self.context().epoch {
+            return Ok(Some(TranscriptHashes {
+                confirmed_transcript_hash: self.confirmed_transcript_hash().to_vec(),
+                interim_transcript_hash: self.interim_transcript_hash().to_vec(),
+            }));
+        }
+
+        Ok(self
+            .state_repo
+            .get_epoch_mut(epoch_id)
+            .await?
+            .map(|epoch| TranscriptHashes {
+                confirmed_transcript_hash: epoch.context.confirmed_transcript_hash.to_vec(),
+                interim_transcript_hash: epoch.interim_transcript_hash.to_vec(),
+            }))
+    }
+
     /// Get the
     /// [epoch_authenticator](https://messaginglayersecurity.rocks/mls-protocol/draft-ietf-mls-protocol.html#name-key-schedule)
     /// of the current epoch.
@@ -1778,6 +2229,31 @@ where
             .map(Into::into)
     }
 
+    /// Export a secret reserved for the component `component_id`, e.g. a
+    /// custom extension. Secrets exported for different components, or with
+    /// [Group::export_secret], are independent of each other. As with
+    /// [Group::export_secret], secrets can be derived until the epoch changes
+    /// or [Group::delete_exporter] is called.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn export_component_secret(
+        &self,
+        component_id: ComponentID,
+        label: &[u8],
+        context: &[u8],
+        len: usize,
+    ) -> Result<Secret, MlsError> {
+        self.key_schedule
+            .export_component_secret(
+                component_id,
+                label,
+                context,
+                len,
+                &self.cipher_suite_provider,
+            )
+            .await
+            .map(Into::into)
+    }
+
     /// Delete the exporter secret. Afterwards the state contains no information
     /// about any secrets outputted by [Group::export_secret] (for the current or
     /// past epochs). This means that after calling this function, [Group::export_secret]
@@ -1856,6 +2332,44 @@ where
             .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
     }
 
+    /// Wire format of a proposal or commit sent by `sender`, either derived
+    /// from the encryption options or overridden by the application.
+    #[cfg(feature = "private_message")]
+    pub(crate) fn control_wire_format(
+        &self,
+        sender: Sender,
+        content_type: ContentType,
+        requested: Option<WireFormat>,
+    ) -> Result<WireFormat, MlsError> {
+        let default = self.encryption_options()?.control_wire_format(sender);
+
+        let Some(requested) = requested.filter(|w| *w != default) else {
+            return Ok(default);
+        };
+
+        let valid = match requested {
+            WireFormat::PublicMessage => true,
+            WireFormat::PrivateMessage => matches!(sender, Sender::Member(_)),
+            _ => false,
+        };
+
+        let allowed = valid
+            && self
+                .config
+                .mls_rules()
+                .allow_wire_format_override(
+                    requested,
+                    content_type,
+                    &self.roster(),
+                    self.group_context(),
+                )
+                .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
+
+        allowed
+            .then_some(requested)
+            .ok_or(MlsError::WireFormatOverrideDenied)
+    }
+
     #[cfg(not(feature = "psk"))]
     fn get_psk(&self) -> PskSecret {
         PskSecret::new(self.cipher_suite_provider())
@@ -2049,6 +2563,8 @@ impl<C: ClientConfig> Group<C> {
             signature_public_keys,
             #[cfg(feature = "prior_epoch_membership_key")]
             membership_key: self.key_schedule.membership_key.to_vec(),
+            #[cfg(feature = "prior_epoch_transcript_hash")]
+            interim_transcript_hash: self.state.interim_transcript_hash.clone(),
         };
 
         self.state_repo.insert(past_epoch).await?;
@@ -2111,6 +2627,16 @@ where
             .map(EventOrContent::Content)
     }
 
+    #[cfg(feature = "targeted_messages")]
+    async fn process_targeted_message(
+        &mut self,
+        message: &targeted_message::TargetedMessage,
+    ) -> Result<Self::OutputType, MlsError> {
+        self.decrypt_targeted_message(message)
+            .await
+            .map(ReceivedMessage::TargetedMessage)
+    }
+
     async fn verify_plaintext_authentication(
         &self,
         message: PublicMessage,
@@ -2194,6 +2720,13 @@ where
         confirmation_tag: &ConfirmationTag,
         provisional_state: ProvisionalState,
     ) -> Result<(), MlsError> {
+        #[cfg(feature = "by_ref_proposal")]
+        let committed = provisional_state
+            .applied_proposals
+            .iter_proposals()
+            .filter_map(|p| p.proposal_ref().cloned())
+            .collect::<Vec<_>>();
+
         let commit_secret = if let Some(secrets) = secrets {
             self.private_tree = secrets.0;
             secrets.1
@@ -2265,6 +2798,9 @@ where
         #[cfg(feature = "by_ref_proposal")]
         self.state.proposals.clear();
 
+        #[cfg(feature = "by_ref_proposal")]
+        self.delete_offloaded_proposals().await?;
+
         // Clear the pending updates list
         #[cfg(feature = "by_ref_proposal")]
         {
@@ -2272,6 +2808,14 @@ where
         }
 
         self.pending_commit = Default::default();
+        self.intent_log.supersede();
+
+        #[cfg(feature = "by_ref_proposal")]
+        self.sent_proposals.new_epoch(
+            self.current_epoch(),
+            &committed,
+            self.state.pending_reinit.as_ref(),
+        );
 
         Ok(())
     }
@@ -2331,6 +2875,28 @@ where
         None
     }
 
+    fn validated_key_packages(&self) -> &[KeyPackageRef] {
+        &self.validated_key_packages
+    }
+
+    fn out_of_sync_epoch_lag(&self) -> Option<u64> {
+        Some(self.config.out_of_sync_epoch_lag())
+    }
+
+    #[cfg(feature = "private_message")]
+    fn decrypted_generation(&self) -> Option<u32> {
+        self.decrypted_generation
+    }
+
+    fn unknown_type_checker(&self) -> Result<UnknownTypeChecker, MlsError> {
+        UnknownTypeChecker::new(
+            self.config.unknown_type_policy(),
+            self.config.supported_extensions(),
+            self.config.supported_custom_proposals(),
+            &self.context().extensions,
+        )
+    }
+
     fn cipher_suite_provider(&self) -> &Self::CipherSuiteProvider {
         &self.cipher_suite_provider
     }
@@ -2343,7 +2909,8 @@ pub(crate) mod test_utils;
 mod tests {
     use crate::{
         client::test_utils::{
-            test_client_with_key_pkg, TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+            test_client_with_key_pkg, test_client_with_key_pkg_custom, TestClientBuilder,
+            TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
         },
         client_builder::test_utils::TestClientConfig,
         crypto::test_utils::TestCryptoProvider,
@@ -2359,7 +2926,7 @@ mod tests {
 
     #[cfg(feature = "by_ref_proposal")]
     use crate::{
-        client::test_utils::{test_client_with_key_pkg_custom, TEST_CUSTOM_PROPOSAL_TYPE},
+        client::test_utils::TEST_CUSTOM_PROPOSAL_TYPE,
         client_builder::{ClientBuilder, MlsConfig},
         group::{
             component_operation::ComponentID,
@@ -2638,6 +3205,47 @@ mod tests {
         assert_eq!(plaintext.to_vec(), hpke_decrypted);
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn component_secrets_are_shared_and_domain_separated() {
+        let (mut alice_group, bob_group) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let alice_secret = alice_group
+            .export_component_secret(1, b"label", b"context", 32)
+            .await
+            .unwrap();
+
+        let bob_secret = bob_group
+            .export_component_secret(1, b"label", b"context", 32)
+            .await
+            .unwrap();
+
+        assert_eq!(alice_secret, bob_secret);
+        assert_eq!(alice_secret.as_bytes().len(), 32);
+
+        let other_component = alice_group
+            .export_component_secret(2, b"label", b"context", 32)
+            .await
+            .unwrap();
+
+        assert_ne!(alice_secret, other_component);
+
+        let exported = alice_group
+            .export_secret(b"label", b"context", 32)
+            .await
+            .unwrap();
+
+        assert_ne!(alice_secret, exported);
+
+        alice_group.delete_exporter();
+
+        let res = alice_group
+            .export_component_secret(1, b"label", b"context", 32)
+            .await;
+
+        assert_matches!(res, Err(MlsError::ExporterDeleted));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn safe_context_test_hpke_encrypt_decrypt() {
         let component_id: ComponentID = 1;
@@ -3010,6 +3618,82 @@ mod tests {
         test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
     }
 
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn join_with_tree_extension(
+        keep_tree: Option<bool>,
+    ) -> (TestGroup, Group<TestClientConfig>, NewMemberInfo) {
+        let mut test_group = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            Default::default(),
+            None,
+            Some(CommitOptions::new().with_ratchet_tree_extension(true)),
+        )
+        .await;
+
+        let (bob_client, bob_key_package) = test_client_with_key_pkg_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            "bob",
+            Default::default(),
+            Default::default(),
+            |config| {
+                if let Some(keep_tree) = keep_tree {
+                    config.0.settings.keep_welcome_ratchet_tree = keep_tree;
+                }
+            },
+        )
+        .await;
+
+        let commit_output = test_group
+            .commit_builder()
+            .add_member(bob_key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        test_group.process_pending_commit().await.unwrap();
+
+        let (bob_group, new_member_info) = Group::join(
+            &commit_output.welcome_messages[0],
+            None,
+            bob_client.config,
+            bob_client.signer.unwrap(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        (test_group, bob_group, new_member_info)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_welcome_processing_keeps_tree_extension_by_default() {
+        let (test_group, bob_group, new_member_info) = join_with_tree_extension(None).await;
+
+        let tree = new_member_info
+            .group_info_extensions
+            .get_as::<RatchetTreeExt>()
+            .unwrap()
+            .unwrap()
+            .tree_data;
+
+        assert_eq!(tree, test_group.export_tree());
+        assert_eq!(bob_group.export_tree(), test_group.export_tree());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_welcome_processing_can_release_tree_extension() {
+        let (test_group, bob_group, new_member_info) = join_with_tree_extension(Some(false)).await;
+
+        assert!(!new_member_info
+            .group_info_extensions
+            .has_extension(ExtensionType::RATCHET_TREE));
+
+        assert_eq!(bob_group.export_tree(), test_group.export_tree());
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_welcome_processing_missing_tree() {
         let mut test_group = test_group_custom(
@@ -3800,6 +4484,36 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn application_message_includes_sender_member() {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob_group, _) = alice_group.join("bob").await;
+
+        let msg = bob_group
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let ReceivedMessage::ApplicationMessage(desc) =
+            alice_group.process_incoming_message(msg).await.unwrap()
+        else {
+            panic!("expected application message");
+        };
+
+        let sender = desc.sender.unwrap();
+
+        assert_eq!(sender.index, bob_group.current_member_index());
+        assert_eq!(
+            &sender.signing_identity,
+            bob_group.current_member_signing_identity().unwrap()
+        );
+        assert_eq!(
+            sender.extensions,
+            bob_group.current_user_leaf_node().unwrap().extensions
+        );
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn members_of_a_group_have_identical_authentication_secrets() {
         let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
@@ -4667,6 +5381,51 @@ mod tests {
         );
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn stale_member_is_out_of_sync_and_can_resync() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut commits = Vec::new();
+
+        for _ in 0..5 {
+            commits.push(alice.commit(vec![]).await.unwrap().commit_message);
+            alice.apply_pending_commit().await.unwrap();
+        }
+
+        let res = bob.process_message(commits[2].clone()).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+
+        let local_epoch = bob.current_epoch();
+        let res = bob.process_message(commits[4].clone()).await;
+
+        assert_matches!(
+            res,
+            Err(MlsError::OutOfSync { local_epoch: l, observed_epoch: o })
+                if l == local_epoch && o == local_epoch + 4
+        );
+
+        let group_info = alice
+            .group_info_message_allowing_ext_commit(true)
+            .await
+            .unwrap();
+
+        let (bob, commit) = bob
+            .resync_commit_builder()
+            .unwrap()
+            .build(group_info)
+            .await
+            .unwrap();
+
+        alice.process_message(commit).await.unwrap();
+
+        assert_eq!(alice.roster().members_iter().count(), 2);
+        assert_eq!(
+            alice.epoch_authenticator().unwrap(),
+            bob.epoch_authenticator().unwrap()
+        );
+    }
+
     #[cfg(feature = "psk")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn can_join_with_psk() {
@@ -5683,6 +6442,72 @@ mod tests {
         assert_eq!(new_epoch.applied_proposals[0].sender, Sender::Member(0));
     }
 
+    #[cfg(all(feature = "by_ref_proposal", feature = "private_message"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn wire_format_can_be_overridden_per_message() {
+        let (alice, _) = test_client_with_key_pkg_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            "alice",
+            Default::default(),
+            Default::default(),
+            |c| c.0.mls_rules.encryption_options.encrypt_control_messages = true,
+        )
+        .await;
+
+        let mut alice = TestGroup {
+            group: alice
+                .create_group(Default::default(), Default::default(), None)
+                .await
+                .unwrap(),
+        };
+
+        let mut bob = alice.join("bob").await.0;
+
+        let proposal = Proposal::GroupContextExtensions(ExtensionList::new());
+
+        let message = alice
+            .propose_with_wire_format(
+                proposal.clone(),
+                WireFormat::PublicMessage,
+                b"export".to_vec(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(message.wire_format(), WireFormat::PublicMessage);
+        bob.process_incoming_message(message).await.unwrap();
+
+        let res = alice
+            .propose_with_wire_format(proposal, WireFormat::Welcome, vec![])
+            .await;
+
+        assert_matches!(res, Err(MlsError::WireFormatOverrideDenied));
+
+        let commit = alice
+            .commit_builder()
+            .wire_format(WireFormat::PublicMessage)
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            commit.commit_message.wire_format(),
+            WireFormat::PublicMessage
+        );
+        bob.process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap();
+        assert_eq!(
+            commit.commit_message.wire_format(),
+            WireFormat::PrivateMessage
+        );
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn commit_clears_proposals() {
@@ -5755,6 +6580,25 @@ mod tests {
         assert_eq!(restored.group_state(), group.group_state());
     }
 
+    #[cfg(feature = "tree_visualization")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn exported_tree_can_be_visualized() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        let tree = alice.export_tree();
+
+        assert_eq!(
+            tree.to_ascii(),
+            "Blank Root (1)\n├╼ Leaf 0 (0) [member]\n└╼ Leaf 1 (2) [bob]"
+        );
+
+        let dot = tree.to_dot();
+
+        assert!(dot.contains("n0 [label=\"Leaf 0 (0)\\nmember\"];"));
+        assert!(dot.contains("n2 [label=\"Leaf 1 (2)\\nbob\"];"));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn delete_exporter() {
         let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
@@ -5769,4 +6613,61 @@ mod tests {
         group.apply_pending_commit().await.unwrap();
         group.export_secret(b"123", b"", 15).await.unwrap();
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_description_includes_transcript_hashes() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let commit = alice.commit(vec![]).await.unwrap();
+        let sent = alice.apply_pending_commit().await.unwrap();
+
+        let ReceivedMessage::Commit(received) =
+            bob.process_message(commit.commit_message).await.unwrap()
+        else {
+            panic!("expected a commit");
+        };
+
+        for description in [&sent, &received] {
+            assert_eq!(
+                description.confirmed_transcript_hash,
+                alice.confirmed_transcript_hash()
+            );
+
+            assert_eq!(
+                description.interim_transcript_hash,
+                alice.interim_transcript_hash()
+            );
+        }
+
+        assert_eq!(
+            alice.interim_transcript_hash(),
+            bob.interim_transcript_hash()
+        );
+        assert_ne!(
+            alice.confirmed_transcript_hash(),
+            alice.interim_transcript_hash()
+        );
+    }
+
+    #[cfg(feature = "prior_epoch_transcript_hash")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn transcript_hashes_of_prior_epochs_are_retained() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let epoch = group.current_epoch();
+        let confirmed = group.confirmed_transcript_hash().to_vec();
+        let interim = group.interim_transcript_hash().to_vec();
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+
+        let hashes = group.epoch_transcript_hashes(epoch).await.unwrap().unwrap();
+
+        assert_eq!(hashes.confirmed_transcript_hash, confirmed);
+        assert_eq!(hashes.interim_transcript_hash, interim);
+
+        let hashes = group.epoch_transcript_hashes(epoch + 10).await.unwrap();
+        assert!(hashes.is_none());
+    }
 }
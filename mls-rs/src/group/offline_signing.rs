@@ -0,0 +1,441 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use crate::{client::MlsError, client_config::ClientConfig, signer::Signable};
+
+use super::{
+    commit::{CommitInProgress, SignedCommitInProgress},
+    message_signature::MessageSigningContext,
+    CommitOutput, Group,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use {
+    super::{
+        framing::Content, message_signature::AuthenticatedContent, proposal::Proposal, Sender,
+    },
+    crate::MlsMessage,
+    alloc::boxed::Box,
+};
+
+#[cfg(all(feature = "by_ref_proposal", not(feature = "private_message")))]
+use crate::WireFormat;
+
+/// A proposal that is signed outside of this client, for example by an
+/// offline device holding the signature key of the member.
+///
+/// Created with [`Group::unsigned_proposal`] and turned into a message with
+/// [`Group::finalize_proposal`].
+#[cfg(feature = "by_ref_proposal")]
+pub struct UnsignedProposal {
+    auth_content: AuthenticatedContent,
+    proposal: Proposal,
+    epoch: u64,
+    to_be_signed: Vec<u8>,
+}
+
+#[cfg(feature = "by_ref_proposal")]
+impl UnsignedProposal {
+    /// Bytes to sign with the signature key of the member, as they would be
+    /// passed to [`CipherSuiteProvider::sign`](crate::CipherSuiteProvider::sign).
+    pub fn to_be_signed(&self) -> &[u8] {
+        &self.to_be_signed
+    }
+}
+
+#[cfg(feature = "by_ref_proposal")]
+impl Debug for UnsignedProposal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnsignedProposal")
+            .field("proposal", &self.proposal)
+            .field("epoch", &self.epoch)
+            .field(
+                "to_be_signed",
+                &mls_rs_core::debug::pretty_bytes(&self.to_be_signed),
+            )
+            .finish()
+    }
+}
+
+/// A commit whose content is not yet signed.
+///
+/// Created with [`CommitBuilder::build_unsigned`](super::CommitBuilder::build_unsigned).
+/// Once the content is signed, [`Group::sign_commit`] returns the group info
+/// messages of the commit, which must be signed as well.
+pub struct UnsignedCommit {
+    commit: CommitInProgress,
+    epoch: u64,
+    to_be_signed: Vec<u8>,
+}
+
+impl UnsignedCommit {
+    /// Bytes to sign with the signature key of the member, as they would be
+    /// passed to [`CipherSuiteProvider::sign`](crate::CipherSuiteProvider::sign).
+    pub fn to_be_signed(&self) -> &[u8] {
+        &self.to_be_signed
+    }
+}
+
+impl Debug for UnsignedCommit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnsignedCommit")
+            .field("epoch", &self.epoch)
+            .field(
+                "to_be_signed",
+                &mls_rs_core::debug::pretty_bytes(&self.to_be_signed),
+            )
+            .finish()
+    }
+}
+
+/// A commit with a signed content whose group info messages are not yet
+/// signed.
+///
+/// Created with [`Group::sign_commit`] and completed with
+/// [`Group::finalize_commit`].
+pub struct UnsignedCommitGroupInfo {
+    commit: SignedCommitInProgress,
+    epoch: u64,
+    to_be_signed: Vec<Vec<u8>>,
+}
+
+impl UnsignedCommitGroupInfo {
+    /// Bytes of each group info message to sign with the signature key of the
+    /// member. Signatures are passed to [`Group::finalize_commit`] in the same
+    /// order.
+    pub fn to_be_signed(&self) -> &[Vec<u8>] {
+        &self.to_be_signed
+    }
+}
+
+impl Debug for UnsignedCommitGroupInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnsignedCommitGroupInfo")
+            .field("epoch", &self.epoch)
+            .field("group_info_count", &self.to_be_signed.len())
+            .finish()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    fn message_signing_context(&self) -> MessageSigningContext<'_> {
+        MessageSigningContext {
+            group_context: Some(self.context()),
+            protocol_version: self.protocol_version(),
+        }
+    }
+
+    fn check_unsigned_epoch(&self, epoch: u64) -> Result<(), MlsError> {
+        if epoch != self.current_epoch() {
+            return Err(MlsError::UnsignedMessageEpochMismatch);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn unsigned_commit(
+        &self,
+        commit: CommitInProgress,
+    ) -> Result<UnsignedCommit, MlsError> {
+        let to_be_signed = commit
+            .auth_content
+            .to_be_signed(&self.message_signing_context())?;
+
+        Ok(UnsignedCommit {
+            commit,
+            epoch: self.current_epoch(),
+            to_be_signed,
+        })
+    }
+
+    /// Create a proposal message that is signed outside of this client.
+    ///
+    /// The proposal is sent by calling [`Group::finalize_proposal`] with the
+    /// signature of [`UnsignedProposal::to_be_signed`] in the same epoch.
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn unsigned_proposal(
+        &self,
+        proposal: Proposal,
+        authenticated_data: Vec<u8>,
+    ) -> Result<UnsignedProposal, MlsError> {
+        let sender = Sender::Member(*self.private_tree.self_index);
+
+        let auth_content = AuthenticatedContent::new(
+            self.context(),
+            sender,
+            Content::Proposal(Box::new(proposal.clone())),
+            authenticated_data,
+            #[cfg(feature = "private_message")]
+            self.encryption_options()?.control_wire_format(sender),
+            #[cfg(not(feature = "private_message"))]
+            WireFormat::PublicMessage,
+        );
+
+        let to_be_signed = auth_content.to_be_signed(&self.message_signing_context())?;
+
+        Ok(UnsignedProposal {
+            auth_content,
+            proposal,
+            epoch: self.current_epoch(),
+            to_be_signed,
+        })
+    }
+
+    /// Attach a signature to a proposal created with
+    /// [`Group::unsigned_proposal`] and return the proposal message to send.
+    ///
+    /// The signature is verified with the signature key of the current member.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn finalize_proposal(
+        &mut self,
+        proposal: UnsignedProposal,
+        signature: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        self.check_unsigned_epoch(proposal.epoch)?;
+
+        let mut auth_content = proposal.auth_content;
+        auth_content.write_signature(signature);
+
+        auth_content
+            .verify(
+                &self.cipher_suite_provider,
+                &self.current_member_signing_identity()?.signature_key,
+                &self.message_signing_context(),
+            )
+            .await?;
+
+        self.send_signed_proposal(auth_content, proposal.proposal)
+            .await
+    }
+
+    /// Attach a signature to the content of a commit created with
+    /// [`CommitBuilder::build_unsigned`](super::CommitBuilder::build_unsigned).
+    ///
+    /// The signature is verified with the signature key of the current member.
+    /// The returned group info messages must be signed before the commit is
+    /// completed with [`Group::finalize_commit`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn sign_commit(
+        &self,
+        commit: UnsignedCommit,
+        signature: Vec<u8>,
+    ) -> Result<UnsignedCommitGroupInfo, MlsError> {
+        self.check_unsigned_epoch(commit.epoch)?;
+
+        let mut commit = commit.commit;
+        commit.auth_content.write_signature(signature);
+
+        commit
+            .auth_content
+            .verify(
+                &self.cipher_suite_provider,
+                &self.current_member_signing_identity()?.signature_key,
+                &self.message_signing_context(),
+            )
+            .await?;
+
+        let commit = self.continue_commit(commit).await?;
+
+        let to_be_signed = commit
+            .group_infos()
+            .map(|group_info| group_info.to_be_signed(&()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(UnsignedCommitGroupInfo {
+            commit,
+            epoch: self.current_epoch(),
+            to_be_signed,
+        })
+    }
+
+    /// Attach signatures to the group info messages of a commit and create
+    /// the commit output.
+    ///
+    /// `signatures` must contain one signature for each entry of
+    /// [`UnsignedCommitGroupInfo::to_be_signed`], in the same order. As with
+    /// [`CommitBuilder::build`](super::CommitBuilder::build), the commit
+    /// becomes the pending commit of the group.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn finalize_commit(
+        &mut self,
+        commit: UnsignedCommitGroupInfo,
+        signatures: Vec<Vec<u8>>,
+    ) -> Result<CommitOutput, MlsError> {
+        self.check_unsigned_epoch(commit.epoch)?;
+
+        if !self.pending_commit.is_none() {
+            return Err(MlsError::ExistingPendingCommit);
+        }
+
+        if signatures.len() != commit.to_be_signed.len() {
+            return Err(MlsError::GroupInfoSignatureCountMismatch);
+        }
+
+        let mut commit = commit.commit;
+        let public_key = self
+            .current_member_signing_identity()?
+            .signature_key
+            .clone();
+
+        for (group_info, signature) in commit.group_infos_mut().zip(signatures) {
+            group_info.write_signature(signature);
+
+            group_info
+                .verify(&self.cipher_suite_provider, &public_key, &())
+                .await?;
+        }
+
+        let (output, pending_commit) = self.finish_commit(commit).await?;
+
+        self.pending_commit = pending_commit.try_into()?;
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::CipherSuiteProvider;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{
+            test_utils::{test_group, TestGroup},
+            CommitEffect, ReceivedMessage,
+        },
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn offline_sign(group: &TestGroup, to_be_signed: &[u8]) -> Vec<u8> {
+        group
+            .cipher_suite_provider
+            .sign(&group.signer, to_be_signed)
+            .await
+            .unwrap()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_adding_member_can_be_signed_offline() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let (carol, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let unsigned = alice
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build_unsigned()
+            .await
+            .unwrap();
+
+        let signature = offline_sign(&alice, unsigned.to_be_signed()).await;
+        let unsigned = alice.sign_commit(unsigned, signature).await.unwrap();
+
+        let mut signatures = Vec::new();
+
+        for to_be_signed in unsigned.to_be_signed() {
+            signatures.push(offline_sign(&alice, to_be_signed).await);
+        }
+
+        let output = alice.finalize_commit(unsigned, signatures).await.unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let received = bob.process_message(output.commit_message).await.unwrap();
+
+        assert_matches!(
+            received,
+            ReceivedMessage::Commit(desc) if matches!(desc.effect, CommitEffect::NewEpoch(_))
+        );
+
+        let (carol, _) = carol
+            .join_group(output.ratchet_tree, &output.welcome_messages[0], None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            carol.epoch_authenticator().unwrap(),
+            alice.epoch_authenticator().unwrap()
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn invalid_offline_signature_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let unsigned = alice
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build_unsigned()
+            .await
+            .unwrap();
+
+        let res = alice.sign_commit(unsigned, vec![0; 64]).await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_with_path_can_not_be_signed_offline() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let res = alice.commit_builder().build_unsigned().await;
+
+        assert_matches!(res, Err(MlsError::OfflineSigningRequiresNoPathUpdate));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn proposal_can_be_signed_offline() {
+        use crate::{
+            group::proposal::{Proposal, RemoveProposal},
+            tree_kem::node::LeafIndex,
+        };
+
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let (_, commit) = alice.join("carol").await;
+
+        bob.process_message(commit).await.unwrap();
+
+        let unsigned = alice
+            .unsigned_proposal(
+                Proposal::Remove(RemoveProposal {
+                    to_remove: LeafIndex::unchecked(2),
+                }),
+                vec![],
+            )
+            .unwrap();
+
+        let signature = offline_sign(&alice, unsigned.to_be_signed()).await;
+        let message = alice.finalize_proposal(unsigned, signature).await.unwrap();
+
+        let received = bob.process_message(message).await.unwrap();
+
+        assert_matches!(received, ReceivedMessage::Proposal(_));
+
+        let commit = bob.commit(vec![]).await.unwrap();
+
+        assert_eq!(commit.unused_proposals.len(), 0);
+    }
+}
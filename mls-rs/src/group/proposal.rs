@@ -22,7 +22,7 @@ pub use mls_rs_core::extension::ExtensionList;
 pub use mls_rs_core::group::ProposalType;
 
 #[cfg(feature = "psk")]
-use crate::psk::{ExternalPskId, JustPreSharedKeyID, PreSharedKeyID};
+use crate::psk::{ExternalPskId, JustPreSharedKeyID, PreSharedKeyID, PreSharedKeyReference};
 
 #[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
@@ -168,6 +168,17 @@ impl PreSharedKeyProposal {
             JustPreSharedKeyID::Resumption(_) => None,
         }
     }
+
+    /// The pre-shared key referenced by this proposal.
+    pub fn reference(&self) -> PreSharedKeyReference {
+        PreSharedKeyReference::from(&self.psk.key_id)
+    }
+
+    /// Nonce that makes the pre-shared key of this proposal unique within a
+    /// commit.
+    pub fn psk_nonce(&self) -> &[u8] {
+        &self.psk.psk_nonce.0
+    }
 }
 
 #[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
@@ -427,36 +438,51 @@ impl MlsEncode for Proposal {
 
 impl MlsDecode for Proposal {
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        Self::mls_decode_with_limits(reader, &mls_rs_codec::DecodeLimits::UNLIMITED)
+    }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &mls_rs_codec::DecodeLimits,
+    ) -> Result<Self, mls_rs_codec::Error> {
         let proposal_type = ProposalType::mls_decode(reader)?;
 
         Ok(match proposal_type {
-            ProposalType::ADD => {
-                Proposal::Add(alloc::boxed::Box::new(AddProposal::mls_decode(reader)?))
-            }
+            ProposalType::ADD => Proposal::Add(alloc::boxed::Box::new(
+                AddProposal::mls_decode_with_limits(reader, limits)?,
+            )),
             #[cfg(feature = "by_ref_proposal")]
-            ProposalType::UPDATE => Proposal::Update(UpdateProposal::mls_decode(reader)?),
-            ProposalType::REMOVE => Proposal::Remove(RemoveProposal::mls_decode(reader)?),
+            ProposalType::UPDATE => {
+                Proposal::Update(UpdateProposal::mls_decode_with_limits(reader, limits)?)
+            }
+            ProposalType::REMOVE => {
+                Proposal::Remove(RemoveProposal::mls_decode_with_limits(reader, limits)?)
+            }
             #[cfg(feature = "psk")]
-            ProposalType::PSK => Proposal::Psk(PreSharedKeyProposal::mls_decode(reader)?),
-            ProposalType::RE_INIT => Proposal::ReInit(ReInitProposal::mls_decode(reader)?),
-            ProposalType::EXTERNAL_INIT => {
-                Proposal::ExternalInit(ExternalInit::mls_decode(reader)?)
+            ProposalType::PSK => Proposal::Psk(PreSharedKeyProposal::mls_decode_with_limits(
+                reader, limits,
+            )?),
+            ProposalType::RE_INIT => {
+                Proposal::ReInit(ReInitProposal::mls_decode_with_limits(reader, limits)?)
             }
-            ProposalType::GROUP_CONTEXT_EXTENSIONS => {
-                Proposal::GroupContextExtensions(ExtensionList::mls_decode(reader)?)
+            ProposalType::EXTERNAL_INIT => {
+                Proposal::ExternalInit(ExternalInit::mls_decode_with_limits(reader, limits)?)
             }
+            ProposalType::GROUP_CONTEXT_EXTENSIONS => Proposal::GroupContextExtensions(
+                ExtensionList::mls_decode_with_limits(reader, limits)?,
+            ),
             #[cfg(all(
                 feature = "by_ref_proposal",
                 feature = "custom_proposal",
                 feature = "self_remove_proposal"
             ))]
             ProposalType::SELF_REMOVE => {
-                Proposal::SelfRemove(SelfRemoveProposal::mls_decode(reader)?)
+                Proposal::SelfRemove(SelfRemoveProposal::mls_decode_with_limits(reader, limits)?)
             }
             #[cfg(feature = "custom_proposal")]
             custom => Proposal::Custom(CustomProposal {
                 proposal_type: custom,
-                data: mls_rs_codec::byte_vec::mls_decode(reader)?,
+                data: mls_rs_codec::byte_vec::mls_decode_with_limits(reader, limits)?,
             }),
             // TODO fix test dependency on openssl loading codec with default features
             #[cfg(not(feature = "custom_proposal"))]
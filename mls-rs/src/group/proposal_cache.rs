@@ -5,7 +5,7 @@
 use alloc::vec::Vec;
 
 use super::{
-    message_processor::ProvisionalState,
+    message_processor::{FilteredProposal, ProposalFilterReason, ProvisionalState},
     mls_rules::{CommitDirection, CommitSource, MlsRules},
     proposal_filter::prepare_proposals_for_mls_rules,
     GroupState, ProposalOrRef,
@@ -16,12 +16,18 @@ use crate::{
         proposal_filter::{ProposalApplier, ProposalBundle, ProposalSource},
         Proposal, Sender,
     },
+    key_package::KeyPackageRef,
     time::MlsTime,
 };
 
 #[cfg(feature = "by_ref_proposal")]
 use crate::{
-    group::{message_hash::MessageHash, ProposalMessageDescription, ProposalRef, ProtocolVersion},
+    client_config::ClientConfig,
+    group::{
+        framing::{ContentType, MlsMessagePayload},
+        message_hash::MessageHash,
+        Group, ProposalMessageDescription, ProposalRef, ProtocolVersion, ReceivedMessage,
+    },
     MlsMessage,
 };
 
@@ -35,6 +41,9 @@ use mls_rs_core::{
     psk::PreSharedKeyStorage,
 };
 
+#[cfg(feature = "by_ref_proposal")]
+use mls_rs_core::group::{ProposalCacheStorage, StoredProposal};
+
 #[cfg(feature = "by_ref_proposal")]
 use core::fmt::{self, Debug};
 
@@ -145,6 +154,20 @@ impl ProposalCache {
         Ok(())
     }
 
+    pub(crate) fn remove_own(&mut self, proposal_ref: &ProposalRef) {
+        let message_hash = self
+            .own_proposals
+            .iter()
+            .find(|(_, p)| &p.proposal_ref == proposal_ref)
+            .map(|(message_hash, _)| message_hash.clone());
+
+        if let Some(message_hash) = message_hash {
+            self.own_proposals.remove(&message_hash);
+        }
+
+        self.proposals.remove(proposal_ref);
+    }
+
     #[cfg(all(
         feature = "by_ref_proposal",
         feature = "custom_proposal",
@@ -223,6 +246,208 @@ impl ProposalCache {
     }
 }
 
+/// Limits on the proposals a group caches by reference during an epoch.
+#[cfg(feature = "by_ref_proposal")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProposalCacheLimits {
+    pub(crate) max_memory_bytes: Option<usize>,
+    pub(crate) max_proposals: Option<usize>,
+}
+
+#[cfg(feature = "by_ref_proposal")]
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct OffloadedProposal {
+    proposal_ref: ProposalRef,
+    proposal: CachedProposal,
+}
+
+#[cfg(feature = "by_ref_proposal")]
+fn is_commit_message(message: &MlsMessage) -> bool {
+    match &message.payload {
+        MlsMessagePayload::Plain(plaintext) => {
+            plaintext.content.content_type() == ContentType::Commit
+        }
+        #[cfg(feature = "private_message")]
+        MlsMessagePayload::Cipher(ciphertext) => ciphertext.content_type == ContentType::Commit,
+        _ => false,
+    }
+}
+
+#[cfg(feature = "by_ref_proposal")]
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    pub(crate) fn has_cached_proposals(&self) -> bool {
+        !self.state.proposals.is_empty() || !self.offloaded_proposals.is_empty()
+    }
+
+    /// Recover the references of proposals that were offloaded before the
+    /// group was written to storage.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn with_offloaded_proposals(mut self) -> Result<Self, MlsError> {
+        if self
+            .config
+            .proposal_cache_limits()
+            .max_memory_bytes
+            .is_none()
+        {
+            return Ok(self);
+        }
+
+        let proposal_refs = self
+            .config
+            .proposal_cache_storage()
+            .proposal_refs(self.group_id(), self.current_epoch())
+            .await
+            .map_err(|e| MlsError::ProposalCacheStorageError(e.into_any_error()))?;
+
+        self.offloaded_proposals = proposal_refs
+            .into_iter()
+            .map(ProposalRef::from_bytes)
+            .collect();
+
+        Ok(self)
+    }
+
+    /// Bring offloaded proposals back into memory before `message` is
+    /// processed if it is a commit that may reference them.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn load_offloaded_proposals_for(
+        &mut self,
+        message: &MlsMessage,
+    ) -> Result<(), MlsError> {
+        if is_commit_message(message) {
+            self.load_offloaded_proposals().await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn load_offloaded_proposals(&mut self) -> Result<(), MlsError> {
+        if self.offloaded_proposals.is_empty() {
+            return Ok(());
+        }
+
+        let mut storage = self.config.proposal_cache_storage();
+
+        let stored = storage
+            .proposals(self.group_id(), self.current_epoch())
+            .await
+            .map_err(|e| MlsError::ProposalCacheStorageError(e.into_any_error()))?;
+
+        for stored in stored {
+            let offloaded = OffloadedProposal::mls_decode(&mut &*stored.data)?;
+
+            // Storage may still hold proposals that were discarded with
+            // `Group::clear_proposal_cache`.
+            if self.offloaded_proposals.contains(&offloaded.proposal_ref) {
+                self.state.proposals.insert(
+                    offloaded.proposal_ref,
+                    offloaded.proposal.proposal,
+                    offloaded.proposal.sender,
+                );
+            }
+        }
+
+        self.offloaded_proposals.clear();
+
+        storage
+            .delete(self.group_id())
+            .await
+            .map_err(|e| MlsError::ProposalCacheStorageError(e.into_any_error()))
+    }
+
+    /// Enforce the configured limits after `received` was processed.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn enforce_proposal_cache_limits(
+        &mut self,
+        received: &ReceivedMessage,
+    ) -> Result<(), MlsError> {
+        let ReceivedMessage::Proposal(proposal) = received else {
+            return Ok(());
+        };
+
+        let limits = self.config.proposal_cache_limits();
+
+        if let Some(max_proposals) = limits.max_proposals {
+            let cached = self.state.proposals.proposals.len() + self.offloaded_proposals.len();
+
+            if cached > max_proposals {
+                self.state
+                    .proposals
+                    .proposals
+                    .remove(&proposal.proposal_ref);
+                return Err(MlsError::ProposalCacheQuotaExceeded);
+            }
+        }
+
+        self.offload_proposals().await
+    }
+
+    /// Move all cached proposals to the proposal cache storage if they use
+    /// more memory than allowed.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn offload_proposals(&mut self) -> Result<(), MlsError> {
+        let Some(max_memory_bytes) = self.config.proposal_cache_limits().max_memory_bytes else {
+            return Ok(());
+        };
+
+        if self.state.proposals.proposals.mls_encoded_len() <= max_memory_bytes {
+            return Ok(());
+        }
+
+        let mut storage = self.config.proposal_cache_storage();
+        let epoch = self.current_epoch();
+        let mut offloaded = Vec::new();
+
+        for (proposal_ref, proposal) in self.state.proposals.proposals.iter() {
+            let data = OffloadedProposal {
+                proposal_ref: proposal_ref.clone(),
+                proposal: proposal.clone(),
+            }
+            .mls_encode_to_vec()?;
+
+            storage
+                .insert(
+                    self.group_id(),
+                    epoch,
+                    StoredProposal::new(proposal_ref.to_vec(), data),
+                )
+                .await
+                .map_err(|e| MlsError::ProposalCacheStorageError(e.into_any_error()))?;
+
+            offloaded.push(proposal_ref.clone());
+        }
+
+        self.offloaded_proposals.extend(offloaded);
+        self.state.proposals.proposals.clear();
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn delete_offloaded_proposals(&mut self) -> Result<(), MlsError> {
+        self.offloaded_proposals.clear();
+
+        if self
+            .config
+            .proposal_cache_limits()
+            .max_memory_bytes
+            .is_none()
+        {
+            return Ok(());
+        }
+
+        self.config
+            .proposal_cache_storage()
+            .delete(self.group_id())
+            .await
+            .map_err(|e| MlsError::ProposalCacheStorageError(e.into_any_error()))
+    }
+}
+
 #[cfg(not(feature = "by_ref_proposal"))]
 pub(crate) fn prepare_commit(
     sender: Sender,
@@ -267,6 +492,7 @@ impl GroupState {
         user_rules: &F,
         commit_time: Option<MlsTime>,
         direction: CommitDirection,
+        validated_key_packages: &[KeyPackageRef],
     ) -> Result<ProvisionalState, MlsError>
     where
         C: IdentityProvider,
@@ -296,11 +522,17 @@ impl GroupState {
 
         prepare_proposals_for_mls_rules(&mut proposals, direction, &self.public_tree)?;
 
+        let proposals_before_rules = proposals.clone();
+
         proposals = user_rules
             .filter_proposals(direction, origin, &roster, &self.context, proposals)
             .await
             .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
 
+        let filtered_proposals = removed_proposals(proposals_before_rules, &proposals)
+            .map(|proposal| FilteredProposal::new(proposal, ProposalFilterReason::FilteredByRules))
+            .collect::<Vec<_>>();
+
         let applier = ProposalApplier::new(
             &self.public_tree,
             cipher_suite_provider,
@@ -308,7 +540,8 @@ impl GroupState {
             external_leaf,
             identity_provider,
             psk_storage,
-        );
+        )
+        .with_validated_key_packages(validated_key_packages);
 
         #[cfg(feature = "by_ref_proposal")]
         let applier_output = applier
@@ -329,6 +562,22 @@ impl GroupState {
             &applier_output.applied_proposals,
         );
 
+        #[cfg(feature = "by_ref_proposal")]
+        let filtered_proposals = {
+            let reason = match direction {
+                CommitDirection::Send => ProposalFilterReason::Invalid,
+                CommitDirection::Receive => ProposalFilterReason::NotCommitted,
+            };
+
+            let unlisted = unused_proposals
+                .iter()
+                .filter(|p| !filtered_proposals.iter().any(|f| f.proposal == **p))
+                .map(|p| FilteredProposal::new(p.clone(), reason))
+                .collect::<Vec<_>>();
+
+            filtered_proposals.into_iter().chain(unlisted).collect()
+        };
+
         #[cfg(not(feature = "by_ref_proposal"))]
         let unused_proposals = alloc::vec::Vec::default();
 
@@ -349,10 +598,22 @@ impl GroupState {
             external_init_index: applier_output.external_init_index,
             indexes_of_added_kpkgs: applier_output.indexes_of_added_kpkgs,
             unused_proposals,
+            filtered_proposals,
         })
     }
 }
 
+fn removed_proposals(
+    before: ProposalBundle,
+    after: &ProposalBundle,
+) -> impl Iterator<Item = crate::mls_rules::ProposalInfo<Proposal>> + '_ {
+    before.into_proposals().filter(move |p| {
+        !after.iter_proposals().any(|a| {
+            a.sender == p.sender && a.source == p.source && a.proposal == (&p.proposal).into()
+        })
+    })
+}
+
 #[cfg(feature = "by_ref_proposal")]
 impl Extend<(ProposalRef, CachedProposal)> for ProposalCache {
     fn extend<T>(&mut self, iter: T)
@@ -608,6 +869,7 @@ pub(crate) mod test_utils {
                     &user_rules,
                     None,
                     CommitDirection::Receive,
+                    &[],
                 )
                 .await
         }
@@ -652,6 +914,7 @@ pub(crate) mod test_utils {
                     &user_rules,
                     None,
                     CommitDirection::Send,
+                    &[],
                 )
                 .await
         }
@@ -681,13 +944,19 @@ mod tests {
     use crate::tree_kem::node::LeafIndex;
     use crate::tree_kem::TreeKemPublic;
     use crate::{
-        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client::test_utils::{
+            test_client_with_key_pkg, TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+        },
+        client_config::ClientConfig,
         crypto::{self, test_utils::test_cipher_suite_provider},
         extension::test_utils::TestExtension,
         group::{
-            message_processor::path_update_required,
+            message_processor::{path_update_required, FilteredProposal, ProposalFilterReason},
             proposal_filter::proposer_can_propose,
-            test_utils::{get_test_group_context, random_bytes, test_group, TEST_GROUP},
+            test_utils::{
+                get_test_group_context, random_bytes, test_group, test_group_custom_config,
+                TestGroup, TEST_GROUP,
+            },
         },
         identity::basic::BasicIdentityProvider,
         identity::test_utils::{get_test_signing_identity, BasicWithCustomProvider},
@@ -704,6 +973,7 @@ mod tests {
             },
             Lifetime,
         },
+        MlsMessage,
     };
     use crate::{KeyPackage, MlsRules};
 
@@ -898,6 +1168,7 @@ mod tests {
             external_init_index: None,
             indexes_of_added_kpkgs: vec![LeafIndex::unchecked(1)],
             unused_proposals: vec![],
+            filtered_proposals: vec![],
             applied_proposals: bundle,
         };
 
@@ -2131,6 +2402,14 @@ mod tests {
 
         assert_eq!(processed_proposals.0, Vec::new());
 
+        assert_eq!(
+            processed_proposals.1.filtered_proposals,
+            vec![FilteredProposal::new(
+                proposal_info.clone(),
+                ProposalFilterReason::Invalid
+            )]
+        );
+
         assert_eq!(processed_proposals.1.unused_proposals, vec![proposal_info]);
     }
 
@@ -2729,6 +3008,19 @@ mod tests {
         assert_matches!(res, Err(MlsError::DuplicateLeafData(1)));
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sending_same_key_package_twice_fails() {
+        let (alice, tree) = new_tree("alice").await;
+        let add = Proposal::Add(make_add_proposal().await);
+
+        let res = CommitSender::new(&tree, alice, test_cipher_suite_provider(TEST_CIPHER_SUITE))
+            .with_additional([add.clone(), add])
+            .send()
+            .await;
+
+        assert_matches!(res, Err(MlsError::DuplicateKeyPackageInitKey));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn sending_add_proposals_for_same_client_keeps_only_one() {
         let (alice, tree) = new_tree("alice").await;
@@ -2827,7 +3119,7 @@ mod tests {
         .receive([add])
         .await;
 
-        assert_matches!(res, Err(MlsError::DuplicateLeafData(1)));
+        assert_matches!(res, Err(MlsError::KeyPackageAlreadyAdded(1)));
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
@@ -2854,7 +3146,7 @@ mod tests {
         .send()
         .await;
 
-        assert_matches!(res, Err(MlsError::DuplicateLeafData(1)));
+        assert_matches!(res, Err(MlsError::KeyPackageAlreadyAdded(1)));
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
@@ -3923,7 +4215,7 @@ mod tests {
 
         let (alice, tree) = new_tree("alice").await;
 
-        let (committed, _) =
+        let (committed, state) =
             CommitSender::new(&tree, alice, test_cipher_suite_provider(TEST_CIPHER_SUITE))
                 .with_additional([Proposal::GroupContextExtensions(Default::default())])
                 .with_user_rules(RemoveGroupContextExtensions)
@@ -3932,6 +4224,18 @@ mod tests {
                 .unwrap();
 
         assert_eq!(committed, Vec::new());
+
+        assert_matches!(
+            &*state.filtered_proposals,
+            [FilteredProposal {
+                proposal: ProposalInfo {
+                    proposal: Proposal::GroupContextExtensions(_),
+                    source: ProposalSource::ByValue,
+                    ..
+                },
+                reason: ProposalFilterReason::FilteredByRules,
+            }]
+        );
     }
 
     struct FailureMlsRules;
@@ -4258,5 +4562,98 @@ mod tests {
         };
 
         assert_eq!(p.proposal_ref(), Some(&proposal_ref));
+
+        assert_eq!(
+            state.filtered_proposals,
+            vec![FilteredProposal::new(
+                p.clone(),
+                ProposalFilterReason::NotCommitted
+            )]
+        );
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn group_with_member<F>(config: F) -> (TestGroup, TestGroup)
+    where
+        F: FnOnce(TestClientBuilder) -> TestClientBuilder,
+    {
+        let mut alice =
+            test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, config).await;
+
+        let (bob, _) = alice.join("bob").await;
+
+        (alice, bob)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn add_proposal(proposer: &mut TestGroup, name: &str) -> MlsMessage {
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await;
+
+        proposer.propose_add(key_package, vec![]).await.unwrap()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn proposals_over_memory_limit_are_offloaded_until_commit() {
+        let (mut alice, mut bob) = group_with_member(|b| b.proposal_cache_memory_limit(1)).await;
+
+        let proposal = add_proposal(&mut bob, "charlie").await;
+        alice.process_incoming_message(proposal).await.unwrap();
+
+        let storage = alice.config.proposal_cache_storage();
+
+        assert!(alice.state.proposals.is_empty());
+        assert_eq!(alice.offloaded_proposals.len(), 1);
+        assert_eq!(storage.len(alice.group_id()), 1);
+        assert!(alice.commit_required());
+
+        let commit = alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        assert_eq!(alice.roster().members().len(), 3);
+        assert!(!alice.commit_required());
+        assert_eq!(storage.len(alice.group_id()), 0);
+
+        bob.process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(alice.context(), bob.context());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn offloaded_proposals_are_loaded_for_received_commit() {
+        let (mut alice, mut bob) = group_with_member(|b| b.proposal_cache_memory_limit(1)).await;
+
+        let proposal = add_proposal(&mut bob, "charlie").await;
+        alice.process_incoming_message(proposal).await.unwrap();
+
+        assert_eq!(alice.offloaded_proposals.len(), 1);
+
+        let commit = bob.commit(vec![]).await.unwrap();
+        bob.apply_pending_commit().await.unwrap();
+
+        alice
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(alice.roster().members().len(), 3);
+        assert!(alice.offloaded_proposals.is_empty());
+        assert_eq!(alice.context(), bob.context());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn proposals_over_quota_are_rejected() {
+        let (mut alice, mut bob) = group_with_member(|b| b.proposal_cache_max_proposals(1)).await;
+
+        let proposal = add_proposal(&mut bob, "charlie").await;
+        alice.process_incoming_message(proposal).await.unwrap();
+
+        let proposal = add_proposal(&mut bob, "dave").await;
+        let res = alice.process_incoming_message(proposal).await;
+
+        assert_matches!(res, Err(MlsError::ProposalCacheQuotaExceeded));
+        assert_eq!(alice.state.proposals.proposals.len(), 1);
     }
 }
@@ -253,12 +253,16 @@ where
             proposals.update_senders.remove(i);
         });
 
-        let bad_indices: Vec<_> = wrap_iter(proposals.add_proposals())
+        let adds = proposals.add_proposals();
+        let add_validator = &self.add_validator(new_extensions);
+
+        let bad_indices: Vec<_> = wrap_iter(adds)
             .enumerate()
             .filter_map(|(i, p)| async move {
                 let res = self
-                    .validate_new_node(leaf_node_validator, &p.proposal.key_package, commit_time)
-                    .await;
+                    .validate_new_node(add_validator, &p.proposal.key_package, commit_time)
+                    .await
+                    .and_then(|_| self.check_key_package_reuse(adds, i));
 
                 apply_strategy(strategy, p.is_by_reference(), res)
                     .map(|b| (!b).then_some(i))
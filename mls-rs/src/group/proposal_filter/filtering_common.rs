@@ -5,7 +5,7 @@
 use crate::{
     client::MlsError,
     group::{proposal_filter::ProposalBundle, GroupContext, Sender},
-    key_package::{validate_key_package_properties, KeyPackage},
+    key_package::{validate_key_package_properties, KeyPackage, KeyPackageRef},
     mls_rules::CommitDirection,
     time::MlsTime,
     tree_kem::{
@@ -25,12 +25,15 @@ use crate::extension::{MlsExtension, RequiredCapabilitiesExt};
 #[cfg(feature = "by_ref_proposal")]
 use crate::extension::ExternalSendersExt;
 
-use mls_rs_core::{error::IntoAnyError, identity::MemberValidationContext};
+use mls_rs_core::{
+    error::IntoAnyError,
+    identity::{MemberValidationContext, MemberValidationPurpose},
+};
 
 use alloc::vec::Vec;
 use mls_rs_core::{identity::IdentityProvider, psk::PreSharedKeyStorage};
 
-use crate::group::{ExternalInit, ProposalType, RemoveProposal};
+use crate::group::{AddProposal, ExternalInit, ProposalType, RemoveProposal};
 
 #[cfg(all(feature = "by_ref_proposal", feature = "psk"))]
 use crate::group::proposal::PreSharedKeyProposal;
@@ -55,6 +58,7 @@ pub(crate) struct ProposalApplier<'a, C, P, CSP> {
     pub external_leaf: Option<&'a LeafNode>,
     pub identity_provider: &'a C,
     pub psk_storage: &'a P,
+    pub validated_key_packages: &'a [KeyPackageRef],
 }
 
 #[derive(Debug)]
@@ -89,9 +93,55 @@ where
             external_leaf,
             identity_provider,
             psk_storage,
+            validated_key_packages: &[],
         }
     }
 
+    /// Skip validation of key packages in `validated_key_packages`. They must
+    /// have been validated against the same group context.
+    pub(crate) fn with_validated_key_packages(
+        self,
+        validated_key_packages: &'a [KeyPackageRef],
+    ) -> Self {
+        Self {
+            validated_key_packages,
+            ..self
+        }
+    }
+
+    /// Validator for the leaf nodes of key packages added by a commit that
+    /// sets the group context extensions to `new_extensions`.
+    pub(crate) fn add_validator<'b>(
+        &self,
+        new_extensions: &'b ExtensionList,
+    ) -> LeafNodeValidator<'b, C, CSP>
+    where
+        'a: 'b,
+    {
+        let member_validation_context = MemberValidationContext::ForCommit {
+            current_context: self.original_context,
+            new_extensions,
+        };
+
+        LeafNodeValidator::new(
+            self.cipher_suite_provider,
+            self.identity_provider,
+            member_validation_context,
+        )
+        .with_purpose(MemberValidationPurpose::Add)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn was_validated(&self, key_package: &KeyPackage) -> Result<bool, MlsError> {
+        if self.validated_key_packages.is_empty() {
+            return Ok(false);
+        }
+
+        let reference = key_package.to_reference(self.cipher_suite_provider).await?;
+
+        Ok(self.validated_key_packages.contains(&reference))
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn apply_proposals(
         &self,
@@ -298,6 +348,31 @@ where
         }
     }
 
+    /// Check that the key package of the add at `index` in `adds` is not
+    /// reused, either because it was already added to the group, e.g. by an
+    /// overlapping commit of another member, or because an earlier add uses
+    /// the same init key.
+    pub(super) fn check_key_package_reuse(
+        &self,
+        adds: &[ProposalInfo<AddProposal>],
+        index: usize,
+    ) -> Result<(), MlsError> {
+        let key_package = &adds[index].proposal.key_package;
+
+        if let Some(existing) = self
+            .original_tree
+            .find_existing_leaf(&key_package.leaf_node)
+        {
+            return Err(MlsError::KeyPackageAlreadyAdded(*existing));
+        }
+
+        adds[..index]
+            .iter()
+            .all(|p| p.proposal.key_package.hpke_init_key != key_package.hpke_init_key)
+            .then_some(())
+            .ok_or(MlsError::DuplicateKeyPackageInitKey)
+    }
+
     #[cfg(any(mls_build_async, not(feature = "rayon")))]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn validate_new_node<Ip: IdentityProvider, Cp: CipherSuiteProvider>(
@@ -306,6 +381,10 @@ where
         key_package: &KeyPackage,
         commit_time: Option<MlsTime>,
     ) -> Result<(), MlsError> {
+        if self.was_validated(key_package).await? {
+            return Ok(());
+        }
+
         leaf_node_validator
             .check_if_valid(&key_package.leaf_node, ValidationContext::Add(commit_time))
             .await?;
@@ -325,6 +404,10 @@ where
         key_package: &KeyPackage,
         commit_time: Option<MlsTime>,
     ) -> Result<(), MlsError> {
+        if self.was_validated(key_package)? {
+            return Ok(());
+        }
+
         let (a, b) = rayon::join(
             || {
                 leaf_node_validator
@@ -3,12 +3,8 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use crate::{
-    client::MlsError,
-    group::proposal_filter::ProposalBundle,
-    iter::wrap_iter,
-    protocol_version::ProtocolVersion,
-    time::MlsTime,
-    tree_kem::{leaf_node_validator::LeafNodeValidator, node::LeafIndex},
+    client::MlsError, group::proposal_filter::ProposalBundle, iter::wrap_iter,
+    protocol_version::ProtocolVersion, time::MlsTime, tree_kem::node::LeafIndex,
     CipherSuiteProvider, ExtensionList,
 };
 
@@ -17,10 +13,7 @@ use super::filtering_common::{filter_out_invalid_psks, ApplyProposalsOutput, Pro
 #[cfg(feature = "by_ref_proposal")]
 use {crate::extension::ExternalSendersExt, mls_rs_core::error::IntoAnyError};
 
-use mls_rs_core::{
-    identity::{IdentityProvider, MemberValidationContext},
-    psk::PreSharedKeyStorage,
-};
+use mls_rs_core::{identity::IdentityProvider, psk::PreSharedKeyStorage};
 
 #[cfg(feature = "custom_proposal")]
 use itertools::Itertools;
@@ -127,16 +120,7 @@ where
         new_extensions: &ExtensionList,
         commit_time: Option<MlsTime>,
     ) -> Result<(), MlsError> {
-        let member_validation_context = MemberValidationContext::ForCommit {
-            current_context: self.original_context,
-            new_extensions,
-        };
-
-        let leaf_node_validator = &LeafNodeValidator::new(
-            self.cipher_suite_provider,
-            self.identity_provider,
-            member_validation_context,
-        );
+        let leaf_node_validator = &self.add_validator(new_extensions);
 
         let adds = wrap_iter(proposals.add_proposals());
 
@@ -147,7 +131,10 @@ where
             .try_for_each(|p| {
                 self.validate_new_node(leaf_node_validator, &p.proposal.key_package, commit_time)
             })
-            .await
+            .await?;
+
+        (0..proposals.add_proposals().len())
+            .try_for_each(|i| self.check_key_package_reuse(proposals.add_proposals(), i))
     }
 }
 
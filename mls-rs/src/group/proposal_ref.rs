@@ -43,6 +43,10 @@ impl ProposalRef {
     pub fn as_slice(&self) -> &[u8] {
         &self.0
     }
+
+    pub(crate) fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes.into())
+    }
 }
 
 #[cfg(test)]
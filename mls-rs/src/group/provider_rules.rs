@@ -0,0 +1,168 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_core::group::GroupContext;
+use mls_rs_provider_api::v1::rules::{CommitPolicy, EncryptionPolicy, GroupRules};
+
+#[cfg(feature = "private_message")]
+use mls_rs_provider_api::v1::rules::Padding;
+
+#[cfg(feature = "private_message")]
+use super::padding::PaddingMode;
+
+use super::{
+    mls_rules::{CommitDirection, CommitOptions, CommitSource, EncryptionOptions, MlsRules},
+    proposal_filter::ProposalBundle,
+    Roster,
+};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+
+/// [`MlsRules`] implemented by a [`GroupRules`] provider of the
+/// `mls-rs-provider-api` crate.
+///
+/// Proposals are never filtered or modified.
+#[derive(Clone, Debug, Default)]
+pub struct ProviderRules<R>(pub R);
+
+impl<R> ProviderRules<R> {
+    pub fn new(rules: R) -> Self {
+        Self(rules)
+    }
+}
+
+impl From<CommitPolicy> for CommitOptions {
+    fn from(policy: CommitPolicy) -> Self {
+        CommitOptions::new()
+            .with_path_required(policy.path_required)
+            .with_ratchet_tree_extension(policy.ratchet_tree_extension)
+            .with_single_welcome_message(policy.single_welcome_message)
+            .with_allow_external_commit(policy.allow_external_commit)
+    }
+}
+
+impl From<EncryptionPolicy> for EncryptionOptions {
+    #[cfg(feature = "private_message")]
+    fn from(policy: EncryptionPolicy) -> Self {
+        let padding_mode = match policy.padding {
+            Padding::Padme => PaddingMode::Padme,
+            Padding::None => PaddingMode::None,
+            _ => PaddingMode::StepFunction,
+        };
+
+        EncryptionOptions::new(policy.encrypt_control_messages, padding_mode)
+    }
+
+    #[cfg(not(feature = "private_message"))]
+    fn from(_: EncryptionPolicy) -> Self {
+        EncryptionOptions::default()
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<R: GroupRules> MlsRules for ProviderRules<R> {
+    type Error = R::Error;
+
+    async fn filter_proposals(
+        &self,
+        _direction: CommitDirection,
+        _source: CommitSource,
+        _current_roster: &Roster,
+        _: &GroupContext,
+        proposals: ProposalBundle,
+    ) -> Result<ProposalBundle, Self::Error> {
+        Ok(proposals)
+    }
+
+    fn commit_options(
+        &self,
+        new_roster: &Roster,
+        new_context: &GroupContext,
+        _: &ProposalBundle,
+    ) -> Result<CommitOptions, Self::Error> {
+        let members: Vec<_> = new_roster.members();
+
+        self.0
+            .commit_policy(&members, new_context)
+            .map(CommitOptions::from)
+    }
+
+    fn encryption_options(
+        &self,
+        current_roster: &Roster,
+        current_context: &GroupContext,
+    ) -> Result<EncryptionOptions, Self::Error> {
+        let members: Vec<_> = current_roster.members();
+
+        self.0
+            .encryption_policy(&members, current_context)
+            .map(EncryptionOptions::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use mls_rs_core::group::{GroupContext, Member};
+    use mls_rs_provider_api::v1::rules::{CommitPolicy, EncryptionPolicy, GroupRules};
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{
+            mls_rules::{CommitOptions, MlsRules},
+            test_utils::test_group,
+        },
+    };
+
+    use super::ProviderRules;
+
+    struct LargeGroupRules;
+
+    impl GroupRules for LargeGroupRules {
+        type Error = Infallible;
+
+        fn commit_policy(
+            &self,
+            new_members: &[Member],
+            _: &GroupContext,
+        ) -> Result<CommitPolicy, Infallible> {
+            Ok(CommitPolicy::new().with_ratchet_tree_extension(new_members.len() < 2))
+        }
+
+        fn encryption_policy(
+            &self,
+            _: &[Member],
+            _: &GroupContext,
+        ) -> Result<EncryptionPolicy, Infallible> {
+            Ok(EncryptionPolicy::new().with_encrypt_control_messages(true))
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn provider_rules_are_adapted() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let rules = ProviderRules::new(LargeGroupRules);
+
+        let options = rules
+            .commit_options(&group.roster(), group.context(), &Default::default())
+            .unwrap();
+
+        assert_eq!(
+            options,
+            CommitOptions::new().with_ratchet_tree_extension(true)
+        );
+
+        #[cfg(feature = "private_message")]
+        assert!(
+            rules
+                .encryption_options(&group.roster(), group.context())
+                .unwrap()
+                .encrypt_control_messages
+        );
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(feature = "by_ref_proposal")]
+use alloc::vec::Vec;
+
+use mls_rs_core::crypto::CipherSuiteProvider;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    psk::{resolver::PskResolver, JustPreSharedKeyID, ResumptionPSKUsage, ResumptionPsk},
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::{
+    psk::{PreSharedKeyID, PskGroupId},
+    MlsMessage,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use super::proposal::Proposal;
+
+use super::{proposal::PreSharedKeyProposal, Group};
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a proposal message that adds the resumption secret of
+    /// `psk_epoch` of this group with the given `usage`.
+    ///
+    /// Only [`ResumptionPSKUsage::Application`] is allowed in a proposal,
+    /// other usages return
+    /// [`MlsError::InvalidTypeOrUsageInPreSharedKeyProposal`]. The proposal
+    /// is checked with [`Group::validate_psk_proposal`] before it is sent.
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn propose_resumption_psk_with_usage(
+        &mut self,
+        psk_epoch: u64,
+        usage: ResumptionPSKUsage,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        let key_id = JustPreSharedKeyID::Resumption(ResumptionPsk {
+            usage,
+            psk_group_id: PskGroupId(self.group_id().to_vec()),
+            psk_epoch,
+        });
+
+        let proposal = PreSharedKeyProposal {
+            psk: PreSharedKeyID::new(key_id, &self.cipher_suite_provider)?,
+        };
+
+        self.validate_psk_proposal(&proposal).await?;
+
+        self.proposal_message(Proposal::Psk(proposal), authenticated_data)
+            .await
+    }
+
+    /// Check that `proposal` can be committed in the current epoch.
+    ///
+    /// The proposal must reference an external key or an application
+    /// resumption key, its nonce must have the length required by the cipher
+    /// suite, and the referenced key must be available to this member, i.e.
+    /// in the [`PreSharedKeyStorage`](crate::PreSharedKeyStorage) or in the
+    /// retained prior epochs of this group.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn validate_psk_proposal(
+        &self,
+        proposal: &PreSharedKeyProposal,
+    ) -> Result<(), MlsError> {
+        let valid_usage = matches!(
+            proposal.psk.key_id,
+            JustPreSharedKeyID::External(_)
+                | JustPreSharedKeyID::Resumption(ResumptionPsk {
+                    usage: ResumptionPSKUsage::Application,
+                    ..
+                })
+        );
+
+        if !valid_usage {
+            return Err(MlsError::InvalidTypeOrUsageInPreSharedKeyProposal);
+        }
+
+        if proposal.psk.psk_nonce.0.len() != self.cipher_suite_provider.kdf_extract_size() {
+            return Err(MlsError::InvalidPskNonceLength);
+        }
+
+        PskResolver {
+            group_context: Some(self.context()),
+            current_epoch: Some(&self.epoch_secrets),
+            prior_epochs: Some(&self.state_repo),
+            psk_store: &self.config.secret_store(),
+        }
+        .resolve_to_secret(
+            core::slice::from_ref(&proposal.psk),
+            &self.cipher_suite_provider,
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        client_config::ClientConfig,
+        group::{
+            proposal::PreSharedKeyProposal,
+            test_utils::{test_group, test_n_member_group},
+        },
+        psk::{ExternalPskId, JustPreSharedKeyID, PreSharedKeyID, PreSharedKeyReference, PskNonce},
+    };
+
+    #[cfg(feature = "by_ref_proposal")]
+    use crate::{group::ReceivedMessage, psk::ResumptionPSKUsage};
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn resumption_psk_proposal_is_described() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let epoch = groups[0].current_epoch();
+
+        let res = groups[0]
+            .propose_resumption_psk_with_usage(epoch, ResumptionPSKUsage::Reinit, vec![])
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidTypeOrUsageInPreSharedKeyProposal));
+
+        let proposal = groups[0]
+            .propose_resumption_psk_with_usage(epoch, ResumptionPSKUsage::Application, vec![])
+            .await
+            .unwrap();
+
+        let ReceivedMessage::Proposal(description) =
+            groups[1].process_message(proposal).await.unwrap()
+        else {
+            panic!("expected proposal");
+        };
+
+        assert_eq!(
+            description.psk_reference(),
+            Some(PreSharedKeyReference::Resumption {
+                usage: ResumptionPSKUsage::Application,
+                group_id: groups[0].group_id().to_vec(),
+                epoch,
+            })
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_psk_proposal_is_validated() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let psk_id = ExternalPskId::new(vec![1]);
+
+        let mut proposal = PreSharedKeyProposal {
+            psk: PreSharedKeyID::new(
+                JustPreSharedKeyID::External(psk_id.clone()),
+                &group.cipher_suite_provider,
+            )
+            .unwrap(),
+        };
+
+        assert_eq!(
+            proposal.reference(),
+            PreSharedKeyReference::External(psk_id.clone())
+        );
+
+        let res = group.validate_psk_proposal(&proposal).await;
+        assert_matches!(res, Err(MlsError::MissingRequiredPsk));
+
+        group.config.secret_store().insert(psk_id, vec![2].into());
+
+        group.validate_psk_proposal(&proposal).await.unwrap();
+
+        proposal.psk.psk_nonce = PskNonce(vec![0; 3]);
+
+        let res = group.validate_psk_proposal(&proposal).await;
+        assert_matches!(res, Err(MlsError::InvalidPskNonceLength));
+    }
+}
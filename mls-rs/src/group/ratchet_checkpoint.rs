@@ -0,0 +1,191 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::{Group, ReceivedMessage};
+
+/// Changes to the group since it was last written to storage.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RatchetCheckpoint {
+    epoch: Option<u64>,
+    received: u32,
+    dirty: bool,
+}
+
+impl RatchetCheckpoint {
+    pub(crate) fn new(epoch: u64) -> Self {
+        Self {
+            epoch: Some(epoch),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn record(&mut self, received: &ReceivedMessage) {
+        match received {
+            ReceivedMessage::ApplicationMessage(_) => {
+                self.received = self.received.saturating_add(1)
+            }
+            _ => self.dirty = true,
+        }
+    }
+
+    pub(crate) fn record_sent(&mut self) {
+        self.dirty = true;
+    }
+
+    fn is_due(&self, epoch: u64, interval: u32) -> bool {
+        self.dirty || self.epoch != Some(epoch) || self.received >= interval.max(1)
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Number of application messages decrypted since the group was last
+    /// written to storage.
+    ///
+    /// After restoring the stored state, the secret tree derives the keys of
+    /// these messages again when later messages are received.
+    pub fn unpersisted_application_messages(&self) -> u32 {
+        self.ratchet_checkpoint.received
+    }
+
+    /// Whether [`Group::write_ratchet_checkpoint`] would write the group to
+    /// storage.
+    pub fn ratchet_checkpoint_due(&self) -> bool {
+        self.ratchet_checkpoint.is_due(
+            self.context().epoch,
+            self.forward_secrecy.ratchet_checkpoint_interval,
+        )
+    }
+
+    /// Write the group to storage if a checkpoint is due, returning whether
+    /// it was written.
+    ///
+    /// This can be called in place of [`Group::write_to_storage`] after each
+    /// processed message. Decrypting application messages only advances the
+    /// secret tree ratchets of other members, so the write is skipped until
+    /// [`ClientBuilder::ratchet_checkpoint_interval`](crate::client_builder::ClientBuilder::ratchet_checkpoint_interval)
+    /// messages were decrypted. The state is always written after the epoch
+    /// changed, after any other message was processed and after a message was
+    /// sent, since the sending ratchet must never be restored to a generation
+    /// that was already used.
+    ///
+    /// # Warning
+    ///
+    /// Application messages decrypted since the last checkpoint can be
+    /// decrypted again after restoring the stored state. Applications that
+    /// can't tolerate this should deduplicate messages by their
+    /// [`generation`](super::ApplicationMessageDescription::generation).
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn write_ratchet_checkpoint(&mut self) -> Result<bool, MlsError> {
+        if !self.ratchet_checkpoint_due() {
+            return Ok(false);
+        }
+
+        self.write_to_storage().await?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            Client,
+        },
+        group::test_utils::{test_group_custom_config, TestGroup},
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_groups(interval: u32) -> (TestGroup, TestGroup) {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.ratchet_checkpoint_interval(interval)
+        })
+        .await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.ratchet_checkpoint_interval = interval
+            })
+            .await
+            .unwrap();
+
+        alice.write_to_storage().await.unwrap();
+        bob.write_to_storage().await.unwrap();
+
+        (alice, bob)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn checkpoint_is_written_every_interval() {
+        let (mut alice, mut bob) = test_groups(3).await;
+
+        for i in 1..=3 {
+            let message = alice
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+
+            bob.process_incoming_message(message).await.unwrap();
+
+            let written = bob.write_ratchet_checkpoint().await.unwrap();
+            assert_eq!(written, i == 3);
+        }
+
+        assert_eq!(bob.unpersisted_application_messages(), 0);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn checkpoint_is_always_written_after_sending() {
+        let (mut alice, _) = test_groups(3).await;
+
+        assert!(!alice.ratchet_checkpoint_due());
+
+        alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let written = alice.write_ratchet_checkpoint().await.unwrap();
+        assert!(written);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn restored_group_catches_up_on_skipped_generations() {
+        let (mut alice, mut bob) = test_groups(3).await;
+        let group_id = bob.group_id().to_vec();
+
+        for _ in 0..2 {
+            let message = alice
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+
+            bob.process_incoming_message(message).await.unwrap();
+            let written = bob.write_ratchet_checkpoint().await.unwrap();
+            assert!(!written);
+        }
+
+        let mut restored = Client::new(bob.config.clone(), None, None, TEST_PROTOCOL_VERSION)
+            .load_group(&group_id)
+            .await
+            .unwrap();
+
+        let message = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        restored.process_incoming_message(message).await.unwrap();
+
+        assert_eq!(restored.sender_generations(0).unwrap().next, 3);
+    }
+}
@@ -0,0 +1,321 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_codec::MlsDecode;
+
+use crate::{client::MlsError, client_config::ClientConfig, mls_rules::ProposalInfo, MlsMessage};
+
+use super::{
+    commit::PendingCommit, message_hash::MessageHash, proposal::Proposal,
+    snapshot::PendingCommitSnapshot, CommitEffect, CommitMessageDescription, CommitOutput, Group,
+    GroupState, ReceivedMessage, Sender,
+};
+
+/// Result of [`Group::rebase_pending_commit`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RebasedCommit {
+    /// Description of the commit that was accepted instead of the pending
+    /// commit.
+    pub accepted_commit: CommitMessageDescription,
+    /// New pending commit for the next epoch. This is `None` if none of the
+    /// proposals of the rejected commit remain valid or if this member can
+    /// no longer commit, e.g. because it was removed.
+    pub commit_output: Option<CommitOutput>,
+    /// Proposals of the rejected commit that are not part of the new commit
+    /// and were not applied by the accepted commit.
+    pub dropped_proposals: Vec<ProposalInfo<Proposal>>,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Process `new_commit_message`, a commit that was accepted by the
+    /// delivery service instead of the pending commit of this group, and
+    /// create a new pending commit with the proposals of the rejected commit
+    /// that are still valid in the new epoch.
+    ///
+    /// Only proposals sent by this member are carried over. Proposals sent by
+    /// other members, proposals already applied by `new_commit_message` and
+    /// removals of members whose leaf changed owner are dropped. The signing
+    /// identity, leaf node extensions and authenticated data of the rejected
+    /// commit are kept.
+    ///
+    /// If `new_commit_message` is the pending commit itself, it is applied and
+    /// no new commit is created.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn rebase_pending_commit(
+        &mut self,
+        new_commit_message: MlsMessage,
+    ) -> Result<RebasedCommit, MlsError> {
+        let pending = match &self.pending_commit {
+            PendingCommitSnapshot::PendingCommit(bytes) => {
+                PendingCommit::mls_decode(&mut &**bytes)?
+            }
+            _ => return Err(MlsError::PendingCommitNotFound),
+        };
+
+        let message_hash =
+            MessageHash::compute(&self.cipher_suite_provider, &new_commit_message).await?;
+
+        let ReceivedMessage::Commit(accepted_commit) =
+            self.process_incoming_message(new_commit_message).await?
+        else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        if message_hash == pending.commit_message_hash {
+            return Ok(RebasedCommit {
+                accepted_commit,
+                commit_output: None,
+                dropped_proposals: Vec::new(),
+            });
+        }
+
+        let self_sender = Sender::Member(*self.private_tree.self_index);
+
+        let (prior_state, proposals) = match pending.output.effect {
+            CommitEffect::NewEpoch(new_epoch) | CommitEffect::Removed { new_epoch, .. } => {
+                (Some(new_epoch.prior_state), new_epoch.applied_proposals)
+            }
+            CommitEffect::ReInit(reinit) => (
+                None,
+                vec![ProposalInfo {
+                    proposal: Proposal::ReInit(reinit.proposal),
+                    sender: reinit.sender,
+                    source: reinit.source,
+                }],
+            ),
+        };
+
+        let accepted_proposals = match &accepted_commit.effect {
+            CommitEffect::NewEpoch(new_epoch) => Some(&new_epoch.applied_proposals),
+            _ => None,
+        };
+
+        let had_proposals = !proposals.is_empty();
+        let mut kept = Vec::new();
+        let mut dropped_proposals = Vec::new();
+
+        for info in proposals {
+            let already_applied = accepted_proposals
+                .map(|applied| applied.iter().any(|p| p.proposal == info.proposal))
+                .unwrap_or_default();
+
+            if already_applied {
+                continue;
+            }
+
+            let keep = accepted_proposals.is_some()
+                && self.state.pending_reinit.is_none()
+                && info.sender == self_sender
+                && self.is_still_valid(&info.proposal, prior_state.as_ref());
+
+            if keep {
+                kept.push(info.proposal);
+            } else {
+                dropped_proposals.push(info);
+            }
+        }
+
+        if accepted_proposals.is_none() || (kept.is_empty() && had_proposals) {
+            return Ok(RebasedCommit {
+                accepted_commit,
+                commit_output: None,
+                dropped_proposals,
+            });
+        }
+
+        let current_leaf = self.current_user_leaf_node()?.clone();
+        let self_index = self.private_tree.self_index;
+
+        let mut builder = self
+            .commit_builder()
+            .raw_proposals(kept)
+            .authenticated_data(pending.output.authenticated_data);
+
+        if let Ok(new_leaf) = pending.state.public_tree.get_leaf_node(self_index) {
+            if new_leaf.signing_identity != current_leaf.signing_identity {
+                builder = builder
+                    .set_new_signing_identity(pending.signer, new_leaf.signing_identity.clone());
+            }
+
+            if new_leaf.ungreased_extensions() != current_leaf.ungreased_extensions() {
+                builder = builder.set_leaf_node_extensions(new_leaf.ungreased_extensions());
+            }
+        }
+
+        let commit_output = builder.build().await?;
+
+        Ok(RebasedCommit {
+            accepted_commit,
+            commit_output: Some(commit_output),
+            dropped_proposals,
+        })
+    }
+
+    fn is_still_valid(&self, proposal: &Proposal, prior_state: Option<&GroupState>) -> bool {
+        let tree = &self.state.public_tree;
+
+        match proposal {
+            Proposal::Add(add) => !tree.non_empty_leaves().any(|(_, leaf)| {
+                leaf.signing_identity == add.key_package.leaf_node.signing_identity
+            }),
+            Proposal::Remove(remove) => {
+                let prior_leaf = prior_state
+                    .and_then(|state| state.public_tree.get_leaf_node(remove.to_remove).ok());
+
+                let current_leaf = tree.get_leaf_node(remove.to_remove).ok();
+
+                matches!(
+                    (prior_leaf, current_leaf),
+                    (Some(prior), Some(current)) if prior.signing_identity == current.signing_identity
+                )
+            }
+            #[cfg(feature = "by_ref_proposal")]
+            Proposal::Update(_) => false,
+            Proposal::ExternalInit(_) => false,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{proposal::Proposal, test_utils::test_group, CommitEffect, ReceivedMessage},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rejected_commit_is_rebased_onto_accepted_commit() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let (_, carol) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+        let (_, dave) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "dave").await;
+
+        alice
+            .commit_builder()
+            .add_member(carol)
+            .unwrap()
+            .authenticated_data(b"ad".to_vec())
+            .build()
+            .await
+            .unwrap();
+
+        let bob_commit = bob
+            .commit_builder()
+            .add_member(dave)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        bob.apply_pending_commit().await.unwrap();
+
+        let rebased = alice
+            .rebase_pending_commit(bob_commit.commit_message)
+            .await
+            .unwrap();
+
+        assert!(rebased.dropped_proposals.is_empty());
+        assert_eq!(alice.context().epoch, 2);
+
+        let commit = rebased.commit_output.unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let received = bob.process_message(commit.commit_message).await.unwrap();
+        let ReceivedMessage::Commit(description) = received else {
+            panic!("expected a commit");
+        };
+
+        assert_eq!(description.authenticated_data, b"ad".to_vec());
+
+        let CommitEffect::NewEpoch(new_epoch) = description.effect else {
+            panic!("expected a new epoch");
+        };
+
+        assert_matches!(
+            new_epoch.applied_proposals.as_slice(),
+            [info] if matches!(info.proposal, Proposal::Add(_))
+        );
+
+        assert_eq!(alice.roster().members_iter().count(), 4);
+        assert_eq!(alice.context(), bob.context());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn proposals_applied_by_accepted_commit_are_not_recommitted() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let (_, carol) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        alice
+            .commit_builder()
+            .add_member(carol.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let bob_commit = bob
+            .commit_builder()
+            .add_member(carol)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let rebased = alice
+            .rebase_pending_commit(bob_commit.commit_message)
+            .await
+            .unwrap();
+
+        assert!(rebased.commit_output.is_none());
+        assert!(rebased.dropped_proposals.is_empty());
+        assert_eq!(alice.roster().members_iter().count(), 3);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn own_commit_is_applied_without_rebasing() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (_bob, _) = alice.join("bob").await;
+
+        let commit = alice.commit(vec![]).await.unwrap();
+
+        let rebased = alice
+            .rebase_pending_commit(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert!(rebased.commit_output.is_none());
+        assert!(!alice.has_pending_commit());
+        assert_eq!(alice.context().epoch, 2);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rebasing_requires_pending_commit() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let commit = bob.commit(vec![]).await.unwrap();
+
+        let res = alice.rebase_pending_commit(commit.commit_message).await;
+
+        assert_matches!(res, Err(MlsError::PendingCommitNotFound));
+    }
+}
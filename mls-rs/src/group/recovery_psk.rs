@@ -0,0 +1,453 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+#[cfg(feature = "private_message")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "private_message")]
+use mls_rs_core::group::Member;
+
+#[cfg(feature = "private_message")]
+use crate::{group::ContentType, mls_rules::MessageAuthorization, WireFormat};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    error::IntoAnyError,
+    extension::{ExtensionList, ExtensionType, MlsCodecExtension},
+    group::GroupContext,
+    psk::{ExternalPskId, PreSharedKeyStorage},
+    time::MlsTime,
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    mls_rules::{CommitDirection, CommitOptions, CommitSource, EncryptionOptions, ProposalBundle},
+    Client, MlsMessage, MlsRules,
+};
+
+use super::{external_commit::ExternalCommitBuilder, Group, Roster};
+
+/// Disaster recovery ("escrow") pre-shared key registered in the group
+/// context when the group is created.
+///
+/// The key itself is held by a recovery service outside of the group. If all
+/// devices of a user are lost, the user can rejoin the group by external
+/// commit with [`Client::recovery_commit_builder`], which injects the
+/// recovery key so that only parties holding it can complete the recovery.
+/// Members detect such commits with [`RecoveryPskRules`].
+///
+/// Since it is a group context extension, all members must list
+/// [`RecoveryPskExt::EXTENSION_TYPE`] among their supported extension types.
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct RecoveryPskExt {
+    psk_id: ExternalPskId,
+}
+
+impl RecoveryPskExt {
+    /// Extension type of the recovery PSK, taken from the private use range.
+    pub const EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF0A2);
+
+    /// Create a new recovery PSK extension.
+    pub fn new(psk_id: ExternalPskId) -> Self {
+        Self { psk_id }
+    }
+
+    /// Identifier of the recovery PSK.
+    pub fn psk_id(&self) -> &ExternalPskId {
+        &self.psk_id
+    }
+
+    fn from_extensions(extensions: &ExtensionList) -> Result<Option<ExternalPskId>, MlsError> {
+        Ok(extensions.get_as::<Self>()?.map(|ext| ext.psk_id))
+    }
+}
+
+impl MlsCodecExtension for RecoveryPskExt {
+    fn extension_type() -> ExtensionType {
+        Self::EXTENSION_TYPE
+    }
+}
+
+impl<C> Client<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a group with `recovery_psk` registered as its disaster recovery
+    /// PSK. See [`RecoveryPskExt`].
+    ///
+    /// The PSK must be present in the
+    /// [`PreSharedKeyStorage`](crate::PreSharedKeyStorage) of this client,
+    /// otherwise [`MlsError::MissingRequiredPsk`] is returned.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn create_group_with_recovery_psk(
+        &self,
+        mut group_context_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+        recovery_psk: ExternalPskId,
+        timestamp: Option<MlsTime>,
+    ) -> Result<Group<C>, MlsError> {
+        let found = self
+            .config
+            .secret_store()
+            .contains(&recovery_psk)
+            .await
+            .map_err(|e| MlsError::PskStoreError(e.into_any_error()))?;
+
+        if !found {
+            return Err(MlsError::MissingRequiredPsk);
+        }
+
+        group_context_extensions.set_from(RecoveryPskExt::new(recovery_psk))?;
+
+        self.create_group(group_context_extensions, leaf_node_extensions, timestamp)
+            .await
+    }
+
+    /// Start the recovery join flow for the group described by `group_info`.
+    ///
+    /// The returned builder injects the recovery PSK registered in the group
+    /// context of `group_info`, which must be present in the
+    /// [`PreSharedKeyStorage`](crate::PreSharedKeyStorage) of this client.
+    /// The external commit is produced by
+    /// [`ExternalCommitBuilder::build`] with the same `group_info`.
+    pub fn recovery_commit_builder(
+        &self,
+        group_info: &MlsMessage,
+    ) -> Result<ExternalCommitBuilder<C>, MlsError> {
+        let group_info = group_info
+            .as_group_info()
+            .ok_or(MlsError::UnexpectedMessageType)?;
+
+        let psk_id = RecoveryPskExt::from_extensions(&group_info.group_context.extensions)?
+            .ok_or(MlsError::RecoveryPskNotConfigured)?;
+
+        Ok(self.external_commit_builder()?.with_external_psk(psk_id))
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Identifier of the recovery PSK registered in the group context, if
+    /// any.
+    pub fn recovery_psk_id(&self) -> Result<Option<ExternalPskId>, MlsError> {
+        RecoveryPskExt::from_extensions(&self.context().extensions)
+    }
+}
+
+/// Use of the recovery PSK of a group by a commit, passed to the hook of
+/// [`RecoveryPskRules`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RecoveryPskUse {
+    /// Whether the commit is being sent or received.
+    pub direction: CommitDirection,
+    /// Committer, typically a new member joining by external commit.
+    pub source: CommitSource,
+    /// Identifier of the recovery PSK.
+    pub psk_id: ExternalPskId,
+}
+
+/// [`MlsRules`] that call a hook whenever a commit uses the recovery PSK of
+/// the group, and delegate everything else to `rules`.
+///
+/// The hook can be used to notify the user that the group was recovered.
+/// If it returns `false`, the commit is rejected with
+/// [`MlsError::RecoveryPskRejected`].
+#[derive(Clone, Debug)]
+pub struct RecoveryPskRules<R, F> {
+    rules: R,
+    hook: F,
+}
+
+impl<R, F> RecoveryPskRules<R, F>
+where
+    R: MlsRules,
+    F: Fn(&RecoveryPskUse) -> bool + Send + Sync,
+{
+    /// Wrap `rules`, calling `hook` on each use of the recovery PSK.
+    pub fn new(rules: R, hook: F) -> Self {
+        Self { rules, hook }
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<R, F> MlsRules for RecoveryPskRules<R, F>
+where
+    R: MlsRules,
+    F: Fn(&RecoveryPskUse) -> bool + Send + Sync,
+{
+    type Error = MlsError;
+
+    async fn filter_proposals(
+        &self,
+        direction: CommitDirection,
+        source: CommitSource,
+        current_roster: &Roster,
+        current_context: &GroupContext,
+        proposals: ProposalBundle,
+    ) -> Result<ProposalBundle, Self::Error> {
+        if let Some(psk_id) = RecoveryPskExt::from_extensions(&current_context.extensions)? {
+            let used = proposals
+                .psk_proposals()
+                .iter()
+                .any(|p| p.proposal.external_psk_id() == Some(&psk_id));
+
+            let recovery = || RecoveryPskUse {
+                direction,
+                source: source.clone(),
+                psk_id: psk_id.clone(),
+            };
+
+            if used && !(self.hook)(&recovery()) {
+                return Err(MlsError::RecoveryPskRejected);
+            }
+        }
+
+        self.rules
+            .filter_proposals(
+                direction,
+                source,
+                current_roster,
+                current_context,
+                proposals,
+            )
+            .await
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+    }
+
+    fn commit_options(
+        &self,
+        new_roster: &Roster,
+        new_context: &GroupContext,
+        proposals: &ProposalBundle,
+    ) -> Result<CommitOptions, Self::Error> {
+        self.rules
+            .commit_options(new_roster, new_context, proposals)
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+    }
+
+    fn adjust_commit_options(
+        &self,
+        options: CommitOptions,
+        source: &CommitSource,
+        current_roster: &Roster,
+        current_context: &GroupContext,
+        proposals: &ProposalBundle,
+    ) -> Result<CommitOptions, Self::Error> {
+        self.rules
+            .adjust_commit_options(options, source, current_roster, current_context, proposals)
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+    }
+
+    fn encryption_options(
+        &self,
+        current_roster: &Roster,
+        current_context: &GroupContext,
+    ) -> Result<EncryptionOptions, Self::Error> {
+        self.rules
+            .encryption_options(current_roster, current_context)
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+    }
+
+    #[cfg(feature = "private_message")]
+    fn context_binding(&self, context: &GroupContext) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.rules
+            .context_binding(context)
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+    }
+
+    #[cfg(feature = "private_message")]
+    fn authorize_application_message(
+        &self,
+        sender_index: u32,
+        sender: Option<&Member>,
+        authenticated_data: &[u8],
+        current_context: &GroupContext,
+    ) -> Result<MessageAuthorization, Self::Error> {
+        self.rules
+            .authorize_application_message(
+                sender_index,
+                sender,
+                authenticated_data,
+                current_context,
+            )
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+    }
+
+    #[cfg(feature = "private_message")]
+    fn allow_wire_format_override(
+        &self,
+        wire_format: WireFormat,
+        content_type: ContentType,
+        current_roster: &Roster,
+        current_context: &GroupContext,
+    ) -> Result<bool, Self::Error> {
+        self.rules
+            .allow_wire_format_override(wire_format, content_type, current_roster, current_context)
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use std::sync::Mutex;
+
+    use mls_rs_core::psk::{ExternalPskId, PreSharedKey};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        client_builder::test_utils::TestClientBuilder,
+        client_config::ClientConfig,
+        mls_rules::{CommitDirection, CommitSource, DefaultMlsRules},
+        Client,
+    };
+
+    use super::{RecoveryPskExt, RecoveryPskRules, RecoveryPskUse};
+
+    fn recovery_psk() -> (ExternalPskId, PreSharedKey) {
+        (
+            ExternalPskId::new(b"recovery".to_vec()),
+            PreSharedKey::from(vec![42; 32]),
+        )
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_client<F>(name: &str, hook: F) -> Client<impl ClientConfig>
+    where
+        F: Fn(&RecoveryPskUse) -> bool + Clone + Send + Sync + 'static,
+    {
+        let (psk_id, psk) = recovery_psk();
+
+        TestClientBuilder::new_for_test()
+            .with_random_signing_identity(name, TEST_CIPHER_SUITE)
+            .await
+            .extension_type(RecoveryPskExt::EXTENSION_TYPE)
+            .used_protocol_version(TEST_PROTOCOL_VERSION)
+            .mls_rules(RecoveryPskRules::new(DefaultMlsRules::new(), hook))
+            .psk(psk_id, psk)
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn recovery_join_is_reported_to_members() {
+        let uses = Arc::new(Mutex::new(Vec::<RecoveryPskUse>::new()));
+
+        let alice = test_client("alice", {
+            let uses = uses.clone();
+
+            move |u: &RecoveryPskUse| {
+                uses.lock().unwrap().push(u.clone());
+                true
+            }
+        })
+        .await;
+
+        let (psk_id, _) = recovery_psk();
+
+        let mut group = alice
+            .create_group_with_recovery_psk(
+                Default::default(),
+                Default::default(),
+                psk_id.clone(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(group.recovery_psk_id().unwrap(), Some(psk_id.clone()));
+
+        let group_info = group
+            .group_info_message_allowing_ext_commit(true)
+            .await
+            .unwrap();
+        let bob = test_client("bob", |_: &RecoveryPskUse| true).await;
+
+        let (bob_group, commit) = bob
+            .recovery_commit_builder(&group_info)
+            .unwrap()
+            .build(group_info)
+            .await
+            .unwrap();
+
+        group.process_incoming_message(commit).await.unwrap();
+
+        assert_eq!(
+            group.epoch_authenticator().unwrap(),
+            bob_group.epoch_authenticator().unwrap()
+        );
+
+        let uses = uses.lock().unwrap();
+
+        assert_matches!(
+            &uses[..],
+            [RecoveryPskUse {
+                direction: CommitDirection::Receive,
+                source: CommitSource::NewMember(_),
+                psk_id: id,
+            }] if *id == psk_id
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rejected_recovery_join_fails() {
+        let alice = test_client("alice", |_: &RecoveryPskUse| false).await;
+        let (psk_id, _) = recovery_psk();
+
+        let mut group = alice
+            .create_group_with_recovery_psk(Default::default(), Default::default(), psk_id, None)
+            .await
+            .unwrap();
+
+        let group_info = group
+            .group_info_message_allowing_ext_commit(true)
+            .await
+            .unwrap();
+        let bob = test_client("bob", |_: &RecoveryPskUse| true).await;
+
+        let (_, commit) = bob
+            .recovery_commit_builder(&group_info)
+            .unwrap()
+            .build(group_info)
+            .await
+            .unwrap();
+
+        let res = group.process_incoming_message(commit).await;
+        assert_matches!(res, Err(MlsError::MlsRulesError(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn recovery_psk_must_be_known_to_creator() {
+        let alice = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .extension_type(RecoveryPskExt::EXTENSION_TYPE)
+            .build();
+
+        let res = alice
+            .create_group_with_recovery_psk(
+                Default::default(),
+                Default::default(),
+                recovery_psk().0,
+                None,
+            )
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::MissingRequiredPsk));
+    }
+}
@@ -4,9 +4,17 @@
 
 use alloc::vec::Vec;
 
+#[cfg(feature = "private_message")]
+use alloc::boxed::Box;
+
+#[cfg(feature = "private_message")]
+use core::fmt::{self, Debug};
+
 use mls_rs_core::{
     crypto::{CipherSuite, SignatureSecretKey},
+    error::IntoAnyError,
     extension::ExtensionList,
+    group::GroupIdMappingStorage,
     identity::SigningIdentity,
     protocol_version::ProtocolVersion,
 };
@@ -19,6 +27,12 @@ use super::{
     NewMemberInfo, PreSharedKeyID, PskGroupId, PskSecretInput, ResumptionPSKUsage, ResumptionPsk,
 };
 
+#[cfg(feature = "private_message")]
+use super::{
+    framing::{ContentType, MlsMessagePayload},
+    ApplicationMessageDescription, ReceivedMessage,
+};
+
 struct ResumptionGroupParameters<'a> {
     group_id: &'a [u8],
     cipher_suite: CipherSuite,
@@ -30,6 +44,53 @@ pub struct ReinitClient<C: ClientConfig + Clone> {
     client: Client<C>,
     reinit: ReInitProposal,
     psk_input: PskSecretInput,
+    replaced_group_id: Vec<u8>,
+    #[cfg(feature = "private_message")]
+    predecessor: Option<Group<C>>,
+}
+
+/// Group that was replaced by a reinit, kept to decrypt application messages
+/// that arrive after the new group was created.
+#[cfg(feature = "private_message")]
+#[derive(Clone)]
+pub(crate) struct PredecessorGroup<C: ClientConfig> {
+    group: Box<Group<C>>,
+    expires_at_epoch: u64,
+}
+
+#[cfg(feature = "private_message")]
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Clone, PartialEq, Eq)]
+/// Description of an application message that was sent in the predecessor of
+/// a reinitialized group and received by the new group.
+pub struct PredecessorApplicationMessageDescription {
+    /// Id of the predecessor group.
+    pub group_id: Vec<u8>,
+    /// Epoch of the predecessor group the message was sent in.
+    pub epoch: u64,
+    /// Signing identity of the sender in the predecessor group.
+    pub sender: SigningIdentity,
+    /// The decrypted message. Its `sender_index` is a leaf index in the
+    /// predecessor group.
+    pub message: ApplicationMessageDescription,
+}
+
+#[cfg(feature = "private_message")]
+impl Debug for PredecessorApplicationMessageDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PredecessorApplicationMessageDescription")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("sender", &self.sender)
+            .field("message", &self.message)
+            .finish()
+    }
 }
 
 impl<C> Group<C>
@@ -114,12 +175,23 @@ where
     /// commit to the reinit proposal. The value of [identity](crate::IdentityProvider::identity)
     /// must be the same for `new_signing_identity` and the current identity in use by this
     /// group instance.
+    ///
+    /// If [`ClientBuilder::reinit_predecessor_window`](crate::client_builder::ClientBuilder::reinit_predecessor_window)
+    /// is set, this group is handed over to the new group to decrypt
+    /// application messages that arrive late.
     pub fn get_reinit_client(
         self,
         new_signer: Option<SignatureSecretKey>,
         new_signing_identity: Option<SigningIdentity>,
     ) -> Result<ReinitClient<C>, MlsError> {
         let psk_input = self.resumption_psk_input(ResumptionPSKUsage::Reinit)?;
+        let replaced_group_id = self.group_id().to_vec();
+
+        #[cfg(feature = "private_message")]
+        let predecessor = (self.forward_secrecy.reinit_predecessor_window > 0).then(|| Group {
+            predecessor: None,
+            ..self.clone()
+        });
 
         let new_signing_identity = new_signing_identity
             .map(Ok)
@@ -146,11 +218,128 @@ where
             client,
             reinit,
             psk_input,
+            replaced_group_id,
+            #[cfg(feature = "private_message")]
+            predecessor,
         })
     }
 
+    /// Determine if this group is still able to decrypt application messages
+    /// sent in the group it replaced by a reinit.
+    #[cfg(feature = "private_message")]
+    pub fn has_predecessor(&self) -> bool {
+        self.predecessor.is_some()
+    }
+
+    /// Stop decrypting application messages sent in the group this group
+    /// replaced by a reinit.
+    #[cfg(feature = "private_message")]
+    pub fn drop_predecessor(&mut self) {
+        self.predecessor = None;
+    }
+
+    /// Move the external ids mapped to the group replaced by a reinit to this
+    /// group. Called after the state of this group is written to storage so
+    /// that the mapping never points to a group that was not stored.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn replace_mapped_group_id(&mut self) -> Result<(), MlsError> {
+        let Some(replaced_group_id) = self.replaced_group_id.as_deref() else {
+            return Ok(());
+        };
+
+        self.config
+            .group_id_mapping_storage()
+            .replace_group_id(replaced_group_id, self.group_id())
+            .await
+            .map_err(|e| MlsError::GroupIdMappingStorageError(e.into_any_error()))?;
+
+        self.replaced_group_id = None;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "private_message")]
+    fn with_predecessor(mut self, predecessor: Option<Group<C>>) -> Self {
+        let window = self.forward_secrecy.reinit_predecessor_window;
+
+        self.predecessor = predecessor.map(|group| PredecessorGroup {
+            group: Box::new(group),
+            expires_at_epoch: self.current_epoch().saturating_add(window),
+        });
+
+        self
+    }
+
+    /// Determine if `message` is an application message that should be
+    /// processed by the predecessor group. Expired predecessor groups are
+    /// dropped.
+    #[cfg(feature = "private_message")]
+    pub(crate) fn is_predecessor_message(&mut self, message: &MlsMessage) -> bool {
+        if matches!(&self.predecessor, Some(p) if self.current_epoch() >= p.expires_at_epoch) {
+            self.predecessor = None;
+        }
+
+        let (Some(predecessor), MlsMessagePayload::Cipher(ciphertext)) =
+            (&self.predecessor, &message.payload)
+        else {
+            return false;
+        };
+
+        ciphertext.content_type == ContentType::Application
+            && ciphertext.group_id == predecessor.group.group_id()
+            && ciphertext.epoch <= predecessor.group.current_epoch()
+    }
+
+    /// Process an application message with the predecessor group.
+    ///
+    /// A reinit may keep the group id, in which case a message that can not be
+    /// decrypted by the predecessor is processed by this group instead.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg(feature = "private_message")]
+    pub(crate) async fn process_predecessor_message(
+        &mut self,
+        message: &MlsMessage,
+    ) -> Result<Option<ReceivedMessage>, MlsError> {
+        let Some(predecessor) = self.predecessor.as_mut() else {
+            return Ok(None);
+        };
+
+        let epoch = message.epoch().unwrap_or_default();
+        let same_group_id = predecessor.group.group_id() == self.state.context.group_id;
+
+        let res = MessageProcessor::process_incoming_message(
+            predecessor.group.as_mut(),
+            message.clone(),
+            #[cfg(feature = "by_ref_proposal")]
+            false,
+        )
+        .await;
+
+        let message = match res {
+            Ok(ReceivedMessage::ApplicationMessage(message)) => message,
+            Ok(_) => return Err(MlsError::UnexpectedMessageType),
+            Err(_) if same_group_id => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let sender = predecessor
+            .group
+            .member_at_index(message.sender_index)
+            .ok_or(MlsError::InvalidSender)?
+            .signing_identity;
+
+        Ok(Some(ReceivedMessage::PredecessorApplicationMessage(
+            PredecessorApplicationMessageDescription {
+                group_id: predecessor.group.group_id().to_vec(),
+                epoch,
+                sender,
+                message,
+            },
+        )))
+    }
+
     fn resumption_psk_input(&self, usage: ResumptionPSKUsage) -> Result<PskSecretInput, MlsError> {
-        let psk = self.epoch_secrets.resumption_secret.clone();
+        let psk = self.epoch_secrets.resumption_secret.clone().into();
 
         let id = JustPreSharedKeyID::Resumption(ResumptionPsk {
             usage,
@@ -193,6 +382,9 @@ impl<C: ClientConfig + Clone> ReinitClient<C> {
         new_leaf_node_extensions: ExtensionList,
         timestamp: Option<MlsTime>,
     ) -> Result<(Group<C>, Vec<MlsMessage>), MlsError> {
+        #[cfg(feature = "private_message")]
+        let predecessor = self.predecessor;
+
         let new_group_params = ResumptionGroupParameters {
             group_id: self.reinit.group_id(),
             cipher_suite: self.reinit.new_cipher_suite(),
@@ -200,7 +392,7 @@ impl<C: ClientConfig + Clone> ReinitClient<C> {
             extensions: self.reinit.new_group_context_extensions(),
         };
 
-        resumption_create_group(
+        let (mut group, welcome_messages) = resumption_create_group(
             self.client.config.clone(),
             new_key_packages,
             &new_group_params,
@@ -212,7 +404,14 @@ impl<C: ClientConfig + Clone> ReinitClient<C> {
             self.psk_input,
             timestamp,
         )
-        .await
+        .await?;
+
+        group.replaced_group_id = Some(self.replaced_group_id);
+
+        #[cfg(feature = "private_message")]
+        let group = group.with_predecessor(predecessor);
+
+        Ok((group, welcome_messages))
     }
 
     /// Join a reinitialized group that was created by [`ReinitClient::commit`].
@@ -225,6 +424,9 @@ impl<C: ClientConfig + Clone> ReinitClient<C> {
     ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
         let reinit = self.reinit;
 
+        #[cfg(feature = "private_message")]
+        let predecessor = self.predecessor;
+
         let expected_group_params = ResumptionGroupParameters {
             group_id: reinit.group_id(),
             cipher_suite: reinit.new_cipher_suite(),
@@ -232,7 +434,7 @@ impl<C: ClientConfig + Clone> ReinitClient<C> {
             extensions: reinit.new_group_context_extensions(),
         };
 
-        resumption_join_group(
+        let (mut group, new_member_info) = resumption_join_group(
             self.client.config,
             // This private field is created with `Some(x)` by `get_reinit_client`
             self.client.signer.unwrap(),
@@ -243,7 +445,14 @@ impl<C: ClientConfig + Clone> ReinitClient<C> {
             self.psk_input,
             maybe_time,
         )
-        .await
+        .await?;
+
+        group.replaced_group_id = Some(self.replaced_group_id);
+
+        #[cfg(feature = "private_message")]
+        let group = group.with_predecessor(predecessor);
+
+        Ok((group, new_member_info))
     }
 }
 
@@ -322,3 +531,144 @@ async fn resumption_join_group<C: ClientConfig + Clone>(
         Ok((group, new_member_info))
     }
 }
+
+#[cfg(all(test, feature = "private_message", feature = "prior_epoch"))]
+mod tests {
+    use assert_matches::assert_matches;
+    use mls_rs_core::group::GroupIdMappingStorage;
+
+    use crate::{
+        client::test_utils::{TestClientConfig, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client_config::ClientConfig,
+        group::{test_utils::test_group_custom_config, ReceivedMessage},
+        Group, MlsMessage,
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn reinit_with_late_message(
+        window: u64,
+    ) -> (Group<TestClientConfig>, Group<TestClientConfig>, MlsMessage) {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.reinit_predecessor_window(window)
+        })
+        .await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.reinit_predecessor_window = window
+            })
+            .await
+            .unwrap();
+
+        // Sent by bob before the reinit and delivered to alice after it
+        let late = bob
+            .encrypt_application_message(b"late", vec![])
+            .await
+            .unwrap();
+
+        let commit = alice
+            .commit_builder()
+            .reinit(
+                None,
+                TEST_PROTOCOL_VERSION,
+                TEST_CIPHER_SUITE,
+                Default::default(),
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        bob.process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let alice = alice.group.get_reinit_client(None, None).unwrap();
+        let bob = bob.group.get_reinit_client(None, None).unwrap();
+
+        let key_package = bob.generate_key_package(None).await.unwrap();
+
+        let (alice, welcome) = alice
+            .commit(vec![key_package], Default::default(), None)
+            .await
+            .unwrap();
+
+        let (bob, _) = bob.join(&welcome[0], None, None).await.unwrap();
+
+        (alice, bob, late)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn late_message_is_decrypted_by_predecessor() {
+        let (mut alice, bob, late) = reinit_with_late_message(2).await;
+
+        let predecessor_group_id = late.group_id().unwrap().to_vec();
+
+        let received = alice.process_incoming_message(late).await.unwrap();
+
+        assert_matches!(
+            received,
+            ReceivedMessage::PredecessorApplicationMessage(desc)
+                if desc.group_id == predecessor_group_id
+                    && desc.epoch == 1
+                    && desc.message.data() == b"late"
+                    && &desc.sender == bob.current_member_signing_identity().unwrap()
+        );
+
+        assert!(alice.has_predecessor());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn predecessor_expires_after_window() {
+        let (mut alice, _, late) = reinit_with_late_message(1).await;
+
+        alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+
+        let res = alice.process_incoming_message(late).await;
+
+        assert!(res.is_err());
+        assert!(!alice.has_predecessor());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_id_mapping_is_replaced_when_reinit_group_is_stored() {
+        let (mut alice, _, late) = reinit_with_late_message(0).await;
+
+        let old_group_id = late.group_id().unwrap().to_vec();
+        let mut mapping = alice.config.group_id_mapping_storage();
+
+        mapping.insert(b"chat", &old_group_id).await.unwrap();
+
+        let mapped = mapping.group_id(b"chat").await.unwrap();
+        assert_eq!(mapped, Some(old_group_id.clone()));
+
+        alice.write_to_storage().await.unwrap();
+
+        let mapped = mapping.group_id(b"chat").await.unwrap();
+        assert_eq!(mapped, Some(alice.group_id().to_vec()));
+
+        let external_ids = mapping.external_ids(&old_group_id).await.unwrap();
+        assert!(external_ids.is_empty());
+
+        // Later writes leave mappings made after the reinit untouched
+        mapping.insert(b"archive", &old_group_id).await.unwrap();
+        alice.write_to_storage().await.unwrap();
+
+        let mapped = mapping.group_id(b"archive").await.unwrap();
+        assert_eq!(mapped, Some(old_group_id));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn predecessor_not_kept_by_default() {
+        let (mut alice, _, late) = reinit_with_late_message(0).await;
+
+        assert!(!alice.has_predecessor());
+
+        let res = alice.process_incoming_message(late).await;
+
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,340 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider},
+    error::IntoAnyError,
+    protocol_version::ProtocolVersion,
+    psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+    secret::KeyScheduleSecret,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::key_schedule::{into_bytes, kdf_expand_with_label_secret},
+    psk::{
+        secret::{PskSecret, PskSecretInput},
+        JustPreSharedKeyID, PreSharedKeyID,
+    },
+    MlsMessage,
+};
+
+use super::Group;
+
+/// A [`GroupInfo`](crate::group::GroupInfo) message encrypted with a key
+/// derived from an external pre-shared key.
+///
+/// Publishing a sealed group info instead of a plaintext one restricts
+/// external commits to clients that hold the pre-shared key, for example
+/// all devices of an organization.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub struct SealedGroupInfo {
+    pub(crate) version: ProtocolVersion,
+    pub(crate) cipher_suite: CipherSuite,
+    pub(crate) psk_id: PreSharedKeyID,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub(crate) ciphertext: Vec<u8>,
+}
+
+impl Debug for SealedGroupInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SealedGroupInfo")
+            .field("version", &self.version)
+            .field("cipher_suite", &self.cipher_suite)
+            .field("psk_id", &self.psk_id)
+            .field(
+                "ciphertext",
+                &mls_rs_core::debug::pretty_bytes(&self.ciphertext),
+            )
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct SealedGroupInfoAAD<'a> {
+    version: ProtocolVersion,
+    cipher_suite: CipherSuite,
+    psk_id: &'a PreSharedKeyID,
+}
+
+impl SealedGroupInfo {
+    /// Protocol version of the sealed group info.
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
+
+    /// Cipher suite of the group.
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
+    /// Id of the external pre-shared key required to open the group info.
+    pub fn external_psk_id(&self) -> Option<&ExternalPskId> {
+        match &self.psk_id.key_id {
+            JustPreSharedKeyID::External(id) => Some(id),
+            JustPreSharedKeyID::Resumption(_) => None,
+        }
+    }
+
+    /// Deserialize a sealed group info from bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Serialize a sealed group info for transport.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn seal<P: CipherSuiteProvider>(
+        cipher_suite_provider: &P,
+        group_info: &MlsMessage,
+        psk_id: ExternalPskId,
+        psk: PreSharedKey,
+    ) -> Result<Self, MlsError> {
+        let psk_id =
+            PreSharedKeyID::new(JustPreSharedKeyID::External(psk_id), cipher_suite_provider)?;
+
+        let mut sealed = SealedGroupInfo {
+            version: group_info.version,
+            cipher_suite: cipher_suite_provider.cipher_suite(),
+            psk_id,
+            ciphertext: Vec::new(),
+        };
+
+        let (key, nonce) = sealed.key_and_nonce(cipher_suite_provider, psk).await?;
+        let aad = sealed.aad()?;
+
+        sealed.ciphertext = cipher_suite_provider
+            .aead_seal_with_secret(&key, &group_info.to_bytes()?, Some(&aad), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        Ok(sealed)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn open<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        psk: PreSharedKey,
+    ) -> Result<MlsMessage, MlsError> {
+        let (key, nonce) = self.key_and_nonce(cipher_suite_provider, psk).await?;
+
+        let group_info = cipher_suite_provider
+            .aead_open_with_secret(&key, &self.ciphertext, Some(&self.aad()?), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let group_info = MlsMessage::from_bytes(&group_info)?;
+
+        let matches_header = group_info.version == self.version
+            && matches!(
+                group_info.as_group_info(),
+                Some(info) if info.group_context.cipher_suite == self.cipher_suite
+            );
+
+        matches_header
+            .then_some(group_info)
+            .ok_or(MlsError::InvalidGroupInfo)
+    }
+
+    fn aad(&self) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        SealedGroupInfoAAD {
+            version: self.version,
+            cipher_suite: self.cipher_suite,
+            psk_id: &self.psk_id,
+        }
+        .mls_encode_to_vec()
+    }
+
+    // The psk secret is computed exactly as in the key schedule of a commit
+    // that includes this PSK as its only pre-shared key.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn key_and_nonce<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        psk: PreSharedKey,
+    ) -> Result<(KeyScheduleSecret, Zeroizing<Vec<u8>>), MlsError> {
+        let input = PskSecretInput {
+            id: self.psk_id.clone(),
+            psk: psk.into(),
+        };
+
+        let psk_secret = PskSecret::calculate(&[input], cipher_suite_provider).await?;
+
+        let key = kdf_expand_with_label_secret(
+            cipher_suite_provider,
+            &psk_secret,
+            b"sealed group info key",
+            &[],
+            Some(cipher_suite_provider.aead_key_size()),
+        )
+        .await?;
+
+        let nonce = kdf_expand_with_label_secret(
+            cipher_suite_provider,
+            &psk_secret,
+            b"sealed group info nonce",
+            &[],
+            Some(cipher_suite_provider.aead_nonce_size()),
+        )
+        .await
+        .and_then(into_bytes)?;
+
+        Ok((key, nonce))
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn get_external_psk<S: PreSharedKeyStorage>(
+    psk_store: &S,
+    psk_id: &ExternalPskId,
+) -> Result<PreSharedKey, MlsError> {
+    psk_store
+        .get(psk_id)
+        .await
+        .map_err(|e| MlsError::PskStoreError(e.into_any_error()))?
+        .ok_or(MlsError::MissingRequiredPsk)
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a group info message that can be used for external commits and
+    /// encrypt it with the external pre-shared key identified by `psk_id`.
+    ///
+    /// The resulting [`SealedGroupInfo`] can be opened by any client that has
+    /// the same pre-shared key in its
+    /// [`PreSharedKeyStorage`](mls_rs_core::psk::PreSharedKeyStorage) using
+    /// [`Client::open_sealed_group_info`](crate::Client::open_sealed_group_info).
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn sealed_group_info_message(
+        &self,
+        psk_id: ExternalPskId,
+        with_tree_in_extension: bool,
+    ) -> Result<SealedGroupInfo, MlsError> {
+        let psk = get_external_psk(&self.config.secret_store(), &psk_id).await?;
+
+        let group_info = self
+            .group_info_message_allowing_ext_commit(with_tree_in_extension)
+            .await?;
+
+        SealedGroupInfo::seal(&self.cipher_suite_provider, &group_info, psk_id, psk).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group_custom_config,
+        identity::test_utils::get_test_signing_identity,
+        psk::{ExternalPskId, PreSharedKey},
+        Client,
+    };
+
+    use super::SealedGroupInfo;
+
+    fn psk_id() -> ExternalPskId {
+        ExternalPskId::new(b"org psk".to_vec())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn joiner(
+        psk: Option<PreSharedKey>,
+    ) -> Client<crate::client::test_utils::TestClientConfig> {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+
+        let builder = TestClientBuilder::new_for_test();
+
+        let builder = match psk {
+            Some(psk) => builder.psk(psk_id(), psk),
+            None => builder,
+        };
+
+        builder
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sealed_group_info_can_be_opened_with_psk() {
+        let psk = PreSharedKey::from(b"psk".to_vec());
+
+        let group = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |c| {
+            c.psk(psk_id(), psk.clone())
+        })
+        .await;
+
+        let sealed = group
+            .sealed_group_info_message(psk_id(), true)
+            .await
+            .unwrap();
+
+        assert_eq!(sealed.external_psk_id(), Some(&psk_id()));
+
+        let sealed = SealedGroupInfo::from_bytes(&sealed.to_bytes().unwrap()).unwrap();
+
+        let group_info = joiner(Some(psk))
+            .await
+            .open_sealed_group_info(&sealed)
+            .await
+            .unwrap();
+
+        let group_info = group_info.into_group_info().unwrap();
+
+        assert_eq!(&group_info.group_context, group.context());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sealed_group_info_requires_psk() {
+        let psk = PreSharedKey::from(b"psk".to_vec());
+
+        let group = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |c| {
+            c.psk(psk_id(), psk.clone())
+        })
+        .await;
+
+        let sealed = group
+            .sealed_group_info_message(psk_id(), false)
+            .await
+            .unwrap();
+
+        let res = joiner(None).await.open_sealed_group_info(&sealed).await;
+
+        assert_matches!(res, Err(MlsError::MissingRequiredPsk));
+
+        let wrong_psk = PreSharedKey::from(b"other psk".to_vec());
+
+        let res = joiner(Some(wrong_psk))
+            .await
+            .open_sealed_group_info(&sealed)
+            .await;
+
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sealing_requires_psk_in_store() {
+        let group = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |c| c).await;
+
+        let res = group.sealed_group_info_message(psk_id(), false).await;
+
+        assert_matches!(res, Err(MlsError::MissingRequiredPsk));
+    }
+}
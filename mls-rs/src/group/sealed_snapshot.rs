@@ -0,0 +1,215 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider},
+    error::IntoAnyError,
+    group::GroupContext,
+    protocol_version::ProtocolVersion,
+    secret::KeyScheduleSecret,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    group::key_schedule::{into_bytes, kdf_expand_with_label_secret},
+};
+
+/// Group of an encrypted group state, sent in the clear and authenticated as
+/// additional data.
+///
+/// Shared by all exports that move a group state to another device, which
+/// differ only in how the encryption secret is obtained.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+pub(crate) struct SealedSnapshotHeader {
+    pub(crate) version: ProtocolVersion,
+    pub(crate) cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub(crate) group_id: Vec<u8>,
+    pub(crate) epoch: u64,
+}
+
+impl Debug for SealedSnapshotHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SealedSnapshotHeader")
+            .field("version", &self.version)
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .finish()
+    }
+}
+
+impl SealedSnapshotHeader {
+    pub(crate) fn new(context: &GroupContext) -> Self {
+        Self {
+            version: context.protocol_version,
+            cipher_suite: context.cipher_suite,
+            group_id: context.group_id.clone(),
+            epoch: context.epoch,
+        }
+    }
+
+    /// Check that a decrypted group state belongs to the group and epoch of
+    /// the header.
+    pub(crate) fn verify(&self, context: &GroupContext) -> Result<(), MlsError> {
+        (*self == Self::new(context))
+            .then_some(())
+            .ok_or(MlsError::SealedSnapshotMismatch)
+    }
+
+    /// Encrypt `plaintext` with a key and nonce derived from `secret`.
+    ///
+    /// `binding` is authenticated together with the header and should
+    /// contain everything else the export sends in the clear, such as the
+    /// identifier of the secret.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn seal<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        secret: &KeyScheduleSecret,
+        binding: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, MlsError> {
+        let aad = self.aad(binding)?;
+        let (key, nonce) = key_and_nonce(cipher_suite_provider, secret, &aad).await?;
+
+        cipher_suite_provider
+            .aead_seal_with_secret(&key, plaintext, Some(&aad), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+
+    /// Decrypt `ciphertext` created by [`Self::seal`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn open<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        secret: &KeyScheduleSecret,
+        binding: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        let aad = self.aad(binding)?;
+        let (key, nonce) = key_and_nonce(cipher_suite_provider, secret, &aad).await?;
+
+        cipher_suite_provider
+            .aead_open_with_secret(&key, ciphertext, Some(&aad), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+
+    fn aad(&self, binding: &[u8]) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        let mut aad = self.mls_encode_to_vec()?;
+        aad.extend_from_slice(binding);
+        Ok(aad)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn key_and_nonce<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    secret: &KeyScheduleSecret,
+    aad: &[u8],
+) -> Result<(KeyScheduleSecret, Zeroizing<Vec<u8>>), MlsError> {
+    let key = kdf_expand_with_label_secret(
+        cipher_suite_provider,
+        secret,
+        b"sealed snapshot key",
+        aad,
+        Some(cipher_suite_provider.aead_key_size()),
+    )
+    .await?;
+
+    let nonce = kdf_expand_with_label_secret(
+        cipher_suite_provider,
+        secret,
+        b"sealed snapshot nonce",
+        aad,
+        Some(cipher_suite_provider.aead_nonce_size()),
+    )
+    .await
+    .and_then(into_bytes)?;
+
+    Ok((key, nonce))
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use crate::{
+        client::test_utils::{TestClientBuilder, TestClientConfig, TEST_CIPHER_SUITE},
+        identity::test_utils::get_test_signing_identity,
+        Client,
+    };
+
+    /// Client of the device a group state is moved to.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn new_device<F>(config: F) -> Client<TestClientConfig>
+    where
+        F: FnOnce(TestClientBuilder) -> TestClientBuilder,
+    {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"device").await;
+
+        config(TestClientBuilder::new_for_test())
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_group,
+    };
+
+    use super::SealedSnapshotHeader;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sealed_snapshot_requires_secret_and_header() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let header = SealedSnapshotHeader::new(group.context());
+        let secret = vec![1; 32].into();
+
+        let ciphertext = header.seal(&cs, &secret, b"id", b"state").await.unwrap();
+
+        let plaintext = header.open(&cs, &secret, b"id", &ciphertext).await.unwrap();
+        assert_eq!(plaintext.as_slice(), b"state");
+
+        let res = header
+            .open(&cs, &vec![2; 32].into(), b"id", &ciphertext)
+            .await;
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+
+        let res = header.open(&cs, &secret, b"other id", &ciphertext).await;
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+
+        let mut other_epoch = header.clone();
+        other_epoch.epoch += 1;
+
+        let res = other_epoch.open(&cs, &secret, b"id", &ciphertext).await;
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+
+        assert_matches!(
+            other_epoch.verify(group.context()),
+            Err(MlsError::SealedSnapshotMismatch)
+        );
+
+        header.verify(group.context()).unwrap();
+    }
+}
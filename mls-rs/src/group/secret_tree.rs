@@ -99,6 +99,10 @@ impl<T: TreeIndex> TreeSecretsVec<T> {
     fn take_node(&mut self, index: &T) -> Option<SecretTreeNode> {
         self.inner.remove(index)
     }
+
+    fn set_node_if_absent(&mut self, index: T, value: SecretTreeNode) {
+        self.inner.entry(index).or_insert(value);
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, MlsEncode, MlsDecode, MlsSize)]
@@ -172,6 +176,22 @@ impl<T: TreeIndex> SecretTree<T> {
         }
     }
 
+    /// Reset the tree to the state it had at the start of the epoch, keeping
+    /// the ratchets of `keep` if they were already derived.
+    #[cfg(feature = "secret_tree_recovery")]
+    pub(crate) fn recover(&mut self, encryption_secret: Zeroizing<Vec<u8>>, keep: T) {
+        let kept = self
+            .known_secrets
+            .take_node(&keep)
+            .filter(|node| matches!(node, SecretTreeNode::Ratchet(_)));
+
+        *self = SecretTree::new(self.leaf_count.clone(), encryption_secret);
+
+        if let Some(node) = kept {
+            self.known_secrets.set_node(keep, node);
+        }
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn consume_node<P: CipherSuiteProvider>(
         &mut self,
@@ -192,11 +212,12 @@ impl<T: TreeIndex> SecretTree<T> {
                 kdf_expand_with_label(cipher_suite_provider, &secret, b"tree", b"right", None)
                     .await?;
 
+            // Children may already be known if they were kept when recovering the tree.
             self.known_secrets
-                .set_node(left_index, SecretTreeNode::Secret(left_secret.into()));
+                .set_node_if_absent(left_index, SecretTreeNode::Secret(left_secret.into()));
 
             self.known_secrets
-                .set_node(right_index, SecretTreeNode::Secret(right_secret.into()));
+                .set_node_if_absent(right_index, SecretTreeNode::Secret(right_secret.into()));
         }
 
         Ok(())
@@ -234,6 +255,16 @@ impl<T: TreeIndex> SecretTree<T> {
         })
     }
 
+    /// Next application generation of `leaf_index` along with the lower
+    /// generations whose keys are still available.
+    #[cfg(feature = "private_message")]
+    pub(crate) fn application_generations(&self, leaf_index: &T) -> (u32, Vec<u32>) {
+        match self.known_secrets.inner.get(leaf_index) {
+            Some(SecretTreeNode::Ratchet(ratchets)) => ratchets.application.generations(),
+            _ => (0, Vec::new()),
+        }
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn next_message_key<P: CipherSuiteProvider>(
         &mut self,
@@ -407,6 +438,19 @@ impl SecretKeyRatchet {
         })
     }
 
+    #[cfg(feature = "private_message")]
+    fn generations(&self) -> (u32, Vec<u32>) {
+        #[cfg(feature = "out_of_order")]
+        let mut skipped = self.history.keys().copied().collect::<Vec<_>>();
+
+        #[cfg(not(feature = "out_of_order"))]
+        let mut skipped = Vec::new();
+
+        skipped.sort_unstable();
+
+        (self.generation, skipped)
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn get_message_key<P: CipherSuiteProvider>(
         &mut self,
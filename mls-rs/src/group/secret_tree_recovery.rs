@@ -0,0 +1,186 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{client::MlsError, client_config::ClientConfig, tree_kem::node::NodeIndex};
+
+use super::Group;
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Derive the secret tree of epoch `epoch_id` again from the encryption
+    /// secret of that epoch.
+    ///
+    /// This is meant to recover from a secret tree that was lost or corrupted
+    /// while the rest of the epoch secrets are still available. Only the
+    /// current epoch and prior epochs still retained by the group state
+    /// storage can be recovered. Ratchets already derived for this member are
+    /// kept so that no message key is used twice for sending.
+    ///
+    /// Recovery restores message keys of other members that were already
+    /// used, which means that messages received earlier in the epoch can be
+    /// decrypted again. Applications that require strict forward secrecy
+    /// within an epoch should not enable the `secret_tree_recovery` feature,
+    /// which retains the encryption secret for every stored epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn recover_secret_tree(&mut self, epoch_id: u64) -> Result<(), MlsError> {
+        if epoch_id == self.context().epoch {
+            let self_index = NodeIndex::from(self.private_tree.self_index);
+            return self.epoch_secrets.recover_secret_tree(self_index);
+        }
+
+        #[cfg(feature = "prior_epoch")]
+        {
+            let epoch = self
+                .state_repo
+                .get_epoch_mut(epoch_id)
+                .await?
+                .ok_or(MlsError::EpochNotFound)?;
+
+            let self_index = NodeIndex::from(epoch.self_index);
+            epoch.secrets.recover_secret_tree(self_index)
+        }
+
+        #[cfg(not(feature = "prior_epoch"))]
+        Err(MlsError::EpochNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use mls_rs_codec::{MlsDecode, MlsEncode};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{snapshot::Snapshot, test_utils::test_group, Group, ReceivedMessage},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn recovered_secret_tree_decrypts_again() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let message = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        bob.process_message(message.clone()).await.unwrap();
+
+        // The key for this generation was consumed.
+        let res = bob.process_message(message.clone()).await;
+        assert!(res.is_err());
+
+        let epoch = bob.context().epoch;
+        bob.recover_secret_tree(epoch).await.unwrap();
+
+        let res = bob.process_message(message).await;
+
+        assert_matches!(
+            res,
+            Ok(ReceivedMessage::ApplicationMessage(m)) if m.data() == b"hello"
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn recovery_keeps_own_sending_ratchet() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let first = alice
+            .encrypt_application_message(b"first", vec![])
+            .await
+            .unwrap();
+
+        let epoch = alice.context().epoch;
+        alice.recover_secret_tree(epoch).await.unwrap();
+
+        let second = alice
+            .encrypt_application_message(b"second", vec![])
+            .await
+            .unwrap();
+
+        bob.process_message(first).await.unwrap();
+
+        let res = bob.process_message(second).await;
+
+        assert_matches!(
+            res,
+            Ok(ReceivedMessage::ApplicationMessage(m)) if m.data() == b"second"
+        );
+    }
+
+    #[cfg(feature = "prior_epoch")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn prior_epoch_secret_tree_can_be_recovered() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let message = alice
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let epoch = bob.context().epoch;
+        bob.process_message(message.clone()).await.unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        let res = bob.process_message(message.clone()).await;
+        assert!(res.is_err());
+
+        bob.recover_secret_tree(epoch).await.unwrap();
+
+        let res = bob.process_message(message).await;
+
+        assert_matches!(
+            res,
+            Ok(ReceivedMessage::ApplicationMessage(m)) if m.data() == b"hello"
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unknown_epoch_can_not_be_recovered() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let res = alice.recover_secret_tree(42).await;
+        assert_matches!(res, Err(MlsError::EpochNotFound));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn encryption_secret_is_kept_in_snapshots() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let epoch = alice.context().epoch;
+
+        let snapshot = alice.snapshot().unwrap();
+        let bytes = snapshot.mls_encode_to_vec().unwrap();
+        let decoded = Snapshot::mls_decode(&mut &*bytes).unwrap();
+
+        let mut restored = Group::from_snapshot(alice.config.clone(), decoded)
+            .await
+            .unwrap();
+
+        restored.recover_secret_tree(epoch).await.unwrap();
+
+        // Snapshots written by earlier versions end before the encryption
+        // secret and can still be loaded.
+        let len = bytes.len() - snapshot.trailing_len();
+        let decoded = Snapshot::mls_decode(&mut &bytes[..len]).unwrap();
+
+        let mut restored = Group::from_snapshot(alice.config.clone(), decoded)
+            .await
+            .unwrap();
+
+        let res = restored.recover_secret_tree(epoch).await;
+        assert_matches!(res, Err(MlsError::SecretTreeRecoveryUnavailable));
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+/// Category of a [`SecurityEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SecurityEventKind {
+    /// The identity provider rejected a credential, or a credential did not
+    /// meet the requirements of the group.
+    CredentialValidationFailed,
+    /// A key package or leaf node was used outside of its lifetime.
+    LifetimeViolation,
+    /// A signature, membership tag or confirmation tag did not verify.
+    SignatureFailure,
+    /// A message was received for a key that was already used and deleted.
+    ReplayDetected,
+    /// A message or group used a protocol version or cipher suite other than
+    /// the one expected or allowed.
+    DowngradeAttempt,
+    /// An application message was denied by
+    /// [`MlsRules::authorize_application_message`](crate::MlsRules::authorize_application_message).
+    UnauthorizedSender,
+}
+
+impl SecurityEventKind {
+    /// Stable name of the category, suitable as an event type in log
+    /// pipelines.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CredentialValidationFailed => "credential_validation_failed",
+            Self::LifetimeViolation => "lifetime_violation",
+            Self::SignatureFailure => "signature_failure",
+            Self::ReplayDetected => "replay_detected",
+            Self::DowngradeAttempt => "downgrade_attempt",
+            Self::UnauthorizedSender => "unauthorized_sender",
+        }
+    }
+
+    pub(crate) fn from_error(error: &MlsError) -> Option<Self> {
+        match error {
+            MlsError::IdentityProviderError(_)
+            | MlsError::InvalidExternalSigningIdentity
+            | MlsError::UnknownSigningIdentityForExternalSender
+            | MlsError::RequiredCredentialNotFound(_)
+            | MlsError::CredentialTypeOfNewLeafIsUnsupported
+            | MlsError::InUseCredentialTypeUnsupportedByNewLeaf
+            | MlsError::DifferentIdentityInUpdate(_) => Some(Self::CredentialValidationFailed),
+            MlsError::InvalidLifetime { .. } => Some(Self::LifetimeViolation),
+            MlsError::InvalidSignature
+            | MlsError::InvalidMembershipTag
+            | MlsError::InvalidConfirmationTag => Some(Self::SignatureFailure),
+            MlsError::KeyMissing(_) => Some(Self::ReplayDetected),
+            MlsError::UnsupportedProtocolVersion(_)
+            | MlsError::ProtocolVersionMismatch
+            | MlsError::InvalidProtocolVersionInReInit
+            | MlsError::UnsupportedCipherSuite(_)
+            | MlsError::CipherSuiteMismatch
+            | MlsError::CipherSuiteBelowMinimum(_) => Some(Self::DowngradeAttempt),
+            MlsError::ApplicationMessageDenied(_) => Some(Self::UnauthorizedSender),
+            _ => None,
+        }
+    }
+}
+
+/// Security relevant failure observed while processing data received from
+/// other parties.
+///
+/// Events only carry the category of the failure and public identifiers of
+/// the group it occurred in. They never contain key material, message
+/// content, credentials or the underlying error, so they can be forwarded to
+/// log collection as is.
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SecurityEvent {
+    /// Category of the failure.
+    pub kind: SecurityEventKind,
+    /// Group the failure occurred in, if known.
+    pub group_id: Option<Vec<u8>>,
+    /// Epoch of the group when the failure occurred, if known.
+    pub epoch: Option<u64>,
+}
+
+impl Debug for SecurityEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecurityEvent")
+            .field("kind", &self.kind)
+            .field(
+                "group_id",
+                &self
+                    .group_id
+                    .as_ref()
+                    .map(|id| mls_rs_core::debug::pretty_group_id(id)),
+            )
+            .field("epoch", &self.epoch)
+            .finish()
+    }
+}
+
+/// Receiver of [`SecurityEvent`]s.
+///
+/// See [`ClientBuilder::security_event_sink`](crate::client_builder::ClientBuilder::security_event_sink).
+pub trait SecurityEventSink: Send + Sync {
+    /// Called when a security relevant failure is observed. The failure is
+    /// still returned to the caller as an error.
+    fn security_event(&self, event: &SecurityEvent);
+}
+
+impl<F> SecurityEventSink for F
+where
+    F: Fn(&SecurityEvent) + Send + Sync,
+{
+    fn security_event(&self, event: &SecurityEvent) {
+        self(event)
+    }
+}
+
+impl Debug for dyn SecurityEventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecurityEventSink")
+    }
+}
+
+pub(crate) type SharedSecurityEventSink = Arc<dyn SecurityEventSink>;
+
+/// Report `error` to the security event sink of `config` if it is security
+/// relevant.
+pub(crate) fn report_security_event<C: ClientConfig>(
+    config: &C,
+    error: &MlsError,
+    group_id: Option<&[u8]>,
+    epoch: Option<u64>,
+) {
+    let Some(kind) = SecurityEventKind::from_error(error) else {
+        return;
+    };
+
+    if let Some(sink) = config.security_event_sink() {
+        sink.security_event(&SecurityEvent {
+            kind,
+            group_id: group_id.map(<[u8]>::to_vec),
+            epoch,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use std::sync::Mutex;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group,
+        protocol_version::ProtocolVersion,
+    };
+
+    use super::{SecurityEvent, SecurityEventKind};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn downgraded_message_is_reported() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let events = Arc::new(Mutex::new(Vec::<SecurityEvent>::new()));
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                let events = events.clone();
+
+                c.0.settings.security_event_sink = Some(Arc::new(move |e: &SecurityEvent| {
+                    events.lock().unwrap().push(e.clone())
+                }));
+            })
+            .await
+            .unwrap();
+
+        let mut commit = alice.commit(vec![]).await.unwrap().commit_message;
+        commit.version = ProtocolVersion::from(0xff);
+
+        let res = bob.process_message(commit).await;
+        assert_matches!(res, Err(MlsError::ProtocolVersionMismatch));
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![SecurityEvent {
+                kind: SecurityEventKind::DowngradeAttempt,
+                group_id: Some(bob.group_id().to_vec()),
+                epoch: Some(bob.current_epoch()),
+            }]
+        );
+    }
+
+    #[test]
+    fn errors_without_security_relevance_are_not_reported() {
+        assert_eq!(
+            SecurityEventKind::from_error(&MlsError::GroupNotFound),
+            None
+        );
+
+        assert_eq!(
+            SecurityEventKind::from_error(&MlsError::InvalidSignature),
+            Some(SecurityEventKind::SignatureFailure)
+        );
+    }
+}
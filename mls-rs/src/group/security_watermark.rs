@@ -0,0 +1,568 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::CipherSuite,
+    error::IntoAnyError,
+    extension::ExtensionList,
+    group::{GroupContext, SecurityWatermarkStorage},
+    protocol_version::ProtocolVersion,
+};
+
+use crate::{client::MlsError, client_config::ClientConfig, extension::RequiredCapabilitiesExt};
+
+use super::{snapshot::Snapshot, CipherSuitePolicy, Group};
+
+/// Security parameters of a group, recorded whenever the group state is
+/// written to storage.
+///
+/// A stored group state is only loaded if its parameters are not below the
+/// last recorded watermark, which protects against a rollback of the group
+/// state storage to a state with weaker parameters or to an epoch whose
+/// secrets were already deleted.
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct SecurityWatermark {
+    pub protocol_version: ProtocolVersion,
+    pub cipher_suite: CipherSuite,
+    /// Capabilities required from all members, empty if the group has no
+    /// [`RequiredCapabilitiesExt`].
+    pub required_capabilities: RequiredCapabilitiesExt,
+    pub forward_secrecy: ForwardSecrecyPolicy,
+    /// Secrets of earlier epochs were deleted for forward secrecy, so a state
+    /// of an earlier epoch should never be used again.
+    pub epoch: u64,
+}
+
+/// Settings that determine how long secrets of a group are kept after they
+/// were used.
+///
+/// They are taken from the client config when the group is created or
+/// joined and stored with the group, so that changing the config does not
+/// affect existing groups. A larger value of any setting is weaker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ForwardSecrecyPolicy {
+    /// See [`ClientBuilder::ratchet_checkpoint_interval`](crate::client_builder::ClientBuilder::ratchet_checkpoint_interval).
+    pub ratchet_checkpoint_interval: u32,
+    /// See [`ClientBuilder::reinit_predecessor_window`](crate::client_builder::ClientBuilder::reinit_predecessor_window).
+    pub reinit_predecessor_window: u64,
+    /// Whether the encryption secret of every stored epoch is retained by
+    /// the `secret_tree_recovery` feature.
+    pub retains_encryption_secrets: bool,
+}
+
+impl ForwardSecrecyPolicy {
+    pub(crate) fn from_config<C: ClientConfig>(config: &C) -> Self {
+        #[cfg(not(feature = "private_message"))]
+        let _ = config;
+
+        Self {
+            #[cfg(feature = "private_message")]
+            ratchet_checkpoint_interval: config.ratchet_checkpoint_interval(),
+            #[cfg(not(feature = "private_message"))]
+            ratchet_checkpoint_interval: 1,
+            #[cfg(all(feature = "psk", feature = "private_message"))]
+            reinit_predecessor_window: config.reinit_predecessor_window(),
+            #[cfg(not(all(feature = "psk", feature = "private_message")))]
+            reinit_predecessor_window: 0,
+            retains_encryption_secrets: cfg!(feature = "secret_tree_recovery"),
+        }
+    }
+
+    fn is_weaker(&self, other: &ForwardSecrecyPolicy) -> bool {
+        self.ratchet_checkpoint_interval > other.ratchet_checkpoint_interval
+            || self.reinit_predecessor_window > other.reinit_predecessor_window
+            || self.retains_encryption_secrets && !other.retains_encryption_secrets
+    }
+}
+
+/// Parameter of a stored group state that is below its [`SecurityWatermark`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WatermarkViolation {
+    ProtocolVersion,
+    CipherSuite,
+    RequiredCapabilities,
+    ForwardSecrecy,
+    Epoch,
+}
+
+impl SecurityWatermark {
+    pub(crate) fn new(
+        context: &GroupContext,
+        forward_secrecy: ForwardSecrecyPolicy,
+    ) -> Result<Self, MlsError> {
+        Ok(Self {
+            protocol_version: context.protocol_version,
+            cipher_suite: context.cipher_suite,
+            required_capabilities: required_capabilities(&context.extensions)?,
+            forward_secrecy,
+            epoch: context.epoch,
+        })
+    }
+
+    /// Whether `self` differs from `watermark` without any parameter below
+    /// it.
+    fn raises(&self, watermark: &SecurityWatermark, policy: &CipherSuitePolicy) -> bool {
+        self != watermark && self.violation(watermark, policy).is_none()
+    }
+
+    /// First parameter of `self` that is below `watermark`, if any.
+    ///
+    /// The cipher suite is below the watermark if it differs and is not
+    /// stronger according to `policy`. Required capabilities are below the
+    /// watermark if any capability required by the watermark is no longer
+    /// required.
+    pub fn violation(
+        &self,
+        watermark: &SecurityWatermark,
+        policy: &CipherSuitePolicy,
+    ) -> Option<WatermarkViolation> {
+        let required = &self.required_capabilities;
+        let watermark_required = &watermark.required_capabilities;
+
+        let capabilities_kept = watermark_required
+            .extensions
+            .iter()
+            .all(|e| required.extensions.contains(e))
+            && watermark_required
+                .proposals
+                .iter()
+                .all(|p| required.proposals.contains(p))
+            && watermark_required
+                .credentials
+                .iter()
+                .all(|c| required.credentials.contains(c));
+
+        if self.protocol_version < watermark.protocol_version {
+            Some(WatermarkViolation::ProtocolVersion)
+        } else if self.cipher_suite != watermark.cipher_suite
+            && !policy.is_weaker(watermark.cipher_suite, self.cipher_suite)
+        {
+            Some(WatermarkViolation::CipherSuite)
+        } else if !capabilities_kept {
+            Some(WatermarkViolation::RequiredCapabilities)
+        } else if self.forward_secrecy.is_weaker(&watermark.forward_secrecy) {
+            Some(WatermarkViolation::ForwardSecrecy)
+        } else if self.epoch < watermark.epoch {
+            Some(WatermarkViolation::Epoch)
+        } else {
+            None
+        }
+    }
+}
+
+fn required_capabilities(extensions: &ExtensionList) -> Result<RequiredCapabilitiesExt, MlsError> {
+    Ok(extensions
+        .get_as::<RequiredCapabilitiesExt>()?
+        .unwrap_or_default())
+}
+
+/// Object safe view of a [`SecurityWatermarkStorage`], so that the storage
+/// can be configured without adding a type parameter to the client config.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+pub trait WatermarkStorage: Send + Sync {
+    async fn watermark(&self, group_id: &[u8]) -> Result<Option<SecurityWatermark>, MlsError>;
+
+    /// Replace the watermark of the group with `group_id` by `watermark`
+    /// unless `keep` returns `true` for the stored watermark. If the stored
+    /// watermark changes before it is replaced, `keep` is evaluated again.
+    async fn update_watermark(
+        &self,
+        group_id: &[u8],
+        watermark: &SecurityWatermark,
+        keep: &(dyn for<'a> Fn(&'a SecurityWatermark) -> bool + Send + Sync),
+    ) -> Result<(), MlsError>;
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl<S> WatermarkStorage for S
+where
+    S: SecurityWatermarkStorage + Clone,
+{
+    async fn watermark(&self, group_id: &[u8]) -> Result<Option<SecurityWatermark>, MlsError> {
+        SecurityWatermarkStorage::watermark(self, group_id)
+            .await
+            .map_err(|e| MlsError::SecurityWatermarkStorageError(e.into_any_error()))?
+            .map(|watermark| SecurityWatermark::mls_decode(&mut &*watermark))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn update_watermark(
+        &self,
+        group_id: &[u8],
+        watermark: &SecurityWatermark,
+        keep: &(dyn for<'a> Fn(&'a SecurityWatermark) -> bool + Send + Sync),
+    ) -> Result<(), MlsError> {
+        // Storages are cheap handles to shared state, as for the other
+        // storages of the client config.
+        let mut storage = self.clone();
+        let encoded = watermark.mls_encode_to_vec()?;
+
+        loop {
+            let stored = SecurityWatermarkStorage::watermark(&storage, group_id)
+                .await
+                .map_err(|e| MlsError::SecurityWatermarkStorageError(e.into_any_error()))?;
+
+            if let Some(stored) = &stored {
+                if keep(&SecurityWatermark::mls_decode(&mut &**stored)?) {
+                    return Ok(());
+                }
+            }
+
+            let replaced = storage
+                .compare_and_set_watermark(group_id, stored.as_deref(), encoded.clone())
+                .await
+                .map_err(|e| MlsError::SecurityWatermarkStorageError(e.into_any_error()))?;
+
+            if replaced {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Debug for dyn WatermarkStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecurityWatermarkStorage")
+    }
+}
+
+pub(crate) type SharedSecurityWatermarkStorage = Arc<dyn WatermarkStorage>;
+
+/// Check a stored group state against the watermark stored for its group.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn check_security_watermark<C: ClientConfig>(
+    config: &C,
+    snapshot: &Snapshot,
+) -> Result<(), MlsError> {
+    let Some(storage) = config.security_watermark_storage() else {
+        return Ok(());
+    };
+
+    let context = &snapshot.state.context;
+
+    let Some(watermark) = storage.watermark(&context.group_id).await? else {
+        return Ok(());
+    };
+
+    let current = SecurityWatermark::new(context, snapshot.forward_secrecy_policy(config))?;
+
+    match current.violation(&watermark, &config.cipher_suite_policy()) {
+        Some(violation) => Err(MlsError::BelowSecurityWatermark(violation)),
+        None => Ok(()),
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Record the parameters of the current state, which was just written
+    /// to storage, as the watermark of the group if they are above the
+    /// recorded watermark.
+    ///
+    /// The watermark is never lowered, so that writing a stale copy of the
+    /// group does not make its state loadable.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn record_security_watermark(&self) -> Result<(), MlsError> {
+        let Some(storage) = self.config.security_watermark_storage() else {
+            return Ok(());
+        };
+
+        let watermark = SecurityWatermark::new(self.context(), self.forward_secrecy)?;
+        let policy = self.config.cipher_suite_policy();
+
+        storage
+            .update_watermark(self.group_id(), &watermark, &|stored| {
+                !watermark.raises(stored, &policy)
+            })
+            .await
+    }
+
+    /// Replace the watermark of the group by the parameters of the current
+    /// state, even if they are below it.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn reset_security_watermark(&self) -> Result<(), MlsError> {
+        let Some(storage) = self.config.security_watermark_storage() else {
+            return Ok(());
+        };
+
+        let watermark = SecurityWatermark::new(self.context(), self.forward_secrecy)?;
+
+        storage
+            .update_watermark(self.group_id(), &watermark, &|stored| *stored == watermark)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::{
+        extension::ExtensionType,
+        group::{GroupState, GroupStateStorage},
+    };
+
+    use mls_rs_codec::MlsDecode;
+
+    use crate::{
+        client::{
+            test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+            MlsError,
+        },
+        client_config::ClientConfig,
+        extension::RequiredCapabilitiesExt,
+        group::CipherSuitePolicy,
+        storage_provider::in_memory::InMemorySecurityWatermarkStorage,
+    };
+
+    use super::{
+        ForwardSecrecyPolicy, SecurityWatermark, SecurityWatermarkStorage, WatermarkViolation,
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn watermark_epoch(
+        storage: &InMemorySecurityWatermarkStorage,
+        group_id: &[u8],
+    ) -> Option<u64> {
+        storage.watermark(group_id).await.unwrap().map(|watermark| {
+            SecurityWatermark::mls_decode(&mut &*watermark)
+                .unwrap()
+                .epoch
+        })
+    }
+
+    fn watermark() -> SecurityWatermark {
+        SecurityWatermark {
+            protocol_version: crate::client::test_utils::TEST_PROTOCOL_VERSION,
+            cipher_suite: TEST_CIPHER_SUITE,
+            required_capabilities: RequiredCapabilitiesExt::new(
+                vec![ExtensionType::new(100)],
+                vec![],
+                vec![],
+            ),
+            forward_secrecy: ForwardSecrecyPolicy {
+                ratchet_checkpoint_interval: 1,
+                reinit_predecessor_window: 0,
+                retains_encryption_secrets: false,
+            },
+            epoch: 5,
+        }
+    }
+
+    #[test]
+    fn weaker_parameters_are_below_the_watermark() {
+        let policy = CipherSuitePolicy::default();
+        let watermark = watermark();
+
+        let newer = SecurityWatermark {
+            epoch: 6,
+            ..watermark.clone()
+        };
+
+        assert_eq!(newer.violation(&watermark, &policy), None);
+
+        let older = SecurityWatermark {
+            epoch: 4,
+            ..watermark.clone()
+        };
+
+        assert_eq!(
+            older.violation(&watermark, &policy),
+            Some(WatermarkViolation::Epoch)
+        );
+
+        let fewer_capabilities = SecurityWatermark {
+            required_capabilities: Default::default(),
+            ..newer.clone()
+        };
+
+        assert_eq!(
+            fewer_capabilities.violation(&watermark, &policy),
+            Some(WatermarkViolation::RequiredCapabilities)
+        );
+
+        let other_suite = SecurityWatermark {
+            cipher_suite: 0xff00.into(),
+            ..newer.clone()
+        };
+
+        assert_eq!(
+            other_suite.violation(&watermark, &policy),
+            Some(WatermarkViolation::CipherSuite)
+        );
+
+        let longer_retention = SecurityWatermark {
+            forward_secrecy: ForwardSecrecyPolicy {
+                reinit_predecessor_window: 2,
+                ..watermark.forward_secrecy
+            },
+            ..newer.clone()
+        };
+
+        assert_eq!(
+            longer_retention.violation(&watermark, &policy),
+            Some(WatermarkViolation::ForwardSecrecy)
+        );
+
+        // A cipher suite ranked stronger by the policy is accepted
+        let policy = CipherSuitePolicy::new(vec![0xff00.into(), TEST_CIPHER_SUITE]);
+        assert_eq!(other_suite.violation(&watermark, &policy), None);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rolled_back_group_state_is_refused() {
+        let watermarks = InMemorySecurityWatermarkStorage::new();
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .security_watermark_storage(watermarks.clone())
+            .build();
+
+        let mut group = client
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+
+        let group_id = group.group_id().to_vec();
+        let mut storage = client.config.group_state_storage();
+        let old_state = storage.state(&group_id).await.unwrap().unwrap();
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+        group.write_to_storage().await.unwrap();
+
+        let epoch = watermark_epoch(&watermarks, &group_id).await;
+        assert_eq!(epoch, Some(1));
+
+        storage
+            .write(
+                GroupState {
+                    id: group_id.clone(),
+                    data: old_state,
+                },
+                vec![],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let res = client.load_group(&group_id).await.map(|_| ());
+
+        assert_matches!(
+            res,
+            Err(MlsError::BelowSecurityWatermark(WatermarkViolation::Epoch))
+        );
+
+        // Loading explicitly below the watermark lowers it
+        let group = client.load_group_below_watermark(&group_id).await.unwrap();
+        assert_eq!(group.current_epoch(), 0);
+
+        let epoch = watermark_epoch(&watermarks, &group_id).await;
+        assert_eq!(epoch, Some(0));
+
+        client.load_group(&group_id).await.unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn stale_group_does_not_lower_the_watermark() {
+        let watermarks = InMemorySecurityWatermarkStorage::new();
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .security_watermark_storage(watermarks.clone())
+            .build();
+
+        let mut group = client
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+
+        let mut stale = group.clone();
+        let group_id = group.group_id().to_vec();
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+        group.write_to_storage().await.unwrap();
+
+        stale.write_to_storage().await.unwrap();
+
+        let epoch = watermark_epoch(&watermarks, &group_id).await;
+        assert_eq!(epoch, Some(1));
+
+        let res = client.load_group(&group_id).await.map(|_| ());
+
+        assert_matches!(
+            res,
+            Err(MlsError::BelowSecurityWatermark(WatermarkViolation::Epoch))
+        );
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn forward_secrecy_policy_is_stored_with_the_group() {
+        let watermarks = InMemorySecurityWatermarkStorage::new();
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .security_watermark_storage(watermarks.clone())
+            .build();
+
+        let mut group = client
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+
+        let group_id = group.group_id().to_vec();
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .group_state_storage(client.config.group_state_storage())
+            .security_watermark_storage(watermarks)
+            .ratchet_checkpoint_interval(10)
+            .build();
+
+        // Changing the config does not affect the existing group
+        let group = client.load_group(&group_id).await.unwrap();
+        assert_eq!(group.forward_secrecy.ratchet_checkpoint_interval, 1);
+
+        let group = client
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(group.forward_secrecy.ratchet_checkpoint_interval, 10);
+    }
+}
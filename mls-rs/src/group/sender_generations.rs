@@ -0,0 +1,116 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    tree_kem::node::{LeafIndex, NodeIndex},
+};
+
+use super::Group;
+
+/// Generations of the application messages of a member in the current epoch,
+/// returned by [`Group::sender_generations`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SenderGenerations {
+    /// Generation of the next application message expected from the member.
+    /// All messages with a lower generation were either received or are
+    /// listed in `missing`.
+    pub next: u32,
+    /// Generations lower than `next` that were skipped and not received yet.
+    ///
+    /// This is always empty without the `out_of_order` feature, as skipped
+    /// messages can't be decrypted anymore.
+    pub missing: Vec<u32>,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Application message generations of the member at `sender_index` in
+    /// the current epoch.
+    ///
+    /// Comparing the [`generation`](super::ApplicationMessageDescription::generation)
+    /// of received messages to these values allows detecting messages that
+    /// were lost in transport and should be requested again from the
+    /// delivery service.
+    pub fn sender_generations(&self, sender_index: u32) -> Result<SenderGenerations, MlsError> {
+        let leaf_index = LeafIndex::try_from(sender_index)?;
+        self.current_epoch_tree().get_leaf_node(leaf_index)?;
+
+        let (next, missing) = self
+            .epoch_secrets
+            .secret_tree
+            .application_generations(&NodeIndex::from(leaf_index));
+
+        Ok(SenderGenerations { next, missing })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{test_utils::test_group, ReceivedMessage},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn generations_follow_received_messages() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        assert_eq!(bob.sender_generations(0).unwrap().next, 0);
+
+        let mut messages = vec![];
+
+        for _ in 0..3 {
+            messages.push(
+                alice
+                    .encrypt_application_message(b"hello", vec![])
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let received = bob
+            .process_incoming_message(messages[2].clone())
+            .await
+            .unwrap();
+
+        let ReceivedMessage::ApplicationMessage(description) = received else {
+            panic!("expected application message");
+        };
+
+        assert_eq!(description.generation, 2);
+        assert_eq!(description.epoch, bob.context().epoch);
+
+        let generations = bob.sender_generations(0).unwrap();
+        assert_eq!(generations.next, 3);
+
+        #[cfg(feature = "out_of_order")]
+        {
+            assert_eq!(generations.missing, vec![0, 1]);
+
+            bob.process_incoming_message(messages[0].clone())
+                .await
+                .unwrap();
+
+            assert_eq!(bob.sender_generations(0).unwrap().missing, vec![1]);
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn generations_of_non_member_are_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        assert!(bob.sender_generations(5).is_err());
+    }
+}
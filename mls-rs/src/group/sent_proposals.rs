@@ -0,0 +1,190 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::{
+    proposal::{Proposal, ReInitProposal},
+    proposal_ref::ProposalRef,
+    Group,
+};
+
+/// Status of a proposal sent with one of the `propose_*` functions of
+/// [`Group`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SentProposalStatus {
+    /// The proposal was not committed yet and can still be committed in the
+    /// current epoch.
+    Pending,
+    /// The proposal was committed by the commit starting `epoch`.
+    Committed { epoch: u64 },
+    /// The epoch of the proposal ended without the proposal being committed.
+    /// `epoch` is the epoch that started without it. The proposal can no longer
+    /// be committed and must be sent again if it is still needed.
+    Expired { epoch: u64 },
+}
+
+/// Proposal sent by this member, see [`Group::sent_proposals`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct SentProposal {
+    pub proposal_ref: ProposalRef,
+    pub proposal: Proposal,
+    /// Epoch in which the proposal was sent.
+    pub epoch: u64,
+    pub status: SentProposalStatus,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SentProposalLog {
+    proposals: Vec<SentProposal>,
+}
+
+impl SentProposalLog {
+    pub(crate) fn sent(&mut self, proposal_ref: ProposalRef, proposal: Proposal, epoch: u64) {
+        self.proposals.push(SentProposal {
+            proposal_ref,
+            proposal,
+            epoch,
+            status: SentProposalStatus::Pending,
+        });
+    }
+
+    pub(crate) fn new_epoch(
+        &mut self,
+        epoch: u64,
+        committed: &[ProposalRef],
+        reinit: Option<&ReInitProposal>,
+    ) {
+        let pending = self
+            .proposals
+            .iter_mut()
+            .filter(|p| p.status == SentProposalStatus::Pending);
+
+        for sent in pending {
+            let is_committed = committed.contains(&sent.proposal_ref)
+                || matches!((&sent.proposal, reinit), (Proposal::ReInit(p), Some(r)) if p == r);
+
+            sent.status = if is_committed {
+                SentProposalStatus::Committed { epoch }
+            } else {
+                SentProposalStatus::Expired { epoch }
+            };
+        }
+    }
+
+    fn withdraw(&mut self, proposal_ref: &ProposalRef) -> bool {
+        let len = self.proposals.len();
+
+        self.proposals
+            .retain(|p| p.status != SentProposalStatus::Pending || &p.proposal_ref != proposal_ref);
+
+        self.proposals.len() != len
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Proposals sent by this member, in the order they were sent, along with
+    /// their status.
+    ///
+    /// Sent proposals are not part of the stored group state. They are lost if
+    /// the group is reloaded from storage.
+    pub fn sent_proposals(&self) -> &[SentProposal] {
+        &self.sent_proposals.proposals
+    }
+
+    /// Withdraw a pending proposal sent by this member.
+    ///
+    /// The proposal is removed from the local proposal cache, so that it is no
+    /// longer included in commits created by this member, and from
+    /// [`Group::sent_proposals`]. Other members that received the proposal may
+    /// still commit it in the current epoch.
+    pub fn withdraw_sent_proposal(&mut self, proposal_ref: &ProposalRef) -> Result<(), MlsError> {
+        if !self.sent_proposals.withdraw(proposal_ref) {
+            return Err(MlsError::ProposalNotFound);
+        }
+
+        self.state.proposals.remove_own(proposal_ref);
+        self.offloaded_proposals.retain(|r| r != proposal_ref);
+
+        Ok(())
+    }
+
+    /// Forget committed and expired proposals, keeping only the pending ones.
+    pub fn clear_resolved_sent_proposals(&mut self) {
+        self.sent_proposals
+            .proposals
+            .retain(|p| p.status == SentProposalStatus::Pending);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group,
+    };
+
+    use super::SentProposalStatus;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sent_proposals_are_committed_or_expire() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let proposal = bob.propose_update(vec![]).await.unwrap();
+        alice.process_incoming_message(proposal).await.unwrap();
+        bob.propose_update(vec![]).await.unwrap();
+
+        let commit = alice.commit(vec![]).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let epoch = bob.current_epoch();
+
+        assert_matches!(
+            bob.sent_proposals(),
+            [first, second] if first.status == SentProposalStatus::Committed { epoch }
+                && second.status == SentProposalStatus::Expired { epoch }
+                && first.epoch == epoch - 1
+        );
+
+        bob.clear_resolved_sent_proposals();
+        assert!(bob.sent_proposals().is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn withdrawn_proposals_are_not_committed() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        alice
+            .propose_remove(bob.current_member_index(), vec![])
+            .await
+            .unwrap();
+
+        let proposal_ref = alice.sent_proposals()[0].proposal_ref.clone();
+        alice.withdraw_sent_proposal(&proposal_ref).unwrap();
+
+        assert!(alice.sent_proposals().is_empty());
+        assert!(!alice.commit_required());
+
+        let res = alice.withdraw_sent_proposal(&proposal_ref);
+        assert_matches!(res, Err(MlsError::ProposalNotFound));
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::{
+    crypto::{
+        CipherSuite, CipherSuiteProvider, SignatureKeyStorage, SignaturePublicKey,
+        SignatureSecretKey,
+    },
+    error::IntoAnyError,
+    identity::SigningIdentity,
+};
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+use super::cipher_suite_provider;
+
+/// Generate a signature key pair for a new group or key package if the
+/// client uses per group signature keys.
+///
+/// The public key is certified by the credential issuer of `config` and the
+/// secret key is inserted in the signature key storage.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn generate_signature_key<C: ClientConfig>(
+    config: &C,
+    cipher_suite: CipherSuite,
+) -> Result<Option<(SigningIdentity, SignatureSecretKey)>, MlsError> {
+    let Some(issuer) = config.credential_issuer() else {
+        return Ok(None);
+    };
+
+    let cs = cipher_suite_provider(config.crypto_provider(), cipher_suite)?;
+
+    let (secret_key, public_key) = cs
+        .signature_key_generate()
+        .await
+        .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+    let signing_identity = issuer
+        .issue_credential(&public_key, cipher_suite)
+        .map_err(MlsError::CredentialIssuerError)?;
+
+    if signing_identity.signature_key != public_key {
+        return Err(MlsError::IssuedSignatureKeyMismatch);
+    }
+
+    config
+        .signature_key_storage()
+        .insert(&public_key, secret_key.clone())
+        .await
+        .map_err(|e| MlsError::SignatureKeyStorageError(e.into_any_error()))?;
+
+    Ok(Some((signing_identity, secret_key)))
+}
+
+/// Secret key matching `public_key` in the signature key storage of
+/// `config`, if any.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn stored_signer<C: ClientConfig>(
+    config: &C,
+    public_key: &SignaturePublicKey,
+) -> Result<Option<SignatureSecretKey>, MlsError> {
+    config
+        .signature_key_storage()
+        .get(public_key)
+        .await
+        .map_err(|e| MlsError::SignatureKeyStorageError(e.into_any_error()))
+}
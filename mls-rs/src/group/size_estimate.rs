@@ -0,0 +1,411 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsSize, VarInt};
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
+
+use crate::{client::MlsError, client_config::ClientConfig, ExtensionList, MlsRules};
+
+use super::{
+    framing::Sender, message_processor::path_update_required, mls_rules::CommitSource,
+    proposal::Proposal, CommitBuilder, Group,
+};
+
+#[cfg(not(feature = "by_ref_proposal"))]
+use super::proposal_cache::prepare_commit;
+
+// Protocol version and wire format of an `MlsMessage`.
+const MLS_MESSAGE_HEADER_LEN: usize = 4;
+// Authentication tag of all AEAD algorithms used by MLS cipher suites.
+const AEAD_TAG_LEN: usize = 16;
+// Leaf index, generation and reuse guard of an encrypted `SenderData`.
+#[cfg(feature = "private_message")]
+const SENDER_DATA_LEN: usize = 12;
+// Empty extension with a grease type added to group infos.
+const GREASE_EXTENSION_LEN: usize = 3;
+
+/// Estimated encoded sizes of the messages produced by a commit, returned by
+/// [`CommitBuilder::estimate_sizes`].
+///
+/// Sizes of signatures and HPKE keys are taken from the leaf of the committer,
+/// so the estimates may be off by a few bytes. All other values are derived from
+/// the current state of the group and the proposals of the commit, assuming that
+/// none of them are filtered out by [`MlsRules::filter_proposals`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct CommitSizeEstimate {
+    /// Size of the commit message.
+    pub commit_message: usize,
+    /// Size of the largest welcome message, or zero if no members are added.
+    pub welcome_message: usize,
+    /// Size of the group info encrypted in welcome messages, or zero if no
+    /// members are added.
+    pub group_info: usize,
+    /// Size of the ratchet tree after the commit is applied, whether it is
+    /// sent in the welcome message or out of band.
+    pub ratchet_tree: usize,
+}
+
+impl<C> CommitBuilder<'_, C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Estimate the sizes of the messages that [`CommitBuilder::build`]
+    /// would output for the proposals added so far, without building them.
+    ///
+    /// Proposals received during the current epoch are included by reference
+    /// as they would be in the resulting commit.
+    pub fn estimate_sizes(&self) -> Result<CommitSizeEstimate, MlsError> {
+        self.group.estimate_commit_sizes(
+            self.proposals.clone(),
+            self.authenticated_data.len(),
+            &self.group_info_extensions,
+        )
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Estimate the size of the message returned by
+    /// [`Group::group_info_message`] without signing it.
+    pub fn estimate_group_info_message_size(
+        &self,
+        with_tree_in_extension: bool,
+    ) -> Result<usize, MlsError> {
+        let ratchet_tree_ext_len = if with_tree_in_extension {
+            ratchet_tree_ext_len(self.state.public_tree.nodes.mls_encoded_len())
+        } else {
+            0
+        };
+
+        let extensions_len = extension_list_len(&ExtensionList::new(), ratchet_tree_ext_len);
+
+        Ok(MLS_MESSAGE_HEADER_LEN
+            + self.group_info_len(self.context().mls_encoded_len(), extensions_len)?)
+    }
+
+    fn estimate_commit_sizes(
+        &self,
+        proposals: Vec<Proposal>,
+        authenticated_data_len: usize,
+        group_info_extensions: &ExtensionList,
+    ) -> Result<CommitSizeEstimate, MlsError> {
+        let sender = Sender::Member(*self.private_tree.self_index);
+
+        #[cfg(feature = "by_ref_proposal")]
+        let proposals = self.state.proposals.prepare_commit(sender, proposals);
+
+        #[cfg(not(feature = "by_ref_proposal"))]
+        let proposals = prepare_commit(sender, proposals);
+
+        let mls_rules = self.config.mls_rules();
+
+        let source = CommitSource::ExistingMember(
+            self.roster()
+                .member_with_index(*self.private_tree.self_index)?,
+        );
+
+        let commit_options = mls_rules
+            .commit_options(&self.roster(), self.context(), &proposals)
+            .and_then(|options| {
+                mls_rules.adjust_commit_options(
+                    options,
+                    &source,
+                    &self.roster(),
+                    self.context(),
+                    &proposals,
+                )
+            })
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
+
+        let own_leaf = self.current_user_leaf_node()?;
+        let hash_len = self.cipher_suite_provider.kdf_extract_size();
+        let public_key_len = own_leaf.public_key.mls_encoded_len();
+        let kem_output_len = own_leaf.public_key.as_ref().len();
+
+        let tree = &self.state.public_tree;
+        let added = proposals.add_proposals();
+        let removed = proposals.remove_proposals();
+
+        // The tree is truncated after the last remaining member before added
+        // members fill its blank leaves from the left.
+        let (members, used_leaves) = tree
+            .non_empty_leaves()
+            .filter(|(index, _)| !removed.iter().any(|p| p.proposal.to_remove == *index))
+            .fold((0, 0), |(members, _), (index, _)| (members + 1, *index + 1));
+
+        let blank_leaves = used_leaves - members;
+        let total_leaves = tree.total_leaf_count();
+
+        let new_total_leaves =
+            (used_leaves + (added.len() as u32).saturating_sub(blank_leaves)).next_power_of_two();
+
+        let depth = new_total_leaves.trailing_zeros() as usize;
+
+        let perform_path_update = commit_options.path_required || path_update_required(&proposals);
+
+        // Added leaves replace blank nodes, the tree is extended with blank
+        // nodes if there are not enough of them and added members are listed
+        // as unmerged leaves of their parents.
+        let mut ratchet_tree = tree.nodes.mls_encoded_len()
+            + 2 * new_total_leaves.saturating_sub(total_leaves) as usize
+            + added
+                .iter()
+                .map(|p| p.proposal.key_package.leaf_node.mls_encoded_len() + 1 + 4 * depth)
+                .sum::<usize>();
+
+        let mut path_len = 0;
+
+        if perform_path_update {
+            let parent_node_len = 2 + public_key_len + vec_len(hash_len) + 1;
+            ratchet_tree += depth * parent_node_len + hash_len;
+
+            // Path secrets are encrypted at most once for every other
+            // current member.
+            let ciphertexts = members.saturating_sub(1) as usize;
+            let ciphertext_len = vec_len(kem_output_len) + vec_len(hash_len + AEAD_TAG_LEN);
+
+            let nodes_len = vec_len(depth * (public_key_len + 4) + ciphertexts * ciphertext_len);
+
+            // The leaf in the path is sourced from the commit and carries a
+            // parent hash, which is empty if the committer is the only member.
+            let parent_hash_len = if depth > 0 { hash_len } else { 0 };
+
+            let leaf_len = own_leaf.mls_encoded_len() - own_leaf.leaf_node_source.mls_encoded_len()
+                + 1
+                + vec_len(parent_hash_len);
+
+            path_len = leaf_len + nodes_len;
+        }
+
+        let commit_len = proposals.proposals_or_refs().mls_encoded_len() + 1 + path_len;
+
+        let commit_message =
+            self.commit_message_len(commit_len, authenticated_data_len, hash_len)?;
+
+        if added.is_empty() {
+            return Ok(CommitSizeEstimate {
+                commit_message,
+                ratchet_tree,
+                ..Default::default()
+            });
+        }
+
+        let context_len = self.context().mls_encoded_len()
+            + proposals
+                .group_context_extensions_proposal()
+                .map(|p| p.proposal.mls_encoded_len())
+                .unwrap_or_default()
+            - proposals
+                .group_context_extensions_proposal()
+                .map(|_| self.context().extensions.mls_encoded_len())
+                .unwrap_or_default();
+
+        let ratchet_tree_ext_len = if commit_options.ratchet_tree_extension {
+            ratchet_tree_ext_len(ratchet_tree)
+        } else {
+            0
+        };
+
+        let extensions_len = extension_list_len(group_info_extensions, ratchet_tree_ext_len);
+        let group_info = self.group_info_len(context_len, extensions_len)?;
+
+        #[cfg(feature = "psk")]
+        let psks_len = proposals
+            .psk_proposals()
+            .iter()
+            .map(|p| p.proposal.psk.mls_encoded_len())
+            .sum::<usize>();
+
+        #[cfg(not(feature = "psk"))]
+        let psks_len = 0;
+
+        let path_secret_len = if perform_path_update {
+            vec_len(hash_len)
+        } else {
+            0
+        };
+
+        let group_secrets_len = vec_len(hash_len) + 1 + path_secret_len + vec_len(psks_len);
+
+        let secrets_len = added
+            .iter()
+            .map(|p| {
+                vec_len(hash_len)
+                    + vec_len(p.proposal.key_package.hpke_init_key.as_ref().len())
+                    + vec_len(group_secrets_len + AEAD_TAG_LEN)
+            })
+            .collect::<Vec<_>>();
+
+        let secrets_len = if commit_options.single_welcome_message {
+            secrets_len.iter().sum()
+        } else {
+            secrets_len.into_iter().max().unwrap_or_default()
+        };
+
+        let welcome_message =
+            MLS_MESSAGE_HEADER_LEN + 2 + vec_len(secrets_len) + vec_len(group_info + AEAD_TAG_LEN);
+
+        Ok(CommitSizeEstimate {
+            commit_message,
+            welcome_message,
+            group_info,
+            ratchet_tree,
+        })
+    }
+
+    fn group_info_len(&self, context_len: usize, extensions_len: usize) -> Result<usize, MlsError> {
+        Ok(context_len
+            + extensions_len
+            + self.state.confirmation_tag.mls_encoded_len()
+            + 4
+            + vec_len(self.current_user_leaf_node()?.signature.len()))
+    }
+
+    fn commit_message_len(
+        &self,
+        commit_len: usize,
+        authenticated_data_len: usize,
+        hash_len: usize,
+    ) -> Result<usize, MlsError> {
+        let group_id_len = vec_len(self.context().group_id.len());
+        let authenticated_data_len = vec_len(authenticated_data_len);
+
+        // Signature and confirmation tag.
+        let auth_len = vec_len(self.current_user_leaf_node()?.signature.len()) + vec_len(hash_len);
+
+        #[cfg(feature = "private_message")]
+        {
+            let encryption_options = self.encryption_options()?;
+
+            if encryption_options.encrypt_control_messages {
+                let content_len = encryption_options
+                    .padding_mode
+                    .padded_size(commit_len + auth_len);
+
+                return Ok(MLS_MESSAGE_HEADER_LEN
+                    + group_id_len
+                    + 8
+                    + 1
+                    + authenticated_data_len
+                    + vec_len(SENDER_DATA_LEN + AEAD_TAG_LEN)
+                    + vec_len(content_len + AEAD_TAG_LEN));
+            }
+        }
+
+        // Public message with a member sender and a membership tag.
+        Ok(MLS_MESSAGE_HEADER_LEN
+            + group_id_len
+            + 8
+            + 5
+            + authenticated_data_len
+            + 1
+            + commit_len
+            + auth_len
+            + vec_len(hash_len))
+    }
+}
+
+fn vec_len(len: usize) -> usize {
+    VarInt::try_from(len).unwrap_or(VarInt(0)).mls_encoded_len() + len
+}
+
+fn ratchet_tree_ext_len(tree_len: usize) -> usize {
+    2 + vec_len(tree_len)
+}
+
+fn extension_list_len(extensions: &ExtensionList, additional_len: usize) -> usize {
+    let extensions_len = extensions
+        .iter()
+        .map(|ext| ext.mls_encoded_len())
+        .sum::<usize>();
+
+    vec_len(extensions_len + additional_len + GREASE_EXTENSION_LEN)
+}
+
+#[cfg(test)]
+mod tests {
+    use mls_rs_codec::MlsSize;
+
+    use crate::{
+        client::test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    fn assert_close(estimate: usize, actual: usize) {
+        assert!(
+            estimate.abs_diff(actual) <= actual / 20 + 16,
+            "estimate {estimate} too far from actual size {actual}"
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_estimates_are_close_to_actual_sizes() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (_bob, _) = alice.join("bob").await;
+
+        let (_, carol) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let (_, dave) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "dave").await;
+
+        let builder = alice
+            .commit_builder()
+            .add_member(carol)
+            .unwrap()
+            .add_member(dave)
+            .unwrap();
+
+        let estimate = builder.estimate_sizes().unwrap();
+        let output = builder.build().await.unwrap();
+
+        assert_close(
+            estimate.commit_message,
+            output.commit_message.mls_encoded_len(),
+        );
+
+        assert_close(
+            estimate.welcome_message,
+            output.welcome_messages[0].mls_encoded_len(),
+        );
+
+        assert!(estimate.ratchet_tree > 0);
+        assert!(estimate.group_info > 0);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_without_added_members_has_no_welcome() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (_bob, _) = alice.join("bob").await;
+
+        let builder = alice.commit_builder().remove_member(1).unwrap();
+        let estimate = builder.estimate_sizes().unwrap();
+        let output = builder.build().await.unwrap();
+
+        assert_eq!(estimate.welcome_message, 0);
+
+        assert_close(
+            estimate.commit_message,
+            output.commit_message.mls_encoded_len(),
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_info_estimate_is_close_to_actual_size() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        for with_tree in [false, true] {
+            let estimate = bob.estimate_group_info_message_size(with_tree).unwrap();
+            let actual = bob.group_info_message(with_tree).await.unwrap();
+
+            assert_close(estimate, actual.mls_encoded_len());
+        }
+    }
+}
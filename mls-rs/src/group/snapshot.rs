@@ -31,22 +31,48 @@ use mls_rs_core::crypto::SignatureSecretKey;
 #[cfg(feature = "tree_index")]
 use mls_rs_core::identity::IdentityProvider;
 
-use super::PendingCommit;
+use super::{
+    security_watermark::ForwardSecrecyPolicy, signature_keys::stored_signer, PendingCommit,
+};
+
+#[cfg(feature = "private_message")]
+use super::ratchet_checkpoint::RatchetCheckpoint;
+
+#[cfg(feature = "secret_tree_recovery")]
+use super::epoch::EncryptionSecret;
+
+#[cfg(feature = "broadcast")]
+use super::broadcast::BroadcastReservation;
 
 pub(crate) use legacy::LegacyPendingCommit;
 
 #[derive(Debug, PartialEq, Clone, MlsEncode, MlsDecode, MlsSize)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Snapshot {
-    version: u16,
+    pub(crate) version: u16,
     pub(crate) state: RawGroupState,
-    private_tree: TreeKemPrivate,
-    epoch_secrets: EpochSecrets,
-    key_schedule: KeySchedule,
+    pub(crate) private_tree: TreeKemPrivate,
+    #[cfg_attr(
+        feature = "secret_tree_recovery",
+        mls_codec(with = "legacy::epoch_secrets")
+    )]
+    pub(crate) epoch_secrets: EpochSecrets,
+    pub(crate) key_schedule: KeySchedule,
     #[cfg(feature = "by_ref_proposal")]
-    pending_updates: SmallMap<HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>)>,
-    pending_commit_snapshot: PendingCommitSnapshot,
-    signer: SignatureSecretKey,
+    pub(crate) pending_updates:
+        SmallMap<HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>)>,
+    pub(crate) pending_commit_snapshot: PendingCommitSnapshot,
+    pub(crate) signer: SignatureSecretKey,
+    // Kept out of `epoch_secrets` and encoded after the fields of earlier
+    // versions so that their snapshots can still be decoded.
+    #[cfg(feature = "secret_tree_recovery")]
+    #[mls_codec(with = "legacy::trailing")]
+    pub(crate) encryption_secret: EncryptionSecret,
+    #[cfg(feature = "broadcast")]
+    #[mls_codec(with = "legacy::trailing")]
+    pub(crate) broadcast_reservation: BroadcastReservation,
+    #[mls_codec(with = "legacy::trailing")]
+    pub(crate) forward_secrecy: Option<ForwardSecrecyPolicy>,
 }
 
 #[derive(Debug, PartialEq, Clone, Default, MlsSize, MlsEncode, MlsDecode)]
@@ -188,6 +214,18 @@ impl RawGroupState {
     }
 }
 
+impl Snapshot {
+    /// Forward secrecy policy of the group. It is taken from `config` for
+    /// snapshots written before the policy was stored with the group.
+    pub(crate) fn forward_secrecy_policy<C: ClientConfig>(
+        &self,
+        config: &C,
+    ) -> ForwardSecrecyPolicy {
+        self.forward_secrecy
+            .unwrap_or_else(|| ForwardSecrecyPolicy::from_config(config))
+    }
+}
+
 impl<C> Group<C>
 where
     C: ClientConfig + Clone,
@@ -197,7 +235,18 @@ where
     /// that is currently in use by the group.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage(&mut self) -> Result<(), MlsError> {
-        self.state_repo.write_to_storage(self.snapshot()?).await
+        self.state_repo.write_to_storage(self.snapshot()?).await?;
+        self.record_security_watermark().await?;
+
+        #[cfg(feature = "psk")]
+        self.replace_mapped_group_id().await?;
+
+        #[cfg(feature = "private_message")]
+        {
+            self.ratchet_checkpoint = RatchetCheckpoint::new(self.context().epoch);
+        }
+
+        Ok(())
     }
 
     /// Write the current state of the group to the
@@ -210,10 +259,31 @@ where
         let mut snapshot = self.snapshot()?;
         snapshot.state.public_tree.nodes = Default::default();
 
-        self.state_repo.write_to_storage(snapshot).await
+        self.state_repo.write_to_storage(snapshot).await?;
+        self.record_security_watermark().await?;
+
+        #[cfg(feature = "psk")]
+        self.replace_mapped_group_id().await?;
+
+        #[cfg(feature = "private_message")]
+        {
+            self.ratchet_checkpoint = RatchetCheckpoint::new(self.context().epoch);
+        }
+
+        Ok(())
     }
 
     pub(crate) fn snapshot(&self) -> Result<Snapshot, MlsError> {
+        #[cfg(feature = "secret_tree_recovery")]
+        let (epoch_secrets, encryption_secret) = {
+            let mut epoch_secrets = self.epoch_secrets.clone();
+            let encryption_secret = core::mem::take(&mut epoch_secrets.encryption_secret);
+            (epoch_secrets, encryption_secret)
+        };
+
+        #[cfg(not(feature = "secret_tree_recovery"))]
+        let epoch_secrets = self.epoch_secrets.clone();
+
         Ok(Snapshot {
             state: RawGroupState::export(&self.state),
             private_tree: self.private_tree.clone(),
@@ -221,9 +291,14 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: self.pending_updates.clone(),
             pending_commit_snapshot: self.pending_commit.clone(),
-            epoch_secrets: self.epoch_secrets.clone(),
+            epoch_secrets,
             version: 1,
             signer: self.signer.clone(),
+            #[cfg(feature = "secret_tree_recovery")]
+            encryption_secret,
+            #[cfg(feature = "broadcast")]
+            broadcast_reservation: self.broadcast_reservation,
+            forward_secrecy: Some(self.forward_secrecy),
         })
     }
 
@@ -243,9 +318,24 @@ where
             config.group_state_storage(),
             config.key_package_repo(),
             None,
-        )?;
+        )?
+        .with_snapshot_format(config.snapshot_format());
 
-        Ok(Group {
+        #[cfg(feature = "private_message")]
+        let epoch = snapshot.state.context.epoch;
+
+        let forward_secrecy = snapshot.forward_secrecy_policy(&config);
+
+        #[cfg(feature = "secret_tree_recovery")]
+        let epoch_secrets = EpochSecrets {
+            encryption_secret: snapshot.encryption_secret,
+            ..snapshot.epoch_secrets
+        };
+
+        #[cfg(not(feature = "secret_tree_recovery"))]
+        let epoch_secrets = snapshot.epoch_secrets;
+
+        let mut group = Group {
             config,
             state: snapshot
                 .state
@@ -261,13 +351,65 @@ where
             pending_commit: snapshot.pending_commit_snapshot,
             #[cfg(test)]
             commit_modifiers: Default::default(),
-            epoch_secrets: snapshot.epoch_secrets,
+            epoch_secrets,
             state_repo,
             cipher_suite_provider,
             #[cfg(feature = "psk")]
             previous_psk: None,
+            #[cfg(all(feature = "psk", feature = "private_message"))]
+            predecessor: None,
+            #[cfg(feature = "psk")]
+            replaced_group_id: None,
+            #[cfg(feature = "by_ref_proposal")]
+            offloaded_proposals: Vec::new(),
+            #[cfg(feature = "by_ref_proposal")]
+            sent_proposals: Default::default(),
+            validated_key_packages: Vec::new(),
+            intent_log: Default::default(),
+            #[cfg(feature = "private_message")]
+            decrypted_generation: None,
+            #[cfg(feature = "private_message")]
+            ratchet_checkpoint: RatchetCheckpoint::new(epoch),
+            #[cfg(feature = "broadcast")]
+            broadcast_reservation: snapshot.broadcast_reservation,
+            forward_secrecy,
             signer: snapshot.signer,
-        })
+        };
+
+        // Groups created or joined with a per group signature key sign with
+        // the stored key matching their leaf. Groups stored without the
+        // ratchet tree are loaded with the tree by the client.
+        if !group.state.public_tree.nodes.is_empty() {
+            let public_key = &group
+                .current_user_leaf_node()?
+                .signing_identity
+                .signature_key;
+
+            if let Some(signer) = stored_signer(&group.config, public_key).await? {
+                group.signer = signer;
+            }
+        }
+
+        #[cfg(feature = "by_ref_proposal")]
+        let group = group.with_offloaded_proposals().await?;
+
+        Ok(group)
+    }
+
+    /// Compute the tree hashes, which are otherwise computed lazily by the
+    /// first operation that needs them.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn prepare(&mut self) -> Result<(), MlsError> {
+        // Groups stored without the ratchet tree need the tree to be provided
+        // before they can be used.
+        if !self.state.public_tree.nodes.is_empty() {
+            self.state
+                .public_tree
+                .tree_hash(&self.cipher_suite_provider)
+                .await?;
+        }
+
+        Ok(())
     }
 }
 
@@ -284,12 +426,99 @@ mod legacy {
         pub commit_secret: PathSecret,
         pub commit_message_hash: MessageHash,
     }
+
+    /// Epoch secrets without the encryption secret, which is encoded at the
+    /// end of the snapshot by [`trailing`].
+    #[cfg(feature = "secret_tree_recovery")]
+    pub(crate) mod epoch_secrets {
+        use mls_rs_codec::{byte_vec, DecodeLimits};
+
+        use super::*;
+
+        pub fn mls_encoded_len(secrets: &EpochSecrets) -> usize {
+            #[cfg(feature = "psk")]
+            let len = byte_vec::mls_encoded_len(&secrets.resumption_secret);
+
+            #[cfg(not(feature = "psk"))]
+            let len = 0;
+
+            len + byte_vec::mls_encoded_len(&secrets.sender_data_secret)
+                + secrets.secret_tree.mls_encoded_len()
+        }
+
+        pub fn mls_encode(
+            secrets: &EpochSecrets,
+            writer: &mut Vec<u8>,
+        ) -> Result<(), mls_rs_codec::Error> {
+            #[cfg(feature = "psk")]
+            byte_vec::mls_encode(&secrets.resumption_secret, writer)?;
+
+            byte_vec::mls_encode(&secrets.sender_data_secret, writer)?;
+            secrets.secret_tree.mls_encode(writer)
+        }
+
+        pub fn mls_decode(reader: &mut &[u8]) -> Result<EpochSecrets, mls_rs_codec::Error> {
+            mls_decode_with_limits(reader, &DecodeLimits::UNLIMITED)
+        }
+
+        pub fn mls_decode_with_limits(
+            reader: &mut &[u8],
+            limits: &DecodeLimits,
+        ) -> Result<EpochSecrets, mls_rs_codec::Error> {
+            Ok(EpochSecrets {
+                #[cfg(feature = "psk")]
+                resumption_secret: byte_vec::mls_decode_with_limits(reader, limits)?,
+                sender_data_secret: byte_vec::mls_decode_with_limits(reader, limits)?,
+                secret_tree: MlsDecode::mls_decode_with_limits(reader, limits)?,
+                encryption_secret: Default::default(),
+            })
+        }
+    }
+
+    /// Optional value at the end of a snapshot, decoded as the default value
+    /// from snapshots written without it.
+    pub(crate) mod trailing {
+        use mls_rs_codec::DecodeLimits;
+
+        use super::*;
+
+        pub fn mls_encoded_len<T: MlsSize>(value: &T) -> usize {
+            value.mls_encoded_len()
+        }
+
+        pub fn mls_encode<T: MlsEncode>(
+            value: &T,
+            writer: &mut Vec<u8>,
+        ) -> Result<(), mls_rs_codec::Error> {
+            value.mls_encode(writer)
+        }
+
+        pub fn mls_decode<T: MlsDecode + Default>(
+            reader: &mut &[u8],
+        ) -> Result<T, mls_rs_codec::Error> {
+            mls_decode_with_limits(reader, &DecodeLimits::UNLIMITED)
+        }
+
+        pub fn mls_decode_with_limits<T: MlsDecode + Default>(
+            reader: &mut &[u8],
+            limits: &DecodeLimits,
+        ) -> Result<T, mls_rs_codec::Error> {
+            if reader.is_empty() {
+                return Ok(T::default());
+            }
+
+            T::mls_decode_with_limits(reader, limits)
+        }
+    }
 }
 
 #[cfg(test)]
 pub(crate) mod test_utils {
     use alloc::vec;
 
+    #[cfg(feature = "secret_tree_recovery")]
+    use mls_rs_codec::MlsSize;
+
     use crate::{
         cipher_suite::CipherSuite,
         crypto::test_utils::test_cipher_suite_provider,
@@ -326,6 +555,28 @@ pub(crate) mod test_utils {
             pending_commit_snapshot: Default::default(),
             version: 1,
             signer: vec![].into(),
+            #[cfg(feature = "secret_tree_recovery")]
+            encryption_secret: Default::default(),
+            #[cfg(feature = "broadcast")]
+            broadcast_reservation: Default::default(),
+            forward_secrecy: None,
+        }
+    }
+
+    #[cfg(feature = "secret_tree_recovery")]
+    impl Snapshot {
+        /// Encoded length of the fields that are missing from snapshots
+        /// written by earlier versions.
+        pub(crate) fn trailing_len(&self) -> usize {
+            let len = self.forward_secrecy.mls_encoded_len();
+
+            #[cfg(feature = "secret_tree_recovery")]
+            let len = len + self.encryption_secret.mls_encoded_len();
+
+            #[cfg(feature = "broadcast")]
+            let len = len + self.broadcast_reservation.mls_encoded_len();
+
+            len
         }
     }
 }
@@ -0,0 +1,536 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Versioned wire format of the group state written to
+//! [`GroupStateStorage`](mls_rs_core::group::GroupStateStorage).
+//!
+//! A versioned snapshot starts with the 4 byte magic `MLSS` followed by the
+//! format version as a big-endian `u16`. All integers use the big-endian
+//! encoding of the MLS codec, so snapshots do not depend on the endianness or
+//! pointer width of the platform that wrote them, and the same state is always
+//! encoded to the same bytes.
+//!
+//! The body of [`SnapshotFormat::Sectioned`] is an MLS vector of
+//! `(section_type: u16, data: opaque<V>)` pairs. Each section holds one
+//! component of the group state, so a snapshot written with one set of crate
+//! features can be read with another: unknown sections are skipped and
+//! sections that the writer did not have are filled with defaults where that
+//! is safe.
+//!
+//! Snapshots without the magic use [`SnapshotFormat::Legacy`], the plain
+//! encoding of the group state written by earlier versions of this crate.
+//! Their layout depends on the enabled features.
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{iter::mls_decode_split_on_collection, MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::group::GroupContext;
+
+use crate::{client::MlsError, group::TreeKemPublic};
+
+#[cfg(feature = "private_message")]
+use crate::tree_kem::node::LeafIndex;
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::map::SmallMap;
+
+#[cfg(feature = "by_ref_proposal")]
+use core::hash::Hash;
+
+use super::{
+    epoch::EpochSecrets,
+    snapshot::{RawGroupState, Snapshot},
+};
+
+/// Parts of a stored group state needed to open a message of its epoch,
+/// returned by [`Snapshot::epoch_from_bytes`].
+#[cfg(feature = "private_message")]
+pub(crate) struct EpochSnapshot {
+    pub(crate) context: GroupContext,
+    pub(crate) self_index: LeafIndex,
+    pub(crate) public_tree: TreeKemPublic,
+    pub(crate) epoch_secrets: EpochSecrets,
+}
+
+/// Magic bytes at the start of every versioned snapshot.
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"MLSS";
+
+const HEADER_LEN: usize = SNAPSHOT_MAGIC.len() + 2;
+
+/// Wire format used when writing group state to storage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+#[non_exhaustive]
+pub enum SnapshotFormat {
+    /// Headerless encoding written by earlier versions of this crate. It can
+    /// only be read by clients with the same crate features as the writer.
+    ///
+    /// This is the default, since it is the only format older versions of
+    /// this crate can read.
+    #[default]
+    Legacy = 1,
+    /// Versioned encoding made of independent sections. It can not be read
+    /// by versions of this crate that predate it.
+    Sectioned = 2,
+}
+
+impl SnapshotFormat {
+    /// Formats this version of the crate can read and write, newest first.
+    pub const SUPPORTED: &'static [SnapshotFormat] =
+        &[SnapshotFormat::Sectioned, SnapshotFormat::Legacy];
+
+    /// Version number stored in the snapshot header.
+    pub fn version(&self) -> u16 {
+        *self as u16
+    }
+
+    pub fn from_version(version: u16) -> Option<Self> {
+        Self::SUPPORTED
+            .iter()
+            .copied()
+            .find(|f| f.version() == version)
+    }
+
+    /// Newest format supported both by this client and by a reader that
+    /// supports `other`, for example an older deployment sharing the same
+    /// storage.
+    pub fn negotiate(other: &[SnapshotFormat]) -> Option<Self> {
+        Self::SUPPORTED.iter().copied().find(|f| other.contains(f))
+    }
+
+    /// Format of a stored snapshot. Snapshots without the magic are
+    /// [`SnapshotFormat::Legacy`].
+    pub fn detect(snapshot: &[u8]) -> Result<Self, MlsError> {
+        if !snapshot.starts_with(&SNAPSHOT_MAGIC) {
+            return Ok(Self::Legacy);
+        }
+
+        let version = snapshot
+            .get(SNAPSHOT_MAGIC.len()..HEADER_LEN)
+            .map(|v| u16::from_be_bytes([v[0], v[1]]))
+            .ok_or(mls_rs_codec::Error::UnexpectedEOF)?;
+
+        match Self::from_version(version) {
+            Some(Self::Legacy) | None => Err(MlsError::UnsupportedSnapshotVersion(version)),
+            Some(format) => Ok(format),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+enum SectionType {
+    Context = 1,
+    TreeNodes = 2,
+    InterimTranscriptHash = 3,
+    PendingReInit = 4,
+    ConfirmationTag = 5,
+    PrivateTree = 6,
+    SenderDataSecret = 7,
+    #[cfg(feature = "psk")]
+    ResumptionSecret = 8,
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    SecretTree = 9,
+    #[cfg(feature = "secret_tree_recovery")]
+    EncryptionSecret = 10,
+    KeySchedule = 11,
+    #[cfg(feature = "by_ref_proposal")]
+    Proposals = 12,
+    #[cfg(feature = "by_ref_proposal")]
+    OwnProposals = 13,
+    #[cfg(feature = "by_ref_proposal")]
+    PendingUpdates = 14,
+    PendingCommit = 15,
+    Signer = 16,
+    #[cfg(feature = "broadcast")]
+    BroadcastReservation = 17,
+    ForwardSecrecy = 18,
+}
+
+#[derive(Clone, Debug, MlsSize, MlsEncode, MlsDecode)]
+struct Section {
+    section_type: u16,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct SectionWriter(Vec<Section>);
+
+impl SectionWriter {
+    fn push<T: MlsEncode>(&mut self, section_type: SectionType, value: &T) -> Result<(), MlsError> {
+        self.0.push(Section {
+            section_type: section_type as u16,
+            data: value.mls_encode_to_vec()?,
+        });
+
+        Ok(())
+    }
+
+    /// Maps are written with entries sorted by encoded key, so that the
+    /// output does not depend on the iteration order of the map.
+    #[cfg(feature = "by_ref_proposal")]
+    fn push_map<K, V>(
+        &mut self,
+        section_type: SectionType,
+        map: &SmallMap<K, V>,
+    ) -> Result<(), MlsError>
+    where
+        K: MlsEncode + Hash + Eq,
+        V: MlsEncode,
+    {
+        let mut entries = map
+            .iter()
+            .map(|(k, v)| Ok((k.mls_encode_to_vec()?, (k, v))))
+            .collect::<Result<Vec<_>, mls_rs_codec::Error>>()?;
+
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let entries = entries.into_iter().map(|(_, e)| e).collect::<Vec<_>>();
+
+        self.push(section_type, &entries)
+    }
+}
+
+/// Sections of an encoded snapshot, borrowed so that only the sections that
+/// are read get decoded.
+struct SectionReader<'a>(Vec<(u16, &'a [u8])>);
+
+impl<'a> SectionReader<'a> {
+    fn new(mut bytes: &'a [u8]) -> Result<Self, MlsError> {
+        let (mut body, _) = mls_decode_split_on_collection(&mut bytes)?;
+        let mut sections = Vec::new();
+
+        while !body.is_empty() {
+            let section_type = u16::mls_decode(&mut body)?;
+            let (data, rest) = mls_decode_split_on_collection(&mut body)?;
+
+            sections.push((section_type, data));
+            body = rest;
+        }
+
+        Ok(Self(sections))
+    }
+
+    fn get<T: MlsDecode>(&self, section_type: SectionType) -> Result<Option<T>, MlsError> {
+        self.0
+            .iter()
+            .find(|(t, _)| *t == section_type as u16)
+            .map(|(_, data)| T::mls_decode(&mut &**data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    fn require<T: MlsDecode>(&self, section_type: SectionType) -> Result<T, MlsError> {
+        self.get(section_type)?
+            .ok_or(MlsError::MissingSnapshotSection(section_type as u16))
+    }
+
+    fn epoch_secrets(&self) -> Result<EpochSecrets, MlsError> {
+        Ok(EpochSecrets {
+            #[cfg(feature = "psk")]
+            resumption_secret: self.require(SectionType::ResumptionSecret)?,
+            sender_data_secret: self.require(SectionType::SenderDataSecret)?,
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            secret_tree: self.require(SectionType::SecretTree)?,
+            // Stored with `Snapshot::encryption_secret`.
+            #[cfg(feature = "secret_tree_recovery")]
+            encryption_secret: Default::default(),
+        })
+    }
+
+    fn get_or_default<T: MlsDecode + Default>(
+        &self,
+        section_type: SectionType,
+    ) -> Result<T, MlsError> {
+        Ok(self.get(section_type)?.unwrap_or_default())
+    }
+}
+
+impl Snapshot {
+    pub(crate) fn to_bytes(&self, format: SnapshotFormat) -> Result<Vec<u8>, MlsError> {
+        if format == SnapshotFormat::Legacy {
+            return self.mls_encode_to_vec().map_err(Into::into);
+        }
+
+        let mut sections = SectionWriter::default();
+        let state = &self.state;
+        let secrets = &self.epoch_secrets;
+
+        sections.push(SectionType::Context, &state.context)?;
+        sections.push(SectionType::TreeNodes, &state.public_tree.nodes)?;
+        sections.push(
+            SectionType::InterimTranscriptHash,
+            &state.interim_transcript_hash,
+        )?;
+        sections.push(SectionType::PendingReInit, &state.pending_reinit)?;
+        sections.push(SectionType::ConfirmationTag, &state.confirmation_tag)?;
+        sections.push(SectionType::PrivateTree, &self.private_tree)?;
+        sections.push(SectionType::SenderDataSecret, &secrets.sender_data_secret)?;
+
+        #[cfg(feature = "psk")]
+        sections.push(SectionType::ResumptionSecret, &secrets.resumption_secret)?;
+
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        sections.push(SectionType::SecretTree, &secrets.secret_tree)?;
+
+        #[cfg(feature = "secret_tree_recovery")]
+        sections.push(SectionType::EncryptionSecret, &self.encryption_secret)?;
+
+        sections.push(SectionType::KeySchedule, &self.key_schedule)?;
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            sections.push_map(SectionType::Proposals, &state.proposals)?;
+            sections.push_map(SectionType::OwnProposals, &state.own_proposals)?;
+            sections.push_map(SectionType::PendingUpdates, &self.pending_updates)?;
+        }
+
+        sections.push(SectionType::PendingCommit, &self.pending_commit_snapshot)?;
+        sections.push(SectionType::Signer, &self.signer)?;
+
+        #[cfg(feature = "broadcast")]
+        sections.push(
+            SectionType::BroadcastReservation,
+            &self.broadcast_reservation,
+        )?;
+
+        sections.push(SectionType::ForwardSecrecy, &self.forward_secrecy)?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        bytes.extend_from_slice(&SNAPSHOT_MAGIC);
+        bytes.extend_from_slice(&format.version().to_be_bytes());
+        sections.0.mls_encode(&mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        match SnapshotFormat::detect(bytes)? {
+            SnapshotFormat::Legacy => Snapshot::mls_decode(&mut &*bytes).map_err(Into::into),
+            SnapshotFormat::Sectioned => Self::from_sections(&bytes[HEADER_LEN..]),
+        }
+    }
+
+    /// Group context of an encoded snapshot, read without decoding the tree
+    /// or any secrets.
+    pub(crate) fn context_from_bytes(bytes: &[u8]) -> Result<GroupContext, MlsError> {
+        match SnapshotFormat::detect(bytes)? {
+            SnapshotFormat::Legacy => {
+                // A legacy snapshot starts with a version number followed by
+                // the group context.
+                let mut reader = bytes;
+                let _version = u16::mls_decode(&mut reader)?;
+
+                GroupContext::mls_decode(&mut reader).map_err(Into::into)
+            }
+            SnapshotFormat::Sectioned => {
+                SectionReader::new(&bytes[HEADER_LEN..])?.require(SectionType::Context)
+            }
+        }
+    }
+
+    /// Decode the parts of an encoded snapshot needed to open a message of
+    /// its epoch. With [`SnapshotFormat::Sectioned`], the private keys,
+    /// proposals, pending commit and signer are not decoded.
+    #[cfg(feature = "private_message")]
+    pub(crate) fn epoch_from_bytes(bytes: &[u8]) -> Result<EpochSnapshot, MlsError> {
+        if SnapshotFormat::detect(bytes)? == SnapshotFormat::Legacy {
+            let snapshot = Snapshot::mls_decode(&mut &*bytes)?;
+
+            return Ok(EpochSnapshot {
+                context: snapshot.state.context,
+                self_index: snapshot.private_tree.self_index,
+                public_tree: snapshot.state.public_tree,
+                epoch_secrets: snapshot.epoch_secrets,
+            });
+        }
+
+        let sections = SectionReader::new(&bytes[HEADER_LEN..])?;
+
+        let mut public_tree = TreeKemPublic::new();
+        public_tree.nodes = sections.require(SectionType::TreeNodes)?;
+
+        Ok(EpochSnapshot {
+            context: sections.require(SectionType::Context)?,
+            // The private tree starts with the index of this member, so its
+            // keys are left undecoded.
+            self_index: sections.require(SectionType::PrivateTree)?,
+            public_tree,
+            epoch_secrets: sections.epoch_secrets()?,
+        })
+    }
+
+    fn from_sections(bytes: &[u8]) -> Result<Self, MlsError> {
+        let sections = SectionReader::new(bytes)?;
+
+        let mut public_tree = TreeKemPublic::new();
+        public_tree.nodes = sections.require(SectionType::TreeNodes)?;
+
+        let state = RawGroupState {
+            context: sections.require(SectionType::Context)?,
+            #[cfg(feature = "by_ref_proposal")]
+            proposals: sections.get_or_default(SectionType::Proposals)?,
+            #[cfg(feature = "by_ref_proposal")]
+            own_proposals: sections.get_or_default(SectionType::OwnProposals)?,
+            public_tree,
+            interim_transcript_hash: sections.require(SectionType::InterimTranscriptHash)?,
+            pending_reinit: sections.require(SectionType::PendingReInit)?,
+            confirmation_tag: sections.require(SectionType::ConfirmationTag)?,
+        };
+
+        Ok(Snapshot {
+            version: 1,
+            state,
+            private_tree: sections.require(SectionType::PrivateTree)?,
+            epoch_secrets: sections.epoch_secrets()?,
+            key_schedule: sections.require(SectionType::KeySchedule)?,
+            #[cfg(feature = "by_ref_proposal")]
+            pending_updates: sections.get_or_default(SectionType::PendingUpdates)?,
+            pending_commit_snapshot: sections.get_or_default(SectionType::PendingCommit)?,
+            signer: sections.require(SectionType::Signer)?,
+            // Without the encryption secret, the secret tree can not be
+            // recovered but the snapshot is otherwise usable.
+            #[cfg(feature = "secret_tree_recovery")]
+            encryption_secret: sections.get_or_default(SectionType::EncryptionSecret)?,
+            #[cfg(feature = "broadcast")]
+            broadcast_reservation: sections.get_or_default(SectionType::BroadcastReservation)?,
+            forward_secrecy: sections.get_or_default(SectionType::ForwardSecrecy)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_codec::{MlsDecode, MlsEncode};
+    use mls_rs_core::group::GroupStateStorage;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        client_config::ClientConfig,
+        group::{snapshot::Snapshot, test_utils::test_group, Group},
+    };
+
+    use super::{Section, SnapshotFormat, SNAPSHOT_MAGIC};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sectioned_snapshot_round_trip() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        let snapshot = alice.snapshot().unwrap();
+        let bytes = snapshot.to_bytes(SnapshotFormat::Sectioned).unwrap();
+
+        assert_eq!(bytes[..4], SNAPSHOT_MAGIC);
+        assert_eq!(bytes[4..6], [0, 2]);
+        assert_eq!(
+            SnapshotFormat::detect(&bytes).unwrap(),
+            SnapshotFormat::Sectioned
+        );
+
+        assert_eq!(Snapshot::from_bytes(&bytes).unwrap(), snapshot);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn legacy_snapshots_are_readable() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let snapshot = alice.snapshot().unwrap();
+
+        let bytes = snapshot.mls_encode_to_vec().unwrap();
+
+        assert_eq!(bytes, snapshot.to_bytes(SnapshotFormat::Legacy).unwrap());
+        assert_eq!(
+            SnapshotFormat::detect(&bytes).unwrap(),
+            SnapshotFormat::Legacy
+        );
+        assert_eq!(Snapshot::from_bytes(&bytes).unwrap(), snapshot);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_state_is_stored_as_legacy_by_default() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+        alice.write_to_storage().await.unwrap();
+
+        let stored = alice
+            .config
+            .group_state_storage()
+            .state(alice.group_id())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(SnapshotFormat::default(), SnapshotFormat::Legacy);
+        assert_eq!(
+            SnapshotFormat::detect(&stored).unwrap(),
+            SnapshotFormat::Legacy
+        );
+
+        // Readers that predate the sectioned format decode the plain snapshot.
+        let snapshot = Snapshot::mls_decode(&mut &*stored).unwrap();
+        assert_eq!(snapshot, alice.snapshot().unwrap());
+        assert_eq!(Snapshot::from_bytes(&stored).unwrap(), snapshot);
+
+        let restored = Group::from_snapshot(alice.config.clone(), snapshot)
+            .await
+            .unwrap();
+
+        assert!(Group::equal_group_state(&alice, &restored));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unknown_sections_are_skipped_and_required_ones_checked() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let snapshot = alice.snapshot().unwrap();
+        let bytes = snapshot.to_bytes(SnapshotFormat::Sectioned).unwrap();
+
+        let (header, mut body) = bytes.split_at(6);
+        let mut sections = Vec::<Section>::mls_decode(&mut body).unwrap();
+
+        sections.push(Section {
+            section_type: 0xffff,
+            data: vec![1, 2, 3],
+        });
+
+        let with_unknown = [header, &sections.mls_encode_to_vec().unwrap()[..]].concat();
+        assert!(Snapshot::from_bytes(&with_unknown).is_ok());
+
+        sections.retain(|s| s.section_type != 1);
+
+        let without_context = [header, &sections.mls_encode_to_vec().unwrap()[..]].concat();
+
+        assert_matches!(
+            Snapshot::from_bytes(&without_context),
+            Err(MlsError::MissingSnapshotSection(1))
+        );
+    }
+
+    #[test]
+    fn format_negotiation() {
+        assert_eq!(
+            SnapshotFormat::negotiate(&[SnapshotFormat::Legacy]),
+            Some(SnapshotFormat::Legacy)
+        );
+
+        assert_eq!(
+            SnapshotFormat::negotiate(SnapshotFormat::SUPPORTED),
+            Some(SnapshotFormat::Sectioned)
+        );
+
+        assert_eq!(SnapshotFormat::negotiate(&[]), None);
+
+        let future = [&SNAPSHOT_MAGIC[..], &[0u8, 3][..]].concat();
+
+        assert_matches!(
+            SnapshotFormat::detect(&future),
+            Err(MlsError::UnsupportedSnapshotVersion(3))
+        );
+    }
+}
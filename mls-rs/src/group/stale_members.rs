@@ -0,0 +1,324 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::HpkePublicKey, identity::SigningIdentity, time::MlsTime};
+
+use crate::{client::MlsError, client_config::ClientConfig, tree_kem::node::LeafIndex};
+
+use super::{CommitBuilder, Group};
+
+/// What [`StaleMemberTracker::commit_builder`] does with stale members.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StaleMemberAction {
+    /// Only report stale members so that the application can review them.
+    #[default]
+    Report,
+    /// Remove stale members in the commit.
+    Remove,
+}
+
+/// When [`StaleMemberTracker`] considers a member to be stale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StaleMemberPolicy {
+    /// Maximum time since the leaf of a member was last updated.
+    pub max_inactivity: Duration,
+    pub action: StaleMemberAction,
+}
+
+impl StaleMemberPolicy {
+    pub fn new(max_inactivity: Duration) -> Self {
+        Self {
+            max_inactivity,
+            action: StaleMemberAction::default(),
+        }
+    }
+
+    pub fn with_action(self, action: StaleMemberAction) -> Self {
+        Self { action, ..self }
+    }
+}
+
+/// Last observed update of the leaf of a member.
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct LeafActivity {
+    /// Leaf index of the member.
+    pub index: u32,
+    /// HPKE public key of the leaf when the update was observed.
+    pub leaf_public_key: HpkePublicKey,
+    /// Time at which the update was observed.
+    pub last_update: MlsTime,
+    /// Epoch in which the update was observed.
+    pub last_update_epoch: u64,
+}
+
+/// Member whose leaf was not updated within
+/// [`StaleMemberPolicy::max_inactivity`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StaleMember {
+    /// Leaf index of the member.
+    pub index: u32,
+    pub signing_identity: SigningIdentity,
+    /// Time at which the last update of the leaf was observed.
+    pub last_update: MlsTime,
+    /// Epoch in which the last update of the leaf was observed.
+    pub last_update_epoch: u64,
+}
+
+/// Identify members whose leaf is not updated anymore, such as lost devices.
+///
+/// Leaf nodes carry no update time, so the tracker records the time and
+/// epoch at which it first observes each HPKE public key of each leaf. A
+/// member seen for the first time is considered updated at that time, which
+/// means a member is never reported as stale earlier than it should be.
+///
+/// The tracker is not part of the group state and should be kept next to
+/// the group by the application. The observed updates can be persisted with
+/// [`StaleMemberTracker::activity`] and restored with
+/// [`StaleMemberTracker::restore`], otherwise a new tracker starts counting
+/// from the time of its first use.
+#[derive(Clone, Debug)]
+pub struct StaleMemberTracker {
+    policy: StaleMemberPolicy,
+    activity: Vec<LeafActivity>,
+}
+
+impl StaleMemberTracker {
+    pub fn new(policy: StaleMemberPolicy) -> Self {
+        Self::restore(policy, Vec::new())
+    }
+
+    /// Create a tracker from updates previously observed by another tracker.
+    pub fn restore(policy: StaleMemberPolicy, mut activity: Vec<LeafActivity>) -> Self {
+        activity.sort_by_key(|a| a.index);
+        activity.dedup_by_key(|a| a.index);
+
+        Self { policy, activity }
+    }
+
+    pub fn policy(&self) -> &StaleMemberPolicy {
+        &self.policy
+    }
+
+    /// Updates observed so far, sorted by leaf index.
+    pub fn activity(&self) -> &[LeafActivity] {
+        &self.activity
+    }
+
+    /// Record the current leaves of `group` at time `now`.
+    ///
+    /// Leaves with a new HPKE public key, including leaves of new members,
+    /// are recorded as updated at `now`. Leaves that were removed are
+    /// forgotten.
+    pub fn observe<C>(&mut self, group: &Group<C>, now: MlsTime)
+    where
+        C: ClientConfig + Clone,
+    {
+        let epoch = group.current_epoch();
+
+        let activity = group
+            .current_epoch_tree()
+            .non_empty_leaves()
+            .map(|(index, leaf)| {
+                let index = *index;
+
+                match self.find(index) {
+                    Some(previous) if previous.leaf_public_key == leaf.public_key => {
+                        previous.clone()
+                    }
+                    _ => LeafActivity {
+                        index,
+                        leaf_public_key: leaf.public_key.clone(),
+                        last_update: now,
+                        last_update_epoch: epoch,
+                    },
+                }
+            })
+            .collect();
+
+        self.activity = activity;
+    }
+
+    /// Members of `group` other than this member that are stale at `now`,
+    /// sorted by last update.
+    pub fn stale_members<C>(
+        &mut self,
+        group: &Group<C>,
+        now: MlsTime,
+    ) -> Result<Vec<StaleMember>, MlsError>
+    where
+        C: ClientConfig + Clone,
+    {
+        self.observe(group, now);
+
+        let own_index = group.current_member_index();
+        let tree = group.current_epoch_tree();
+
+        let mut stale = self
+            .activity
+            .iter()
+            .filter(|a| a.index != own_index && self.is_stale(a, now))
+            .map(|a| {
+                let leaf = tree.get_leaf_node(LeafIndex::try_from(a.index)?)?;
+
+                Ok(StaleMember {
+                    index: a.index,
+                    signing_identity: leaf.signing_identity.clone(),
+                    last_update: a.last_update,
+                    last_update_epoch: a.last_update_epoch,
+                })
+            })
+            .collect::<Result<Vec<_>, MlsError>>()?;
+
+        stale.sort_by_key(|s| s.last_update);
+
+        Ok(stale)
+    }
+
+    /// Start a commit in `group` at time `now` and report stale members.
+    ///
+    /// If the action of the policy is [`StaleMemberAction::Remove`], the
+    /// returned builder already removes the reported members. Otherwise the
+    /// builder is empty and the application decides what to do with them.
+    pub fn commit_builder<'a, C>(
+        &mut self,
+        group: &'a mut Group<C>,
+        now: MlsTime,
+    ) -> Result<(CommitBuilder<'a, C>, Vec<StaleMember>), MlsError>
+    where
+        C: ClientConfig + Clone,
+    {
+        let stale = self.stale_members(group, now)?;
+        let mut builder = group.commit_builder();
+
+        if self.policy.action == StaleMemberAction::Remove {
+            builder = stale
+                .iter()
+                .try_fold(builder, |builder, s| builder.remove_member(s.index))?;
+        }
+
+        Ok((builder, stale))
+    }
+
+    fn find(&self, index: u32) -> Option<&LeafActivity> {
+        self.activity
+            .binary_search_by_key(&index, |a| a.index)
+            .ok()
+            .map(|i| &self.activity[i])
+    }
+
+    fn is_stale(&self, activity: &LeafActivity, now: MlsTime) -> bool {
+        let inactivity = Duration::from_secs(
+            now.seconds_since_epoch()
+                .saturating_sub(activity.last_update.seconds_since_epoch()),
+        );
+
+        inactivity >= self.policy.max_inactivity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use core::time::Duration;
+
+    use mls_rs_core::time::MlsTime;
+
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
+    use crate::group::test_utils::test_n_member_group;
+
+    use super::{StaleMemberAction, StaleMemberPolicy, StaleMemberTracker};
+
+    const DAY: Duration = Duration::from_secs(86400);
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_without_updates_are_reported() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+        let mut tracker = StaleMemberTracker::new(StaleMemberPolicy::new(DAY * 30));
+
+        let start = MlsTime::from(1000);
+        let start_epoch = groups[0].current_epoch();
+
+        assert!(tracker.stale_members(&groups[0], start).unwrap().is_empty());
+
+        // Bob updates his leaf, Carol never does
+        let commit = groups[1].commit(vec![]).await.unwrap();
+        groups[1].process_pending_commit().await.unwrap();
+
+        groups[0]
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        tracker.observe(&groups[0], start + DAY * 10);
+
+        let stale = tracker.stale_members(&groups[0], start + DAY * 30).unwrap();
+
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].index, 2);
+        assert_eq!(stale[0].last_update, start);
+        assert_eq!(stale[0].last_update_epoch, start_epoch);
+
+        // The tracker can be restored from its observed updates
+        let mut restored =
+            StaleMemberTracker::restore(*tracker.policy(), tracker.activity().to_vec());
+
+        assert_eq!(
+            restored
+                .stale_members(&groups[0], start + DAY * 40)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn stale_members_are_removed_by_commit() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let policy = StaleMemberPolicy::new(DAY).with_action(StaleMemberAction::Remove);
+        let mut tracker = StaleMemberTracker::new(policy);
+
+        let start = MlsTime::from(1000);
+        tracker.observe(&groups[0], start);
+
+        let (builder, stale) = tracker.commit_builder(&mut groups[0], start + DAY).unwrap();
+        builder.build().await.unwrap();
+
+        assert_eq!(stale.len(), 2);
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        assert_eq!(groups[0].roster().members_iter().count(), 1);
+
+        // Removed leaves are forgotten
+        tracker.observe(&groups[0], start + DAY);
+        assert_eq!(tracker.activity().len(), 1);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn stale_members_are_only_reported_by_default() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let mut tracker = StaleMemberTracker::new(StaleMemberPolicy::new(DAY));
+
+        let start = MlsTime::from(1000);
+        tracker.observe(&groups[0], start);
+
+        let (builder, stale) = tracker.commit_builder(&mut groups[0], start + DAY).unwrap();
+        builder.build().await.unwrap();
+
+        assert_eq!(stale.len(), 1);
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        assert_eq!(groups[0].roster().members_iter().count(), 2);
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, CryptoProvider},
+    error::IntoAnyError,
+    secret::KeyScheduleSecret,
+};
+use zeroize::Zeroizing;
+
+use crate::{client::MlsError, client_config::ClientConfig};
+
+#[cfg(feature = "prior_epoch")]
+use super::PriorEpoch;
+
+use super::{
+    sealed_snapshot::SealedSnapshotHeader, snapshot::Snapshot, snapshot_format::SnapshotFormat,
+    Group,
+};
+
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct StateBlob {
+    header: SealedSnapshotHeader,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    salt: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    ciphertext: Vec<u8>,
+}
+
+// Prior epochs are encoded separately so that blobs can be imported by
+// clients built without the `prior_epoch` feature.
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct StateBlobContent {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    snapshot: Vec<u8>,
+    prior_epochs: Vec<Vec<u8>>,
+}
+
+impl StateBlob {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn seal<P: CipherSuiteProvider>(
+        cipher_suite_provider: &P,
+        snapshot: &Snapshot,
+        content: &StateBlobContent,
+        protection_key: &[u8],
+    ) -> Result<Self, MlsError> {
+        // A fresh salt gives every blob its own key and nonce, even if the
+        // same protection key is used for many exports.
+        let salt = cipher_suite_provider
+            .random_bytes_vec(cipher_suite_provider.kdf_extract_size())
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let header = SealedSnapshotHeader::new(&snapshot.state.context);
+        let secret = blob_secret(cipher_suite_provider, &salt, protection_key).await?;
+        let plaintext = Zeroizing::new(content.mls_encode_to_vec()?);
+
+        let ciphertext = header
+            .seal(cipher_suite_provider, &secret, &salt, &plaintext)
+            .await?;
+
+        Ok(Self {
+            header,
+            salt,
+            ciphertext,
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn open<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        protection_key: &[u8],
+    ) -> Result<(Snapshot, StateBlobContent), MlsError> {
+        let secret = blob_secret(cipher_suite_provider, &self.salt, protection_key).await?;
+
+        let plaintext = self
+            .header
+            .open(cipher_suite_provider, &secret, &self.salt, &self.ciphertext)
+            .await?;
+
+        let content = StateBlobContent::mls_decode(&mut &**plaintext)?;
+        let snapshot = Snapshot::from_bytes(&content.snapshot)?;
+        self.header.verify(&snapshot.state.context)?;
+
+        Ok((snapshot, content))
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn blob_secret<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    salt: &[u8],
+    protection_key: &[u8],
+) -> Result<KeyScheduleSecret, MlsError> {
+    cipher_suite_provider
+        .kdf_extract(salt, protection_key)
+        .await
+        .map(Into::into)
+        .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Serialize everything needed to resume this group on another device or
+    /// storage backend into a single blob, encrypted and authenticated with a
+    /// key derived from `protection_key`.
+    ///
+    /// The blob contains the ratchet tree, the private keys and epoch secrets
+    /// of this member, its signing key, pending commit and proposals as well
+    /// as the prior epochs kept for decrypting late messages. It is imported
+    /// with [`Client::import_state_blob`](crate::Client::import_state_blob).
+    ///
+    /// `protection_key` should be a uniformly random secret shared between the
+    /// two devices. The group must not be used on this device after the
+    /// export, any copy of it in the
+    /// [GroupStateStorage](crate::GroupStateStorage) of this client should be
+    /// deleted by the application.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn export_state_blob(&self, protection_key: &[u8]) -> Result<Vec<u8>, MlsError> {
+        let snapshot = self.snapshot()?;
+
+        #[cfg(feature = "prior_epoch")]
+        let prior_epochs = self
+            .state_repo
+            .prior_epochs()
+            .await?
+            .iter()
+            .map(|epoch| epoch.mls_encode_to_vec())
+            .collect::<Result<_, _>>()?;
+
+        #[cfg(not(feature = "prior_epoch"))]
+        let prior_epochs = Vec::new();
+
+        let content = StateBlobContent {
+            snapshot: snapshot.to_bytes(SnapshotFormat::default())?,
+            prior_epochs,
+        };
+
+        StateBlob::seal(
+            &self.cipher_suite_provider,
+            &snapshot,
+            &content,
+            protection_key,
+        )
+        .await?
+        .mls_encode_to_vec()
+        .map_err(Into::into)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn import_state_blob(
+        config: C,
+        blob: &[u8],
+        protection_key: &[u8],
+    ) -> Result<Self, MlsError> {
+        let blob = StateBlob::mls_decode(&mut &*blob)?;
+
+        let header = &blob.header;
+
+        if !config.version_supported(header.version) {
+            return Err(MlsError::UnsupportedProtocolVersion(header.version));
+        }
+
+        let cipher_suite_provider = config
+            .crypto_provider()
+            .cipher_suite_provider(header.cipher_suite)
+            .ok_or(MlsError::UnsupportedCipherSuite(header.cipher_suite))?;
+
+        let (snapshot, content) = blob.open(&cipher_suite_provider, protection_key).await?;
+        let mut group = Group::from_snapshot(config, snapshot).await?;
+
+        #[cfg(feature = "prior_epoch")]
+        for epoch in content.prior_epochs {
+            let epoch = PriorEpoch::mls_decode(&mut &*epoch)?;
+            group.state_repo.insert(epoch).await?;
+        }
+
+        #[cfg(not(feature = "prior_epoch"))]
+        let _ = content;
+
+        group.write_to_storage().await?;
+
+        Ok(group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{sealed_snapshot::test_utils::new_device, test_utils::test_group},
+    };
+
+    const PROTECTION_KEY: &[u8] = b"device migration key";
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn state_blob_round_trips_with_in_memory_storage() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let blob = alice.export_state_blob(PROTECTION_KEY).await.unwrap();
+        let device = new_device(|builder| builder).await;
+
+        let mut moved = device
+            .import_state_blob(&blob, PROTECTION_KEY)
+            .await
+            .unwrap();
+
+        assert_eq!(moved.context(), alice.context());
+
+        // The imported group was written to the storage of the new device.
+        let restored = device.load_group(moved.group_id()).await.unwrap();
+        assert_eq!(restored.context(), alice.context());
+
+        let commit = bob.commit(Vec::new()).await.unwrap();
+        bob.apply_pending_commit().await.unwrap();
+
+        moved
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(moved.context(), bob.context());
+    }
+
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn prior_epochs_are_included_in_state_blob() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let message = bob
+            .encrypt_application_message(b"late", Vec::new())
+            .await
+            .unwrap();
+
+        let commit = alice.commit(Vec::new()).await.unwrap();
+        alice.apply_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        let blob = alice.export_state_blob(PROTECTION_KEY).await.unwrap();
+
+        let mut moved = new_device(|builder| builder)
+            .await
+            .import_state_blob(&blob, PROTECTION_KEY)
+            .await
+            .unwrap();
+
+        moved.process_incoming_message(message).await.unwrap();
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn state_blob_round_trips_with_sqlite_storage() {
+        use mls_rs_provider_sqlite::{
+            connection_strategy::MemoryStrategy, SqLiteDataStorageEngine,
+        };
+
+        use crate::{
+            client_builder::ClientBuilder,
+            crypto::test_utils::TestCryptoProvider,
+            identity::{basic::BasicIdentityProvider, test_utils::get_test_signing_identity},
+        };
+
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let blob = alice.export_state_blob(PROTECTION_KEY).await.unwrap();
+
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"device").await;
+
+        let device =
+            ClientBuilder::new_sqlite(SqLiteDataStorageEngine::new(MemoryStrategy).unwrap())
+                .unwrap()
+                .crypto_provider(TestCryptoProvider::new())
+                .identity_provider(BasicIdentityProvider::new())
+                .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+                .build();
+
+        let imported = device
+            .import_state_blob(&blob, PROTECTION_KEY)
+            .await
+            .unwrap();
+
+        let mut restored = device.load_group(imported.group_id()).await.unwrap();
+        assert_eq!(restored.context(), alice.context());
+
+        // A blob exported from SQLite storage imports into in-memory storage.
+        let blob = restored.export_state_blob(PROTECTION_KEY).await.unwrap();
+
+        let mut moved = new_device(|builder| builder)
+            .await
+            .import_state_blob(&blob, PROTECTION_KEY)
+            .await
+            .unwrap();
+
+        let commit = bob.commit(Vec::new()).await.unwrap();
+        bob.apply_pending_commit().await.unwrap();
+
+        restored
+            .process_incoming_message(commit.commit_message.clone())
+            .await
+            .unwrap();
+
+        moved
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(restored.context(), bob.context());
+        assert_eq!(moved.context(), bob.context());
+    }
+}
@@ -12,7 +12,7 @@ use mls_rs_codec::{MlsDecode, MlsEncode};
 use mls_rs_core::group::{EpochRecord, GroupState};
 use mls_rs_core::{error::IntoAnyError, group::GroupStateStorage, key_package::KeyPackageStorage};
 
-use super::snapshot::Snapshot;
+use super::{snapshot::Snapshot, snapshot_format::SnapshotFormat};
 
 #[cfg(feature = "psk")]
 use crate::group::ResumptionPsk;
@@ -39,6 +39,7 @@ where
     group_id: Vec<u8>,
     storage: S,
     key_package_repo: K,
+    snapshot_format: SnapshotFormat,
 }
 
 impl<S, K> Debug for GroupStateRepository<S, K>
@@ -59,6 +60,7 @@ where
             )
             .field("storage", &self.storage)
             .field("key_package_repo", &self.key_package_repo)
+            .field("snapshot_format", &self.snapshot_format)
             .finish()
     }
 }
@@ -81,9 +83,18 @@ where
             pending_key_package_removal: key_package_to_remove,
             pending_commit: Default::default(),
             key_package_repo,
+            snapshot_format: SnapshotFormat::default(),
         })
     }
 
+    /// Format used to encode the group state in [`Self::write_to_storage`].
+    pub fn with_snapshot_format(self, snapshot_format: SnapshotFormat) -> Self {
+        Self {
+            snapshot_format,
+            ..self
+        }
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn find_max_id(&self) -> Result<Option<u64>, MlsError> {
         if let Some(max) = self.pending_commit.inserts.back().map(|e| e.epoch_id()) {
@@ -191,6 +202,48 @@ where
         Ok(())
     }
 
+    /// All prior epochs of the group, including the ones not yet written to
+    /// storage, in ascending order of epoch id.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn prior_epochs(&self) -> Result<Vec<PriorEpoch>, MlsError> {
+        let mut epochs = Vec::new();
+        let mut epoch_id = self.find_max_id().await?;
+
+        while let Some(id) = epoch_id {
+            let Some(epoch) = self.prior_epoch(id).await? else {
+                break;
+            };
+
+            epochs.push(epoch);
+            epoch_id = id.checked_sub(1);
+        }
+
+        epochs.reverse();
+
+        Ok(epochs)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn prior_epoch(&self, epoch_id: u64) -> Result<Option<PriorEpoch>, MlsError> {
+        let pending = self
+            .pending_commit
+            .inserts
+            .iter()
+            .chain(self.pending_commit.updates.iter())
+            .find(|epoch| epoch.epoch_id() == epoch_id);
+
+        if let Some(epoch) = pending {
+            return Ok(Some(epoch.clone()));
+        }
+
+        self.storage
+            .epoch(&self.group_id, epoch_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
+            .map(|epoch| PriorEpoch::mls_decode(&mut &*epoch).map_err(Into::into))
+            .transpose()
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage(&mut self, group_snapshot: Snapshot) -> Result<(), MlsError> {
         let inserts = self
@@ -208,7 +261,7 @@ where
             .collect::<Result<_, MlsError>>()?;
 
         let group_state = GroupState {
-            data: group_snapshot.mls_encode_to_vec()?,
+            data: group_snapshot.to_bytes(self.snapshot_format)?,
             id: group_snapshot.state.context.group_id,
         };
 
@@ -334,7 +387,10 @@ mod tests {
 
         let stored = storage.get(TEST_GROUP).unwrap();
 
-        assert_eq!(stored.state_data, snapshot.mls_encode_to_vec().unwrap());
+        assert_eq!(
+            stored.state_data,
+            snapshot.to_bytes(SnapshotFormat::default()).unwrap()
+        );
 
         assert_eq!(stored.epoch_data.len(), 1);
 
@@ -402,7 +458,10 @@ mod tests {
 
         let stored = storage.get(TEST_GROUP).unwrap();
 
-        assert_eq!(stored.state_data, snapshot.mls_encode_to_vec().unwrap());
+        assert_eq!(
+            stored.state_data,
+            snapshot.to_bytes(SnapshotFormat::default()).unwrap()
+        );
 
         assert_eq!(stored.epoch_data.len(), 1);
 
@@ -570,4 +629,26 @@ mod tests {
 
         assert!(repo.key_package_repo.get(&key_package.reference).is_none());
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn prior_epochs_include_stored_and_pending_epochs() {
+        let mut repo = test_group_state_repo(2);
+
+        for epoch_id in 0..3 {
+            repo.insert(test_epoch(epoch_id)).await.unwrap();
+        }
+
+        repo.write_to_storage(test_snapshot(2).await).await.unwrap();
+        repo.insert(test_epoch(3)).await.unwrap();
+
+        let epoch_ids = repo
+            .prior_epochs()
+            .await
+            .unwrap()
+            .iter()
+            .map(PriorEpoch::epoch_id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(epoch_ids, vec![1, 2, 3]);
+    }
 }
@@ -6,14 +6,13 @@ use crate::client::MlsError;
 use crate::key_package::KeyPackageRef;
 
 use alloc::vec::Vec;
-use mls_rs_codec::MlsEncode;
 use mls_rs_core::{
     error::IntoAnyError,
     group::{GroupState, GroupStateStorage},
     key_package::KeyPackageStorage,
 };
 
-use super::snapshot::Snapshot;
+use super::{snapshot::Snapshot, snapshot_format::SnapshotFormat};
 
 #[derive(Debug, Clone)]
 pub(crate) struct GroupStateRepository<S, K>
@@ -24,6 +23,7 @@ where
     pending_key_package_removal: Option<KeyPackageRef>,
     storage: S,
     key_package_repo: K,
+    snapshot_format: SnapshotFormat,
 }
 
 impl<S, K> GroupStateRepository<S, K>
@@ -41,13 +41,22 @@ where
             storage,
             pending_key_package_removal: key_package_to_remove,
             key_package_repo,
+            snapshot_format: SnapshotFormat::default(),
         })
     }
 
+    /// Format used to encode the group state in [`Self::write_to_storage`].
+    pub fn with_snapshot_format(self, snapshot_format: SnapshotFormat) -> Self {
+        Self {
+            snapshot_format,
+            ..self
+        }
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage(&mut self, group_snapshot: Snapshot) -> Result<(), MlsError> {
         let group_state = GroupState {
-            data: group_snapshot.mls_encode_to_vec()?,
+            data: group_snapshot.to_bytes(self.snapshot_format)?,
             id: group_snapshot.state.context.group_id,
         };
 
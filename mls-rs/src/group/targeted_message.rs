@@ -0,0 +1,576 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, HpkeCiphertext},
+    error::IntoAnyError,
+    extension::ExtensionType,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{key_schedule::kdf_expand_with_label, GroupContext},
+    signer::Signable,
+    tree_kem::{hpke_encryption::HpkeEncryptable, node::LeafIndex},
+    MlsMessage,
+};
+
+use super::{framing::MlsMessagePayload, Group};
+
+const SENDER_AUTH_DATA_SECRET_LABEL: &[u8] = b"targeted message sender auth data secret";
+
+/// A message encrypted by one group member for exactly one other member of
+/// the group, as defined by the targeted messages extension of the
+/// MLS extensions draft.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub(crate) struct TargetedMessage {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub group_id: Vec<u8>,
+    pub epoch: u64,
+    pub recipient_leaf_index: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub authenticated_data: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub encrypted_sender_auth_data: Vec<u8>,
+    pub hpke_ciphertext: HpkeCiphertext,
+}
+
+impl Debug for TargetedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TargetedMessage")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("recipient_leaf_index", &self.recipient_leaf_index)
+            .field(
+                "authenticated_data",
+                &mls_rs_core::debug::pretty_bytes(&self.authenticated_data),
+            )
+            .field(
+                "encrypted_sender_auth_data",
+                &mls_rs_core::debug::pretty_bytes(&self.encrypted_sender_auth_data),
+            )
+            .field("hpke_ciphertext", &self.hpke_ciphertext)
+            .finish()
+    }
+}
+
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Clone, PartialEq, Eq)]
+/// Description of a targeted message that was decrypted by its recipient.
+pub struct TargetedMessageDescription {
+    /// Index of the member that sent the message.
+    pub sender_index: u32,
+    /// Received application data.
+    pub(crate) data: Zeroizing<Vec<u8>>,
+    /// Plaintext authenticated data in the received MLS packet.
+    pub authenticated_data: Vec<u8>,
+}
+
+impl Debug for TargetedMessageDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TargetedMessageDescription")
+            .field("sender_index", &self.sender_index)
+            .field("data", &mls_rs_core::debug::pretty_bytes(&self.data))
+            .field(
+                "authenticated_data",
+                &mls_rs_core::debug::pretty_bytes(&self.authenticated_data),
+            )
+            .finish()
+    }
+}
+
+#[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen)]
+impl TargetedMessageDescription {
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct TargetedMessageSenderAuthData {
+    sender_leaf_index: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+/// AEAD key and nonce protecting the sender authentication data.
+type SenderAuthDataKey = (Zeroizing<Vec<u8>>, Zeroizing<Vec<u8>>);
+
+#[derive(MlsSize, MlsEncode)]
+struct TargetedMessageSenderAuthDataAAD<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    recipient_leaf_index: u32,
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct TargetedMessageContentContext<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    recipient_leaf_index: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    authenticated_data: &'a [u8],
+}
+
+struct TargetedMessageContent(Zeroizing<Vec<u8>>);
+
+impl HpkeEncryptable for TargetedMessageContent {
+    const ENCRYPT_LABEL: &'static str = "TargetedMessageContent";
+
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, MlsError> {
+        Ok(Self(Zeroizing::new(bytes)))
+    }
+
+    fn get_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        Ok(self.0.to_vec())
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct TargetedMessageTBS<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    recipient_leaf_index: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    authenticated_data: &'a [u8],
+    sender_leaf_index: u32,
+    hpke_ciphertext: &'a HpkeCiphertext,
+    group_context: &'a GroupContext,
+}
+
+struct SignedTargetedMessage<'a> {
+    tbs: TargetedMessageTBS<'a>,
+    signature: Vec<u8>,
+}
+
+impl<'a> Signable<'a> for SignedTargetedMessage<'a> {
+    const SIGN_LABEL: &'static str = "TargetedMessageTBS";
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        self.tbs.mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Encrypt `message` so that it can only be decrypted by the member at
+    /// leaf index `recipient`.
+    ///
+    /// The message is encrypted to the HPKE key of the recipient's leaf and
+    /// signed by this member. It is bound to the current epoch and can only
+    /// be processed by the recipient while it is in the same epoch.
+    ///
+    /// The recipient must advertise
+    /// [`ExtensionType::TARGETED_MESSAGES_CAPABILITY`] in its capabilities.
+    ///
+    /// # Warning
+    ///
+    /// `authenticated_data` is sent unencrypted.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn encrypt_targeted_message(
+        &self,
+        recipient: u32,
+        message: &[u8],
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        let recipient_index = LeafIndex::try_from(recipient)?;
+
+        if recipient_index == self.private_tree.self_index {
+            return Err(MlsError::InvalidTargetedMessageRecipient);
+        }
+
+        let recipient_leaf = self.state.public_tree.get_leaf_node(recipient_index)?;
+
+        if !recipient_leaf
+            .capabilities
+            .extensions
+            .contains(&ExtensionType::TARGETED_MESSAGES_CAPABILITY)
+        {
+            return Err(MlsError::ExtensionNotInCapabilities(
+                ExtensionType::TARGETED_MESSAGES_CAPABILITY,
+            ));
+        }
+
+        let context = self.context();
+
+        let content_context = TargetedMessageContentContext {
+            group_id: &context.group_id,
+            epoch: context.epoch,
+            recipient_leaf_index: recipient,
+            authenticated_data: &authenticated_data,
+        }
+        .mls_encode_to_vec()?;
+
+        let hpke_ciphertext = TargetedMessageContent(Zeroizing::new(message.to_vec()))
+            .encrypt(
+                &self.cipher_suite_provider,
+                &recipient_leaf.public_key,
+                &content_context,
+            )
+            .await?;
+
+        let sender_leaf_index = *self.private_tree.self_index;
+
+        let mut signed = SignedTargetedMessage {
+            tbs: TargetedMessageTBS {
+                group_id: &context.group_id,
+                epoch: context.epoch,
+                recipient_leaf_index: recipient,
+                authenticated_data: &authenticated_data,
+                sender_leaf_index,
+                hpke_ciphertext: &hpke_ciphertext,
+                group_context: context,
+            },
+            signature: Vec::new(),
+        };
+
+        signed
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        let sender_auth_data = TargetedMessageSenderAuthData {
+            sender_leaf_index,
+            signature: signed.signature,
+        }
+        .mls_encode_to_vec()
+        .map(Zeroizing::new)?;
+
+        let (key, nonce) = self
+            .sender_auth_data_key(&hpke_ciphertext.kem_output)
+            .await?;
+
+        let aad = TargetedMessageSenderAuthDataAAD {
+            group_id: &context.group_id,
+            epoch: context.epoch,
+            recipient_leaf_index: recipient,
+        }
+        .mls_encode_to_vec()?;
+
+        let encrypted_sender_auth_data = self
+            .cipher_suite_provider
+            .aead_seal(&key, &sender_auth_data, Some(&aad), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let targeted = TargetedMessage {
+            group_id: context.group_id.clone(),
+            epoch: context.epoch,
+            recipient_leaf_index: recipient,
+            authenticated_data,
+            encrypted_sender_auth_data,
+            hpke_ciphertext,
+        };
+
+        Ok(MlsMessage::new(
+            self.protocol_version(),
+            MlsMessagePayload::Targeted(targeted),
+        ))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn decrypt_targeted_message(
+        &self,
+        message: &TargetedMessage,
+    ) -> Result<TargetedMessageDescription, MlsError> {
+        let context = self.context();
+
+        if message.group_id != context.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if message.epoch != context.epoch {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        if message.recipient_leaf_index != *self.private_tree.self_index {
+            return Err(MlsError::InvalidTargetedMessageRecipient);
+        }
+
+        let (key, nonce) = self
+            .sender_auth_data_key(&message.hpke_ciphertext.kem_output)
+            .await?;
+
+        let aad = TargetedMessageSenderAuthDataAAD {
+            group_id: &message.group_id,
+            epoch: message.epoch,
+            recipient_leaf_index: message.recipient_leaf_index,
+        }
+        .mls_encode_to_vec()?;
+
+        let sender_auth_data = self
+            .cipher_suite_provider
+            .aead_open(
+                &key,
+                &message.encrypted_sender_auth_data,
+                Some(&aad),
+                &nonce,
+            )
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let sender_auth_data = TargetedMessageSenderAuthData::mls_decode(&mut &**sender_auth_data)?;
+        let sender_index = LeafIndex::try_from(sender_auth_data.sender_leaf_index)?;
+
+        if sender_index == self.private_tree.self_index {
+            return Err(MlsError::CantProcessMessageFromSelf);
+        }
+
+        let sender_leaf = self.state.public_tree.get_leaf_node(sender_index)?;
+
+        let signed = SignedTargetedMessage {
+            tbs: TargetedMessageTBS {
+                group_id: &message.group_id,
+                epoch: message.epoch,
+                recipient_leaf_index: message.recipient_leaf_index,
+                authenticated_data: &message.authenticated_data,
+                sender_leaf_index: sender_auth_data.sender_leaf_index,
+                hpke_ciphertext: &message.hpke_ciphertext,
+                group_context: context,
+            },
+            signature: sender_auth_data.signature,
+        };
+
+        signed
+            .verify(
+                &self.cipher_suite_provider,
+                &sender_leaf.signing_identity.signature_key,
+                &(),
+            )
+            .await?;
+
+        let content_context = TargetedMessageContentContext {
+            group_id: &message.group_id,
+            epoch: message.epoch,
+            recipient_leaf_index: message.recipient_leaf_index,
+            authenticated_data: &message.authenticated_data,
+        }
+        .mls_encode_to_vec()?;
+
+        let self_private_key = self.private_tree.secret_keys[0]
+            .as_ref()
+            .ok_or(MlsError::InvalidTreeKemPrivateKey)?;
+
+        let content = TargetedMessageContent::decrypt(
+            &self.cipher_suite_provider,
+            self_private_key,
+            &self.current_user_leaf_node()?.public_key,
+            &content_context,
+            &message.hpke_ciphertext,
+        )
+        .await?;
+
+        Ok(TargetedMessageDescription {
+            sender_index: sender_auth_data.sender_leaf_index,
+            data: content.0,
+            authenticated_data: message.authenticated_data.clone(),
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn sender_auth_data_key(
+        &self,
+        kem_output: &[u8],
+    ) -> Result<SenderAuthDataKey, MlsError> {
+        let cs = &self.cipher_suite_provider;
+
+        let secret = self
+            .key_schedule
+            .export_secret(
+                SENDER_AUTH_DATA_SECRET_LABEL,
+                &[],
+                cs.kdf_extract_size(),
+                cs,
+            )
+            .await?;
+
+        let key = kdf_expand_with_label(cs, &secret, b"key", kem_output, Some(cs.aead_key_size()))
+            .await?;
+
+        let nonce = kdf_expand_with_label(
+            cs,
+            &secret,
+            b"nonce",
+            kem_output,
+            Some(cs.aead_nonce_size()),
+        )
+        .await?;
+
+        Ok((key, nonce))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::extension::ExtensionType;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{
+            framing::MlsMessagePayload,
+            test_utils::{test_group_custom, TestGroup},
+            ReceivedMessage,
+        },
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn targeted_test_groups(bob_supports_capability: bool) -> (TestGroup, TestGroup) {
+        let mut alice = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            vec![ExtensionType::TARGETED_MESSAGES_CAPABILITY],
+            None,
+            None,
+        )
+        .await;
+
+        let (bob, _) = alice
+            .join_with_custom_config("bob", true, |c| {
+                if bob_supports_capability {
+                    c.0.settings
+                        .extension_types
+                        .push(ExtensionType::TARGETED_MESSAGES_CAPABILITY)
+                }
+            })
+            .await
+            .unwrap();
+
+        (alice, bob)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn targeted_message_can_be_decrypted_by_recipient() {
+        let (mut alice, mut bob) = targeted_test_groups(true).await;
+
+        let message = alice
+            .encrypt_targeted_message(1, b"call key", b"aad".to_vec())
+            .await
+            .unwrap();
+
+        let received = bob.process_message(message).await.unwrap();
+
+        assert_matches!(
+            received,
+            ReceivedMessage::TargetedMessage(desc)
+                if desc.sender_index == 0
+                    && desc.data() == b"call key"
+                    && desc.authenticated_data == b"aad"
+        );
+
+        // Alice can receive a targeted message from Bob in the same way
+        let message = bob
+            .encrypt_targeted_message(0, b"reply", vec![])
+            .await
+            .unwrap();
+
+        let received = alice.process_message(message).await.unwrap();
+
+        assert_matches!(
+            received,
+            ReceivedMessage::TargetedMessage(desc) if desc.sender_index == 1 && desc.data() == b"reply"
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn targeted_message_requires_recipient_capability() {
+        let (alice, _) = targeted_test_groups(false).await;
+
+        let res = alice.encrypt_targeted_message(1, b"call key", vec![]).await;
+
+        assert_matches!(
+            res,
+            Err(MlsError::ExtensionNotInCapabilities(t))
+                if t == ExtensionType::TARGETED_MESSAGES_CAPABILITY
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn targeted_message_cannot_target_self() {
+        let (alice, _) = targeted_test_groups(true).await;
+
+        let res = alice.encrypt_targeted_message(0, b"call key", vec![]).await;
+
+        assert_matches!(res, Err(MlsError::InvalidTargetedMessageRecipient));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn targeted_message_rejected_by_other_member() {
+        let (mut alice, mut bob) = targeted_test_groups(true).await;
+
+        let (mut carol, commit) = alice
+            .join_with_custom_config("carol", true, |c| {
+                c.0.settings
+                    .extension_types
+                    .push(ExtensionType::TARGETED_MESSAGES_CAPABILITY)
+            })
+            .await
+            .unwrap();
+
+        bob.process_message(commit).await.unwrap();
+
+        let message = alice
+            .encrypt_targeted_message(1, b"call key", vec![])
+            .await
+            .unwrap();
+
+        let res = carol.process_message(message).await;
+
+        assert_matches!(res, Err(MlsError::InvalidTargetedMessageRecipient));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn targeted_message_with_modified_authenticated_data_is_rejected() {
+        let (alice, mut bob) = targeted_test_groups(true).await;
+
+        let mut message = alice
+            .encrypt_targeted_message(1, b"call key", b"aad".to_vec())
+            .await
+            .unwrap();
+
+        let MlsMessagePayload::Targeted(ref mut targeted) = message.payload else {
+            panic!("expected targeted message")
+        };
+
+        targeted.authenticated_data = b"other".to_vec();
+
+        let res = bob.process_message(message).await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+}
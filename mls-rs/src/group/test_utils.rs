@@ -513,6 +513,10 @@ impl MessageProcessor for GroupWithoutKeySchedule {
         self.inner.min_epoch_available()
     }
 
+    fn unknown_type_checker(&self) -> Result<UnknownTypeChecker, MlsError> {
+        self.inner.unknown_type_checker()
+    }
+
     async fn apply_update_path(
         &mut self,
         sender: LeafIndex,
@@ -533,6 +537,15 @@ impl MessageProcessor for GroupWithoutKeySchedule {
         self.inner.process_ciphertext(cipher_text).await
     }
 
+    #[cfg(feature = "targeted_messages")]
+    #[cfg_attr(coverage_nightly, coverage(off))]
+    async fn process_targeted_message(
+        &mut self,
+        message: &crate::group::targeted_message::TargetedMessage,
+    ) -> Result<Self::OutputType, MlsError> {
+        self.inner.process_targeted_message(message).await
+    }
+
     #[cfg_attr(coverage_nightly, coverage(off))]
     async fn verify_plaintext_authentication(
         &self,
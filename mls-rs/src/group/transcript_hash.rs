@@ -53,6 +53,34 @@ pub(crate) async fn create<P: CipherSuiteProvider>(
         .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
 }
 
+/// Confirmed and interim transcript hashes of an epoch.
+#[cfg(feature = "prior_epoch_transcript_hash")]
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TranscriptHashes {
+    /// Confirmed transcript hash included in the group context of the epoch.
+    pub confirmed_transcript_hash: Vec<u8>,
+    /// Interim transcript hash used to compute the confirmed transcript hash
+    /// of the next epoch.
+    pub interim_transcript_hash: Vec<u8>,
+}
+
+#[cfg(feature = "prior_epoch_transcript_hash")]
+impl Debug for TranscriptHashes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranscriptHashes")
+            .field(
+                "confirmed_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.confirmed_transcript_hash),
+            )
+            .field(
+                "interim_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.interim_transcript_hash),
+            )
+            .finish()
+    }
+}
+
 #[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct InterimTranscriptHash(
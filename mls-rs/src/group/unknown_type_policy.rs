@@ -0,0 +1,528 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    extension::{ExtensionList, ExtensionType},
+    group::ProposalType,
+};
+
+use crate::{
+    client::MlsError, extension::RequiredCapabilitiesExt, grease::is_grease_value,
+    key_package::KeyPackage, tree_kem::leaf_node::LeafNode,
+};
+
+#[cfg(any(all(test, feature = "custom_proposal"), feature = "by_ref_proposal"))]
+use super::proposal::Proposal;
+
+/// Action taken when an extension or proposal type that is not supported by
+/// this client is received.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum UnknownTypeAction {
+    /// Fail processing with [`MlsError::UnknownExtensionRejected`] or
+    /// [`MlsError::UnknownProposalTypeRejected`].
+    Reject,
+    /// Accept the message without reporting the unknown type.
+    #[default]
+    Ignore,
+    /// Accept the message and report the unknown type as an [`UnknownType`].
+    Report,
+}
+
+/// Structure in which an unknown extension was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum ExtensionContainer {
+    /// Extensions of a leaf node, either in the ratchet tree or in a proposal
+    /// or commit.
+    LeafNode = 1u8,
+    /// Extensions of a key package added to the group.
+    KeyPackage = 2u8,
+    /// Extensions of the group info used to join a group.
+    GroupInfo = 3u8,
+}
+
+/// Unknown extension reported by an [`UnknownTypePolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnknownExtension {
+    /// Structure in which the extension was found.
+    pub container: ExtensionContainer,
+    /// Type of the extension.
+    pub extension_type: ExtensionType,
+}
+
+/// Unknown type that was accepted and reported because of an
+/// [`UnknownTypePolicy`] using [`UnknownTypeAction::Report`], or an encoding
+/// irregularity reported because of
+/// [`UnknownTypePolicy::with_canonical_encoding_check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum UnknownType {
+    /// Extension of a type that is not supported by this client.
+    Extension(UnknownExtension) = 1u8,
+    /// Received proposal of a type that is not supported by this client.
+    Proposal(ProposalType) = 2u8,
+    /// Extension list that is not sorted by extension type, as done by
+    /// implementations with a non-deterministic extension order.
+    NonCanonicalExtensions(ExtensionContainer) = 3u8,
+}
+
+/// Handling of extension and proposal types that are not supported by this
+/// client, chosen separately for each [`ExtensionContainer`].
+///
+/// A type is unknown if it is not defined by the MLS RFC, is not a GREASE
+/// value and was not registered with
+/// [`ClientBuilder::extension_type`](crate::client_builder::ClientBuilder::extension_type)
+/// or
+/// [`ClientBuilder::custom_proposal_type`](crate::client_builder::ClientBuilder::custom_proposal_type).
+/// Types listed in the required capabilities of the group are handled by the
+/// protocol rules and never by this policy.
+///
+/// The default policy ignores all unknown types and does not check the
+/// extension order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct UnknownTypePolicy {
+    leaf_node: UnknownTypeAction,
+    key_package: UnknownTypeAction,
+    group_info: UnknownTypeAction,
+    proposal: UnknownTypeAction,
+    canonical_encoding_check: bool,
+}
+
+impl UnknownTypePolicy {
+    /// Create a policy that ignores all unknown types.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the action taken for unknown extensions found in `container`.
+    pub fn with_extension_action(
+        mut self,
+        container: ExtensionContainer,
+        action: UnknownTypeAction,
+    ) -> Self {
+        match container {
+            ExtensionContainer::LeafNode => self.leaf_node = action,
+            ExtensionContainer::KeyPackage => self.key_package = action,
+            ExtensionContainer::GroupInfo => self.group_info = action,
+        }
+
+        self
+    }
+
+    /// Set the action taken for received proposals of an unknown type.
+    pub fn with_proposal_action(self, action: UnknownTypeAction) -> Self {
+        Self {
+            proposal: action,
+            ..self
+        }
+    }
+
+    /// Report received extension lists that are not in the canonical order
+    /// used by this client as [`UnknownType::NonCanonicalExtensions`].
+    ///
+    /// Such lists are valid and are never rejected.
+    pub fn with_canonical_encoding_check(self, enabled: bool) -> Self {
+        Self {
+            canonical_encoding_check: enabled,
+            ..self
+        }
+    }
+
+    /// Action taken for unknown extensions found in `container`.
+    pub fn extension_action(&self, container: ExtensionContainer) -> UnknownTypeAction {
+        match container {
+            ExtensionContainer::LeafNode => self.leaf_node,
+            ExtensionContainer::KeyPackage => self.key_package,
+            ExtensionContainer::GroupInfo => self.group_info,
+        }
+    }
+
+    /// Action taken for received proposals of an unknown type.
+    pub fn proposal_action(&self) -> UnknownTypeAction {
+        self.proposal
+    }
+
+    /// Whether non-canonical extension lists are reported.
+    pub fn canonical_encoding_check(&self) -> bool {
+        self.canonical_encoding_check
+    }
+}
+
+/// Applies an [`UnknownTypePolicy`] to received structures and collects the
+/// reported types.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct UnknownTypeChecker {
+    policy: UnknownTypePolicy,
+    supported_extensions: Vec<ExtensionType>,
+    #[cfg_attr(not(feature = "custom_proposal"), allow(dead_code))]
+    supported_proposals: Vec<ProposalType>,
+    required_capabilities: RequiredCapabilitiesExt,
+    reports: Vec<UnknownType>,
+}
+
+impl UnknownTypeChecker {
+    pub(crate) fn new(
+        policy: UnknownTypePolicy,
+        supported_extensions: Vec<ExtensionType>,
+        supported_proposals: Vec<ProposalType>,
+        group_context_extensions: &ExtensionList,
+    ) -> Result<Self, MlsError> {
+        let required_capabilities = group_context_extensions
+            .get_as::<RequiredCapabilitiesExt>()?
+            .unwrap_or_default();
+
+        Ok(Self {
+            policy,
+            supported_extensions,
+            supported_proposals,
+            required_capabilities,
+            reports: Vec::new(),
+        })
+    }
+
+    pub(crate) fn check_extensions(
+        &mut self,
+        container: ExtensionContainer,
+        extensions: &ExtensionList,
+    ) -> Result<(), MlsError> {
+        if self.policy.canonical_encoding_check && !extensions.is_canonical() {
+            self.reports
+                .push(UnknownType::NonCanonicalExtensions(container));
+        }
+
+        let action = self.policy.extension_action(container);
+
+        if action == UnknownTypeAction::Ignore {
+            return Ok(());
+        }
+
+        let unknown = extensions
+            .iter()
+            .map(|ext| ext.extension_type)
+            .filter(|ext_type| {
+                !ext_type.is_default()
+                    && !is_grease_value(ext_type.raw_value())
+                    && !self.supported_extensions.contains(ext_type)
+                    && !self.required_capabilities.extensions.contains(ext_type)
+            })
+            .collect::<Vec<_>>();
+
+        for extension_type in unknown {
+            if action == UnknownTypeAction::Reject {
+                return Err(MlsError::UnknownExtensionRejected(extension_type));
+            }
+
+            self.reports.push(UnknownType::Extension(UnknownExtension {
+                container,
+                extension_type,
+            }));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn check_leaf_node(&mut self, leaf_node: &LeafNode) -> Result<(), MlsError> {
+        self.check_extensions(ExtensionContainer::LeafNode, &leaf_node.extensions)
+    }
+
+    pub(crate) fn check_key_package(&mut self, key_package: &KeyPackage) -> Result<(), MlsError> {
+        self.check_extensions(ExtensionContainer::KeyPackage, &key_package.extensions)?;
+        self.check_leaf_node(&key_package.leaf_node)
+    }
+
+    #[cfg(any(all(test, feature = "custom_proposal"), feature = "by_ref_proposal"))]
+    pub(crate) fn check_proposal(&mut self, proposal: &Proposal) -> Result<(), MlsError> {
+        match proposal {
+            Proposal::Add(add) => self.check_key_package(&add.key_package),
+            #[cfg(feature = "by_ref_proposal")]
+            Proposal::Update(update) => self.check_leaf_node(&update.leaf_node),
+            #[cfg(feature = "custom_proposal")]
+            Proposal::Custom(custom) => self.check_proposal_type(custom.proposal_type()),
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    fn check_proposal_type(&mut self, proposal_type: ProposalType) -> Result<(), MlsError> {
+        let unknown = !ProposalType::DEFAULT.contains(&proposal_type)
+            && !is_grease_value(proposal_type.raw_value())
+            && !self.supported_proposals.contains(&proposal_type)
+            && !self
+                .required_capabilities
+                .proposals
+                .contains(&proposal_type);
+
+        if !unknown {
+            return Ok(());
+        }
+
+        match self.policy.proposal_action() {
+            UnknownTypeAction::Reject => Err(MlsError::UnknownProposalTypeRejected(proposal_type)),
+            UnknownTypeAction::Ignore => Ok(()),
+            UnknownTypeAction::Report => {
+                self.reports.push(UnknownType::Proposal(proposal_type));
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn into_reports(self) -> Vec<UnknownType> {
+        self.reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_codec::{MlsDecode, MlsEncode};
+    use mls_rs_core::extension::{Extension, ExtensionList};
+
+    use crate::{
+        client::{
+            test_utils::{
+                test_client_with_key_pkg_custom, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+            },
+            MlsError,
+        },
+        client_builder::test_utils::TestClientConfig,
+        extension::RequiredCapabilitiesExt,
+        group::{
+            test_utils::{test_group_custom_config, TestGroup},
+            Group, ReceivedMessage,
+        },
+    };
+
+    use super::{
+        ExtensionContainer, UnknownExtension, UnknownType, UnknownTypeAction, UnknownTypeChecker,
+        UnknownTypePolicy,
+    };
+
+    fn extensions(types: &[u16]) -> ExtensionList {
+        types
+            .iter()
+            .map(|t| Extension::new((*t).into(), vec![]))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    fn checker(action: UnknownTypeAction) -> UnknownTypeChecker {
+        let policy = UnknownTypePolicy::new()
+            .with_extension_action(ExtensionContainer::GroupInfo, action)
+            .with_proposal_action(action);
+
+        let mut context_extensions = ExtensionList::new();
+
+        context_extensions
+            .set_from(RequiredCapabilitiesExt::new(
+                vec![66.into()],
+                vec![],
+                vec![],
+            ))
+            .unwrap();
+
+        UnknownTypeChecker::new(policy, vec![65.into()], vec![], &context_extensions).unwrap()
+    }
+
+    #[test]
+    fn known_required_and_grease_types_are_not_reported() {
+        let mut checker = checker(UnknownTypeAction::Reject);
+
+        checker
+            .check_extensions(
+                ExtensionContainer::GroupInfo,
+                &extensions(&[1, 65, 66, 0x0A0A]),
+            )
+            .unwrap();
+
+        assert!(checker.into_reports().is_empty());
+    }
+
+    #[test]
+    fn unknown_extensions_are_handled_by_container() {
+        let mut reporter = checker(UnknownTypeAction::Report);
+
+        reporter
+            .check_extensions(ExtensionContainer::GroupInfo, &extensions(&[67]))
+            .unwrap();
+
+        reporter
+            .check_extensions(ExtensionContainer::LeafNode, &extensions(&[68]))
+            .unwrap();
+
+        assert_eq!(
+            reporter.into_reports(),
+            vec![UnknownType::Extension(UnknownExtension {
+                container: ExtensionContainer::GroupInfo,
+                extension_type: 67.into(),
+            })]
+        );
+
+        let res = checker(UnknownTypeAction::Reject)
+            .check_extensions(ExtensionContainer::GroupInfo, &extensions(&[67]));
+
+        assert_matches!(res, Err(MlsError::UnknownExtensionRejected(t)) if t == 67.into());
+    }
+
+    #[test]
+    fn non_canonical_extensions_are_reported_when_checked() {
+        let encoded = vec![
+            Extension::new(65.into(), vec![]),
+            Extension::new(1.into(), vec![]),
+        ]
+        .mls_encode_to_vec()
+        .unwrap();
+
+        let non_canonical = ExtensionList::mls_decode(&mut &*encoded).unwrap();
+
+        let mut checker = checker(UnknownTypeAction::Ignore);
+
+        checker
+            .check_extensions(ExtensionContainer::LeafNode, &non_canonical)
+            .unwrap();
+
+        assert!(checker.into_reports().is_empty());
+
+        let policy = UnknownTypePolicy::new().with_canonical_encoding_check(true);
+        let mut checker =
+            UnknownTypeChecker::new(policy, vec![65.into()], vec![], &ExtensionList::new())
+                .unwrap();
+
+        checker
+            .check_extensions(ExtensionContainer::LeafNode, &extensions(&[65, 1]))
+            .unwrap();
+
+        checker
+            .check_extensions(ExtensionContainer::LeafNode, &non_canonical)
+            .unwrap();
+
+        assert_eq!(
+            checker.into_reports(),
+            vec![UnknownType::NonCanonicalExtensions(
+                ExtensionContainer::LeafNode
+            )]
+        );
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn group_with_unknown_leaf_extension(
+        policy: UnknownTypePolicy,
+    ) -> (TestGroup, Group<TestClientConfig>) {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |c| {
+            c.unknown_type_policy(policy)
+        })
+        .await;
+
+        let (bob, key_package) = test_client_with_key_pkg_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            "bob",
+            Default::default(),
+            extensions(&[99]),
+            |c| c.0.settings.extension_types.push(99.into()),
+        )
+        .await;
+
+        let output = alice
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let (bob, _) = bob
+            .join_group(output.ratchet_tree, &output.welcome_messages[0], None)
+            .await
+            .unwrap();
+
+        (alice, bob)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unknown_leaf_extensions_in_commits_are_reported() {
+        let policy = UnknownTypePolicy::new()
+            .with_extension_action(ExtensionContainer::LeafNode, UnknownTypeAction::Report);
+
+        let (mut alice, mut bob) = group_with_unknown_leaf_extension(policy).await;
+
+        let commit = bob.commit(Vec::new()).await.unwrap().commit_message;
+
+        let ReceivedMessage::Commit(desc) = alice.process_message(commit).await.unwrap() else {
+            panic!("expected commit");
+        };
+
+        assert_eq!(
+            desc.unknown_types,
+            vec![UnknownType::Extension(UnknownExtension {
+                container: ExtensionContainer::LeafNode,
+                extension_type: 99.into(),
+            })]
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn joining_with_rejected_unknown_leaf_extension_fails() {
+        let (mut alice, _) = group_with_unknown_leaf_extension(UnknownTypePolicy::new()).await;
+
+        let policy = UnknownTypePolicy::new()
+            .with_extension_action(ExtensionContainer::LeafNode, UnknownTypeAction::Reject);
+
+        let (carol, key_package) = test_client_with_key_pkg_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            "carol",
+            Default::default(),
+            Default::default(),
+            |c| c.0.settings.unknown_type_policy = policy,
+        )
+        .await;
+
+        let output = alice
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let res = carol
+            .join_group(output.ratchet_tree, &output.welcome_messages[0], None)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::UnknownExtensionRejected(t)) if t == 99.into());
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[test]
+    fn unknown_proposal_types_are_handled() {
+        use crate::group::proposal::{CustomProposal, Proposal};
+        use mls_rs_core::group::ProposalType;
+
+        let proposal = Proposal::Custom(CustomProposal::new(ProposalType::new(42), vec![]));
+
+        let mut reporter = checker(UnknownTypeAction::Report);
+        reporter.check_proposal(&proposal).unwrap();
+
+        assert_eq!(
+            reporter.into_reports(),
+            vec![UnknownType::Proposal(ProposalType::new(42))]
+        );
+
+        let res = checker(UnknownTypeAction::Reject).check_proposal(&proposal);
+
+        assert_matches!(res, Err(MlsError::UnknownProposalTypeRejected(_)));
+    }
+}
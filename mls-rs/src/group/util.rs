@@ -11,12 +11,17 @@ use mls_rs_core::{
 use crate::{
     cipher_suite::CipherSuite,
     client::MlsError,
-    extension::RatchetTreeExt,
+    extension::{ExtensionType, RatchetTreeExt},
     key_package::KeyPackageGeneration,
     protocol_version::ProtocolVersion,
     signer::Signable,
     time::MlsTime,
-    tree_kem::{node::LeafIndex, tree_validator::TreeValidator, TreeKemPublic},
+    tree_kem::{
+        leaf_node::LeafNode,
+        node::{LeafIndex, Node, NodeIndex, NodeVec},
+        tree_validator::TreeValidator,
+        TreeKemPublic,
+    },
     CipherSuiteProvider, CryptoProvider,
 };
 
@@ -31,6 +36,15 @@ use super::{
 
 use super::message_processor::ProvisionalState;
 
+#[cfg(feature = "private_message")]
+use crate::{client_config::ClientConfig, group::mls_rules::MlsRules};
+
+use alloc::vec::Vec;
+use mls_rs_codec::MlsDecode;
+
+#[cfg(feature = "private_message")]
+use mls_rs_core::group::GroupContext;
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 pub(crate) async fn validate_group_info_common<C: CipherSuiteProvider>(
     msg_version: ProtocolVersion,
@@ -105,15 +119,140 @@ pub(crate) async fn validate_tree_joiner<C: CipherSuiteProvider, I: IdentityProv
     cs: &C,
     maybe_time: Option<MlsTime>,
 ) -> Result<TreeKemPublic, MlsError> {
-    let tree = match group_info.extensions.get_as::<RatchetTreeExt>()? {
-        Some(ext) => ext.tree_data,
-        None => tree.ok_or(MlsError::RatchetTreeNotFound)?,
+    let nodes = joiner_tree_nodes(group_info, tree)?;
+
+    import_tree_joiner(nodes, group_info, id_provider, cs, maybe_time).await
+}
+
+/// Validate the ratchet tree and group info of a Welcome message.
+///
+/// Unlike [`validate_tree_and_info_joiner`], the group info is verified
+/// before the tree is decoded, using the leaf of the signer decoded on its
+/// own from the ratchet tree extension. The extension is then taken out of
+/// `group_info` and its nodes are decoded one at a time, so that at most the
+/// encoded and the decoded tree exist at once. The encoded tree is put back
+/// into `group_info` only if `keep_tree_extension` is set, otherwise it is
+/// released before the tree is indexed and validated.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn validate_tree_and_info_welcome<C: CipherSuiteProvider, I: IdentityProvider>(
+    msg_version: ProtocolVersion,
+    group_info: &mut GroupInfo,
+    tree: Option<ExportedTree<'_>>,
+    keep_tree_extension: bool,
+    id_provider: &I,
+    cs: &C,
+    maybe_time: Option<MlsTime>,
+) -> Result<TreeKemPublic, MlsError> {
+    let tree_extension = group_info
+        .extensions
+        .iter()
+        .find(|ext| ext.extension_type == ExtensionType::RATCHET_TREE);
+
+    let Some(tree_extension) = tree_extension else {
+        let nodes: NodeVec = tree.ok_or(MlsError::RatchetTreeNotFound)?.into();
+        let signer = &nodes.borrow_as_leaf(group_info.signer)?.signing_identity;
+
+        validate_group_info_joiner(msg_version, group_info, signer, id_provider, cs).await?;
+
+        return import_tree_joiner(nodes, group_info, id_provider, cs, maybe_time).await;
     };
 
+    let signer = decode_tree_leaf(&tree_extension.extension_data, group_info.signer)?;
+
+    validate_group_info_joiner(
+        msg_version,
+        group_info,
+        &signer.signing_identity,
+        id_provider,
+        cs,
+    )
+    .await?;
+
+    let tree_extension = group_info
+        .extensions
+        .take(ExtensionType::RATCHET_TREE)
+        .ok_or(MlsError::RatchetTreeNotFound)?;
+
+    let nodes = decode_tree_nodes(&tree_extension.extension_data)?;
+
+    if keep_tree_extension {
+        group_info.extensions.set(tree_extension);
+    } else {
+        drop(tree_extension);
+    }
+
+    import_tree_joiner(nodes, group_info, id_provider, cs, maybe_time).await
+}
+
+fn joiner_tree_nodes(
+    group_info: &GroupInfo,
+    tree: Option<ExportedTree<'_>>,
+) -> Result<NodeVec, MlsError> {
+    match group_info.extensions.get_as::<RatchetTreeExt>()? {
+        Some(ext) => Ok(ext.tree_data.into()),
+        None => Ok(tree.ok_or(MlsError::RatchetTreeNotFound)?.into()),
+    }
+}
+
+/// Decode the nodes of an encoded ratchet tree extension one at a time.
+fn decode_tree_nodes(mut data: &[u8]) -> Result<NodeVec, MlsError> {
+    let nodes = mls_rs_codec::iter::mls_decode_collection(&mut data, |items| {
+        let mut nodes = Vec::new();
+
+        while !items.is_empty() {
+            nodes.push(Option::<Node>::mls_decode(items)?);
+        }
+
+        Ok(nodes)
+    })?;
+
+    Ok(nodes.into())
+}
+
+/// Decode the leaf at `index` of an encoded ratchet tree extension without
+/// decoding the whole tree.
+fn decode_tree_leaf(mut data: &[u8], index: LeafIndex) -> Result<LeafNode, MlsError> {
+    let node_index = NodeIndex::from(index);
+
+    let (node, node_count) = mls_rs_codec::iter::mls_decode_collection(&mut data, |items| {
+        let mut node_count = 0;
+        let mut found = None;
+
+        while !items.is_empty() {
+            let node = Option::<Node>::mls_decode(items)?;
+
+            if node_count == node_index as usize {
+                found = node;
+            }
+
+            node_count += 1;
+        }
+
+        Ok((found, node_count))
+    })?;
+
+    // Same errors as looking up the leaf in the decoded tree
+    if node_index as usize >= node_count.next_power_of_two() {
+        return Err(MlsError::InvalidNodeIndex(node_index));
+    }
+
+    match node {
+        Some(Node::Leaf(leaf)) => Ok(leaf),
+        _ => Err(MlsError::ExpectedNode),
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn import_tree_joiner<C: CipherSuiteProvider, I: IdentityProvider>(
+    nodes: NodeVec,
+    group_info: &GroupInfo,
+    id_provider: &I,
+    cs: &C,
+    maybe_time: Option<MlsTime>,
+) -> Result<TreeKemPublic, MlsError> {
     let context = &group_info.group_context;
 
-    let mut tree =
-        TreeKemPublic::import_node_data(tree.into(), id_provider, &context.extensions).await?;
+    let mut tree = TreeKemPublic::import_node_data(nodes, id_provider, &context.extensions).await?;
 
     // Verify the integrity of the ratchet tree
     TreeValidator::new(cs, context, id_provider)
@@ -231,3 +370,17 @@ where
         .cipher_suite_provider(cipher_suite)
         .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))
 }
+
+#[cfg(feature = "private_message")]
+pub(crate) fn context_binding<C>(
+    config: &C,
+    context: &GroupContext,
+) -> Result<Option<Vec<u8>>, MlsError>
+where
+    C: ClientConfig,
+{
+    config
+        .mls_rules()
+        .context_binding(context)
+        .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))
+}
@@ -0,0 +1,153 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::{self, Debug};
+
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+use crate::{client_config::ClientConfig, group::Group, map::LargeMap};
+
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+
+/// Preparation state of a group, see [`Client::preload_groups`](crate::Client::preload_groups).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GroupReadiness {
+    /// The group is not preloaded. Loading it reads the state from storage.
+    Cold,
+    /// The group is being preloaded.
+    Loading,
+    /// The group is preloaded and the next call to
+    /// [`Client::load_group`](crate::Client::load_group) returns it without
+    /// accessing storage.
+    Ready,
+}
+
+/// Preloaded group as stored in the cache.
+///
+/// Clients do not require `C: ClientConfig`, so the cache cannot name
+/// `Group<C>` directly and stores groups behind this trait instead.
+trait CachedGroup<C>: Send {
+    fn into_group(self: Box<Self>) -> Group<C>
+    where
+        C: ClientConfig;
+}
+
+impl<C: ClientConfig> CachedGroup<C> for Group<C> {
+    fn into_group(self: Box<Self>) -> Group<C> {
+        *self
+    }
+}
+
+enum CacheEntry<C> {
+    Loading,
+    Ready(Box<dyn CachedGroup<C>>),
+}
+
+/// Groups preloaded by a client. Clones of a client share the same cache.
+pub(crate) struct GroupCache<C> {
+    inner: Arc<Mutex<LargeMap<Vec<u8>, CacheEntry<C>>>>,
+}
+
+impl<C> Clone for GroupCache<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C> Default for GroupCache<C> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<C> Debug for GroupCache<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupCache")
+            .field("len", &self.lock().len())
+            .finish()
+    }
+}
+
+impl<C> GroupCache<C> {
+    #[cfg(feature = "std")]
+    fn lock(&self) -> MutexGuard<'_, LargeMap<Vec<u8>, CacheEntry<C>>> {
+        self.inner.lock().unwrap()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn lock(&self) -> MutexGuard<'_, LargeMap<Vec<u8>, CacheEntry<C>>> {
+        self.inner.lock()
+    }
+
+    pub(crate) fn readiness(&self, group_id: &[u8]) -> GroupReadiness {
+        match self.lock().get(group_id) {
+            None => GroupReadiness::Cold,
+            Some(CacheEntry::Loading) => GroupReadiness::Loading,
+            Some(CacheEntry::Ready(_)) => GroupReadiness::Ready,
+        }
+    }
+
+    /// Mark the group as loading. Returns `false` if it is already loading
+    /// or ready.
+    pub(crate) fn start_loading(&self, group_id: &[u8]) -> bool {
+        let mut cache = self.lock();
+
+        if cache.contains_key(group_id) {
+            return false;
+        }
+
+        cache.insert(group_id.to_vec(), CacheEntry::Loading);
+
+        true
+    }
+
+    pub(crate) fn finish_loading(&self, group_id: &[u8], group: Option<Group<C>>)
+    where
+        C: ClientConfig + 'static,
+    {
+        let mut cache = self.lock();
+
+        match group {
+            Some(group) => cache.insert(group_id.to_vec(), CacheEntry::Ready(Box::new(group))),
+            None => cache.remove(group_id),
+        };
+    }
+
+    /// Remove a ready group from the cache. Groups still loading are left in
+    /// place.
+    pub(crate) fn take(&self, group_id: &[u8]) -> Option<Group<C>>
+    where
+        C: ClientConfig,
+    {
+        let mut cache = self.lock();
+
+        if !matches!(cache.get(group_id), Some(CacheEntry::Ready(_))) {
+            return None;
+        }
+
+        match cache.remove(group_id) {
+            Some(CacheEntry::Ready(group)) => Some(group.into_group()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut cache = self.lock();
+        cache.retain(|_, entry| matches!(entry, CacheEntry::Loading));
+    }
+}
@@ -11,10 +11,20 @@ pub mod x509 {
     pub use mls_rs_identity_x509::*;
 }
 
+mod credential_issuer;
+
+pub use credential_issuer::CredentialIssuer;
+pub(crate) use credential_issuer::SharedCredentialIssuer;
+
 pub use mls_rs_core::identity::{
     Credential, CredentialType, CustomCredential, MlsCredential, SigningIdentity,
 };
 
+#[cfg(feature = "key_transparency")]
+pub use mls_rs_core::identity::{
+    InclusionProof, KeyTransparencyError, KeyTransparencyIdentityProvider, KeyTransparencyLog,
+};
+
 #[cfg(test)]
 pub(crate) mod test_utils {
     #[cfg(feature = "std")]
@@ -181,3 +191,245 @@ pub(crate) mod test_utils {
         BasicCredential::new(identity).into_credential()
     }
 }
+
+#[cfg(all(test, feature = "key_transparency", feature = "std"))]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::{
+        crypto::CipherSuiteProvider, error::IntoAnyError, group::GroupContext,
+        identity::SigningIdentity, time::MlsTime,
+    };
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        client_builder::{BaseConfig, ClientBuilder, WithCryptoProvider, WithIdentityProvider},
+        crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
+        identity::basic::BasicIdentityProvider,
+        Client,
+    };
+
+    use super::{
+        test_utils::get_test_signing_identity, InclusionProof, KeyTransparencyIdentityProvider,
+        KeyTransparencyLog,
+    };
+
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(thiserror::Error))]
+    #[cfg_attr(feature = "std", error("signature key not found in log"))]
+    struct NotInLogError;
+
+    impl IntoAnyError for NotInLogError {
+        fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+            Ok(self.into())
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct TestLog {
+        key_packages: Arc<Mutex<Vec<SigningIdentity>>>,
+        leaf_nodes: Arc<Mutex<Vec<(SigningIdentity, u64)>>>,
+        excluded: Arc<Mutex<Vec<SigningIdentity>>>,
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl KeyTransparencyLog for TestLog {
+        type Error = NotInLogError;
+
+        async fn log_key_package(
+            &self,
+            signing_identity: &SigningIdentity,
+            _key_package: &[u8],
+        ) -> Result<(), Self::Error> {
+            self.key_packages
+                .lock()
+                .unwrap()
+                .push(signing_identity.clone());
+
+            Ok(())
+        }
+
+        async fn log_leaf_node(
+            &self,
+            signing_identity: &SigningIdentity,
+            _leaf_node: &[u8],
+            context: &GroupContext,
+        ) -> Result<(), Self::Error> {
+            self.leaf_nodes
+                .lock()
+                .unwrap()
+                .push((signing_identity.clone(), context.epoch));
+
+            Ok(())
+        }
+
+        async fn verify_member(
+            &self,
+            signing_identity: &SigningIdentity,
+            _timestamp: Option<MlsTime>,
+        ) -> Result<(), Self::Error> {
+            if self.excluded.lock().unwrap().contains(signing_identity) {
+                Err(NotInLogError)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    type KeyTransparencyConfig = WithIdentityProvider<
+        KeyTransparencyIdentityProvider<BasicIdentityProvider, TestLog>,
+        WithCryptoProvider<TestCryptoProvider, BaseConfig>,
+    >;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_client(
+        name: &str,
+        log: TestLog,
+    ) -> (Client<KeyTransparencyConfig>, SigningIdentity) {
+        let (identity, secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, name.as_bytes()).await;
+
+        let client = ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(KeyTransparencyIdentityProvider::new(
+                BasicIdentityProvider::new(),
+                log,
+            ))
+            .signing_identity(identity.clone(), secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        (client, identity)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn published_key_packages_are_logged() {
+        let log = TestLog::default();
+        let (bob, bob_identity) = test_client("bob", log.clone()).await;
+
+        bob.generate_key_package_message(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(*log.key_packages.lock().unwrap(), vec![bob_identity]);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn leaf_changes_are_logged_on_commit() {
+        let alice_log = TestLog::default();
+        let (alice, alice_identity) = test_client("alice", alice_log.clone()).await;
+        let (bob, bob_identity) = test_client("bob", TestLog::default()).await;
+
+        let mut group = alice
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let key_package = bob
+            .generate_key_package_message(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        group.apply_pending_commit().await.unwrap();
+
+        // Adding bob does not update the leaf of alice
+        let logged = alice_log.leaf_nodes.lock().unwrap().clone();
+        assert_eq!(logged, vec![(bob_identity, 1)]);
+
+        // An empty commit includes a path that updates the leaf of alice
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+
+        let logged = alice_log.leaf_nodes.lock().unwrap().clone();
+        assert_eq!(logged[1..], [(alice_identity, 2)]);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn member_missing_from_log_is_rejected() {
+        let alice_log = TestLog::default();
+        let (alice, _) = test_client("alice", alice_log.clone()).await;
+        let (bob, bob_identity) = test_client("bob", TestLog::default()).await;
+
+        alice_log.excluded.lock().unwrap().push(bob_identity);
+
+        let mut group = alice
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let key_package = bob
+            .generate_key_package_message(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        let res = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::IdentityProviderError(_)));
+        assert!(alice_log.leaf_nodes.lock().unwrap().is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn inclusion_proofs_verify_against_root() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let entries: [&[u8]; 3] = [b"entry 0", b"entry 1", b"entry 2"];
+
+        let mut leaves = Vec::new();
+
+        for entry in entries {
+            leaves.push(InclusionProof::leaf_hash(&cs, entry).await.unwrap());
+        }
+
+        let node = cs
+            .hash(&[&[1u8][..], &leaves[0], &leaves[1]].concat())
+            .await
+            .unwrap();
+
+        let root = cs
+            .hash(&[&[1u8][..], &node, &leaves[2]].concat())
+            .await
+            .unwrap();
+
+        let audit_paths = [
+            vec![leaves[1].clone(), leaves[2].clone()],
+            vec![leaves[0].clone(), leaves[2].clone()],
+            vec![node],
+        ];
+
+        for (i, audit_path) in audit_paths.into_iter().enumerate() {
+            let proof = InclusionProof {
+                leaf_index: i as u64,
+                tree_size: 3,
+                audit_path,
+            };
+
+            let valid = proof.verify(&cs, entries[i], &root).await.unwrap();
+            assert!(valid);
+
+            let valid = proof.verify(&cs, b"other entry", &root).await.unwrap();
+            assert!(!valid);
+
+            let proof = InclusionProof {
+                tree_size: 2,
+                ..proof
+            };
+
+            let valid = proof.verify(&cs, entries[i], &root).await.unwrap();
+            assert!(!valid);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::sync::Arc;
+use core::fmt::{self, Debug};
+
+use mls_rs_core::{
+    crypto::{CipherSuite, SignaturePublicKey},
+    error::AnyError,
+    identity::SigningIdentity,
+};
+
+/// Issuer of credentials for freshly generated signature keys.
+///
+/// When configured with
+/// [`ClientBuilder::per_group_signature_keys`](crate::client_builder::ClientBuilder::per_group_signature_keys),
+/// the client generates a new signature key pair for every group it creates
+/// and every key package it generates, and asks the issuer to certify the
+/// public key. The returned signing identity must contain `signature_key`.
+pub trait CredentialIssuer: Send + Sync {
+    fn issue_credential(
+        &self,
+        signature_key: &SignaturePublicKey,
+        cipher_suite: CipherSuite,
+    ) -> Result<SigningIdentity, AnyError>;
+}
+
+impl<F> CredentialIssuer for F
+where
+    F: Fn(&SignaturePublicKey, CipherSuite) -> Result<SigningIdentity, AnyError> + Send + Sync,
+{
+    fn issue_credential(
+        &self,
+        signature_key: &SignaturePublicKey,
+        cipher_suite: CipherSuite,
+    ) -> Result<SigningIdentity, AnyError> {
+        self(signature_key, cipher_suite)
+    }
+}
+
+impl Debug for dyn CredentialIssuer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CredentialIssuer")
+    }
+}
+
+pub(crate) type SharedCredentialIssuer = Arc<dyn CredentialIssuer>;
@@ -9,7 +9,7 @@ use mls_rs_core::{error::IntoAnyError, key_package::KeyPackageData};
 
 use crate::client::MlsError;
 use crate::{
-    crypto::{HpkeSecretKey, SignatureSecretKey},
+    crypto::{HpkePublicKey, HpkeSecretKey, SignatureSecretKey},
     group::framing::MlsMessagePayload,
     identity::SigningIdentity,
     protocol_version::ProtocolVersion,
@@ -98,6 +98,60 @@ where
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
+        self.package(
+            init_secret_key,
+            public_init,
+            lifetime,
+            capabilities,
+            key_package_extensions,
+            leaf_node_extensions,
+        )
+        .await
+    }
+
+    /// Generate a key package with the HPKE init key pair of `generation`
+    /// and a new leaf node. The protocol version, cipher suite and signing
+    /// identity of `generation` must be those of this generator.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn repackage(
+        &self,
+        generation: KeyPackageGeneration,
+        lifetime: Lifetime,
+        capabilities: Capabilities,
+        key_package_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+    ) -> Result<KeyPackageGeneration, MlsError> {
+        let key_package = generation.key_package;
+
+        let reusable = key_package.version == self.protocol_version
+            && key_package.cipher_suite == self.cipher_suite_provider.cipher_suite()
+            && key_package.signing_identity() == self.signing_identity;
+
+        if !reusable {
+            return Err(MlsError::UnsafeKeyPackageRepackaging);
+        }
+
+        self.package(
+            generation.init_secret_key,
+            key_package.hpke_init_key,
+            lifetime,
+            capabilities,
+            key_package_extensions,
+            leaf_node_extensions,
+        )
+        .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn package(
+        &self,
+        init_secret_key: HpkeSecretKey,
+        public_init: HpkePublicKey,
+        lifetime: Lifetime,
+        capabilities: Capabilities,
+        key_package_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+    ) -> Result<KeyPackageGeneration, MlsError> {
         let properties = ConfigProperties {
             capabilities,
             extensions: leaf_node_extensions,
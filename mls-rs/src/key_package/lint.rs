@@ -0,0 +1,352 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::{
+    crypto::{CipherSuite, CryptoProvider},
+    extension::ExtensionType,
+    group::ProposalType,
+    identity::{CredentialType, IdentityProvider, MemberValidationContext},
+    protocol_version::ProtocolVersion,
+    time::MlsTime,
+};
+
+use crate::{
+    client::MlsError,
+    extension::RequiredCapabilitiesExt,
+    tree_kem::{
+        leaf_node::LeafNodeSource,
+        leaf_node_validator::{LeafNodeValidator, ValidationContext},
+    },
+};
+
+use super::{validate_key_package_properties, KeyPackage};
+
+/// Violation of a [`KeyPackageLint`] policy.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum KeyPackageViolation {
+    /// The protocol version is not in the allowed list.
+    ProtocolVersionNotAllowed(ProtocolVersion),
+    /// The cipher suite is not in the allowed list.
+    CipherSuiteNotAllowed(CipherSuite),
+    /// The cipher suite is not supported by the crypto provider. No
+    /// cryptographic checks are performed in this case.
+    UnsupportedCipherSuite(CipherSuite),
+    /// The leaf node of the key package is not a key package leaf node.
+    InvalidLeafNodeSource,
+    /// The key package is not yet valid at the time of the check.
+    NotYetValid { not_before: MlsTime },
+    /// The key package is expired at the time of the check.
+    Expired { not_after: MlsTime },
+    /// The lifetime of the key package is longer than allowed.
+    LifetimeTooLong {
+        not_before: MlsTime,
+        not_after: MlsTime,
+    },
+    /// An extension of the key package or of its leaf node is not allowed.
+    ExtensionNotAllowed(ExtensionType),
+    /// The capabilities of the leaf node do not include a required extension.
+    RequiredExtensionNotFound(ExtensionType),
+    /// The capabilities of the leaf node do not include a required proposal.
+    RequiredProposalNotFound(ProposalType),
+    /// The capabilities of the leaf node do not include a required credential.
+    RequiredCredentialNotFound(CredentialType),
+    /// The key package is invalid according to RFC 9420, e.g. its signature
+    /// is invalid or its credential is rejected by the identity provider.
+    Invalid(MlsError),
+}
+
+/// Standalone validator for key packages uploaded to a delivery service.
+///
+/// In addition to the checks performed when a key package is added to a
+/// group, a configurable policy is checked. All violations are reported
+/// instead of stopping at the first one.
+#[derive(Clone, Debug)]
+pub struct KeyPackageLint<P, I> {
+    crypto_provider: P,
+    identity_provider: I,
+    protocol_versions: Vec<ProtocolVersion>,
+    cipher_suites: Option<Vec<CipherSuite>>,
+    extensions: Option<Vec<ExtensionType>>,
+    required_capabilities: RequiredCapabilitiesExt,
+    max_lifetime: Option<u64>,
+}
+
+impl<P, I> KeyPackageLint<P, I>
+where
+    P: CryptoProvider,
+    I: IdentityProvider,
+{
+    /// Create a validator allowing any cipher suite supported by
+    /// `crypto_provider`, MLS 1.0 and any extension.
+    pub fn new(crypto_provider: P, identity_provider: I) -> Self {
+        Self {
+            crypto_provider,
+            identity_provider,
+            protocol_versions: alloc::vec![ProtocolVersion::MLS_10],
+            cipher_suites: None,
+            extensions: None,
+            required_capabilities: Default::default(),
+            max_lifetime: None,
+        }
+    }
+
+    /// Allow only the given protocol versions.
+    pub fn with_protocol_versions(self, protocol_versions: Vec<ProtocolVersion>) -> Self {
+        Self {
+            protocol_versions,
+            ..self
+        }
+    }
+
+    /// Allow only the given cipher suites.
+    pub fn with_cipher_suites(self, cipher_suites: Vec<CipherSuite>) -> Self {
+        Self {
+            cipher_suites: Some(cipher_suites),
+            ..self
+        }
+    }
+
+    /// Allow only the given extensions in the key package and its leaf node.
+    pub fn with_extensions(self, extensions: Vec<ExtensionType>) -> Self {
+        Self {
+            extensions: Some(extensions),
+            ..self
+        }
+    }
+
+    /// Require the leaf node capabilities to include the given extensions,
+    /// proposals and credentials.
+    pub fn with_required_capabilities(
+        self,
+        required_capabilities: RequiredCapabilitiesExt,
+    ) -> Self {
+        Self {
+            required_capabilities,
+            ..self
+        }
+    }
+
+    /// Reject key packages with a lifetime longer than `seconds`.
+    pub fn with_max_lifetime(self, seconds: u64) -> Self {
+        Self {
+            max_lifetime: Some(seconds),
+            ..self
+        }
+    }
+
+    /// Check `key_package` against the policy, returning all violations.
+    ///
+    /// The lifetime is checked against `time`, or the current time if `time`
+    /// is `None` and the `std` feature is enabled.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn lint(
+        &self,
+        key_package: &KeyPackage,
+        time: Option<MlsTime>,
+    ) -> Vec<KeyPackageViolation> {
+        let mut violations = Vec::new();
+
+        if !self.protocol_versions.contains(&key_package.version) {
+            violations.push(KeyPackageViolation::ProtocolVersionNotAllowed(
+                key_package.version,
+            ));
+        }
+
+        if let Some(cipher_suites) = &self.cipher_suites {
+            if !cipher_suites.contains(&key_package.cipher_suite) {
+                violations.push(KeyPackageViolation::CipherSuiteNotAllowed(
+                    key_package.cipher_suite,
+                ));
+            }
+        }
+
+        self.lint_lifetime(key_package, time, &mut violations);
+        self.lint_extensions(key_package, &mut violations);
+        self.lint_capabilities(key_package, &mut violations);
+
+        let Some(cs) = self
+            .crypto_provider
+            .cipher_suite_provider(key_package.cipher_suite)
+        else {
+            violations.push(KeyPackageViolation::UnsupportedCipherSuite(
+                key_package.cipher_suite,
+            ));
+
+            return violations;
+        };
+
+        // The lifetime was checked above.
+        let res =
+            LeafNodeValidator::new(&cs, &self.identity_provider, MemberValidationContext::None)
+                .check_if_valid(&key_package.leaf_node, ValidationContext::Add(None))
+                .await;
+
+        if let Err(e) = res {
+            violations.push(KeyPackageViolation::Invalid(e));
+        }
+
+        let res = validate_key_package_properties(key_package, key_package.version, &cs).await;
+
+        if let Err(e) = res {
+            violations.push(KeyPackageViolation::Invalid(e));
+        }
+
+        violations
+    }
+
+    fn lint_lifetime(
+        &self,
+        key_package: &KeyPackage,
+        time: Option<MlsTime>,
+        violations: &mut Vec<KeyPackageViolation>,
+    ) {
+        let LeafNodeSource::KeyPackage(lifetime) = &key_package.leaf_node.leaf_node_source else {
+            violations.push(KeyPackageViolation::InvalidLeafNodeSource);
+            return;
+        };
+
+        #[cfg(feature = "std")]
+        let time = time.or_else(|| Some(MlsTime::now()));
+
+        if let Some(time) = time {
+            if time < lifetime.not_before {
+                violations.push(KeyPackageViolation::NotYetValid {
+                    not_before: lifetime.not_before,
+                });
+            }
+
+            if time > lifetime.not_after {
+                violations.push(KeyPackageViolation::Expired {
+                    not_after: lifetime.not_after,
+                });
+            }
+        }
+
+        let duration = lifetime
+            .not_after
+            .seconds_since_epoch()
+            .saturating_sub(lifetime.not_before.seconds_since_epoch());
+
+        if matches!(self.max_lifetime, Some(max) if duration > max) {
+            violations.push(KeyPackageViolation::LifetimeTooLong {
+                not_before: lifetime.not_before,
+                not_after: lifetime.not_after,
+            });
+        }
+    }
+
+    fn lint_extensions(&self, key_package: &KeyPackage, violations: &mut Vec<KeyPackageViolation>) {
+        let Some(allowed) = &self.extensions else {
+            return;
+        };
+
+        key_package
+            .extensions
+            .iter()
+            .chain(key_package.leaf_node.extensions.iter())
+            .map(|ext| ext.extension_type)
+            .filter(|ext_type| !allowed.contains(ext_type))
+            .for_each(|ext_type| {
+                violations.push(KeyPackageViolation::ExtensionNotAllowed(ext_type))
+            });
+    }
+
+    fn lint_capabilities(
+        &self,
+        key_package: &KeyPackage,
+        violations: &mut Vec<KeyPackageViolation>,
+    ) {
+        let capabilities = &key_package.leaf_node.capabilities;
+        let required = &self.required_capabilities;
+
+        required
+            .extensions
+            .iter()
+            .filter(|ext| !capabilities.extensions.contains(ext))
+            .for_each(|ext| violations.push(KeyPackageViolation::RequiredExtensionNotFound(*ext)));
+
+        required
+            .proposals
+            .iter()
+            .filter(|p| !capabilities.proposals.contains(p))
+            .for_each(|p| violations.push(KeyPackageViolation::RequiredProposalNotFound(*p)));
+
+        required
+            .credentials
+            .iter()
+            .filter(|c| !capabilities.credentials.contains(c))
+            .for_each(|c| violations.push(KeyPackageViolation::RequiredCredentialNotFound(*c)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use mls_rs_core::{extension::ExtensionType, time::MlsTime};
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        crypto::test_utils::TestCryptoProvider,
+        extension::RequiredCapabilitiesExt,
+        identity::basic::BasicIdentityProvider,
+        key_package::test_utils::test_key_package,
+        CipherSuite,
+    };
+
+    use super::{KeyPackageLint, KeyPackageViolation};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn valid_key_package_has_no_violations() {
+        let key_package = test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let lint = KeyPackageLint::new(TestCryptoProvider::new(), BasicIdentityProvider)
+            .with_cipher_suites(vec![TEST_CIPHER_SUITE]);
+
+        let violations = lint.lint(&key_package, None).await;
+
+        assert!(violations.is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn all_violations_are_reported() {
+        let mut key_package =
+            test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        key_package.signature[0] ^= 1;
+
+        let other_suite = if TEST_CIPHER_SUITE == CipherSuite::P256_AES128 {
+            CipherSuite::CURVE25519_AES128
+        } else {
+            CipherSuite::P256_AES128
+        };
+
+        let required = RequiredCapabilitiesExt {
+            extensions: vec![ExtensionType::new(0xff00)],
+            ..Default::default()
+        };
+
+        let lint = KeyPackageLint::new(TestCryptoProvider::new(), BasicIdentityProvider)
+            .with_cipher_suites(vec![other_suite])
+            .with_max_lifetime(86400)
+            .with_required_capabilities(required);
+
+        let violations = lint.lint(&key_package, Some(MlsTime::from(0))).await;
+
+        assert_matches!(
+            violations.as_slice(),
+            [
+                KeyPackageViolation::CipherSuiteNotAllowed(cs),
+                KeyPackageViolation::NotYetValid { .. },
+                KeyPackageViolation::LifetimeTooLong { .. },
+                KeyPackageViolation::RequiredExtensionNotFound(ext),
+                KeyPackageViolation::Invalid(_),
+            ] if *cs == TEST_CIPHER_SUITE && *ext == ExtensionType::new(0xff00)
+        );
+    }
+}
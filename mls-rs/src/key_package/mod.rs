@@ -4,7 +4,7 @@
 
 use crate::cipher_suite::CipherSuite;
 use crate::client::MlsError;
-use crate::crypto::HpkePublicKey;
+use crate::crypto::{Fingerprint, HpkePublicKey};
 use crate::hash_reference::HashReference;
 use crate::identity::SigningIdentity;
 use crate::protocol_version::ProtocolVersion;
@@ -20,9 +20,12 @@ use core::{
 use mls_rs_codec::MlsDecode;
 use mls_rs_codec::MlsEncode;
 use mls_rs_codec::MlsSize;
+use mls_rs_core::error::IntoAnyError;
 use mls_rs_core::extension::ExtensionList;
 
+mod lint;
 mod validator;
+pub use lint::{KeyPackageLint, KeyPackageViolation};
 pub(crate) use validator::*;
 
 pub(crate) mod generator;
@@ -131,6 +134,44 @@ impl KeyPackage {
         ))
     }
 
+    /// Fingerprint of the signature key of the leaf node, equal to the
+    /// fingerprint of the same member in the roster of a group using the
+    /// cipher suite of this key package.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn signature_key_fingerprint<CP: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &CP,
+    ) -> Result<Fingerprint, MlsError> {
+        if cipher_suite_provider.cipher_suite() != self.cipher_suite {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        self.leaf_node
+            .signing_identity
+            .fingerprint(cipher_suite_provider)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+
+    /// Fingerprint of the HPKE key of the leaf node.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn hpke_key_fingerprint<CP: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &CP,
+    ) -> Result<Fingerprint, MlsError> {
+        if cipher_suite_provider.cipher_suite() != self.cipher_suite {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        self.leaf_node
+            .public_key
+            .fingerprint(cipher_suite_provider)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+
     /// Time after which the key package is expired.
     pub fn expiration(&self) -> Result<MlsTime, MlsError> {
         if let LeafNodeSource::KeyPackage(lifetime) = &self.leaf_node.leaf_node_source {
@@ -317,6 +358,34 @@ mod tests {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_package_fingerprints_match_leaf_keys() {
+        let key_package = test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "test").await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let signature_key = key_package.signature_key_fingerprint(&cs).await.unwrap();
+        let hpke_key = key_package.hpke_key_fingerprint(&cs).await.unwrap();
+
+        let identity_key = key_package
+            .signing_identity()
+            .fingerprint(&cs)
+            .await
+            .unwrap();
+
+        let leaf_key = key_package
+            .leaf_node
+            .public_key
+            .fingerprint(&cs)
+            .await
+            .unwrap();
+
+        assert_eq!(signature_key, identity_key);
+        assert_eq!(hpke_key, leaf_key);
+
+        assert_eq!(signature_key.cipher_suite(), TEST_CIPHER_SUITE);
+        assert_ne!(signature_key, hpke_key);
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn key_package_ref_fails_invalid_cipher_suite() {
         let key_package = test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "test").await;
@@ -128,6 +128,14 @@ mod protocol_version {
 
 pub use protocol_version::ProtocolVersion;
 
+/// Adversarial decoding corpus and budget checks.
+#[cfg(feature = "adversarial")]
+#[cfg_attr(docsrs, doc(cfg(feature = "adversarial")))]
+pub mod adversarial;
+/// Commits applied to many groups of a client at once.
+#[cfg(feature = "bulk_commit")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bulk_commit")))]
+pub mod bulk_commit;
 pub mod client;
 pub mod client_builder;
 mod client_config;
@@ -143,24 +151,34 @@ pub mod external_client;
 mod grease;
 /// E2EE group created by a [`Client`].
 pub mod group;
+mod group_cache;
 mod hash_reference;
 /// Identity providers to use with [`ClientBuilder`](client_builder::ClientBuilder).
 pub mod identity;
 mod iter;
 mod key_package;
+/// Simulated group members for load testing of a delivery service.
+#[cfg(feature = "load_test")]
+#[cfg_attr(docsrs, doc(cfg(feature = "load_test")))]
+pub mod load_test;
 pub(crate) mod map;
 /// Pre-shared key support.
 pub mod psk;
+/// High level conversations that hide proposals and epochs.
+#[cfg(feature = "session")]
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+pub mod session;
 mod signer;
 /// Storage providers to use with
 /// [`ClientBuilder`](client_builder::ClientBuilder).
 pub mod storage_provider;
 
 pub use mls_rs_core::{
+    compression::Compressor,
     crypto::{CipherSuiteProvider, CryptoProvider},
-    group::GroupStateStorage,
+    group::{GroupStateStorage, ProposalCacheStorage},
     identity::IdentityProvider,
-    key_package::KeyPackageStorage,
+    key_package::{KeyPackageReservation, KeyPackageStorage},
     psk::PreSharedKeyStorage,
 };
 
@@ -175,6 +193,13 @@ pub mod mls_rules {
 
     #[cfg(feature = "by_ref_proposal")]
     pub use crate::group::proposal_ref::ProposalRef;
+
+    #[cfg(feature = "private_message")]
+    pub use crate::group::mls_rules::MessageAuthorization;
+
+    #[cfg(feature = "provider_api")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "provider_api")))]
+    pub use crate::group::provider_rules::ProviderRules;
 }
 
 pub use mls_rs_core::extension::{Extension, ExtensionList};
@@ -186,7 +211,7 @@ pub use crate::{
         mls_rules::MlsRules,
         Group,
     },
-    key_package::{KeyPackage, KeyPackageRef},
+    key_package::{KeyPackage, KeyPackageLint, KeyPackageRef, KeyPackageViolation},
 };
 
 /// Error types.
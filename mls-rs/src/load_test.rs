@@ -0,0 +1,522 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Load testing of a delivery service with simulated group members.
+//!
+//! A [`Simulator`] drives one group with many [`VirtualMember`]s according
+//! to a [`Scenario`] of joins, commits, application messages and removals,
+//! and reports [`LoadMetrics`] describing the traffic a delivery service
+//! would handle for it.
+//!
+//! Virtual members are not full clients. They all share the configuration
+//! of a template [`Client`], including its crypto provider, identity
+//! provider and storages, and only own their signature key and group state.
+//! Signature keys are never shared between members, so every message is
+//! signed and verified as it would be by real clients.
+//!
+//! ```ignore
+//! use mls_rs::load_test::{LoadMetrics, Scenario, Simulator};
+//!
+//! let mut simulator = Simulator::new(&client, cipher_suite)?;
+//!
+//! let scenario = Scenario::new()
+//!     .join(1000, 100)
+//!     .messages(500, 256)
+//!     .commit(10);
+//!
+//! let metrics = simulator.run(&scenario)?;
+//! println!("{}\n{}", LoadMetrics::csv_header(), metrics.to_csv_row());
+//! ```
+
+use alloc::{format, string::String, vec, vec::Vec};
+use std::time::{Duration, Instant};
+
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider, CryptoProvider},
+    error::IntoAnyError,
+    identity::{BasicCredential, SigningIdentity},
+    key_package::KeyPackageStorage,
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{CommitOutput, Group},
+    protocol_version::ProtocolVersion,
+    Client, MlsMessage,
+};
+
+/// A step of a [`Scenario`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Step {
+    /// Add `members` new members, `batch_size` per commit.
+    Join { members: usize, batch_size: usize },
+    /// Send `count` commits without proposals, each by the next member in
+    /// turn.
+    Commit { count: usize },
+    /// Send `count` application messages of `size` bytes, each by the next
+    /// member in turn.
+    Messages { count: usize, size: usize },
+    /// Remove the `members` most recently added members in one commit.
+    Remove { members: usize },
+}
+
+/// Ordered list of [`Step`]s run by a [`Simulator`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scenario {
+    steps: Vec<Step>,
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add `members` new members, `batch_size` per commit.
+    pub fn join(self, members: usize, batch_size: usize) -> Self {
+        self.step(Step::Join {
+            members,
+            batch_size: batch_size.max(1),
+        })
+    }
+
+    /// Send `count` commits without proposals.
+    pub fn commit(self, count: usize) -> Self {
+        self.step(Step::Commit { count })
+    }
+
+    /// Send `count` application messages of `size` bytes.
+    pub fn messages(self, count: usize, size: usize) -> Self {
+        self.step(Step::Messages { count, size })
+    }
+
+    /// Remove the `members` most recently added members.
+    pub fn remove(self, members: usize) -> Self {
+        self.step(Step::Remove { members })
+    }
+
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+/// Traffic and processing time measured by a [`Simulator`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LoadMetrics {
+    /// Number of members of the group.
+    pub members: usize,
+    pub key_packages: u64,
+    pub commits: u64,
+    pub welcomes: u64,
+    pub application_messages: u64,
+    /// Total size of the messages sent to the delivery service.
+    pub bytes_sent: u64,
+    /// Number of messages delivered to members, counting each recipient.
+    pub deliveries: u64,
+    /// Total size of the messages delivered to members, counting each
+    /// recipient.
+    pub bytes_delivered: u64,
+    pub largest_message: usize,
+    /// Time spent creating messages.
+    pub send_time: Duration,
+    /// Time spent processing delivered messages.
+    pub process_time: Duration,
+}
+
+impl LoadMetrics {
+    pub fn csv_header() -> &'static str {
+        "members,key_packages,commits,welcomes,application_messages,bytes_sent,deliveries,\
+         bytes_delivered,largest_message,send_time_us,process_time_us"
+    }
+
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            self.members,
+            self.key_packages,
+            self.commits,
+            self.welcomes,
+            self.application_messages,
+            self.bytes_sent,
+            self.deliveries,
+            self.bytes_delivered,
+            self.largest_message,
+            self.send_time.as_micros(),
+            self.process_time.as_micros()
+        )
+    }
+
+    fn sent(&mut self, size: usize, recipients: usize) {
+        self.bytes_sent += size as u64;
+        self.deliveries += recipients as u64;
+        self.bytes_delivered += (size * recipients) as u64;
+        self.largest_message = self.largest_message.max(size);
+    }
+}
+
+/// A simulated member of the group of a [`Simulator`].
+pub struct VirtualMember<C: ClientConfig> {
+    name: String,
+    group: Group<C>,
+}
+
+impl<C: ClientConfig + Clone> VirtualMember<C> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn group(&self) -> &Group<C> {
+        &self.group
+    }
+}
+
+/// Driver of a group of [`VirtualMember`]s.
+///
+/// The group is created by a first virtual member when the simulator is
+/// created. Every message sent by a member is processed by all other
+/// members, so the simulator also measures the processing cost on clients.
+pub struct Simulator<C: ClientConfig> {
+    config: C,
+    cipher_suite: CipherSuite,
+    version: ProtocolVersion,
+    members: Vec<VirtualMember<C>>,
+    created_members: usize,
+    next_sender: usize,
+    metrics: LoadMetrics,
+}
+
+impl<C> Simulator<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a simulator whose members share the configuration of
+    /// `template`.
+    ///
+    /// Members use basic credentials, which must be accepted by the identity
+    /// provider of `template`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn new(template: &Client<C>, cipher_suite: CipherSuite) -> Result<Self, MlsError> {
+        let mut simulator = Self {
+            config: template.config.clone(),
+            cipher_suite,
+            version: template.version,
+            members: Vec::new(),
+            created_members: 0,
+            next_sender: 0,
+            metrics: LoadMetrics::default(),
+        };
+
+        let (name, client) = simulator.virtual_client().await?;
+
+        let group = client
+            .create_group(Default::default(), Default::default(), None)
+            .await?;
+
+        simulator.members.push(VirtualMember { name, group });
+        simulator.metrics.members = 1;
+
+        Ok(simulator)
+    }
+
+    pub fn members(&self) -> &[VirtualMember<C>] {
+        &self.members
+    }
+
+    pub fn metrics(&self) -> &LoadMetrics {
+        &self.metrics
+    }
+
+    /// Run all steps of `scenario` and return the metrics accumulated since
+    /// the simulator was created.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn run(&mut self, scenario: &Scenario) -> Result<LoadMetrics, MlsError> {
+        for step in scenario.steps() {
+            match *step {
+                Step::Join {
+                    members,
+                    batch_size,
+                } => self.join(members, batch_size).await?,
+                Step::Commit { count } => self.commit(count).await?,
+                Step::Messages { count, size } => self.send_messages(count, size).await?,
+                Step::Remove { members } => self.remove(members).await?,
+            }
+        }
+
+        Ok(self.metrics.clone())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn virtual_client(&mut self) -> Result<(String, Client<C>), MlsError> {
+        let name = format!("member-{}", self.created_members);
+        self.created_members += 1;
+
+        let (secret_key, public_key) = self
+            .cipher_suite_provider()?
+            .signature_key_generate()
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let credential = BasicCredential::new(name.as_bytes().to_vec()).into_credential();
+        let signing_identity = SigningIdentity::new(credential, public_key);
+
+        let client = Client::new(
+            self.config.clone(),
+            Some(secret_key),
+            Some((signing_identity, self.cipher_suite)),
+            self.version,
+        );
+
+        Ok((name, client))
+    }
+
+    fn cipher_suite_provider(
+        &self,
+    ) -> Result<<C::CryptoProvider as CryptoProvider>::CipherSuiteProvider, MlsError> {
+        self.config
+            .crypto_provider()
+            .cipher_suite_provider(self.cipher_suite)
+            .ok_or(MlsError::UnsupportedCipherSuite(self.cipher_suite))
+    }
+
+    fn next_sender(&mut self) -> usize {
+        let sender = self.next_sender % self.members.len();
+        self.next_sender = self.next_sender.wrapping_add(1);
+        sender
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn join(&mut self, members: usize, batch_size: usize) -> Result<(), MlsError> {
+        let cs = self.cipher_suite_provider()?;
+        let mut remaining = members;
+
+        while remaining > 0 {
+            let batch = remaining.min(batch_size);
+            remaining -= batch;
+
+            let mut joiners = Vec::with_capacity(batch);
+            let mut key_packages = Vec::with_capacity(batch);
+
+            for _ in 0..batch {
+                let (name, client) = self.virtual_client().await?;
+
+                let key_package = client
+                    .generate_key_package_message(Default::default(), Default::default(), None)
+                    .await?;
+
+                let key_package_ref = key_package
+                    .key_package_reference(&cs)
+                    .await?
+                    .ok_or(MlsError::UnexpectedMessageType)?;
+
+                self.metrics.key_packages += 1;
+                self.metrics.sent(key_package.to_bytes()?.len(), 1);
+
+                key_packages.push(key_package);
+                joiners.push((name, client, key_package_ref));
+            }
+
+            let sender = self.next_sender();
+            let start = Instant::now();
+
+            let commit = key_packages
+                .into_iter()
+                .try_fold(
+                    self.members[sender].group.commit_builder(),
+                    |builder, key_package| builder.add_member(key_package),
+                )?
+                .build()
+                .await?;
+
+            self.members[sender].group.apply_pending_commit().await?;
+            self.metrics.send_time += start.elapsed();
+
+            let tree = self.members[sender].group.export_tree().into_owned();
+            self.deliver_commit(sender, &commit).await?;
+
+            for welcome in commit.welcome_messages.iter() {
+                self.metrics.welcomes += 1;
+                self.metrics.sent(welcome.to_bytes()?.len(), joiners.len());
+            }
+
+            for (name, client, key_package_ref) in joiners {
+                let start = Instant::now();
+
+                let welcome = commit
+                    .welcome_messages
+                    .first()
+                    .ok_or(MlsError::WelcomeKeyPackageNotFound)?;
+
+                let (group, _) = client.join_group(Some(tree.clone()), welcome, None).await?;
+
+                self.metrics.process_time += start.elapsed();
+                self.members.push(VirtualMember { name, group });
+
+                // The key package storage is shared by all members, so the
+                // used key package is deleted for the next joiner to find
+                // its own.
+                self.config
+                    .key_package_repo()
+                    .delete(&key_package_ref)
+                    .await
+                    .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
+            }
+        }
+
+        self.metrics.members = self.members.len();
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn commit(&mut self, count: usize) -> Result<(), MlsError> {
+        for _ in 0..count {
+            let sender = self.next_sender();
+            let start = Instant::now();
+
+            let commit = self.members[sender].group.commit(vec![]).await?;
+            self.members[sender].group.apply_pending_commit().await?;
+
+            self.metrics.send_time += start.elapsed();
+            self.deliver_commit(sender, &commit).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn send_messages(&mut self, count: usize, size: usize) -> Result<(), MlsError> {
+        let payload = vec![0u8; size];
+
+        for _ in 0..count {
+            let sender = self.next_sender();
+            let start = Instant::now();
+
+            let message = self.members[sender]
+                .group
+                .encrypt_application_message(&payload, vec![])
+                .await?;
+
+            self.metrics.send_time += start.elapsed();
+            self.metrics.application_messages += 1;
+            self.deliver(sender, &message).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn remove(&mut self, members: usize) -> Result<(), MlsError> {
+        // The first member is kept so that the group is never empty.
+        let members = members.min(self.members.len() - 1);
+
+        if members == 0 {
+            return Ok(());
+        }
+
+        let removed = self.members.split_off(self.members.len() - members);
+        let start = Instant::now();
+
+        let commit = removed
+            .iter()
+            .try_fold(self.members[0].group.commit_builder(), |builder, member| {
+                builder.remove_member(member.group.current_member_index())
+            })?
+            .build()
+            .await?;
+
+        self.members[0].group.apply_pending_commit().await?;
+        self.metrics.send_time += start.elapsed();
+
+        // Removed members receive the commit as well.
+        self.metrics.deliveries += removed.len() as u64;
+        self.deliver_commit(0, &commit).await?;
+        self.metrics.members = self.members.len();
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn deliver_commit(
+        &mut self,
+        sender: usize,
+        commit: &CommitOutput,
+    ) -> Result<(), MlsError> {
+        self.metrics.commits += 1;
+        self.deliver(sender, &commit.commit_message).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn deliver(&mut self, sender: usize, message: &MlsMessage) -> Result<(), MlsError> {
+        let recipients = self.members.len() - 1;
+        self.metrics.sent(message.to_bytes()?.len(), recipients);
+
+        let start = Instant::now();
+
+        for (i, member) in self.members.iter_mut().enumerate() {
+            if i != sender {
+                member
+                    .group
+                    .process_incoming_message(message.clone())
+                    .await?;
+            }
+        }
+
+        self.metrics.process_time += start.elapsed();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE};
+
+    use super::{LoadMetrics, Scenario, Simulator};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn scenario_is_simulated() {
+        let template = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("template", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let mut simulator = Simulator::new(&template, TEST_CIPHER_SUITE).await.unwrap();
+
+        let scenario = Scenario::new()
+            .join(5, 2)
+            .commit(2)
+            .messages(3, 100)
+            .remove(2);
+
+        let metrics = simulator.run(&scenario).await.unwrap();
+
+        assert_eq!(metrics.members, 4);
+        assert_eq!(metrics.key_packages, 5);
+        assert_eq!(metrics.welcomes, 3);
+        assert_eq!(metrics.commits, 6);
+        assert_eq!(metrics.application_messages, 3);
+        assert!(metrics.largest_message > 100);
+
+        let epoch = simulator.members()[0].group().current_epoch();
+
+        assert!(simulator
+            .members()
+            .iter()
+            .all(|member| member.group().current_epoch() == epoch));
+
+        let row = metrics.to_csv_row();
+
+        assert_eq!(
+            row.split(',').count(),
+            LoadMetrics::csv_header().split(',').count()
+        );
+    }
+}
@@ -95,6 +95,13 @@ where
     fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
         SmallMapInner::mls_decode(reader).map(Self)
     }
+
+    fn mls_decode_with_limits(
+        reader: &mut &[u8],
+        limits: &mls_rs_codec::DecodeLimits,
+    ) -> Result<Self, mls_rs_codec::Error> {
+        SmallMapInner::mls_decode_with_limits(reader, limits).map(Self)
+    }
 }
 
 impl<K, V> MlsSize for SmallMap<K, V>
@@ -117,12 +117,46 @@ pub(crate) struct ResumptionPsk {
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
-pub(crate) enum ResumptionPSKUsage {
+/// Purpose of a resumption pre-shared key.
+///
+/// Only [`ResumptionPSKUsage::Application`] may be used in a
+/// [`PreSharedKeyProposal`](crate::group::proposal::PreSharedKeyProposal).
+/// The other usages are injected by reinitialization and branching.
+pub enum ResumptionPSKUsage {
     Application = 1u8,
     Reinit = 2u8,
     Branch = 3u8,
 }
 
+/// Pre-shared key referenced by a
+/// [`PreSharedKeyProposal`](crate::group::proposal::PreSharedKeyProposal).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PreSharedKeyReference {
+    /// Key provided by the [`PreSharedKeyStorage`](mls_rs_core::psk::PreSharedKeyStorage)
+    /// of each member.
+    External(ExternalPskId),
+    /// Resumption secret of an epoch of a group.
+    Resumption {
+        usage: ResumptionPSKUsage,
+        group_id: Vec<u8>,
+        epoch: u64,
+    },
+}
+
+impl From<&JustPreSharedKeyID> for PreSharedKeyReference {
+    fn from(key_id: &JustPreSharedKeyID) -> Self {
+        match key_id {
+            JustPreSharedKeyID::External(id) => Self::External(id.clone()),
+            JustPreSharedKeyID::Resumption(psk) => Self::Resumption {
+                usage: psk.usage.clone(),
+                group_id: psk.psk_group_id.0.clone(),
+                epoch: psk.psk_epoch,
+            },
+        }
+    }
+}
+
 #[cfg(feature = "psk")]
 #[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode)]
 struct PSKLabel<'a> {
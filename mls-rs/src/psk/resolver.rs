@@ -9,6 +9,7 @@ use mls_rs_core::{
     group::GroupStateStorage,
     key_package::KeyPackageStorage,
     psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+    secret::KeyScheduleSecret,
 };
 
 use crate::{
@@ -54,9 +55,12 @@ impl<GS: GroupStateStorage, K: KeyPackageStorage, PS: PreSharedKeyStorage>
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn resolve_external(&self, psk_id: &ExternalPskId) -> Result<PreSharedKey, MlsError> {
+    async fn resolve_external(
+        &self,
+        psk_id: &ExternalPskId,
+    ) -> Result<KeyScheduleSecret, MlsError> {
         self.psk_store
-            .get(psk_id)
+            .get_secret(psk_id)
             .await
             .map_err(|e| MlsError::PskStoreError(e.into_any_error()))?
             .ok_or(MlsError::MissingRequiredPsk)
@@ -70,7 +74,7 @@ impl<GS: GroupStateStorage, K: KeyPackageStorage, PS: PreSharedKeyStorage>
             let psk = match &id.key_id {
                 JustPreSharedKeyID::External(external) => self.resolve_external(external).await,
                 JustPreSharedKeyID::Resumption(resumption) => {
-                    self.resolve_resumption(resumption).await
+                    self.resolve_resumption(resumption).await.map(Into::into)
                 }
             }?;
 
@@ -3,24 +3,24 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use alloc::vec;
+#[cfg(any(test, feature = "compliance"))]
 use alloc::vec::Vec;
 use core::{
     fmt::{self, Debug},
     ops::Deref,
 };
-use mls_rs_core::crypto::CipherSuiteProvider;
-use zeroize::Zeroizing;
+use mls_rs_core::{crypto::CipherSuiteProvider, secret::KeyScheduleSecret};
 
 #[cfg(feature = "psk")]
 use mls_rs_codec::MlsEncode;
 
 #[cfg(feature = "psk")]
-use mls_rs_core::{error::IntoAnyError, psk::PreSharedKey};
+use mls_rs_core::error::IntoAnyError;
 
 #[cfg(feature = "psk")]
 use crate::{
     client::MlsError,
-    group::key_schedule::kdf_expand_with_label,
+    group::key_schedule::kdf_expand_with_label_secret,
     psk::{PSKLabel, PreSharedKeyID},
 };
 
@@ -28,29 +28,29 @@ use crate::{
 #[derive(Clone)]
 pub(crate) struct PskSecretInput {
     pub id: PreSharedKeyID,
-    pub psk: PreSharedKey,
+    pub psk: KeyScheduleSecret,
 }
 
+/// Secret combining the pre-shared keys of an epoch. It is non-extractable
+/// if the provider derived it from a [SecretHandle](mls_rs_core::secret::SecretHandle).
 #[derive(PartialEq, Eq, Clone)]
-pub(crate) struct PskSecret(Zeroizing<Vec<u8>>);
+pub(crate) struct PskSecret(KeyScheduleSecret);
 
 impl Debug for PskSecret {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        mls_rs_core::debug::pretty_bytes(&self.0)
-            .named("PskSecret")
-            .fmt(f)
+        f.debug_tuple("PskSecret").field(&self.0).finish()
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "compliance"))]
 impl From<Vec<u8>> for PskSecret {
     fn from(value: Vec<u8>) -> Self {
-        PskSecret(Zeroizing::new(value))
+        PskSecret(value.into())
     }
 }
 
 impl Deref for PskSecret {
-    type Target = [u8];
+    type Target = KeyScheduleSecret;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -59,7 +59,7 @@ impl Deref for PskSecret {
 
 impl PskSecret {
     pub(crate) fn new<P: CipherSuiteProvider>(provider: &P) -> PskSecret {
-        PskSecret(Zeroizing::new(vec![0u8; provider.kdf_extract_size()]))
+        PskSecret(vec![0u8; provider.kdf_extract_size()].into())
     }
 
     #[cfg(feature = "psk")]
@@ -69,11 +69,20 @@ impl PskSecret {
         cipher_suite_provider: &P,
     ) -> Result<PskSecret, MlsError> {
         let len = u16::try_from(input.len()).map_err(|_| MlsError::TooManyPskIds)?;
-        let mut psk_secret = PskSecret::new(cipher_suite_provider);
+        let zero = Self::new(cipher_suite_provider).0;
+        let mut psk_secret = zero.clone();
 
         for (index, psk_secret_input) in input.iter().enumerate() {
             let index = index as u16;
 
+            // A PSK held by a hardware-backed store only enters the key
+            // schedule through the provider operations below.
+            if !psk_secret_input.psk.is_extractable()
+                && !cipher_suite_provider.supports_secret_handles()
+            {
+                return Err(MlsError::SecretHandlesNotSupported);
+            }
+
             let label = PSKLabel {
                 id: &psk_secret_input.id,
                 index,
@@ -81,14 +90,11 @@ impl PskSecret {
             };
 
             let psk_extracted = cipher_suite_provider
-                .kdf_extract(
-                    &vec![0; cipher_suite_provider.kdf_extract_size()],
-                    &psk_secret_input.psk,
-                )
+                .kdf_extract_secret(&zero, &psk_secret_input.psk)
                 .await
                 .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
-            let psk_input = kdf_expand_with_label(
+            let psk_input = kdf_expand_with_label_secret(
                 cipher_suite_provider,
                 &psk_extracted,
                 b"derived psk",
@@ -98,36 +104,36 @@ impl PskSecret {
             .await?;
 
             psk_secret = cipher_suite_provider
-                .kdf_extract(&psk_input, &psk_secret)
+                .kdf_extract_secret(&psk_input, &psk_secret)
                 .await
-                .map(PskSecret)
                 .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
         }
 
-        Ok(psk_secret)
+        Ok(PskSecret(psk_secret))
     }
 }
 
 #[cfg(feature = "psk")]
 #[cfg(test)]
 mod tests {
+    use alloc::vec;
     use alloc::vec::Vec;
+    use assert_matches::assert_matches;
     #[cfg(not(mls_build_async))]
     use core::iter;
+    use mls_rs_core::secret::SecretHandle;
     use serde::{Deserialize, Serialize};
 
     use crate::{
-        crypto::test_utils::try_test_cipher_suite_provider,
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        crypto::test_utils::{test_cipher_suite_provider, try_test_cipher_suite_provider},
         psk::ExternalPskId,
         psk::{JustPreSharedKeyID, PreSharedKeyID, PskNonce},
         CipherSuiteProvider,
     };
 
     #[cfg(not(mls_build_async))]
-    use crate::{
-        crypto::test_utils::test_cipher_suite_provider, psk::test_utils::make_external_psk_id,
-        CipherSuite,
-    };
+    use crate::{psk::test_utils::make_external_psk_id, CipherSuite};
 
     use super::{PskSecret, PskSecretInput};
 
@@ -196,7 +202,7 @@ mod tests {
                         TestScenario {
                             cipher_suite: cs.into(),
                             psks: psks.to_vec(),
-                            psk_secret: psk_secret.to_vec(),
+                            psk_secret: psk_secret.as_bytes().unwrap().to_vec(),
                         }
                     },
                 )
@@ -232,8 +238,25 @@ mod tests {
                 let computed =
                     TestScenario::compute_psk_secret(&provider, scenario.psks.clone()).await;
 
-                assert_eq!(scenario.psk_secret, computed.to_vec());
+                assert_eq!(scenario.psk_secret, computed.as_bytes().unwrap());
             }
         }
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn psk_handles_require_provider_support() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let input = PskSecretInput {
+            id: PreSharedKeyID {
+                key_id: JustPreSharedKeyID::External(ExternalPskId::new(vec![1])),
+                psk_nonce: crate::psk::test_utils::make_nonce(TEST_CIPHER_SUITE),
+            },
+            psk: SecretHandle::new(vec![2]).into(),
+        };
+
+        let res = PskSecret::calculate(&[input], &cs).await;
+
+        assert_matches!(res, Err(MlsError::SecretHandlesNotSupported));
+    }
 }
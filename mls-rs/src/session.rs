@@ -0,0 +1,421 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{CommitEffect, Member, ReceivedMessage},
+    Client, ExtensionList, Group, MlsMessage,
+};
+
+/// Policies applied automatically by a [`Conversation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SessionPolicy {
+    /// Commit fresh keys for this member before sending a message once this
+    /// many messages were sent since the last commit of this member. `None`
+    /// disables automatic updates.
+    pub update_after_messages: Option<u32>,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        Self {
+            update_after_messages: Some(100),
+        }
+    }
+}
+
+impl SessionPolicy {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_update_after_messages(self, update_after_messages: Option<u32>) -> Self {
+        Self {
+            update_after_messages,
+        }
+    }
+}
+
+/// Messages produced by [`Conversation::invite`].
+#[derive(Clone, Debug)]
+pub struct Invitation {
+    /// Commit to send to the current members of the conversation.
+    pub commit: MlsMessage,
+    /// Welcome messages to send to the invited clients.
+    pub welcome_messages: Vec<MlsMessage>,
+}
+
+/// Event returned by [`Conversation::receive`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum ConversationEvent {
+    /// A member sent a message.
+    Message {
+        sender: Option<Member>,
+        data: Vec<u8>,
+    },
+    /// Members joined or left the conversation.
+    MembersChanged {
+        added: Vec<Member>,
+        removed: Vec<Member>,
+    },
+    /// Keys of the conversation were updated without changing its members.
+    KeysUpdated,
+    /// This client was removed from the conversation. No further messages
+    /// can be sent or received.
+    Removed,
+}
+
+/// A group managed on behalf of the application.
+///
+/// A conversation commits pending proposals before sending, refreshes the
+/// keys of this member according to its [`SessionPolicy`] and writes the
+/// group state to the storage of the client after every change. Proposals
+/// and epochs are never exposed.
+#[derive(Clone)]
+pub struct Conversation<C>
+where
+    C: ClientConfig,
+{
+    group: Group<C>,
+    policy: SessionPolicy,
+    sent_since_commit: u32,
+}
+
+impl<C> Conversation<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Start a new conversation with `client` as the only member.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn create(client: &Client<C>, policy: SessionPolicy) -> Result<Self, MlsError> {
+        let group = client
+            .create_group(ExtensionList::new(), ExtensionList::new(), None)
+            .await?;
+
+        Self::new(group, policy).await
+    }
+
+    /// Join a conversation using a welcome message from
+    /// [`Conversation::invite`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn join(
+        client: &Client<C>,
+        welcome_message: &MlsMessage,
+        policy: SessionPolicy,
+    ) -> Result<Self, MlsError> {
+        let (group, _) = client.join_group(None, welcome_message, None).await?;
+
+        Self::new(group, policy).await
+    }
+
+    /// Load a conversation previously written to the storage of `client`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn load(
+        client: &Client<C>,
+        conversation_id: &[u8],
+        policy: SessionPolicy,
+    ) -> Result<Self, MlsError> {
+        Ok(Self {
+            group: client.load_group(conversation_id).await?,
+            policy,
+            sent_since_commit: 0,
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn new(mut group: Group<C>, policy: SessionPolicy) -> Result<Self, MlsError> {
+        group.write_to_storage().await?;
+
+        Ok(Self {
+            group,
+            policy,
+            sent_since_commit: 0,
+        })
+    }
+
+    /// Unique identifier of this conversation.
+    pub fn id(&self) -> &[u8] {
+        self.group.group_id()
+    }
+
+    /// Current members of this conversation.
+    pub fn members(&self) -> Vec<Member> {
+        self.group.roster().members()
+    }
+
+    /// Add the clients that published `key_packages` to the conversation.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn invite(&mut self, key_packages: Vec<MlsMessage>) -> Result<Invitation, MlsError> {
+        let builder = key_packages
+            .into_iter()
+            .try_fold(self.group.commit_builder(), |builder, key_package| {
+                builder.add_member(key_package)
+            })?;
+
+        let output = builder.build().await?;
+        self.apply_own_commit().await?;
+
+        Ok(Invitation {
+            commit: output.commit_message,
+            welcome_messages: output.welcome_messages,
+        })
+    }
+
+    /// Remove `member` from the conversation. The returned commit must be
+    /// sent to the remaining members.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn remove(&mut self, member: &Member) -> Result<MlsMessage, MlsError> {
+        let output = self
+            .group
+            .commit_builder()
+            .remove_member(member.index)?
+            .build()
+            .await?;
+
+        self.apply_own_commit().await?;
+
+        Ok(output.commit_message)
+    }
+
+    /// Encrypt `data` for the members of the conversation.
+    ///
+    /// The returned messages must be sent in order. A commit precedes the
+    /// encrypted message if proposals were pending or if the keys of this
+    /// member were due for an update.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn send(&mut self, data: &[u8]) -> Result<Vec<MlsMessage>, MlsError> {
+        let mut messages = Vec::new();
+
+        if self.commit_required() {
+            let output = self.group.commit(Vec::new()).await?;
+            self.apply_own_commit().await?;
+            messages.push(output.commit_message);
+        }
+
+        let message = self
+            .group
+            .encrypt_application_message(data, Vec::new())
+            .await?;
+
+        self.sent_since_commit += 1;
+
+        // Message keys are deleted once used, which must be persisted before
+        // the message is sent.
+        self.group.write_to_storage().await?;
+        messages.push(message);
+
+        Ok(messages)
+    }
+
+    /// Process a message sent to the conversation.
+    ///
+    /// `None` is returned for messages that don't require any action from
+    /// the application, such as proposals that are committed automatically
+    /// before the next message is sent.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn receive(
+        &mut self,
+        message: MlsMessage,
+    ) -> Result<Option<ConversationEvent>, MlsError> {
+        let members_before = self.members();
+        let received = self.group.process_incoming_message(message).await?;
+
+        let event = match received {
+            ReceivedMessage::ApplicationMessage(message) => Some(ConversationEvent::Message {
+                data: message.data().to_vec(),
+                sender: message.sender,
+            }),
+            ReceivedMessage::Commit(commit) => match commit.effect {
+                CommitEffect::Removed { .. } | CommitEffect::ReInit(_) => {
+                    Some(ConversationEvent::Removed)
+                }
+                CommitEffect::NewEpoch(_) => Some(self.membership_event(members_before)),
+            },
+            _ => None,
+        };
+
+        self.group.write_to_storage().await?;
+
+        Ok(event)
+    }
+
+    /// Leave the conversation. The returned proposal must be sent to the
+    /// other members, one of which commits it.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn leave(mut self) -> Result<MlsMessage, MlsError> {
+        let index = self.group.current_member_index();
+        let proposal = self.group.propose_remove(index, Vec::new()).await?;
+        self.group.write_to_storage().await?;
+
+        Ok(proposal)
+    }
+
+    /// Access the underlying group for operations not covered by this API.
+    pub fn group(&self) -> &Group<C> {
+        &self.group
+    }
+
+    fn commit_required(&self) -> bool {
+        #[cfg(feature = "by_ref_proposal")]
+        if self.group.has_cached_proposals() {
+            return true;
+        }
+
+        self.policy
+            .update_after_messages
+            .map_or(false, |limit| self.sent_since_commit >= limit)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn apply_own_commit(&mut self) -> Result<(), MlsError> {
+        self.group.apply_pending_commit().await?;
+        self.sent_since_commit = 0;
+        self.group.write_to_storage().await
+    }
+
+    fn membership_event(&self, members_before: Vec<Member>) -> ConversationEvent {
+        let members_after = self.members();
+
+        let same_member =
+            |a: &Member, b: &Member| a.index == b.index && a.signing_identity == b.signing_identity;
+
+        let added: Vec<_> = members_after
+            .iter()
+            .filter(|m| !members_before.iter().any(|b| same_member(m, b)))
+            .cloned()
+            .collect();
+
+        let removed: Vec<_> = members_before
+            .into_iter()
+            .filter(|m| !members_after.iter().any(|a| same_member(m, a)))
+            .collect();
+
+        if added.is_empty() && removed.is_empty() {
+            ConversationEvent::KeysUpdated
+        } else {
+            ConversationEvent::MembersChanged { added, removed }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use assert_matches::assert_matches;
+
+    use crate::client::test_utils::{
+        test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+    };
+
+    use super::{Conversation, ConversationEvent, SessionPolicy};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_exchange_messages() {
+        let (alice_client, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let (bob_client, bob_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let policy = SessionPolicy::new().with_update_after_messages(Some(2));
+
+        let mut alice = Conversation::create(&alice_client, policy.clone())
+            .await
+            .unwrap();
+
+        let invitation = alice.invite(vec![bob_key_package]).await.unwrap();
+
+        let mut bob = Conversation::join(&bob_client, &invitation.welcome_messages[0], policy)
+            .await
+            .unwrap();
+
+        assert_eq!(alice.members(), bob.members());
+
+        for i in 0..3u8 {
+            let messages = alice.send(&[i]).await.unwrap();
+
+            // The third message is preceded by an update.
+            assert_eq!(messages.len(), if i == 2 { 2 } else { 1 });
+
+            let mut events = Vec::new();
+
+            for message in messages {
+                events.push(bob.receive(message).await.unwrap());
+            }
+
+            if i == 2 {
+                assert_matches!(events[0], Some(ConversationEvent::KeysUpdated));
+            }
+
+            assert_matches!(
+                events.last(),
+                Some(Some(ConversationEvent::Message { data, .. })) if data == &[i]
+            );
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn removal_is_reported() {
+        let (alice_client, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let (bob_client, bob_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let (carol_client, carol_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let mut alice = Conversation::create(&alice_client, SessionPolicy::new())
+            .await
+            .unwrap();
+
+        let invitation = alice
+            .invite(vec![bob_key_package, carol_key_package])
+            .await
+            .unwrap();
+
+        let mut bob = Conversation::join(
+            &bob_client,
+            &invitation.welcome_messages[0],
+            SessionPolicy::new(),
+        )
+        .await
+        .unwrap();
+
+        let mut carol = Conversation::join(
+            &carol_client,
+            &invitation.welcome_messages[0],
+            SessionPolicy::new(),
+        )
+        .await
+        .unwrap();
+
+        let carol_index = carol.group().current_member_index();
+
+        let carol_member = carol
+            .members()
+            .into_iter()
+            .find(|m| m.index == carol_index)
+            .unwrap();
+
+        let commit = alice.remove(&carol_member).await.unwrap();
+
+        let event = bob.receive(commit.clone()).await.unwrap();
+
+        assert_matches!(
+            event,
+            Some(ConversationEvent::MembersChanged { added, removed })
+                if added.is_empty() && removed == vec![carol_member]
+        );
+
+        let event = carol.receive(commit).await.unwrap();
+        assert_matches!(event, Some(ConversationEvent::Removed));
+    }
+}
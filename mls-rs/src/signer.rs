@@ -3,14 +3,16 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use alloc::vec::Vec;
+#[cfg(test)]
 use core::fmt::{self, Debug};
-use mls_rs_codec::{MlsEncode, MlsSize};
+use mls_rs_codec::{MlsEncode, VarInt};
 use mls_rs_core::error::IntoAnyError;
 
 use crate::client::MlsError;
 use crate::crypto::{CipherSuiteProvider, SignaturePublicKey, SignatureSecretKey};
 
-#[derive(Clone, MlsSize, MlsEncode)]
+#[cfg(test)]
+#[derive(Clone, mls_rs_codec::MlsSize, MlsEncode)]
 struct SignContent {
     #[mls_codec(with = "mls_rs_codec::byte_vec")]
     label: Vec<u8>,
@@ -18,6 +20,7 @@ struct SignContent {
     content: Vec<u8>,
 }
 
+#[cfg(test)]
 impl Debug for SignContent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SignContent")
@@ -27,15 +30,20 @@ impl Debug for SignContent {
     }
 }
 
+#[cfg(test)]
 impl SignContent {
     pub fn new(label: &str, content: Vec<u8>) -> Self {
         Self {
-            label: [b"MLS 1.0 ", label.as_bytes()].concat(),
+            label: sign_label(label),
             content,
         }
     }
 }
 
+fn sign_label(label: &str) -> Vec<u8> {
+    [b"MLS 1.0 ", label.as_bytes()].concat()
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
 #[cfg_attr(
@@ -56,16 +64,32 @@ pub(crate) trait Signable<'a> {
 
     fn write_signature(&mut self, signature: Vec<u8>);
 
+    /// Bytes passed to the signature algorithm, including the label.
+    ///
+    /// The label and the length of the content are written in front of the
+    /// encoded content, so that large content such as a group info with a
+    /// ratchet tree is encoded only once. Inserting the header still shifts
+    /// the content within its buffer, which may reallocate it.
+    fn to_be_signed(&self, context: &Self::SigningContext) -> Result<Vec<u8>, MlsError> {
+        let mut content = self.signable_content(context)?;
+
+        let mut header = Vec::new();
+        mls_rs_codec::byte_vec::mls_encode(&sign_label(Self::SIGN_LABEL), &mut header)?;
+        VarInt::try_from(content.len())?.mls_encode(&mut header)?;
+
+        content.splice(0..0, header);
+
+        Ok(content)
+    }
+
     async fn sign<P: CipherSuiteProvider>(
         &mut self,
         signature_provider: &P,
         signer: &SignatureSecretKey,
         context: &Self::SigningContext,
     ) -> Result<(), MlsError> {
-        let sign_content = SignContent::new(Self::SIGN_LABEL, self.signable_content(context)?);
-
         let signature = signature_provider
-            .sign(signer, &sign_content.mls_encode_to_vec()?)
+            .sign(signer, &self.to_be_signed(context)?)
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
@@ -80,14 +104,8 @@ pub(crate) trait Signable<'a> {
         public_key: &SignaturePublicKey,
         context: &Self::SigningContext,
     ) -> Result<(), MlsError> {
-        let sign_content = SignContent::new(Self::SIGN_LABEL, self.signable_content(context)?);
-
         signature_provider
-            .verify(
-                public_key,
-                self.signature(),
-                &sign_content.mls_encode_to_vec()?,
-            )
+            .verify(public_key, self.signature(), &self.to_be_signed(context)?)
             .await
             .map_err(|_| MlsError::InvalidSignature)
     }
@@ -354,4 +372,21 @@ mod tests {
 
         assert_matches!(res, Err(MlsError::InvalidSignature));
     }
+
+    #[test]
+    fn to_be_signed_matches_sign_content_encoding() {
+        for len in [0, 32, 100_000] {
+            let test_signable = TestSignable {
+                content: random_bytes(len),
+                signature: vec![],
+            };
+
+            let expected =
+                SignContent::new(TestSignable::SIGN_LABEL, test_signable.content.clone())
+                    .mls_encode_to_vec()
+                    .unwrap();
+
+            assert_eq!(test_signable.to_be_signed(&vec![]).unwrap(), expected);
+        }
+    }
 }
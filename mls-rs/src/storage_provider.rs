@@ -2,11 +2,17 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+mod encrypted;
 /// Storage providers that operate completely in memory.
 pub mod in_memory;
 pub(crate) mod key_package;
+mod migration;
+mod observed;
 
+pub use encrypted::{EncryptedGroupStateStorage, StorageMasterKey};
 pub use key_package::*;
+pub use migration::{MigrationProgress, StorageMigration};
+pub use observed::{GroupStateObserver, GroupStateWrite, ObservedGroupStateStorage};
 
 #[cfg(feature = "sqlite")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
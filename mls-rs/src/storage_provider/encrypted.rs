@@ -0,0 +1,443 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::CipherSuiteProvider,
+    error::IntoAnyError,
+    group::{EpochRecord, GroupState, GroupStateStorage},
+};
+use zeroize::Zeroizing;
+
+use crate::client::MlsError;
+
+const GROUP_KEY_LABEL: &[u8] = b"mls-rs group state key";
+
+/// Master secret from which [`EncryptedGroupStateStorage`] derives the
+/// storage key of each group.
+///
+/// `id` is stored next to every record encrypted with a key derived from
+/// this master key, so that records can be matched with the right master key
+/// while it is being rotated. Each master key must have a distinct `id`.
+#[derive(Clone)]
+pub struct StorageMasterKey {
+    id: u32,
+    secret: Zeroizing<Vec<u8>>,
+}
+
+impl StorageMasterKey {
+    pub fn new(id: u32, secret: Vec<u8>) -> Self {
+        Self {
+            id,
+            secret: Zeroizing::new(secret),
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Debug for StorageMasterKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StorageMasterKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(MlsSize, MlsEncode, MlsDecode)]
+struct EncryptedRecord {
+    master_key_id: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    nonce: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    ciphertext: Vec<u8>,
+}
+
+/// [`GroupStateStorage`] that encrypts the group state and prior epochs
+/// before forwarding them to an inner storage.
+///
+/// Every group is encrypted with its own key, derived from a
+/// [`StorageMasterKey`] and the group id. Records are bound to their group
+/// and epoch, so they can't be swapped within the inner storage.
+///
+/// To rotate the master key, create the storage with the new master key and
+/// register the old one with
+/// [`with_previous_master_key`](EncryptedGroupStateStorage::with_previous_master_key).
+/// Records encrypted with the old key remain readable and are encrypted with
+/// the new key the next time the group is written, or immediately with
+/// [`rotate_group`](EncryptedGroupStateStorage::rotate_group). The old key can
+/// be dropped once all groups were rotated.
+#[derive(Clone, Debug)]
+pub struct EncryptedGroupStateStorage<S, P> {
+    storage: S,
+    cipher_suite_provider: P,
+    master_key: StorageMasterKey,
+    previous_master_keys: Vec<StorageMasterKey>,
+}
+
+impl<S, P> EncryptedGroupStateStorage<S, P>
+where
+    S: GroupStateStorage,
+    P: CipherSuiteProvider,
+{
+    /// Encrypt group states written to `storage` with keys derived from
+    /// `master_key` using the KDF and AEAD of `cipher_suite_provider`.
+    pub fn new(storage: S, cipher_suite_provider: P, master_key: StorageMasterKey) -> Self {
+        Self {
+            storage,
+            cipher_suite_provider,
+            master_key,
+            previous_master_keys: Vec::new(),
+        }
+    }
+
+    /// Accept records encrypted with `master_key` when reading, while the
+    /// master key is being rotated.
+    pub fn with_previous_master_key(mut self, master_key: StorageMasterKey) -> Self {
+        self.previous_master_keys.push(master_key);
+        self
+    }
+
+    /// Storage that encrypted group states are written to.
+    pub fn inner(&self) -> &S {
+        &self.storage
+    }
+
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+
+    /// Encrypt the state and all prior epochs of the group `group_id` with
+    /// the current master key.
+    ///
+    /// Returns `false` if no record of the group was encrypted with a
+    /// previous master key.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn rotate_group(&mut self, group_id: &[u8]) -> Result<bool, MlsError> {
+        let Some(state) = self.stored_record(group_id, None).await? else {
+            return Ok(false);
+        };
+
+        let mut rotated = state.master_key_id != self.master_key.id;
+        let mut epochs = Vec::new();
+
+        if let Some(max_epoch_id) = self.max_epoch_id(group_id).await? {
+            for epoch_id in (0..=max_epoch_id).rev() {
+                let Some(epoch) = self.stored_record(group_id, Some(epoch_id)).await? else {
+                    break;
+                };
+
+                rotated |= epoch.master_key_id != self.master_key.id;
+
+                let data = self.decrypt(group_id, Some(epoch_id), epoch).await?;
+                epochs.push(EpochRecord::new(epoch_id, data.to_vec()));
+            }
+        }
+
+        if !rotated {
+            return Ok(false);
+        }
+
+        let data = self.decrypt(group_id, None, state).await?;
+
+        let state = GroupState {
+            id: group_id.to_vec(),
+            data: data.to_vec(),
+        };
+
+        self.write(state, Vec::new(), epochs).await?;
+
+        Ok(true)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn stored_record(
+        &self,
+        group_id: &[u8],
+        epoch_id: Option<u64>,
+    ) -> Result<Option<EncryptedRecord>, MlsError> {
+        let data = match epoch_id {
+            Some(epoch_id) => self.storage.epoch(group_id, epoch_id).await,
+            None => self.storage.state(group_id).await,
+        }
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        data.map(|data| EncryptedRecord::mls_decode(&mut &*data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn group_key(
+        &self,
+        master_key: &StorageMasterKey,
+        group_id: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        let cs = &self.cipher_suite_provider;
+
+        let prk = cs
+            .kdf_extract(&[], &master_key.secret)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let info = [GROUP_KEY_LABEL, group_id].concat();
+
+        cs.kdf_expand(&prk, &info, cs.aead_key_size())
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn encrypt(
+        &self,
+        group_id: &[u8],
+        epoch_id: Option<u64>,
+        data: &[u8],
+    ) -> Result<Vec<u8>, MlsError> {
+        let cs = &self.cipher_suite_provider;
+        let key = self.group_key(&self.master_key, group_id).await?;
+
+        let nonce = cs
+            .random_bytes_vec(cs.aead_nonce_size())
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let ciphertext = cs
+            .aead_seal(&key, data, Some(&record_aad(group_id, epoch_id)?), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let record = EncryptedRecord {
+            master_key_id: self.master_key.id,
+            nonce,
+            ciphertext,
+        };
+
+        Ok(record.mls_encode_to_vec()?)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn decrypt(
+        &self,
+        group_id: &[u8],
+        epoch_id: Option<u64>,
+        record: EncryptedRecord,
+    ) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        let master_key = core::iter::once(&self.master_key)
+            .chain(&self.previous_master_keys)
+            .find(|key| key.id == record.master_key_id)
+            .ok_or(MlsError::StorageMasterKeyNotFound(record.master_key_id))?;
+
+        let key = self.group_key(master_key, group_id).await?;
+
+        self.cipher_suite_provider
+            .aead_open(
+                &key,
+                &record.ciphertext,
+                Some(&record_aad(group_id, epoch_id)?),
+                &record.nonce,
+            )
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+}
+
+fn record_aad(group_id: &[u8], epoch_id: Option<u64>) -> Result<Vec<u8>, MlsError> {
+    let mut aad = Vec::new();
+    mls_rs_codec::byte_vec::mls_encode(&group_id, &mut aad)?;
+    epoch_id.mls_encode(&mut aad)?;
+
+    Ok(aad)
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<S, P> GroupStateStorage for EncryptedGroupStateStorage<S, P>
+where
+    S: GroupStateStorage,
+    P: CipherSuiteProvider,
+{
+    type Error = MlsError;
+
+    async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let Some(record) = self.stored_record(group_id, None).await? else {
+            return Ok(None);
+        };
+
+        let data = self.decrypt(group_id, None, record).await?;
+
+        Ok(Some(data.to_vec()))
+    }
+
+    async fn epoch(&self, group_id: &[u8], epoch_id: u64) -> Result<Option<Vec<u8>>, Self::Error> {
+        let Some(record) = self.stored_record(group_id, Some(epoch_id)).await? else {
+            return Ok(None);
+        };
+
+        let data = self.decrypt(group_id, Some(epoch_id), record).await?;
+
+        Ok(Some(data.to_vec()))
+    }
+
+    async fn write(
+        &mut self,
+        state: GroupState,
+        epoch_inserts: Vec<EpochRecord>,
+        epoch_updates: Vec<EpochRecord>,
+    ) -> Result<(), Self::Error> {
+        let GroupState { id, data } = state;
+
+        let state = GroupState {
+            data: self.encrypt(&id, None, &data).await?,
+            id,
+        };
+
+        let mut encrypted_inserts = Vec::with_capacity(epoch_inserts.len());
+
+        for epoch in epoch_inserts {
+            let data = self.encrypt(&state.id, Some(epoch.id), &epoch.data).await?;
+            encrypted_inserts.push(EpochRecord::new(epoch.id, data));
+        }
+
+        let mut encrypted_updates = Vec::with_capacity(epoch_updates.len());
+
+        for epoch in epoch_updates {
+            let data = self.encrypt(&state.id, Some(epoch.id), &epoch.data).await?;
+            encrypted_updates.push(EpochRecord::new(epoch.id, data));
+        }
+
+        self.storage
+            .write(state, encrypted_inserts, encrypted_updates)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+
+    async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        self.storage
+            .max_epoch_id(group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::{
+        crypto::CryptoProvider,
+        group::{EpochRecord, GroupState, GroupStateStorage},
+    };
+
+    use crate::{
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
+        storage_provider::in_memory::InMemoryGroupStateStorage,
+    };
+
+    use super::{EncryptedGroupStateStorage, StorageMasterKey};
+
+    type TestCipherSuiteProvider = <TestCryptoProvider as CryptoProvider>::CipherSuiteProvider;
+
+    const GROUP_A: &[u8] = b"group a";
+    const GROUP_B: &[u8] = b"group b";
+
+    fn master_key(id: u32) -> StorageMasterKey {
+        StorageMasterKey::new(id, vec![id as u8; 32])
+    }
+
+    fn encrypted(
+        inner: &InMemoryGroupStateStorage,
+        key: StorageMasterKey,
+    ) -> EncryptedGroupStateStorage<InMemoryGroupStateStorage, TestCipherSuiteProvider> {
+        EncryptedGroupStateStorage::new(
+            inner.clone(),
+            test_cipher_suite_provider(TEST_CIPHER_SUITE),
+            key,
+        )
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn write_group<S: GroupStateStorage>(storage: &mut S, group_id: &[u8]) {
+        let state = GroupState {
+            id: group_id.to_vec(),
+            data: b"state".to_vec(),
+        };
+
+        storage
+            .write(state, vec![EpochRecord::new(0, b"epoch".to_vec())], vec![])
+            .await
+            .unwrap();
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn read_group<S: GroupStateStorage>(storage: &S, group_id: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let state = storage.state(group_id).await.unwrap().unwrap();
+        let epoch = storage.epoch(group_id, 0).await.unwrap().unwrap();
+
+        (state, epoch)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn records_are_encrypted_per_group() {
+        let inner = InMemoryGroupStateStorage::new();
+        let mut storage = encrypted(&inner, master_key(1));
+
+        write_group(&mut storage, GROUP_A).await;
+        write_group(&mut storage, GROUP_B).await;
+
+        let records = read_group(&storage, GROUP_A).await;
+        assert_eq!(records, (b"state".to_vec(), b"epoch".to_vec()));
+
+        let stored = inner.state(GROUP_A).await.unwrap().unwrap();
+        assert!(!stored.windows(5).any(|w| w == b"state"));
+
+        // A record moved to another group can't be decrypted
+        let mut moved = inner.clone();
+
+        let state = GroupState {
+            id: GROUP_B.to_vec(),
+            data: stored,
+        };
+
+        moved.write(state, vec![], vec![]).await.unwrap();
+
+        let res = storage.state(GROUP_B).await;
+
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn master_key_can_be_rotated() {
+        let inner = InMemoryGroupStateStorage::new();
+        write_group(&mut encrypted(&inner, master_key(1)), GROUP_A).await;
+
+        let mut storage = encrypted(&inner, master_key(2)).with_previous_master_key(master_key(1));
+
+        let expected = (b"state".to_vec(), b"epoch".to_vec());
+        let records = read_group(&storage, GROUP_A).await;
+        assert_eq!(records, expected);
+
+        let rotated = storage.rotate_group(GROUP_A).await.unwrap();
+        assert!(rotated);
+
+        let rotated = storage.rotate_group(GROUP_A).await.unwrap();
+        assert!(!rotated);
+
+        let storage = encrypted(&inner, master_key(2));
+        let records = read_group(&storage, GROUP_A).await;
+        assert_eq!(records, expected);
+
+        let storage = encrypted(&inner, master_key(1));
+        let res = storage.state(GROUP_A).await;
+
+        assert_matches!(res, Err(MlsError::StorageMasterKeyNotFound(2)));
+    }
+}
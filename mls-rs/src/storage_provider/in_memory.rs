@@ -2,10 +2,20 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+mod group_id_mapping_storage;
 mod group_state_storage;
+mod key_package_reservation;
 mod key_package_storage;
+mod proposal_cache_storage;
 mod psk_storage;
+mod security_watermark_storage;
+mod signature_key_storage;
 
+pub use group_id_mapping_storage::*;
 pub use group_state_storage::*;
+pub use key_package_reservation::*;
 pub use key_package_storage::*;
+pub use proposal_cache_storage::*;
 pub use psk_storage::*;
+pub use security_watermark_storage::*;
+pub use signature_key_storage::*;
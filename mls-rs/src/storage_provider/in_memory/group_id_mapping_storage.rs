@@ -0,0 +1,91 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use mls_rs_core::group::GroupIdMappingStorage;
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+
+use crate::map::LargeMap;
+
+#[derive(Clone, Debug, Default)]
+/// In memory mapping from external ids to group ids backed by a HashMap.
+///
+/// All clones of an instance of this type share the same underlying HashMap.
+pub struct InMemoryGroupIdMappingStorage {
+    inner: Arc<Mutex<LargeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl InMemoryGroupIdMappingStorage {
+    /// Create an empty mapping storage.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[cfg(feature = "std")]
+    fn lock(&self) -> MutexGuard<'_, LargeMap<Vec<u8>, Vec<u8>>> {
+        self.inner.lock().unwrap()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn lock(&self) -> MutexGuard<'_, LargeMap<Vec<u8>, Vec<u8>>> {
+        self.inner.lock()
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl GroupIdMappingStorage for InMemoryGroupIdMappingStorage {
+    type Error = Infallible;
+
+    async fn group_id(&self, external_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.lock().get(external_id).cloned())
+    }
+
+    async fn external_ids(&self, group_id: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(self
+            .lock()
+            .iter()
+            .filter(|(_, mapped)| mapped.as_slice() == group_id)
+            .map(|(external_id, _)| external_id.clone())
+            .collect())
+    }
+
+    async fn insert(&mut self, external_id: &[u8], group_id: &[u8]) -> Result<(), Self::Error> {
+        self.lock().insert(external_id.to_vec(), group_id.to_vec());
+        Ok(())
+    }
+
+    async fn replace_group_id(
+        &mut self,
+        old_group_id: &[u8],
+        new_group_id: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.lock()
+            .values_mut()
+            .filter(|mapped| mapped.as_slice() == old_group_id)
+            .for_each(|mapped| *mapped = new_group_id.to_vec());
+
+        Ok(())
+    }
+
+    async fn delete(&mut self, external_id: &[u8]) -> Result<(), Self::Error> {
+        self.lock().remove(external_id);
+        Ok(())
+    }
+}
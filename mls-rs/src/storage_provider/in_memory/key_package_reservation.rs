@@ -0,0 +1,78 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+use core::convert::Infallible;
+
+use alloc::{collections::BTreeSet, vec::Vec};
+use mls_rs_core::key_package::KeyPackageReservation;
+
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+
+#[derive(Clone, Debug, Default)]
+/// In memory key package reservations backed by a set of key package
+/// references.
+///
+/// All clones of an instance of this type share the same underlying set, so
+/// committers within one process can share reservations.
+pub struct InMemoryKeyPackageReservation {
+    inner: Arc<Mutex<BTreeSet<Vec<u8>>>>,
+}
+
+impl InMemoryKeyPackageReservation {
+    /// Create an empty set of reservations.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Reserve the key package referenced by `id`, returning `false` if it
+    /// is already reserved.
+    pub fn reserve(&self, id: &[u8]) -> bool {
+        self.lock().insert(id.to_vec())
+    }
+
+    /// Release the reservation of the key package referenced by `id`.
+    pub fn release(&self, id: &[u8]) {
+        self.lock().remove(id);
+    }
+
+    /// Whether the key package referenced by `id` is reserved.
+    pub fn is_reserved(&self, id: &[u8]) -> bool {
+        self.lock().contains(id)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeSet<Vec<u8>>> {
+        #[cfg(feature = "std")]
+        return self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        return self.inner.lock();
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl KeyPackageReservation for InMemoryKeyPackageReservation {
+    type Error = Infallible;
+
+    async fn reserve(&self, id: &[u8]) -> Result<bool, Self::Error> {
+        Ok((*self).reserve(id))
+    }
+
+    async fn release(&self, id: &[u8]) -> Result<(), Self::Error> {
+        (*self).release(id);
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use mls_rs_core::group::{ProposalCacheStorage, StoredProposal};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+#[cfg(not(feature = "std"))]
+use spin::Mutex;
+
+use crate::map::LargeMap;
+
+// Epoch and proposals cached for each group.
+type ProposalCacheMap = LargeMap<Vec<u8>, (u64, Vec<StoredProposal>)>;
+
+#[derive(Clone, Debug, Default)]
+/// In memory proposal cache storage backed by a HashMap.
+///
+/// All clones of an instance of this type share the same underlying HashMap.
+pub struct InMemoryProposalCacheStorage {
+    inner: Arc<Mutex<ProposalCacheMap>>,
+}
+
+impl InMemoryProposalCacheStorage {
+    /// Create an empty proposal cache storage.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Number of proposals stored for the group `group_id`.
+    pub fn len(&self, group_id: &[u8]) -> usize {
+        #[cfg(feature = "std")]
+        let lock = self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        let lock = self.inner.lock();
+
+        lock.get(group_id)
+            .map(|(_, proposals)| proposals.len())
+            .unwrap_or_default()
+    }
+
+    fn epoch_proposals(&self, group_id: &[u8], epoch: u64) -> Vec<StoredProposal> {
+        #[cfg(feature = "std")]
+        let lock = self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        let lock = self.inner.lock();
+
+        match lock.get(group_id) {
+            Some((stored_epoch, proposals)) if *stored_epoch == epoch => proposals.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl ProposalCacheStorage for InMemoryProposalCacheStorage {
+    type Error = Infallible;
+
+    async fn insert(
+        &mut self,
+        group_id: &[u8],
+        epoch: u64,
+        proposal: StoredProposal,
+    ) -> Result<(), Self::Error> {
+        #[cfg(feature = "std")]
+        let mut lock = self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        let mut lock = self.inner.lock();
+
+        let entry = lock
+            .entry(group_id.to_vec())
+            .or_insert_with(|| (epoch, Vec::new()));
+
+        // Proposals of older epochs can never be committed.
+        if entry.0 != epoch {
+            *entry = (epoch, Vec::new());
+        }
+
+        entry.1.push(proposal);
+
+        Ok(())
+    }
+
+    async fn proposal_refs(
+        &self,
+        group_id: &[u8],
+        epoch: u64,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(self
+            .epoch_proposals(group_id, epoch)
+            .into_iter()
+            .map(|p| p.proposal_ref)
+            .collect())
+    }
+
+    async fn proposals(
+        &self,
+        group_id: &[u8],
+        epoch: u64,
+    ) -> Result<Vec<StoredProposal>, Self::Error> {
+        Ok(self.epoch_proposals(group_id, epoch))
+    }
+
+    async fn delete(&mut self, group_id: &[u8]) -> Result<(), Self::Error> {
+        #[cfg(feature = "std")]
+        let mut lock = self.inner.lock().unwrap();
+
+        #[cfg(not(feature = "std"))]
+        let mut lock = self.inner.lock();
+
+        lock.remove(group_id);
+
+        Ok(())
+    }
+}
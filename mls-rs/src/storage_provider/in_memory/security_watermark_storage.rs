@@ -0,0 +1,78 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use mls_rs_core::group::SecurityWatermarkStorage;
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+
+use crate::map::LargeMap;
+
+#[derive(Clone, Debug, Default)]
+/// In memory security watermark storage backed by a HashMap.
+///
+/// All clones of an instance of this type share the same underlying HashMap.
+/// Watermarks kept in memory only protect against rollbacks of the group
+/// state storage while the process is running.
+pub struct InMemorySecurityWatermarkStorage {
+    inner: Arc<Mutex<LargeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl InMemorySecurityWatermarkStorage {
+    /// Create an empty watermark storage.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[cfg(feature = "std")]
+    fn lock(&self) -> MutexGuard<'_, LargeMap<Vec<u8>, Vec<u8>>> {
+        self.inner.lock().unwrap()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn lock(&self) -> MutexGuard<'_, LargeMap<Vec<u8>, Vec<u8>>> {
+        self.inner.lock()
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl SecurityWatermarkStorage for InMemorySecurityWatermarkStorage {
+    type Error = Infallible;
+
+    async fn watermark(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.lock().get(group_id).cloned())
+    }
+
+    async fn compare_and_set_watermark(
+        &mut self,
+        group_id: &[u8],
+        expected: Option<&[u8]>,
+        watermark: Vec<u8>,
+    ) -> Result<bool, Self::Error> {
+        let mut watermarks = self.lock();
+
+        if watermarks.get(group_id).map(Vec::as_slice) != expected {
+            return Ok(false);
+        }
+
+        watermarks.insert(group_id.to_vec(), watermark);
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use mls_rs_core::crypto::{SignatureKeyStorage, SignaturePublicKey, SignatureSecretKey};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+
+use crate::map::LargeMap;
+
+#[derive(Clone, Debug, Default)]
+/// In memory signature key storage backed by a HashMap.
+///
+/// All clones of an instance of this type share the same underlying HashMap.
+pub struct InMemorySignatureKeyStorage {
+    inner: Arc<Mutex<LargeMap<Vec<u8>, SignatureSecretKey>>>,
+}
+
+impl InMemorySignatureKeyStorage {
+    /// Create an empty signature key storage.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Number of stored keys.
+    pub fn count(&self) -> usize {
+        self.lock().len()
+    }
+
+    #[cfg(feature = "std")]
+    fn lock(&self) -> MutexGuard<'_, LargeMap<Vec<u8>, SignatureSecretKey>> {
+        self.inner.lock().unwrap()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn lock(&self) -> MutexGuard<'_, LargeMap<Vec<u8>, SignatureSecretKey>> {
+        self.inner.lock()
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl SignatureKeyStorage for InMemorySignatureKeyStorage {
+    type Error = Infallible;
+
+    async fn get(
+        &self,
+        public_key: &SignaturePublicKey,
+    ) -> Result<Option<SignatureSecretKey>, Self::Error> {
+        Ok(self.lock().get(public_key.as_bytes()).cloned())
+    }
+
+    async fn insert(
+        &mut self,
+        public_key: &SignaturePublicKey,
+        secret_key: SignatureSecretKey,
+    ) -> Result<(), Self::Error> {
+        self.lock().insert(public_key.to_vec(), secret_key);
+        Ok(())
+    }
+
+    async fn delete(&mut self, public_key: &SignaturePublicKey) -> Result<(), Self::Error> {
+        self.lock().remove(public_key.as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,464 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_core::{
+    error::{AnyError, IntoAnyError},
+    group::{EpochRecord, GroupState, GroupStateStorage},
+    key_package::KeyPackageStorage,
+    psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{snapshot::Snapshot, Group},
+    Client,
+};
+
+type PskWriter<'a> =
+    Box<dyn FnMut(&ExternalPskId, &PreSharedKey) -> Result<(), AnyError> + Send + 'a>;
+
+type ProgressCallback<'a> = Box<dyn FnMut(&MigrationProgress) + Send + 'a>;
+
+/// Number of items copied so far by a [`StorageMigration`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MigrationProgress {
+    /// Groups copied and verified.
+    pub groups: usize,
+    /// Groups to copy.
+    pub total_groups: usize,
+    /// Prior epochs copied with the groups.
+    pub epochs: usize,
+    /// Key packages copied.
+    pub key_packages: usize,
+    /// Key packages to copy.
+    pub total_key_packages: usize,
+    /// Pre-shared keys copied.
+    pub pre_shared_keys: usize,
+    /// Pre-shared keys to copy.
+    pub total_pre_shared_keys: usize,
+}
+
+/// Copy groups, prior epochs, key packages and pre-shared keys from the
+/// storage providers of one client to those of another, e.g. when moving
+/// from in-memory storage to SQLite.
+///
+/// The storage traits can't enumerate their content, so the items to copy
+/// are listed by the application, typically from provider specific APIs such
+/// as [`InMemoryGroupStateStorage::stored_groups`](crate::storage_provider::in_memory::InMemoryGroupStateStorage::stored_groups).
+/// Items that no longer exist in the source, e.g. a key package consumed
+/// while the migration is running, are skipped.
+///
+/// The source storage can remain in use while the migration runs. Running the
+/// migration again copies the latest state of the groups, so the application
+/// can run it once while live and a final time after switching over to the
+/// destination.
+///
+/// Every copied group is loaded from the destination storage and its epoch
+/// authenticator is compared with the one of the copied state.
+pub struct StorageMigration<'a> {
+    group_ids: Vec<Vec<u8>>,
+    key_package_ids: Vec<Vec<u8>>,
+    psk_ids: Vec<ExternalPskId>,
+    psk_writer: Option<PskWriter<'a>>,
+    progress: Option<ProgressCallback<'a>>,
+}
+
+impl Debug for StorageMigration<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StorageMigration")
+            .field("groups", &self.group_ids.len())
+            .field("key_packages", &self.key_package_ids.len())
+            .field("pre_shared_keys", &self.psk_ids.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for StorageMigration<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> StorageMigration<'a> {
+    pub fn new() -> Self {
+        Self {
+            group_ids: Vec::new(),
+            key_package_ids: Vec::new(),
+            psk_ids: Vec::new(),
+            psk_writer: None,
+            progress: None,
+        }
+    }
+
+    /// Copy the groups `group_ids` with all their prior epochs.
+    pub fn with_groups(self, group_ids: Vec<Vec<u8>>) -> Self {
+        Self { group_ids, ..self }
+    }
+
+    /// Copy the key packages `key_package_ids`.
+    pub fn with_key_packages(self, key_package_ids: Vec<Vec<u8>>) -> Self {
+        Self {
+            key_package_ids,
+            ..self
+        }
+    }
+
+    /// Copy the pre-shared keys `psk_ids` by passing them to `writer`.
+    ///
+    /// [`PreSharedKeyStorage`] is read only, so pre-shared keys are inserted
+    /// into the destination storage by `writer`.
+    pub fn with_pre_shared_keys<F, E>(self, psk_ids: Vec<ExternalPskId>, mut writer: F) -> Self
+    where
+        F: FnMut(&ExternalPskId, &PreSharedKey) -> Result<(), E> + Send + 'a,
+        E: IntoAnyError,
+    {
+        Self {
+            psk_ids,
+            psk_writer: Some(Box::new(move |id, psk| {
+                writer(id, psk).map_err(|e| e.into_any_error())
+            })),
+            ..self
+        }
+    }
+
+    /// Call `progress` after each copied item.
+    pub fn with_progress<F>(self, progress: F) -> Self
+    where
+        F: FnMut(&MigrationProgress) + Send + 'a,
+    {
+        Self {
+            progress: Some(Box::new(progress)),
+            ..self
+        }
+    }
+
+    /// Copy all listed items from the storages of `source` to the storages of
+    /// `destination`.
+    ///
+    /// Returns [`MlsError::StorageMigrationVerificationFailed`] if a group
+    /// loaded from the destination doesn't match the copied state.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn run<C, D>(
+        mut self,
+        source: &Client<C>,
+        destination: &Client<D>,
+    ) -> Result<MigrationProgress, MlsError>
+    where
+        C: ClientConfig + Clone,
+        D: ClientConfig + Clone,
+    {
+        let mut progress = MigrationProgress {
+            total_groups: self.group_ids.len(),
+            total_key_packages: self.key_package_ids.len(),
+            total_pre_shared_keys: self.psk_ids.len(),
+            ..Default::default()
+        };
+
+        let group_ids = core::mem::take(&mut self.group_ids);
+
+        for group_id in &group_ids {
+            if let Some(epochs) = migrate_group(source, destination, group_id).await? {
+                progress.groups += 1;
+                progress.epochs += epochs;
+                self.report(&progress);
+            }
+        }
+
+        let key_package_ids = core::mem::take(&mut self.key_package_ids);
+        let source_key_packages = source.key_package_store();
+        let mut destination_key_packages = destination.key_package_store();
+
+        for id in key_package_ids {
+            let key_package = source_key_packages
+                .get(&id)
+                .await
+                .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
+
+            if let Some(key_package) = key_package {
+                destination_key_packages
+                    .insert(id, key_package)
+                    .await
+                    .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
+
+                progress.key_packages += 1;
+                self.report(&progress);
+            }
+        }
+
+        let psk_ids = core::mem::take(&mut self.psk_ids);
+        let source_psks = source.secret_store();
+
+        for id in &psk_ids {
+            let psk = source_psks
+                .get(id)
+                .await
+                .map_err(|e| MlsError::PskStoreError(e.into_any_error()))?;
+
+            if let (Some(psk), Some(writer)) = (psk, self.psk_writer.as_mut()) {
+                writer(id, &psk).map_err(MlsError::PskStoreError)?;
+                progress.pre_shared_keys += 1;
+                self.report(&progress);
+            }
+        }
+
+        Ok(progress)
+    }
+
+    fn report(&mut self, progress: &MigrationProgress) {
+        if let Some(callback) = self.progress.as_mut() {
+            callback(progress)
+        }
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn migrate_group<C, D>(
+    source: &Client<C>,
+    destination: &Client<D>,
+    group_id: &[u8],
+) -> Result<Option<usize>, MlsError>
+where
+    C: ClientConfig + Clone,
+    D: ClientConfig + Clone,
+{
+    let source_storage = source.group_state_storage();
+    let mut destination_storage = destination.group_state_storage();
+
+    let Some(data) = source_storage
+        .state(group_id)
+        .await
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
+    else {
+        return Ok(None);
+    };
+
+    let mut epochs = Vec::new();
+
+    let max_epoch_id = source_storage
+        .max_epoch_id(group_id)
+        .await
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+    if let Some(max_epoch_id) = max_epoch_id {
+        for epoch_id in (0..=max_epoch_id).rev() {
+            let epoch = source_storage
+                .epoch(group_id, epoch_id)
+                .await
+                .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+            let Some(epoch) = epoch else {
+                break;
+            };
+
+            epochs.push(EpochRecord::new(epoch_id, epoch));
+        }
+    }
+
+    epochs.reverse();
+
+    // Epochs copied by a previous run are updated rather than inserted again.
+    let copied_epoch_id = destination_storage
+        .max_epoch_id(group_id)
+        .await
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+    let (updates, inserts): (Vec<_>, Vec<_>) = epochs
+        .iter()
+        .cloned()
+        .partition(|epoch| copied_epoch_id.map_or(false, |copied| epoch.id <= copied));
+
+    let state = GroupState {
+        id: group_id.to_vec(),
+        data: data.clone(),
+    };
+
+    destination_storage
+        .write(state, inserts, updates)
+        .await
+        .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+    verify_group(destination, group_id, &data, &epochs).await?;
+
+    Ok(Some(epochs.len()))
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn verify_group<D>(
+    destination: &Client<D>,
+    group_id: &[u8],
+    data: &[u8],
+    epochs: &[EpochRecord],
+) -> Result<(), MlsError>
+where
+    D: ClientConfig + Clone,
+{
+    let snapshot = Snapshot::from_bytes(data)?;
+    let copied = Group::from_snapshot(destination.config.clone(), snapshot).await?;
+    let loaded = destination.load_group(group_id).await?;
+
+    let mut matches = loaded.current_epoch() == copied.current_epoch()
+        && loaded.epoch_authenticator()? == copied.epoch_authenticator()?;
+
+    let destination_storage = destination.group_state_storage();
+
+    for epoch in epochs {
+        let stored = destination_storage
+            .epoch(group_id, epoch.id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        // The destination may retain fewer epochs than the source.
+        matches &= stored.map_or(true, |stored| stored == epoch.data);
+    }
+
+    matches
+        .then_some(())
+        .ok_or_else(|| MlsError::StorageMigrationVerificationFailed(group_id.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::psk::{ExternalPskId, PreSharedKey};
+
+    use crate::{
+        client::test_utils::{
+            test_client_with_key_pkg, TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+        },
+        client_builder::test_utils::TestClientConfig,
+        group::test_utils::random_bytes,
+        identity::test_utils::get_test_signing_identity,
+        Client,
+    };
+
+    use super::{MigrationProgress, StorageMigration};
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn destination_client() -> Client<TestClientConfig> {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        TestClientBuilder::new_for_test()
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn all_items_are_copied_and_verified() {
+        let (source, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let mut group = source
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            group.commit(vec![]).await.unwrap();
+            group.apply_pending_commit().await.unwrap();
+        }
+
+        group.write_to_storage().await.unwrap();
+
+        let psk_id = ExternalPskId::new(random_bytes(32));
+        let psk = PreSharedKey::from(random_bytes(32));
+        source.secret_store().insert(psk_id.clone(), psk.clone());
+
+        let destination = destination_client().await;
+        let mut destination_psks = destination.secret_store();
+        let mut updates = Vec::new();
+
+        let key_package_ids = source
+            .key_package_store()
+            .key_packages()
+            .into_iter()
+            .map(|(id, _)| id)
+            .chain([b"consumed".to_vec()])
+            .collect();
+
+        let progress = StorageMigration::new()
+            .with_groups(source.group_state_storage().stored_groups())
+            .with_key_packages(key_package_ids)
+            .with_pre_shared_keys(vec![psk_id.clone()], |id, psk| {
+                destination_psks.insert(id.clone(), psk.clone());
+                Ok::<_, core::convert::Infallible>(())
+            })
+            .with_progress(|progress| updates.push(progress.clone()))
+            .run(&source, &destination)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            progress,
+            MigrationProgress {
+                groups: 1,
+                total_groups: 1,
+                epochs: if cfg!(feature = "prior_epoch") { 3 } else { 0 },
+                key_packages: 1,
+                total_key_packages: 2,
+                pre_shared_keys: 1,
+                total_pre_shared_keys: 1,
+            }
+        );
+
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates.last(), Some(&progress));
+
+        let loaded = destination.load_group(group.group_id()).await.unwrap();
+
+        assert_eq!(
+            loaded.epoch_authenticator().unwrap(),
+            group.epoch_authenticator().unwrap()
+        );
+
+        assert_eq!(destination.key_package_store().key_packages().len(), 1);
+        assert_eq!(destination.secret_store().get(&psk_id), Some(psk));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn migration_can_be_repeated_after_new_epochs() {
+        let (source, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let mut group = source
+            .create_group(Default::default(), Default::default(), None)
+            .await
+            .unwrap();
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+        group.write_to_storage().await.unwrap();
+
+        let destination = destination_client().await;
+        let group_ids = source.group_state_storage().stored_groups();
+
+        StorageMigration::new()
+            .with_groups(group_ids.clone())
+            .run(&source, &destination)
+            .await
+            .unwrap();
+
+        group.commit(vec![]).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+        group.write_to_storage().await.unwrap();
+
+        let res = StorageMigration::new()
+            .with_groups(group_ids)
+            .run(&source, &destination)
+            .await;
+
+        let epochs = if cfg!(feature = "prior_epoch") { 2 } else { 0 };
+        assert_matches!(res, Ok(MigrationProgress { groups: 1, epochs: e, .. }) if e == epochs);
+
+        let loaded = destination.load_group(group.group_id()).await.unwrap();
+        assert_eq!(loaded.current_epoch(), 2);
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_core::{
+    error::IntoAnyError,
+    group::{EpochRecord, GroupState, GroupStateStorage},
+};
+
+use crate::{client::MlsError, group::snapshot::Snapshot};
+
+/// Description of a group state that was successfully persisted.
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GroupStateWrite<'a> {
+    /// Identifier of the group that was written.
+    pub group_id: &'a [u8],
+    /// Current epoch of the group at the time of the write.
+    pub epoch: u64,
+    /// Serialized group state that was written.
+    pub data: &'a [u8],
+}
+
+impl Debug for GroupStateWrite<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupStateWrite")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("data", &mls_rs_core::debug::pretty_bytes(self.data))
+            .finish()
+    }
+}
+
+/// Callback notified every time a group state is persisted.
+///
+/// See [`ClientBuilder::group_state_observer`](crate::client_builder::ClientBuilder::group_state_observer).
+pub trait GroupStateObserver: Send + Sync {
+    /// Called after `write` was successfully persisted by the underlying
+    /// [`GroupStateStorage`].
+    fn group_state_written(&self, write: &GroupStateWrite<'_>);
+}
+
+impl<F> GroupStateObserver for F
+where
+    F: Fn(&GroupStateWrite<'_>) + Send + Sync,
+{
+    fn group_state_written(&self, write: &GroupStateWrite<'_>) {
+        self(write)
+    }
+}
+
+/// [`GroupStateStorage`] that forwards all operations to an inner storage and
+/// notifies a [`GroupStateObserver`] after each successful write.
+#[derive(Clone, Debug)]
+pub struct ObservedGroupStateStorage<S, O> {
+    storage: S,
+    observer: O,
+}
+
+impl<S, O> ObservedGroupStateStorage<S, O>
+where
+    S: GroupStateStorage,
+    O: GroupStateObserver,
+{
+    pub fn new(storage: S, observer: O) -> Self {
+        Self { storage, observer }
+    }
+
+    /// Storage that group states are written to.
+    pub fn inner(&self) -> &S {
+        &self.storage
+    }
+
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<S, O> GroupStateStorage for ObservedGroupStateStorage<S, O>
+where
+    S: GroupStateStorage,
+    O: GroupStateObserver,
+{
+    type Error = MlsError;
+
+    async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.storage
+            .state(group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+
+    async fn epoch(&self, group_id: &[u8], epoch_id: u64) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.storage
+            .epoch(group_id, epoch_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+
+    async fn write(
+        &mut self,
+        state: GroupState,
+        epoch_inserts: Vec<EpochRecord>,
+        epoch_updates: Vec<EpochRecord>,
+    ) -> Result<(), Self::Error> {
+        let epoch = Snapshot::context_from_bytes(&state.data)?.epoch;
+        let GroupState { id, data } = state.clone();
+
+        self.storage
+            .write(state, epoch_inserts, epoch_updates)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        self.observer.group_state_written(&GroupStateWrite {
+            group_id: &id,
+            epoch,
+            data: &data,
+        });
+
+        Ok(())
+    }
+
+    async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        self.storage
+            .max_epoch_id(group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    #[cfg(target_has_atomic = "ptr")]
+    use alloc::sync::Arc;
+
+    #[cfg(not(target_has_atomic = "ptr"))]
+    use portable_atomic_util::Arc;
+
+    #[cfg(feature = "std")]
+    use std::sync::Mutex;
+
+    #[cfg(not(feature = "std"))]
+    use spin::Mutex;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client_builder::test_utils::TestClientBuilder,
+        group::test_utils::{group_extensions, TEST_GROUP},
+        identity::test_utils::get_test_signing_identity,
+    };
+
+    use super::GroupStateWrite;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn observer_is_notified_of_each_write() {
+        let writes = Arc::new(Mutex::new(Vec::new()));
+        let observed_writes = writes.clone();
+
+        let (signing_identity, secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let client = TestClientBuilder::new_for_test()
+            .group_state_observer(move |write: &GroupStateWrite<'_>| {
+                #[cfg(feature = "std")]
+                let mut writes = observed_writes.lock().unwrap();
+                #[cfg(not(feature = "std"))]
+                let mut writes = observed_writes.lock();
+
+                writes.push((write.group_id.to_vec(), write.epoch, write.data.len()));
+            })
+            .used_protocol_version(TEST_PROTOCOL_VERSION)
+            .signing_identity(signing_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let mut group = client
+            .create_group_with_id(
+                TEST_GROUP.to_vec(),
+                group_extensions(),
+                Default::default(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        group.write_to_storage().await.unwrap();
+        group.commit(Vec::new()).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+        group.write_to_storage().await.unwrap();
+
+        #[cfg(feature = "std")]
+        let writes = writes.lock().unwrap();
+        #[cfg(not(feature = "std"))]
+        let writes = writes.lock();
+
+        assert_eq!(writes.len(), 2);
+        assert_eq!(writes[0].0, TEST_GROUP);
+        assert_eq!(writes[0].1, 0);
+        assert_eq!(writes[1].1, 1);
+        assert!(writes.iter().all(|(_, _, len)| *len > 0));
+    }
+}
@@ -6,7 +6,7 @@ use super::leaf_node::{LeafNode, LeafNodeSigningContext, LeafNodeSource};
 use crate::client::MlsError;
 use crate::CipherSuiteProvider;
 use crate::{signer::Signable, time::MlsTime};
-use mls_rs_core::identity::MemberValidationContext;
+use mls_rs_core::identity::{MemberValidation, MemberValidationContext, MemberValidationPurpose};
 use mls_rs_core::{error::IntoAnyError, identity::IdentityProvider};
 
 use crate::extension::RequiredCapabilitiesExt;
@@ -29,6 +29,14 @@ impl ValidationContext<'_> {
         }
     }
 
+    fn purpose(&self) -> MemberValidationPurpose {
+        match self {
+            ValidationContext::Add(_) => MemberValidationPurpose::Add,
+            ValidationContext::Update(_) => MemberValidationPurpose::Update,
+            ValidationContext::Commit(_) => MemberValidationPurpose::Commit,
+        }
+    }
+
     fn generation_time(&self) -> Option<MlsTime> {
         match *self {
             ValidationContext::Add(t) => t,
@@ -47,6 +55,7 @@ where
     cipher_suite_provider: &'a CP,
     identity_provider: &'a C,
     context: MemberValidationContext<'a>,
+    purpose: Option<MemberValidationPurpose>,
 }
 
 impl<'a, C: IdentityProvider, CP: CipherSuiteProvider> LeafNodeValidator<'a, C, CP> {
@@ -59,6 +68,16 @@ impl<'a, C: IdentityProvider, CP: CipherSuiteProvider> LeafNodeValidator<'a, C,
             cipher_suite_provider,
             identity_provider,
             context,
+            purpose: None,
+        }
+    }
+
+    /// Purpose reported to the identity provider for all leaves, instead of
+    /// the purpose derived from the source of each leaf.
+    pub fn with_purpose(self, purpose: MemberValidationPurpose) -> Self {
+        Self {
+            purpose: Some(purpose),
+            ..self
         }
     }
 
@@ -188,12 +207,14 @@ impl<'a, C: IdentityProvider, CP: CipherSuiteProvider> LeafNodeValidator<'a, C,
         self.check_context(leaf_node, &context)?;
 
         // Verify the credential
+        let validation = MemberValidation::new(
+            self.context,
+            self.purpose.unwrap_or_else(|| context.purpose()),
+            context.generation_time(),
+        );
+
         self.identity_provider
-            .validate_member(
-                &leaf_node.signing_identity,
-                context.generation_time(),
-                self.context,
-            )
+            .validate_member_in_context(&leaf_node.signing_identity, validation)
             .await
             .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
 
@@ -263,10 +284,17 @@ mod tests {
     use crate::identity::basic::BasicCredential;
     use crate::identity::basic::BasicIdentityProvider;
     use crate::identity::test_utils::get_test_signing_identity;
+    use crate::identity::SigningIdentity;
     use crate::tree_kem::leaf_node::test_utils::*;
-    use crate::tree_kem::leaf_node_validator::test_utils::FailureIdentityProvider;
+    use crate::tree_kem::leaf_node_validator::test_utils::{
+        FailureIdentityProvider, TestFailureError,
+    };
     use crate::tree_kem::Capabilities;
     use crate::ExtensionList;
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+    use mls_rs_codec::MlsEncode;
 
     impl<'a, P: IdentityProvider, C: CipherSuiteProvider> LeafNodeValidator<'a, P, C> {
         pub fn new_for_test(cipher_suite_provider: &'a C, identity_provider: &'a P) -> Self {
@@ -274,6 +302,7 @@ mod tests {
                 cipher_suite_provider,
                 identity_provider,
                 context: MemberValidationContext::None,
+                purpose: None,
             }
         }
     }
@@ -591,6 +620,94 @@ mod tests {
         );
     }
 
+    struct ExpectedPurposeProvider(MemberValidationPurpose);
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl IdentityProvider for ExpectedPurposeProvider {
+        type Error = TestFailureError;
+
+        async fn validate_member(
+            &self,
+            _signing_identity: &SigningIdentity,
+            _timestamp: Option<MlsTime>,
+            _context: MemberValidationContext<'_>,
+        ) -> Result<(), Self::Error> {
+            Err(TestFailureError)
+        }
+
+        async fn validate_member_in_context(
+            &self,
+            _signing_identity: &SigningIdentity,
+            validation: MemberValidation<'_>,
+        ) -> Result<(), Self::Error> {
+            (validation.purpose == self.0
+                && validation.group_id() == Some(b"unused".as_slice())
+                && validation.epoch() == Some(0))
+            .then_some(())
+            .ok_or(TestFailureError)
+        }
+
+        async fn validate_external_sender(
+            &self,
+            _signing_identity: &SigningIdentity,
+            _timestamp: Option<MlsTime>,
+            _extensions: Option<&ExtensionList>,
+        ) -> Result<(), Self::Error> {
+            Err(TestFailureError)
+        }
+
+        async fn identity(
+            &self,
+            signing_id: &SigningIdentity,
+            _extensions: &ExtensionList,
+        ) -> Result<Vec<u8>, Self::Error> {
+            Ok(signing_id.credential.mls_encode_to_vec().unwrap())
+        }
+
+        async fn valid_successor(
+            &self,
+            _predecessor: &SigningIdentity,
+            _successor: &SigningIdentity,
+            _extensions: &ExtensionList,
+        ) -> Result<bool, Self::Error> {
+            Err(TestFailureError)
+        }
+
+        fn supported_types(&self) -> Vec<crate::identity::CredentialType> {
+            vec![BasicCredential::credential_type()]
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn identity_provider_receives_validation_context() {
+        let cipher_suite_provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let (leaf_node, _) = get_test_add_node().await;
+
+        let group_context = fake_group_context();
+        let extensions = ExtensionList::new();
+
+        let context = MemberValidationContext::ForCommit {
+            current_context: &group_context,
+            new_extensions: &extensions,
+        };
+
+        let provider = ExpectedPurposeProvider(MemberValidationPurpose::Add);
+        let validator = LeafNodeValidator::new(&cipher_suite_provider, &provider, context);
+
+        validator
+            .check_if_valid(&leaf_node, ValidationContext::Add(None))
+            .await
+            .unwrap();
+
+        let res = validator
+            .with_purpose(MemberValidationPurpose::Join)
+            .check_if_valid(&leaf_node, ValidationContext::Add(None))
+            .await;
+
+        assert_matches!(res, Err(MlsError::IdentityProviderError(_)));
+    }
+
     fn fake_group_context() -> GroupContext {
         GroupContext {
             protocol_version: TEST_PROTOCOL_VERSION,
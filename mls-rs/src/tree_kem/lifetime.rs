@@ -2,7 +2,10 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::{client::MlsError, time::MlsTime};
+use crate::{
+    client::MlsError,
+    time::{checked_days, checked_years, MlsTime},
+};
 use core::time::Duration;
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
@@ -36,30 +39,34 @@ impl Lifetime {
             not_before
         };
 
-        let not_after = MlsTime::from(
-            not_before
-                .seconds_since_epoch()
-                .checked_add(s)
-                .ok_or(MlsError::TimeOverflow)?,
-        );
+        let not_after = not_before
+            .checked_add(Duration::from_secs(s))
+            .ok_or(MlsError::TimeOverflow)?;
 
         Ok(Lifetime {
             // Subtract 1 hour to address time difference between machines
-            not_before: not_before - Duration::from_secs(3600),
+            not_before: not_before.saturating_sub(Duration::from_secs(3600)),
             not_after,
         })
     }
 
     pub fn days(d: u32, maybe_not_before: Option<MlsTime>) -> Result<Self, MlsError> {
-        Self::seconds((d * 86400) as u64, maybe_not_before)
+        let duration = checked_days(d.into()).ok_or(MlsError::TimeOverflow)?;
+        Self::seconds(duration.as_secs(), maybe_not_before)
     }
 
     pub fn years(y: u8, maybe_not_before: Option<MlsTime>) -> Result<Self, MlsError> {
-        Self::days(365 * y as u32, maybe_not_before)
+        let duration = checked_years(y.into()).ok_or(MlsError::TimeOverflow)?;
+        Self::seconds(duration.as_secs(), maybe_not_before)
+    }
+
+    /// Whether `not_before` is not after `not_after`.
+    pub fn is_valid(&self) -> bool {
+        self.not_before <= self.not_after
     }
 
     pub(crate) fn within_lifetime(&self, time: MlsTime) -> bool {
-        self.not_before <= time && time <= self.not_after
+        time.is_within(self.not_before, self.not_after, Duration::ZERO)
     }
 }
 
@@ -79,6 +86,17 @@ mod tests {
         assert_matches!(res, Err(MlsError::TimeOverflow))
     }
 
+    #[test]
+    fn test_large_days_do_not_overflow() {
+        let lifetime = Lifetime::days(u32::MAX, Some(MlsTime::from(0))).unwrap();
+
+        assert_eq!(lifetime.not_after, MlsTime::from(u32::MAX as u64 * 86400));
+
+        // The hour of tolerance saturates at the unix epoch
+        assert_eq!(lifetime.not_before, MlsTime::from(0));
+        assert!(lifetime.is_valid());
+    }
+
     #[test]
     fn test_seconds() {
         let seconds = 10;
@@ -66,6 +66,9 @@ mod tree_index;
 #[cfg(feature = "std")]
 pub(crate) mod tree_utils;
 
+#[cfg(feature = "tree_visualization")]
+pub(crate) mod tree_visualization;
+
 #[cfg(test)]
 mod interop_test_vectors;
 
@@ -140,6 +143,19 @@ impl TreeKemPublic {
         Ok(())
     }
 
+    /// Index of the leaf identical to `leaf_node`, found using its HPKE
+    /// public key.
+    pub(crate) fn find_existing_leaf(&self, leaf_node: &LeafNode) -> Option<LeafIndex> {
+        #[cfg(feature = "tree_index")]
+        return self
+            .index
+            .get_leaf_index_with_hpke_key(&leaf_node.public_key)
+            .filter(|&index| self.nodes.borrow_as_leaf(index).ok() == Some(leaf_node));
+
+        #[cfg(not(feature = "tree_index"))]
+        return self.find_leaf_node(leaf_node);
+    }
+
     #[cfg(feature = "tree_index")]
     pub(crate) fn get_leaf_node_with_identity(&self, identity: &[u8]) -> Option<LeafIndex> {
         self.index.get_leaf_index_with_identity(identity)
@@ -189,7 +205,6 @@ impl TreeKemPublic {
         self.nodes.total_leaf_count()
     }
 
-    #[cfg(any(test, all(feature = "custom_proposal", feature = "tree_index")))]
     pub fn occupied_leaf_count(&self) -> u32 {
         self.nodes.occupied_leaf_count()
     }
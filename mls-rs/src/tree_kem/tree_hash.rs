@@ -11,7 +11,6 @@ use crate::crypto::CipherSuiteProvider;
 use crate::tree_kem::math as tree_math;
 use crate::tree_kem::node::Parent;
 use crate::tree_kem::TreeKemPublic;
-use alloc::collections::VecDeque;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
@@ -22,6 +21,12 @@ use tree_math::TreeIndex;
 
 use core::ops::Deref;
 
+#[cfg(all(not(mls_build_async), feature = "rayon"))]
+use rayon::prelude::*;
+
+#[cfg(any(mls_build_async, not(feature = "rayon")))]
+use alloc::collections::VecDeque;
+
 #[derive(Clone, Default, MlsSize, MlsEncode, MlsDecode, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct TreeHash(
@@ -86,6 +91,15 @@ impl TreeKemPublic {
         Ok(self.tree_hashes.current[root as usize].to_vec())
     }
 
+    #[cfg(feature = "compliance")]
+    pub(crate) fn node_tree_hashes(&self) -> Vec<Vec<u8>> {
+        self.tree_hashes
+            .current
+            .iter()
+            .map(|h| h.to_vec())
+            .collect()
+    }
+
     // Update hashes after `committer` makes changes to the tree. `path_blank` is the
     // list of leaves whose paths were blanked, i.e. updates and removes.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -262,6 +276,7 @@ impl TreeKemPublic {
     }
 }
 
+#[cfg(any(mls_build_async, not(feature = "rayon")))]
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 async fn tree_hash<P: CipherSuiteProvider>(
     hashes: &mut Vec<TreeHash>,
@@ -313,6 +328,87 @@ async fn tree_hash<P: CipherSuiteProvider>(
     Ok(())
 }
 
+// Same as above, but leaves and then each level of parents are hashed in
+// parallel. A parent is only hashed once all nodes below it are.
+#[cfg(all(not(mls_build_async), feature = "rayon"))]
+fn tree_hash<P: CipherSuiteProvider>(
+    hashes: &mut Vec<TreeHash>,
+    nodes: &NodeVec,
+    leaves_to_update: Option<Vec<LeafIndex>>,
+    filtered_leaves: &[LeafIndex],
+    num_leaves: u32,
+    cipher_suite_provider: &P,
+) -> Result<(), MlsError> {
+    let leaves_to_update =
+        leaves_to_update.unwrap_or_else(|| (0..num_leaves).map(LeafIndex::unchecked).collect_vec());
+
+    // Resize the array in case the tree was extended or truncated
+    hashes.resize(num_leaves as usize * 2 - 1, TreeHash::default());
+
+    let leaf_hashes = leaves_to_update
+        .par_iter()
+        .filter(|l| ***l < num_leaves)
+        .map(|l| {
+            let leaf = (!filtered_leaves.contains(l))
+                .then_some(nodes.borrow_as_leaf(*l).ok())
+                .flatten();
+
+            Ok((
+                2 * **l,
+                TreeHash(hash_for_leaf(*l, leaf, cipher_suite_provider)?),
+            ))
+        })
+        .collect::<Result<Vec<_>, MlsError>>()?;
+
+    let mut level = Vec::with_capacity(leaf_hashes.len());
+
+    for (n, hash) in leaf_hashes {
+        hashes[n as usize] = hash;
+        level.push(n);
+    }
+
+    loop {
+        let mut parents = level
+            .iter()
+            .filter_map(|n| n.parent_sibling(&num_leaves).map(|ps| ps.parent))
+            .collect_vec();
+
+        if parents.is_empty() {
+            return Ok(());
+        }
+
+        parents.sort_unstable_by_key(|n| n.trailing_ones());
+        let lowest = parents[0].trailing_ones();
+
+        // Parents above the lowest level are hashed in a later iteration.
+        let (current, higher): (Vec<_>, Vec<_>) = parents
+            .into_iter()
+            .partition(|n| n.trailing_ones() == lowest);
+
+        let current = current.into_iter().unique().collect_vec();
+
+        let parent_hashes = current
+            .par_iter()
+            .map(|n| {
+                hash_for_parent(
+                    nodes.borrow_as_parent(*n).ok(),
+                    cipher_suite_provider,
+                    filtered_leaves,
+                    &hashes[n.left_unchecked() as usize],
+                    &hashes[n.right_unchecked() as usize],
+                )
+                .map(TreeHash)
+            })
+            .collect::<Result<Vec<_>, MlsError>>()?;
+
+        for (n, hash) in current.iter().zip(parent_hashes) {
+            hashes[*n as usize] = hash;
+        }
+
+        level = current.into_iter().chain(higher).collect();
+    }
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 async fn hash_for_leaf<P: CipherSuiteProvider>(
     leaf_index: LeafIndex,
@@ -364,6 +460,7 @@ mod tests {
 
     use crate::{
         cipher_suite::CipherSuite,
+        client::test_utils::TEST_CIPHER_SUITE,
         crypto::test_utils::{test_cipher_suite_provider, try_test_cipher_suite_provider},
         identity::basic::BasicIdentityProvider,
         tree_kem::{node::NodeVec, parent_hash::test_utils::get_test_tree_fig_12},
@@ -435,4 +532,31 @@ mod tests {
             assert_eq!(calculated_hash, one_case.tree_hash);
         }
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn updated_hashes_match_full_computation() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut tree = get_test_tree_fig_12(TEST_CIPHER_SUITE).await;
+
+        tree.tree_hash(&cs).await.unwrap();
+
+        let updated = [LeafIndex::unchecked(2), LeafIndex::unchecked(5)];
+
+        for leaf in updated {
+            tree.nodes.blank_leaf_node(leaf).unwrap();
+            tree.nodes.blank_direct_path(leaf).unwrap();
+        }
+
+        tree.update_hashes(&updated, &cs).await.unwrap();
+
+        let mut recomputed = tree.clone();
+        recomputed.tree_hashes.current.clear();
+
+        let updated_hash = tree.tree_hash(&cs).await.unwrap();
+        let recomputed_hash = recomputed.tree_hash(&cs).await.unwrap();
+
+        assert_eq!(updated_hash, recomputed_hash);
+
+        assert_eq!(tree.tree_hashes, recomputed.tree_hashes);
+    }
 }
@@ -223,6 +223,10 @@ impl TreeIndex {
         self.identities.get(&Identifier(identity.to_vec())).copied()
     }
 
+    pub(crate) fn get_leaf_index_with_hpke_key(&self, key: &HpkePublicKey) -> Option<LeafIndex> {
+        self.hpke_key.get(key).copied()
+    }
+
     pub fn remove(&mut self, leaf_node: &LeafNode, identity: &[u8]) {
         let existed = self
             .identities
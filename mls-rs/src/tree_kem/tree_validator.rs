@@ -17,7 +17,7 @@ use crate::iter::wrap_impl_iter;
 use crate::time::MlsTime;
 use crate::tree_kem::math as tree_math;
 use crate::tree_kem::{leaf_node_validator::LeafNodeValidator, TreeKemPublic};
-use mls_rs_core::identity::{IdentityProvider, MemberValidationContext};
+use mls_rs_core::identity::{IdentityProvider, MemberValidationContext, MemberValidationPurpose};
 
 #[cfg(all(not(mls_build_async), feature = "rayon"))]
 use rayon::prelude::*;
@@ -52,12 +52,14 @@ impl<'a, C: IdentityProvider, CSP: CipherSuiteProvider> TreeValidator<'a, C, CSP
                 cipher_suite_provider,
                 identity_provider,
                 member_validation_context,
-            ),
+            )
+            .with_purpose(MemberValidationPurpose::Join),
             group_id: &context.group_id,
             cipher_suite_provider,
         }
     }
 
+    #[cfg(any(mls_build_async, not(feature = "rayon")))]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn validate(
         &self,
@@ -74,6 +76,30 @@ impl<'a, C: IdentityProvider, CSP: CipherSuiteProvider> TreeValidator<'a, C, CSP
         validate_unmerged(tree)
     }
 
+    #[cfg(all(not(mls_build_async), feature = "rayon"))]
+    pub fn validate(
+        &self,
+        tree: &mut TreeKemPublic,
+        maybe_time: Option<MlsTime>,
+    ) -> Result<(), MlsError> {
+        self.validate_tree_hash(tree)?;
+
+        // Parent hashes and leaves are independent once the tree hash is known,
+        // so check them concurrently. Errors are reported in the same order as
+        // in the sequential version.
+        let tree = &*tree;
+
+        let (parent_hashes, leaves) = rayon::join(
+            || tree.validate_parent_hashes(self.cipher_suite_provider),
+            || self.validate_leaves(tree, maybe_time),
+        );
+
+        parent_hashes?;
+        self.validate_no_trailing_blanks(tree)?;
+        leaves?;
+        validate_unmerged(tree)
+    }
+
     fn validate_no_trailing_blanks(&self, tree: &TreeKemPublic) -> Result<(), MlsError> {
         tree.nodes
             .last()
@@ -0,0 +1,284 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use mls_rs_core::identity::Credential;
+
+use super::{
+    leaf_node::LeafNodeSource,
+    math::TreeIndex,
+    node::{Node, NodeIndex, NodeVec},
+};
+
+/// Number of leading bytes of a hash that are shown.
+const HASH_PREFIX_LEN: usize = 4;
+
+struct NodeDescription {
+    label: String,
+    details: Vec<String>,
+    blank: bool,
+}
+
+fn describe(nodes: &NodeVec, index: NodeIndex) -> NodeDescription {
+    let name = if nodes.is_leaf(index) {
+        format!("Leaf {} ({index})", index / 2)
+    } else if nodes.total_leaf_count().root() == index {
+        format!("Root ({index})")
+    } else {
+        format!("Parent ({index})")
+    };
+
+    let mut details = Vec::new();
+
+    match nodes.borrow_node(index).ok().and_then(Option::as_ref) {
+        None => {
+            return NodeDescription {
+                label: format!("Blank {name}"),
+                details,
+                blank: true,
+            }
+        }
+        Some(Node::Leaf(leaf)) => {
+            details.push(identity_label(&leaf.signing_identity.credential));
+
+            if let LeafNodeSource::Commit(parent_hash) = &leaf.leaf_node_source {
+                details.push(format!("parent hash: {}", truncated_hex(parent_hash)));
+            }
+        }
+        Some(Node::Parent(parent)) => {
+            if !parent.parent_hash.is_empty() {
+                details.push(format!(
+                    "parent hash: {}",
+                    truncated_hex(&parent.parent_hash)
+                ));
+            }
+
+            if !parent.unmerged_leaves.is_empty() {
+                let unmerged = parent
+                    .unmerged_leaves
+                    .iter()
+                    .map(|leaf| (**leaf).to_string())
+                    .collect::<Vec<_>>();
+
+                details.push(format!("unmerged leaves: {}", unmerged.join(",")));
+            }
+        }
+    }
+
+    NodeDescription {
+        label: name,
+        details,
+        blank: false,
+    }
+}
+
+fn identity_label(credential: &Credential) -> String {
+    match credential.as_basic() {
+        Some(basic) => String::from_utf8_lossy(&basic.identifier).into_owned(),
+        None => format!(
+            "credential type {}",
+            credential.credential_type().raw_value()
+        ),
+    }
+}
+
+fn truncated_hex(bytes: &[u8]) -> String {
+    let mut out = String::new();
+
+    for b in bytes.iter().take(HASH_PREFIX_LEN) {
+        let _ = write!(out, "{b:02x}");
+    }
+
+    if bytes.len() > HASH_PREFIX_LEN {
+        out.push_str("..");
+    }
+
+    out
+}
+
+/// Render the tree as indented text, one node per line.
+pub(crate) fn render_ascii(nodes: &NodeVec) -> String {
+    let mut out = String::new();
+    write_ascii(nodes, nodes.total_leaf_count().root(), "", None, &mut out);
+    out
+}
+
+fn write_ascii(
+    nodes: &NodeVec,
+    index: NodeIndex,
+    prefix: &str,
+    is_last: Option<bool>,
+    out: &mut String,
+) {
+    let description = describe(nodes, index);
+
+    let (branch, child_prefix) = match is_last {
+        None => ("", String::new()),
+        Some(true) => ("└╼ ", format!("{prefix}  ")),
+        Some(false) => ("├╼ ", format!("{prefix}│ ")),
+    };
+
+    if !out.is_empty() {
+        out.push('\n');
+    }
+
+    out.push_str(prefix);
+    out.push_str(branch);
+    out.push_str(&description.label);
+
+    for detail in description.details {
+        out.push_str(" [");
+        out.push_str(&detail);
+        out.push(']');
+    }
+
+    if !nodes.is_leaf(index) {
+        write_ascii(
+            nodes,
+            index.left_unchecked(),
+            &child_prefix,
+            Some(false),
+            out,
+        );
+
+        write_ascii(
+            nodes,
+            index.right_unchecked(),
+            &child_prefix,
+            Some(true),
+            out,
+        );
+    }
+}
+
+/// Render the tree as a Graphviz DOT digraph with edges from parents to
+/// their children. Blank nodes are drawn dashed.
+pub(crate) fn render_dot(nodes: &NodeVec) -> String {
+    let mut out = String::from("digraph ratchet_tree {\n    node [shape=box];\n");
+    write_dot(nodes, nodes.total_leaf_count().root(), &mut out);
+    out.push('}');
+    out.push('\n');
+    out
+}
+
+fn write_dot(nodes: &NodeVec, index: NodeIndex, out: &mut String) {
+    let description = describe(nodes, index);
+
+    let label = core::iter::once(description.label)
+        .chain(description.details)
+        .map(|line| escape_dot(&line))
+        .collect::<Vec<_>>()
+        .join("\\n");
+
+    let style = if description.blank {
+        ", style=dashed"
+    } else {
+        ""
+    };
+
+    let _ = writeln!(out, "    n{index} [label=\"{label}\"{style}];");
+
+    if !nodes.is_leaf(index) {
+        for child in [index.left_unchecked(), index.right_unchecked()] {
+            let _ = writeln!(out, "    n{index} -> n{child};");
+            write_dot(nodes, child, out);
+        }
+    }
+}
+
+fn escape_dot(s: &str) -> String {
+    s.chars()
+        .fold(String::with_capacity(s.len()), |mut out, c| {
+            if matches!(c, '"' | '\\') {
+                out.push('\\');
+            }
+
+            out.push(c);
+            out
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::test_utils::TEST_CIPHER_SUITE,
+        crypto::test_utils::test_cipher_suite_provider,
+        identity::basic::BasicIdentityProvider,
+        tree_kem::{
+            node::Parent,
+            parent_hash::ParentHash,
+            test_utils::{get_test_leaf_nodes, get_test_tree},
+        },
+    };
+
+    use alloc::vec;
+
+    use super::{render_ascii, render_dot, truncated_hex};
+
+    #[test]
+    fn hashes_are_truncated() {
+        assert_eq!(truncated_hex(&[0xab, 0xcd]), "abcd");
+        assert_eq!(truncated_hex(&[1, 2, 3, 4, 5]), "01020304..");
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tree_is_rendered_with_identities_and_unmerged_leaves() {
+        let cipher_suite_provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut tree = get_test_tree(TEST_CIPHER_SUITE).await.public;
+        let leaf_nodes = get_test_leaf_nodes(TEST_CIPHER_SUITE).await;
+
+        tree.add_leaves(
+            leaf_nodes[..2].to_vec(),
+            &BasicIdentityProvider,
+            &cipher_suite_provider,
+        )
+        .await
+        .unwrap();
+
+        tree.nodes[3] = Parent {
+            public_key: vec![].into(),
+            parent_hash: ParentHash::from(vec![1, 2, 3, 4, 5, 6]),
+            unmerged_leaves: vec![],
+        }
+        .into();
+
+        tree.add_leaves(
+            leaf_nodes[2..].to_vec(),
+            &BasicIdentityProvider,
+            &cipher_suite_provider,
+        )
+        .await
+        .unwrap();
+
+        let expected = concat!(
+            "Root (3) [parent hash: 01020304..] [unmerged leaves: 3]\n",
+            "├╼ Blank Parent (1)\n",
+            "│ ├╼ Leaf 0 (0) [creator]\n",
+            "│ └╼ Leaf 1 (2) [A]\n",
+            "└╼ Blank Parent (5)\n",
+            "  ├╼ Leaf 2 (4) [B]\n",
+            "  └╼ Leaf 3 (6) [C]",
+        );
+
+        assert_eq!(render_ascii(&tree.nodes), expected);
+
+        let dot = render_dot(&tree.nodes);
+
+        assert!(dot.starts_with("digraph ratchet_tree {"));
+        assert!(
+            dot.contains("n3 [label=\"Root (3)\\nparent hash: 01020304..\\nunmerged leaves: 3\"];")
+        );
+        assert!(dot.contains("n1 [label=\"Blank Parent (1)\", style=dashed];"));
+        assert!(dot.contains("n4 [label=\"Leaf 2 (4)\\nB\"];"));
+        assert!(dot.contains("n3 -> n5;"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
@@ -7,7 +7,7 @@ use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::{
     error::IntoAnyError,
     group::GroupContext,
-    identity::{IdentityProvider, MemberValidationContext},
+    identity::{IdentityProvider, MemberValidationContext, MemberValidationPurpose},
 };
 
 use super::{
@@ -58,11 +58,18 @@ pub(crate) async fn validate_update_path<C: IdentityProvider, CSP: CipherSuitePr
         new_extensions: &state.group_context.extensions,
     };
 
+    let purpose = if state.applied_proposals.external_initializations.is_empty() {
+        MemberValidationPurpose::Commit
+    } else {
+        MemberValidationPurpose::ExternalCommit
+    };
+
     let leaf_validator = LeafNodeValidator::new(
         cipher_suite_provider,
         identity_provider,
         member_validation_context,
-    );
+    )
+    .with_purpose(purpose);
 
     leaf_validator
         .check_if_valid(
@@ -190,6 +197,7 @@ mod tests {
             indexes_of_added_kpkgs: vec![],
             external_init_index: None,
             unused_proposals: vec![],
+            filtered_proposals: vec![],
         }
     }
 
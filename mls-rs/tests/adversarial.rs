@@ -0,0 +1,29 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::time::Duration;
+
+use mls_rs::adversarial::{check_corpus, corpus, CountingAllocator, DecodeBudget};
+use mls_rs::mls_rs_codec::DecodeLimits;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+// A single test so that no other test allocates while memory is measured.
+#[test]
+fn adversarial_inputs_are_rejected_within_budget() {
+    let tight = DecodeBudget::new(
+        DecodeLimits::new(1 << 12, 1 << 8),
+        Duration::from_millis(100),
+        1 << 20,
+    );
+
+    assert!(corpus(&tight.limits).len() > 10);
+
+    let violations = check_corpus(&tight, Some(&ALLOCATOR));
+    assert!(violations.is_empty(), "{violations:?}");
+
+    let violations = check_corpus(&DecodeBudget::default(), Some(&ALLOCATOR));
+    assert!(violations.is_empty(), "{violations:?}");
+}